@@ -53,6 +53,7 @@ const int HEALING_BEAM = 13;
 const int ENERGY_NATURE = 14;
 const int FLAMETHROWER = 15;
 const int FIRE_SHOCKWAVE = 16;
+const int TETHER = 17;
 
 // meters per second squared (acceleration)
 const float earth_gravity = 9.807;
@@ -302,6 +303,13 @@ void main() {
             vec4(1, 0.6 + rand7 * 0.3 - 5 * inst_lifespan + 2 * lifetime, 0, 0.8 - 3.5 * inst_lifespan),
             spin_in_axis(vec3(rand3, rand4, rand5), rand6)
         );
+    } else if (inst_mode == TETHER) {
+        attr = Attr(
+            vec3(0),
+            vec3(0.4),
+            vec4(vec3(0.45, 0.33, 0.2), 1),
+            spin_in_axis(vec3(1,0,0),0)
+        );
     } else {
         attr = Attr(
             linear_motion(
@@ -0,0 +1,78 @@
+//! Tracking of the block edits the client has asked the server for, until the
+//! server confirms them.
+
+use hashbrown::HashMap;
+use std::time::{Duration, Instant};
+use vek::*;
+
+/// How long to wait for the server to confirm a block edit before giving up
+/// on it. Edits the server rejects, or that lose to someone else's edit of the
+/// same block, are never confirmed.
+const BLOCK_EDIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Default)]
+pub struct PendingBlockEdits {
+    /// Position and send time of each edit, by sequence number
+    edits: HashMap<u64, (Vec3<i32>, Instant)>,
+    next_seq: u64,
+}
+
+impl PendingBlockEdits {
+    /// Records an edit of the block at `pos`, returning the sequence number to
+    /// send along with it
+    pub fn push(&mut self, pos: Vec3<i32>, now: Instant) -> u64 {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.edits.insert(seq, (pos, now));
+        seq
+    }
+
+    /// The server applied the edit with this sequence number
+    pub fn confirm(&mut self, seq: u64) { self.edits.remove(&seq); }
+
+    /// Forgets edits the server has had plenty of time to confirm
+    pub fn maintain(&mut self, now: Instant) {
+        self.edits
+            .retain(|_, (_, sent)| now.duration_since(*sent) < BLOCK_EDIT_TIMEOUT);
+    }
+
+    /// Whether an edit of the block at `pos` is still awaiting confirmation
+    pub fn is_pending(&self, pos: Vec3<i32>) -> bool { self.edits.values().any(|(p, _)| *p == pos) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn confirmed_edits_stop_pending() {
+        let mut edits = PendingBlockEdits::default();
+        let now = Instant::now();
+        let pos = Vec3::new(1, 2, 3);
+        let first = edits.push(pos, now);
+        let second = edits.push(pos, now);
+        assert_ne!(first, second);
+
+        edits.confirm(first);
+        assert!(edits.is_pending(pos));
+        edits.confirm(second);
+        assert!(!edits.is_pending(pos));
+    }
+
+    #[test]
+    fn unconfirmed_edits_expire() {
+        let mut edits = PendingBlockEdits::default();
+        let now = Instant::now();
+        let rejected = Vec3::new(1, 2, 3);
+        let recent = Vec3::new(4, 5, 6);
+        edits.push(rejected, now);
+        edits.push(recent, now + BLOCK_EDIT_TIMEOUT / 2);
+
+        edits.maintain(now + BLOCK_EDIT_TIMEOUT / 2);
+        assert!(edits.is_pending(rejected));
+
+        edits.maintain(now + BLOCK_EDIT_TIMEOUT);
+        assert!(!edits.is_pending(rejected));
+        assert!(edits.is_pending(recent));
+    }
+}
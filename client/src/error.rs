@@ -1,3 +1,4 @@
+use crate::replay::ReplayError;
 use authc::AuthClientError;
 pub use network::NetworkError;
 use network::{ParticipantError, StreamError};
@@ -19,6 +20,7 @@ pub enum Error {
     Banned(String),
     /// Persisted character data is invalid or missing
     InvalidCharacter,
+    Replay(ReplayError),
     //TODO: InvalidAlias,
     Other(String),
 }
@@ -38,3 +40,7 @@ impl From<StreamError> for Error {
 impl From<AuthClientError> for Error {
     fn from(err: AuthClientError) -> Self { Self::AuthClientError(err) }
 }
+
+impl From<ReplayError> for Error {
+    fn from(err: ReplayError) -> Self { Self::Replay(err) }
+}
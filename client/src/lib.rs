@@ -2,11 +2,17 @@
 #![deny(clippy::clone_on_ref_ptr)]
 #![feature(label_break_value, option_zip)]
 
+pub mod block_edits;
 pub mod cmd;
 pub mod error;
+pub mod replay;
 
 // Reexports
-pub use crate::error::Error;
+pub use crate::{
+    block_edits::PendingBlockEdits,
+    error::Error,
+    replay::{ReplayError, ReplayReader, ReplayWriter},
+};
 pub use authc::AuthClientError;
 pub use specs::{
     join::Join,
@@ -16,7 +22,7 @@ pub use specs::{
 
 use byteorder::{ByteOrder, LittleEndian};
 use common::{
-    character::{CharacterId, CharacterItem},
+    character::{CharacterId, CharacterItem, HotbarSlot},
     comp::{
         self,
         chat::{KillSource, KillType},
@@ -24,11 +30,13 @@ use common::{
         InventoryManip, InventoryUpdateEvent,
     },
     event::{EventBus, LocalEvent},
+    gameplay::GameplaySettings,
     msg::{
         validate_chat_msg, ChatMsgValidationError, ClientGeneral, ClientInGame, ClientMsg,
-        ClientRegister, ClientType, DisconnectReason, InviteAnswer, Notification, PingMsg,
-        PlayerInfo, PlayerListUpdate, RegisterError, ServerGeneral, ServerInfo, ServerInit,
-        ServerRegisterAnswer, MAX_BYTES_CHAT_MSG,
+        ClientRegister, ClientType, DisconnectReason, InviteAnswer, KillCause, Notification,
+        ObjectiveUpdate, PingMsg, PlayerInfo, PlayerListUpdate, RegisterError, ServerGeneral,
+        ServerGeneralStream, ServerInfo, ServerInit, ServerMsg, ServerRegisterAnswer,
+        ThrottleReason, WorldMapMsg, MAX_BYTES_CHAT_MSG, MAX_OBJECTIVE_ENTRIES,
     },
     outcome::Outcome,
     recipe::RecipeBook,
@@ -48,7 +56,10 @@ use num::traits::FloatConst;
 use rayon::prelude::*;
 use std::{
     collections::VecDeque,
+    fs::File,
+    io::{BufWriter, Read},
     net::SocketAddr,
+    path::Path,
     sync::Arc,
     time::{Duration, Instant},
 };
@@ -64,9 +75,28 @@ pub enum Event {
     DisconnectionNotification(u64),
     InventoryUpdated(InventoryUpdateEvent),
     Kicked(String),
+    Kill {
+        killer: Option<Uid>,
+        victim: Uid,
+        cause: KillCause,
+    },
     Notification(Notification),
     SetViewDistance(u32),
+    ViewDistanceThrottled {
+        vd: u32,
+        reason: ThrottleReason,
+    },
+    ViewDistanceRestored,
     Outcome(Outcome),
+    /// A short-lived light the frontend should show for `duration`
+    LightFlash {
+        pos: Vec3<f32>,
+        color: Rgb<f32>,
+        strength: f32,
+        duration: Duration,
+    },
+    /// The hotbar layout the server has saved for the active character
+    Hotbar(Vec<Option<HotbarSlot>>),
 }
 
 pub struct Client {
@@ -130,10 +160,31 @@ pub struct Client {
     entity: EcsEntity,
 
     view_distance: Option<u32>,
+    /// A temporary limit on the view distance imposed by the server
+    view_distance_throttle: Option<(u32, ThrottleReason)>,
     // TODO: move into voxygen
     loaded_distance: f32,
 
     pending_chunks: HashMap<Vec2<i32>, Instant>,
+    chunk_gen_progress: Option<(u32, u32)>,
+    music_mood: f32,
+    threat_tables: HashMap<Uid, Vec<(Uid, f32)>>,
+    /// Block edits sent to the server that it hasn't confirmed yet
+    pending_block_edits: PendingBlockEdits,
+    /// Game mode objectives to display, in the order the server sent them
+    objectives: Vec<(String, String)>,
+    /// The dialogue an NPC is showing the player, until a response is picked
+    dialogue: Option<Dialogue>,
+    /// If set, every message received from the server is written here
+    replay_log: Option<ReplayWriter<BufWriter<File>>>,
+}
+
+/// Text an NPC is showing the player, see `ServerGeneral::Dialogue`
+#[derive(Clone, Debug)]
+pub struct Dialogue {
+    pub speaker: Uid,
+    pub lines: Vec<String>,
+    pub responses: Vec<String>,
 }
 
 /// Holds data related to the current players characters, as well as some
@@ -200,6 +251,7 @@ impl Client {
                 client_timeout,
                 world_map,
                 recipe_book,
+                gameplay_settings,
             } => {
                 // Initialize `State`
                 let mut state = State::default();
@@ -210,6 +262,14 @@ impl Client {
 
                 let entity = state.ecs_mut().apply_entity_package(entity_package);
                 *state.ecs_mut().write_resource() = time_of_day;
+                if gameplay_settings.version == GameplaySettings::VERSION {
+                    *state.ecs_mut().write_resource() = gameplay_settings;
+                } else {
+                    warn!(
+                        version = gameplay_settings.version,
+                        "Server sent gameplay settings of an unknown version, keeping the defaults"
+                    );
+                }
 
                 let map_size_lg = common::terrain::MapSizeLg::new(world_map.dimensions_lg)
                     .map_err(|_| {
@@ -221,12 +281,10 @@ impl Client {
                 let map_size = map_size_lg.chunks();
                 let max_height = world_map.max_height;
                 let sea_level = world_map.sea_level;
-                let rgba = world_map.rgba;
+                let rgba = WorldMapMsg::decode_rgba(map_size_lg, &world_map.rgba)
+                    .map_err(|_| Error::Other("Server sent a bad world map image".into()))?;
                 let alt = world_map.alt;
                 let expected_size = (u32::from(map_size.x) * u32::from(map_size.y)) as usize;
-                if rgba.len() != expected_size {
-                    return Err(Error::Other("Server sent a bad world map image".into()));
-                }
                 if alt.len() != expected_size {
                     return Err(Error::Other("Server sent a bad altitude map.".into()));
                 }
@@ -414,9 +472,17 @@ impl Client {
             state,
             entity,
             view_distance,
+            view_distance_throttle: None,
             loaded_distance: 0.0,
 
             pending_chunks: HashMap::new(),
+            chunk_gen_progress: None,
+            music_mood: 0.0,
+            threat_tables: HashMap::new(),
+            pending_block_edits: PendingBlockEdits::default(),
+            objectives: Vec::new(),
+            dialogue: None,
+            replay_log: None,
         })
     }
 
@@ -490,15 +556,19 @@ impl Client {
                     ClientGeneral::ControllerInputs(_)
                     | ClientGeneral::ControlEvent(_)
                     | ClientGeneral::ControlAction(_)
+                    | ClientGeneral::CancelAbility
+                    | ClientGeneral::UseInventoryItem { .. }
                     | ClientGeneral::SetViewDistance(_)
-                    | ClientGeneral::BreakBlock(_)
-                    | ClientGeneral::PlaceBlock(_, _)
+                    | ClientGeneral::BreakBlock(_, _)
+                    | ClientGeneral::PlaceBlock(_, _, _)
                     | ClientGeneral::ExitInGame
                     | ClientGeneral::PlayerPhysics { .. }
                     | ClientGeneral::TerrainChunkRequest { .. }
                     | ClientGeneral::UnlockSkill(_)
                     | ClientGeneral::RefundSkill(_)
-                    | ClientGeneral::UnlockSkillGroup(_) => &mut self.in_game_stream,
+                    | ClientGeneral::UnlockSkillGroup(_)
+                    | ClientGeneral::DialogueResponse { .. }
+                    | ClientGeneral::SetHotbar(_) => &mut self.in_game_stream,
                     //Always possible
                     ClientGeneral::ChatMsg(_)
                     | ClientGeneral::Disconnect
@@ -529,6 +599,7 @@ impl Client {
 
         //Assume we are in_game unless server tells us otherwise
         self.in_game = Some(ClientInGame::Character);
+        self.chunk_gen_progress = None;
 
         self.active_character_id = Some(character_id);
     }
@@ -572,6 +643,12 @@ impl Client {
         )));
     }
 
+    /// Consume the item in an inventory slot. The server ignores the request
+    /// unless the slot holds a consumable.
+    pub fn use_inventory_item(&mut self, slot: usize) {
+        self.send_msg(ClientGeneral::UseInventoryItem { slot });
+    }
+
     pub fn swap_slots(&mut self, a: comp::slot::Slot, b: comp::slot::Slot) {
         self.send_msg(ClientGeneral::ControlEvent(ControlEvent::InventoryManip(
             InventoryManip::Swap(a, b),
@@ -824,10 +901,44 @@ impl Client {
         self.send_msg(ClientGeneral::ControlAction(control_action));
     }
 
+    /// Ask the server to cancel the ability being prepared or charged.
+    pub fn cancel_ability(&mut self) { self.send_msg(ClientGeneral::CancelAbility); }
+
     pub fn view_distance(&self) -> Option<u32> { self.view_distance }
 
+    /// The temporary view distance limit the server has imposed, if any, and
+    /// why.
+    pub fn view_distance_throttle(&self) -> Option<(u32, ThrottleReason)> {
+        self.view_distance_throttle
+    }
+
+    /// The view distance terrain is actually loaded within, taking the
+    /// server's throttle into account.
+    fn effective_view_distance(&self) -> Option<u32> {
+        let vd = self.view_distance?;
+        Some(
+            self.view_distance_throttle
+                .map_or(vd, |(limit, _)| vd.min(limit)),
+        )
+    }
+
     pub fn loaded_distance(&self) -> f32 { self.loaded_distance }
 
+    /// How many chunks around the player are ready and how many the server
+    /// expects to send in total, while first filling the view distance.
+    /// Cleared whenever the client enters or leaves the game.
+    pub fn chunk_gen_progress(&self) -> Option<(u32, u32)> { self.chunk_gen_progress }
+
+    /// How threatening the player's surroundings are, from 0 (calm) to 1
+    /// (intense)
+    pub fn music_mood(&self) -> f32 { self.music_mood }
+
+    /// The attackers with the most threat on `entity`, highest first, if the
+    /// player is fighting it
+    pub fn threat_table(&self, entity: Uid) -> Option<&[(Uid, f32)]> {
+        self.threat_tables.get(&entity).map(|top| top.as_slice())
+    }
+
     pub fn current_chunk(&self) -> Option<Arc<TerrainChunk>> {
         let chunk_pos = Vec2::from(
             self.state
@@ -865,11 +976,46 @@ impl Client {
     }
 
     pub fn place_block(&mut self, pos: Vec3<i32>, block: Block) {
-        self.send_msg(ClientGeneral::PlaceBlock(pos, block));
+        let seq = self.next_block_edit(pos);
+        self.send_msg(ClientGeneral::PlaceBlock(pos, block, seq));
     }
 
     pub fn remove_block(&mut self, pos: Vec3<i32>) {
-        self.send_msg(ClientGeneral::BreakBlock(pos));
+        let seq = self.next_block_edit(pos);
+        self.send_msg(ClientGeneral::BreakBlock(pos, seq));
+    }
+
+    fn next_block_edit(&mut self, pos: Vec3<i32>) -> u64 {
+        self.pending_block_edits.push(pos, Instant::now())
+    }
+
+    /// Whether a block edit we asked for at `pos` is still awaiting
+    /// confirmation from the server.
+    pub fn is_block_edit_pending(&self, pos: Vec3<i32>) -> bool {
+        self.pending_block_edits.is_pending(pos)
+    }
+
+    /// Game mode objectives to show in the overlay, as label and value pairs
+    /// in display order.
+    pub fn objectives(&self) -> &[(String, String)] { &self.objectives }
+
+    /// The dialogue an NPC is showing the player, if any
+    pub fn dialogue(&self) -> Option<&Dialogue> { self.dialogue.as_ref() }
+
+    /// Closes the open dialogue without answering it, for dialogues that have
+    /// no responses to pick from.
+    pub fn close_dialogue(&mut self) { self.dialogue = None; }
+
+    /// Answers the open dialogue with the response at `index`, closing it.
+    pub fn respond_to_dialogue(&mut self, index: usize) {
+        if self.dialogue.take().is_some() {
+            self.send_msg(ClientGeneral::DialogueResponse { index });
+        }
+    }
+
+    /// Saves the hotbar layout of the active character on the server
+    pub fn set_hotbar(&mut self, slots: Vec<Option<HotbarSlot>>) {
+        self.send_msg(ClientGeneral::SetHotbar(slots));
     }
 
     pub fn collect_block(&mut self, pos: Vec3<i32>) {
@@ -968,6 +1114,8 @@ impl Client {
         {
             self.group_invite = None;
         }
+        // Give up on block edits the server never confirmed
+        self.pending_block_edits.maintain(Instant::now());
 
         // 4) Tick the client's LocalState
         self.state.tick(dt, add_foreign_systems, true);
@@ -983,7 +1131,7 @@ impl Client {
             .read_storage::<comp::Pos>()
             .get(self.entity)
             .cloned();
-        if let (Some(pos), Some(view_distance)) = (pos, self.view_distance) {
+        if let (Some(pos), Some(view_distance)) = (pos, self.effective_view_distance()) {
             let chunk_pos = self.state.terrain().pos_key(pos.0.map(|e| e as i32));
 
             // Remove chunks that are too far from the player.
@@ -1106,6 +1254,63 @@ impl Client {
         Ok(frontend_events)
     }
 
+    /// Start writing every message received from the server to a replay log
+    /// at `path`, see [`Client::replay`].
+    pub fn record_replay(&mut self, path: &Path) -> Result<(), Error> {
+        let file = File::create(path).map_err(ReplayError::from)?;
+        // Replays are checked against the build that plays them back, which is
+        // the one handling the messages
+        self.replay_log = Some(ReplayWriter::new(
+            BufWriter::new(file),
+            *common::util::GIT_HASH,
+        )?);
+        Ok(())
+    }
+
+    pub fn stop_recording_replay(&mut self) -> Result<(), Error> {
+        if let Some(mut log) = self.replay_log.take() {
+            log.flush()?;
+        }
+        Ok(())
+    }
+
+    fn record_msg(&mut self, msg: impl FnOnce() -> ServerMsg) {
+        if let Some(log) = &mut self.replay_log {
+            if let Err(e) = log.write(&msg()) {
+                warn!(?e, "Failed to write to the replay log, no longer recording");
+                self.replay_log = None;
+            }
+        }
+    }
+
+    /// Feed the messages of a replay log into the client as if they were just
+    /// received from the server. The log has to be recorded against this
+    /// build of the game. Connection setup and pings aren't replayed.
+    pub fn replay<R: Read>(&mut self, log: ReplayReader<R>) -> Result<Vec<Event>, Error> {
+        log.check_git_hash(*common::util::GIT_HASH)?;
+        let mut frontend_events = Vec::new();
+        for msg in log {
+            match msg? {
+                ServerMsg::General(msg) => match msg.stream() {
+                    ServerGeneralStream::CharacterScreen => {
+                        self.handle_server_character_screen_msg(msg)?
+                    },
+                    ServerGeneralStream::InGame => {
+                        self.handle_server_in_game_msg(&mut frontend_events, msg)?
+                    },
+                    ServerGeneralStream::General => {
+                        self.handle_server_msg(&mut frontend_events, msg)?
+                    },
+                },
+                ServerMsg::Info(_)
+                | ServerMsg::Init(_)
+                | ServerMsg::RegisterAnswer(_)
+                | ServerMsg::Ping(_) => {},
+            }
+        }
+        Ok(frontend_events)
+    }
+
     /// Clean up the client after a tick.
     pub fn cleanup(&mut self) {
         // Cleanup the local state
@@ -1174,6 +1379,8 @@ impl Client {
                         Some(character) => Some(common::msg::CharacterInfo {
                             name: character.name.to_string(),
                             level: next_level,
+                            health: character.health,
+                            energy: character.energy,
                         }),
                         None => {
                             warn!(
@@ -1187,6 +1394,16 @@ impl Client {
                     };
                 }
             },
+            ServerGeneral::PlayerListUpdate(PlayerListUpdate::Vitals(uid, health, energy)) => {
+                if let Some(character) = self
+                    .player_list
+                    .get_mut(&uid)
+                    .and_then(|player_info| player_info.character.as_mut())
+                {
+                    character.health = health;
+                    character.energy = energy;
+                }
+            },
             ServerGeneral::PlayerListUpdate(PlayerListUpdate::Remove(uid)) => {
                 // Instead of removing players, mark them as offline because we need to
                 // remember the names of disconnected players in chat.
@@ -1224,6 +1441,15 @@ impl Client {
                 }
             },
             ServerGeneral::ChatMsg(m) => frontend_events.push(Event::Chat(m)),
+            ServerGeneral::KillEvent {
+                killer,
+                victim,
+                cause,
+            } => frontend_events.push(Event::Kill {
+                killer,
+                victim,
+                cause,
+            }),
             ServerGeneral::SetPlayerEntity(uid) => {
                 if let Some(entity) = self.state.ecs().entity_from_uid(uid.0) {
                     self.entity = entity;
@@ -1368,6 +1594,8 @@ impl Client {
             // Cleanup for when the client goes back to the `in_game = None`
             ServerGeneral::ExitInGameSuccess => {
                 self.in_game = None;
+                self.objectives.clear();
+                self.dialogue = None;
                 self.clean_state();
             },
             ServerGeneral::InventoryUpdate(mut inventory, event) => {
@@ -1390,18 +1618,57 @@ impl Client {
                 }
                 self.pending_chunks.remove(&key);
             },
-            ServerGeneral::TerrainBlockUpdates(mut blocks) => {
+            ServerGeneral::TerrainBlockUpdates {
+                mut blocks,
+                confirmed,
+            } => {
                 blocks.drain().for_each(|(pos, block)| {
                     self.state.set_block(pos, block);
                 });
+                let uid = self.uid();
+                for (_, seq) in confirmed.into_iter().filter(|(by, _)| Some(*by) == uid) {
+                    self.pending_block_edits.confirm(seq);
+                }
             },
             ServerGeneral::SetViewDistance(vd) => {
                 self.view_distance = Some(vd);
                 frontend_events.push(Event::SetViewDistance(vd));
             },
+            ServerGeneral::ViewDistanceThrottle { vd, reason } => {
+                self.view_distance_throttle = Some((vd, reason));
+                frontend_events.push(Event::ViewDistanceThrottled { vd, reason });
+            },
+            ServerGeneral::ViewDistanceThrottleLifted => {
+                if self.view_distance_throttle.take().is_some() {
+                    frontend_events.push(Event::ViewDistanceRestored);
+                }
+            },
             ServerGeneral::Outcomes(outcomes) => {
                 frontend_events.extend(outcomes.into_iter().map(Event::Outcome))
             },
+            ServerGeneral::LightFlash {
+                pos,
+                color,
+                strength,
+                duration,
+            } => frontend_events.push(Event::LightFlash {
+                pos,
+                color,
+                strength,
+                duration,
+            }),
+            ServerGeneral::Hotbar(slots) => frontend_events.push(Event::Hotbar(slots)),
+            ServerGeneral::ChunkGenProgress { requested, ready } => {
+                self.chunk_gen_progress = Some((ready, requested));
+            },
+            ServerGeneral::MusicMood(mood) => self.music_mood = mood,
+            ServerGeneral::ThreatTable { entity, top } => {
+                if top.is_empty() {
+                    self.threat_tables.remove(&entity);
+                } else {
+                    self.threat_tables.insert(entity, top);
+                }
+            },
             ServerGeneral::Knockback(impulse) => {
                 self.state
                     .ecs()
@@ -1411,6 +1678,28 @@ impl Client {
                         impulse,
                     });
             },
+            ServerGeneral::ObjectiveUpdate(update) => match update {
+                ObjectiveUpdate::Set(mut entries) => {
+                    entries.truncate(MAX_OBJECTIVE_ENTRIES);
+                    self.objectives = entries;
+                },
+                ObjectiveUpdate::Clear => self.objectives.clear(),
+            },
+            ServerGeneral::ChunkNamed { key, name } => {
+                // Chunks loaded later already carry the name
+                self.state.set_chunk_name(key, name);
+            },
+            ServerGeneral::Dialogue {
+                speaker,
+                lines,
+                responses,
+            } => {
+                self.dialogue = Some(Dialogue {
+                    speaker,
+                    lines,
+                    responses,
+                });
+            },
             _ => unreachable!("Not a in_game message"),
         }
         Ok(())
@@ -1478,16 +1767,24 @@ impl Client {
             );
             *cnt += 1;
             if let Some(msg) = m1 {
-                self.handle_server_msg(frontend_events, msg?)?;
+                let msg = msg?;
+                self.record_msg(|| ServerMsg::General(msg.clone()));
+                self.handle_server_msg(frontend_events, msg)?;
             }
             if let Some(msg) = m2 {
-                self.handle_ping_msg(msg?)?;
+                let msg = msg?;
+                self.record_msg(|| ServerMsg::Ping(msg));
+                self.handle_ping_msg(msg)?;
             }
             if let Some(msg) = m3 {
-                self.handle_server_character_screen_msg(msg?)?;
+                let msg = msg?;
+                self.record_msg(|| ServerMsg::General(msg.clone()));
+                self.handle_server_character_screen_msg(msg)?;
             }
             if let Some(msg) = m4 {
-                self.handle_server_in_game_msg(frontend_events, msg?)?;
+                let msg = msg?;
+                self.record_msg(|| ServerMsg::General(msg.clone()));
+                self.handle_server_in_game_msg(frontend_events, msg)?;
             }
         }
     }
@@ -1604,6 +1901,8 @@ impl Client {
             .map(|u| u.into())
             .expect("Client doesn't have a Uid!!!");
 
+        self.chunk_gen_progress = None;
+
         // Clear ecs of all entities
         self.state.ecs_mut().delete_all();
         self.state.ecs_mut().maintain();
@@ -1629,6 +1928,22 @@ impl Client {
         }
     }
 
+    /// Name to show for an entity: the player's alias if it is a player,
+    /// otherwise its character name.
+    pub fn display_name(&self, uid: Uid) -> String {
+        if let Some(player_info) = self.player_list.get(&uid) {
+            return self.personalize_alias(uid, player_info.player_alias.clone());
+        }
+        let ecs = self.state.ecs();
+        (
+            &ecs.read_storage::<comp::Stats>(),
+            &ecs.read_storage::<Uid>(),
+        )
+            .join()
+            .find(|(_, u)| **u == uid)
+            .map_or("<?>".to_string(), |(stats, _)| stats.name.clone())
+    }
+
     /// Format a message for the client (voxygen chat box or chat-cli)
     pub fn format_message(&self, msg: &comp::ChatMsg, character_name: bool) -> String {
         let comp::ChatMsg {
@@ -1755,6 +2070,9 @@ impl Client {
                         KillSource::FallDamage => {
                             format!("[{}] died from fall damage", alias_of_uid(victim))
                         },
+                        KillSource::Trap => {
+                            format!("[{}] was killed by a trap", alias_of_uid(victim))
+                        },
                         KillSource::Suicide => {
                             format!("[{}] died from self-inflicted wounds", alias_of_uid(victim))
                         },
@@ -1796,6 +2114,7 @@ impl Client {
                             .replace("{name}", &alias_of_uid(victim))
                             .replace("{environment}", environment),
                         KillSource::FallDamage => message.replace("{name}", &alias_of_uid(victim)),
+                        KillSource::Trap => message.replace("{name}", &alias_of_uid(victim)),
                         KillSource::Suicide => message.replace("{name}", &alias_of_uid(victim)),
                         KillSource::Other => message.replace("{name}", &alias_of_uid(victim)),
                     }
@@ -0,0 +1,317 @@
+//! Recording of the messages a client receives, so that a session can be
+//! replayed later to reproduce client-side bugs.
+//!
+//! A replay log starts with a header holding the format version and the git
+//! hash of the build that recorded it, followed by every message as a
+//! length-prefixed bincode blob.
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use common::msg::ServerMsg;
+use std::io::{self, Read, Write};
+
+const MAGIC: &[u8; 4] = b"VRPL";
+const FORMAT_VERSION: u32 = 1;
+/// Longest git hash a log header may hold
+const MAX_GIT_HASH_LEN: u32 = 256;
+/// Largest message a log may hold. Generous, since terrain chunks are sent
+/// whole, but small enough that a corrupt length can't exhaust memory.
+const MAX_MSG_LEN: u32 = 64 * 1024 * 1024;
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    Serialization(bincode::Error),
+    /// The data doesn't start with a replay log header
+    NotAReplay,
+    UnsupportedVersion(u32),
+    /// A length in the log is larger than any valid log would hold, so it is
+    /// probably corrupt
+    TooLong(u32),
+    /// The log was recorded against a different build of the game
+    GitHashMismatch {
+        expected: String,
+        found: String,
+    },
+}
+
+impl From<io::Error> for ReplayError {
+    fn from(err: io::Error) -> Self { Self::Io(err) }
+}
+
+impl From<bincode::Error> for ReplayError {
+    fn from(err: bincode::Error) -> Self { Self::Serialization(err) }
+}
+
+pub struct ReplayWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> ReplayWriter<W> {
+    /// Starts a new log, recorded by the build `git_hash`.
+    pub fn new(mut writer: W, git_hash: &str) -> Result<Self, ReplayError> {
+        writer.write_all(MAGIC)?;
+        writer.write_u32::<LittleEndian>(FORMAT_VERSION)?;
+        writer.write_u32::<LittleEndian>(git_hash.len() as u32)?;
+        writer.write_all(git_hash.as_bytes())?;
+        Ok(Self { writer })
+    }
+
+    pub fn write(&mut self, msg: &ServerMsg) -> Result<(), ReplayError> {
+        let bytes = bincode::serialize(msg)?;
+        self.writer.write_u32::<LittleEndian>(bytes.len() as u32)?;
+        self.writer.write_all(&bytes)?;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<(), ReplayError> { Ok(self.writer.flush()?) }
+}
+
+pub struct ReplayReader<R: Read> {
+    reader: R,
+    git_hash: String,
+}
+
+impl<R: Read> ReplayReader<R> {
+    /// Reads the header of a log. Messages can then be taken from the reader
+    /// as an iterator.
+    pub fn new(mut reader: R) -> Result<Self, ReplayError> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(ReplayError::NotAReplay);
+        }
+        let version = reader.read_u32::<LittleEndian>()?;
+        if version != FORMAT_VERSION {
+            return Err(ReplayError::UnsupportedVersion(version));
+        }
+        let len = reader.read_u32::<LittleEndian>()?;
+        if len > MAX_GIT_HASH_LEN {
+            return Err(ReplayError::TooLong(len));
+        }
+        let mut git_hash = vec![0; len as usize];
+        reader.read_exact(&mut git_hash)?;
+        let git_hash = String::from_utf8(git_hash).map_err(|_| ReplayError::NotAReplay)?;
+        Ok(Self { reader, git_hash })
+    }
+
+    /// The git hash of the build that recorded the log
+    pub fn git_hash(&self) -> &str { &self.git_hash }
+
+    /// Fails unless the log was recorded against the build `git_hash`.
+    pub fn check_git_hash(&self, git_hash: &str) -> Result<(), ReplayError> {
+        if self.git_hash == git_hash {
+            Ok(())
+        } else {
+            Err(ReplayError::GitHashMismatch {
+                expected: git_hash.to_string(),
+                found: self.git_hash.clone(),
+            })
+        }
+    }
+}
+
+impl<R: Read> Iterator for ReplayReader<R> {
+    type Item = Result<ServerMsg, ReplayError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let len = match self.reader.read_u32::<LittleEndian>() {
+            Ok(len) => len,
+            // The log simply ends after the last message
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return None,
+            Err(err) => return Some(Err(err.into())),
+        };
+        if len > MAX_MSG_LEN {
+            return Some(Err(ReplayError::TooLong(len)));
+        }
+        let mut bytes = vec![0; len as usize];
+        Some(
+            self.reader
+                .read_exact(&mut bytes)
+                .map_err(ReplayError::from)
+                .and_then(|_| Ok(bincode::deserialize(&bytes)?)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Client;
+    use common::{
+        gameplay::GameplaySettings,
+        msg::{ClientType, PingMsg, ServerGeneral, ServerInfo, ServerInit, WorldMapMsg},
+        recipe::RecipeBook,
+        state::TimeOfDay,
+        sync::{EntityPackage, Uid},
+        terrain::MapSizeLg,
+        util::{GIT_DATE, GIT_HASH},
+    };
+    use futures_executor::block_on;
+    use network::{Network, Pid, Promises, ProtocolAddr};
+    use std::{net::TcpListener, thread, time::Duration};
+    use vek::*;
+
+    #[test]
+    fn messages_read_back_identically() {
+        let msgs = vec![
+            ServerMsg::General(ServerGeneral::SetPlayerEntity(Uid(7))),
+            ServerMsg::Ping(PingMsg::Ping),
+            ServerMsg::General(ServerGeneral::TimeOfDay(TimeOfDay(43200.0))),
+        ];
+
+        let mut log = Vec::new();
+        let mut writer = ReplayWriter::new(&mut log, "abc123").unwrap();
+        for msg in msgs.iter() {
+            writer.write(msg).unwrap();
+        }
+
+        let reader = ReplayReader::new(log.as_slice()).unwrap();
+        assert_eq!(reader.git_hash(), "abc123");
+        assert!(reader.check_git_hash("abc123").is_ok());
+        assert!(reader.check_git_hash("def456").is_err());
+        // `ServerMsg` can't be compared directly
+        let read = reader
+            .map(|msg| format!("{:?}", msg.unwrap()))
+            .collect::<Vec<_>>();
+        let written = msgs
+            .iter()
+            .map(|msg| format!("{:?}", msg))
+            .collect::<Vec<_>>();
+        assert_eq!(read, written);
+    }
+
+    #[test]
+    fn rejects_other_data() {
+        assert!(matches!(
+            ReplayReader::new(&b"not a replay log"[..]),
+            Err(ReplayError::NotAReplay)
+        ));
+    }
+
+    #[test]
+    fn rejects_huge_lengths() {
+        let mut log = Vec::new();
+        ReplayWriter::new(&mut log, "abc123").unwrap();
+        log.extend_from_slice(&u32::MAX.to_le_bytes());
+
+        let mut reader = ReplayReader::new(log.as_slice()).unwrap();
+        assert!(matches!(
+            reader.next(),
+            Some(Err(ReplayError::TooLong(len))) if len == u32::MAX
+        ));
+
+        let mut header = MAGIC.to_vec();
+        header.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        header.extend_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            ReplayReader::new(header.as_slice()),
+            Err(ReplayError::TooLong(_))
+        ));
+    }
+
+    /// Connects a `Client` to a stand-in for a server that does nothing but
+    /// the initial sync, returning the client along with the server's end of
+    /// the connection, which has to outlive it.
+    fn connect_client() -> (Client, impl Sized) {
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let (network, scheduler) = Network::new(Pid::new());
+        thread::spawn(scheduler);
+        block_on(network.listen(ProtocolAddr::Tcp(addr))).unwrap();
+
+        let server = thread::spawn(move || {
+            let participant = block_on(network.connected()).unwrap();
+            let reliable = Promises::ORDERED | Promises::CONSISTENCY;
+            let reliablec = reliable | Promises::COMPRESSED;
+            let general_stream = block_on(participant.open(10, reliablec)).unwrap();
+            let ping_stream = block_on(participant.open(5, reliable)).unwrap();
+            let mut register_stream = block_on(participant.open(10, reliablec)).unwrap();
+            let character_screen_stream = block_on(participant.open(10, reliablec)).unwrap();
+            let in_game_stream = block_on(participant.open(10, reliablec)).unwrap();
+
+            register_stream
+                .send(ServerInfo {
+                    name: "Replay test".to_string(),
+                    description: String::new(),
+                    git_hash: GIT_HASH.to_string(),
+                    git_date: GIT_DATE.to_string(),
+                    auth_provider: None,
+                })
+                .unwrap();
+            let _: ClientType = block_on(register_stream.recv()).unwrap();
+
+            // The smallest possible world
+            let map_size_lg = MapSizeLg::new(Vec2::zero()).unwrap();
+            register_stream
+                .send(ServerInit::GameSync {
+                    entity_package: EntityPackage {
+                        uid: 1,
+                        comps: Vec::new(),
+                    },
+                    time_of_day: TimeOfDay(0.0),
+                    max_group_size: 6,
+                    client_timeout: Duration::from_secs(40),
+                    world_map: WorldMapMsg {
+                        dimensions_lg: map_size_lg.vec(),
+                        sea_level: 0.0,
+                        max_height: 1.0,
+                        rgba: WorldMapMsg::encode_rgba(map_size_lg, &[0]),
+                        alt: vec![0],
+                        horizons: [(vec![0], vec![0]), (vec![0], vec![0])],
+                    },
+                    recipe_book: RecipeBook::default(),
+                    gameplay_settings: GameplaySettings::default(),
+                })
+                .unwrap();
+
+            (
+                network,
+                participant,
+                general_stream,
+                ping_stream,
+                register_stream,
+                character_screen_stream,
+                in_game_stream,
+            )
+        });
+
+        let client = Client::new(addr, None).unwrap();
+        (client, server.join().unwrap())
+    }
+
+    #[test]
+    fn replay_feeds_client() {
+        let mut log = Vec::new();
+        let mut writer = ReplayWriter::new(&mut log, *GIT_HASH).unwrap();
+        for msg in vec![
+            ServerMsg::Ping(PingMsg::Ping),
+            ServerMsg::General(ServerGeneral::TimeOfDay(TimeOfDay(43200.0))),
+            ServerMsg::General(ServerGeneral::CharacterListUpdate(Vec::new())),
+            ServerMsg::General(ServerGeneral::MusicMood(0.75)),
+        ] {
+            writer.write(&msg).unwrap();
+        }
+
+        let (mut client, server) = connect_client();
+        client.character_list.loading = true;
+        client
+            .replay(ReplayReader::new(log.as_slice()).unwrap())
+            .unwrap();
+        assert!((client.state().get_time_of_day() - 43200.0).abs() < f64::EPSILON);
+        assert!(!client.character_list.loading);
+        assert!((client.music_mood() - 0.75).abs() < f32::EPSILON);
+
+        // Logs recorded by other builds aren't replayed
+        let mut log = Vec::new();
+        ReplayWriter::new(&mut log, "not this build").unwrap();
+        assert!(client
+            .replay(ReplayReader::new(log.as_slice()).unwrap())
+            .is_err());
+
+        // The client has to let go of the connection first
+        drop(client);
+        drop(server);
+    }
+}
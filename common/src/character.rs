@@ -6,6 +6,18 @@ use serde::{Deserialize, Serialize};
 /// The limit on how many characters that a player can have
 pub const MAX_CHARACTERS_PER_PLAYER: usize = 8;
 pub type CharacterId = i64;
+/// The number of slots on a character's hotbar
+pub const MAX_HOTBAR_SLOTS: usize = 10;
+
+/// Something a hotbar slot can be bound to. The layout is stored per character
+/// on the server so that it follows the character between clients.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum HotbarSlot {
+    /// An item in the character's inventory, by inventory slot
+    Inventory(usize),
+    /// The third ability of the active weapon
+    Ability3,
+}
 
 /// The minimum character data we need to create a new character on the server.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -42,6 +42,7 @@ pub enum ChatCommand {
     Campfire,
     Debug,
     DebugColumn,
+    Dialogue,
     Dummy,
     Explosion,
     Faction,
@@ -89,6 +90,7 @@ pub static CHAT_COMMANDS: &[ChatCommand] = &[
     ChatCommand::Campfire,
     ChatCommand::Debug,
     ChatCommand::DebugColumn,
+    ChatCommand::Dialogue,
     ChatCommand::Dummy,
     ChatCommand::Explosion,
     ChatCommand::Faction,
@@ -219,6 +221,12 @@ impl ChatCommand {
                 "Prints some debug information about a column",
                 NoAdmin,
             ),
+            ChatCommand::Dialogue => cmd(
+                vec![PlayerName(Required), Message(Required)],
+                "Shows a player a dialogue from you, with any responses after the text separated \
+                 by '|'",
+                Admin,
+            ),
             ChatCommand::Dummy => cmd(vec![], "Spawns a training dummy", Admin),
             ChatCommand::Explosion => cmd(
                 vec![Float("radius", 5.0, Required)],
@@ -417,6 +425,7 @@ impl ChatCommand {
             ChatCommand::Campfire => "campfire",
             ChatCommand::Debug => "debug",
             ChatCommand::DebugColumn => "debug_column",
+            ChatCommand::Dialogue => "dialogue",
             ChatCommand::Dummy => "dummy",
             ChatCommand::Explosion => "explosion",
             ChatCommand::Faction => "faction",
@@ -1,19 +1,25 @@
 use crate::{
     comp::{
-        item::{armor::Protection, Item, ItemKind},
-        Body, CharacterState, EnergySource, Gravity, LightEmitter, Projectile, StateUpdate,
+        damage::BLOCK_EFFICIENCY,
+        item::{
+            armor::{Protection, SetBonus, SET_BONUSES},
+            Hands, Item, ItemKind, ToolKind,
+        },
+        projectile, Body, BuffKind, CharacterState, DamageKind, DamageOverTime, EnergySource,
+        Gravity, HomingParams, Knockback, LightEmitter, Projectile, StateUpdate,
     },
     states::{
         utils::{AbilityKey, StageSection},
         *,
     },
     sys::character_behavior::JoinData,
+    terrain::Block,
 };
 use arraygen::Arraygen;
 use serde::{Deserialize, Serialize};
 use specs::{Component, FlaggedStorage};
 use specs_idvs::IdvStorage;
-use std::time::Duration;
+use std::{collections::HashMap, time::Duration};
 use vek::Vec3;
 
 #[derive(Copy, Clone, Hash, Eq, PartialEq, Debug, Serialize, Deserialize)]
@@ -40,7 +46,7 @@ impl From<&CharacterState> for CharacterAbilityType {
             CharacterState::BasicRanged(_) => Self::BasicRanged,
             CharacterState::Boost(_) => Self::Boost,
             CharacterState::DashMelee(data) => Self::DashMelee(data.stage_section),
-            CharacterState::BasicBlock => Self::BasicBlock,
+            CharacterState::BasicBlock(_) => Self::BasicBlock,
             CharacterState::LeapMelee(data) => Self::LeapMelee(data.stage_section),
             CharacterState::ComboMelee(data) => Self::ComboMelee(data.stage_section, data.stage),
             CharacterState::SpinMelee(data) => Self::SpinMelee(data.stage_section),
@@ -61,9 +67,17 @@ pub enum CharacterAbility {
         buildup_duration: Duration,
         recover_duration: Duration,
         base_healthchange: i32,
-        knockback: f32,
+        knockback: Knockback,
         range: f32,
         max_angle: f32,
+        /// Damage dealt over time to whoever is hit
+        #[serde(default)]
+        dot: Option<DamageOverTime>,
+        /// The kind of damage dealt
+        #[serde(default)]
+        damage_kind: DamageKind,
+        #[serde(default)]
+        meta: AbilityMeta,
     },
     BasicRanged {
         energy_cost: u32,
@@ -75,6 +89,20 @@ pub enum CharacterAbility {
         projectile_light: Option<LightEmitter>,
         projectile_gravity: Option<Gravity>,
         projectile_speed: f32,
+        homing: Option<HomingParams>,
+        /// How many entities the projectile passes through before stopping
+        pierce: u32,
+        /// How many times the projectile bounces off terrain before stopping
+        #[serde(default)]
+        bounces: u32,
+        /// Fraction of its speed the projectile keeps on each bounce
+        #[serde(default)]
+        bounce_damping: f32,
+        /// How long the projectile flies before despawning
+        #[serde(default = "default_projectile_lifetime")]
+        projectile_lifetime: Duration,
+        #[serde(default)]
+        meta: AbilityMeta,
     },
     RepeaterRanged {
         energy_cost: u32,
@@ -89,17 +117,50 @@ pub enum CharacterAbility {
         projectile_gravity: Option<Gravity>,
         projectile_speed: f32,
         reps_remaining: u32,
+        #[serde(default)]
+        meta: AbilityMeta,
     },
     Boost {
         duration: Duration,
         only_up: bool,
+        #[serde(default)]
+        meta: AbilityMeta,
+    },
+    Harvest {
+        buildup_duration: Duration,
+        recover_duration: Duration,
+        range: f32,
+        #[serde(default)]
+        meta: AbilityMeta,
+    },
+    PlaceBlock {
+        block: Block,
+        range: f32,
+        buildup_duration: Duration,
+        #[serde(default)]
+        meta: AbilityMeta,
+    },
+    Grapple {
+        energy_cost: u32,
+        range: f32,
+        pull_speed: f32,
+        arc_height: f32,
+        buildup_duration: Duration,
+        pull_duration: Duration,
+        #[serde(default)]
+        meta: AbilityMeta,
+    },
+    SwapWeapons {
+        duration: Duration,
+        #[serde(default)]
+        meta: AbilityMeta,
     },
     DashMelee {
         energy_cost: u32,
         base_damage: u32,
         max_damage: u32,
-        base_knockback: f32,
-        max_knockback: f32,
+        base_knockback: Knockback,
+        max_knockback: Knockback,
         range: f32,
         angle: f32,
         energy_drain: u32,
@@ -110,9 +171,28 @@ pub enum CharacterAbility {
         recover_duration: Duration,
         infinite_charge: bool,
         is_interruptible: bool,
+        /// The kind of damage dealt
+        #[serde(default)]
+        damage_kind: DamageKind,
+        #[serde(default)]
+        meta: AbilityMeta,
+    },
+    BasicBlock {
+        /// Buff the blocker gets while blocking, as (kind, strength, max
+        /// duration)
+        self_buff: Option<(BuffKind, f32, Duration)>,
+        /// Fraction of incoming damage the block stops
+        block_strength: f32,
+        /// How long after raising the block projectiles are reflected back
+        /// at whoever shot them
+        reflect_window: Option<Duration>,
+        #[serde(default)]
+        meta: AbilityMeta,
+    },
+    Roll {
+        #[serde(default)]
+        meta: AbilityMeta,
     },
-    BasicBlock,
-    Roll,
     ComboMelee {
         stage_data: Vec<combo_melee::Stage>,
         initial_energy_gain: u32,
@@ -121,6 +201,28 @@ pub enum CharacterAbility {
         speed_increase: f32,
         max_speed_increase: f32,
         is_interruptible: bool,
+        /// How long a press made just before recovery is remembered for
+        #[serde(default)]
+        input_buffer: Duration,
+        /// The kind of damage dealt
+        #[serde(default)]
+        damage_kind: DamageKind,
+        #[serde(default)]
+        meta: AbilityMeta,
+    },
+    /// A strike that can only interrupt a combo of at least `min_combo`
+    /// strikes, ending the combo to deal extra damage for each of them
+    ComboFinisher {
+        min_combo: u32,
+        base_damage: u32,
+        damage_per_combo: u32,
+        buildup_duration: Duration,
+        recover_duration: Duration,
+        knockback: Knockback,
+        range: f32,
+        max_angle: f32,
+        #[serde(default)]
+        meta: AbilityMeta,
     },
     LeapMelee {
         energy_cost: u32,
@@ -131,44 +233,65 @@ pub enum CharacterAbility {
         base_damage: u32,
         range: f32,
         max_angle: f32,
-        knockback: f32,
+        knockback: Knockback,
         forward_leap_strength: f32,
         vertical_leap_strength: f32,
+        impact_radius: f32,
+        /// The kind of damage dealt
+        #[serde(default)]
+        damage_kind: DamageKind,
+        #[serde(default)]
+        meta: AbilityMeta,
     },
     SpinMelee {
         buildup_duration: Duration,
         swing_duration: Duration,
         recover_duration: Duration,
         base_damage: u32,
-        knockback: f32,
+        knockback: Knockback,
         range: f32,
+        vertical_range: f32,
         energy_cost: u32,
         is_infinite: bool,
         is_helicopter: bool,
         is_interruptible: bool,
         forward_speed: f32,
         num_spins: u32,
+        max_targets: Option<u32>,
+        /// Buff the spinner gets while spinning, as (kind, strength, max
+        /// duration)
+        self_buff: Option<(BuffKind, f32, Duration)>,
+        /// The kind of damage dealt
+        #[serde(default)]
+        damage_kind: DamageKind,
+        #[serde(default)]
+        meta: AbilityMeta,
     },
     ChargedMelee {
         energy_cost: u32,
         energy_drain: u32,
         initial_damage: u32,
         max_damage: u32,
-        initial_knockback: f32,
-        max_knockback: f32,
+        initial_knockback: Knockback,
+        max_knockback: Knockback,
         range: f32,
         max_angle: f32,
         charge_duration: Duration,
         swing_duration: Duration,
         recover_duration: Duration,
+        /// The kind of damage dealt
+        #[serde(default)]
+        damage_kind: DamageKind,
+        #[serde(default)]
+        meta: AbilityMeta,
     },
     ChargedRanged {
         energy_cost: u32,
         energy_drain: u32,
         initial_damage: u32,
         max_damage: u32,
-        initial_knockback: f32,
-        max_knockback: f32,
+        initial_knockback: Knockback,
+        max_knockback: Knockback,
         prepare_duration: Duration,
         charge_duration: Duration,
         recover_duration: Duration,
@@ -177,6 +300,29 @@ pub enum CharacterAbility {
         projectile_gravity: Option<Gravity>,
         initial_projectile_speed: f32,
         max_projectile_speed: f32,
+        /// How many entities the projectile passes through before stopping
+        pierce: u32,
+        /// How many times the projectile bounces off terrain before stopping
+        #[serde(default)]
+        bounces: u32,
+        /// Fraction of its speed the projectile keeps on each bounce
+        #[serde(default)]
+        bounce_damping: f32,
+        /// Charge window (start and end fractions) in which releasing
+        /// multiplies the attack by the third value
+        #[serde(default)]
+        sweet_spot: Option<(f32, f32, f32)>,
+        /// How long the arrow flies before despawning
+        #[serde(default = "default_projectile_lifetime")]
+        projectile_lifetime: Duration,
+        /// The kind of damage dealt
+        #[serde(default)]
+        damage_kind: DamageKind,
+        /// What damage is multiplied by when the arrow hits a weakpoint
+        #[serde(default = "projectile::default_weakpoint_multiplier")]
+        weakpoint_multiplier: f32,
+        #[serde(default)]
+        meta: AbilityMeta,
     },
     Shockwave {
         energy_cost: u32,
@@ -184,13 +330,18 @@ pub enum CharacterAbility {
         swing_duration: Duration,
         recover_duration: Duration,
         damage: u32,
-        knockback: f32,
+        knockback: Knockback,
         shockwave_angle: f32,
         shockwave_vertical_angle: f32,
         shockwave_speed: f32,
         shockwave_duration: Duration,
         requires_ground: bool,
         move_efficiency: f32,
+        max_targets: Option<u32>,
+        #[serde(default)]
+        stun: Option<Duration>,
+        #[serde(default)]
+        meta: AbilityMeta,
     },
     BasicBeam {
         buildup_duration: Duration,
@@ -205,15 +356,227 @@ pub enum CharacterAbility {
         energy_regen: u32,
         energy_cost: u32,
         energy_drain: u32,
+        /// The kind of damage dealt
+        #[serde(default)]
+        damage_kind: DamageKind,
+        #[serde(default)]
+        meta: AbilityMeta,
     },
 }
 
+/// What an ability needs to be held in the user's hands
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HandRequirement {
+    /// Usable whatever is held
+    Any,
+    /// Needs a one-handed weapon in the main hand, with at most another
+    /// one-handed item in the off hand
+    OneHanded,
+    /// Needs a two-handed weapon, with nothing in the off hand
+    TwoHanded,
+    /// Needs both hands free
+    Empty,
+}
+
+impl Default for HandRequirement {
+    fn default() -> Self { HandRequirement::Any }
+}
+
+impl HandRequirement {
+    /// Whether the active and second items of `loadout` satisfy this
+    /// requirement
+    pub fn met_by(self, loadout: &Loadout) -> bool {
+        // NPCs without a weapon hold an `Empty` placeholder tool
+        let hands = |item: &Option<ItemConfig>| {
+            item.as_ref()
+                .map(|config| config.item.kind())
+                .and_then(|kind| match kind {
+                    ItemKind::Tool(tool) if tool.kind == ToolKind::Empty => None,
+                    kind => kind.hands(),
+                })
+        };
+        let (main, off) = (hands(&loadout.active_item), hands(&loadout.second_item));
+        match self {
+            HandRequirement::Any => true,
+            HandRequirement::OneHanded => {
+                main == Some(Hands::OneHand) && off != Some(Hands::TwoHand)
+            },
+            HandRequirement::TwoHanded => main == Some(Hands::TwoHand) && off.is_none(),
+            HandRequirement::Empty => main.is_none() && off.is_none(),
+        }
+    }
+}
+
+/// Modifiers any ability can have on top of what it does, see
+/// `CharacterAbility::meta`
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AbilityMeta {
+    /// How long after being used the ability can't be used again
+    #[serde(default)]
+    pub cooldown: Option<Duration>,
+    /// Effects on the user as it starts the ability
+    #[serde(default)]
+    pub on_activate: Vec<ActivationEffect>,
+    /// What the user needs to be holding to use the ability
+    #[serde(default)]
+    pub hand_requirement: HandRequirement,
+}
+
+/// Something that happens to a character the moment it starts using an
+/// ability, see `StateUpdate::apply_activation_effects`
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum ActivationEffect {
+    /// Restores this much of the user's health
+    HealSelf(u32),
+    /// Gives the user this much energy
+    GrantEnergy(u32),
+    /// Buffs the user, as (kind, strength, duration)
+    Buff(BuffKind, f32, Duration),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum AbilityReqError {
+    /// The ability was used too recently, with the time left until it is ready
+    OnCooldown(Duration),
+    /// Not enough energy, or not in a position to use the ability
+    RequirementsUnmet,
+    /// Crowd control is preventing any abilities from being used
+    Stunned,
+    /// The ability's `HandRequirement` isn't met by what is held
+    WrongHands,
+    /// A combo finisher was used without a long enough combo going
+    ComboTooShort { combo: u32, required: u32 },
+}
+
 impl CharacterAbility {
-    /// Attempts to fulfill requirements, mutating `update` (taking energy) if
-    /// applicable.
-    pub fn requirements_paid(&self, data: &JoinData, update: &mut StateUpdate) -> bool {
+    /// The modifiers of this ability
+    pub fn meta(&self) -> &AbilityMeta {
         match self {
-            CharacterAbility::Roll => {
+            CharacterAbility::BasicMelee { meta, .. }
+            | CharacterAbility::BasicRanged { meta, .. }
+            | CharacterAbility::RepeaterRanged { meta, .. }
+            | CharacterAbility::Boost { meta, .. }
+            | CharacterAbility::Harvest { meta, .. }
+            | CharacterAbility::PlaceBlock { meta, .. }
+            | CharacterAbility::Grapple { meta, .. }
+            | CharacterAbility::SwapWeapons { meta, .. }
+            | CharacterAbility::DashMelee { meta, .. }
+            | CharacterAbility::BasicBlock { meta, .. }
+            | CharacterAbility::Roll { meta }
+            | CharacterAbility::ComboMelee { meta, .. }
+            | CharacterAbility::ComboFinisher { meta, .. }
+            | CharacterAbility::LeapMelee { meta, .. }
+            | CharacterAbility::SpinMelee { meta, .. }
+            | CharacterAbility::ChargedMelee { meta, .. }
+            | CharacterAbility::ChargedRanged { meta, .. }
+            | CharacterAbility::Shockwave { meta, .. }
+            | CharacterAbility::BasicBeam { meta, .. } => meta,
+        }
+    }
+
+    fn meta_mut(&mut self) -> &mut AbilityMeta {
+        match self {
+            CharacterAbility::BasicMelee { meta, .. }
+            | CharacterAbility::BasicRanged { meta, .. }
+            | CharacterAbility::RepeaterRanged { meta, .. }
+            | CharacterAbility::Boost { meta, .. }
+            | CharacterAbility::Harvest { meta, .. }
+            | CharacterAbility::PlaceBlock { meta, .. }
+            | CharacterAbility::Grapple { meta, .. }
+            | CharacterAbility::SwapWeapons { meta, .. }
+            | CharacterAbility::DashMelee { meta, .. }
+            | CharacterAbility::BasicBlock { meta, .. }
+            | CharacterAbility::Roll { meta }
+            | CharacterAbility::ComboMelee { meta, .. }
+            | CharacterAbility::ComboFinisher { meta, .. }
+            | CharacterAbility::LeapMelee { meta, .. }
+            | CharacterAbility::SpinMelee { meta, .. }
+            | CharacterAbility::ChargedMelee { meta, .. }
+            | CharacterAbility::ChargedRanged { meta, .. }
+            | CharacterAbility::Shockwave { meta, .. }
+            | CharacterAbility::BasicBeam { meta, .. } => meta,
+        }
+    }
+
+    /// Makes this ability usable only once every `cooldown`.
+    pub fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        self.meta_mut().cooldown = Some(cooldown);
+        self
+    }
+
+    /// Makes starting this ability also apply `effects` to its user.
+    pub fn with_on_activate(mut self, effects: Vec<ActivationEffect>) -> Self {
+        self.meta_mut().on_activate = effects;
+        self
+    }
+
+    /// Makes this ability usable only while `hand_requirement` is met.
+    pub fn with_hand_requirement(mut self, hand_requirement: HandRequirement) -> Self {
+        self.meta_mut().hand_requirement = hand_requirement;
+        self
+    }
+
+    /// The effects applied to the user when it starts this ability
+    pub fn activation_effects(&self) -> &[ActivationEffect] { &self.meta().on_activate }
+
+    /// The total damage dealt if this ability is a combo finisher, ending a
+    /// combo of `combo` strikes
+    pub fn finisher_damage(&self, combo: u32) -> Option<u32> {
+        match self {
+            CharacterAbility::ComboFinisher {
+                base_damage,
+                damage_per_combo,
+                ..
+            } => Some(base_damage + damage_per_combo * combo),
+            _ => None,
+        }
+    }
+
+    /// Scales the range of melee abilities by the `reach` of the tool they
+    /// are used with, so longer weapons hit further with the same ability.
+    pub fn with_reach(mut self, reach: f32) -> Self {
+        match &mut self {
+            CharacterAbility::BasicMelee { range, .. }
+            | CharacterAbility::DashMelee { range, .. }
+            | CharacterAbility::LeapMelee { range, .. }
+            | CharacterAbility::SpinMelee { range, .. }
+            | CharacterAbility::ChargedMelee { range, .. }
+            | CharacterAbility::ComboFinisher { range, .. } => *range *= reach,
+            CharacterAbility::ComboMelee { stage_data, .. } => {
+                for stage in stage_data {
+                    stage.range *= reach;
+                }
+            },
+            _ => {},
+        }
+        self
+    }
+
+    /// Attempts to fulfill requirements, mutating `update` (taking energy) if
+    /// applicable. Abilities with a cooldown also record being used under
+    /// `key`.
+    pub fn requirements_paid(
+        &self,
+        key: AbilityKey,
+        data: &JoinData,
+        update: &mut StateUpdate,
+    ) -> Result<(), AbilityReqError> {
+        check_stun(data.stunned, data.time.0)?;
+
+        if !self.meta().hand_requirement.met_by(data.loadout) {
+            return Err(AbilityReqError::WrongHands);
+        }
+
+        let cooldown = self.meta().cooldown;
+        if let Some(remaining) = cooldown.and_then(|cooldown| {
+            data.cooldowns
+                .and_then(|c| c.remaining(key, cooldown, data.time.0))
+        }) {
+            return Err(AbilityReqError::OnCooldown(remaining));
+        }
+
+        let paid = match self {
+            CharacterAbility::Roll { .. } => {
                 data.physics.on_ground
                     && data.body.is_humanoid()
                     && data.vel.0.xy().magnitude_squared() > 0.5
@@ -258,9 +621,93 @@ impl CharacterAbility {
                 .energy
                 .try_change_by(-(*energy_cost as i32), EnergySource::Ability)
                 .is_ok(),
+            CharacterAbility::Grapple { energy_cost, .. } => update
+                .energy
+                .try_change_by(-(*energy_cost as i32), EnergySource::Ability)
+                .is_ok(),
+            CharacterAbility::ComboFinisher { min_combo, .. } => {
+                check_combo(data.character.combo(), *min_combo)?;
+                true
+            },
             _ => true,
+        };
+        if paid {
+            if cooldown.is_some() {
+                let mut cooldowns = data.cooldowns.cloned().unwrap_or_default();
+                cooldowns.mark_used(key, data.time.0);
+                data.updater.insert(data.entity, cooldowns);
+            }
+            Ok(())
+        } else {
+            Err(AbilityReqError::RequirementsUnmet)
+        }
+    }
+}
+
+/// When each ability slot of an entity was last used, for abilities with a
+/// cooldown
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AbilityCooldowns {
+    last_used: HashMap<AbilityKey, f64>,
+}
+
+impl AbilityCooldowns {
+    pub fn mark_used(&mut self, key: AbilityKey, time: f64) { self.last_used.insert(key, time); }
+
+    /// How long until the ability under `key` may be used again, if it is
+    /// still cooling down at `time`.
+    pub fn remaining(&self, key: AbilityKey, cooldown: Duration, time: f64) -> Option<Duration> {
+        let elapsed = time - self.last_used.get(&key)?;
+        let remaining = cooldown.as_secs_f64() - elapsed;
+        if remaining > 0.0 {
+            Some(Duration::from_secs_f64(remaining))
+        } else {
+            None
+        }
+    }
+}
+
+impl Component for AbilityCooldowns {
+    type Storage = IdvStorage<Self>;
+}
+
+/// Crowd control that stops an entity from moving or using abilities until
+/// `until` (in state time)
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Stunned {
+    pub until: f64,
+}
+
+impl Stunned {
+    pub fn new(time: f64, duration: Duration) -> Self {
+        Self {
+            until: time + duration.as_secs_f64(),
         }
     }
+
+    pub fn is_active(&self, time: f64) -> bool { time < self.until }
+}
+
+impl Component for Stunned {
+    type Storage = IdvStorage<Self>;
+}
+
+fn default_projectile_lifetime() -> Duration { projectile::DEFAULT_LIFETIME }
+
+fn check_stun(stunned: Option<&Stunned>, time: f64) -> Result<(), AbilityReqError> {
+    if stunned.map_or(false, |s| s.is_active(time)) {
+        Err(AbilityReqError::Stunned)
+    } else {
+        Ok(())
+    }
+}
+
+fn check_combo(combo: u32, required: u32) -> Result<(), AbilityReqError> {
+    if combo < required {
+        Err(AbilityReqError::ComboTooShort { combo, required })
+    } else {
+        Ok(())
+    }
 }
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
@@ -284,8 +731,15 @@ impl From<Item> for ItemConfig {
                 ability1: ability_drain.next(),
                 ability2: ability_drain.next(),
                 ability3: ability_drain.next(),
-                block_ability: Some(CharacterAbility::BasicBlock),
-                dodge_ability: Some(CharacterAbility::Roll),
+                block_ability: Some(CharacterAbility::BasicBlock {
+                    self_buff: None,
+                    block_strength: BLOCK_EFFICIENCY,
+                    reflect_window: Some(Duration::from_millis(200)),
+                    meta: AbilityMeta::default(),
+                }),
+                dodge_ability: Some(CharacterAbility::Roll {
+                    meta: AbilityMeta::default(),
+                }),
             };
         }
 
@@ -343,12 +797,122 @@ impl Loadout {
                 Protection::Normal(protection) => Some(protection),
                 Protection::Invincible => None,
             })
-            .sum::<Option<f32>>();
+            .sum::<Option<f32>>()
+            .map(|protection| {
+                protection
+                    + self
+                        .active_set_bonuses()
+                        .iter()
+                        .map(|bonus| match bonus {
+                            SetBonus::Protection(protection) => *protection,
+                            _ => 0.0,
+                        })
+                        .sum::<f32>()
+            });
         match protection {
             Some(dr) => dr / (60.0 + dr.abs()),
             None => 1.0,
         }
     }
+
+    /// The fraction of damage of the given kind that the worn armor resists,
+    /// on top of [`Loadout::get_damage_reduction`]
+    pub fn get_resistance(&self, kind: DamageKind) -> f32 {
+        self.get_armor()
+            .iter()
+            .flat_map(|armor| armor.as_ref())
+            .filter_map(|item| {
+                if let ItemKind::Armor(armor) = &item.kind() {
+                    Some(armor.get_resistances().get(kind))
+                } else {
+                    None
+                }
+            })
+            .sum::<f32>()
+            .min(1.0)
+    }
+
+    /// The fraction of knockback that the worn armor shrugs off
+    pub fn knockback_resistance(&self) -> f32 {
+        self.get_armor()
+            .iter()
+            .flat_map(|armor| armor.as_ref())
+            .filter_map(|item| {
+                if let ItemKind::Armor(armor) = &item.kind() {
+                    Some(armor.get_knockback_resistance())
+                } else {
+                    None
+                }
+            })
+            .sum::<f32>()
+            .min(1.0)
+    }
+
+    /// Exchanges the active and second items, if there is a second item to
+    /// swap to.
+    pub fn swap_weapons(&mut self) {
+        if self.second_item.is_some() {
+            std::mem::swap(&mut self.active_item, &mut self.second_item);
+        }
+    }
+
+    /// Whether the main and off hand items can be equipped together, see
+    /// `Hands::compatible_with`.
+    pub fn hands_compatible(&self) -> bool {
+        let hands = |item: &Option<ItemConfig>| item.as_ref().and_then(|i| i.item.kind().hands());
+        match (hands(&self.active_item), hands(&self.second_item)) {
+            (Some(active), Some(second)) => active.compatible_with(second),
+            _ => true,
+        }
+    }
+
+    /// The bonuses granted by every armor set with enough pieces equipped.
+    pub fn active_set_bonuses(&self) -> Vec<SetBonus> {
+        let mut set_pieces = HashMap::<&str, usize>::new();
+        for set_id in self
+            .get_armor()
+            .iter()
+            .flat_map(|armor| armor.as_ref())
+            .filter_map(|item| match item.kind() {
+                ItemKind::Armor(armor) => armor.set_id.as_deref(),
+                _ => None,
+            })
+        {
+            *set_pieces.entry(set_id).or_default() += 1;
+        }
+
+        set_pieces
+            .values()
+            .flat_map(|pieces| {
+                SET_BONUSES
+                    .iter()
+                    .filter(move |(threshold, _)| pieces >= threshold)
+                    .map(|(_, bonus)| *bonus)
+            })
+            .collect()
+    }
+
+    /// Maximum energy granted by armor set bonuses
+    pub fn set_bonus_energy(&self) -> u32 {
+        self.active_set_bonuses()
+            .iter()
+            .map(|bonus| match bonus {
+                SetBonus::Energy(energy) => *energy,
+                _ => 0,
+            })
+            .sum()
+    }
+
+    /// Fraction of extra movement speed granted by armor set bonuses
+    pub fn set_bonus_speed(&self) -> f32 {
+        self.active_set_bonuses()
+            .iter()
+            .map(|bonus| match bonus {
+                SetBonus::Speed(speed) => *speed,
+                _ => 0.0,
+            })
+            .sum()
+    }
 }
 
 impl From<(&CharacterAbility, AbilityKey)> for CharacterState {
@@ -361,15 +925,21 @@ impl From<(&CharacterAbility, AbilityKey)> for CharacterState {
                 knockback,
                 range,
                 max_angle,
+                dot,
                 energy_cost: _,
+                damage_kind,
+                meta: _,
             } => CharacterState::BasicMelee(basic_melee::Data {
-                exhausted: false,
+                timer: Duration::default(),
+                stage_section: StageSection::Buildup,
                 buildup_duration: *buildup_duration,
                 recover_duration: *recover_duration,
                 base_healthchange: *base_healthchange,
                 knockback: *knockback,
                 range: *range,
                 max_angle: *max_angle,
+                dot: *dot,
+                damage_kind: *damage_kind,
             }),
             CharacterAbility::BasicRanged {
                 holdable,
@@ -380,7 +950,13 @@ impl From<(&CharacterAbility, AbilityKey)> for CharacterState {
                 projectile_light,
                 projectile_gravity,
                 projectile_speed,
+                homing,
+                pierce,
+                bounces,
+                bounce_damping,
+                projectile_lifetime,
                 energy_cost: _,
+                meta: _,
             } => CharacterState::BasicRanged(basic_ranged::Data {
                 exhausted: false,
                 prepare_timer: Duration::default(),
@@ -392,12 +968,74 @@ impl From<(&CharacterAbility, AbilityKey)> for CharacterState {
                 projectile_light: *projectile_light,
                 projectile_gravity: *projectile_gravity,
                 projectile_speed: *projectile_speed,
+                homing: *homing,
+                pierce: *pierce,
+                bounces: *bounces,
+                bounce_damping: *bounce_damping,
+                projectile_lifetime: *projectile_lifetime,
                 ability_key: key,
             }),
-            CharacterAbility::Boost { duration, only_up } => CharacterState::Boost(boost::Data {
+            CharacterAbility::Boost {
+                duration,
+                only_up,
+                meta: _,
+            } => CharacterState::Boost(boost::Data {
                 duration: *duration,
                 only_up: *only_up,
             }),
+            CharacterAbility::Harvest {
+                buildup_duration,
+                recover_duration,
+                range,
+                meta: _,
+            } => CharacterState::Harvest(harvest::Data {
+                static_data: harvest::StaticData {
+                    buildup_duration: *buildup_duration,
+                    recover_duration: *recover_duration,
+                    range: *range,
+                },
+                timer: Duration::default(),
+                stage_section: StageSection::Buildup,
+            }),
+            CharacterAbility::PlaceBlock {
+                block,
+                range,
+                buildup_duration,
+                meta: _,
+            } => CharacterState::PlaceBlock(place_block::Data {
+                static_data: place_block::StaticData {
+                    block: *block,
+                    buildup_duration: *buildup_duration,
+                    range: *range,
+                },
+                timer: Duration::default(),
+            }),
+            CharacterAbility::Grapple {
+                energy_cost: _,
+                range,
+                pull_speed,
+                arc_height,
+                buildup_duration,
+                pull_duration,
+                meta: _,
+            } => CharacterState::Grapple(grapple::Data {
+                static_data: grapple::StaticData {
+                    range: *range,
+                    pull_speed: *pull_speed,
+                    arc_height: *arc_height,
+                    buildup_duration: *buildup_duration,
+                    pull_duration: *pull_duration,
+                },
+                timer: Duration::default(),
+                stage_section: StageSection::Buildup,
+                anchor: None,
+            }),
+            CharacterAbility::SwapWeapons { duration, meta: _ } => {
+                CharacterState::SwapWeapons(swap_weapons::Data {
+                    duration: *duration,
+                    timer: Duration::default(),
+                })
+            },
             CharacterAbility::DashMelee {
                 energy_cost: _,
                 base_damage,
@@ -414,6 +1052,8 @@ impl From<(&CharacterAbility, AbilityKey)> for CharacterState {
                 recover_duration,
                 infinite_charge,
                 is_interruptible,
+                damage_kind,
+                meta: _,
             } => CharacterState::DashMelee(dash_melee::Data {
                 static_data: dash_melee::StaticData {
                     base_damage: *base_damage,
@@ -430,14 +1070,48 @@ impl From<(&CharacterAbility, AbilityKey)> for CharacterState {
                     swing_duration: *swing_duration,
                     recover_duration: *recover_duration,
                     is_interruptible: *is_interruptible,
+                    damage_kind: *damage_kind,
                 },
                 end_charge: false,
                 timer: Duration::default(),
                 stage_section: StageSection::Buildup,
                 exhausted: false,
             }),
-            CharacterAbility::BasicBlock => CharacterState::BasicBlock,
-            CharacterAbility::Roll => CharacterState::Roll(roll::Data {
+            CharacterAbility::BasicBlock {
+                self_buff,
+                block_strength,
+                reflect_window,
+                meta: _,
+            } => CharacterState::BasicBlock(basic_block::Data {
+                self_buff: *self_buff,
+                block_strength: *block_strength,
+                reflect_window: *reflect_window,
+                timer: Duration::default(),
+            }),
+            // The damage for the combo being finished is added on activation
+            CharacterAbility::ComboFinisher {
+                min_combo: _,
+                base_damage,
+                damage_per_combo: _,
+                buildup_duration,
+                recover_duration,
+                knockback,
+                range,
+                max_angle,
+                meta: _,
+            } => CharacterState::BasicMelee(basic_melee::Data {
+                timer: Duration::default(),
+                stage_section: StageSection::Buildup,
+                buildup_duration: *buildup_duration,
+                recover_duration: *recover_duration,
+                base_healthchange: -(*base_damage as i32),
+                knockback: *knockback,
+                range: *range,
+                max_angle: *max_angle,
+                dot: None,
+                damage_kind: DamageKind::Physical,
+            }),
+            CharacterAbility::Roll { meta: _ } => CharacterState::Roll(roll::Data {
                 remaining_duration: Duration::from_millis(500),
                 was_wielded: false, // false by default. utils might set it to true
             }),
@@ -449,6 +1123,9 @@ impl From<(&CharacterAbility, AbilityKey)> for CharacterState {
                 speed_increase,
                 max_speed_increase,
                 is_interruptible,
+                input_buffer,
+                damage_kind,
+                meta: _,
             } => CharacterState::ComboMelee(combo_melee::Data {
                 static_data: combo_melee::StaticData {
                     num_stages: stage_data.len() as u32,
@@ -459,12 +1136,15 @@ impl From<(&CharacterAbility, AbilityKey)> for CharacterState {
                     speed_increase: 1.0 - *speed_increase,
                     max_speed_increase: *max_speed_increase - 1.0,
                     is_interruptible: *is_interruptible,
+                    input_buffer: *input_buffer,
+                    damage_kind: *damage_kind,
                 },
                 stage: 1,
                 combo: 0,
                 timer: Duration::default(),
                 stage_section: StageSection::Buildup,
-                next_stage: false,
+                next_stage: None,
+                buffered_input: None,
             }),
             CharacterAbility::LeapMelee {
                 energy_cost: _,
@@ -478,6 +1158,9 @@ impl From<(&CharacterAbility, AbilityKey)> for CharacterState {
                 max_angle,
                 forward_leap_strength,
                 vertical_leap_strength,
+                impact_radius,
+                damage_kind,
+                meta: _,
             } => CharacterState::LeapMelee(leap_melee::Data {
                 static_data: leap_melee::StaticData {
                     buildup_duration: *buildup_duration,
@@ -490,6 +1173,8 @@ impl From<(&CharacterAbility, AbilityKey)> for CharacterState {
                     max_angle: *max_angle,
                     forward_leap_strength: *forward_leap_strength,
                     vertical_leap_strength: *vertical_leap_strength,
+                    impact_radius: *impact_radius,
+                    damage_kind: *damage_kind,
                 },
                 timer: Duration::default(),
                 stage_section: StageSection::Buildup,
@@ -502,12 +1187,17 @@ impl From<(&CharacterAbility, AbilityKey)> for CharacterState {
                 base_damage,
                 knockback,
                 range,
+                vertical_range,
                 energy_cost,
                 is_infinite,
                 is_helicopter,
                 is_interruptible,
                 forward_speed,
                 num_spins,
+                max_targets,
+                self_buff,
+                damage_kind,
+                meta: _,
             } => CharacterState::SpinMelee(spin_melee::Data {
                 static_data: spin_melee::StaticData {
                     buildup_duration: *buildup_duration,
@@ -516,12 +1206,16 @@ impl From<(&CharacterAbility, AbilityKey)> for CharacterState {
                     base_damage: *base_damage,
                     knockback: *knockback,
                     range: *range,
+                    vertical_range: *vertical_range,
                     energy_cost: *energy_cost,
                     is_infinite: *is_infinite,
                     is_helicopter: *is_helicopter,
                     is_interruptible: *is_interruptible,
                     forward_speed: *forward_speed,
                     num_spins: *num_spins,
+                    max_targets: *max_targets,
+                    self_buff: *self_buff,
+                    damage_kind: *damage_kind,
                 },
                 timer: Duration::default(),
                 spins_remaining: *num_spins - 1,
@@ -540,6 +1234,8 @@ impl From<(&CharacterAbility, AbilityKey)> for CharacterState {
                 recover_duration,
                 range,
                 max_angle,
+                damage_kind,
+                meta: _,
             } => CharacterState::ChargedMelee(charged_melee::Data {
                 static_data: charged_melee::StaticData {
                     energy_cost: *energy_cost,
@@ -553,6 +1249,7 @@ impl From<(&CharacterAbility, AbilityKey)> for CharacterState {
                     charge_duration: *charge_duration,
                     swing_duration: *swing_duration,
                     recover_duration: *recover_duration,
+                    damage_kind: *damage_kind,
                 },
                 stage_section: StageSection::Charge,
                 timer: Duration::default(),
@@ -574,6 +1271,14 @@ impl From<(&CharacterAbility, AbilityKey)> for CharacterState {
                 projectile_gravity,
                 initial_projectile_speed,
                 max_projectile_speed,
+                pierce,
+                bounces,
+                bounce_damping,
+                sweet_spot,
+                projectile_lifetime,
+                damage_kind,
+                weakpoint_multiplier,
+                meta: _,
             } => CharacterState::ChargedRanged(charged_ranged::Data {
                 exhausted: false,
                 energy_drain: *energy_drain,
@@ -590,6 +1295,13 @@ impl From<(&CharacterAbility, AbilityKey)> for CharacterState {
                 projectile_gravity: *projectile_gravity,
                 initial_projectile_speed: *initial_projectile_speed,
                 max_projectile_speed: *max_projectile_speed,
+                pierce: *pierce,
+                bounces: *bounces,
+                bounce_damping: *bounce_damping,
+                sweet_spot: *sweet_spot,
+                projectile_lifetime: *projectile_lifetime,
+                damage_kind: *damage_kind,
+                weakpoint_multiplier: *weakpoint_multiplier,
             }),
             CharacterAbility::RepeaterRanged {
                 energy_cost: _,
@@ -604,6 +1316,7 @@ impl From<(&CharacterAbility, AbilityKey)> for CharacterState {
                 projectile_gravity,
                 projectile_speed,
                 reps_remaining,
+                meta: _,
             } => CharacterState::RepeaterRanged(repeater_ranged::Data {
                 static_data: repeater_ranged::StaticData {
                     movement_duration: *movement_duration,
@@ -634,6 +1347,9 @@ impl From<(&CharacterAbility, AbilityKey)> for CharacterState {
                 shockwave_duration,
                 requires_ground,
                 move_efficiency,
+                max_targets,
+                stun,
+                meta: _,
             } => CharacterState::Shockwave(shockwave::Data {
                 static_data: shockwave::StaticData {
                     buildup_duration: *buildup_duration,
@@ -647,6 +1363,8 @@ impl From<(&CharacterAbility, AbilityKey)> for CharacterState {
                     shockwave_duration: *shockwave_duration,
                     requires_ground: *requires_ground,
                     move_efficiency: *move_efficiency,
+                    max_targets: *max_targets,
+                    stun: *stun,
                 },
                 timer: Duration::default(),
                 stage_section: StageSection::Buildup,
@@ -664,6 +1382,8 @@ impl From<(&CharacterAbility, AbilityKey)> for CharacterState {
                 energy_regen,
                 energy_cost,
                 energy_drain,
+                damage_kind,
+                meta: _,
             } => CharacterState::BasicBeam(basic_beam::Data {
                 static_data: basic_beam::StaticData {
                     buildup_duration: *buildup_duration,
@@ -679,6 +1399,7 @@ impl From<(&CharacterAbility, AbilityKey)> for CharacterState {
                     energy_cost: *energy_cost,
                     energy_drain: *energy_drain,
                     ability_key: key,
+                    damage_kind: *damage_kind,
                 },
                 timer: Duration::default(),
                 stage_section: StageSection::Buildup,
@@ -692,3 +1413,614 @@ impl From<(&CharacterAbility, AbilityKey)> for CharacterState {
 impl Component for Loadout {
     type Storage = FlaggedStorage<Self, IdvStorage<Self>>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        comp::{
+            humanoid, item::ItemDef, Attacking, Controller, Energy, Ori, PhysicsState, Pos, Stats,
+            Vel,
+        },
+        event::{EventBus, ServerEvent},
+        gameplay::GameplaySettings,
+        state::{DeltaTime, State, Time},
+        sync::{Uid, WorldSyncExt},
+        terrain::TerrainGrid,
+        util::Dir,
+    };
+    use specs::{Builder, LazyUpdate, World, WorldExt};
+    use std::sync::Arc;
+    use vek::Vec2;
+
+    /// An idle humanoid for `requirements_paid` to check abilities against
+    struct User {
+        world: World,
+        entity: specs::Entity,
+        character: CharacterState,
+        energy: Energy,
+        loadout: Loadout,
+        physics: PhysicsState,
+        time: f64,
+    }
+
+    impl User {
+        fn new() -> Self {
+            let mut world = World::new();
+            world.register::<AbilityCooldowns>();
+            world.register::<Stunned>();
+            let entity = world.create_entity().build();
+            Self {
+                world,
+                entity,
+                character: CharacterState::Idle,
+                energy: Energy::new(1000),
+                loadout: Loadout::default(),
+                physics: PhysicsState::default(),
+                time: 0.0,
+            }
+        }
+
+        /// Tries to start `ability` from the slot `key`, applying anything it
+        /// records about the user
+        fn activate(
+            &mut self,
+            ability: &CharacterAbility,
+            key: AbilityKey,
+        ) -> Result<(), AbilityReqError> {
+            let body = Body::Humanoid(humanoid::Body::random());
+            let controller = Controller::default();
+            let result = {
+                let cooldowns = self.world.read_storage::<AbilityCooldowns>();
+                let stunned = self.world.read_storage::<Stunned>();
+                let data = JoinData {
+                    entity: self.entity,
+                    uid: &Uid(1),
+                    character: &self.character,
+                    pos: &Pos(Vec3::zero()),
+                    vel: &Vel(Vec3::zero()),
+                    ori: &Ori::default(),
+                    dt: &DeltaTime(0.05),
+                    time: &Time(self.time),
+                    controller: &controller,
+                    inputs: &controller.inputs,
+                    stats: &Stats::new("Test".to_string(), body),
+                    energy: &self.energy,
+                    loadout: &self.loadout,
+                    body: &body,
+                    physics: &self.physics,
+                    attacking: None,
+                    cooldowns: cooldowns.get(self.entity),
+                    stunned: stunned.get(self.entity),
+                    buffs: None,
+                    updater: &self.world.read_resource::<LazyUpdate>(),
+                    terrain: &TerrainGrid::new().unwrap(),
+                    tracked_positions: &hashbrown::HashMap::new(),
+                    gameplay: &GameplaySettings::default(),
+                };
+                let mut update = StateUpdate::from(&data);
+                let result = ability.requirements_paid(key, &data, &mut update);
+                self.energy = update.energy;
+                result
+            };
+            self.world.maintain();
+            result
+        }
+    }
+
+    #[test]
+    fn reach_extends_melee_range() {
+        let melee = CharacterAbility::BasicMelee {
+            energy_cost: 0,
+            buildup_duration: Duration::from_millis(100),
+            recover_duration: Duration::from_millis(300),
+            base_healthchange: -50,
+            knockback: Knockback::default(),
+            range: 3.0,
+            max_angle: 20.0,
+            dot: None,
+            damage_kind: DamageKind::Physical,
+            meta: AbilityMeta::default(),
+        };
+        let range = |ability: &CharacterAbility| match ability {
+            CharacterAbility::BasicMelee { range, .. } => *range,
+            _ => unreachable!(),
+        };
+        assert!((range(&melee.clone().with_reach(1.0)) - 3.0).abs() < f32::EPSILON);
+
+        let long = melee.with_reach(1.5);
+        assert!((range(&long) - 4.5).abs() < f32::EPSILON);
+
+        // A target just past the base range is now hit
+        let attack = Attacking {
+            base_damage: 50,
+            base_heal: 0,
+            damage_kind: DamageKind::Physical,
+            range: range(&long),
+            max_angle: 20_f32.to_radians(),
+            applied: false,
+            hit_count: 0,
+            knockback: Knockback::default(),
+            vertical_range: None,
+            max_targets: None,
+            dot: None,
+        };
+        let hits = |dist: f32| {
+            crate::sys::combat::hits_target(
+                &attack,
+                Vec3::zero(),
+                Vec2::unit_y(),
+                1.0,
+                Vec3::new(0.0, dist, 0.0),
+                0.5,
+                1.8,
+            )
+        };
+        assert!(hits(4.0));
+        assert!(!hits(5.5));
+    }
+
+    #[test]
+    fn reach_keeps_cooldown() {
+        let ability = CharacterAbility::SpinMelee {
+            buildup_duration: Duration::from_millis(100),
+            swing_duration: Duration::from_millis(250),
+            recover_duration: Duration::from_millis(100),
+            base_damage: 60,
+            knockback: Knockback::default(),
+            range: 2.0,
+            vertical_range: 4.0,
+            energy_cost: 100,
+            is_infinite: true,
+            is_helicopter: true,
+            is_interruptible: false,
+            forward_speed: 0.0,
+            num_spins: 1,
+            max_targets: None,
+            self_buff: None,
+            damage_kind: DamageKind::Physical,
+            meta: AbilityMeta::default(),
+        }
+        .with_cooldown(Duration::from_secs(1))
+        .with_reach(2.0);
+        match ability {
+            CharacterAbility::SpinMelee { range, .. } => {
+                assert!((range - 4.0).abs() < f32::EPSILON)
+            },
+            _ => unreachable!(),
+        }
+        assert_eq!(ability.meta().cooldown, Some(Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn healing_bomb_heals_the_caster() {
+        let sceptre = ItemConfig::from(Item::new_from_asset_expect(
+            "common.items.weapons.sceptre.starter_sceptre",
+        ));
+        let heal = match sceptre
+            .ability2
+            .as_ref()
+            .map(|bomb| bomb.activation_effects())
+        {
+            Some([ActivationEffect::HealSelf(heal)]) => *heal as i32,
+            effects => panic!("Unexpected healing bomb effects {:?}", effects),
+        };
+        assert!(heal > 0);
+
+        let mut state = State::default();
+        let body = Body::Humanoid(humanoid::Body::random());
+        let mut controller = Controller::default();
+        controller.inputs.secondary.set_state(true);
+        let caster = state
+            .ecs_mut()
+            .create_entity_synced()
+            .with(Pos(Vec3::zero()))
+            .with(Vel::default())
+            .with(Ori(Dir::new(Vec3::unit_y())))
+            .with(Energy::new(1000))
+            .with(controller)
+            .with(Stats::new("Test".to_string(), body))
+            .with(body)
+            .with(PhysicsState::default())
+            .with(Loadout {
+                active_item: Some(sceptre),
+                ..Loadout::default()
+            })
+            .with(CharacterState::Wielding)
+            .build();
+        let caster_uid = *state.ecs().read_storage::<Uid>().get(caster).unwrap();
+
+        state.tick(Duration::from_millis(50), |_| {}, false);
+
+        assert!(matches!(
+            state.ecs().read_storage::<CharacterState>().get(caster),
+            Some(CharacterState::BasicRanged(_))
+        ));
+        let heals = state
+            .ecs()
+            .read_resource::<EventBus<ServerEvent>>()
+            .recv_all()
+            .filter_map(|event| match event {
+                ServerEvent::Damage { uid, change } if uid == caster_uid => Some(change.amount),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(heals, vec![heal]);
+    }
+
+    #[test]
+    fn cooldown_blocks_until_elapsed() {
+        let cooldown = Duration::from_secs(5);
+        let mut cooldowns = AbilityCooldowns::default();
+        assert_eq!(
+            cooldowns.remaining(AbilityKey::Skill1, cooldown, 10.0),
+            None
+        );
+
+        cooldowns.mark_used(AbilityKey::Skill1, 10.0);
+        let remaining = cooldowns.remaining(AbilityKey::Skill1, cooldown, 12.0);
+        assert!(remaining.map_or(false, |r| (r.as_secs_f64() - 3.0).abs() < 1e-6));
+        assert_eq!(
+            cooldowns.remaining(AbilityKey::Skill1, cooldown, 15.0),
+            None
+        );
+        assert_eq!(
+            cooldowns.remaining(AbilityKey::Skill1, cooldown, 20.0),
+            None
+        );
+    }
+
+    #[test]
+    fn stun_blocks_abilities_until_expiry() {
+        let ability = CharacterAbility::Boost {
+            duration: Duration::from_millis(50),
+            only_up: false,
+            meta: AbilityMeta::default(),
+        };
+
+        let mut user = User::new();
+        user.time = 10.0;
+        assert_eq!(user.activate(&ability, AbilityKey::Mouse1), Ok(()));
+
+        user.world
+            .write_storage()
+            .insert(user.entity, Stunned::new(10.0, Duration::from_secs(2)))
+            .unwrap();
+        assert_eq!(
+            user.activate(&ability, AbilityKey::Mouse1),
+            Err(AbilityReqError::Stunned)
+        );
+        user.time = 11.9;
+        assert_eq!(
+            user.activate(&ability, AbilityKey::Skill1),
+            Err(AbilityReqError::Stunned)
+        );
+        user.time = 12.0;
+        assert_eq!(user.activate(&ability, AbilityKey::Mouse1), Ok(()));
+    }
+
+    fn finisher(min_combo: u32) -> CharacterAbility {
+        CharacterAbility::ComboFinisher {
+            min_combo,
+            base_damage: 50,
+            damage_per_combo: 15,
+            buildup_duration: Duration::from_millis(300),
+            recover_duration: Duration::from_millis(500),
+            knockback: Knockback::default(),
+            range: 3.5,
+            max_angle: 30.0,
+            meta: AbilityMeta::default(),
+        }
+    }
+
+    #[test]
+    fn finisher_blocked_below_min_combo() {
+        let mut user = User::new();
+        let finisher = finisher(3);
+        assert_eq!(
+            user.activate(&finisher, AbilityKey::Skill1),
+            Err(AbilityReqError::ComboTooShort {
+                combo: 0,
+                required: 3
+            })
+        );
+
+        // The finisher follows up on the combo of the sword's basic attack
+        let swing = ItemConfig::from(Item::new_from_asset_expect(
+            "common.items.weapons.sword.zweihander_sword_0",
+        ))
+        .ability1
+        .unwrap();
+        let mut combo = |combo| {
+            let mut state = CharacterState::from((&swing, AbilityKey::Mouse1));
+            match &mut state {
+                CharacterState::ComboMelee(data) => data.combo = combo,
+                _ => panic!("expected the sword's basic attack to be a combo"),
+            }
+            user.character = state;
+            user.activate(&finisher, AbilityKey::Skill1)
+        };
+        assert_eq!(
+            combo(2),
+            Err(AbilityReqError::ComboTooShort {
+                combo: 2,
+                required: 3
+            })
+        );
+        assert_eq!(combo(3), Ok(()));
+        assert_eq!(combo(7), Ok(()));
+    }
+
+    #[test]
+    fn finisher_damage_scales_with_combo() {
+        let ability = finisher(3).with_cooldown(Duration::from_secs(5));
+        assert_eq!(ability.finisher_damage(3), Some(95));
+        assert_eq!(ability.finisher_damage(10), Some(200));
+        let roll = CharacterAbility::Roll {
+            meta: AbilityMeta::default(),
+        };
+        assert_eq!(roll.finisher_damage(10), None);
+
+        match (&ability, AbilityKey::Skill1).into() {
+            CharacterState::BasicMelee(data) => assert_eq!(data.base_healthchange, -50),
+            _ => panic!("expected a melee strike"),
+        }
+    }
+
+    #[test]
+    fn cooldowns_are_per_slot() {
+        let cooldown = Duration::from_secs(5);
+        let mut cooldowns = AbilityCooldowns::default();
+        cooldowns.mark_used(AbilityKey::Mouse2, 1.0);
+        assert!(cooldowns
+            .remaining(AbilityKey::Mouse2, cooldown, 2.0)
+            .is_some());
+        assert_eq!(cooldowns.remaining(AbilityKey::Mouse1, cooldown, 2.0), None);
+    }
+
+    #[test]
+    fn cooldown_keeps_state() {
+        let ability = CharacterAbility::Boost {
+            duration: Duration::from_millis(50),
+            only_up: true,
+            meta: AbilityMeta::default(),
+        };
+        let state = CharacterState::from((&ability, AbilityKey::Mouse1));
+        let limited = ability.with_cooldown(Duration::from_secs(1));
+        assert_eq!(CharacterState::from((&limited, AbilityKey::Mouse1)), state);
+    }
+
+    #[test]
+    fn cooldown_blocks_reuse() {
+        let ability = CharacterAbility::Boost {
+            duration: Duration::from_millis(50),
+            only_up: false,
+            meta: AbilityMeta::default(),
+        }
+        .with_cooldown(Duration::from_secs(5));
+
+        let mut user = User::new();
+        user.time = 10.0;
+        assert_eq!(user.activate(&ability, AbilityKey::Skill1), Ok(()));
+
+        user.time = 12.0;
+        match user.activate(&ability, AbilityKey::Skill1) {
+            Err(AbilityReqError::OnCooldown(remaining)) => {
+                assert!((remaining.as_secs_f64() - 3.0).abs() < 1e-6)
+            },
+            result => panic!("expected a cooldown, got {:?}", result),
+        }
+        // Other slots have their own cooldowns
+        assert_eq!(user.activate(&ability, AbilityKey::Mouse2), Ok(()));
+
+        user.time = 15.0;
+        assert_eq!(user.activate(&ability, AbilityKey::Skill1), Ok(()));
+        user.time = 16.0;
+        assert!(matches!(
+            user.activate(&ability, AbilityKey::Skill1),
+            Err(AbilityReqError::OnCooldown(_))
+        ));
+    }
+
+    #[test]
+    fn unpaid_abilities_dont_start_cooldown() {
+        let ability = CharacterAbility::LeapMelee {
+            energy_cost: 600,
+            buildup_duration: Duration::from_millis(100),
+            movement_duration: Duration::from_millis(100),
+            swing_duration: Duration::from_millis(100),
+            recover_duration: Duration::from_millis(100),
+            base_damage: 100,
+            range: 3.0,
+            max_angle: 50.0,
+            knockback: Knockback::default(),
+            forward_leap_strength: 20.0,
+            vertical_leap_strength: 8.0,
+            impact_radius: 3.0,
+            damage_kind: DamageKind::Physical,
+            meta: AbilityMeta::default(),
+        }
+        .with_cooldown(Duration::from_secs(5));
+
+        let mut user = User::new();
+        user.energy = Energy::new(500);
+        assert_eq!(
+            user.activate(&ability, AbilityKey::Skill1),
+            Err(AbilityReqError::RequirementsUnmet)
+        );
+        user.energy = Energy::new(1000);
+        assert_eq!(user.activate(&ability, AbilityKey::Skill1), Ok(()));
+    }
+
+    fn set_piece(kind: &str, set_id: Option<&str>) -> Option<Item> {
+        let def = ron::de::from_str::<ItemDef>(&format!(
+            "(name: \"Test\", description: \"\", kind: Armor((kind: {}(\"Test\"), stats: \
+             (protection: Normal(1.0)), set_id: {})), quality: Common)",
+            kind,
+            set_id.map_or("None".to_string(), |id| format!("Some(\"{}\")", id)),
+        ))
+        .unwrap();
+        Some(Item::new(Arc::new(def)))
+    }
+
+    #[test]
+    fn partial_set_grants_lower_threshold() {
+        let loadout = Loadout {
+            chest: set_piece("Chest", Some("Plate")),
+            pants: set_piece("Pants", Some("Plate")),
+            foot: set_piece("Foot", Some("Plate")),
+            hand: set_piece("Hand", Some("Leather")),
+            head: set_piece("Head", None),
+            ..Loadout::default()
+        };
+        assert_eq!(loadout.active_set_bonuses(), vec![SetBonus::Protection(
+            10.0
+        )]);
+    }
+
+    #[test]
+    fn full_set_grants_all_bonuses() {
+        let loadout = Loadout {
+            chest: set_piece("Chest", Some("Plate")),
+            pants: set_piece("Pants", Some("Plate")),
+            foot: set_piece("Foot", Some("Plate")),
+            hand: set_piece("Hand", Some("Plate")),
+            head: set_piece("Head", Some("Plate")),
+            ..Loadout::default()
+        };
+        assert_eq!(
+            loadout.active_set_bonuses(),
+            SET_BONUSES
+                .iter()
+                .map(|(_, bonus)| *bonus)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(loadout.set_bonus_energy(), 200);
+        assert!((loadout.set_bonus_speed() - 0.05).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn set_bonus_reduces_damage() {
+        let mixed = Loadout {
+            chest: set_piece("Chest", Some("Plate")),
+            pants: set_piece("Pants", Some("Plate")),
+            foot: set_piece("Foot", Some("Leather")),
+            ..Loadout::default()
+        };
+        let set = Loadout {
+            foot: set_piece("Foot", Some("Plate")),
+            ..mixed.clone()
+        };
+        // 3 protection from the armor and 10 from the set
+        assert!((mixed.get_damage_reduction() - 3.0 / 63.0).abs() < 1e-6);
+        assert!((set.get_damage_reduction() - 13.0 / 73.0).abs() < 1e-6);
+    }
+
+    fn holding(asset: Option<&str>) -> Loadout {
+        Loadout {
+            active_item: asset.map(|asset| ItemConfig::from(Item::new_from_asset_expect(asset))),
+            ..Loadout::default()
+        }
+    }
+
+    #[test]
+    fn dagger_slash_bleeds() {
+        let dagger = holding(Some("common.items.weapons.dagger.starter_dagger"));
+        match dagger.active_item.and_then(|item| item.ability1) {
+            Some(CharacterAbility::BasicMelee { dot: Some(dot), .. }) => {
+                assert_eq!(dot.kind, DamageKind::Physical);
+                assert!(dot.tick_damage > 0);
+                assert!(dot.ticks() > 1);
+            },
+            ability => panic!("Expected a bleeding slash, got {:?}", ability),
+        }
+    }
+
+    #[test]
+    fn hand_requirements_check_both_hands() {
+        let sword = holding(Some("common.items.weapons.sword.zweihander_sword_0"));
+        let dagger = holding(Some("common.items.weapons.dagger.starter_dagger"));
+        let empty_handed = holding(None);
+
+        for loadout in &[&sword, &dagger, &empty_handed] {
+            assert!(HandRequirement::Any.met_by(loadout));
+        }
+
+        assert!(HandRequirement::OneHanded.met_by(&dagger));
+        assert!(!HandRequirement::OneHanded.met_by(&sword));
+        assert!(!HandRequirement::OneHanded.met_by(&empty_handed));
+
+        assert!(HandRequirement::TwoHanded.met_by(&sword));
+        assert!(!HandRequirement::TwoHanded.met_by(&dagger));
+        assert!(!HandRequirement::TwoHanded.met_by(&empty_handed));
+
+        assert!(HandRequirement::Empty.met_by(&empty_handed));
+        assert!(!HandRequirement::Empty.met_by(&sword));
+        assert!(!HandRequirement::Empty.met_by(&dagger));
+
+        // Something in the off hand
+        let shield = || {
+            Some(ItemConfig::from(Item::new_from_asset_expect(
+                "common.items.weapons.shield.shield_1",
+            )))
+        };
+        let dagger_and_shield = Loadout {
+            second_item: shield(),
+            ..dagger.clone()
+        };
+        let sword_and_shield = Loadout {
+            second_item: shield(),
+            ..sword.clone()
+        };
+        let shield_only = Loadout {
+            second_item: shield(),
+            ..empty_handed.clone()
+        };
+        assert!(HandRequirement::OneHanded.met_by(&dagger_and_shield));
+        assert!(!HandRequirement::TwoHanded.met_by(&sword_and_shield));
+        assert!(!HandRequirement::Empty.met_by(&shield_only));
+        assert!(!HandRequirement::OneHanded.met_by(&shield_only));
+    }
+
+    #[test]
+    fn wrong_hands_rejected() {
+        let boost = CharacterAbility::Boost {
+            duration: Duration::from_millis(50),
+            only_up: false,
+            meta: AbilityMeta::default(),
+        };
+        let unarmed = boost.clone().with_hand_requirement(HandRequirement::Empty);
+        let two_handed = boost.with_hand_requirement(HandRequirement::TwoHanded);
+
+        let mut user = User::new();
+        assert_eq!(user.activate(&unarmed, AbilityKey::Skill1), Ok(()));
+        assert_eq!(
+            user.activate(&two_handed, AbilityKey::Skill1),
+            Err(AbilityReqError::WrongHands)
+        );
+
+        user.loadout = holding(Some("common.items.weapons.sword.zweihander_sword_0"));
+        assert_eq!(user.activate(&two_handed, AbilityKey::Skill1), Ok(()));
+
+        // A shield in the off hand ties up the hands either ability needs
+        user.loadout.second_item = Some(ItemConfig::from(Item::new_from_asset_expect(
+            "common.items.weapons.shield.shield_1",
+        )));
+        assert_eq!(
+            user.activate(&two_handed, AbilityKey::Skill1),
+            Err(AbilityReqError::WrongHands)
+        );
+        user.loadout.active_item = None;
+        assert_eq!(
+            user.activate(&unarmed, AbilityKey::Skill1),
+            Err(AbilityReqError::WrongHands)
+        );
+    }
+
+    #[test]
+    fn placeholder_tool_counts_as_empty_hands() {
+        let npc = holding(Some("common.items.weapons.empty.empty"));
+        assert!(HandRequirement::Empty.met_by(&npc));
+        assert!(!HandRequirement::OneHanded.met_by(&npc));
+    }
+}
@@ -3,9 +3,15 @@ use crate::{
     path::Chaser,
     sync::Uid,
 };
+use hashbrown::HashMap;
 use specs::{Component, Entity as EcsEntity};
 use specs_idvs::IdvStorage;
 use vek::*;
+
+/// Seconds it takes for threat to fall to half
+const THREAT_HALF_LIFE: f32 = 10.0;
+/// Threat below which an attacker is forgotten
+const MIN_THREAT: f32 = 1.0;
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum Alignment {
     /// Wild animals and gentle giants
@@ -164,6 +170,51 @@ impl Component for Agent {
     type Storage = IdvStorage<Self>;
 }
 
+/// How much each attacker has provoked an entity, built up from the damage
+/// they dealt it and fading over time
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ThreatTable {
+    threat: HashMap<Uid, f32>,
+}
+
+impl ThreatTable {
+    pub fn add(&mut self, attacker: Uid, damage: f32) {
+        *self.threat.entry(attacker).or_insert(0.0) += damage.max(0.0);
+    }
+
+    /// Lets threat fade over `dt` seconds, forgetting attackers who haven't
+    /// kept it up
+    pub fn decay(&mut self, dt: f32) {
+        let factor = 0.5f32.powf(dt / THREAT_HALF_LIFE);
+        self.threat.retain(|_, threat| {
+            *threat *= factor;
+            *threat >= MIN_THREAT
+        });
+    }
+
+    pub fn get(&self, attacker: Uid) -> f32 { self.threat.get(&attacker).copied().unwrap_or(0.0) }
+
+    pub fn contains(&self, attacker: Uid) -> bool { self.threat.contains_key(&attacker) }
+
+    pub fn is_empty(&self) -> bool { self.threat.is_empty() }
+
+    /// The `n` attackers with the most threat, highest first
+    pub fn top(&self, n: usize) -> Vec<(Uid, f32)> {
+        let mut top = self
+            .threat
+            .iter()
+            .map(|(uid, threat)| (*uid, *threat))
+            .collect::<Vec<_>>();
+        top.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        top.truncate(n);
+        top
+    }
+}
+
+impl Component for ThreatTable {
+    type Storage = IdvStorage<Self>;
+}
+
 #[derive(Clone, Debug)]
 pub enum Activity {
     Idle(Vec2<f32>),
@@ -189,3 +240,38 @@ impl Activity {
 impl Default for Activity {
     fn default() -> Self { Activity::Idle(Vec2::zero()) }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn more_damage_more_threat() {
+        let mut table = ThreatTable::default();
+        table.add(Uid(1), 30.0);
+        table.add(Uid(2), 50.0);
+        assert_eq!(table.top(1)[0].0, Uid(2));
+
+        table.add(Uid(1), 40.0);
+        assert!((table.get(Uid(1)) - 70.0).abs() < f32::EPSILON);
+        assert_eq!(
+            table.top(5).iter().map(|(uid, _)| *uid).collect::<Vec<_>>(),
+            vec![Uid(1), Uid(2)]
+        );
+    }
+
+    #[test]
+    fn stale_threat_decays() {
+        let mut table = ThreatTable::default();
+        table.add(Uid(1), 40.0);
+        table.add(Uid(2), 2.0);
+
+        table.decay(THREAT_HALF_LIFE);
+        assert!((table.get(Uid(1)) - 20.0).abs() < 0.001);
+        assert!((table.get(Uid(2)) - 1.0).abs() < 0.001);
+
+        table.decay(THREAT_HALF_LIFE);
+        assert!(!table.contains(Uid(2)));
+        assert!(table.contains(Uid(1)));
+    }
+}
@@ -213,6 +213,23 @@ impl Body {
         }
     }
 
+    /// The region, relative to the body's position, that takes extra damage
+    /// from projectiles (i.e. the head). Bodies without an obvious weak spot
+    /// return `None`. This ignores orientation, so only upright bodies with
+    /// their head on top have one.
+    pub fn weakpoint_aabb(&self) -> Option<Aabb<f32>> {
+        let head_height = match self {
+            Body::Humanoid(_) => 0.25,
+            Body::BipedLarge(_) | Body::Golem(_) => 0.2,
+            _ => return None,
+        } * self.height();
+        let head_radius = self.radius() * 0.7;
+        Some(Aabb {
+            min: Vec3::new(-head_radius, -head_radius, self.height() - head_height),
+            max: Vec3::new(head_radius, head_radius, self.height()),
+        })
+    }
+
     pub fn base_energy(&self) -> u32 {
         match self {
             Body::BipedLarge(biped_large) => match biped_large.species {
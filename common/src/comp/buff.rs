@@ -17,6 +17,10 @@ pub enum BuffKind {
     /// Prefixes an entity's name with "Cursed"
     /// Currently placeholder buff to show other stuff is possible
     Cursed,
+    /// Increases movement speed
+    Swiftness,
+    /// Reduces damage taken
+    Protection,
 }
 
 impl BuffKind {
@@ -26,6 +30,8 @@ impl BuffKind {
             BuffKind::Regeneration { .. } => true,
             BuffKind::Bleeding { .. } => false,
             BuffKind::Cursed { .. } => false,
+            BuffKind::Swiftness { .. } => true,
+            BuffKind::Protection { .. } => true,
         }
     }
 }
@@ -47,6 +53,8 @@ pub enum BuffCategory {
     Magical,
     Divine,
     PersistOnDeath,
+    /// Granted by the ability the entity is using, and removed when it ends
+    FromActiveAbility,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -62,6 +70,10 @@ pub enum BuffEffect {
     HealthChangeOverTime { rate: f32, accumulated: f32 },
     /// Changes maximum health by a certain amount
     MaxHealthModifier { value: f32, kind: ModifierKind },
+    /// Multiplies movement speed
+    MovementSpeed(f32),
+    /// Reduces incoming damage by a fraction
+    DamageReduction(f32),
 }
 
 /// Actual de/buff.
@@ -137,6 +149,14 @@ impl Buff {
                 }],
                 data.duration,
             ),
+            BuffKind::Swiftness => (
+                vec![BuffEffect::MovementSpeed(1.0 + data.strength)],
+                data.duration,
+            ),
+            BuffKind::Protection => (
+                vec![BuffEffect::DamageReduction(data.strength.min(1.0).max(0.0))],
+                data.duration,
+            ),
         };
         Buff {
             kind,
@@ -270,9 +290,36 @@ impl Buffs {
             .map(|buff| buff.unwrap())
     }
 
+    /// How many buffs of a given kind are applied, whether or not they're the
+    /// one in effect
+    pub fn stacks(&self, kind: BuffKind) -> usize { self.kinds.get(&kind).map_or(0, Vec::len) }
+
     // Gets most powerful buff of a given kind
     // pub fn get_active_kind(&self, kind: BuffKind) -> Buff
 
+    /// Multiplier applied to movement speed by the active buffs
+    pub fn movement_speed(&self) -> f32 {
+        self.iter_active()
+            .flat_map(|buff| buff.effects.iter())
+            .map(|effect| match effect {
+                BuffEffect::MovementSpeed(mult) => *mult,
+                _ => 1.0,
+            })
+            .product()
+    }
+
+    /// Fraction of incoming damage prevented by the active buffs
+    pub fn damage_reduction(&self) -> f32 {
+        1.0 - self
+            .iter_active()
+            .flat_map(|buff| buff.effects.iter())
+            .map(|effect| match effect {
+                BuffEffect::DamageReduction(reduction) => 1.0 - *reduction,
+                _ => 1.0,
+            })
+            .product::<f32>()
+    }
+
     pub fn remove(&mut self, buff_id: BuffId) {
         let kind = self.buffs.remove(&buff_id).unwrap().kind;
         self.kinds
@@ -287,3 +334,71 @@ pub type BuffId = u64;
 impl Component for Buffs {
     type Storage = FlaggedStorage<Self, IdvStorage<Self>>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{msg::EcsCompPacket, sync::UpdateTracker};
+    use specs::{Builder, World, WorldExt};
+
+    fn bleed(strength: f32) -> Buff {
+        Buff::new(
+            BuffKind::Bleeding,
+            BuffData {
+                strength,
+                duration: Some(Duration::from_secs(10)),
+            },
+            vec![BuffCategory::Physical],
+            BuffSource::Character { by: Uid(4) },
+        )
+    }
+
+    // Buffs reach clients through the component sync, so the icons they draw
+    // come from what survives the trip
+    fn synced(buffs: &Buffs) -> Buffs {
+        let ser = bincode::serialize(&EcsCompPacket::Buffs(buffs.clone())).unwrap();
+        match bincode::deserialize(&ser).unwrap() {
+            EcsCompPacket::Buffs(buffs) => buffs,
+            _ => panic!("Expected Buffs"),
+        }
+    }
+
+    #[test]
+    fn buffs_sync_kind_time_and_stacks() {
+        let mut buffs = Buffs::default();
+        buffs.insert(bleed(5.0));
+        buffs.insert(bleed(2.0));
+
+        let de = synced(&buffs);
+        assert_eq!(de.stacks(BuffKind::Bleeding), 2);
+        assert_eq!(de.stacks(BuffKind::Regeneration), 0);
+        let active = de.iter_active().collect::<Vec<_>>();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].kind, BuffKind::Bleeding);
+        assert_eq!(active[0].time, Some(Duration::from_secs(10)));
+        assert!((active[0].data.strength - 5.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn removed_buffs_sync_as_cleared() {
+        let mut world = World::new();
+        world.register::<Buffs>();
+        let mut buffs = Buffs::default();
+        buffs.insert(bleed(5.0));
+        let entity = world.create_entity().with(buffs).build();
+        let mut tracker = UpdateTracker::<Buffs>::new(&mut world);
+
+        world
+            .write_storage::<Buffs>()
+            .get_mut(entity)
+            .unwrap()
+            .remove_kind(BuffKind::Bleeding);
+        // Only flagged changes are sent, so the removal has to be flagged
+        tracker.record_changes(&world.read_storage());
+        assert!(tracker.modified().contains(entity.id()));
+
+        let de = synced(world.read_storage::<Buffs>().get(entity).unwrap());
+        assert_eq!(de.stacks(BuffKind::Bleeding), 0);
+        assert_eq!(de.iter_active().count(), 0);
+    }
+}
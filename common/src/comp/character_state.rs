@@ -1,13 +1,18 @@
 use crate::{
-    comp::{Energy, Ori, Pos, Vel},
+    comp::{
+        ActivationEffect, Buff, BuffCategory, BuffChange, BuffData, BuffKind, BuffSource,
+        DamageKind, DamageOverTime, Energy, EnergySource, HealthChange, HealthSource, Knockback,
+        Ori, Pos, Vel,
+    },
     event::{LocalEvent, ServerEvent},
-    states::*,
+    states::{utils::StageSection, *},
+    sync::Uid,
     sys::character_behavior::JoinData,
 };
 use serde::{Deserialize, Serialize};
-use specs::{Component, FlaggedStorage, VecStorage};
+use specs::{Component, Entity, FlaggedStorage, VecStorage};
 use specs_idvs::IdvStorage;
-use std::collections::VecDeque;
+use std::{collections::VecDeque, time::Duration};
 
 /// Data returned from character behavior fn's to Character Behavior System.
 pub struct StateUpdate {
@@ -35,6 +40,48 @@ impl From<&JoinData<'_>> for StateUpdate {
         }
     }
 }
+impl StateUpdate {
+    /// Applies the effects of `entity` starting an ability, see
+    /// `CharacterAbility::activation_effects`.
+    pub fn apply_activation_effects(
+        &mut self,
+        entity: Entity,
+        uid: Uid,
+        effects: &[ActivationEffect],
+    ) {
+        for effect in effects {
+            match effect {
+                ActivationEffect::HealSelf(amount) => {
+                    self.server_events.push_back(ServerEvent::Damage {
+                        uid,
+                        change: HealthChange {
+                            amount: *amount as i32,
+                            cause: HealthSource::Healing { by: Some(uid) },
+                        },
+                    })
+                },
+                ActivationEffect::GrantEnergy(amount) => {
+                    self.energy.change_by(*amount as i32, EnergySource::Ability)
+                },
+                ActivationEffect::Buff(kind, strength, duration) => {
+                    self.server_events.push_back(ServerEvent::Buff {
+                        entity,
+                        buff_change: BuffChange::Add(Buff::new(
+                            *kind,
+                            BuffData {
+                                strength: *strength,
+                                duration: Some(*duration),
+                            },
+                            Vec::new(),
+                            BuffSource::Character { by: uid },
+                        )),
+                    })
+                },
+            }
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum CharacterState {
     Idle,
@@ -42,10 +89,12 @@ pub enum CharacterState {
     Sit,
     Dance,
     Sneak,
+    /// Run faster than walking, draining energy
+    Sprint,
     Glide,
     GlideWield,
     /// A basic blocking state
-    BasicBlock,
+    BasicBlock(basic_block::Data),
     /// Player is busy equipping or unequipping weapons
     Equipping(equipping::Data),
     /// Player is holding a weapon and can perform other actions
@@ -78,6 +127,14 @@ pub enum CharacterState {
     /// A continuous attack that affects all creatures in a cone originating
     /// from the source
     BasicBeam(basic_beam::Data),
+    /// Take a collectible sprite from the terrain
+    Harvest(harvest::Data),
+    /// Put a block into the terrain
+    PlaceBlock(place_block::Data),
+    /// Exchange the active and second weapons, unable to attack meanwhile
+    SwapWeapons(swap_weapons::Data),
+    /// Fire a tether at a block and get pulled towards it
+    Grapple(grapple::Data),
 }
 
 impl CharacterState {
@@ -88,7 +145,7 @@ impl CharacterState {
             | CharacterState::BasicRanged(_)
             | CharacterState::DashMelee(_)
             | CharacterState::ComboMelee(_)
-            | CharacterState::BasicBlock
+            | CharacterState::BasicBlock(_)
             | CharacterState::LeapMelee(_)
             | CharacterState::SpinMelee(_)
             | CharacterState::ChargedMelee(_)
@@ -121,7 +178,7 @@ impl CharacterState {
             | CharacterState::BasicRanged(_)
             | CharacterState::DashMelee(_)
             | CharacterState::ComboMelee(_)
-            | CharacterState::BasicBlock
+            | CharacterState::BasicBlock(_)
             | CharacterState::LeapMelee(_)
             | CharacterState::ChargedMelee(_)
             | CharacterState::ChargedRanged(_)
@@ -131,10 +188,119 @@ impl CharacterState {
         )
     }
 
-    pub fn is_block(&self) -> bool { matches!(self, CharacterState::BasicBlock) }
+    pub fn is_block(&self) -> bool { matches!(self, CharacterState::BasicBlock(_)) }
+
+    /// Fraction of incoming damage stopped by blocking, if blocking
+    pub fn block_strength(&self) -> Option<f32> {
+        match self {
+            CharacterState::BasicBlock(data) => Some(data.block_strength),
+            _ => None,
+        }
+    }
+
+    /// Whether a projectile arriving now would be reflected by a block
+    pub fn can_reflect(&self) -> bool {
+        matches!(self, CharacterState::BasicBlock(data) if data.can_reflect())
+    }
 
     pub fn is_dodge(&self) -> bool { matches!(self, CharacterState::Roll(_)) }
 
+    /// Number of consecutive strikes in the current combo, if in one
+    pub fn combo(&self) -> u32 {
+        match self {
+            CharacterState::ComboMelee(data) => data.combo,
+            _ => 0,
+        }
+    }
+
+    /// Whether the current ability may be cancelled in its current stage.
+    /// Abilities can be cancelled while being prepared or charged, but not
+    /// once they've been released.
+    pub fn is_interruptible(&self) -> bool {
+        match self {
+            CharacterState::BasicRanged(data) => !data.exhausted,
+            CharacterState::ChargedRanged(data) => !data.exhausted,
+            CharacterState::DashMelee(data) => data.stage_section == StageSection::Buildup,
+            CharacterState::ComboMelee(data) => data.stage_section == StageSection::Buildup,
+            CharacterState::LeapMelee(data) => data.stage_section == StageSection::Buildup,
+            CharacterState::SpinMelee(data) => data.stage_section == StageSection::Buildup,
+            CharacterState::ChargedMelee(data) => data.stage_section == StageSection::Charge,
+            CharacterState::RepeaterRanged(data) => data.stage_section == StageSection::Buildup,
+            CharacterState::Shockwave(data) => data.stage_section == StageSection::Buildup,
+            CharacterState::BasicBeam(data) => data.stage_section == StageSection::Buildup,
+            CharacterState::Harvest(data) => data.stage_section == StageSection::Buildup,
+            CharacterState::PlaceBlock(_) => true,
+            CharacterState::Grapple(data) => data.stage_section == StageSection::Buildup,
+            _ => false,
+        }
+    }
+
+    /// How much energy is given back when the current ability is cancelled,
+    /// i.e. the energy spent charging it so far.
+    pub fn refund_on_cancel(&self) -> u32 {
+        match self {
+            CharacterState::ChargedRanged(data) if !data.exhausted => {
+                (data.energy_drain as f32 * data.charge_timer.as_secs_f32()) as u32
+            },
+            CharacterState::ChargedMelee(data) if data.stage_section == StageSection::Charge => {
+                (data.static_data.energy_drain as f32
+                    * data.static_data.charge_duration.as_secs_f32()
+                    * data.charge_amount) as u32
+            },
+            _ => 0,
+        }
+    }
+
+    /// Cancels the current ability if it's interruptible, returning how much
+    /// energy should be refunded.
+    pub fn try_cancel(&mut self) -> Option<u32> {
+        if self.is_interruptible() {
+            let refund = self.refund_on_cancel();
+            *self = CharacterState::Wielding;
+            Some(refund)
+        } else {
+            None
+        }
+    }
+
+    /// The buff this state grants its character while it lasts, as (kind,
+    /// strength, max duration)
+    pub fn self_buff(&self) -> Option<(BuffKind, f32, Duration)> {
+        match self {
+            CharacterState::BasicBlock(data) => data.self_buff,
+            CharacterState::SpinMelee(data) => data.static_data.self_buff,
+            _ => None,
+        }
+    }
+
+    /// Changes to the character's buffs when it goes from this state into
+    /// `next`: entering a state applies its buff, and leaving it removes it.
+    pub fn self_buff_changes(&self, next: &Self, by: Uid) -> Vec<BuffChange> {
+        if self.same_variant(next) {
+            return Vec::new();
+        }
+        let mut changes = Vec::new();
+        if self.self_buff().is_some() {
+            changes.push(BuffChange::RemoveByCategory {
+                all_required: vec![BuffCategory::FromActiveAbility],
+                any_required: vec![],
+                none_required: vec![],
+            });
+        }
+        if let Some((kind, strength, duration)) = next.self_buff() {
+            changes.push(BuffChange::Add(Buff::new(
+                kind,
+                BuffData {
+                    strength,
+                    duration: Some(duration),
+                },
+                vec![BuffCategory::FromActiveAbility],
+                BuffSource::Character { by },
+            )));
+        }
+        changes
+    }
+
     /// Compares for shallow equality (does not check internal struct equality)
     pub fn same_variant(&self, other: &Self) -> bool {
         // Check if state is the same without looking at the inner data
@@ -154,13 +320,144 @@ impl Component for CharacterState {
 pub struct Attacking {
     pub base_damage: u32,
     pub base_heal: u32,
+    pub damage_kind: DamageKind,
     pub range: f32,
     pub max_angle: f32,
     pub applied: bool,
     pub hit_count: u32,
-    pub knockback: f32,
+    pub knockback: Knockback,
+    /// If set, targets are only hit if they overlap the vertical band this far
+    /// above and below the attacker, and range is measured horizontally
+    pub vertical_range: Option<f32>,
+    /// If set, at most this many targets are hit, nearest first
+    pub max_targets: Option<u32>,
+    /// Damage dealt over time to each target that is damaged by the attack
+    pub dot: Option<DamageOverTime>,
 }
 
 impl Component for Attacking {
     type Storage = VecStorage<Self>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::comp::{object, Body};
+    use std::time::Duration;
+
+    fn charged_ranged(prepare_duration: Duration, charge_timer: Duration) -> CharacterState {
+        CharacterState::ChargedRanged(charged_ranged::Data {
+            exhausted: false,
+            energy_drain: 300,
+            initial_damage: 10,
+            max_damage: 200,
+            initial_knockback: Knockback::launch(10.0),
+            max_knockback: Knockback::launch(20.0),
+            prepare_duration,
+            charge_duration: Duration::from_millis(1500),
+            charge_timer,
+            recover_duration: Duration::from_millis(500),
+            projectile_body: Body::Object(object::Body::Arrow),
+            projectile_light: None,
+            projectile_gravity: None,
+            initial_projectile_speed: 100.0,
+            max_projectile_speed: 500.0,
+            pierce: 0,
+            bounces: 0,
+            bounce_damping: 0.0,
+            sweet_spot: None,
+            projectile_lifetime: Duration::from_secs(15),
+            damage_kind: DamageKind::Physical,
+            weakpoint_multiplier: 2.0,
+        })
+    }
+
+    fn charged_melee(stage_section: StageSection) -> CharacterState {
+        CharacterState::ChargedMelee(charged_melee::Data {
+            static_data: charged_melee::StaticData {
+                energy_cost: 1,
+                energy_drain: 300,
+                initial_damage: 10,
+                max_damage: 170,
+                initial_knockback: Knockback::from_horizontal(10.0),
+                max_knockback: Knockback::from_horizontal(60.0),
+                range: 3.5,
+                max_angle: 30.0,
+                charge_duration: Duration::from_millis(1200),
+                swing_duration: Duration::from_millis(400),
+                recover_duration: Duration::from_millis(100),
+                damage_kind: DamageKind::Physical,
+            },
+            stage_section,
+            timer: Duration::default(),
+            exhausted: false,
+            charge_amount: 0.5,
+        })
+    }
+
+    #[test]
+    fn cancel_while_preparing() {
+        let mut state = charged_ranged(Duration::from_millis(100), Duration::default());
+        assert_eq!(state.try_cancel(), Some(0));
+        assert_eq!(state, CharacterState::Wielding);
+    }
+
+    #[test]
+    fn cancel_refunds_charge() {
+        let mut state = charged_ranged(Duration::default(), Duration::from_millis(500));
+        assert_eq!(state.try_cancel(), Some(150));
+
+        let mut state = charged_melee(StageSection::Charge);
+        assert_eq!(state.try_cancel(), Some(180));
+    }
+
+    #[test]
+    fn cannot_cancel_swing() {
+        let mut state = charged_melee(StageSection::Swing);
+        assert!(!state.is_interruptible());
+        assert_eq!(state.try_cancel(), None);
+        assert_eq!(state, charged_melee(StageSection::Swing));
+
+        let mut idle = CharacterState::Idle;
+        assert_eq!(idle.try_cancel(), None);
+    }
+
+    #[test]
+    fn self_buff_lasts_for_the_state() {
+        let block = CharacterState::BasicBlock(basic_block::Data {
+            self_buff: Some((BuffKind::Protection, 0.25, Duration::from_secs(10))),
+            block_strength: 0.9,
+            reflect_window: None,
+            timer: Duration::default(),
+        });
+        let by = Uid(1);
+
+        // Activating the block applies its buff
+        let mut changes = CharacterState::Wielding.self_buff_changes(&block, by);
+        assert_eq!(changes.len(), 1);
+        let mut buffs = crate::comp::Buffs::default();
+        match changes.pop() {
+            Some(BuffChange::Add(buff)) => {
+                assert_eq!(buff.kind, BuffKind::Protection);
+                assert!(buff.cat_ids.contains(&BuffCategory::FromActiveAbility));
+                buffs.insert(buff);
+            },
+            _ => panic!("Expected the buff to be added"),
+        }
+        assert!((buffs.damage_reduction() - 0.25).abs() < f32::EPSILON);
+
+        // Staying in the state changes nothing
+        assert!(block.self_buff_changes(&block, by).is_empty());
+
+        // Leaving the state removes it
+        match block
+            .self_buff_changes(&CharacterState::Wielding, by)
+            .as_slice()
+        {
+            [BuffChange::RemoveByCategory { all_required, .. }] => {
+                assert_eq!(all_required, &vec![BuffCategory::FromActiveAbility])
+            },
+            _ => panic!("Expected the buff to be removed"),
+        }
+    }
+}
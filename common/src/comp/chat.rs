@@ -61,6 +61,7 @@ pub enum KillSource {
     NonPlayer(String, KillType),
     Environment(String),
     FallDamage,
+    Trap,
     Suicide,
     Other,
 }
@@ -17,6 +17,8 @@ pub enum InventoryManip {
     Pickup(Uid),
     Collect(Vec3<i32>),
     Use(Slot),
+    /// Use the item in an inventory slot, but only if it's a consumable
+    Consume(usize),
     Swap(Slot, Slot),
     Drop(Slot),
     CraftRecipe(String),
@@ -174,6 +176,7 @@ pub struct ControllerInputs {
     pub ability3: Input,
     pub jump: Input,
     pub roll: Input,
+    pub sprint: Input,
     pub glide: Input,
     pub wall_leap: Input,
     pub charge: Input,
@@ -182,6 +185,11 @@ pub struct ControllerInputs {
     pub swimdown: Input,
     pub move_dir: Vec2<f32>,
     pub look_dir: Dir,
+    /// The block being looked at, if any
+    pub select_pos: Option<Vec3<i32>>,
+    /// The entity being looked at, if any
+    #[serde(default)]
+    pub select_entity: Option<Uid>,
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
@@ -200,6 +208,7 @@ impl ControllerInputs {
         self.ability3.tick(dt);
         self.jump.tick(dt);
         self.roll.tick(dt);
+        self.sprint.tick(dt);
         self.glide.tick(dt);
         self.wall_leap.tick(dt);
         self.charge.tick(dt);
@@ -213,6 +222,7 @@ impl ControllerInputs {
         self.ability3.tick_freshness();
         self.jump.tick_freshness();
         self.roll.tick_freshness();
+        self.sprint.tick_freshness();
         self.glide.tick_freshness();
         self.wall_leap.tick_freshness();
         self.charge.tick_freshness();
@@ -227,6 +237,7 @@ impl ControllerInputs {
         self.ability3.update_with_new(new.ability3);
         self.jump.update_with_new(new.jump);
         self.roll.update_with_new(new.roll);
+        self.sprint.update_with_new(new.sprint);
         self.glide.update_with_new(new.glide);
         self.wall_leap.update_with_new(new.wall_leap);
         self.charge.update_with_new(new.charge);
@@ -235,6 +246,8 @@ impl ControllerInputs {
         self.swimdown.update_with_new(new.swimdown);
         self.move_dir = new.move_dir;
         self.look_dir = new.look_dir;
+        self.select_pos = new.select_pos;
+        self.select_entity = new.select_entity;
     }
 
     pub fn holding_ability_key(&self) -> bool {
@@ -1,11 +1,21 @@
-use crate::comp::Loadout;
+use crate::{
+    comp::{Loadout, Stats},
+    sync::Uid,
+};
 use serde::{Deserialize, Serialize};
+use specs::Component;
+use specs_idvs::IdvStorage;
+use std::time::Duration;
 
+/// Fraction of damage stopped by a regular block
 pub const BLOCK_EFFICIENCY: f32 = 0.9;
+/// Extra fraction of base damage dealt per level above the first
+pub const DAMAGE_SCALING_PER_LEVEL: f32 = 0.05;
 
 pub struct Damage {
     pub healthchange: f32,
     pub source: DamageSource,
+    pub kind: DamageKind,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -19,8 +29,25 @@ pub enum DamageSource {
     Energy,
 }
 
+/// The element of a hit, which armor may resist separately from its base
+/// protection
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DamageKind {
+    Physical,
+    Fire,
+    Frost,
+    Poison,
+    Magic,
+}
+
+impl Default for DamageKind {
+    fn default() -> Self { DamageKind::Physical }
+}
+
 impl Damage {
-    pub fn modify_damage(&mut self, block: bool, loadout: &Loadout) {
+    /// Applies the target's defences to the damage, where `block` is the
+    /// strength of the target's block if the hit was blocked.
+    pub fn modify_damage(&mut self, block: Option<f32>, loadout: &Loadout) {
         match self.source {
             DamageSource::Melee => {
                 // Critical hit
@@ -29,8 +56,8 @@ impl Damage {
                     critdamage = self.healthchange * 0.3;
                 }
                 // Block
-                if block {
-                    self.healthchange *= 1.0 - BLOCK_EFFICIENCY
+                if let Some(block_strength) = block {
+                    self.healthchange *= 1.0 - block_strength
                 }
                 // Armor
                 let damage_reduction = loadout.get_damage_reduction();
@@ -47,8 +74,8 @@ impl Damage {
                     self.healthchange *= 1.2;
                 }
                 // Block
-                if block {
-                    self.healthchange *= 1.0 - BLOCK_EFFICIENCY
+                if let Some(block_strength) = block {
+                    self.healthchange *= 1.0 - block_strength
                 }
                 // Armor
                 let damage_reduction = loadout.get_damage_reduction();
@@ -56,8 +83,8 @@ impl Damage {
             },
             DamageSource::Explosion => {
                 // Block
-                if block {
-                    self.healthchange *= 1.0 - BLOCK_EFFICIENCY
+                if let Some(block_strength) = block {
+                    self.healthchange *= 1.0 - block_strength
                 }
                 // Armor
                 let damage_reduction = loadout.get_damage_reduction();
@@ -75,5 +102,347 @@ impl Damage {
             },
             _ => {},
         }
+        // Elemental resistance
+        if self.healthchange < 0.0 {
+            self.healthchange *= 1.0 - loadout.get_resistance(self.kind);
+        }
+    }
+}
+
+/// What happens when a damage over time effect hits a target that already
+/// suffers from one of the same kind, applied by the same attacker
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DotStacking {
+    /// The existing effect starts over, without dealing more damage per tick
+    Refresh,
+    /// Another effect is added, up to `max_stacks` at once. Past that, the
+    /// effect closest to running out starts over instead.
+    Stack { max_stacks: u32 },
+}
+
+/// Damage dealt to the target of a hit in regular ticks after the hit itself,
+/// such as bleeding or burning
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DamageOverTime {
+    pub tick_damage: u32,
+    pub tick_interval: Duration,
+    pub duration: Duration,
+    pub kind: DamageKind,
+    pub stacking: DotStacking,
+}
+
+impl DamageOverTime {
+    fn tick_interval(&self) -> Duration { self.tick_interval.max(Duration::from_millis(1)) }
+
+    /// How many times the effect deals damage before running out
+    pub fn ticks(&self) -> u32 {
+        (self.duration.as_secs_f64() / self.tick_interval().as_secs_f64()) as u32
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct ActiveDot {
+    dot: DamageOverTime,
+    by: Uid,
+    ticks_left: u32,
+    /// Time since the effect last dealt damage
+    elapsed: Duration,
+}
+
+/// The damage over time effects an entity currently suffers from
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DamageOverTimeEffects(Vec<ActiveDot>);
+
+impl DamageOverTimeEffects {
+    /// Applies `dot` from an attack by `by`, following its stacking rule
+    pub fn apply(&mut self, dot: DamageOverTime, by: Uid) {
+        let mut same = self
+            .0
+            .iter_mut()
+            .filter(|active| active.by == by && active.dot.kind == dot.kind)
+            .collect::<Vec<_>>();
+        let refreshed = match dot.stacking {
+            DotStacking::Refresh => same.into_iter().next(),
+            DotStacking::Stack { max_stacks } if same.len() as u32 >= max_stacks.max(1) => {
+                same.sort_by_key(|active| active.ticks_left);
+                same.into_iter().next()
+            },
+            DotStacking::Stack { .. } => None,
+        };
+        match refreshed {
+            Some(active) => {
+                active.dot = dot;
+                active.ticks_left = dot.ticks();
+            },
+            None => self.0.push(ActiveDot {
+                dot,
+                by,
+                ticks_left: dot.ticks(),
+                elapsed: Duration::default(),
+            }),
+        }
+    }
+
+    /// Advances the effects by `dt`, returning the damage dealt in that time
+    /// along with who caused it. Effects that ran out are removed.
+    pub fn tick(&mut self, dt: Duration) -> Vec<(Uid, u32, DamageKind)> {
+        let mut hits = Vec::new();
+        for active in self.0.iter_mut() {
+            active.elapsed += dt;
+            while active.ticks_left > 0 && active.elapsed >= active.dot.tick_interval() {
+                active.elapsed -= active.dot.tick_interval();
+                active.ticks_left -= 1;
+                hits.push((active.by, active.dot.tick_damage, active.dot.kind));
+            }
+        }
+        self.0.retain(|active| active.ticks_left > 0);
+        hits
+    }
+
+    pub fn is_empty(&self) -> bool { self.0.is_empty() }
+}
+
+impl Component for DamageOverTimeEffects {
+    type Storage = IdvStorage<Self>;
+}
+
+/// How long, in seconds, the last entity to hurt another still gets credit
+/// for killing it
+pub const KILL_CREDIT_WINDOW: f64 = 10.0;
+
+/// The last entity to hurt this one and when, so that deaths with nobody
+/// directly to blame (e.g. falling after being knocked off a ledge) can still
+/// be credited to whoever caused them
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LastHitBy {
+    pub by: Uid,
+    pub time: f64,
+}
+
+impl LastHitBy {
+    /// Who gets credit for a kill at `time`, or `None` if the hit was too
+    /// long ago to count
+    pub fn credit(&self, time: f64) -> Option<Uid> {
+        Some(self.by).filter(|_| time - self.time <= KILL_CREDIT_WINDOW)
+    }
+}
+
+impl Component for LastHitBy {
+    type Storage = IdvStorage<Self>;
+}
+
+/// Scales the base damage of an attack by the attacker's stats
+pub fn scaled_damage(base: u32, stats: &Stats) -> u32 {
+    let levels = stats.level.level().saturating_sub(1) as f32;
+    (base as f32 * (1.0 + levels * DAMAGE_SCALING_PER_LEVEL)).round() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        comp::{
+            humanoid, item::ItemDef, AbilityMeta, Body, CharacterAbility, CharacterState,
+            Controller, Energy, Item, Knockback, Ori, PhysicsState, Pos, Vel,
+        },
+        event::{EventBus, ServerEvent},
+        state::State,
+        states::utils::AbilityKey,
+        sync::WorldSyncExt,
+        util::Dir,
+    };
+    use specs::{Builder, WorldExt};
+    use std::sync::Arc;
+    use vek::*;
+
+    fn armor(resistances: &str) -> Option<Item> {
+        let def = ron::de::from_str::<ItemDef>(&format!(
+            "(name: \"Test\", description: \"\", kind: Armor((kind: Chest(\"Test\"), stats: \
+             (protection: Normal(0.0), resistances: {}))), quality: Common)",
+            resistances
+        ))
+        .unwrap();
+        Some(Item::new(Arc::new(def)))
+    }
+
+    /// The damage dealt by a swing of 100 damage and kind `damage_kind` to a
+    /// target wearing `chest`
+    fn swing_damage(damage_kind: DamageKind, chest: Option<Item>) -> i32 {
+        let swing = CharacterAbility::BasicMelee {
+            energy_cost: 0,
+            buildup_duration: Duration::default(),
+            recover_duration: Duration::from_secs(1),
+            base_healthchange: -100,
+            knockback: Knockback::default(),
+            range: 3.5,
+            max_angle: 45.0,
+            dot: None,
+            damage_kind,
+            meta: AbilityMeta::default(),
+        };
+
+        let mut state = State::default();
+        let body = Body::Humanoid(humanoid::Body::random());
+        let spawn = |state: &mut State, pos: Vec3<f32>, loadout: Loadout, character| {
+            state
+                .ecs_mut()
+                .create_entity_synced()
+                .with(Pos(pos))
+                .with(Vel::default())
+                .with(Ori(Dir::new(Vec3::unit_y())))
+                .with(Energy::new(1000))
+                .with(Controller::default())
+                .with(Stats::new("Test".to_string(), body))
+                .with(body)
+                .with(PhysicsState::default())
+                .with(loadout)
+                .with(character)
+                .build()
+        };
+        spawn(
+            &mut state,
+            Vec3::zero(),
+            Loadout::default(),
+            CharacterState::from((&swing, AbilityKey::Mouse1)),
+        );
+        let target = spawn(
+            &mut state,
+            Vec3::new(0.0, 2.0, 0.0),
+            Loadout {
+                chest,
+                ..Loadout::default()
+            },
+            CharacterState::Idle,
+        );
+        let target_uid = *state.ecs().read_storage::<Uid>().get(target).unwrap();
+
+        for _ in 0..3 {
+            state.tick(Duration::from_millis(50), |_| {}, false);
+        }
+        state
+            .ecs()
+            .read_resource::<EventBus<ServerEvent>>()
+            .recv_all()
+            .filter_map(|event| match event {
+                ServerEvent::Damage { uid, change } if uid == target_uid => Some(-change.amount),
+                _ => None,
+            })
+            .sum()
+    }
+
+    #[test]
+    fn fire_resistance_reduces_fire_damage() {
+        // Melee hits may crit for 30% more
+        let resisted = 50..=65;
+        let full = 100..=130;
+
+        assert!(resisted.contains(&swing_damage(DamageKind::Fire, armor("(fire: 0.5)"))));
+        // Other kinds of damage aren't resisted
+        assert!(full.contains(&swing_damage(DamageKind::Physical, armor("(fire: 0.5)"))));
+        assert!(full.contains(&swing_damage(DamageKind::Frost, armor("(fire: 0.5)"))));
+        assert!(full.contains(&swing_damage(DamageKind::Fire, armor("()"))));
+    }
+
+    #[test]
+    fn latest_hit_gets_kill_credit() {
+        let mut last_hit = LastHitBy {
+            by: Uid(1),
+            time: 100.0,
+        };
+        assert_eq!(last_hit.credit(105.0), Some(Uid(1)));
+
+        // Someone else landed a hit since
+        last_hit = LastHitBy {
+            by: Uid(2),
+            time: 104.0,
+        };
+        assert_eq!(last_hit.credit(105.0), Some(Uid(2)));
+        assert_eq!(last_hit.credit(104.0 + KILL_CREDIT_WINDOW), Some(Uid(2)));
+    }
+
+    #[test]
+    fn stale_hit_gets_no_kill_credit() {
+        let last_hit = LastHitBy {
+            by: Uid(1),
+            time: 100.0,
+        };
+        // Fell to their death long after the fight
+        assert_eq!(last_hit.credit(100.0 + KILL_CREDIT_WINDOW + 1.0), None);
+    }
+
+    #[test]
+    fn damage_scales_with_level() {
+        let mut stats = Stats::empty();
+        assert_eq!(scaled_damage(100, &stats), 100);
+
+        stats.level.set_level(11);
+        assert_eq!(scaled_damage(100, &stats), 150);
+        assert_eq!(scaled_damage(0, &stats), 0);
+    }
+
+    #[test]
+    fn higher_level_deals_more_damage() {
+        let mut low = Stats::empty();
+        low.level.set_level(3);
+        let mut high = Stats::empty();
+        high.level.set_level(20);
+        assert!(scaled_damage(40, &high) > scaled_damage(40, &low));
+    }
+
+    fn bleed(stacking: DotStacking) -> DamageOverTime {
+        DamageOverTime {
+            tick_damage: 5,
+            tick_interval: Duration::from_millis(500),
+            duration: Duration::from_secs(3),
+            kind: DamageKind::Physical,
+            stacking,
+        }
+    }
+
+    fn total_damage(effects: &mut DamageOverTimeEffects, secs: u32) -> u32 {
+        (0..secs * 10)
+            .flat_map(|_| effects.tick(Duration::from_millis(100)))
+            .map(|(_, damage, _)| damage)
+            .sum()
+    }
+
+    #[test]
+    fn dot_deals_damage_over_its_ticks() {
+        let mut effects = DamageOverTimeEffects::default();
+        effects.apply(bleed(DotStacking::Refresh), Uid(1));
+        assert_eq!(bleed(DotStacking::Refresh).ticks(), 6);
+        // Nothing is dealt before the first tick
+        assert!(effects.tick(Duration::from_millis(400)).is_empty());
+        assert_eq!(effects.tick(Duration::from_millis(100)), vec![(
+            Uid(1),
+            5,
+            DamageKind::Physical
+        )]);
+        assert_eq!(total_damage(&mut effects, 10), 25);
+        assert!(effects.is_empty());
+    }
+
+    #[test]
+    fn refreshing_dot_doesnt_stack() {
+        let mut effects = DamageOverTimeEffects::default();
+        effects.apply(bleed(DotStacking::Refresh), Uid(1));
+        effects.tick(Duration::from_secs(2));
+        effects.apply(bleed(DotStacking::Refresh), Uid(1));
+        assert_eq!(total_damage(&mut effects, 10), 30);
+
+        // The same effect from someone else is separate
+        effects.apply(bleed(DotStacking::Refresh), Uid(1));
+        effects.apply(bleed(DotStacking::Refresh), Uid(2));
+        assert_eq!(total_damage(&mut effects, 10), 60);
+    }
+
+    #[test]
+    fn stacking_dot_is_capped() {
+        let stacking = DotStacking::Stack { max_stacks: 2 };
+        let mut effects = DamageOverTimeEffects::default();
+        for _ in 0..3 {
+            effects.apply(bleed(stacking), Uid(1));
+        }
+        assert_eq!(total_damage(&mut effects, 10), 60);
     }
 }
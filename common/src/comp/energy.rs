@@ -6,21 +6,50 @@ use specs_idvs::IdvStorage;
 pub struct Energy {
     current: u32,
     maximum: u32,
-    pub regen_rate: f32,
     pub last_change: Option<(i32, f64, EnergySource)>,
+    /// Seconds since energy was last spent on an ability
+    since_ability: f32,
+    /// Whether energy was drained (e.g. by climbing) since the last regen
+    drained: bool,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum EnergySource {
     Ability,
     Climb,
+    Sprint,
     LevelUp,
     HitEnemy,
     Regen,
     Revive,
+    Refund,
     Unknown,
 }
 
+impl EnergySource {
+    /// Whether this source continuously drains energy, preventing any
+    /// regeneration while it does.
+    pub fn is_drain(&self) -> bool { matches!(self, EnergySource::Climb | EnergySource::Sprint) }
+}
+
+/// Tuning for passive energy regeneration
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EnergyRegen {
+    /// How long regeneration pauses after energy is spent on an ability
+    pub delay: f32,
+    /// Energy regained per second once regeneration resumes
+    pub rate: f32,
+}
+
+impl Default for EnergyRegen {
+    fn default() -> Self {
+        Self {
+            delay: 1.0,
+            rate: 100.0,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum StatChangeError {
     Underflow,
@@ -32,8 +61,9 @@ impl Energy {
         Energy {
             current: amount,
             maximum: amount,
-            regen_rate: 0.0,
             last_change: None,
+            since_ability: f32::MAX,
+            drained: false,
         }
     }
 
@@ -50,6 +80,13 @@ impl Energy {
     pub fn change_by(&mut self, amount: i32, cause: EnergySource) {
         self.current = ((self.current as i32 + amount).max(0) as u32).min(self.maximum);
         self.last_change = Some((amount, 0.0, cause));
+        if amount < 0 {
+            match cause {
+                EnergySource::Ability => self.since_ability = 0.0,
+                cause if cause.is_drain() => self.drained = true,
+                _ => {},
+            }
+        }
     }
 
     pub fn try_change_by(
@@ -71,8 +108,100 @@ impl Energy {
         self.maximum = amount;
         self.current = self.current.min(self.maximum);
     }
+
+    /// Whether regeneration is still paused after energy was spent on an
+    /// ability.
+    pub fn regen_paused(&self, regen: &EnergyRegen) -> bool { self.since_ability < regen.delay }
+
+    /// Counts down the pause after spending energy on an ability, for when
+    /// energy isn't regenerating anyway.
+    pub fn advance_regen_pause(&mut self, dt: f32) { self.since_ability += dt; }
+
+    /// Regenerates energy over `dt` seconds. Nothing is regained until
+    /// `regen.delay` has passed since energy was last spent on an ability, or
+    /// while energy is being drained.
+    pub fn regen(&mut self, dt: f32, regen: &EnergyRegen) {
+        let paused_for = (regen.delay - self.since_ability).max(0.0);
+        self.since_ability += dt;
+        if std::mem::replace(&mut self.drained, false) || self.current >= self.maximum {
+            return;
+        }
+
+        // Only regenerate for the part of this tick after the pause ended
+        let amount = ((dt - paused_for).max(0.0) * regen.rate) as i32;
+        if amount > 0 {
+            self.change_by(amount, EnergySource::Regen);
+        }
+    }
 }
 
 impl Component for Energy {
     type Storage = FlaggedStorage<Self, IdvStorage<Self>>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REGEN: EnergyRegen = EnergyRegen {
+        delay: 0.5,
+        rate: 100.0,
+    };
+
+    #[test]
+    fn regen_paused_after_ability() {
+        let mut energy = Energy::new(1000);
+        energy.change_by(-500, EnergySource::Ability);
+
+        for _ in 0..5 {
+            energy.regen(0.1, &REGEN);
+            assert_eq!(energy.current(), 500);
+        }
+        for i in 1..=5 {
+            energy.regen(0.1, &REGEN);
+            assert_eq!(energy.current(), 500 + i * 10);
+        }
+    }
+
+    #[test]
+    fn pause_counts_down_without_regen() {
+        let mut energy = Energy::new(1000);
+        energy.change_by(-500, EnergySource::Ability);
+        while energy.regen_paused(&REGEN) {
+            energy.advance_regen_pause(0.1);
+        }
+        energy.regen(0.1, &REGEN);
+        assert_eq!(energy.current(), 510);
+    }
+
+    #[test]
+    fn regen_ignores_other_spending() {
+        let mut energy = Energy::new(1000);
+        energy.change_by(-500, EnergySource::Unknown);
+        energy.regen(0.1, &REGEN);
+        assert_eq!(energy.current(), 510);
+    }
+
+    #[test]
+    fn drain_suppresses_regen() {
+        let mut energy = Energy::new(1000);
+        energy.change_by(-500, EnergySource::Unknown);
+        for _ in 0..3 {
+            energy.change_by(-5, EnergySource::Climb);
+            energy.regen(0.1, &REGEN);
+        }
+        assert_eq!(energy.current(), 485);
+
+        // Regen resumes once the drain stops
+        energy.regen(0.1, &REGEN);
+        assert_eq!(energy.current(), 495);
+    }
+
+    #[test]
+    fn regen_stops_at_maximum() {
+        let mut energy = Energy::new(100);
+        energy.change_by(-5, EnergySource::Unknown);
+        energy.regen(1.0, &REGEN);
+        assert_eq!(energy.current(), 100);
+    }
+}
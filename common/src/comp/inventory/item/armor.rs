@@ -1,3 +1,4 @@
+use crate::comp::DamageKind;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -27,6 +28,34 @@ impl Armor {
 #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Stats {
     protection: Protection,
+    #[serde(default)]
+    resistances: Resistances,
+    /// The fraction of knockback shrugged off
+    #[serde(default)]
+    knockback_resistance: f32,
+}
+
+/// The fraction of each kind of elemental damage a piece of armor shrugs off
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Resistances {
+    fire: f32,
+    frost: f32,
+    poison: f32,
+    magic: f32,
+}
+
+impl Resistances {
+    pub fn get(&self, kind: DamageKind) -> f32 {
+        match kind {
+            // Covered by protection
+            DamageKind::Physical => 0.0,
+            DamageKind::Fire => self.fire,
+            DamageKind::Frost => self.frost,
+            DamageKind::Poison => self.poison,
+            DamageKind::Magic => self.magic,
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
@@ -39,8 +68,33 @@ pub enum Protection {
 pub struct Armor {
     pub kind: ArmorKind,
     pub stats: Stats,
+    /// Pieces sharing a set id grant bonuses when worn together
+    #[serde(default)]
+    pub set_id: Option<String>,
 }
 
 impl Armor {
     pub fn get_protection(&self) -> Protection { self.stats.protection }
+
+    pub fn get_resistances(&self) -> Resistances { self.stats.resistances }
+
+    pub fn get_knockback_resistance(&self) -> f32 { self.stats.knockback_resistance }
 }
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SetBonus {
+    /// Flat protection on top of that of the armor itself
+    Protection(f32),
+    /// Extra maximum energy
+    Energy(u32),
+    /// Fraction of extra movement speed
+    Speed(f32),
+}
+
+/// Bonuses granted for wearing at least the given number of pieces from the
+/// same set
+pub const SET_BONUSES: [(usize, SetBonus); 3] = [
+    (3, SetBonus::Protection(10.0)),
+    (5, SetBonus::Energy(200)),
+    (5, SetBonus::Speed(0.05)),
+];
@@ -6,6 +6,7 @@ pub use tool::{Hands, Tool, ToolCategory, ToolKind};
 
 use crate::{
     assets::{self, Asset, Error},
+    comp::CharacterAbility,
     effect::Effect,
     lottery::Lottery,
     terrain::{Block, SpriteKind},
@@ -61,6 +62,16 @@ impl Lantern {
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Glider {
     pub kind: String,
+    lift_thousandths: u32,
+    drag_thousandths: u32,
+}
+
+impl Glider {
+    /// The fraction of gravity the glider cancels out while descending
+    pub fn lift(&self) -> f32 { self.lift_thousandths as f32 / 1000_f32 }
+
+    /// How quickly the glider loses horizontal speed, per second
+    pub fn drag(&self) -> f32 { self.drag_thousandths as f32 / 1000_f32 }
 }
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Copy)]
 pub enum Quality {
@@ -96,6 +107,16 @@ pub enum ItemKind {
     },
 }
 
+impl ItemKind {
+    /// How many hands the item takes to hold, if it is held at all
+    pub fn hands(&self) -> Option<Hands> {
+        match self {
+            ItemKind::Tool(tool) => Some(tool.hands()),
+            _ => None,
+        }
+    }
+}
+
 pub type ItemId = AtomicCell<Option<NonZeroU64>>;
 
 /* /// The only way to access an item id outside this module is to mutably, atomically update it using
@@ -292,6 +313,16 @@ impl Item {
 
     pub fn kind(&self) -> &ItemKind { &self.item_def.kind }
 
+    /// The abilities the item grants when wielded, or none if it isn't a tool.
+    /// Unlike `ItemConfig::from`, this accepts any item and doesn't add block
+    /// and dodge abilities.
+    pub fn tool_abilities(&self) -> Vec<CharacterAbility> {
+        match self.kind() {
+            ItemKind::Tool(tool) => tool.get_abilities(),
+            _ => Vec::new(),
+        }
+    }
+
     pub fn amount(&self) -> u32 { u32::from(self.amount) }
 
     pub fn quality(&self) -> Quality { self.item_def.quality }
@@ -381,3 +412,25 @@ pub struct ItemDrop(pub Item);
 impl Component for ItemDrop {
     type Storage = FlaggedStorage<Self, IdvStorage<Self>>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tools_grant_their_abilities() {
+        let sword = Item::new_from_asset_expect("common.items.weapons.sword.zweihander_sword_0");
+        let abilities = sword.tool_abilities();
+        assert!(!abilities.is_empty());
+        match sword.kind() {
+            ItemKind::Tool(tool) => assert_eq!(abilities, tool.get_abilities()),
+            _ => panic!("sword isn't a tool"),
+        }
+    }
+
+    #[test]
+    fn armor_grants_no_abilities() {
+        let chest = Item::new_from_asset_expect("common.items.armor.starter.rugged_chest");
+        assert!(chest.tool_abilities().is_empty());
+    }
+}
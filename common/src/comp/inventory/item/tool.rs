@@ -2,7 +2,11 @@
 // version in voxygen\src\meta.rs in order to reset save files to being empty
 
 use crate::{
-    comp::{body::object, projectile, Body, CharacterAbility, Gravity, LightEmitter, Projectile},
+    comp::{
+        body::object, damage::BLOCK_EFFICIENCY, projectile, AbilityMeta, ActivationEffect, Body,
+        BuffKind, CharacterAbility, DamageKind, DamageOverTime, DotStacking, Gravity, Knockback,
+        KnockbackKind, LightEmitter, LightFlicker, Projectile,
+    },
     states::combo_melee,
     Explosion,
 };
@@ -45,11 +49,32 @@ impl ToolKind {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Hands {
     OneHand,
     TwoHand,
 }
 
+impl Hands {
+    /// Whether items needing `self` and `other` hands can be equipped in the
+    /// main and off hand at the same time. A two-handed weapon can share the
+    /// loadout with another one to swap to, but not with a one-handed item
+    /// that would be held alongside it, such as a shield.
+    pub fn compatible_with(self, other: Hands) -> bool {
+        match (self, other) {
+            // Held together, e.g. a dagger and a shield
+            (Hands::OneHand, Hands::OneHand) => true,
+            // Only one is held at a time, the other is swapped to
+            (Hands::TwoHand, Hands::TwoHand) => true,
+            // The offhand item would be held alongside the two-handed weapon
+            (Hands::TwoHand, Hands::OneHand) => false,
+            // Swapping weapons would leave the two-handed one in the main hand
+            // next to a one-handed item
+            (Hands::OneHand, Hands::TwoHand) => false,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ToolCategory {
     Sword,
@@ -89,8 +114,13 @@ impl From<&ToolKind> for ToolCategory {
 pub struct Stats {
     equip_time_millis: u32,
     power: f32,
+    /// Multiplier on the range of the tool's melee abilities
+    #[serde(default = "default_reach")]
+    reach: f32,
 }
 
+fn default_reach() -> f32 { 1.0 }
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Tool {
     pub kind: ToolKind,
@@ -105,18 +135,30 @@ impl Tool {
             stats: Stats {
                 equip_time_millis: 0,
                 power: 1.00,
+                reach: 1.0,
             },
         }
     }
 
+    pub fn hands(&self) -> Hands { self.kind.hands() }
+
     // Keep power between 0.5 and 2.00
     pub fn base_power(&self) -> f32 { self.stats.power }
 
+    pub fn reach(&self) -> f32 { self.stats.reach }
+
     pub fn equip_time(&self) -> Duration {
         Duration::from_millis(self.stats.equip_time_millis as u64)
     }
 
     pub fn get_abilities(&self) -> Vec<CharacterAbility> {
+        self.base_abilities()
+            .into_iter()
+            .map(|ability| ability.with_reach(self.reach()))
+            .collect()
+    }
+
+    fn base_abilities(&self) -> Vec<CharacterAbility> {
         use CharacterAbility::*;
         use ToolKind::*;
 
@@ -129,39 +171,45 @@ impl Tool {
                             base_damage: (100.0 * self.base_power()) as u32,
                             max_damage: (120.0 * self.base_power()) as u32,
                             damage_increase: (10.0 * self.base_power()) as u32,
-                            knockback: 10.0,
+                            knockback: Knockback::from_horizontal(10.0),
                             range: 4.0,
                             angle: 30.0,
                             base_buildup_duration: Duration::from_millis(350),
                             base_swing_duration: Duration::from_millis(100),
                             base_recover_duration: Duration::from_millis(400),
                             forward_movement: 0.5,
+                            branches: Vec::new(),
+                            energy_cost: 0,
                         },
                         combo_melee::Stage {
                             stage: 2,
                             base_damage: (80.0 * self.base_power()) as u32,
                             max_damage: (110.0 * self.base_power()) as u32,
                             damage_increase: (15.0 * self.base_power()) as u32,
-                            knockback: 12.0,
+                            knockback: Knockback::from_horizontal(12.0),
                             range: 3.5,
                             angle: 180.0,
                             base_buildup_duration: Duration::from_millis(400),
                             base_swing_duration: Duration::from_millis(600),
                             base_recover_duration: Duration::from_millis(400),
                             forward_movement: 0.0,
+                            branches: Vec::new(),
+                            energy_cost: 0,
                         },
                         combo_melee::Stage {
                             stage: 3,
                             base_damage: (130.0 * self.base_power()) as u32,
                             max_damage: (170.0 * self.base_power()) as u32,
                             damage_increase: (20.0 * self.base_power()) as u32,
-                            knockback: 14.0,
+                            knockback: Knockback::from_horizontal(14.0),
                             range: 6.0,
                             angle: 10.0,
                             base_buildup_duration: Duration::from_millis(500),
                             base_swing_duration: Duration::from_millis(200),
                             base_recover_duration: Duration::from_millis(300),
                             forward_movement: 1.2,
+                            branches: Vec::new(),
+                            energy_cost: 0,
                         },
                     ],
                     initial_energy_gain: 0,
@@ -170,13 +218,16 @@ impl Tool {
                     speed_increase: 0.05,
                     max_speed_increase: 1.8,
                     is_interruptible: true,
+                    input_buffer: Duration::from_millis(150),
+                    damage_kind: DamageKind::Physical,
+                    meta: AbilityMeta::default(),
                 },
                 DashMelee {
                     energy_cost: 200,
                     base_damage: (120.0 * self.base_power()) as u32,
                     max_damage: (260.0 * self.base_power()) as u32,
-                    base_knockback: 10.0,
-                    max_knockback: 20.0,
+                    base_knockback: Knockback::from_horizontal(10.0),
+                    max_knockback: Knockback::from_horizontal(20.0),
                     range: 5.0,
                     angle: 45.0,
                     energy_drain: 500,
@@ -187,20 +238,32 @@ impl Tool {
                     recover_duration: Duration::from_millis(500),
                     infinite_charge: true,
                     is_interruptible: true,
+                    damage_kind: DamageKind::Physical,
+                    meta: AbilityMeta::default(),
                 },
                 SpinMelee {
                     buildup_duration: Duration::from_millis(750),
                     swing_duration: Duration::from_millis(500),
                     recover_duration: Duration::from_millis(500),
                     base_damage: (140.0 * self.base_power()) as u32,
-                    knockback: 10.0,
+                    // Sweeps push targets away without lifting them
+                    knockback: Knockback {
+                        horizontal: 10.0,
+                        vertical: 0.0,
+                        kind: KnockbackKind::Away,
+                    },
                     range: 3.5,
+                    vertical_range: 2.0,
                     energy_cost: 200,
                     is_infinite: false,
                     is_helicopter: false,
                     is_interruptible: true,
                     forward_speed: 1.0,
                     num_spins: 3,
+                    max_targets: None,
+                    self_buff: Some((BuffKind::Swiftness, 0.3, Duration::from_secs(3))),
+                    damage_kind: DamageKind::Physical,
+                    meta: AbilityMeta::default(),
                 },
             ],
             Axe(_) => vec![
@@ -209,23 +272,31 @@ impl Tool {
                     buildup_duration: Duration::from_millis(700),
                     recover_duration: Duration::from_millis(300),
                     base_healthchange: (-120.0 * self.base_power()) as i32,
-                    knockback: 0.0,
+                    knockback: Knockback::default(),
                     range: 3.5,
                     max_angle: 20.0,
+                    dot: None,
+                    damage_kind: DamageKind::Physical,
+                    meta: AbilityMeta::default(),
                 },
                 SpinMelee {
                     buildup_duration: Duration::from_millis(100),
                     swing_duration: Duration::from_millis(250),
                     recover_duration: Duration::from_millis(100),
                     base_damage: (60.0 * self.base_power()) as u32,
-                    knockback: 0.0,
+                    knockback: Knockback::default(),
                     range: 3.5,
+                    vertical_range: 4.0,
                     energy_cost: 100,
                     is_infinite: true,
                     is_helicopter: true,
                     is_interruptible: false,
                     forward_speed: 0.0,
                     num_spins: 1,
+                    max_targets: None,
+                    self_buff: Some((BuffKind::Swiftness, 0.2, Duration::from_secs(10))),
+                    damage_kind: DamageKind::Physical,
+                    meta: AbilityMeta::default(),
                 },
                 LeapMelee {
                     energy_cost: 450,
@@ -234,11 +305,14 @@ impl Tool {
                     swing_duration: Duration::from_millis(200),
                     recover_duration: Duration::from_millis(200),
                     base_damage: (240.0 * self.base_power()) as u32,
-                    knockback: 12.0,
+                    knockback: Knockback::from_horizontal(12.0),
                     range: 4.5,
                     max_angle: 30.0,
                     forward_leap_strength: 28.0,
                     vertical_leap_strength: 8.0,
+                    impact_radius: 0.0,
+                    damage_kind: DamageKind::Physical,
+                    meta: AbilityMeta::default(),
                 },
             ],
             Hammer(_) => vec![
@@ -247,22 +321,32 @@ impl Tool {
                     buildup_duration: Duration::from_millis(700),
                     recover_duration: Duration::from_millis(300),
                     base_healthchange: (-120.0 * self.base_power()) as i32,
-                    knockback: 0.0,
+                    knockback: Knockback::default(),
                     range: 3.5,
                     max_angle: 20.0,
+                    dot: None,
+                    damage_kind: DamageKind::Physical,
+                    meta: AbilityMeta::default(),
                 },
                 ChargedMelee {
                     energy_cost: 1,
                     energy_drain: 300,
                     initial_damage: (10.0 * self.base_power()) as u32,
                     max_damage: (170.0 * self.base_power()) as u32,
-                    initial_knockback: 10.0,
-                    max_knockback: 60.0,
+                    initial_knockback: Knockback::from_horizontal(10.0),
+                    // A fully charged swing pops targets up into the air
+                    max_knockback: Knockback {
+                        horizontal: 60.0,
+                        vertical: 40.0,
+                        kind: KnockbackKind::Away,
+                    },
                     range: 3.5,
                     max_angle: 30.0,
                     charge_duration: Duration::from_millis(1200),
                     swing_duration: Duration::from_millis(400),
                     recover_duration: Duration::from_millis(100),
+                    damage_kind: DamageKind::Physical,
+                    meta: AbilityMeta::default(),
                 },
                 LeapMelee {
                     energy_cost: 700,
@@ -271,22 +355,36 @@ impl Tool {
                     swing_duration: Duration::from_millis(150),
                     recover_duration: Duration::from_millis(200),
                     base_damage: (240.0 * self.base_power()) as u32,
-                    knockback: 25.0,
+                    knockback: Knockback::from_horizontal(25.0),
                     range: 4.5,
                     max_angle: 360.0,
                     forward_leap_strength: 28.0,
                     vertical_leap_strength: 8.0,
+                    impact_radius: 5.0,
+                    damage_kind: DamageKind::Physical,
+                    meta: AbilityMeta::default(),
+                },
+            ],
+            Farming(_) => vec![
+                BasicMelee {
+                    energy_cost: 1,
+                    buildup_duration: Duration::from_millis(700),
+                    recover_duration: Duration::from_millis(150),
+                    base_healthchange: (-50.0 * self.base_power()) as i32,
+                    knockback: Knockback::default(),
+                    range: 3.5,
+                    max_angle: 20.0,
+                    dot: None,
+                    damage_kind: DamageKind::Physical,
+                    meta: AbilityMeta::default(),
+                },
+                Harvest {
+                    buildup_duration: Duration::from_millis(300),
+                    recover_duration: Duration::from_millis(150),
+                    range: 5.0,
+                    meta: AbilityMeta::default(),
                 },
             ],
-            Farming(_) => vec![BasicMelee {
-                energy_cost: 1,
-                buildup_duration: Duration::from_millis(700),
-                recover_duration: Duration::from_millis(150),
-                base_healthchange: (-50.0 * self.base_power()) as i32,
-                knockback: 0.0,
-                range: 3.5,
-                max_angle: 20.0,
-            }],
             Bow(_) => vec![
                 BasicRanged {
                     energy_cost: 0,
@@ -297,26 +395,39 @@ impl Tool {
                         hit_solid: vec![projectile::Effect::Stick],
                         hit_entity: vec![
                             projectile::Effect::Damage((-40.0 * self.base_power()) as i32),
-                            projectile::Effect::Knockback(10.0),
+                            projectile::Effect::Knockback(Knockback::launch(10.0)),
                             projectile::Effect::RewardEnergy(50),
                             projectile::Effect::Vanish,
                         ],
                         time_left: Duration::from_secs(15),
                         owner: None,
                         ignore_group: true,
+                        homing: None,
+                        pierce: 0,
+                        hit_entities: Vec::new(),
+                        bounces: 0,
+                        bounce_damping: 0.0,
+                        damage_kind: DamageKind::Physical,
+                        weakpoint_multiplier: 2.0,
                     },
                     projectile_body: Body::Object(object::Body::Arrow),
                     projectile_light: None,
                     projectile_gravity: Some(Gravity(0.2)),
                     projectile_speed: 100.0,
+                    homing: None,
+                    pierce: 0,
+                    bounces: 0,
+                    bounce_damping: 0.0,
+                    projectile_lifetime: Duration::from_secs(15),
+                    meta: AbilityMeta::default(),
                 },
                 ChargedRanged {
                     energy_cost: 0,
                     energy_drain: 300,
                     initial_damage: (40.0 * self.base_power()) as u32,
                     max_damage: (200.0 * self.base_power()) as u32,
-                    initial_knockback: 10.0,
-                    max_knockback: 20.0,
+                    initial_knockback: Knockback::launch(10.0),
+                    max_knockback: Knockback::launch(20.0),
                     prepare_duration: Duration::from_millis(100),
                     charge_duration: Duration::from_millis(1500),
                     recover_duration: Duration::from_millis(500),
@@ -325,6 +436,14 @@ impl Tool {
                     projectile_gravity: Some(Gravity(0.2)),
                     initial_projectile_speed: 100.0,
                     max_projectile_speed: 500.0,
+                    pierce: 0,
+                    bounces: 0,
+                    bounce_damping: 0.0,
+                    sweet_spot: None,
+                    projectile_lifetime: Duration::from_secs(15),
+                    damage_kind: DamageKind::Physical,
+                    weakpoint_multiplier: 2.0,
+                    meta: AbilityMeta::default(),
                 },
                 RepeaterRanged {
                     energy_cost: 450,
@@ -337,30 +456,55 @@ impl Tool {
                         hit_solid: vec![projectile::Effect::Stick],
                         hit_entity: vec![
                             projectile::Effect::Damage((-40.0 * self.base_power()) as i32),
-                            projectile::Effect::Knockback(10.0),
+                            projectile::Effect::Knockback(Knockback::launch(10.0)),
                             projectile::Effect::RewardEnergy(50),
                             projectile::Effect::Vanish,
                         ],
                         time_left: Duration::from_secs(15),
                         owner: None,
                         ignore_group: true,
+                        homing: None,
+                        pierce: 0,
+                        hit_entities: Vec::new(),
+                        bounces: 0,
+                        bounce_damping: 0.0,
+                        damage_kind: DamageKind::Physical,
+                        weakpoint_multiplier: 2.0,
                     },
                     projectile_body: Body::Object(object::Body::Arrow),
                     projectile_light: None,
                     projectile_gravity: Some(Gravity(0.2)),
                     projectile_speed: 100.0,
                     reps_remaining: 5,
+                    meta: AbilityMeta::default(),
+                },
+            ],
+            Dagger(_) => vec![
+                BasicMelee {
+                    energy_cost: 0,
+                    buildup_duration: Duration::from_millis(100),
+                    recover_duration: Duration::from_millis(400),
+                    base_healthchange: (-50.0 * self.base_power()) as i32,
+                    knockback: Knockback::default(),
+                    range: 3.5,
+                    max_angle: 20.0,
+                    // Daggers leave their targets bleeding
+                    dot: Some(DamageOverTime {
+                        tick_damage: (5.0 * self.base_power()) as u32,
+                        tick_interval: Duration::from_millis(500),
+                        duration: Duration::from_secs(4),
+                        kind: DamageKind::Physical,
+                        stacking: DotStacking::Stack { max_stacks: 3 },
+                    }),
+                    damage_kind: DamageKind::Physical,
+                    meta: AbilityMeta::default(),
+                },
+                // A dagger in the off hand can be drawn in a flash
+                SwapWeapons {
+                    duration: Duration::from_millis(150),
+                    meta: AbilityMeta::default(),
                 },
             ],
-            Dagger(_) => vec![BasicMelee {
-                energy_cost: 0,
-                buildup_duration: Duration::from_millis(100),
-                recover_duration: Duration::from_millis(400),
-                base_healthchange: (-50.0 * self.base_power()) as i32,
-                knockback: 0.0,
-                range: 3.5,
-                max_angle: 20.0,
-            }],
             Sceptre(_) => vec![
                 BasicBeam {
                     buildup_duration: Duration::from_millis(250),
@@ -375,6 +519,8 @@ impl Tool {
                     energy_regen: 50,
                     energy_cost: 100,
                     energy_drain: 0,
+                    damage_kind: DamageKind::Physical,
+                    meta: AbilityMeta::default(),
                 },
                 BasicRanged {
                     energy_cost: 800,
@@ -409,6 +555,13 @@ impl Tool {
                         time_left: Duration::from_secs(20),
                         owner: None,
                         ignore_group: true,
+                        homing: None,
+                        pierce: 0,
+                        hit_entities: Vec::new(),
+                        bounces: 0,
+                        bounce_damping: 0.0,
+                        damage_kind: DamageKind::Physical,
+                        weakpoint_multiplier: 1.0,
                     },
                     projectile_body: Body::Object(object::Body::BoltNature),
                     projectile_light: Some(LightEmitter {
@@ -417,7 +570,17 @@ impl Tool {
                     }),
                     projectile_gravity: Some(Gravity(0.5)),
                     projectile_speed: 40.0,
-                },
+                    homing: None,
+                    pierce: 0,
+                    bounces: 0,
+                    bounce_damping: 0.0,
+                    projectile_lifetime: Duration::from_secs(20),
+                    meta: AbilityMeta::default(),
+                }
+                // Casting the healing bomb also mends the caster a little
+                .with_on_activate(vec![ActivationEffect::HealSelf(
+                    (30.0 * self.base_power()) as u32,
+                )]),
             ],
             Staff(_) => vec![
                 BasicRanged {
@@ -453,14 +616,31 @@ impl Tool {
                         time_left: Duration::from_secs(20),
                         owner: None,
                         ignore_group: true,
+                        homing: None,
+                        pierce: 0,
+                        hit_entities: Vec::new(),
+                        bounces: 0,
+                        bounce_damping: 0.0,
+                        damage_kind: DamageKind::Physical,
+                        weakpoint_multiplier: 1.0,
                     },
                     projectile_body: Body::Object(object::Body::BoltFire),
                     projectile_light: Some(LightEmitter {
                         col: (1.0, 0.75, 0.11).into(),
+                        periodic_flicker: Some(LightFlicker {
+                            amplitude: 0.3,
+                            frequency: 8.0,
+                        }),
                         ..Default::default()
                     }),
                     projectile_gravity: Some(Gravity(0.3)),
                     projectile_speed: 60.0,
+                    homing: None,
+                    pierce: 0,
+                    bounces: 0,
+                    bounce_damping: 0.0,
+                    projectile_lifetime: Duration::from_secs(20),
+                    meta: AbilityMeta::default(),
                 },
                 BasicBeam {
                     buildup_duration: Duration::from_millis(250),
@@ -475,6 +655,8 @@ impl Tool {
                     energy_regen: 0,
                     energy_cost: 0,
                     energy_drain: 350,
+                    damage_kind: DamageKind::Fire,
+                    meta: AbilityMeta::default(),
                 },
                 Shockwave {
                     energy_cost: 600,
@@ -482,13 +664,16 @@ impl Tool {
                     swing_duration: Duration::from_millis(100),
                     recover_duration: Duration::from_millis(300),
                     damage: (200.0 * self.base_power()) as u32,
-                    knockback: 25.0,
+                    knockback: Knockback::launch(25.0),
                     shockwave_angle: 360.0,
                     shockwave_vertical_angle: 90.0,
                     shockwave_speed: 20.0,
                     shockwave_duration: Duration::from_millis(500),
                     requires_ground: false,
                     move_efficiency: 0.1,
+                    max_targets: None,
+                    stun: None,
+                    meta: AbilityMeta::default(),
                 },
             ],
             Shield(_) => vec![
@@ -497,11 +682,19 @@ impl Tool {
                     buildup_duration: Duration::from_millis(100),
                     recover_duration: Duration::from_millis(400),
                     base_healthchange: (-40.0 * self.base_power()) as i32,
-                    knockback: 0.0,
+                    knockback: Knockback::default(),
                     range: 3.0,
                     max_angle: 120.0,
+                    dot: None,
+                    damage_kind: DamageKind::Physical,
+                    meta: AbilityMeta::default(),
+                },
+                BasicBlock {
+                    self_buff: None,
+                    block_strength: BLOCK_EFFICIENCY,
+                    reflect_window: Some(Duration::from_millis(250)),
+                    meta: AbilityMeta::default(),
                 },
-                BasicBlock,
             ],
             NpcWeapon(kind) => {
                 if kind == "StoneGolemsFist" {
@@ -510,10 +703,13 @@ impl Tool {
                             energy_cost: 0,
                             buildup_duration: Duration::from_millis(500),
                             recover_duration: Duration::from_millis(250),
-                            knockback: 25.0,
+                            knockback: Knockback::from_horizontal(25.0),
                             base_healthchange: -200,
                             range: 5.0,
                             max_angle: 120.0,
+                            dot: None,
+                            damage_kind: DamageKind::Physical,
+                            meta: AbilityMeta::default(),
                         },
                         Shockwave {
                             energy_cost: 0,
@@ -521,13 +717,16 @@ impl Tool {
                             swing_duration: Duration::from_millis(200),
                             recover_duration: Duration::from_millis(800),
                             damage: 500,
-                            knockback: -40.0,
+                            knockback: Knockback::launch(-40.0),
                             shockwave_angle: 90.0,
                             shockwave_vertical_angle: 15.0,
                             shockwave_speed: 20.0,
                             shockwave_duration: Duration::from_millis(2000),
                             requires_ground: true,
                             move_efficiency: 0.05,
+                            max_targets: None,
+                            stun: Some(Duration::from_secs(1)),
+                            meta: AbilityMeta::default(),
                         },
                     ]
                 } else if kind == "BeastClaws" {
@@ -535,10 +734,13 @@ impl Tool {
                         energy_cost: 0,
                         buildup_duration: Duration::from_millis(500),
                         recover_duration: Duration::from_millis(250),
-                        knockback: 25.0,
+                        knockback: Knockback::from_horizontal(25.0),
                         base_healthchange: -200,
                         range: 5.0,
                         max_angle: 120.0,
+                        dot: None,
+                        damage_kind: DamageKind::Physical,
+                        meta: AbilityMeta::default(),
                     }]
                 } else {
                     vec![BasicMelee {
@@ -546,9 +748,12 @@ impl Tool {
                         buildup_duration: Duration::from_millis(100),
                         recover_duration: Duration::from_millis(300),
                         base_healthchange: -10,
-                        knockback: 0.0,
+                        knockback: Knockback::default(),
                         range: 1.0,
                         max_angle: 30.0,
+                        dot: None,
+                        damage_kind: DamageKind::Physical,
+                        meta: AbilityMeta::default(),
                     }]
                 }
             },
@@ -558,10 +763,12 @@ impl Tool {
                         CharacterAbility::Boost {
                             duration: Duration::from_millis(50),
                             only_up: false,
+                            meta: AbilityMeta::default(),
                         },
                         CharacterAbility::Boost {
                             duration: Duration::from_millis(50),
                             only_up: true,
+                            meta: AbilityMeta::default(),
                         },
                         BasicRanged {
                             energy_cost: 0,
@@ -577,6 +784,13 @@ impl Tool {
                                 time_left: Duration::from_secs(10),
                                 owner: None,
                                 ignore_group: false,
+                                homing: None,
+                                pierce: 0,
+                                hit_entities: Vec::new(),
+                                bounces: 0,
+                                bounce_damping: 0.0,
+                                damage_kind: DamageKind::Physical,
+                                weakpoint_multiplier: 1.0,
                             },
                             projectile_body: Body::Object(object::Body::ArrowSnake),
                             projectile_light: Some(LightEmitter {
@@ -585,8 +799,24 @@ impl Tool {
                             }),
                             projectile_gravity: None,
                             projectile_speed: 100.0,
+                            homing: None,
+                            pierce: 0,
+                            bounces: 0,
+                            bounce_damping: 0.0,
+                            projectile_lifetime: Duration::from_secs(10),
+                            meta: AbilityMeta::default(),
                         },
                     ]
+                } else if kind == "Grapple" {
+                    vec![Grapple {
+                        energy_cost: 100,
+                        range: 40.0,
+                        pull_speed: 25.0,
+                        arc_height: 6.0,
+                        buildup_duration: Duration::from_millis(250),
+                        pull_duration: Duration::from_secs(3),
+                        meta: AbilityMeta::default(),
+                    }]
                 } else {
                     vec![]
                 }
@@ -596,9 +826,12 @@ impl Tool {
                 buildup_duration: Duration::from_millis(0),
                 recover_duration: Duration::from_millis(1000),
                 base_healthchange: -20,
-                knockback: 0.0,
+                knockback: Knockback::default(),
                 range: 3.5,
                 max_angle: 15.0,
+                dot: None,
+                damage_kind: DamageKind::Physical,
+                meta: AbilityMeta::default(),
             }],
         }
     }
@@ -610,3 +843,24 @@ impl Tool {
         ToolCategory::from(&self.kind) == ToolCategory::from(&other.kind)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_handed_items_go_together() {
+        assert!(Hands::OneHand.compatible_with(Hands::OneHand));
+    }
+
+    #[test]
+    fn two_handed_weapons_can_be_swapped_between() {
+        assert!(Hands::TwoHand.compatible_with(Hands::TwoHand));
+    }
+
+    #[test]
+    fn mixed_hands_are_incompatible() {
+        assert!(!Hands::TwoHand.compatible_with(Hands::OneHand));
+        assert!(!Hands::OneHand.compatible_with(Hands::TwoHand));
+    }
+}
@@ -36,7 +36,11 @@ impl Inventory {
 
     pub fn slots(&self) -> &[Option<Item>] { &self.slots }
 
-    pub fn len(&self) -> usize { self.slots.len() }
+    /// Same as `capacity`: the number of slots, not of items.
+    pub fn len(&self) -> usize { self.capacity() }
+
+    /// Number of slots the inventory has, whether or not they are occupied.
+    pub fn capacity(&self) -> usize { self.slots.len() }
 
     /// Total number of occupied slots in the inventory.
     pub fn amount(&self) -> u32 { self.amount }
@@ -45,6 +49,15 @@ impl Inventory {
         self.amount = self.slots.iter().filter(|i| i.is_some()).count() as u32;
     }
 
+    /// Like `push`, but returns the item as an error if it didn't fit, so that
+    /// the caller can decide what to do with it (e.g. drop it on the ground).
+    pub fn try_push(&mut self, item: Item) -> Result<(), Item> {
+        match self.push(item) {
+            None => Ok(()),
+            Some(item) => Err(item),
+        }
+    }
+
     /// Adds a new item to the first fitting group of the inventory or starts a
     /// new group. Returns the item again if no space was found.
     pub fn push(&mut self, item: Item) -> Option<Item> {
@@ -161,6 +174,8 @@ impl Inventory {
         }
     }
 
+    /// Whether every slot is occupied. Stackable items may still fit into an
+    /// existing stack of the same kind, see `try_push`.
     pub fn is_full(&self) -> bool { self.slots.iter().all(|slot| slot.is_some()) }
 
     /// O(n) count the number of items in this inventory.
@@ -176,6 +191,12 @@ impl Inventory {
         self.slots.get(cell).and_then(Option::as_ref)
     }
 
+    /// Get the item in a slot, if it's something that can be consumed
+    pub fn get_consumable(&self, cell: usize) -> Option<&Item> {
+        self.get(cell)
+            .filter(|item| matches!(item.kind(), item::ItemKind::Consumable { .. }))
+    }
+
     /// Swap the items inside of two slots
     pub fn swap_slots(&mut self, a: usize, b: usize) {
         if a.max(b) < self.slots.len() {
@@ -163,10 +163,9 @@ fn swap_inventory_loadout(
     loadout: &mut Loadout,
 ) {
     // Check if loadout slot can hold item
-    if inventory
-        .get(inventory_slot)
-        .map_or(true, |item| equip_slot.can_hold(&item.kind()))
-    {
+    if inventory.get(inventory_slot).map_or(true, |item| {
+        equip_slot.can_hold(&item.kind()) && offhand_fits(equip_slot, item, loadout)
+    }) {
         // Take item from loadout
         let from_equip = loadout_remove(equip_slot, loadout);
         // Swap with item in the inventory
@@ -181,6 +180,32 @@ fn swap_inventory_loadout(
         if let Some(item) = from_inv {
             loadout_insert(equip_slot, item, loadout).unwrap_none(); // Can never fail
         }
+        if let EquipSlot::Mainhand = equip_slot {
+            unequip_incompatible_offhand(inventory, loadout);
+        }
+    }
+}
+
+/// Whether `item` can go in `equip_slot` given the item held in the main hand.
+/// Only the offhand is restricted, since a new mainhand item instead pushes out
+/// an offhand item it can't be held with.
+fn offhand_fits(equip_slot: EquipSlot, item: &item::Item, loadout: &Loadout) -> bool {
+    match (equip_slot, item.kind().hands()) {
+        (EquipSlot::Offhand, Some(hands)) => loadout
+            .active_item
+            .as_ref()
+            .and_then(|i| i.item.kind().hands())
+            .map_or(true, |active| active.compatible_with(hands)),
+        _ => true,
+    }
+}
+
+/// Moves the offhand item into the inventory if it can't be held alongside the
+/// mainhand item, e.g. a shield next to a two-handed sword. It stays equipped
+/// if the inventory is full.
+fn unequip_incompatible_offhand(inventory: &mut Inventory, loadout: &mut Loadout) {
+    if !loadout.hands_compatible() {
+        unequip(EquipSlot::Offhand, inventory, loadout);
     }
 }
 
@@ -239,7 +264,8 @@ pub fn swap(
 
 /// Equip an item from a slot in inventory. The currently equipped item will go
 /// into inventory. If the item is going to mainhand, put mainhand in
-/// offhand and place offhand into inventory.
+/// offhand and place offhand into inventory. If the new mainhand item can't be
+/// held alongside the offhand one, the offhand item goes into inventory too.
 ///
 /// ```
 /// use veloren_common::{
@@ -365,6 +391,95 @@ mod tests {
         assert_eq!(inv.slots.len(), 1);
     }
 
+    #[test]
+    fn test_equip_two_handed_clears_offhand() {
+        let sword = LoadoutBuilder::default_item_config_from_str(
+            "common.items.weapons.sword.zweihander_sword_0",
+        );
+        let dagger = LoadoutBuilder::default_item_config_from_str(
+            "common.items.weapons.dagger.starter_dagger",
+        );
+        let shield =
+            LoadoutBuilder::default_item_config_from_str("common.items.weapons.shield.shield_1");
+
+        let mut inv = Inventory {
+            slots: vec![Some(sword.item.clone()), None],
+            amount: 1,
+        };
+
+        let mut loadout = LoadoutBuilder::new()
+            .defaults()
+            .active_item(Some(dagger.clone()))
+            .second_item(Some(shield.clone()))
+            .build();
+
+        equip(0, &mut inv, &mut loadout);
+
+        // The dagger would be held alongside the sword, so it goes into inventory
+        assert_eq!(Some(sword), loadout.active_item);
+        assert_eq!(None, loadout.second_item);
+        assert_eq!(inv.slots[0], Some(shield.item));
+        assert_eq!(inv.slots[1], Some(dagger.item));
+    }
+
+    #[test]
+    fn test_offhand_blocked_by_two_handed() {
+        let sword = LoadoutBuilder::default_item_config_from_str(
+            "common.items.weapons.sword.zweihander_sword_0",
+        );
+        let shield = Item::new_from_asset_expect("common.items.weapons.shield.shield_1");
+
+        let mut inv = Inventory {
+            slots: vec![Some(shield.clone())],
+            amount: 1,
+        };
+
+        let mut loadout = LoadoutBuilder::new()
+            .defaults()
+            .active_item(Some(sword.clone()))
+            .build();
+
+        swap(
+            Slot::Inventory(0),
+            Slot::Equip(EquipSlot::Offhand),
+            Some(&mut inv),
+            Some(&mut loadout),
+        );
+
+        assert_eq!(Some(sword), loadout.active_item);
+        assert_eq!(None, loadout.second_item);
+        assert_eq!(inv.slots[0], Some(shield));
+    }
+
+    #[test]
+    fn test_offhand_two_handed_with_two_handed() {
+        let sword = LoadoutBuilder::default_item_config_from_str(
+            "common.items.weapons.sword.zweihander_sword_0",
+        );
+        let axe = Item::new_from_asset_expect("common.items.weapons.axe.bloodsteel_axe-0");
+
+        let mut inv = Inventory {
+            slots: vec![Some(axe.clone())],
+            amount: 1,
+        };
+
+        let mut loadout = LoadoutBuilder::new()
+            .defaults()
+            .active_item(Some(sword.clone()))
+            .build();
+
+        swap(
+            Slot::Inventory(0),
+            Slot::Equip(EquipSlot::Offhand),
+            Some(&mut inv),
+            Some(&mut loadout),
+        );
+
+        assert_eq!(Some(sword), loadout.active_item);
+        assert_eq!(Some(axe), loadout.second_item.map(|i| i.item));
+        assert_eq!(inv.slots[0], None);
+    }
+
     #[test]
     fn test_equip_item() {
         let boots: Option<comp::Item> = Some(Item::new_from_asset_expect(
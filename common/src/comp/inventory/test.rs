@@ -69,3 +69,39 @@ fn push_all_unique_empty() {
         "Pushing unique items into an empty inventory that didn't contain them didn't work!",
     );
 }
+
+/// Only slots holding a consumable may be consumed from.
+#[test]
+fn get_consumable() {
+    let potion = Item::new_from_asset_expect("common.items.consumable.potion_minor");
+    let inv = Inventory {
+        slots: vec![Some(potion.clone()), None, Some(TEST_ITEMS[0].clone())],
+        amount: 0,
+    };
+    assert_eq!(inv.get_consumable(0), Some(&potion));
+    assert_eq!(inv.get_consumable(1), None);
+    assert_eq!(inv.get_consumable(2), None);
+    assert_eq!(inv.get_consumable(3), None);
+}
+
+/// Filling an inventory up to its capacity should work, after which further
+/// items are handed back.
+#[test]
+fn try_push_overflow() {
+    let mut inv = Inventory {
+        slots: vec![None; 2],
+        amount: 0,
+    };
+    assert_eq!(inv.capacity(), 2);
+    for item in TEST_ITEMS.iter() {
+        assert!(!inv.is_full());
+        inv.try_push(item.clone())
+            .expect("Pushing into an inventory with free slots didn't work!");
+    }
+    assert!(inv.is_full());
+    assert_eq!(inv.amount(), 2);
+
+    let rejected = Item::new_from_asset_expect("common.items.debug.boost");
+    assert_eq!(inv.try_push(rejected.clone()), Err(rejected));
+    assert_eq!(inv.amount(), 2);
+}
@@ -0,0 +1,183 @@
+use crate::util::Dir;
+use serde::{Deserialize, Serialize};
+use specs::Component;
+use specs_idvs::IdvStorage;
+use vek::*;
+
+/// How hard an attack knocks its targets back, split into the push away from
+/// the attacker along the ground and the lift upwards
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Knockback {
+    pub horizontal: f32,
+    pub vertical: f32,
+    #[serde(default)]
+    pub kind: KnockbackKind,
+}
+
+/// Which way a knockback pushes its target, relative to the direction from the
+/// attacker to the target
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum KnockbackKind {
+    /// Away along the ground, with the knockback's own lift
+    Away,
+    /// Thrown up into the air, or dragged in and slammed into the ground when
+    /// the strength is negative. Only the horizontal strength is used.
+    Launch,
+}
+
+impl Default for KnockbackKind {
+    fn default() -> Self { KnockbackKind::Away }
+}
+
+impl Knockback {
+    /// Fraction of the horizontal strength used as lift when only a
+    /// horizontal strength is given
+    pub const DEFAULT_LIFT: f32 = 0.3;
+
+    /// Knockback with the given horizontal strength and a small lift
+    pub fn from_horizontal(horizontal: f32) -> Self {
+        Self {
+            horizontal,
+            vertical: horizontal.abs() * Self::DEFAULT_LIFT,
+            kind: KnockbackKind::Away,
+        }
+    }
+
+    /// Knockback that launches targets with the given strength, see
+    /// [`KnockbackKind::Launch`]
+    pub fn launch(strength: f32) -> Self {
+        Self {
+            horizontal: strength,
+            vertical: 0.0,
+            kind: KnockbackKind::Launch,
+        }
+    }
+
+    pub fn is_zero(&self) -> bool { self.horizontal == 0.0 && self.vertical == 0.0 }
+
+    /// Knockback `t` of the way from `self` to `other`
+    pub fn lerp(self, other: Self, t: f32) -> Self {
+        Self {
+            horizontal: self.horizontal + (other.horizontal - self.horizontal) * t,
+            vertical: self.vertical + (other.vertical - self.vertical) * t,
+            kind: self.kind,
+        }
+    }
+
+    /// Knockback `factor` times as strong as `self`
+    pub fn scaled(self, factor: f32) -> Self {
+        Self {
+            horizontal: self.horizontal * factor,
+            vertical: self.vertical * factor,
+            kind: self.kind,
+        }
+    }
+
+    /// The impulse that knocks a target away in direction `dir`. Knockbacks
+    /// that push away only use the horizontal part of `dir`, so the lift is
+    /// the same no matter where the attacker stands relative to the target.
+    pub fn impulse(&self, dir: Vec3<f32>) -> Vec3<f32> {
+        match self.kind {
+            KnockbackKind::Away => {
+                let horizontal_dir = Vec2::from(dir).try_normalized().unwrap_or_default();
+                Vec3::from(horizontal_dir * self.horizontal) + Vec3::unit_z() * self.vertical
+            },
+            KnockbackKind::Launch => {
+                let dir = Dir::from_unnormalized(dir).unwrap_or_default();
+                let (towards, factor) = if self.horizontal < 0.0 {
+                    (-Vec3::unit_z(), 0.85)
+                } else {
+                    (Vec3::unit_z(), 0.5)
+                };
+                self.horizontal * *Dir::slerp(dir, Dir::new(towards), factor)
+            },
+        }
+    }
+}
+
+/// When an entity was last knocked back
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LastKnockback(pub f64);
+
+impl Component for LastKnockback {
+    type Storage = IdvStorage<Self>;
+}
+
+/// How long entities ignore further knockback after being knocked back, so
+/// they can't be chain-knocked without a chance to recover. Damage still
+/// applies during this window.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct KnockbackImmunity {
+    /// Length of the window in seconds
+    pub duration: f64,
+}
+
+impl Default for KnockbackImmunity {
+    fn default() -> Self { Self { duration: 0.5 } }
+}
+
+impl KnockbackImmunity {
+    /// Whether a knockback at `time` should be applied to an entity last
+    /// knocked back at `last`. Entities that haven't been knocked back yet
+    /// are never immune, so the first hit of a combo always knocks back.
+    pub fn allows(&self, last: Option<&LastKnockback>, time: f64) -> bool {
+        last.map_or(true, |last| time - last.0 >= self.duration)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn impulse_has_configured_vertical_component() {
+        let knockback = Knockback {
+            horizontal: 10.0,
+            vertical: 4.0,
+            kind: KnockbackKind::Away,
+        };
+        let impulse = knockback.impulse(Vec3::new(0.0, 1.0, 0.5));
+        assert_eq!(impulse, Vec3::new(0.0, 10.0, 4.0));
+
+        let grounded = Knockback {
+            horizontal: 10.0,
+            vertical: 0.0,
+            kind: KnockbackKind::Away,
+        };
+        assert_eq!(grounded.impulse(Vec3::new(1.0, 0.0, 0.0)).z, 0.0);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn horizontal_knockback_gets_default_lift() {
+        let knockback = Knockback::from_horizontal(20.0);
+        assert_eq!(knockback.horizontal, 20.0);
+        assert!(knockback.vertical > 0.0 && knockback.vertical < knockback.horizontal);
+        assert!(Knockback::from_horizontal(0.0).is_zero());
+    }
+
+    #[test]
+    fn launch_bends_halfway_upwards() {
+        // Thrown at 45°, with the full strength
+        let impulse = Knockback::launch(30.0).impulse(Vec3::unit_x());
+        let expected = Vec3::new(1.0, 0.0, 1.0).normalized() * 30.0;
+        assert!((impulse - expected).magnitude() < 0.001);
+
+        // Negative strengths pull the target in towards the attacker
+        let impulse = Knockback::launch(-40.0).impulse(Vec3::unit_x());
+        assert!(impulse.x < 0.0 && impulse.z > 0.0);
+        assert!((impulse.magnitude() - 40.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn rapid_second_knockback_ignored() {
+        let immunity = KnockbackImmunity { duration: 0.5 };
+
+        assert!(immunity.allows(None, 10.0));
+        let last = LastKnockback(10.0);
+
+        assert!(!immunity.allows(Some(&last), 10.2));
+        assert!(immunity.allows(Some(&last), 10.5));
+    }
+}
@@ -12,6 +12,7 @@ mod energy;
 pub mod group;
 mod inputs;
 mod inventory;
+mod knockback;
 mod last;
 mod location;
 mod misc;
@@ -24,9 +25,12 @@ mod stats;
 pub mod visual;
 
 // Reexports
-pub use ability::{CharacterAbility, CharacterAbilityType, ItemConfig, Loadout};
+pub use ability::{
+    AbilityCooldowns, AbilityMeta, AbilityReqError, ActivationEffect, CharacterAbility,
+    CharacterAbilityType, HandRequirement, ItemConfig, Loadout, Stunned,
+};
 pub use admin::Admin;
-pub use agent::{Agent, Alignment};
+pub use agent::{Agent, Alignment, ThreatTable};
 pub use beam::{Beam, BeamSegment};
 pub use body::{
     biped_large, bird_medium, bird_small, dragon, fish_medium, fish_small, golem, humanoid, object,
@@ -44,8 +48,11 @@ pub use controller::{
     Climb, ControlAction, ControlEvent, Controller, ControllerInputs, GroupManip, Input,
     InventoryManip, MountState, Mounting,
 };
-pub use damage::{Damage, DamageSource};
-pub use energy::{Energy, EnergySource};
+pub use damage::{
+    scaled_damage, Damage, DamageKind, DamageOverTime, DamageOverTimeEffects, DamageSource,
+    DotStacking, LastHitBy, KILL_CREDIT_WINDOW,
+};
+pub use energy::{Energy, EnergyRegen, EnergySource};
 pub use group::Group;
 pub use inputs::CanBuild;
 pub use inventory::{
@@ -53,13 +60,14 @@ pub use inventory::{
     item::{Item, ItemDrop},
     slot, Inventory, InventoryUpdate, InventoryUpdateEvent, MAX_PICKUP_RANGE_SQR,
 };
+pub use knockback::{Knockback, KnockbackImmunity, KnockbackKind, LastKnockback};
 pub use last::Last;
 pub use location::{Waypoint, WaypointArea};
 pub use misc::Object;
 pub use phys::{Collider, ForceUpdate, Gravity, Mass, Ori, PhysicsState, Pos, Scale, Sticky, Vel};
 pub use player::{Player, MAX_MOUNT_RANGE_SQR};
-pub use projectile::Projectile;
+pub use projectile::{DespawnReason, Homing, HomingParams, Projectile};
 pub use shockwave::{Shockwave, ShockwaveHitEntities};
 pub use skills::{Skill, SkillGroup, SkillGroupType, SkillSet};
 pub use stats::{Exp, HealthChange, HealthSource, Level, Stats};
-pub use visual::{LightAnimation, LightEmitter};
+pub use visual::{LightAnimation, LightEmitter, LightFlicker};
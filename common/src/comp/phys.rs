@@ -1,4 +1,4 @@
-use crate::{sync::Uid, util::Dir};
+use crate::{sync::Uid, terrain::BlockKind, util::Dir};
 use serde::{Deserialize, Serialize};
 use specs::{Component, FlaggedStorage, NullStorage};
 use specs_idvs::IdvStorage;
@@ -95,8 +95,12 @@ pub struct PhysicsState {
     pub on_ground: bool,
     pub on_ceiling: bool,
     pub on_wall: Option<Vec3<f32>>,
+    /// Whether the wall the entity is against can be climbed
+    pub on_climbable: bool,
     pub touch_entities: Vec<Uid>,
     pub in_fluid: Option<f32>, // Depth
+    /// The kind of block the entity is standing on, if any
+    pub ground_kind: Option<BlockKind>,
 }
 
 impl PhysicsState {
@@ -1,13 +1,19 @@
-use crate::{sync::Uid, Explosion};
+use crate::{
+    comp::{DamageKind, Knockback},
+    sync::Uid,
+    util::Dir,
+    Explosion,
+};
 use serde::{Deserialize, Serialize};
 use specs::{Component, FlaggedStorage};
 use specs_idvs::IdvStorage;
 use std::time::Duration;
+use vek::*;
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum Effect {
     Damage(i32),
-    Knockback(f32),
+    Knockback(Knockback),
     RewardEnergy(u32),
     Explode(Explosion),
     Vanish,
@@ -15,6 +21,20 @@ pub enum Effect {
     Possess,
 }
 
+/// How long projectiles last when an ability doesn't say otherwise
+pub const DEFAULT_LIFETIME: Duration = Duration::from_secs(15);
+
+/// Why a projectile was removed from the world
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DespawnReason {
+    /// It vanished on hitting an entity or the terrain
+    Hit,
+    /// Its lifetime ran out
+    Expired,
+    /// It left the loaded world
+    OutOfBounds,
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Projectile {
     // TODO: use SmallVec for these effects
@@ -26,8 +46,357 @@ pub struct Projectile {
     /// Whether projectile collides with entities in the same group as its
     /// owner
     pub ignore_group: bool,
+    /// If set, the projectile steers towards nearby targets while in flight
+    pub homing: Option<Homing>,
+    /// How many more entities the projectile may pass through before it
+    /// stops at one
+    #[serde(default)]
+    pub pierce: u32,
+    /// Entities that were already hit, so that they aren't hit again
+    #[serde(default)]
+    pub hit_entities: Vec<Uid>,
+    /// The kind of damage dealt by `Effect::Damage`
+    #[serde(default)]
+    pub damage_kind: DamageKind,
+    /// How many more times the projectile bounces off terrain before its
+    /// `hit_solid` effects apply
+    #[serde(default)]
+    pub bounces: u32,
+    /// Fraction of its speed the projectile keeps on each bounce
+    #[serde(default)]
+    pub bounce_damping: f32,
+    /// What damage is multiplied by when hitting a body's weakpoint
+    #[serde(default = "default_weakpoint_multiplier")]
+    pub weakpoint_multiplier: f32,
+}
+
+pub(crate) fn default_weakpoint_multiplier() -> f32 { 1.0 }
+
+impl Projectile {
+    /// Takes the effects to apply to `target`, or `None` if it was already
+    /// hit. While the projectile can still pierce, effects that would stop it
+    /// are left out and the rest are kept for the next target.
+    pub fn hit(&mut self, target: Uid) -> Option<Vec<Effect>> {
+        if self.hit_entities.contains(&target) {
+            return None;
+        }
+        self.hit_entities.push(target);
+        if self.pierce > 0 {
+            self.pierce -= 1;
+            Some(
+                self.hit_entity
+                    .iter()
+                    .filter(|effect| !matches!(effect, Effect::Vanish | Effect::Stick))
+                    .cloned()
+                    .collect(),
+            )
+        } else {
+            Some(self.hit_entity.drain(..).collect())
+        }
+    }
+
+    /// Counts down the projectile's lifetime by `dt`, returning
+    /// `DespawnReason::Expired` once it has run out.
+    pub fn age(&mut self, dt: Duration) -> Option<DespawnReason> {
+        self.time_left = self.time_left.checked_sub(dt).unwrap_or_default();
+        if self.time_left == Duration::default() {
+            Some(DespawnReason::Expired)
+        } else {
+            None
+        }
+    }
+
+    /// Bounces the projectile off a block face with the given `normal`,
+    /// returning its new velocity, or `None` if it has no bounces left.
+    pub fn bounce(&mut self, vel: Vec3<f32>, normal: Vec3<i32>) -> Option<Vec3<f32>> {
+        let normal = normal.map(|e| e as f32).try_normalized()?;
+        if self.bounces == 0 {
+            return None;
+        }
+        self.bounces -= 1;
+        Some((vel - normal * 2.0 * vel.dot(normal)) * self.bounce_damping)
+    }
+
+    /// Scales `damage` by the weakpoint multiplier if the projectile's path
+    /// from `from` to `to` passes through `weakpoint`
+    pub fn weakpoint_damage(
+        &self,
+        damage: f32,
+        weakpoint: Option<Aabb<f32>>,
+        from: Vec3<f32>,
+        to: Vec3<f32>,
+    ) -> f32 {
+        if weakpoint.map_or(false, |weakpoint| segment_hits_aabb(from, to, weakpoint)) {
+            damage * self.weakpoint_multiplier
+        } else {
+            damage
+        }
+    }
+
+    /// Sends the projectile back the way it came after `reflector` blocked
+    /// it, returning its new velocity. The reflector takes ownership so that
+    /// it isn't hit again, and homing projectiles go after the original
+    /// shooter.
+    pub fn reflect(&mut self, reflector: Uid, vel: Vec3<f32>) -> Vec3<f32> {
+        let shooter = self.owner.replace(reflector);
+        if let Some(homing) = &mut self.homing {
+            homing.target = shooter.filter(|shooter| *shooter != reflector);
+        }
+        // Anything hit on the way in may be hit again on the way back
+        self.hit_entities.clear();
+        self.hit_entities.push(reflector);
+        -vel
+    }
+}
+
+/// Whether the line segment from `from` to `to` passes through `aabb`
+fn segment_hits_aabb(from: Vec3<f32>, to: Vec3<f32>, aabb: Aabb<f32>) -> bool {
+    let dir = to - from;
+    let (mut t_min, mut t_max) = (0.0f32, 1.0f32);
+    for i in 0..3 {
+        if dir[i].abs() < f32::EPSILON {
+            // Parallel to this slab, so it has to start inside it
+            if from[i] < aabb.min[i] || from[i] > aabb.max[i] {
+                return false;
+            }
+        } else {
+            let t0 = (aabb.min[i] - from[i]) / dir[i];
+            let t1 = (aabb.max[i] - from[i]) / dir[i];
+            t_min = t_min.max(t0.min(t1));
+            t_max = t_max.min(t0.max(t1));
+            if t_min > t_max {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+/// Tuning for projectiles that track targets
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HomingParams {
+    /// Maximum angle (in radians) the projectile may turn per second
+    pub turn_rate: f32,
+    /// How far away a target may be to be tracked
+    pub max_range: f32,
+    /// Whether to pick a new target once the current one is lost, otherwise
+    /// the projectile continues in a straight line
+    pub retarget: bool,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Homing {
+    pub params: HomingParams,
+    /// The entity currently being tracked
+    pub target: Option<Uid>,
+}
+
+impl From<HomingParams> for Homing {
+    fn from(params: HomingParams) -> Self {
+        Self {
+            params,
+            target: None,
+        }
+    }
+}
+
+/// Rotates `vel` towards `to_target` by at most `max_angle` radians while
+/// keeping its speed
+pub fn steer_towards(vel: Vec3<f32>, to_target: Vec3<f32>, max_angle: f32) -> Vec3<f32> {
+    let speed = vel.magnitude();
+    match (vel.try_normalized(), to_target.try_normalized()) {
+        (Some(dir), Some(target_dir)) => {
+            let angle = dir.angle_between(target_dir);
+            if angle <= max_angle {
+                target_dir * speed
+            } else {
+                *Dir::slerp(Dir::new(dir), Dir::new(target_dir), max_angle / angle) * speed
+            }
+        },
+        _ => vel,
+    }
 }
 
 impl Component for Projectile {
     type Storage = FlaggedStorage<Self, IdvStorage<Self>>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reflection_reverses_and_retargets() {
+        let shooter = Uid(1);
+        let blocker = Uid(2);
+        let mut projectile = Projectile {
+            hit_solid: vec![Effect::Vanish],
+            hit_entity: vec![Effect::Damage(-10), Effect::Vanish],
+            time_left: Duration::from_secs(10),
+            owner: Some(shooter),
+            ignore_group: true,
+            homing: Some(Homing {
+                params: HomingParams {
+                    turn_rate: 1.0,
+                    max_range: 50.0,
+                    retarget: false,
+                },
+                target: Some(blocker),
+            }),
+            pierce: 0,
+            hit_entities: Vec::new(),
+            bounces: 0,
+            bounce_damping: 0.0,
+            damage_kind: DamageKind::Physical,
+            weakpoint_multiplier: 1.0,
+        };
+
+        let vel = projectile.reflect(blocker, Vec3::new(20.0, -5.0, 1.0));
+        assert_eq!(vel, Vec3::new(-20.0, 5.0, -1.0));
+        assert_eq!(projectile.owner, Some(blocker));
+        assert_eq!(projectile.homing.and_then(|h| h.target), Some(shooter));
+        // The blocker doesn't get hit by its own reflection
+        assert_eq!(projectile.hit(blocker), None);
+        assert_eq!(
+            projectile.hit(shooter),
+            Some(vec![Effect::Damage(-10), Effect::Vanish])
+        );
+    }
+
+    #[test]
+    fn heading_converges_on_moving_target() {
+        let dt = 1.0 / 30.0;
+        let turn_rate = std::f32::consts::PI;
+        let mut pos = Vec3::zero();
+        let mut vel = Vec3::unit_x() * 20.0;
+        let mut target = Vec3::new(0.0, 30.0, 0.0);
+        let target_vel = Vec3::new(5.0, 0.0, 0.0);
+
+        for _ in 0..30 {
+            let new_vel = steer_towards(vel, target - pos, turn_rate * dt);
+            // Never turns faster than the turn rate and keeps its speed
+            assert!(vel.angle_between(new_vel) <= turn_rate * dt + 0.001);
+            assert!((new_vel.magnitude() - 20.0).abs() < 0.001);
+            vel = new_vel;
+            pos += vel * dt;
+            target += target_vel * dt;
+        }
+        assert!(vel.angle_between(target - pos) < 0.05);
+    }
+
+    #[test]
+    fn pierce_hits_each_target_once() {
+        let mut projectile = Projectile {
+            hit_solid: vec![Effect::Stick],
+            hit_entity: vec![Effect::Damage(-10), Effect::Vanish],
+            time_left: Duration::from_secs(15),
+            owner: Some(Uid(0)),
+            ignore_group: true,
+            homing: None,
+            pierce: 2,
+            hit_entities: Vec::new(),
+            bounces: 0,
+            bounce_damping: 0.0,
+            damage_kind: DamageKind::Physical,
+            weakpoint_multiplier: 1.0,
+        };
+
+        // Passes through the first two targets, stopping at the third
+        for target in 1..=2 {
+            assert_eq!(projectile.hit(Uid(target)), Some(vec![Effect::Damage(-10)]));
+        }
+        assert_eq!(projectile.hit(Uid(2)), None);
+        assert_eq!(
+            projectile.hit(Uid(3)),
+            Some(vec![Effect::Damage(-10), Effect::Vanish])
+        );
+        assert_eq!(projectile.hit(Uid(4)), Some(Vec::new()));
+    }
+
+    #[test]
+    fn expires_after_lifetime() {
+        let mut projectile = Projectile {
+            hit_solid: vec![Effect::Vanish],
+            hit_entity: vec![Effect::Damage(-10), Effect::Vanish],
+            time_left: Duration::from_millis(100),
+            owner: Some(Uid(0)),
+            ignore_group: true,
+            homing: None,
+            pierce: 0,
+            hit_entities: Vec::new(),
+            bounces: 0,
+            bounce_damping: 0.0,
+            damage_kind: DamageKind::Physical,
+            weakpoint_multiplier: 1.0,
+        };
+
+        let dt = Duration::from_millis(40);
+        assert_eq!(projectile.age(dt), None);
+        assert_eq!(projectile.age(dt), None);
+        assert_eq!(projectile.age(dt), Some(DespawnReason::Expired));
+        // Stays expired even if the despawn is delayed
+        assert_eq!(projectile.age(dt), Some(DespawnReason::Expired));
+    }
+
+    #[test]
+    fn no_steering_without_direction() {
+        let vel = Vec3::new(1.0, 2.0, 3.0);
+        assert_eq!(steer_towards(vel, Vec3::zero(), 1.0), vel);
+        assert_eq!(steer_towards(Vec3::zero(), vel, 1.0), Vec3::zero());
+    }
+
+    #[test]
+    fn weakpoint_hit_multiplies_damage() {
+        let projectile = Projectile {
+            hit_solid: vec![Effect::Stick],
+            hit_entity: vec![Effect::Damage(-40), Effect::Vanish],
+            time_left: Duration::from_secs(15),
+            owner: Some(Uid(0)),
+            ignore_group: true,
+            homing: None,
+            pierce: 0,
+            hit_entities: Vec::new(),
+            bounces: 0,
+            bounce_damping: 0.0,
+            damage_kind: DamageKind::Physical,
+            weakpoint_multiplier: 2.0,
+        };
+        let head = Aabb {
+            min: Vec3::new(-0.25, -0.25, 1.35),
+            max: Vec3::new(0.25, 0.25, 1.8),
+        };
+
+        // Straight through the head
+        let damage = projectile.weakpoint_damage(
+            -40.0,
+            Some(head),
+            Vec3::new(-3.0, 0.0, 1.6),
+            Vec3::new(0.5, 0.0, 1.6),
+        );
+        assert!((damage + 80.0).abs() < f32::EPSILON);
+        // Into the chest
+        let damage = projectile.weakpoint_damage(
+            -40.0,
+            Some(head),
+            Vec3::new(-3.0, 0.0, 1.0),
+            Vec3::new(0.5, 0.0, 1.0),
+        );
+        assert!((damage + 40.0).abs() < f32::EPSILON);
+        // Stopping short of the head
+        let damage = projectile.weakpoint_damage(
+            -40.0,
+            Some(head),
+            Vec3::new(-3.0, 0.0, 1.6),
+            Vec3::new(-1.0, 0.0, 1.6),
+        );
+        assert!((damage + 40.0).abs() < f32::EPSILON);
+        // No weakpoint to hit
+        let damage = projectile.weakpoint_damage(
+            -40.0,
+            None,
+            Vec3::new(-3.0, 0.0, 1.6),
+            Vec3::new(0.5, 0.0, 1.6),
+        );
+        assert!((damage + 40.0).abs() < f32::EPSILON);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::sync::Uid;
+use crate::{comp::Knockback, sync::Uid};
 use serde::{Deserialize, Serialize};
 use specs::{Component, FlaggedStorage};
 use specs_idvs::IdvStorage;
@@ -10,8 +10,12 @@ pub struct Properties {
     pub vertical_angle: f32,
     pub speed: f32,
     pub damage: u32,
-    pub knockback: f32,
+    pub knockback: Knockback,
     pub requires_ground: bool,
+    pub max_targets: Option<u32>,
+    /// How long anything hit is stunned for
+    #[serde(default)]
+    pub stun: Option<Duration>,
     pub duration: Duration,
     pub owner: Option<Uid>,
 }
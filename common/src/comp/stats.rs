@@ -24,6 +24,7 @@ pub enum HealthSource {
     Buff { owner: Option<Uid> },
     Suicide,
     World,
+    Trap,
     Revive,
     Command,
     LevelUp,
@@ -9,6 +9,9 @@ pub struct LightEmitter {
     pub strength: f32,
     pub flicker: f32,
     pub animated: bool,
+    /// Smooth variation of the strength over time, on top of `flicker`
+    #[serde(default)]
+    pub periodic_flicker: Option<LightFlicker>,
 }
 
 impl Default for LightEmitter {
@@ -18,10 +21,42 @@ impl Default for LightEmitter {
             strength: 1.0,
             flicker: 0.0,
             animated: false,
+            periodic_flicker: None,
         }
     }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LightFlicker {
+    /// Largest change to the light's strength
+    pub amplitude: f32,
+    /// How many times a second the strength changes direction, roughly
+    pub frequency: f32,
+}
+
+impl LightFlicker {
+    /// The change to the light's strength at `time`. This interpolates smoothly
+    /// between random-looking samples taken `frequency` times a second, so it
+    /// is always within `amplitude` of zero. Lights with different `seed`s
+    /// flicker out of step with each other.
+    pub fn strength_offset(&self, time: f64, seed: u32) -> f32 {
+        // Pseudo-random value in [-1, 1] for each sample
+        let sample = |n: i64| {
+            let mut x = (n as u64)
+                .wrapping_add(u64::from(seed) << 32)
+                .wrapping_mul(0x9E37_79B9_7F4A_7C15);
+            x ^= x >> 29;
+            (x as u32) as f32 / u32::MAX as f32 * 2.0 - 1.0
+        };
+        let t = time * f64::from(self.frequency);
+        let n = t.floor();
+        let frac = (t - n) as f32;
+        let (a, b) = (sample(n as i64), sample(n as i64 + 1));
+        let smooth = frac * frac * (3.0 - 2.0 * frac);
+        self.amplitude * (a + (b - a) * smooth)
+    }
+}
+
 impl Component for LightEmitter {
     type Storage = FlaggedStorage<Self, IdvStorage<Self>>;
 }
@@ -46,3 +81,28 @@ impl Default for LightAnimation {
 impl Component for LightAnimation {
     type Storage = FlaggedStorage<Self, IdvStorage<Self>>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flicker_stays_within_amplitude() {
+        let flicker = LightFlicker {
+            amplitude: 0.4,
+            frequency: 7.0,
+        };
+        let offsets = (0..10_000)
+            .map(|i| flicker.strength_offset(i as f64 * 0.013, 3))
+            .collect::<Vec<_>>();
+        assert!(offsets.iter().all(|o| o.abs() <= flicker.amplitude));
+        // It actually flickers
+        let min = offsets.iter().copied().fold(f32::INFINITY, f32::min);
+        let max = offsets.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        assert!(max - min > flicker.amplitude);
+        // Without jumps between samples
+        assert!(offsets
+            .windows(2)
+            .all(|w| (w[1] - w[0]).abs() < flicker.amplitude));
+    }
+}
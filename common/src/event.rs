@@ -1,4 +1,11 @@
-use crate::{character::CharacterId, comp, sync::Uid, util::Dir, Explosion};
+use crate::{
+    character::{CharacterId, HotbarSlot},
+    comp,
+    sync::Uid,
+    terrain::Block,
+    util::Dir,
+    Explosion,
+};
 use comp::{
     item::{Item, Reagent},
     Ori, Pos,
@@ -63,6 +70,8 @@ pub enum ServerEvent {
         entity: EcsEntity,
         impulse: Vec3<f32>,
     },
+    /// Cancel the ability an entity is preparing, if it can still be cancelled
+    CancelAbility(EcsEntity),
     BeamSegment {
         properties: comp::beam::Properties,
         pos: Pos,
@@ -90,6 +99,16 @@ pub enum ServerEvent {
     ExitIngame {
         entity: EcsEntity,
     },
+    /// Removes a projectile, letting clients know why it went away
+    DespawnProjectile {
+        entity: EcsEntity,
+        reason: comp::DespawnReason,
+    },
+    /// Saves the hotbar layout of the entity's character
+    SetHotbar {
+        entity: EcsEntity,
+        layout: Vec<Option<HotbarSlot>>,
+    },
     CreateNpc {
         pos: comp::Pos,
         stats: comp::Stats,
@@ -110,6 +129,24 @@ pub enum ServerEvent {
         entity: EcsEntity,
         buff_change: comp::BuffChange,
     },
+    /// Inflict damage over time on an entity hit by an attack from `by`
+    DamageOverTime {
+        entity: EcsEntity,
+        dot: comp::DamageOverTime,
+        by: Uid,
+    },
+    /// A player picked `response` from a dialogue `speaker` showed them
+    DialogueResponse {
+        entity: EcsEntity,
+        speaker: Uid,
+        response: String,
+    },
+    /// An entity finished placing `block` at `pos`
+    PlaceBlock {
+        entity: EcsEntity,
+        pos: Vec3<i32>,
+        block: Block,
+    },
 }
 
 pub struct EventBus<E> {
@@ -0,0 +1,94 @@
+use crate::sys::phys::GRAVITY;
+use serde::{Deserialize, Serialize};
+
+/// Balance constants that a server can tune without clients being rebuilt.
+/// The server sends its settings to each client as part of the initial sync.
+/// Clients that don't understand the `version` of a server's settings keep
+/// the defaults.
+///
+/// `gravity`, `max_fall_speed`, `max_move_speed` and `max_swim_speed` feed
+/// into physics and movement, so the client needs them to predict its own
+/// movement the way the server will. The remaining fields are only used by
+/// the server and are sent for display.
+///
+/// Fields missing from a server's settings file take their default values.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameplaySettings {
+    /// Revision of these settings, bumped whenever a field is added or the
+    /// meaning of one changes so that clients can ignore settings they don't
+    /// understand
+    pub version: u32,
+    /// Downwards acceleration, in blocks per second squared
+    pub gravity: f32,
+    /// Fastest speed anything can fall at, in blocks per second
+    pub max_fall_speed: f32,
+    /// Fastest horizontal speed entities can walk or fly at by themselves, in
+    /// blocks per second. Knockbacks can push them past it.
+    pub max_move_speed: f32,
+    /// Fastest speed entities can swim at, in blocks per second
+    pub max_swim_speed: f32,
+    /// Landing speed above which entities take fall damage, in blocks per
+    /// second
+    pub fall_damage_speed: f32,
+}
+
+impl GameplaySettings {
+    pub const VERSION: u32 = 2;
+
+    /// Damage taken from landing at `speed` blocks per second. Landings slower
+    /// than `fall_damage_speed` are harmless, and no landing heals.
+    pub fn fall_damage(&self, speed: f32) -> f32 {
+        if speed >= self.fall_damage_speed {
+            ((speed.powi(2) / 20.0 - 40.0) * 10.0).max(0.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+impl Default for GameplaySettings {
+    fn default() -> Self {
+        Self {
+            version: Self::VERSION,
+            gravity: GRAVITY,
+            max_fall_speed: 80.0,
+            max_move_speed: 100.0,
+            max_swim_speed: 180.0,
+            fall_damage_speed: 30.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fall_damage_starts_at_threshold() {
+        let settings = GameplaySettings::default();
+        assert!(settings.fall_damage(20.0) < f32::EPSILON);
+        assert!((settings.fall_damage(30.0) - 50.0).abs() < 0.01);
+        assert!((settings.fall_damage(40.0) - 400.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn low_threshold_never_heals() {
+        let settings = GameplaySettings {
+            fall_damage_speed: 5.0,
+            ..GameplaySettings::default()
+        };
+        // `(v² / 20 - 40) * 10` is negative at this speed
+        assert!(settings.fall_damage(10.0) < f32::EPSILON);
+        assert!(settings.fall_damage(10.0) >= 0.0);
+    }
+
+    #[test]
+    fn missing_settings_use_defaults() {
+        let settings: GameplaySettings = ron::de::from_str("(gravity: 30.0)").unwrap();
+        assert_eq!(settings, GameplaySettings {
+            gravity: 30.0,
+            ..GameplaySettings::default()
+        });
+    }
+}
@@ -27,6 +27,7 @@ pub mod effect;
 pub mod event;
 pub mod explosion;
 pub mod figure;
+pub mod gameplay;
 pub mod generation;
 pub mod loadout_builder;
 pub mod lottery;
@@ -1,10 +1,37 @@
 use crate::comp::{
     biped_large, golem,
     item::{Item, ItemKind},
-    Alignment, Body, CharacterAbility, ItemConfig, Loadout,
+    slot::{ArmorSlot, EquipSlot, Slot},
+    AbilityMeta, Alignment, Body, CharacterAbility, DamageKind, ItemConfig, Knockback, Loadout,
 };
 use rand::Rng;
-use std::time::Duration;
+use std::{error::Error, fmt, time::Duration};
+
+/// Ways in which a loadout can be inconsistent
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoadoutError {
+    /// An item is in a slot that can't hold it
+    WrongSlot { slot: EquipSlot, item: String },
+    /// The main and off hand items can't be held together, see
+    /// `Hands::compatible_with`
+    IncompatibleHands,
+    /// No starting tool was picked
+    MissingTool,
+    /// There's no item with this asset id
+    UnknownItem(String),
+}
+
+impl fmt::Display for LoadoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WrongSlot { slot, item } => write!(f, "{} can't be equipped as {:?}", item, slot),
+            Self::IncompatibleHands => write!(f, "main and off hand items can't be held together"),
+            Self::MissingTool => write!(f, "no tool was picked"),
+            Self::UnknownItem(item) => write!(f, "{} is not an item", item),
+        }
+    }
+}
+impl Error for LoadoutError {}
 
 /// Builder for character Loadouts, containing weapon and armour items belonging
 /// to a character, along with some helper methods for loading Items and
@@ -154,7 +181,9 @@ impl LoadoutBuilder {
                 ability2: ability_drain.next(),
                 ability3: ability_drain.next(),
                 block_ability: None,
-                dodge_ability: Some(CharacterAbility::Roll),
+                dodge_ability: Some(CharacterAbility::Roll {
+                    meta: AbilityMeta::default(),
+                }),
             })
         } else {
             Some(ItemConfig {
@@ -165,9 +194,12 @@ impl LoadoutBuilder {
                     buildup_duration: Duration::from_millis(0),
                     recover_duration: Duration::from_millis(400),
                     base_healthchange: -40,
-                    knockback: 0.0,
+                    knockback: Knockback::default(),
                     range: 3.5,
                     max_angle: 15.0,
+                    dot: None,
+                    damage_kind: DamageKind::Physical,
+                    meta: AbilityMeta::default(),
                 }),
                 ability2: None,
                 ability3: None,
@@ -346,9 +378,12 @@ impl LoadoutBuilder {
                     buildup_duration: Duration::from_millis(600),
                     recover_duration: Duration::from_millis(100),
                     base_healthchange: -(body.base_dmg() as i32),
-                    knockback: 0.0,
+                    knockback: Knockback::default(),
                     range: body.base_range(),
                     max_angle: 20.0,
+                    dot: None,
+                    damage_kind: DamageKind::Physical,
+                    meta: AbilityMeta::default(),
                 }),
                 ability2: None,
                 ability3: None,
@@ -390,6 +425,23 @@ impl LoadoutBuilder {
         Self::default_item_config_from_item(Item::new_from_asset_expect(item_ref))
     }
 
+    /// Like [default_item_config_from_str](#method.default_item_config_from_str),
+    /// but for item references that can't be trusted, such as those sent by
+    /// clients. Fails instead of panicking if the item doesn't exist or isn't a
+    /// tool.
+    pub fn try_item_config_from_str(item_ref: &str) -> Result<ItemConfig, LoadoutError> {
+        let item = Item::new_from_asset(item_ref)
+            .map_err(|_| LoadoutError::UnknownItem(item_ref.to_string()))?;
+        if let ItemKind::Tool(_) = item.kind() {
+            Ok(Self::default_item_config_from_item(item))
+        } else {
+            Err(LoadoutError::WrongSlot {
+                slot: EquipSlot::Mainhand,
+                item: item_ref.to_string(),
+            })
+        }
+    }
+
     pub fn active_item(mut self, item: Option<ItemConfig>) -> Self {
         self.0.active_item = item;
 
@@ -468,4 +520,158 @@ impl LoadoutBuilder {
     }
 
     pub fn build(self) -> Loadout { self.0 }
+
+    /// Like `build`, but fails if an item is in a slot that can't hold it or
+    /// the main and off hand items can't be held together.
+    pub fn try_build(self) -> Result<Loadout, LoadoutError> {
+        let loadout = self.0;
+        let slots = [
+            (
+                EquipSlot::Mainhand,
+                loadout.active_item.as_ref().map(|i| &i.item),
+            ),
+            (
+                EquipSlot::Offhand,
+                loadout.second_item.as_ref().map(|i| &i.item),
+            ),
+            (EquipSlot::Lantern, loadout.lantern.as_ref()),
+            (EquipSlot::Glider, loadout.glider.as_ref()),
+            (
+                EquipSlot::Armor(ArmorSlot::Shoulders),
+                loadout.shoulder.as_ref(),
+            ),
+            (EquipSlot::Armor(ArmorSlot::Chest), loadout.chest.as_ref()),
+            (EquipSlot::Armor(ArmorSlot::Belt), loadout.belt.as_ref()),
+            (EquipSlot::Armor(ArmorSlot::Hands), loadout.hand.as_ref()),
+            (EquipSlot::Armor(ArmorSlot::Legs), loadout.pants.as_ref()),
+            (EquipSlot::Armor(ArmorSlot::Feet), loadout.foot.as_ref()),
+            (EquipSlot::Armor(ArmorSlot::Back), loadout.back.as_ref()),
+            (EquipSlot::Armor(ArmorSlot::Ring), loadout.ring.as_ref()),
+            (EquipSlot::Armor(ArmorSlot::Neck), loadout.neck.as_ref()),
+            (EquipSlot::Armor(ArmorSlot::Head), loadout.head.as_ref()),
+            (EquipSlot::Armor(ArmorSlot::Tabard), loadout.tabard.as_ref()),
+        ];
+        for (slot, item) in slots.iter() {
+            if let Some(item) = item {
+                if !Slot::Equip(*slot).can_hold(item.kind()) {
+                    return Err(LoadoutError::WrongSlot {
+                        slot: *slot,
+                        item: item.item_definition_id().to_string(),
+                    });
+                }
+            }
+        }
+
+        if !loadout.hands_compatible() {
+            return Err(LoadoutError::IncompatibleHands);
+        }
+
+        Ok(loadout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SWORD: &str = "common.items.weapons.sword.zweihander_sword_0";
+    const DAGGER: &str = "common.items.weapons.dagger.starter_dagger";
+    const SHIELD: &str = "common.items.weapons.shield.shield_1";
+
+    #[test]
+    fn valid_loadout_builds() {
+        let loadout = LoadoutBuilder::new()
+            .defaults()
+            .active_item(Some(LoadoutBuilder::default_item_config_from_str(SWORD)))
+            .try_build();
+        assert!(loadout.is_ok());
+
+        let loadout = LoadoutBuilder::new()
+            .defaults()
+            .active_item(Some(LoadoutBuilder::default_item_config_from_str(DAGGER)))
+            .second_item(Some(LoadoutBuilder::default_item_config_from_str(SHIELD)))
+            .try_build();
+        assert!(loadout.is_ok());
+    }
+
+    #[test]
+    fn tool_in_armor_slot_is_rejected() {
+        let loadout = LoadoutBuilder::new()
+            .chest(Some(Item::new_from_asset_expect(SWORD)))
+            .try_build();
+        assert_eq!(
+            loadout,
+            Err(LoadoutError::WrongSlot {
+                slot: EquipSlot::Armor(ArmorSlot::Chest),
+                item: SWORD.to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn armor_in_wrong_armor_slot_is_rejected() {
+        let pants = "common.items.armor.starter.rugged_pants";
+        let loadout = LoadoutBuilder::new()
+            .chest(Some(Item::new_from_asset_expect(pants)))
+            .try_build();
+        assert_eq!(
+            loadout,
+            Err(LoadoutError::WrongSlot {
+                slot: EquipSlot::Armor(ArmorSlot::Chest),
+                item: pants.to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn armor_in_tool_slot_is_rejected() {
+        let chest = "common.items.armor.starter.rugged_chest";
+        // `ItemConfig::from` only accepts tools
+        let loadout = LoadoutBuilder::new()
+            .active_item(Some(ItemConfig {
+                item: Item::new_from_asset_expect(chest),
+                ability1: None,
+                ability2: None,
+                ability3: None,
+                block_ability: None,
+                dodge_ability: None,
+            }))
+            .try_build();
+        assert_eq!(
+            loadout,
+            Err(LoadoutError::WrongSlot {
+                slot: EquipSlot::Mainhand,
+                item: chest.to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn two_handed_with_one_handed_is_rejected() {
+        let loadout = LoadoutBuilder::new()
+            .active_item(Some(LoadoutBuilder::default_item_config_from_str(SWORD)))
+            .second_item(Some(LoadoutBuilder::default_item_config_from_str(SHIELD)))
+            .try_build();
+        assert_eq!(loadout, Err(LoadoutError::IncompatibleHands));
+    }
+
+    #[test]
+    fn untrusted_item_refs_are_checked() {
+        assert!(LoadoutBuilder::try_item_config_from_str(SWORD).is_ok());
+
+        let chest = "common.items.armor.starter.rugged_chest";
+        assert_eq!(
+            LoadoutBuilder::try_item_config_from_str(chest).map(|_| ()),
+            Err(LoadoutError::WrongSlot {
+                slot: EquipSlot::Mainhand,
+                item: chest.to_string(),
+            })
+        );
+
+        let unknown = "common.items.weapons.sword.does_not_exist";
+        assert_eq!(
+            LoadoutBuilder::try_item_config_from_str(unknown).map(|_| ()),
+            Err(LoadoutError::UnknownItem(unknown.to_string()))
+        );
+    }
 }
@@ -43,6 +43,18 @@ impl<T> Lottery<T> {
     pub fn choose(&self) -> &T { self.choose_seeded(thread_rng().gen()) }
 
     pub fn iter(&self) -> impl Iterator<Item = &(f32, T)> { self.items.iter() }
+
+    /// The sum of the rates of all items
+    pub fn total(&self) -> f32 { self.total }
+
+    /// Adds more items to the lottery, with the given rates.
+    pub fn extended(mut self, items: impl Iterator<Item = (f32, T)>) -> Self {
+        for (rate, item) in items {
+            self.items.push((self.total, item));
+            self.total += rate;
+        }
+        self
+    }
 }
 
 #[cfg(test)]
@@ -1,6 +1,6 @@
 use super::PingMsg;
 use crate::{
-    character::CharacterId,
+    character::{CharacterId, HotbarSlot},
     comp,
     comp::{Skill, SkillGroupType},
     terrain::block::Block,
@@ -61,9 +61,17 @@ pub enum ClientGeneral {
     ControllerInputs(comp::ControllerInputs),
     ControlEvent(comp::ControlEvent),
     ControlAction(comp::ControlAction),
+    /// Cancel the ability currently being prepared or charged
+    CancelAbility,
+    /// Consume the item in this inventory slot, e.g. drink a potion
+    UseInventoryItem {
+        slot: usize,
+    },
     SetViewDistance(u32),
-    BreakBlock(Vec3<i32>),
-    PlaceBlock(Vec3<i32>, Block),
+    /// Block edits carry a sequence number, which is echoed back once the edit
+    /// has been applied
+    BreakBlock(Vec3<i32>, u64),
+    PlaceBlock(Vec3<i32>, Block, u64),
     ExitInGame,
     PlayerPhysics {
         pos: comp::Pos,
@@ -76,6 +84,13 @@ pub enum ClientGeneral {
     UnlockSkill(Skill),
     RefundSkill(Skill),
     UnlockSkillGroup(SkillGroupType),
+    /// The player picked one of the responses to the last
+    /// `ServerGeneral::Dialogue`
+    DialogueResponse {
+        index: usize,
+    },
+    /// Replaces the hotbar layout stored for the current character
+    SetHotbar(Vec<Option<HotbarSlot>>),
     //Always possible
     ChatMsg(String),
     Disconnect,
@@ -107,15 +122,19 @@ impl ClientMsg {
                         ClientGeneral::ControllerInputs(_)
                         | ClientGeneral::ControlEvent(_)
                         | ClientGeneral::ControlAction(_)
+                        | ClientGeneral::CancelAbility
+                        | ClientGeneral::UseInventoryItem { .. }
                         | ClientGeneral::SetViewDistance(_)
-                        | ClientGeneral::BreakBlock(_)
-                        | ClientGeneral::PlaceBlock(_, _)
+                        | ClientGeneral::BreakBlock(_, _)
+                        | ClientGeneral::PlaceBlock(_, _, _)
                         | ClientGeneral::ExitInGame
                         | ClientGeneral::PlayerPhysics { .. }
                         | ClientGeneral::TerrainChunkRequest { .. }
                         | ClientGeneral::UnlockSkill(_)
                         | ClientGeneral::RefundSkill(_)
-                        | ClientGeneral::UnlockSkillGroup(_) => {
+                        | ClientGeneral::UnlockSkillGroup(_)
+                        | ClientGeneral::DialogueResponse { .. }
+                        | ClientGeneral::SetHotbar(_) => {
                             c_type == ClientType::Game && in_game.is_some()
                         },
                         //Always possible
@@ -148,3 +167,17 @@ impl Into<ClientMsg> for ClientGeneral {
 impl Into<ClientMsg> for PingMsg {
     fn into(self) -> ClientMsg { ClientMsg::Ping(self) }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dialogue_response_round_trip() {
+        let ser = ron::ser::to_string(&ClientGeneral::DialogueResponse { index: 3 }).unwrap();
+        assert!(matches!(
+            ron::de::from_str(&ser).unwrap(),
+            ClientGeneral::DialogueResponse { index: 3 }
+        ));
+    }
+}
@@ -8,8 +8,10 @@ pub use self::{
     client::{ClientGeneral, ClientMsg, ClientRegister, ClientType},
     ecs_packet::EcsCompPacket,
     server::{
-        CharacterInfo, DisconnectReason, InviteAnswer, Notification, PlayerInfo, PlayerListUpdate,
-        RegisterError, ServerGeneral, ServerInfo, ServerInit, ServerMsg, ServerRegisterAnswer,
+        CharacterInfo, DisconnectReason, InviteAnswer, KillCause, Notification, ObjectiveUpdate,
+        PlayerInfo, PlayerListUpdate, RegisterError, ServerGeneral, ServerGeneralStream,
+        ServerInfo, ServerInit, ServerMsg, ServerRegisterAnswer, ThrottleReason,
+        MAX_OBJECTIVE_ENTRIES,
     },
     world_msg::WorldMapMsg,
 };
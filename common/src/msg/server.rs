@@ -1,7 +1,8 @@
 use super::{ClientType, EcsCompPacket, PingMsg};
 use crate::{
-    character::CharacterItem,
+    character::{CharacterItem, HotbarSlot},
     comp,
+    gameplay::GameplaySettings,
     outcome::Outcome,
     recipe::RecipeBook,
     state, sync,
@@ -16,7 +17,7 @@ use vek::*;
 
 ///This struct contains all messages the server might send (on different
 /// streams though)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ServerMsg {
     /// Basic info about server, send ONCE, clients need it to Register
     Info(ServerInfo),
@@ -54,6 +55,7 @@ pub enum ServerInit {
         client_timeout: Duration,
         world_map: crate::msg::world_msg::WorldMapMsg,
         recipe_book: RecipeBook,
+        gameplay_settings: GameplaySettings,
     },
 }
 
@@ -97,15 +99,78 @@ pub enum ServerGeneral {
         key: Vec2<i32>,
         chunk: Result<Box<TerrainChunk>, ()>,
     },
-    TerrainBlockUpdates(HashMap<Vec3<i32>, Block>),
+    /// Blocks changed this tick, along with the client edit requests (by
+    /// sequence number) that caused them
+    TerrainBlockUpdates {
+        blocks: HashMap<Vec3<i32>, Block>,
+        confirmed: Vec<(Uid, u64)>,
+    },
     SetViewDistance(u32),
+    /// Temporarily limit the client's view distance below what it asked for,
+    /// without changing its setting. Unlike `SetViewDistance` this is lifted
+    /// again with `ViewDistanceThrottleLifted`.
+    ViewDistanceThrottle {
+        vd: u32,
+        reason: ThrottleReason,
+    },
+    ViewDistanceThrottleLifted,
     Outcomes(Vec<Outcome>),
     Knockback(Vec3<f32>),
+    /// Replace or clear the game mode objectives shown in the client's overlay
+    ObjectiveUpdate(ObjectiveUpdate),
+    /// A chunk was given a custom name, or had it cleared
+    ChunkNamed {
+        key: Vec2<i32>,
+        name: Option<String>,
+    },
+    /// Text spoken by an NPC, to show in a dialogue box rather than the chat.
+    /// The client answers with the index of one of the `responses` in
+    /// `ClientGeneral::DialogueResponse`.
+    Dialogue {
+        speaker: Uid,
+        lines: Vec<String>,
+        responses: Vec<String>,
+    },
+    /// A brief flash of light at `pos`, such as from lightning, that isn't
+    /// tied to any entity or block. The client must remove the light again
+    /// once `duration` has passed.
+    LightFlash {
+        pos: Vec3<f32>,
+        color: Rgb<f32>,
+        strength: f32,
+        duration: Duration,
+    },
+    /// The hotbar layout saved for the character, sent once their data has
+    /// been loaded
+    Hotbar(Vec<Option<HotbarSlot>>),
+    /// How many of the chunks around the client are ready, out of those it
+    /// has to wait for when it first enters the game. Sent whenever another
+    /// one is sent to the client until all are, for a loading screen.
+    ChunkGenProgress {
+        requested: u32,
+        ready: u32,
+    },
+    /// How threatening the client's surroundings are, from 0 (calm) to 1
+    /// (intense), for the music to follow
+    MusicMood(f32),
+    /// The attackers with the most threat on `entity`, highest first, sent
+    /// to those among them. Empty once the client is no longer on it.
+    ThreatTable {
+        entity: Uid,
+        top: Vec<(Uid, f32)>,
+    },
     // Always possible
     PlayerListUpdate(PlayerListUpdate),
     /// A message to go into the client chat box. The client is responsible for
     /// formatting the message and turning it into a speech bubble.
     ChatMsg(comp::ChatMsg),
+    /// An entity died, for the client's kill feed. `killer` is `None` for
+    /// deaths with nobody to blame, such as falling.
+    KillEvent {
+        killer: Option<Uid>,
+        victim: Uid,
+        cause: KillCause,
+    },
     SetPlayerEntity(Uid),
     TimeOfDay(state::TimeOfDay),
     EntitySync(sync::EntitySyncPackage),
@@ -128,6 +193,8 @@ pub enum PlayerListUpdate {
     Add(Uid, PlayerInfo),
     SelectedCharacter(Uid, CharacterInfo),
     LevelChange(Uid, u32),
+    /// Current and maximum health and energy of a group member
+    Vitals(Uid, Option<(u32, u32)>, Option<(u32, u32)>),
     Admin(Uid, bool),
     Remove(Uid),
     Alias(Uid, String),
@@ -145,6 +212,70 @@ pub struct PlayerInfo {
 pub struct CharacterInfo {
     pub name: String,
     pub level: u32,
+    /// Current and maximum health, only sent to members of the same group
+    #[serde(default)]
+    pub health: Option<(u32, u32)>,
+    /// Current and maximum energy, only sent to members of the same group
+    #[serde(default)]
+    pub energy: Option<(u32, u32)>,
+}
+
+/// How an entity died, as shown in the kill feed
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KillCause {
+    Melee,
+    Ranged,
+    Fall,
+    Trap,
+    Environment,
+    Suicide,
+}
+
+impl KillCause {
+    /// Who, if anyone, is responsible for a death from `source`, and how it
+    /// happened.
+    pub fn from_health_source(source: &comp::HealthSource) -> (Option<Uid>, Self) {
+        use comp::HealthSource;
+        match *source {
+            HealthSource::Attack { by } => (Some(by), KillCause::Melee),
+            HealthSource::Projectile { owner }
+            | HealthSource::Explosion { owner }
+            | HealthSource::Energy { owner } => (owner, KillCause::Ranged),
+            HealthSource::Buff { owner } => (owner, KillCause::Environment),
+            HealthSource::World => (None, KillCause::Fall),
+            HealthSource::Trap => (None, KillCause::Trap),
+            HealthSource::Suicide => (None, KillCause::Suicide),
+            HealthSource::Revive
+            | HealthSource::Command
+            | HealthSource::LevelUp
+            | HealthSource::Item
+            | HealthSource::Healing { .. }
+            | HealthSource::Unknown => (None, KillCause::Environment),
+        }
+    }
+}
+
+/// Most objective entries the server will send, and the client will display,
+/// at once
+pub const MAX_OBJECTIVE_ENTRIES: usize = 16;
+
+/// Generic scoreboard for game modes (capture points, kill counts, ...), kept
+/// separate from chat.
+///
+/// Each entry is a label and its value. The client displays the entries in
+/// the order they are sent, replacing whatever it showed before.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ObjectiveUpdate {
+    Set(Vec<(String, String)>),
+    Clear,
+}
+
+impl ObjectiveUpdate {
+    /// Builds a `Set` update, keeping only the first
+    /// [`MAX_OBJECTIVE_ENTRIES`] entries.
+    pub fn set(entries: impl IntoIterator<Item = (String, String)>) -> Self {
+        Self::Set(entries.into_iter().take(MAX_OBJECTIVE_ENTRIES).collect())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -159,6 +290,15 @@ pub enum Notification {
     WaypointSaved,
 }
 
+/// Why the server is limiting a client's view distance
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ThrottleReason {
+    /// The server can't keep up with generating and sending terrain
+    ServerLoad,
+    /// The client isn't keeping up with the terrain it is sent
+    ClientLag,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum DisconnectReason {
     /// Server shut down
@@ -192,41 +332,19 @@ impl ServerMsg {
             },
             ServerMsg::General(g) => {
                 registered
-                    && match g {
-                        //Character Screen related
-                        ServerGeneral::CharacterDataLoadError(_)
-                        | ServerGeneral::CharacterListUpdate(_)
-                        | ServerGeneral::CharacterActionError(_) => {
-                            c_type != ClientType::ChatOnly && in_game.is_none()
+                    && match g.stream() {
+                        ServerGeneralStream::CharacterScreen => {
+                            in_game.is_none()
+                                && if let ServerGeneral::CharacterSuccess = g {
+                                    c_type == ClientType::Game
+                                } else {
+                                    c_type != ClientType::ChatOnly
+                                }
                         },
-                        ServerGeneral::CharacterSuccess => {
-                            c_type == ClientType::Game && in_game.is_none()
-                        },
-                        //Ingame related
-                        ServerGeneral::GroupUpdate(_)
-                        | ServerGeneral::GroupInvite { .. }
-                        | ServerGeneral::InvitePending(_)
-                        | ServerGeneral::InviteComplete { .. }
-                        | ServerGeneral::ExitInGameSuccess
-                        | ServerGeneral::InventoryUpdate(_, _)
-                        | ServerGeneral::TerrainChunkUpdate { .. }
-                        | ServerGeneral::TerrainBlockUpdates(_)
-                        | ServerGeneral::SetViewDistance(_)
-                        | ServerGeneral::Outcomes(_)
-                        | ServerGeneral::Knockback(_) => {
+                        ServerGeneralStream::InGame => {
                             c_type == ClientType::Game && in_game.is_some()
                         },
-                        // Always possible
-                        ServerGeneral::PlayerListUpdate(_)
-                        | ServerGeneral::ChatMsg(_)
-                        | ServerGeneral::SetPlayerEntity(_)
-                        | ServerGeneral::TimeOfDay(_)
-                        | ServerGeneral::EntitySync(_)
-                        | ServerGeneral::CompSync(_)
-                        | ServerGeneral::CreateEntity(_)
-                        | ServerGeneral::DeleteEntity(_)
-                        | ServerGeneral::Disconnect(_)
-                        | ServerGeneral::Notification(_) => true,
+                        ServerGeneralStream::General => true,
                     }
             },
             ServerMsg::Ping(_) => true,
@@ -234,6 +352,62 @@ impl ServerMsg {
     }
 }
 
+/// The stream a [`ServerGeneral`] message is sent on, which decides in which
+/// client states it can be received
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ServerGeneralStream {
+    CharacterScreen,
+    InGame,
+    /// Always possible
+    General,
+}
+
+impl ServerGeneral {
+    pub fn stream(&self) -> ServerGeneralStream {
+        match self {
+            //Character Screen related
+            ServerGeneral::CharacterDataLoadError(_)
+            | ServerGeneral::CharacterListUpdate(_)
+            | ServerGeneral::CharacterActionError(_)
+            | ServerGeneral::CharacterSuccess => ServerGeneralStream::CharacterScreen,
+            //Ingame related
+            ServerGeneral::GroupUpdate(_)
+            | ServerGeneral::GroupInvite { .. }
+            | ServerGeneral::InvitePending(_)
+            | ServerGeneral::InviteComplete { .. }
+            | ServerGeneral::ExitInGameSuccess
+            | ServerGeneral::InventoryUpdate(_, _)
+            | ServerGeneral::TerrainChunkUpdate { .. }
+            | ServerGeneral::TerrainBlockUpdates { .. }
+            | ServerGeneral::SetViewDistance(_)
+            | ServerGeneral::ViewDistanceThrottle { .. }
+            | ServerGeneral::ViewDistanceThrottleLifted
+            | ServerGeneral::Outcomes(_)
+            | ServerGeneral::Knockback(_)
+            | ServerGeneral::ObjectiveUpdate(_)
+            | ServerGeneral::ChunkNamed { .. }
+            | ServerGeneral::Dialogue { .. }
+            | ServerGeneral::LightFlash { .. }
+            | ServerGeneral::Hotbar(_)
+            | ServerGeneral::ChunkGenProgress { .. }
+            | ServerGeneral::MusicMood(_)
+            | ServerGeneral::ThreatTable { .. } => ServerGeneralStream::InGame,
+            // Always possible
+            ServerGeneral::PlayerListUpdate(_)
+            | ServerGeneral::ChatMsg(_)
+            | ServerGeneral::KillEvent { .. }
+            | ServerGeneral::SetPlayerEntity(_)
+            | ServerGeneral::TimeOfDay(_)
+            | ServerGeneral::EntitySync(_)
+            | ServerGeneral::CompSync(_)
+            | ServerGeneral::CreateEntity(_)
+            | ServerGeneral::DeleteEntity(_)
+            | ServerGeneral::Disconnect(_)
+            | ServerGeneral::Notification(_) => ServerGeneralStream::General,
+        }
+    }
+}
+
 impl From<AuthClientError> for RegisterError {
     fn from(err: AuthClientError) -> Self { Self::AuthError(err.to_string()) }
 }
@@ -261,3 +435,245 @@ impl Into<ServerMsg> for ServerGeneral {
 impl Into<ServerMsg> for PingMsg {
     fn into(self) -> ServerMsg { ServerMsg::Ping(self) }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn character_info_round_trip() {
+        let info = CharacterInfo {
+            name: "Zoltan".to_string(),
+            level: 12,
+            health: Some((640, 900)),
+            energy: Some((250, 1000)),
+        };
+        let ser = ron::ser::to_string(&info).unwrap();
+        let de: CharacterInfo = ron::de::from_str(&ser).unwrap();
+        assert_eq!(de.name, info.name);
+        assert_eq!(de.level, info.level);
+        assert_eq!(de.health, info.health);
+        assert_eq!(de.energy, info.energy);
+    }
+
+    #[test]
+    fn character_info_vitals_are_optional() {
+        let de: CharacterInfo = ron::de::from_str("(name: \"Zoltan\", level: 12)").unwrap();
+        assert_eq!(de.health, None);
+        assert_eq!(de.energy, None);
+    }
+
+    #[test]
+    fn objective_update_round_trip() {
+        let update = ObjectiveUpdate::set(vec![
+            ("Red".to_string(), "3".to_string()),
+            ("Blue".to_string(), "5".to_string()),
+            ("Time left".to_string(), "2:30".to_string()),
+        ]);
+        let ser = ron::ser::to_string(&update).unwrap();
+        let de: ObjectiveUpdate = ron::de::from_str(&ser).unwrap();
+        // Order must survive the trip since the client displays entries as sent
+        assert_eq!(de, update);
+
+        let ser = ron::ser::to_string(&ObjectiveUpdate::Clear).unwrap();
+        let de: ObjectiveUpdate = ron::de::from_str(&ser).unwrap();
+        assert_eq!(de, ObjectiveUpdate::Clear);
+    }
+
+    #[test]
+    fn objective_update_is_bounded() {
+        let entries = (0..MAX_OBJECTIVE_ENTRIES + 5).map(|i| (i.to_string(), String::new()));
+        match ObjectiveUpdate::set(entries) {
+            ObjectiveUpdate::Set(entries) => {
+                assert_eq!(entries.len(), MAX_OBJECTIVE_ENTRIES);
+                assert_eq!(entries[0].0, "0");
+            },
+            ObjectiveUpdate::Clear => panic!("Expected a Set update"),
+        }
+    }
+
+    #[test]
+    fn kill_cause_round_trip() {
+        for cause in [
+            KillCause::Melee,
+            KillCause::Ranged,
+            KillCause::Fall,
+            KillCause::Trap,
+            KillCause::Environment,
+            KillCause::Suicide,
+        ]
+        .iter()
+        {
+            for killer in [Some(Uid(3)), None].iter() {
+                let msg = ServerGeneral::KillEvent {
+                    killer: *killer,
+                    victim: Uid(7),
+                    cause: *cause,
+                };
+                let ser = ron::ser::to_string(&msg).unwrap();
+                match ron::de::from_str(&ser).unwrap() {
+                    ServerGeneral::KillEvent {
+                        killer: de_killer,
+                        victim,
+                        cause: de_cause,
+                    } => {
+                        assert_eq!(de_killer, *killer);
+                        assert_eq!(victim, Uid(7));
+                        assert_eq!(de_cause, *cause);
+                    },
+                    _ => panic!("Expected a KillEvent"),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn environmental_deaths_have_no_killer() {
+        assert_eq!(
+            KillCause::from_health_source(&comp::HealthSource::World),
+            (None, KillCause::Fall)
+        );
+        assert_eq!(
+            KillCause::from_health_source(&comp::HealthSource::Trap),
+            (None, KillCause::Trap)
+        );
+        assert_eq!(
+            KillCause::from_health_source(&comp::HealthSource::Projectile {
+                owner: Some(Uid(2))
+            }),
+            (Some(Uid(2)), KillCause::Ranged)
+        );
+    }
+
+    #[test]
+    fn view_distance_throttle_round_trip() {
+        for reason in [ThrottleReason::ServerLoad, ThrottleReason::ClientLag].iter() {
+            let msg = ServerGeneral::ViewDistanceThrottle {
+                vd: 5,
+                reason: *reason,
+            };
+            let ser = ron::ser::to_string(&msg).unwrap();
+            match ron::de::from_str(&ser).unwrap() {
+                ServerGeneral::ViewDistanceThrottle {
+                    vd,
+                    reason: de_reason,
+                } => {
+                    assert_eq!(vd, 5);
+                    assert_eq!(de_reason, *reason);
+                },
+                _ => panic!("Expected a ViewDistanceThrottle"),
+            }
+        }
+
+        let ser = ron::ser::to_string(&ServerGeneral::ViewDistanceThrottleLifted).unwrap();
+        assert!(matches!(
+            ron::de::from_str(&ser).unwrap(),
+            ServerGeneral::ViewDistanceThrottleLifted
+        ));
+    }
+
+    #[test]
+    fn dialogue_round_trip() {
+        let msg = ServerGeneral::Dialogue {
+            speaker: Uid(12),
+            lines: vec![
+                "Well met, traveller.".to_string(),
+                "Seen any wolves?".to_string(),
+            ],
+            responses: vec!["Yes".to_string(), "No".to_string()],
+        };
+        let ser = ron::ser::to_string(&msg).unwrap();
+        match ron::de::from_str(&ser).unwrap() {
+            ServerGeneral::Dialogue {
+                speaker,
+                lines,
+                responses,
+            } => {
+                assert_eq!(speaker, Uid(12));
+                assert_eq!(lines, vec!["Well met, traveller.", "Seen any wolves?"]);
+                assert_eq!(responses, vec!["Yes", "No"]);
+            },
+            _ => panic!("Expected a Dialogue"),
+        }
+    }
+
+    #[test]
+    fn light_flash_round_trip() {
+        let msg = ServerGeneral::LightFlash {
+            pos: Vec3::new(10.0, -4.5, 120.0),
+            color: Rgb::new(0.8, 0.9, 1.0),
+            strength: 50.0,
+            duration: Duration::from_millis(250),
+        };
+        let ser = ron::ser::to_string(&msg).unwrap();
+        match ron::de::from_str(&ser).unwrap() {
+            ServerGeneral::LightFlash {
+                pos,
+                color,
+                strength,
+                duration,
+            } => {
+                assert_eq!(pos, Vec3::new(10.0, -4.5, 120.0));
+                assert_eq!(color, Rgb::new(0.8, 0.9, 1.0));
+                assert!((strength - 50.0).abs() < f32::EPSILON);
+                assert_eq!(duration, Duration::from_millis(250));
+            },
+            _ => panic!("Expected a LightFlash"),
+        }
+    }
+
+    #[test]
+    fn chunk_gen_progress_round_trip() {
+        let msg = ServerGeneral::ChunkGenProgress {
+            requested: 120,
+            ready: 45,
+        };
+        let ser = ron::ser::to_string(&msg).unwrap();
+        match ron::de::from_str(&ser).unwrap() {
+            ServerGeneral::ChunkGenProgress { requested, ready } => {
+                assert_eq!(requested, 120);
+                assert_eq!(ready, 45);
+            },
+            _ => panic!("Expected ChunkGenProgress"),
+        }
+    }
+
+    #[test]
+    fn music_mood_round_trip() {
+        let ser = ron::ser::to_string(&ServerGeneral::MusicMood(0.75)).unwrap();
+        match ron::de::from_str(&ser).unwrap() {
+            ServerGeneral::MusicMood(mood) => assert!((mood - 0.75).abs() < f32::EPSILON),
+            _ => panic!("Expected MusicMood"),
+        }
+    }
+
+    #[test]
+    fn threat_table_round_trip() {
+        let msg = ServerGeneral::ThreatTable {
+            entity: Uid(7),
+            top: vec![(Uid(3), 120.5), (Uid(9), 40.0)],
+        };
+        let ser = ron::ser::to_string(&msg).unwrap();
+        match ron::de::from_str(&ser).unwrap() {
+            ServerGeneral::ThreatTable { entity, top } => {
+                assert_eq!(entity, Uid(7));
+                assert_eq!(top, vec![(Uid(3), 120.5), (Uid(9), 40.0)]);
+            },
+            _ => panic!("Expected ThreatTable"),
+        }
+    }
+
+    #[test]
+    fn gameplay_settings_round_trip() {
+        // The network sends messages with bincode
+        let settings = GameplaySettings {
+            gravity: 30.0,
+            ..GameplaySettings::default()
+        };
+        let ser = bincode::serialize(&settings).unwrap();
+        assert_eq!(
+            bincode::deserialize::<GameplaySettings>(&ser).unwrap(),
+            settings
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use crate::terrain::MapSizeLg;
 use serde::{Deserialize, Serialize};
 use vek::*;
 
@@ -30,7 +31,11 @@ pub struct WorldMapMsg {
     pub max_height: f32,
     /// RGB+A; the alpha channel is currently unused, but will be used in the
     /// future. Entries are in the usual chunk order.
-    pub rgba: Vec<u32>,
+    ///
+    /// NOTE: This is stored in the encoded form produced by
+    /// [WorldMapMsg::encode_rgba]; use [WorldMapMsg::decode_rgba] to recover
+    /// the per-chunk colors.
+    pub rgba: Vec<u8>,
     /// Altitudes: bits 2 to 0 are unused, then bits 15 to 3 are used for
     /// altitude. The remainder are currently unused, but we have plans to
     /// use 7 bits for water depth (using an integer f7 encoding), and we
@@ -121,3 +126,124 @@ pub struct WorldMapMsg {
     /// (256 possible angles).
     pub horizons: [(Vec<u8>, Vec<u8>); 2],
 }
+
+/// Tag byte for a color buffer stored as raw little-endian `u32`s.
+const RGBA_RAW: u8 = 0;
+/// Tag byte for a color buffer stored as a sequence of `(run length, color)`
+/// pairs, each a little-endian `u32`.
+const RGBA_RLE: u8 = 1;
+
+impl WorldMapMsg {
+    /// Encode a world map color buffer for sending over the network.
+    ///
+    /// Large maps are dominated by long runs of identical colors (oceans,
+    /// deserts, and so on), so we run-length encode the buffer and fall back
+    /// to the raw format if that would not actually save any space.  The first
+    /// byte of the output is a tag identifying which format was used.
+    pub fn encode_rgba(map_size_lg: MapSizeLg, rgba: &[u32]) -> Vec<u8> {
+        debug_assert_eq!(rgba.len(), map_size_lg.chunks_len());
+
+        let mut rle = vec![RGBA_RLE];
+        let mut colors = rgba.iter().copied().peekable();
+        while let Some(color) = colors.next() {
+            let mut run = 1u32;
+            while colors.peek() == Some(&color) {
+                colors.next();
+                run += 1;
+            }
+            rle.extend_from_slice(&run.to_le_bytes());
+            rle.extend_from_slice(&color.to_le_bytes());
+        }
+
+        if rle.len() < 1 + rgba.len() * 4 {
+            rle
+        } else {
+            let mut raw = Vec::with_capacity(1 + rgba.len() * 4);
+            raw.push(RGBA_RAW);
+            rgba.iter()
+                .for_each(|color| raw.extend_from_slice(&color.to_le_bytes()));
+            raw
+        }
+    }
+
+    /// Decode a color buffer produced by [WorldMapMsg::encode_rgba], returning
+    /// an error if it is malformed or does not contain exactly one color per
+    /// chunk of a map with the given dimensions.
+    pub fn decode_rgba(map_size_lg: MapSizeLg, bytes: &[u8]) -> Result<Vec<u32>, ()> {
+        let expected_len = map_size_lg.chunks_len();
+        let read_u32 = |chunk: &[u8]| u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+
+        let rgba = match bytes.split_first() {
+            Some((&RGBA_RAW, body)) if body.len() % 4 == 0 => {
+                body.chunks_exact(4).map(read_u32).collect::<Vec<_>>()
+            },
+            Some((&RGBA_RLE, body)) if body.len() % 8 == 0 => {
+                let mut rgba = Vec::with_capacity(expected_len);
+                for pair in body.chunks_exact(8) {
+                    let run = read_u32(&pair[0..4]) as usize;
+                    if run > expected_len - rgba.len() {
+                        return Err(());
+                    }
+                    rgba.extend(std::iter::repeat(read_u32(&pair[4..8])).take(run));
+                }
+                rgba
+            },
+            _ => return Err(()),
+        };
+
+        if rgba.len() == expected_len {
+            Ok(rgba)
+        } else {
+            Err(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_map() -> (MapSizeLg, Vec<u32>) {
+        let map_size_lg = MapSizeLg::new(Vec2::new(6, 6)).unwrap();
+        let rgba = (0..map_size_lg.chunks_len())
+            .map(|i| {
+                let (x, y) = (i % 64, i / 64);
+                if x < 20 || y > 50 {
+                    // Ocean
+                    u32::from_le_bytes([0, 32, 64, 0])
+                } else {
+                    // Land, varying in patches
+                    u32::from_le_bytes([(x / 8 * 30) as u8, (y / 8 * 30) as u8, 16, 0])
+                }
+            })
+            .collect();
+        (map_size_lg, rgba)
+    }
+
+    #[test]
+    fn rgba_round_trip() {
+        let (map_size_lg, rgba) = test_map();
+        let encoded = WorldMapMsg::encode_rgba(map_size_lg, &rgba);
+        assert_eq!(encoded[0], RGBA_RLE);
+        assert!(encoded.len() < rgba.len() * 4);
+        assert_eq!(WorldMapMsg::decode_rgba(map_size_lg, &encoded), Ok(rgba));
+    }
+
+    #[test]
+    fn rgba_raw_fallback() {
+        let map_size_lg = MapSizeLg::new(Vec2::new(2, 2)).unwrap();
+        let rgba = (0..map_size_lg.chunks_len() as u32).collect::<Vec<_>>();
+        let encoded = WorldMapMsg::encode_rgba(map_size_lg, &rgba);
+        assert_eq!(encoded[0], RGBA_RAW);
+        assert_eq!(WorldMapMsg::decode_rgba(map_size_lg, &encoded), Ok(rgba));
+    }
+
+    #[test]
+    fn rgba_wrong_size() {
+        let (map_size_lg, rgba) = test_map();
+        let encoded = WorldMapMsg::encode_rgba(map_size_lg, &rgba);
+        let smaller = MapSizeLg::new(Vec2::new(5, 5)).unwrap();
+        assert_eq!(WorldMapMsg::decode_rgba(smaller, &encoded), Err(()));
+        assert_eq!(WorldMapMsg::decode_rgba(map_size_lg, &[]), Err(()));
+    }
+}
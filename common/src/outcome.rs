@@ -1,4 +1,4 @@
-use crate::comp;
+use crate::{comp, terrain::TerrainChunkSize, vol::RectVolSize};
 use comp::item::Reagent;
 use serde::{Deserialize, Serialize};
 use vek::*;
@@ -22,6 +22,13 @@ pub enum Outcome {
         body: comp::Body,
         vel: Vec3<f32>,
     },
+    TrapTriggered {
+        pos: Vec3<f32>,
+    },
+    ProjectileDespawned {
+        pos: Vec3<f32>,
+        reason: comp::DespawnReason,
+    },
 }
 
 impl Outcome {
@@ -29,6 +36,44 @@ impl Outcome {
         match self {
             Outcome::Explosion { pos, .. } => Some(*pos),
             Outcome::ProjectileShot { pos, .. } => Some(*pos),
+            Outcome::TrapTriggered { pos } => Some(*pos),
+            Outcome::ProjectileDespawned { pos, .. } => Some(*pos),
+        }
+    }
+
+    /// Whether this outcome is relevant to a client at `pos` that can see
+    /// `view_distance` chunks away. Outcomes without a position always are.
+    pub fn is_visible_from(&self, pos: Vec3<f32>, view_distance: u32) -> bool {
+        self.get_pos().map_or(true, |o_pos| {
+            pos.xy().distance_squared(o_pos.xy())
+                < (view_distance as f32 * TerrainChunkSize::RECT_SIZE.x as f32).powi(2)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn explosion(pos: Vec3<f32>) -> Outcome {
+        Outcome::Explosion {
+            pos,
+            power: 1.0,
+            radius: 5.0,
+            is_attack: true,
+            reagent: None,
         }
     }
+
+    #[test]
+    fn culled_by_view_distance() {
+        let client_pos = Vec3::new(100.0, 100.0, 50.0);
+        let view_distance = 4;
+        let chunk_size = TerrainChunkSize::RECT_SIZE.x as f32;
+
+        let near = explosion(client_pos + Vec3::new(3.0 * chunk_size, 0.0, 500.0));
+        let far = explosion(client_pos + Vec3::new(0.0, 5.0 * chunk_size, 0.0));
+        assert!(near.is_visible_from(client_pos, view_distance));
+        assert!(!far.is_visible_from(client_pos, view_distance));
+    }
 }
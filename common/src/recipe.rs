@@ -46,7 +46,7 @@ impl Recipe {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct RecipeBook {
     recipes: HashMap<String, Recipe>,
 }
@@ -1,12 +1,14 @@
 use crate::{
     comp,
     event::{EventBus, LocalEvent, ServerEvent},
+    gameplay::GameplaySettings,
     metrics::SysMetrics,
     region::RegionMap,
-    sync::WorldSyncExt,
+    sync::{Uid, WorldSyncExt},
     sys,
-    terrain::{Block, TerrainChunk, TerrainGrid},
+    terrain::{Block, BlockEdit, TerrainChunk, TerrainGrid},
     time::DayPeriod,
+    util::SpatialGrid,
     vol::{ReadVol, WriteVol},
 };
 use hashbrown::{HashMap, HashSet};
@@ -47,10 +49,42 @@ const HUMANOID_JUMP_ACCEL: f32 = 16.0;
 #[derive(Default)]
 pub struct BlockChange {
     blocks: HashMap<Vec3<i32>, Block>,
+    /// The client that asked for each change, and the sequence number it gave
+    /// the request
+    origins: HashMap<Vec3<i32>, (Uid, u64)>,
+    /// The block that must still be in place for a change to be applied
+    expected: HashMap<Vec3<i32>, Block>,
 }
 
 impl BlockChange {
-    pub fn set(&mut self, pos: Vec3<i32>, block: Block) { self.blocks.insert(pos, block); }
+    pub fn set(&mut self, pos: Vec3<i32>, block: Block) {
+        self.blocks.insert(pos, block);
+        self.origins.remove(&pos);
+        self.expected.remove(&pos);
+    }
+
+    /// Like [`BlockChange::set`], but remembers which client asked for the
+    /// change so that it can be told once the change has been applied.
+    pub fn set_from(&mut self, pos: Vec3<i32>, block: Block, by: Uid, seq: u64) {
+        self.blocks.insert(pos, block);
+        self.origins.insert(pos, (by, seq));
+        self.expected.remove(&pos);
+    }
+
+    /// Like [`BlockChange::set_from`], but the change is only applied if the
+    /// block at `pos` is still `expected` by then, so that two clients editing
+    /// the same block don't overwrite each other.
+    pub fn compare_and_set_from(
+        &mut self,
+        pos: Vec3<i32>,
+        expected: Block,
+        block: Block,
+        by: Uid,
+        seq: u64,
+    ) {
+        self.set_from(pos, block, by, seq);
+        self.expected.insert(pos, expected);
+    }
 
     pub fn try_set(&mut self, pos: Vec3<i32>, block: Block) -> Option<()> {
         if !self.blocks.contains_key(&pos) {
@@ -61,7 +95,39 @@ impl BlockChange {
         }
     }
 
-    pub fn clear(&mut self) { self.blocks.clear(); }
+    /// Like [`BlockChange::try_set`], but remembers which client asked for the
+    /// change.
+    pub fn try_set_from(&mut self, pos: Vec3<i32>, block: Block, by: Uid, seq: u64) -> Option<()> {
+        self.try_set(pos, block)?;
+        self.origins.insert(pos, (by, seq));
+        Some(())
+    }
+
+    pub fn clear(&mut self) {
+        self.blocks.clear();
+        self.origins.clear();
+        self.expected.clear();
+    }
+
+    /// Takes all pending changes, keeping only those for which `apply`
+    /// succeeds. `apply` is also given the block the change expects to
+    /// replace, if any. Returns the applied changes along with the client
+    /// requests they confirm.
+    pub fn drain(
+        &mut self,
+        mut apply: impl FnMut(Vec3<i32>, Block, Option<Block>) -> bool,
+    ) -> (HashMap<Vec3<i32>, Block>, Vec<(Uid, u64)>) {
+        let mut blocks = std::mem::take(&mut self.blocks);
+        let expected = std::mem::take(&mut self.expected);
+        blocks.retain(|pos, block| apply(*pos, *block, expected.get(pos).copied()));
+        let confirmed = self
+            .origins
+            .drain()
+            .filter(|(pos, _)| blocks.contains_key(pos))
+            .map(|(_, origin)| origin)
+            .collect();
+        (blocks, confirmed)
+    }
 }
 
 #[derive(Default)]
@@ -70,6 +136,8 @@ pub struct TerrainChanges {
     pub modified_chunks: HashSet<Vec2<i32>>,
     pub removed_chunks: HashSet<Vec2<i32>>,
     pub modified_blocks: HashMap<Vec3<i32>, Block>,
+    /// Client block edit requests that were applied this tick
+    pub confirmed_edits: Vec<(Uid, u64)>,
 }
 
 impl TerrainChanges {
@@ -161,6 +229,12 @@ impl State {
         ecs.register::<comp::Waypoint>();
         ecs.register::<comp::Projectile>();
         ecs.register::<comp::Attacking>();
+        ecs.register::<comp::DamageOverTimeEffects>();
+        ecs.register::<comp::ThreatTable>();
+        ecs.register::<comp::AbilityCooldowns>();
+        ecs.register::<comp::Stunned>();
+        ecs.register::<comp::LastKnockback>();
+        ecs.register::<comp::LastHitBy>();
         ecs.register::<comp::ItemDrop>();
         ecs.register::<comp::ChatMode>();
         ecs.register::<comp::Faction>();
@@ -174,6 +248,9 @@ impl State {
         // Register unsynced resources used by the ECS.
         ecs.insert(Time(0.0));
         ecs.insert(DeltaTime(0.0));
+        ecs.insert(comp::EnergyRegen::default());
+        ecs.insert(comp::KnockbackImmunity::default());
+        ecs.insert(GameplaySettings::default());
         ecs.insert(TerrainGrid::new().unwrap());
         ecs.insert(BlockChange::default());
         ecs.insert(TerrainChanges::default());
@@ -182,6 +259,7 @@ impl State {
         ecs.insert(EventBus::<ServerEvent>::default());
         ecs.insert(comp::group::GroupManager::default());
         ecs.insert(RegionMap::new());
+        ecs.insert(SpatialGrid::default());
         ecs.insert(SysMetrics::default());
 
         ecs
@@ -266,6 +344,34 @@ impl State {
         self.ecs.write_resource::<BlockChange>().set(pos, block);
     }
 
+    /// Queue a batch of block edits, returning the edits needed to undo them.
+    ///
+    /// Like `set_block`, the edits take effect (and are sent to clients) when
+    /// terrain changes are next applied. Edits to positions that aren't
+    /// currently loaded are skipped.
+    pub fn apply_block_edits(
+        &mut self,
+        edits: impl IntoIterator<Item = BlockEdit>,
+    ) -> Vec<BlockEdit> {
+        let terrain = self.ecs.read_resource::<TerrainGrid>();
+        let mut block_change = self.ecs.write_resource::<BlockChange>();
+        let mut inverse = edits
+            .into_iter()
+            .filter_map(|edit| {
+                // Account for edits that are already pending this tick
+                let old = block_change
+                    .blocks
+                    .get(&edit.pos)
+                    .copied()
+                    .or_else(|| terrain.get(edit.pos).ok().copied())?;
+                block_change.set(edit.pos, edit.new);
+                Some(BlockEdit::new(edit.pos, old))
+            })
+            .collect::<Vec<_>>();
+        inverse.reverse();
+        inverse
+    }
+
     /// Check if the block at given position `pos` has already been modified
     /// this tick.
     pub fn can_set_block(&mut self, pos: Vec3<i32>) -> bool {
@@ -285,6 +391,21 @@ impl State {
         });
     }
 
+    /// Set or clear the custom name of the chunk at `key`. Returns `false` if
+    /// the chunk isn't loaded.
+    pub fn set_chunk_name(&mut self, key: Vec2<i32>, name: Option<String>) -> bool {
+        let mut terrain = self.ecs.write_resource::<TerrainGrid>();
+        match terrain.get_key(key) {
+            Some(chunk) => {
+                let mut chunk = chunk.clone();
+                chunk.meta_mut().set_custom_name(name);
+                terrain.insert(key, Arc::new(chunk));
+                true
+            },
+            None => false,
+        }
+    }
+
     /// Insert the provided chunk into this state's terrain.
     pub fn insert_chunk(&mut self, key: Vec2<i32>, chunk: TerrainChunk) {
         if self
@@ -333,12 +454,24 @@ impl State {
     // Apply terrain changes
     pub fn apply_terrain_changes(&self) {
         let mut terrain = self.ecs.write_resource::<TerrainGrid>();
-        let mut modified_blocks =
-            std::mem::take(&mut self.ecs.write_resource::<BlockChange>().blocks);
         // Apply block modifications
         // Only include in `TerrainChanges` if successful
-        modified_blocks.retain(|pos, block| terrain.set(*pos, *block).is_ok());
-        self.ecs.write_resource::<TerrainChanges>().modified_blocks = modified_blocks;
+        let (modified_blocks, confirmed_edits) =
+            self.ecs
+                .write_resource::<BlockChange>()
+                .drain(|pos, block, expected| {
+                    let set = match expected {
+                        Some(expected) => terrain.compare_and_set(pos, expected, block).is_ok(),
+                        None => terrain.set(pos, block).is_ok(),
+                    };
+                    if set {
+                        terrain.reset_block_health(pos);
+                    }
+                    set
+                });
+        let mut terrain_changes = self.ecs.write_resource::<TerrainChanges>();
+        terrain_changes.modified_blocks = modified_blocks;
+        terrain_changes.confirmed_edits = confirmed_edits;
     }
 
     /// Execute a single tick, simulating the game state by the given duration.
@@ -426,3 +559,81 @@ impl State {
         self.ecs.write_resource::<TerrainChanges>().clear();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        comp::DamageKind,
+        terrain::{BlockKind, SpriteKind, TerrainChunkMeta},
+    };
+
+    #[test]
+    fn block_change_echoes_sequence_number() {
+        let mut block_change = BlockChange::default();
+        let pos = Vec3::new(4, 5, 6);
+        let block = Block::air(SpriteKind::Empty);
+        block_change.set_from(pos, block, Uid(3), 42);
+
+        let (blocks, confirmed) = block_change.drain(|_, _, _| true);
+        assert_eq!(blocks.get(&pos), Some(&block));
+        assert_eq!(confirmed, vec![(Uid(3), 42)]);
+    }
+
+    #[test]
+    fn failed_or_overridden_changes_are_not_confirmed() {
+        let mut block_change = BlockChange::default();
+        let block = Block::new(BlockKind::Rock, Rgb::zero());
+        block_change.set_from(Vec3::zero(), block, Uid(3), 1);
+        block_change.set_from(Vec3::unit_z(), block, Uid(3), 2);
+        block_change.set(Vec3::unit_z(), Block::air(SpriteKind::Empty));
+
+        let (blocks, confirmed) = block_change.drain(|pos, _, _| pos != Vec3::zero());
+        assert_eq!(blocks.len(), 1);
+        assert!(confirmed.is_empty());
+    }
+
+    #[test]
+    fn block_change_passes_on_expected_block() {
+        let mut block_change = BlockChange::default();
+        let rock = Block::new(BlockKind::Rock, Rgb::zero());
+        let air = Block::air(SpriteKind::Empty);
+        block_change.compare_and_set_from(Vec3::zero(), rock, air, Uid(3), 1);
+        block_change.set(Vec3::unit_z(), air);
+
+        let mut seen = Vec::new();
+        block_change.drain(|pos, _, expected| {
+            seen.push((pos, expected));
+            true
+        });
+        seen.sort_by_key(|(pos, _)| pos.z);
+        assert_eq!(seen, vec![
+            (Vec3::zero(), Some(rock)),
+            (Vec3::unit_z(), None)
+        ]);
+    }
+
+    #[test]
+    fn replaced_blocks_start_undamaged() {
+        let mut state = State::default();
+        let mut chunk = TerrainChunk::new(
+            0,
+            Block::new(BlockKind::Rock, Rgb::zero()),
+            Block::air(SpriteKind::Empty),
+            TerrainChunkMeta::void(),
+        );
+        let pos = Vec3::new(3, 4, -1);
+        assert!(!chunk.damage_block(pos, 100, DamageKind::Physical));
+        state.terrain_mut().insert(Vec2::zero(), Arc::new(chunk));
+
+        // A block of the same kind placed where the damaged one was
+        state.set_block(pos, Block::new(BlockKind::Rock, Rgb::new(10, 10, 10)));
+        state.apply_terrain_changes();
+
+        let terrain = state.terrain();
+        assert_eq!(
+            terrain.get_key(Vec2::zero()).unwrap().block_health(pos),
+            BlockKind::Rock.max_health()
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use crate::{
-    comp::{beam, humanoid, Body, CharacterState, EnergySource, Ori, Pos, StateUpdate},
+    comp::{beam, humanoid, Body, CharacterState, DamageKind, EnergySource, Ori, Pos, StateUpdate},
     event::ServerEvent,
     states::utils::*,
     sync::Uid,
@@ -39,6 +39,8 @@ pub struct StaticData {
     pub energy_drain: u32,
     /// What key is used to press ability
     pub ability_key: AbilityKey,
+    /// The kind of damage dealt
+    pub damage_kind: DamageKind,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -70,38 +72,38 @@ impl CharacterBehavior for Data {
 
         match self.stage_section {
             StageSection::Buildup => {
-                if self.timer < self.static_data.buildup_duration {
-                    // Build up
-                    update.character = CharacterState::BasicBeam(Data {
-                        static_data: self.static_data,
-                        timer: self
-                            .timer
-                            .checked_add(Duration::from_secs_f32(data.dt.0))
-                            .unwrap_or_default(),
-                        stage_section: self.stage_section,
-                        particle_ori: Some(*data.inputs.look_dir),
-                        offset: self.offset,
-                    });
-                } else {
-                    // Creates beam
-                    data.updater.insert(data.entity, beam::Beam {
-                        hit_entities: Vec::<Uid>::new(),
-                        tick_dur: Duration::from_secs_f32(1.0 / self.static_data.tick_rate),
-                        timer: Duration::default(),
-                    });
-                    // Gets offset
-                    let eye_height = match data.body {
-                        Body::Humanoid(body) => body.eye_height(),
-                        _ => humanoid::DEFAULT_HUMANOID_EYE_HEIGHT,
-                    };
-                    // Build up
-                    update.character = CharacterState::BasicBeam(Data {
-                        static_data: self.static_data,
-                        timer: Duration::default(),
-                        stage_section: StageSection::Cast,
-                        particle_ori: Some(*data.inputs.look_dir),
-                        offset: eye_height * 0.55,
-                    });
+                match advance_section(self.timer, data.dt.0, self.static_data.buildup_duration) {
+                    SectionProgress::Running(timer) => {
+                        // Build up
+                        update.character = CharacterState::BasicBeam(Data {
+                            static_data: self.static_data,
+                            timer,
+                            stage_section: self.stage_section,
+                            particle_ori: Some(*data.inputs.look_dir),
+                            offset: self.offset,
+                        });
+                    },
+                    SectionProgress::Finished(overshoot) => {
+                        // Creates beam
+                        data.updater.insert(data.entity, beam::Beam {
+                            hit_entities: Vec::<Uid>::new(),
+                            tick_dur: Duration::from_secs_f32(1.0 / self.static_data.tick_rate),
+                            timer: Duration::default(),
+                        });
+                        // Gets offset
+                        let eye_height = match data.body {
+                            Body::Humanoid(body) => body.eye_height(),
+                            _ => humanoid::DEFAULT_HUMANOID_EYE_HEIGHT,
+                        };
+                        // Build up
+                        update.character = CharacterState::BasicBeam(Data {
+                            static_data: self.static_data,
+                            timer: overshoot,
+                            stage_section: StageSection::Cast,
+                            particle_ori: Some(*data.inputs.look_dir),
+                            offset: eye_height * 0.55,
+                        });
+                    },
                 }
             },
             StageSection::Cast => {
@@ -128,6 +130,7 @@ impl CharacterBehavior for Data {
                         energy_cost,
                         duration: self.static_data.beam_duration,
                         owner: Some(*data.uid),
+                        damage_kind: self.static_data.damage_kind,
                     };
                     let pos = Pos(data.pos.0 + Vec3::new(0.0, 0.0, self.offset));
                     // Create beam segment
@@ -163,22 +166,22 @@ impl CharacterBehavior for Data {
                 }
             },
             StageSection::Recover => {
-                if self.timer < self.static_data.recover_duration {
-                    update.character = CharacterState::BasicBeam(Data {
-                        static_data: self.static_data,
-                        timer: self
-                            .timer
-                            .checked_add(Duration::from_secs_f32(data.dt.0))
-                            .unwrap_or_default(),
-                        stage_section: self.stage_section,
-                        particle_ori: Some(*data.inputs.look_dir),
-                        offset: self.offset,
-                    });
-                } else {
-                    // Done
-                    update.character = CharacterState::Wielding;
-                    // Make sure attack component is removed
-                    data.updater.remove::<beam::Beam>(data.entity);
+                match advance_section(self.timer, data.dt.0, self.static_data.recover_duration) {
+                    SectionProgress::Running(timer) => {
+                        update.character = CharacterState::BasicBeam(Data {
+                            static_data: self.static_data,
+                            timer,
+                            stage_section: self.stage_section,
+                            particle_ori: Some(*data.inputs.look_dir),
+                            offset: self.offset,
+                        });
+                    },
+                    SectionProgress::Finished(_) => {
+                        // Done
+                        update.character = CharacterState::Wielding;
+                        // Make sure attack component is removed
+                        data.updater.remove::<beam::Beam>(data.entity);
+                    },
                 }
             },
             _ => {
@@ -1,15 +1,35 @@
 use super::utils::*;
 use crate::{
-    comp::StateUpdate,
+    comp::{BuffKind, CharacterState, StateUpdate},
     sys::character_behavior::{CharacterBehavior, JoinData},
 };
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 // const BLOCK_ACCEL: f32 = 30.0;
 // const BLOCK_SPEED: f32 = 75.0;
 
-#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize, Eq, Hash)]
-pub struct Data;
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Data {
+    /// Buff granted to the blocker (kind, strength, max duration) for as long
+    /// as they block
+    pub self_buff: Option<(BuffKind, f32, Duration)>,
+    /// Fraction of incoming damage the block stops
+    pub block_strength: f32,
+    /// How long after raising the block projectiles are reflected rather
+    /// than blocked
+    pub reflect_window: Option<Duration>,
+    /// How long the block has been held
+    pub timer: Duration,
+}
+
+impl Data {
+    /// Whether a projectile arriving now would be reflected
+    pub fn can_reflect(&self) -> bool {
+        self.reflect_window
+            .map_or(false, |window| self.timer < window)
+    }
+}
 
 impl CharacterBehavior for Data {
     fn behavior(&self, data: &JoinData) -> StateUpdate {
@@ -17,6 +37,14 @@ impl CharacterBehavior for Data {
 
         handle_move(&data, &mut update, 0.4);
 
+        update.character = CharacterState::BasicBlock(Data {
+            timer: self
+                .timer
+                .checked_add(Duration::from_secs_f32(data.dt.0))
+                .unwrap_or_default(),
+            ..*self
+        });
+
         if !data.physics.on_ground
             || !(data.inputs.secondary.is_pressed() || data.inputs.primary.is_pressed())
         {
@@ -25,3 +53,34 @@ impl CharacterBehavior for Data {
         update
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reflects_only_within_window() {
+        let block = Data {
+            self_buff: None,
+            block_strength: 0.9,
+            reflect_window: Some(Duration::from_millis(200)),
+            timer: Duration::default(),
+        };
+        assert!(block.can_reflect());
+        assert!(Data {
+            timer: Duration::from_millis(150),
+            ..block
+        }
+        .can_reflect());
+        assert!(!Data {
+            timer: Duration::from_millis(200),
+            ..block
+        }
+        .can_reflect());
+        assert!(!Data {
+            reflect_window: None,
+            ..block
+        }
+        .can_reflect());
+    }
+}
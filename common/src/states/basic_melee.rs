@@ -1,5 +1,7 @@
 use crate::{
-    comp::{Attacking, CharacterState, EnergySource, StateUpdate},
+    comp::{
+        Attacking, CharacterState, DamageKind, DamageOverTime, EnergySource, Knockback, StateUpdate,
+    },
     states::utils::*,
     sys::character_behavior::{CharacterBehavior, JoinData},
 };
@@ -15,13 +17,19 @@ pub struct Data {
     /// Base damage (negative) or healing (positive)
     pub base_healthchange: i32,
     /// Knockback
-    pub knockback: f32,
+    pub knockback: Knockback,
     /// Max range
     pub range: f32,
     /// Max angle (45.0 will give you a 90.0 angle window)
     pub max_angle: f32,
-    /// Whether the attack can deal more damage
-    pub exhausted: bool,
+    /// Timer for each stage
+    pub timer: Duration,
+    /// What section the character stage is in
+    pub stage_section: StageSection,
+    /// Damage dealt over time to whoever is hit
+    pub dot: Option<DamageOverTime>,
+    /// The kind of damage dealt
+    pub damage_kind: DamageKind,
 }
 
 impl CharacterBehavior for Data {
@@ -31,65 +39,63 @@ impl CharacterBehavior for Data {
         handle_move(data, &mut update, 0.7);
         handle_jump(data, &mut update);
 
-        if self.buildup_duration != Duration::default() {
-            // Build up
-            update.character = CharacterState::BasicMelee(Data {
-                buildup_duration: self
-                    .buildup_duration
-                    .checked_sub(Duration::from_secs_f32(data.dt.0))
-                    .unwrap_or_default(),
-                recover_duration: self.recover_duration,
-                base_healthchange: self.base_healthchange,
-                knockback: self.knockback,
-                range: self.range,
-                max_angle: self.max_angle,
-                exhausted: false,
-            });
-        } else if !self.exhausted {
-            let (damage, heal) = if self.base_healthchange > 0 {
-                (0, self.base_healthchange as u32)
-            } else {
-                ((-self.base_healthchange) as u32, 0)
-            };
-            // Hit attempt
-            data.updater.insert(data.entity, Attacking {
-                base_damage: damage,
-                base_heal: heal,
-                range: self.range,
-                max_angle: self.max_angle.to_radians(),
-                applied: false,
-                hit_count: 0,
-                knockback: self.knockback,
-            });
+        match self.stage_section {
+            StageSection::Buildup => {
+                match advance_section(self.timer, data.dt.0, self.buildup_duration) {
+                    SectionProgress::Running(timer) => {
+                        // Build up
+                        update.character = CharacterState::BasicMelee(Data { timer, ..*self });
+                    },
+                    SectionProgress::Finished(overshoot) => {
+                        let (damage, heal) = if self.base_healthchange > 0 {
+                            (0, self.base_healthchange as u32)
+                        } else {
+                            ((-self.base_healthchange) as u32, 0)
+                        };
+                        // Hit attempt
+                        data.updater.insert(data.entity, Attacking {
+                            base_damage: damage,
+                            base_heal: heal,
+                            damage_kind: self.damage_kind,
+                            range: self.range,
+                            max_angle: self.max_angle.to_radians(),
+                            applied: false,
+                            hit_count: 0,
+                            knockback: self.knockback,
+                            vertical_range: None,
+                            max_targets: None,
+                            dot: self.dot,
+                        });
 
-            update.character = CharacterState::BasicMelee(Data {
-                buildup_duration: self.buildup_duration,
-                recover_duration: self.recover_duration,
-                base_healthchange: self.base_healthchange,
-                knockback: self.knockback,
-                range: self.range,
-                max_angle: self.max_angle,
-                exhausted: true,
-            });
-        } else if self.recover_duration != Duration::default() {
-            // Recovery
-            update.character = CharacterState::BasicMelee(Data {
-                buildup_duration: self.buildup_duration,
-                recover_duration: self
-                    .recover_duration
-                    .checked_sub(Duration::from_secs_f32(data.dt.0))
-                    .unwrap_or_default(),
-                base_healthchange: self.base_healthchange,
-                knockback: self.knockback,
-                range: self.range,
-                max_angle: self.max_angle,
-                exhausted: true,
-            });
-        } else {
-            // Done
-            update.character = CharacterState::Wielding;
-            // Make sure attack component is removed
-            data.updater.remove::<Attacking>(data.entity);
+                        // Transitions to recover section
+                        update.character = CharacterState::BasicMelee(Data {
+                            timer: overshoot,
+                            stage_section: StageSection::Recover,
+                            ..*self
+                        });
+                    },
+                }
+            },
+            StageSection::Recover => {
+                match advance_section(self.timer, data.dt.0, self.recover_duration) {
+                    SectionProgress::Running(timer) => {
+                        // Recovery
+                        update.character = CharacterState::BasicMelee(Data { timer, ..*self });
+                    },
+                    SectionProgress::Finished(_) => {
+                        // Done
+                        update.character = CharacterState::Wielding;
+                        // Make sure attack component is removed
+                        data.updater.remove::<Attacking>(data.entity);
+                    },
+                }
+            },
+            _ => {
+                // If it somehow ends up in an incorrect stage section
+                update.character = CharacterState::Wielding;
+                // Make sure attack component is removed
+                data.updater.remove::<Attacking>(data.entity);
+            },
         }
 
         // Grant energy on successful hit
@@ -103,3 +109,93 @@ impl CharacterBehavior for Data {
         update
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        comp::{humanoid, Body, Controller, Energy, Loadout, Ori, PhysicsState, Pos, Stats, Vel},
+        gameplay::GameplaySettings,
+        state::{DeltaTime, Time},
+        sync::Uid,
+        terrain::TerrainGrid,
+    };
+    use specs::{Builder, LazyUpdate, World, WorldExt};
+    use vek::Vec3;
+
+    fn strike() -> Data {
+        Data {
+            buildup_duration: Duration::from_millis(350),
+            recover_duration: Duration::from_millis(250),
+            base_healthchange: -50,
+            knockback: Knockback::default(),
+            range: 3.5,
+            max_angle: 20.0,
+            timer: Duration::default(),
+            stage_section: StageSection::Buildup,
+            dot: None,
+            damage_kind: DamageKind::Physical,
+        }
+    }
+
+    /// Runs a tick of `dt` seconds of `state`
+    fn tick(state: &Data, dt: f32) -> StateUpdate {
+        let mut world = World::new();
+        let entity = world.create_entity().build();
+        let body = Body::Humanoid(humanoid::Body::random());
+        let controller = Controller::default();
+        let data = JoinData {
+            entity,
+            uid: &Uid(1),
+            character: &CharacterState::BasicMelee(*state),
+            pos: &Pos(Vec3::zero()),
+            vel: &Vel(Vec3::zero()),
+            ori: &Ori::default(),
+            dt: &DeltaTime(dt),
+            time: &Time(0.0),
+            controller: &controller,
+            inputs: &controller.inputs,
+            stats: &Stats::new("Test".to_string(), body),
+            energy: &Energy::new(1000),
+            loadout: &Loadout::default(),
+            body: &body,
+            physics: &PhysicsState::default(),
+            attacking: None,
+            cooldowns: None,
+            stunned: None,
+            buffs: None,
+            updater: &world.read_resource::<LazyUpdate>(),
+            terrain: &TerrainGrid::new().unwrap(),
+            tracked_positions: &hashbrown::HashMap::new(),
+            gameplay: &GameplaySettings::default(),
+        };
+        state.behavior(&data)
+    }
+
+    #[test]
+    fn strike_lands_on_time_with_varied_ticks() {
+        let tick_patterns: [&[f32]; 3] =
+            [&[0.1], &[1.0 / 30.0], &[0.016, 0.05, 0.033, 0.09, 0.004]];
+        for dts in tick_patterns.iter() {
+            let mut state = strike();
+            let mut elapsed = 0.0;
+            for &dt in dts.iter().cycle() {
+                elapsed += dt;
+                state = match tick(&state, dt).character {
+                    CharacterState::BasicMelee(next) => next,
+                    other => panic!("Left the strike early as {:?}", other),
+                };
+                if state.stage_section == StageSection::Recover {
+                    break;
+                }
+            }
+            // Recovery starts exactly when the buildup runs out, with the rest of the tick
+            // already counted towards it
+            assert!(
+                (elapsed - state.timer.as_secs_f32() - strike().buildup_duration.as_secs_f32())
+                    .abs()
+                    < 1e-4
+            );
+        }
+    }
+}
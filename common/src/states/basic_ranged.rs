@@ -1,5 +1,7 @@
 use crate::{
-    comp::{Body, CharacterState, Gravity, LightEmitter, Projectile, StateUpdate},
+    comp::{
+        Body, CharacterState, Gravity, Homing, HomingParams, LightEmitter, Projectile, StateUpdate,
+    },
     event::ServerEvent,
     states::utils::*,
     sys::character_behavior::{CharacterBehavior, JoinData},
@@ -22,6 +24,16 @@ pub struct Data {
     pub projectile_light: Option<LightEmitter>,
     pub projectile_gravity: Option<Gravity>,
     pub projectile_speed: f32,
+    /// If set, fired projectiles track nearby targets
+    pub homing: Option<HomingParams>,
+    /// How many entities fired projectiles pass through before stopping
+    pub pierce: u32,
+    /// How many times fired projectiles bounce off terrain before stopping
+    pub bounces: u32,
+    /// Fraction of its speed a projectile keeps on each bounce
+    pub bounce_damping: f32,
+    /// How long fired projectiles last before despawning
+    pub projectile_lifetime: Duration,
     /// Whether the attack fired already
     pub exhausted: bool,
     /// What key is used to press ability
@@ -54,6 +66,11 @@ impl CharacterBehavior for Data {
                 projectile_light: self.projectile_light,
                 projectile_gravity: self.projectile_gravity,
                 projectile_speed: self.projectile_speed,
+                homing: self.homing,
+                pierce: self.pierce,
+                bounces: self.bounces,
+                bounce_damping: self.bounce_damping,
+                projectile_lifetime: self.projectile_lifetime,
                 exhausted: false,
                 ability_key: self.ability_key,
             });
@@ -61,6 +78,11 @@ impl CharacterBehavior for Data {
             // Fire
             let mut projectile = self.projectile.clone();
             projectile.owner = Some(*data.uid);
+            projectile.homing = self.homing.map(Homing::from);
+            projectile.pierce = self.pierce;
+            projectile.bounces = self.bounces;
+            projectile.bounce_damping = self.bounce_damping;
+            projectile.time_left = self.projectile_lifetime;
             update.server_events.push_front(ServerEvent::Shoot {
                 entity: data.entity,
                 dir: data.inputs.look_dir,
@@ -81,6 +103,11 @@ impl CharacterBehavior for Data {
                 projectile_light: self.projectile_light,
                 projectile_gravity: self.projectile_gravity,
                 projectile_speed: self.projectile_speed,
+                homing: self.homing,
+                pierce: self.pierce,
+                bounces: self.bounces,
+                bounce_damping: self.bounce_damping,
+                projectile_lifetime: self.projectile_lifetime,
                 exhausted: true,
                 ability_key: self.ability_key,
             });
@@ -99,6 +126,11 @@ impl CharacterBehavior for Data {
                 projectile_light: self.projectile_light,
                 projectile_gravity: self.projectile_gravity,
                 projectile_speed: self.projectile_speed,
+                homing: self.homing,
+                pierce: self.pierce,
+                bounces: self.bounces,
+                bounce_damping: self.bounce_damping,
+                projectile_lifetime: self.projectile_lifetime,
                 exhausted: true,
                 ability_key: self.ability_key,
             });
@@ -1,5 +1,5 @@
 use crate::{
-    comp::{Attacking, CharacterState, EnergySource, StateUpdate},
+    comp::{Attacking, CharacterState, DamageKind, EnergySource, Knockback, StateUpdate},
     states::utils::{StageSection, *},
     sys::character_behavior::*,
 };
@@ -18,9 +18,9 @@ pub struct StaticData {
     /// How much damage is dealt with max charge
     pub max_damage: u32,
     /// How much knockback there is with no charge
-    pub initial_knockback: f32,
+    pub initial_knockback: Knockback,
     /// How much knockback there is at max charge
-    pub max_knockback: f32,
+    pub max_knockback: Knockback,
     /// Max range
     pub range: f32,
     /// Max angle (45.0 will give you a 90.0 angle window)
@@ -31,6 +31,8 @@ pub struct StaticData {
     pub swing_duration: Duration,
     /// How long the state has until exiting
     pub recover_duration: Duration,
+    /// The kind of damage dealt
+    pub damage_kind: DamageKind,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -118,19 +120,24 @@ impl CharacterBehavior for Data {
                     let damage = self.static_data.initial_damage
                         + ((self.static_data.max_damage - self.static_data.initial_damage) as f32
                             * self.charge_amount) as u32;
-                    let knockback = self.static_data.initial_knockback
-                        + (self.static_data.max_knockback - self.static_data.initial_knockback)
-                            * self.charge_amount;
+                    let knockback = self
+                        .static_data
+                        .initial_knockback
+                        .lerp(self.static_data.max_knockback, self.charge_amount);
 
                     // Hit attempt
                     data.updater.insert(data.entity, Attacking {
                         base_damage: damage as u32,
                         base_heal: 0,
+                        damage_kind: self.static_data.damage_kind,
                         range: self.static_data.range,
                         max_angle: self.static_data.max_angle.to_radians(),
                         applied: false,
                         hit_count: 0,
                         knockback,
+                        vertical_range: None,
+                        max_targets: None,
+                        dot: None,
                     });
 
                     // Starts swinging
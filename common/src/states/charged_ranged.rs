@@ -1,7 +1,7 @@
 use crate::{
     comp::{
-        projectile, Body, CharacterState, EnergySource, Gravity, LightEmitter, Projectile,
-        StateUpdate,
+        projectile, Body, CharacterState, DamageKind, EnergySource, Gravity, Knockback,
+        LightEmitter, Projectile, StateUpdate,
     },
     event::ServerEvent,
     states::utils::*,
@@ -21,9 +21,9 @@ pub struct Data {
     /// How much damage is dealt with max charge
     pub max_damage: u32,
     /// How much knockback there is with no charge
-    pub initial_knockback: f32,
+    pub initial_knockback: Knockback,
     /// How much knockback there is at max charge
-    pub max_knockback: f32,
+    pub max_knockback: Knockback,
     /// How long the weapon needs to be prepared for
     pub prepare_duration: Duration,
     /// How long it takes to charge the weapon to max damage and knockback
@@ -38,6 +38,32 @@ pub struct Data {
     pub projectile_gravity: Option<Gravity>,
     pub initial_projectile_speed: f32,
     pub max_projectile_speed: f32,
+    /// How many entities the projectile passes through before stopping
+    pub pierce: u32,
+    /// How many times fired projectiles bounce off terrain before stopping
+    pub bounces: u32,
+    /// Fraction of its speed a projectile keeps on each bounce
+    pub bounce_damping: f32,
+    /// Fractions of the charge duration between which releasing the attack
+    /// multiplies its damage and knockback by the third value
+    pub sweet_spot: Option<(f32, f32, f32)>,
+    /// How long the fired projectile lasts before despawning
+    pub projectile_lifetime: Duration,
+    /// The kind of damage dealt
+    pub damage_kind: DamageKind,
+    /// What damage is multiplied by when the projectile hits a weakpoint
+    pub weakpoint_multiplier: f32,
+}
+
+impl Data {
+    /// The bonus multiplier for releasing at `charge_amount`, or 1.0 outside
+    /// the sweet spot
+    pub fn sweet_spot_multiplier(&self, charge_amount: f32) -> f32 {
+        match self.sweet_spot {
+            Some((start, end, bonus)) if (start..=end).contains(&charge_amount) => bonus,
+            _ => 1.0,
+        }
+    }
 }
 
 impl CharacterBehavior for Data {
@@ -68,6 +94,13 @@ impl CharacterBehavior for Data {
                 projectile_gravity: self.projectile_gravity,
                 initial_projectile_speed: self.initial_projectile_speed,
                 max_projectile_speed: self.max_projectile_speed,
+                pierce: self.pierce,
+                bounces: self.bounces,
+                bounce_damping: self.bounce_damping,
+                sweet_spot: self.sweet_spot,
+                projectile_lifetime: self.projectile_lifetime,
+                damage_kind: self.damage_kind,
+                weakpoint_multiplier: self.weakpoint_multiplier,
             });
         } else if data.inputs.secondary.is_pressed()
             && self.charge_timer < self.charge_duration
@@ -93,6 +126,13 @@ impl CharacterBehavior for Data {
                 projectile_gravity: self.projectile_gravity,
                 initial_projectile_speed: self.initial_projectile_speed,
                 max_projectile_speed: self.max_projectile_speed,
+                pierce: self.pierce,
+                bounces: self.bounces,
+                bounce_damping: self.bounce_damping,
+                sweet_spot: self.sweet_spot,
+                projectile_lifetime: self.projectile_lifetime,
+                damage_kind: self.damage_kind,
+                weakpoint_multiplier: self.weakpoint_multiplier,
             });
 
             // Consumes energy if there's enough left and RMB is held down
@@ -118,6 +158,13 @@ impl CharacterBehavior for Data {
                 projectile_gravity: self.projectile_gravity,
                 initial_projectile_speed: self.initial_projectile_speed,
                 max_projectile_speed: self.max_projectile_speed,
+                pierce: self.pierce,
+                bounces: self.bounces,
+                bounce_damping: self.bounce_damping,
+                sweet_spot: self.sweet_spot,
+                projectile_lifetime: self.projectile_lifetime,
+                damage_kind: self.damage_kind,
+                weakpoint_multiplier: self.weakpoint_multiplier,
             });
 
             // Consumes energy if there's enough left and RMB is held down
@@ -128,24 +175,33 @@ impl CharacterBehavior for Data {
         } else if !self.exhausted {
             let charge_amount =
                 (self.charge_timer.as_secs_f32() / self.charge_duration.as_secs_f32()).min(1.0);
+            let multiplier = self.sweet_spot_multiplier(charge_amount);
             // Fire
             let mut projectile = Projectile {
                 hit_solid: vec![projectile::Effect::Stick],
                 hit_entity: vec![
                     projectile::Effect::Damage(
-                        -(self.initial_damage as i32
-                            + (charge_amount * (self.max_damage - self.initial_damage) as f32)
-                                as i32),
+                        -((self.initial_damage as f32
+                            + charge_amount * (self.max_damage - self.initial_damage) as f32)
+                            * multiplier) as i32,
                     ),
                     projectile::Effect::Knockback(
                         self.initial_knockback
-                            + charge_amount * (self.max_knockback - self.initial_knockback),
+                            .lerp(self.max_knockback, charge_amount)
+                            .scaled(multiplier),
                     ),
                     projectile::Effect::Vanish,
                 ],
-                time_left: Duration::from_secs(15),
+                time_left: self.projectile_lifetime,
                 owner: None,
                 ignore_group: true,
+                homing: None,
+                pierce: self.pierce,
+                bounces: self.bounces,
+                bounce_damping: self.bounce_damping,
+                hit_entities: Vec::new(),
+                damage_kind: self.damage_kind,
+                weakpoint_multiplier: self.weakpoint_multiplier,
             };
             projectile.owner = Some(*data.uid);
             update.server_events.push_front(ServerEvent::Shoot {
@@ -175,6 +231,13 @@ impl CharacterBehavior for Data {
                 projectile_gravity: self.projectile_gravity,
                 initial_projectile_speed: self.initial_projectile_speed,
                 max_projectile_speed: self.max_projectile_speed,
+                pierce: self.pierce,
+                bounces: self.bounces,
+                bounce_damping: self.bounce_damping,
+                sweet_spot: self.sweet_spot,
+                projectile_lifetime: self.projectile_lifetime,
+                damage_kind: self.damage_kind,
+                weakpoint_multiplier: self.weakpoint_multiplier,
             });
         } else if self.recover_duration != Duration::default() {
             // Recovery
@@ -197,6 +260,13 @@ impl CharacterBehavior for Data {
                 projectile_gravity: self.projectile_gravity,
                 initial_projectile_speed: self.initial_projectile_speed,
                 max_projectile_speed: self.max_projectile_speed,
+                pierce: self.pierce,
+                bounces: self.bounces,
+                bounce_damping: self.bounce_damping,
+                sweet_spot: self.sweet_spot,
+                projectile_lifetime: self.projectile_lifetime,
+                damage_kind: self.damage_kind,
+                weakpoint_multiplier: self.weakpoint_multiplier,
             });
         } else {
             // Done
@@ -206,3 +276,57 @@ impl CharacterBehavior for Data {
         update
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::comp::object;
+
+    fn bow(sweet_spot: Option<(f32, f32, f32)>) -> Data {
+        Data {
+            exhausted: false,
+            energy_drain: 300,
+            initial_damage: 40,
+            max_damage: 200,
+            initial_knockback: Knockback::launch(10.0),
+            max_knockback: Knockback::launch(20.0),
+            prepare_duration: Duration::default(),
+            charge_duration: Duration::from_millis(1500),
+            charge_timer: Duration::default(),
+            recover_duration: Duration::from_millis(500),
+            projectile_body: Body::Object(object::Body::Arrow),
+            projectile_light: None,
+            projectile_gravity: None,
+            initial_projectile_speed: 100.0,
+            max_projectile_speed: 500.0,
+            pierce: 0,
+            bounces: 0,
+            bounce_damping: 0.0,
+            sweet_spot,
+            projectile_lifetime: projectile::DEFAULT_LIFETIME,
+            damage_kind: DamageKind::Physical,
+            weakpoint_multiplier: 2.0,
+        }
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn releasing_in_sweet_spot_multiplies() {
+        let bow = bow(Some((0.7, 0.8, 1.5)));
+        assert_eq!(bow.sweet_spot_multiplier(0.7), 1.5);
+        assert_eq!(bow.sweet_spot_multiplier(0.75), 1.5);
+        assert_eq!(bow.sweet_spot_multiplier(0.8), 1.5);
+        // Just outside the window the charge is linear again
+        assert_eq!(bow.sweet_spot_multiplier(0.69), 1.0);
+        assert_eq!(bow.sweet_spot_multiplier(0.81), 1.0);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn no_sweet_spot_is_linear() {
+        let bow = bow(None);
+        for i in 0..=10 {
+            assert_eq!(bow.sweet_spot_multiplier(i as f32 / 10.0), 1.0);
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use crate::{
-    comp::{CharacterState, Climb, EnergySource, StateUpdate},
+    comp::{CharacterState, Climb, EnergySource, PhysicsState, StateUpdate},
     event::LocalEvent,
     sys::{
         character_behavior::{CharacterBehavior, JoinData},
@@ -23,15 +23,18 @@ impl CharacterBehavior for Data {
     fn behavior(&self, data: &JoinData) -> StateUpdate {
         let mut update = StateUpdate::from(data);
 
-        // If no wall is in front of character or we stopped climbing;
-        let (wall_dir, climb) = if let (Some(wall_dir), Some(climb), false) = (
-            data.physics.on_wall,
-            data.inputs.climb,
-            data.physics.on_ground,
-        ) {
+        // If no climbable wall is in front of character or we stopped climbing;
+        let (wall_dir, climb) = if let (Some(wall_dir), Some(climb)) =
+            (climbable_wall(data.physics), data.inputs.climb)
+        {
             (wall_dir, climb)
         } else {
-            if data.inputs.jump.is_pressed() {
+            // Climbing past the top of the wall pulls them up onto it. They were
+            // against a climbable wall last tick, or they wouldn't be climbing.
+            let over_ledge = data.inputs.climb == Some(Climb::Up)
+                && data.physics.on_wall.is_none()
+                && !data.physics.on_ground;
+            if data.inputs.jump.is_pressed() || over_ledge {
                 // They've climbed atop something, give them a boost
                 update
                     .local_events
@@ -40,7 +43,6 @@ impl CharacterBehavior for Data {
             update.character = CharacterState::Idle {};
             return update;
         };
-
         // Move player
         update.vel.0 += Vec2::broadcast(data.dt.0)
             * data.inputs.move_dir
@@ -50,16 +52,10 @@ impl CharacterBehavior for Data {
                 0.0
             };
 
-        // Expend energy if climbing
-        let energy_use = match climb {
-            Climb::Up => 5,
-            Climb::Down => 1,
-            Climb::Hold => 1,
-        };
-
+        // Expend energy if climbing, letting go once there's none left
         if update
             .energy
-            .try_change_by(-energy_use, EnergySource::Climb)
+            .try_change_by(-climb_energy_cost(climb), EnergySource::Climb)
             .is_err()
         {
             update.character = CharacterState::Idle {};
@@ -101,3 +97,137 @@ impl CharacterBehavior for Data {
         update
     }
 }
+
+/// The direction of the wall an entity could hold on to, if it's up against a
+/// climbable one and not standing on the ground
+pub fn climbable_wall(physics: &PhysicsState) -> Option<Vec3<f32>> {
+    physics
+        .on_wall
+        .filter(|_| physics.on_climbable && !physics.on_ground)
+}
+
+/// Energy spent each tick on climbing
+fn climb_energy_cost(climb: Climb) -> i32 {
+    match climb {
+        Climb::Up => 5,
+        Climb::Down => 1,
+        Climb::Hold => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        comp::{humanoid, Body, Controller, Energy, Loadout, Ori, Pos, Stats, Vel},
+        gameplay::GameplaySettings,
+        state::{DeltaTime, Time},
+        sync::Uid,
+        terrain::TerrainGrid,
+    };
+    use specs::{Builder, LazyUpdate, World, WorldExt};
+
+    fn against_rock() -> PhysicsState {
+        PhysicsState {
+            on_wall: Some(Vec3::unit_x()),
+            on_climbable: true,
+            ..Default::default()
+        }
+    }
+
+    /// Runs a tick of climbing for a humanoid climbing up
+    fn climb_up(energy: &Energy, physics: &PhysicsState) -> StateUpdate {
+        let mut world = World::new();
+        let entity = world.create_entity().build();
+        let body = Body::Humanoid(humanoid::Body::random());
+        let mut controller = Controller::default();
+        controller.inputs.climb = Some(Climb::Up);
+        let data = JoinData {
+            entity,
+            uid: &Uid(1),
+            character: &CharacterState::Climb,
+            pos: &Pos(Vec3::zero()),
+            vel: &Vel(Vec3::zero()),
+            ori: &Ori::default(),
+            dt: &DeltaTime(0.05),
+            time: &Time(0.0),
+            controller: &controller,
+            inputs: &controller.inputs,
+            stats: &Stats::new("Test".to_string(), body),
+            energy,
+            loadout: &Loadout::default(),
+            body: &body,
+            physics,
+            attacking: None,
+            cooldowns: None,
+            stunned: None,
+            buffs: None,
+            updater: &world.read_resource::<LazyUpdate>(),
+            terrain: &TerrainGrid::new().unwrap(),
+            tracked_positions: &hashbrown::HashMap::new(),
+            gameplay: &GameplaySettings::default(),
+        };
+        Data.behavior(&data)
+    }
+
+    #[test]
+    fn climbing_needs_climbable_wall() {
+        let against_wall = PhysicsState {
+            on_wall: Some(Vec3::unit_x()),
+            ..Default::default()
+        };
+        assert_eq!(climbable_wall(&against_wall), None);
+
+        let against_rock = PhysicsState {
+            on_climbable: true,
+            ..against_wall.clone()
+        };
+        assert_eq!(climbable_wall(&against_rock), Some(Vec3::unit_x()));
+
+        // Standing at the foot of the wall isn't climbing it
+        let at_foot = PhysicsState {
+            on_ground: true,
+            ..against_rock
+        };
+        assert_eq!(climbable_wall(&at_foot), None);
+    }
+
+    #[test]
+    fn climbing_drains_energy() {
+        let mut energy = Energy::new(12);
+        let mut state = CharacterState::Climb;
+        let mut ticks = 0;
+        while state == CharacterState::Climb {
+            let update = climb_up(&energy, &against_rock());
+            energy = update.energy;
+            state = update.character;
+            ticks += 1;
+        }
+        // Two ticks of climbing up use up the energy, so the third drops the
+        // climber
+        assert_eq!(ticks, 3);
+        assert_eq!(energy.current(), 2);
+        assert_eq!(state, CharacterState::Idle);
+        assert!(climb_energy_cost(Climb::Hold) < climb_energy_cost(Climb::Up));
+    }
+
+    #[test]
+    fn climbing_over_ledge_pulls_up() {
+        let in_air = PhysicsState::default();
+        let over_ledge = climb_up(&Energy::new(100), &in_air);
+        assert_eq!(over_ledge.character, CharacterState::Idle);
+        assert!(matches!(
+            over_ledge.local_events.front(),
+            Some(LocalEvent::Jump(_))
+        ));
+
+        // Standing at the foot of the wall isn't climbing over it
+        let on_ground = PhysicsState {
+            on_ground: true,
+            ..against_rock()
+        };
+        let at_foot = climb_up(&Energy::new(100), &on_ground);
+        assert_eq!(at_foot.character, CharacterState::Idle);
+        assert!(at_foot.local_events.is_empty());
+    }
+}
@@ -1,11 +1,31 @@
 use crate::{
-    comp::{Attacking, CharacterState, EnergySource, StateUpdate},
+    comp::{Attacking, CharacterState, DamageKind, EnergySource, Knockback, StateUpdate},
     states::utils::*,
     sys::character_behavior::{CharacterBehavior, JoinData},
 };
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
+/// Inputs that can continue a combo into its next stage
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ComboInput {
+    Primary,
+    Secondary,
+}
+
+impl ComboInput {
+    /// The combo input currently pressed, preferring the primary input
+    pub fn pressed(data: &JoinData) -> Option<Self> {
+        if data.inputs.primary.is_pressed() {
+            Some(ComboInput::Primary)
+        } else if data.inputs.secondary.is_pressed() {
+            Some(ComboInput::Secondary)
+        } else {
+            None
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Stage {
     /// Specifies which stage the combo attack is in
@@ -17,7 +37,7 @@ pub struct Stage {
     /// Damage scaling per combo
     pub damage_increase: u32,
     /// Knockback of stage
-    pub knockback: f32,
+    pub knockback: Knockback,
     /// Range of attack
     pub range: f32,
     /// Angle of attack
@@ -31,6 +51,33 @@ pub struct Stage {
     pub base_recover_duration: Duration,
     /// How much forward movement there is in the swing portion of the stage
     pub forward_movement: f32,
+    /// Stages to continue into instead of the next one, by the input pressed
+    /// during recovery
+    #[serde(default)]
+    pub branches: Vec<(ComboInput, u32)>,
+    /// Energy spent when the combo advances into this stage
+    #[serde(default)]
+    pub energy_cost: u32,
+}
+
+impl Stage {
+    /// The stage that pressing `input` during recovery continues into, if any.
+    /// Without a branch for the input, the primary input continues linearly
+    /// to the next stage (wrapping around) and any other input ends the combo.
+    pub fn next_stage(&self, input: ComboInput, num_stages: u32) -> Option<u32> {
+        self.branches
+            .iter()
+            .find(|(branch_input, _)| *branch_input == input)
+            .map(|(_, stage)| *stage)
+            .filter(|stage| (1..=num_stages).contains(stage))
+            .or_else(|| match input {
+                ComboInput::Primary => Some((self.stage % num_stages) + 1),
+                _ => None,
+            })
+    }
+
+    /// Whether `energy` is enough to advance into this stage
+    pub fn affordable(&self, energy: u32) -> bool { energy >= self.energy_cost }
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -53,6 +100,11 @@ pub struct StaticData {
     pub max_speed_increase: f32,
     /// Whether the state can be interrupted by other abilities
     pub is_interruptible: bool,
+    /// How long before recovery starts a press is remembered for, so that
+    /// it still continues the combo once recovery begins
+    pub input_buffer: Duration,
+    /// The kind of damage dealt
+    pub damage_kind: DamageKind,
 }
 /// A sequence of attacks that can incrementally become faster and more
 /// damaging.
@@ -69,8 +121,54 @@ pub struct Data {
     pub timer: Duration,
     /// Checks what section a stage is in
     pub stage_section: StageSection,
-    /// Whether the state should go onto the next stage
-    pub next_stage: bool,
+    /// Which stage the state should go onto after recovering, if any
+    pub next_stage: Option<u32>,
+    /// The last input pressed before recovery, and how long ago it was pressed
+    pub buffered_input: Option<(ComboInput, Duration)>,
+}
+
+impl Data {
+    /// The buffered input after `dt` seconds, where `pressed` is the input
+    /// pressed now. Inputs older than the input buffer are forgotten.
+    pub fn buffer_input(
+        &self,
+        pressed: Option<ComboInput>,
+        dt: f32,
+    ) -> Option<(ComboInput, Duration)> {
+        pressed
+            .map(|input| (input, Duration::default()))
+            .or_else(|| {
+                self.buffered_input
+                    .map(|(input, age)| (input, age + Duration::from_secs_f32(dt)))
+            })
+            .filter(|(_, age)| *age <= self.static_data.input_buffer)
+    }
+
+    /// The stage the buffered input continues into, if any
+    pub fn buffered_next_stage(&self) -> Option<u32> {
+        let (input, _) = self.buffered_input?;
+        self.static_data.stage_data[(self.stage - 1) as usize]
+            .next_stage(input, self.static_data.num_stages)
+    }
+
+    /// How much of the window to continue the combo is left, from 1.0 down
+    /// to 0.0. The window is the recovery of the current stage, so it stays
+    /// full until recovery starts and then drains along with it.
+    pub fn combo_fraction(&self) -> f32 {
+        match self.stage_section {
+            StageSection::Recover => {
+                let window = self.static_data.stage_data[(self.stage - 1) as usize]
+                    .base_recover_duration
+                    .as_secs_f32();
+                if window > 0.0 {
+                    (1.0 - self.timer.as_secs_f32() / window).max(0.0)
+                } else {
+                    0.0
+                }
+            },
+            _ => 1.0,
+        }
+    }
 }
 
 impl CharacterBehavior for Data {
@@ -81,9 +179,12 @@ impl CharacterBehavior for Data {
         handle_move(data, &mut update, 0.3);
 
         let stage_index = (self.stage - 1) as usize;
+        let pressed_next_stage = ComboInput::pressed(data).and_then(|input| {
+            self.static_data.stage_data[stage_index].next_stage(input, self.static_data.num_stages)
+        });
 
         // Allows for other states to interrupt this state
-        if self.static_data.is_interruptible && !data.inputs.primary.is_pressed() {
+        if self.static_data.is_interruptible && pressed_next_stage.is_none() {
             handle_interrupt(data, &mut update);
             match update.character {
                 CharacterState::ComboMelee(_) => {},
@@ -93,35 +194,40 @@ impl CharacterBehavior for Data {
             }
         }
 
+        // Attacks speed up as the combo grows
+        let dt = (1.0
+            + self.static_data.max_speed_increase
+                * (1.0 - self.static_data.speed_increase.powi(self.combo as i32)))
+            * data.dt.0;
+
         match self.stage_section {
-            StageSection::Buildup => {
-                if self.timer < self.static_data.stage_data[stage_index].base_buildup_duration {
+            StageSection::Buildup => match advance_section(
+                self.timer,
+                dt,
+                self.static_data.stage_data[stage_index].base_buildup_duration,
+            ) {
+                SectionProgress::Running(timer) => {
                     // Build up
                     update.character = CharacterState::ComboMelee(Data {
                         static_data: self.static_data.clone(),
                         stage: self.stage,
                         combo: self.combo,
-                        timer: self
-                            .timer
-                            .checked_add(Duration::from_secs_f32(
-                                (1.0 + self.static_data.max_speed_increase
-                                    * (1.0
-                                        - self.static_data.speed_increase.powi(self.combo as i32)))
-                                    * data.dt.0,
-                            ))
-                            .unwrap_or_default(),
+                        timer,
                         stage_section: self.stage_section,
                         next_stage: self.next_stage,
+                        buffered_input: self.buffer_input(ComboInput::pressed(data), data.dt.0),
                     });
-                } else {
+                },
+                SectionProgress::Finished(overshoot) => {
                     // Transitions to swing section of stage
                     update.character = CharacterState::ComboMelee(Data {
                         static_data: self.static_data.clone(),
                         stage: self.stage,
                         combo: self.combo,
-                        timer: Duration::default(),
+                        timer: overshoot,
                         stage_section: StageSection::Swing,
                         next_stage: self.next_stage,
+                        buffered_input: self.buffer_input(ComboInput::pressed(data), data.dt.0),
                     });
 
                     // Hit attempt
@@ -132,16 +238,24 @@ impl CharacterBehavior for Data {
                                     * self.static_data.stage_data[stage_index].damage_increase,
                         ),
                         base_heal: 0,
+                        damage_kind: self.static_data.damage_kind,
                         range: self.static_data.stage_data[stage_index].range,
                         max_angle: self.static_data.stage_data[stage_index].angle.to_radians(),
                         applied: false,
                         hit_count: 0,
                         knockback: self.static_data.stage_data[stage_index].knockback,
+                        vertical_range: None,
+                        max_targets: None,
+                        dot: None,
                     });
-                }
+                },
             },
-            StageSection::Swing => {
-                if self.timer < self.static_data.stage_data[stage_index].base_swing_duration {
+            StageSection::Swing => match advance_section(
+                self.timer,
+                dt,
+                self.static_data.stage_data[stage_index].base_swing_duration,
+            ) {
+                SectionProgress::Running(timer) => {
                     // Forward movement
                     forward_move(
                         data,
@@ -155,91 +269,69 @@ impl CharacterBehavior for Data {
                         static_data: self.static_data.clone(),
                         stage: self.stage,
                         combo: self.combo,
-                        timer: self
-                            .timer
-                            .checked_add(Duration::from_secs_f32(
-                                (1.0 + self.static_data.max_speed_increase
-                                    * (1.0
-                                        - self.static_data.speed_increase.powi(self.combo as i32)))
-                                    * data.dt.0,
-                            ))
-                            .unwrap_or_default(),
+                        timer,
                         stage_section: self.stage_section,
                         next_stage: self.next_stage,
+                        buffered_input: self.buffer_input(ComboInput::pressed(data), data.dt.0),
                     });
-                } else {
+                },
+                SectionProgress::Finished(overshoot) => {
                     // Transitions to recover section of stage
                     update.character = CharacterState::ComboMelee(Data {
                         static_data: self.static_data.clone(),
                         stage: self.stage,
                         combo: self.combo,
-                        timer: Duration::default(),
+                        timer: overshoot,
                         stage_section: StageSection::Recover,
-                        next_stage: self.next_stage,
+                        next_stage: self.next_stage.or_else(|| self.buffered_next_stage()),
+                        buffered_input: None,
                     });
-                }
+                },
             },
-            StageSection::Recover => {
-                if self.timer < self.static_data.stage_data[stage_index].base_recover_duration {
-                    // Recovers
-                    if data.inputs.primary.is_pressed() {
-                        // Checks if state will transition to next stage after recover
+            StageSection::Recover => match advance_section(
+                self.timer,
+                dt,
+                self.static_data.stage_data[stage_index].base_recover_duration,
+            ) {
+                SectionProgress::Running(timer) => {
+                    // Recovers, checking if state will transition to another stage after
+                    update.character = CharacterState::ComboMelee(Data {
+                        static_data: self.static_data.clone(),
+                        stage: self.stage,
+                        combo: self.combo,
+                        timer,
+                        stage_section: self.stage_section,
+                        next_stage: pressed_next_stage.or(self.next_stage),
+                        buffered_input: None,
+                    });
+                },
+                SectionProgress::Finished(overshoot) => {
+                    if let Some(next_stage) = self.next_stage.filter(|next_stage| {
+                        self.static_data.stage_data[(next_stage - 1) as usize]
+                            .affordable(update.energy.current())
+                    }) {
+                        // Transitions to buildup section of next stage
                         update.character = CharacterState::ComboMelee(Data {
                             static_data: self.static_data.clone(),
-                            stage: self.stage,
+                            stage: next_stage,
                             combo: self.combo,
-                            timer: self
-                                .timer
-                                .checked_add(Duration::from_secs_f32(
-                                    (1.0 + self.static_data.max_speed_increase
-                                        * (1.0
-                                            - self
-                                                .static_data
-                                                .speed_increase
-                                                .powi(self.combo as i32)))
-                                        * data.dt.0,
-                                ))
-                                .unwrap_or_default(),
-                            stage_section: self.stage_section,
-                            next_stage: true,
+                            timer: overshoot,
+                            stage_section: StageSection::Buildup,
+                            next_stage: None,
+                            buffered_input: None,
                         });
+                        update.energy.change_by(
+                            -(self.static_data.stage_data[(next_stage - 1) as usize].energy_cost
+                                as i32),
+                            EnergySource::Ability,
+                        );
                     } else {
-                        update.character = CharacterState::ComboMelee(Data {
-                            static_data: self.static_data.clone(),
-                            stage: self.stage,
-                            combo: self.combo,
-                            timer: self
-                                .timer
-                                .checked_add(Duration::from_secs_f32(
-                                    (1.0 + self.static_data.max_speed_increase
-                                        * (1.0
-                                            - self
-                                                .static_data
-                                                .speed_increase
-                                                .powi(self.combo as i32)))
-                                        * data.dt.0,
-                                ))
-                                .unwrap_or_default(),
-                            stage_section: self.stage_section,
-                            next_stage: self.next_stage,
-                        });
+                        // Done, either by choice or because the next stage can't be afforded
+                        update.character = CharacterState::Wielding;
+                        // Make sure attack component is removed
+                        data.updater.remove::<Attacking>(data.entity);
                     }
-                } else if self.next_stage {
-                    // Transitions to buildup section of next stage
-                    update.character = CharacterState::ComboMelee(Data {
-                        static_data: self.static_data.clone(),
-                        stage: (self.stage % self.static_data.num_stages) + 1,
-                        combo: self.combo,
-                        timer: Duration::default(),
-                        stage_section: StageSection::Buildup,
-                        next_stage: false,
-                    });
-                } else {
-                    // Done
-                    update.character = CharacterState::Wielding;
-                    // Make sure attack component is removed
-                    data.updater.remove::<Attacking>(data.entity);
-                }
+                },
             },
             _ => {
                 // If it somehow ends up in an incorrect stage section
@@ -263,6 +355,7 @@ impl CharacterBehavior for Data {
                     timer: self.timer,
                     stage_section: self.stage_section,
                     next_stage: self.next_stage,
+                    buffered_input: self.buffered_input,
                 });
                 data.updater.remove::<Attacking>(data.entity);
                 update.energy.change_by(energy, EnergySource::HitEnemy);
@@ -272,3 +365,212 @@ impl CharacterBehavior for Data {
         update
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        comp::{humanoid, Body, Controller, Energy, Loadout, Ori, PhysicsState, Pos, Stats, Vel},
+        gameplay::GameplaySettings,
+        state::{DeltaTime, Time},
+        sync::Uid,
+        terrain::TerrainGrid,
+    };
+    use specs::{Builder, LazyUpdate, World, WorldExt};
+    use vek::Vec3;
+
+    fn stage(stage: u32, branches: Vec<(ComboInput, u32)>) -> Stage {
+        Stage {
+            stage,
+            base_damage: 100,
+            max_damage: 120,
+            damage_increase: 10,
+            knockback: Knockback::from_horizontal(10.0),
+            range: 4.0,
+            angle: 30.0,
+            base_buildup_duration: Duration::from_millis(350),
+            base_swing_duration: Duration::from_millis(100),
+            base_recover_duration: Duration::from_millis(400),
+            forward_movement: 0.5,
+            branches,
+            energy_cost: 0,
+        }
+    }
+
+    #[test]
+    fn alternate_input_routes_to_branch() {
+        let stage = stage(1, vec![(ComboInput::Secondary, 4)]);
+        assert_eq!(stage.next_stage(ComboInput::Secondary, 4), Some(4));
+        // The primary input still continues linearly
+        assert_eq!(stage.next_stage(ComboInput::Primary, 4), Some(2));
+    }
+
+    #[test]
+    fn linear_without_branches() {
+        let last = stage(3, Vec::new());
+        assert_eq!(last.next_stage(ComboInput::Primary, 3), Some(1));
+        assert_eq!(last.next_stage(ComboInput::Secondary, 3), None);
+    }
+
+    #[test]
+    fn invalid_branch_ignored() {
+        let stage = stage(1, vec![(ComboInput::Primary, 7)]);
+        assert_eq!(stage.next_stage(ComboInput::Primary, 3), Some(2));
+    }
+
+    #[test]
+    fn finisher_blocked_without_energy() {
+        let finisher = Stage {
+            energy_cost: 300,
+            ..stage(3, Vec::new())
+        };
+        assert!(!finisher.affordable(100));
+        assert!(finisher.affordable(300));
+        // Stages without a cost are always free, as before
+        assert!(stage(2, Vec::new()).affordable(0));
+    }
+
+    fn swing(buffered_input: Option<(ComboInput, Duration)>) -> Data {
+        Data {
+            static_data: StaticData {
+                num_stages: 3,
+                stage_data: (1..=3).map(|i| stage(i, Vec::new())).collect(),
+                initial_energy_gain: 0,
+                max_energy_gain: 100,
+                energy_increase: 20,
+                speed_increase: 0.95,
+                max_speed_increase: 0.8,
+                is_interruptible: true,
+                input_buffer: Duration::from_millis(150),
+                damage_kind: DamageKind::Physical,
+            },
+            stage: 1,
+            combo: 0,
+            timer: Duration::default(),
+            stage_section: StageSection::Swing,
+            next_stage: None,
+            buffered_input,
+        }
+    }
+
+    #[test]
+    fn buffered_press_advances_at_recovery() {
+        let pressed = swing(None).buffer_input(Some(ComboInput::Primary), 0.05);
+        assert_eq!(pressed, Some((ComboInput::Primary, Duration::default())));
+
+        // The button was released, but the press is still remembered when the
+        // swing ends and recovery starts
+        let data = swing(swing(pressed).buffer_input(None, 0.1));
+        assert_eq!(data.buffered_next_stage(), Some(2));
+    }
+
+    #[test]
+    fn stale_press_is_forgotten() {
+        let data = swing(Some((ComboInput::Primary, Duration::from_millis(100))));
+        assert_eq!(data.buffer_input(None, 0.1), None);
+        assert_eq!(swing(None).buffered_next_stage(), None);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn combo_fraction_drains_during_recovery() {
+        assert_eq!(swing(None).combo_fraction(), 1.0);
+
+        let recovering = |millis| Data {
+            timer: Duration::from_millis(millis),
+            stage_section: StageSection::Recover,
+            ..swing(None)
+        };
+        let start = recovering(0).combo_fraction();
+        let halfway = recovering(200).combo_fraction();
+        let end = recovering(400).combo_fraction();
+        assert_eq!(start, 1.0);
+        assert!((halfway - 0.5).abs() < 0.001);
+        assert_eq!(end, 0.0);
+        // Recovery can overshoot by a tick before the state moves on
+        assert_eq!(recovering(450).combo_fraction(), 0.0);
+    }
+
+    /// Runs a 50ms tick of `state`, with the primary input held if `pressed`
+    fn tick(state: &Data, pressed: bool) -> CharacterState {
+        let mut world = World::new();
+        let entity = world.create_entity().build();
+        let body = Body::Humanoid(humanoid::Body::random());
+        let mut controller = Controller::default();
+        controller.inputs.primary.set_state(pressed);
+        let data = JoinData {
+            entity,
+            uid: &Uid(1),
+            character: &CharacterState::ComboMelee(state.clone()),
+            pos: &Pos(Vec3::zero()),
+            vel: &Vel(Vec3::zero()),
+            ori: &Ori::default(),
+            dt: &DeltaTime(0.05),
+            time: &Time(0.0),
+            controller: &controller,
+            inputs: &controller.inputs,
+            stats: &Stats::new("Test".to_string(), body),
+            energy: &Energy::new(1000),
+            loadout: &Loadout::default(),
+            body: &body,
+            physics: &PhysicsState::default(),
+            attacking: None,
+            cooldowns: None,
+            stunned: None,
+            buffs: None,
+            updater: &world.read_resource::<LazyUpdate>(),
+            terrain: &TerrainGrid::new().unwrap(),
+            tracked_positions: &hashbrown::HashMap::new(),
+            gameplay: &GameplaySettings::default(),
+        };
+        state.behavior(&data).character
+    }
+
+    /// Ticks `state` with nothing pressed until it leaves recovery
+    fn recover(mut state: Data) -> CharacterState {
+        while state.stage_section == StageSection::Recover {
+            state = match tick(&state, false) {
+                CharacterState::ComboMelee(next) => next,
+                other => return other,
+            };
+        }
+        CharacterState::ComboMelee(state)
+    }
+
+    #[test]
+    fn press_during_swing_continues_combo() {
+        // Pressed halfway through the swing, then released before it ends
+        let state = match tick(&swing(None), true) {
+            CharacterState::ComboMelee(next) => next,
+            other => panic!("Left the combo early as {:?}", other),
+        };
+        let state = match tick(&state, false) {
+            CharacterState::ComboMelee(next) => next,
+            other => panic!("Left the combo early as {:?}", other),
+        };
+        assert_eq!(state.stage_section, StageSection::Recover);
+        assert_eq!(state.next_stage, Some(2));
+
+        match recover(state) {
+            CharacterState::ComboMelee(next) => {
+                assert_eq!(next.stage, 2);
+                assert_eq!(next.stage_section, StageSection::Buildup);
+            },
+            other => panic!("Expected the combo to continue, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn combo_ends_without_press() {
+        let state = match tick(&swing(None), false) {
+            CharacterState::ComboMelee(next) => next,
+            other => panic!("Left the combo early as {:?}", other),
+        };
+        let state = match tick(&state, false) {
+            CharacterState::ComboMelee(next) => next,
+            other => panic!("Left the combo early as {:?}", other),
+        };
+        assert_eq!(state.next_stage, None);
+        assert_eq!(recover(state), CharacterState::Wielding);
+    }
+}
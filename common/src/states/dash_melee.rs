@@ -1,5 +1,5 @@
 use crate::{
-    comp::{Attacking, CharacterState, EnergySource, StateUpdate},
+    comp::{Attacking, CharacterState, DamageKind, EnergySource, Knockback, StateUpdate},
     states::utils::*,
     sys::character_behavior::{CharacterBehavior, JoinData},
 };
@@ -15,9 +15,9 @@ pub struct StaticData {
     /// How much damage the attack does at max charge distance
     pub max_damage: u32,
     /// How much the attack knocks the target back initially
-    pub base_knockback: f32,
+    pub base_knockback: Knockback,
     /// How much knockback happens at max charge distance
-    pub max_knockback: f32,
+    pub max_knockback: Knockback,
     /// Range of the attack
     pub range: f32,
     /// Angle of the attack
@@ -38,6 +38,8 @@ pub struct StaticData {
     pub recover_duration: Duration,
     /// Whether the state can be interrupted by other abilities
     pub is_interruptible: bool,
+    /// The kind of damage dealt
+    pub damage_kind: DamageKind,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -75,27 +77,27 @@ impl CharacterBehavior for Data {
 
         match self.stage_section {
             StageSection::Buildup => {
-                if self.timer < self.static_data.buildup_duration {
-                    // Build up
-                    update.character = CharacterState::DashMelee(Data {
-                        static_data: self.static_data,
-                        end_charge: self.end_charge,
-                        timer: self
-                            .timer
-                            .checked_add(Duration::from_secs_f32(data.dt.0))
-                            .unwrap_or_default(),
-                        stage_section: self.stage_section,
-                        exhausted: self.exhausted,
-                    });
-                } else {
-                    // Transitions to charge section of stage
-                    update.character = CharacterState::DashMelee(Data {
-                        static_data: self.static_data,
-                        end_charge: self.end_charge,
-                        timer: Duration::default(),
-                        stage_section: StageSection::Charge,
-                        exhausted: self.exhausted,
-                    });
+                match advance_section(self.timer, data.dt.0, self.static_data.buildup_duration) {
+                    SectionProgress::Running(timer) => {
+                        // Build up
+                        update.character = CharacterState::DashMelee(Data {
+                            static_data: self.static_data,
+                            end_charge: self.end_charge,
+                            timer,
+                            stage_section: self.stage_section,
+                            exhausted: self.exhausted,
+                        });
+                    },
+                    SectionProgress::Finished(overshoot) => {
+                        // Transitions to charge section of stage
+                        update.character = CharacterState::DashMelee(Data {
+                            static_data: self.static_data,
+                            end_charge: self.end_charge,
+                            timer: overshoot,
+                            stage_section: StageSection::Charge,
+                            exhausted: self.exhausted,
+                        });
+                    },
                 }
             },
             StageSection::Charge => {
@@ -116,18 +118,22 @@ impl CharacterBehavior for Data {
                             - self.static_data.base_damage as f32)
                             * charge_frac
                             + self.static_data.base_damage as f32;
-                        let knockback = (self.static_data.max_knockback
-                            - self.static_data.base_knockback)
-                            * charge_frac
-                            + self.static_data.base_knockback;
+                        let knockback = self
+                            .static_data
+                            .base_knockback
+                            .lerp(self.static_data.max_knockback, charge_frac);
                         data.updater.insert(data.entity, Attacking {
                             base_damage: damage as u32,
                             base_heal: 0,
+                            damage_kind: self.static_data.damage_kind,
                             range: self.static_data.range,
                             max_angle: self.static_data.angle.to_radians(),
                             applied: false,
                             hit_count: 0,
                             knockback,
+                            vertical_range: None,
+                            max_targets: None,
+                            dot: None,
                         });
                     }
 
@@ -209,47 +215,47 @@ impl CharacterBehavior for Data {
                 }
             },
             StageSection::Swing => {
-                if self.timer < self.static_data.swing_duration {
-                    // Swings
-                    update.character = CharacterState::DashMelee(Data {
-                        static_data: self.static_data,
-                        end_charge: self.end_charge,
-                        timer: self
-                            .timer
-                            .checked_add(Duration::from_secs_f32(data.dt.0))
-                            .unwrap_or_default(),
-                        stage_section: self.stage_section,
-                        exhausted: self.exhausted,
-                    });
-                } else {
-                    // Transitions to recover section of stage
-                    update.character = CharacterState::DashMelee(Data {
-                        static_data: self.static_data,
-                        end_charge: self.end_charge,
-                        timer: Duration::default(),
-                        stage_section: StageSection::Recover,
-                        exhausted: self.exhausted,
-                    });
+                match advance_section(self.timer, data.dt.0, self.static_data.swing_duration) {
+                    SectionProgress::Running(timer) => {
+                        // Swings
+                        update.character = CharacterState::DashMelee(Data {
+                            static_data: self.static_data,
+                            end_charge: self.end_charge,
+                            timer,
+                            stage_section: self.stage_section,
+                            exhausted: self.exhausted,
+                        });
+                    },
+                    SectionProgress::Finished(overshoot) => {
+                        // Transitions to recover section of stage
+                        update.character = CharacterState::DashMelee(Data {
+                            static_data: self.static_data,
+                            end_charge: self.end_charge,
+                            timer: overshoot,
+                            stage_section: StageSection::Recover,
+                            exhausted: self.exhausted,
+                        });
+                    },
                 }
             },
             StageSection::Recover => {
-                if self.timer < self.static_data.recover_duration {
-                    // Recover
-                    update.character = CharacterState::DashMelee(Data {
-                        static_data: self.static_data,
-                        end_charge: self.end_charge,
-                        timer: self
-                            .timer
-                            .checked_add(Duration::from_secs_f32(data.dt.0))
-                            .unwrap_or_default(),
-                        stage_section: self.stage_section,
-                        exhausted: self.exhausted,
-                    });
-                } else {
-                    // Done
-                    update.character = CharacterState::Wielding;
-                    // Make sure attack component is removed
-                    data.updater.remove::<Attacking>(data.entity);
+                match advance_section(self.timer, data.dt.0, self.static_data.recover_duration) {
+                    SectionProgress::Running(timer) => {
+                        // Recover
+                        update.character = CharacterState::DashMelee(Data {
+                            static_data: self.static_data,
+                            end_charge: self.end_charge,
+                            timer,
+                            stage_section: self.stage_section,
+                            exhausted: self.exhausted,
+                        });
+                    },
+                    SectionProgress::Finished(_) => {
+                        // Done
+                        update.character = CharacterState::Wielding;
+                        // Make sure attack component is removed
+                        data.updater.remove::<Attacking>(data.entity);
+                    },
                 }
             },
             _ => {
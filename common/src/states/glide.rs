@@ -1,16 +1,57 @@
 use super::utils::handle_climb;
 use crate::{
-    comp::{CharacterState, StateUpdate},
-    sys::character_behavior::{CharacterBehavior, JoinData},
+    comp::{
+        item::{Glider, ItemKind},
+        CharacterState, Loadout, StateUpdate,
+    },
+    sys::{
+        character_behavior::{CharacterBehavior, JoinData},
+        phys::GRAVITY,
+    },
     util::Dir,
 };
 use serde::{Deserialize, Serialize};
-use vek::Vec2;
-// Gravity is 9.81 * 4, so this makes gravity equal to .15
-const GLIDE_ANTIGRAV: f32 = crate::sys::phys::GRAVITY * 0.90;
+use vek::*;
+
 const GLIDE_ACCEL: f32 = 12.0;
 const GLIDE_SPEED: f32 = 45.0;
 
+/// The glider equipped in `loadout`, if there is one
+pub fn equipped_glider(loadout: &Loadout) -> Option<&Glider> {
+    match loadout.glider.as_ref()?.kind() {
+        ItemKind::Glider(glider) => Some(glider),
+        _ => None,
+    }
+}
+
+/// The velocity of an entity gliding at `vel` after `dt` seconds, steering
+/// towards `move_dir`. Gravity is left to the physics system.
+fn glide_vel(glider: &Glider, mut vel: Vec3<f32>, move_dir: Vec2<f32>, dt: f32) -> Vec3<f32> {
+    // Move player according to movement direction vector
+    vel += Vec2::broadcast(dt)
+        * move_dir
+        * if vel.magnitude_squared() < GLIDE_SPEED.powf(2.0) {
+            GLIDE_ACCEL
+        } else {
+            0.0
+        };
+
+    // Apply glider drag
+    vel -= Vec2::<f32>::from(vel) * (glider.drag() * dt).min(1.0);
+
+    // Apply Glide antigrav lift
+    if Vec2::<f32>::from(vel).magnitude_squared() < GLIDE_SPEED.powf(2.0) && vel.z < 0.0 {
+        let lift = GRAVITY * glider.lift() + vel.z.abs().powf(2.0) * 0.15;
+        vel.z += dt
+            * lift
+            * (Vec2::<f32>::from(vel).magnitude() * 0.075)
+                .min(1.0)
+                .max(0.2);
+    }
+
+    vel
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize, Eq, Hash)]
 pub struct Data;
 
@@ -31,38 +72,23 @@ impl CharacterBehavior for Data {
         {
             update.character = CharacterState::Idle;
         }
-        if data.loadout.glider.is_none() {
-            update.character = CharacterState::Idle
+        let glider = match equipped_glider(data.loadout) {
+            Some(glider) => glider,
+            None => {
+                update.character = CharacterState::Idle;
+                return update;
+            },
         };
         // If there is a wall in front of character and they are trying to climb go to
         // climb
         handle_climb(&data, &mut update);
 
-        // Move player according to movement direction vector
-        update.vel.0 += Vec2::broadcast(data.dt.0)
-            * data.inputs.move_dir
-            * if data.vel.0.magnitude_squared() < GLIDE_SPEED.powf(2.0) {
-                GLIDE_ACCEL
-            } else {
-                0.0
-            };
+        update.vel.0 = glide_vel(glider, update.vel.0, data.inputs.move_dir, data.dt.0);
 
         // Determine orientation vector from movement direction vector
         let ori_dir = Vec2::from(update.vel.0);
         update.ori.0 = Dir::slerp_to_vec3(update.ori.0, ori_dir.into(), 2.0 * data.dt.0);
 
-        // Apply Glide antigrav lift
-        if Vec2::<f32>::from(update.vel.0).magnitude_squared() < GLIDE_SPEED.powf(2.0)
-            && update.vel.0.z < 0.0
-        {
-            let lift = GLIDE_ANTIGRAV + update.vel.0.z.abs().powf(2.0) * 0.15;
-            update.vel.0.z += data.dt.0
-                * lift
-                * (Vec2::<f32>::from(update.vel.0).magnitude() * 0.075)
-                    .min(1.0)
-                    .max(0.2);
-        }
-
         update
     }
 
@@ -72,3 +98,35 @@ impl CharacterBehavior for Data {
         update
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LoadoutBuilder;
+
+    #[test]
+    fn gliding_slows_descent() {
+        let loadout = LoadoutBuilder::new().defaults().build();
+        let glider = equipped_glider(&loadout).unwrap();
+        let dt = 1.0 / 30.0;
+
+        let mut vel = Vec3::new(10.0, 0.0, -20.0);
+        for _ in 0..30 {
+            let falling = vel.z - GRAVITY * dt;
+            vel = glide_vel(glider, vel, Vec2::zero(), dt);
+            vel.z -= GRAVITY * dt;
+            assert!(vel.z > falling);
+        }
+        // Lift eventually outweighs gravity entirely
+        assert!(vel.z > -20.0);
+    }
+
+    #[test]
+    fn gliding_requires_glider() {
+        let loadout = LoadoutBuilder::new().defaults().build();
+        assert!(equipped_glider(&loadout).is_some());
+
+        let loadout = LoadoutBuilder::new().defaults().glider(None).build();
+        assert!(equipped_glider(&loadout).is_none());
+    }
+}
@@ -0,0 +1,402 @@
+use crate::{
+    comp::{Body, CharacterState, StateUpdate},
+    states::utils::*,
+    sync::Uid,
+    sys::character_behavior::{CharacterBehavior, JoinData},
+    terrain::{river_flow_dir, river_spline_coeffs, TerrainGrid},
+};
+use hashbrown::HashMap;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use vek::*;
+
+/// How close to the anchor the caster has to get before letting go
+const ARRIVE_DIST: f32 = 1.5;
+
+/// Separated out to condense update portions of character state
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StaticData {
+    /// How far away the anchor can be
+    pub range: f32,
+    /// How fast the caster is pulled along the tether
+    pub pull_speed: f32,
+    /// How far the tether sags upwards from a straight line, at full range
+    pub arc_height: f32,
+    /// How long until the tether is fired
+    pub buildup_duration: Duration,
+    /// How long the caster can be pulled for before letting go
+    pub pull_duration: Duration,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Data {
+    /// Struct containing data that does not change over the course of the
+    /// character state
+    pub static_data: StaticData,
+    /// Timer for each stage
+    pub timer: Duration,
+    /// What section the character stage is in
+    pub stage_section: StageSection,
+    /// What the tether is attached to, once it has been fired
+    pub anchor: Option<GrappleAnchor>,
+}
+
+/// Something a tether can be attached to
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum GrappleAnchor {
+    /// A block of terrain
+    Block(Vec3<i32>),
+    /// Another entity, which drags the tether along as it moves
+    Entity(Uid),
+}
+
+impl CharacterBehavior for Data {
+    fn behavior(&self, data: &JoinData) -> StateUpdate {
+        let mut update = StateUpdate::from(data);
+        let origin = tether_point_on(data.pos.0, data.body);
+
+        match self.stage_section {
+            StageSection::Buildup => {
+                handle_move(data, &mut update, 0.3);
+                if self.timer < self.static_data.buildup_duration {
+                    update.character = CharacterState::Grapple(Data {
+                        timer: self
+                            .timer
+                            .checked_add(Duration::from_secs_f32(data.dt.0))
+                            .unwrap_or_default(),
+                        ..*self
+                    });
+                } else {
+                    // Fire the tether at whatever is being looked at, preferring entities
+                    let anchor = data
+                        .inputs
+                        .select_entity
+                        .filter(|uid| uid != data.uid)
+                        .map(GrappleAnchor::Entity)
+                        .into_iter()
+                        .chain(data.inputs.select_pos.map(GrappleAnchor::Block))
+                        .find(|anchor| {
+                            check_tether(
+                                data.terrain,
+                                data.tracked_positions,
+                                origin,
+                                *anchor,
+                                self.static_data.range,
+                            )
+                            .is_ok()
+                        });
+                    update.character = match anchor {
+                        Some(anchor) => CharacterState::Grapple(Data {
+                            timer: Duration::default(),
+                            stage_section: StageSection::Movement,
+                            anchor: Some(anchor),
+                            ..*self
+                        }),
+                        // Nothing to hold on to
+                        None => CharacterState::Wielding,
+                    };
+                }
+            },
+            StageSection::Movement => {
+                // The anchor is checked again every tick, as the caster swings past things
+                // and entities move around
+                let target = self.anchor.and_then(|anchor| {
+                    check_tether(
+                        data.terrain,
+                        data.tracked_positions,
+                        origin,
+                        anchor,
+                        self.static_data.range,
+                    )
+                    .ok()
+                });
+                match target {
+                    Some(target)
+                        if self.timer < self.static_data.pull_duration
+                            && data.pos.0.distance(target) > ARRIVE_DIST =>
+                    {
+                        update.vel.0 = pull_velocity(
+                            data.pos.0,
+                            target,
+                            self.static_data.arc_height,
+                            self.static_data.range,
+                            self.static_data.pull_speed,
+                        );
+                        update.character = CharacterState::Grapple(Data {
+                            timer: self
+                                .timer
+                                .checked_add(Duration::from_secs_f32(data.dt.0))
+                                .unwrap_or_default(),
+                            ..*self
+                        });
+                    },
+                    // Arrived, ran out of time or lost hold of the anchor
+                    _ => update.character = CharacterState::Wielding,
+                }
+            },
+            _ => update.character = CharacterState::Wielding,
+        }
+
+        update
+    }
+}
+
+/// Why a tether couldn't be attached
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GrappleError {
+    OutOfRange,
+    /// Something solid is in the way
+    Obstructed,
+    /// The anchor is an entity that no longer exists
+    Lost,
+}
+
+/// Where on an entity at `pos` tethers are attached, whether it's the one
+/// firing them or the one they're fired at
+pub fn tether_point_on(pos: Vec3<f32>, body: &Body) -> Vec3<f32> {
+    pos + Vec3::unit_z() * body.height() * 0.5
+}
+
+impl GrappleAnchor {
+    /// The point the tether attaches to, using `entity_point` to find
+    /// entities
+    pub fn point(&self, entity_point: impl FnOnce(Uid) -> Option<Vec3<f32>>) -> Option<Vec3<f32>> {
+        match self {
+            GrappleAnchor::Block(pos) => Some(pos.map(|e| e as f32 + 0.5)),
+            GrappleAnchor::Entity(uid) => entity_point(*uid),
+        }
+    }
+}
+
+/// Checks whether a tether fired from `from` can hold on to `anchor`,
+/// returning the point it attaches to
+pub fn check_tether(
+    terrain: &TerrainGrid,
+    tracked_positions: &HashMap<Uid, Vec3<f32>>,
+    from: Vec3<f32>,
+    anchor: GrappleAnchor,
+    range: f32,
+) -> Result<Vec3<f32>, GrappleError> {
+    let target = anchor
+        .point(|uid| tracked_positions.get(&uid).copied())
+        .ok_or(GrappleError::Lost)?;
+    let dist = from.distance(target);
+    if dist > range {
+        return Err(GrappleError::OutOfRange);
+    }
+    let obstructed = match anchor {
+        GrappleAnchor::Block(block) => terrain
+            .raycast(from, target - from, dist + 1.0)
+            .map_or(false, |(pos, _, _)| pos != block),
+        // Entities aren't solid, so anything at all on the way is in the way
+        GrappleAnchor::Entity(_) => terrain.raycast(from, target - from, dist).is_some(),
+    };
+    if obstructed {
+        Err(GrappleError::Obstructed)
+    } else {
+        Ok(target)
+    }
+}
+
+/// The arc of a tether from `from` to `to`, as the horizontal direction
+/// between them and a spline in the vertical plane along it (horizontal
+/// distance, height), see [`river_spline_coeffs`]. The arc flattens out as the
+/// ends get closer.
+fn tether_spline(
+    from: Vec3<f32>,
+    to: Vec3<f32>,
+    arc_height: f32,
+    range: f32,
+) -> (Vec2<f32>, Vec3<Vec2<f64>>) {
+    let horizontal = (to - from).xy();
+    let dist = horizontal.magnitude();
+    let lift = arc_height * (dist / range).min(1.0);
+    let spline = river_spline_coeffs(
+        Vec2::new(0.0, from.z as f64),
+        Vec2::new(dist, to.z - from.z + lift),
+        Vec2::new(dist as f64, to.z as f64),
+    );
+    (
+        horizontal.try_normalized().unwrap_or_else(Vec2::zero),
+        spline,
+    )
+}
+
+/// The point `t` of the way along a tether from `from` to `to`, for drawing it
+pub fn tether_point(
+    from: Vec3<f32>,
+    to: Vec3<f32>,
+    arc_height: f32,
+    range: f32,
+    t: f32,
+) -> Vec3<f32> {
+    let (dir, spline) = tether_spline(from, to, arc_height, range);
+    let t = t as f64;
+    let point = (spline.x * t * t + spline.y * t + spline.z).map(|e| e as f32);
+    Vec3::new(from.x + dir.x * point.x, from.y + dir.y * point.x, point.y)
+}
+
+/// The velocity that pulls something at `pos` along its tether towards `to`
+pub fn pull_velocity(
+    pos: Vec3<f32>,
+    to: Vec3<f32>,
+    arc_height: f32,
+    range: f32,
+    pull_speed: f32,
+) -> Vec3<f32> {
+    let (dir, spline) = tether_spline(pos, to, arc_height, range);
+    let tangent = river_flow_dir(&spline, 0.0).map(|e| e as f32);
+    Vec3::new(dir.x * tangent.x, dir.y * tangent.x, tangent.y) * pull_speed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        comp::{humanoid, Controller, Energy, Loadout, Ori, PhysicsState, Pos, Stats, Vel},
+        state::State,
+        sync::WorldSyncExt,
+        terrain::{Block, BlockKind, SpriteKind, TerrainChunk, TerrainChunkMeta},
+        util::Dir,
+        vol::WriteVol,
+    };
+    use specs::{Builder, WorldExt};
+    use std::sync::Arc;
+
+    fn test_grid() -> TerrainGrid {
+        let mut grid = TerrainGrid::new().unwrap();
+        grid.insert(
+            Vec2::zero(),
+            Arc::new(TerrainChunk::new(
+                0,
+                Block::new(BlockKind::Rock, Rgb::new(100, 100, 100)),
+                Block::air(SpriteKind::Empty),
+                TerrainChunkMeta::void(),
+            )),
+        );
+        grid
+    }
+
+    fn block_tether(
+        grid: &TerrainGrid,
+        from: Vec3<f32>,
+        anchor: Vec3<i32>,
+        range: f32,
+    ) -> Result<Vec3<f32>, GrappleError> {
+        check_tether(
+            grid,
+            &HashMap::new(),
+            from,
+            GrappleAnchor::Block(anchor),
+            range,
+        )
+    }
+
+    #[test]
+    fn grapple_pulls_towards_anchor() {
+        let mut grid = test_grid();
+        let anchor = Vec3::new(20, 5, 8);
+        grid.set(anchor, Block::new(BlockKind::Wood, Rgb::new(80, 50, 20)))
+            .unwrap();
+
+        let mut pos = Vec3::new(4.5, 5.5, 1.0);
+        let target = block_tether(&grid, pos, anchor, 30.0).unwrap();
+        let start_dist = pos.distance(target);
+        // The tether arcs above the straight line
+        let middle = tether_point(pos, target, 4.0, 30.0, 0.5);
+        assert!(middle.z > (pos.z + target.z) / 2.0);
+        assert!((tether_point(pos, target, 4.0, 30.0, 1.0) - target).magnitude() < 0.001);
+
+        for _ in 0..10 {
+            let vel = pull_velocity(pos, target, 4.0, 30.0, 20.0);
+            assert!((vel.magnitude() - 20.0).abs() < 0.01);
+            pos += vel * 0.05;
+        }
+        assert!(pos.distance(target) < start_dist - 5.0);
+    }
+
+    #[test]
+    fn grapple_fails_out_of_range_or_blocked() {
+        let mut grid = test_grid();
+        let anchor = Vec3::new(28, 5, 3);
+        let wood = Block::new(BlockKind::Wood, Rgb::new(80, 50, 20));
+        grid.set(anchor, wood).unwrap();
+        let pos = Vec3::new(4.5, 5.5, 3.5);
+
+        assert_eq!(
+            block_tether(&grid, pos, anchor, 10.0),
+            Err(GrappleError::OutOfRange)
+        );
+        assert!(block_tether(&grid, pos, anchor, 30.0).is_ok());
+
+        grid.set(Vec3::new(15, 5, 3), wood).unwrap();
+        assert_eq!(
+            block_tether(&grid, pos, anchor, 30.0),
+            Err(GrappleError::Obstructed)
+        );
+    }
+
+    #[test]
+    fn grapple_follows_anchored_entity() {
+        let mut state = State::default();
+        let body = Body::Humanoid(humanoid::Body::random());
+        let target = state
+            .ecs_mut()
+            .create_entity_synced()
+            .with(Pos(Vec3::new(10.0, 0.0, 0.0)))
+            .with(body)
+            .build();
+        let target_uid = *state.ecs().read_storage::<Uid>().get(target).unwrap();
+
+        let mut controller = Controller::default();
+        controller.inputs.select_entity = Some(target_uid);
+        let grappler = state
+            .ecs_mut()
+            .create_entity_synced()
+            .with(Pos(Vec3::zero()))
+            .with(Vel::default())
+            .with(Ori(Dir::new(Vec3::unit_y())))
+            .with(Energy::new(1000))
+            .with(controller)
+            .with(Stats::new("Test".to_string(), body))
+            .with(body)
+            .with(PhysicsState::default())
+            .with(Loadout::default())
+            .with(CharacterState::Grapple(Data {
+                static_data: StaticData {
+                    range: 30.0,
+                    pull_speed: 20.0,
+                    arc_height: 4.0,
+                    buildup_duration: Duration::default(),
+                    pull_duration: Duration::from_secs(5),
+                },
+                timer: Duration::default(),
+                stage_section: StageSection::Buildup,
+                anchor: None,
+            }))
+            .build();
+        let pull = |state: &mut State| {
+            state.tick(Duration::from_millis(50), |_| {}, false);
+            state.ecs().read_storage::<Vel>().get(grappler).unwrap().0
+        };
+
+        // The tether is fired at the entity being looked at
+        pull(&mut state);
+        assert!(matches!(
+            state.ecs().read_storage::<CharacterState>().get(grappler),
+            Some(CharacterState::Grapple(Data {
+                anchor: Some(GrappleAnchor::Entity(uid)),
+                ..
+            })) if *uid == target_uid
+        ));
+        assert!(pull(&mut state).x > 0.0);
+
+        // And keeps pulling towards it when it moves
+        state
+            .ecs()
+            .write_storage::<Pos>()
+            .insert(target, Pos(Vec3::new(-10.0, 0.0, 0.0)))
+            .unwrap();
+        assert!(pull(&mut state).x < 0.0);
+    }
+}
@@ -0,0 +1,122 @@
+use crate::{
+    comp::{CharacterState, InventoryManip, Item, StateUpdate},
+    event::ServerEvent,
+    states::utils::*,
+    sys::character_behavior::{CharacterBehavior, JoinData},
+    terrain::Block,
+};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use vek::*;
+
+/// Separated out to condense update portions of character state
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StaticData {
+    /// How long until the sprite is harvested
+    pub buildup_duration: Duration,
+    /// How long the state has until exiting
+    pub recover_duration: Duration,
+    /// How far away the targeted sprite can be
+    pub range: f32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Data {
+    /// Struct containing data that does not change over the course of the
+    /// character state
+    pub static_data: StaticData,
+    /// Timer for each stage
+    pub timer: Duration,
+    /// What section the character stage is in
+    pub stage_section: StageSection,
+}
+
+impl CharacterBehavior for Data {
+    fn behavior(&self, data: &JoinData) -> StateUpdate {
+        let mut update = StateUpdate::from(data);
+
+        handle_move(data, &mut update, 0.3);
+
+        match self.stage_section {
+            StageSection::Buildup => {
+                if self.timer < self.static_data.buildup_duration {
+                    update.character = CharacterState::Harvest(Data {
+                        timer: self
+                            .timer
+                            .checked_add(Duration::from_secs_f32(data.dt.0))
+                            .unwrap_or_default(),
+                        ..*self
+                    });
+                } else {
+                    // Harvest whatever is being looked at, if it's close enough. Whether
+                    // there's still anything there to harvest is checked by the server.
+                    if let Some(pos) = data.inputs.select_pos.filter(|pos| {
+                        data.pos.0.distance_squared(pos.map(|e| e as f32 + 0.5))
+                            < self.static_data.range.powi(2)
+                    }) {
+                        update.server_events.push_front(ServerEvent::InventoryManip(
+                            data.entity,
+                            InventoryManip::Collect(pos),
+                        ));
+                    }
+                    update.character = CharacterState::Harvest(Data {
+                        timer: Duration::default(),
+                        stage_section: StageSection::Recover,
+                        ..*self
+                    });
+                }
+            },
+            StageSection::Recover => {
+                if self.timer < self.static_data.recover_duration {
+                    update.character = CharacterState::Harvest(Data {
+                        timer: self
+                            .timer
+                            .checked_add(Duration::from_secs_f32(data.dt.0))
+                            .unwrap_or_default(),
+                        ..*self
+                    });
+                } else {
+                    // Done
+                    update.character = CharacterState::Wielding;
+                }
+            },
+            _ => {
+                // If it somehow ends up in an incorrect stage section
+                update.character = CharacterState::Wielding;
+            },
+        }
+
+        update
+    }
+}
+
+/// Takes the collectible sprite from a block, returning the item it yields
+/// and the block left behind. Returns `None` if there's nothing to harvest,
+/// for example if the sprite has already been taken.
+pub fn harvest_block(block: Block) -> Option<(Item, Block)> {
+    if block.is_collectible() {
+        Item::try_reclaim_from_block(block).map(|item| (item, block.into_vacant()))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terrain::SpriteKind;
+
+    #[test]
+    fn harvest_collectible_sprite() {
+        let (item, remaining) = harvest_block(Block::air(SpriteKind::Apple)).unwrap();
+        assert_eq!(item.item_definition_id(), "common.items.food.apple");
+        assert_eq!(remaining, Block::air(SpriteKind::Empty));
+        assert!(harvest_block(remaining).is_none());
+    }
+
+    #[test]
+    fn harvest_nothing() {
+        assert!(harvest_block(Block::air(SpriteKind::LongGrass)).is_none());
+        assert!(harvest_block(Block::air(SpriteKind::Empty)).is_none());
+    }
+}
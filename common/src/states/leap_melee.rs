@@ -1,5 +1,5 @@
 use crate::{
-    comp::{Attacking, CharacterState, StateUpdate},
+    comp::{Attacking, CharacterState, DamageKind, Knockback, StateUpdate},
     states::utils::{StageSection, *},
     sys::character_behavior::{CharacterBehavior, JoinData},
 };
@@ -21,15 +21,45 @@ pub struct StaticData {
     /// Base damage
     pub base_damage: u32,
     /// Knockback
-    pub knockback: f32,
+    pub knockback: Knockback,
     /// Max range
     pub range: f32,
     /// Max angle (45.0 will give you a 90.0 angle window)
     pub max_angle: f32,
+    /// Radius of the ground slam on landing, which hits everything around the
+    /// impact point and knocks it outwards. When zero, the landing is a
+    /// directed strike using `range` and `max_angle` instead.
+    pub impact_radius: f32,
     /// Affects how far forward the player leaps
     pub forward_leap_strength: f32,
     /// Affects how high the player leaps
     pub vertical_leap_strength: f32,
+    /// The kind of damage dealt
+    pub damage_kind: DamageKind,
+}
+
+impl StaticData {
+    /// The attack made on landing
+    pub fn impact(&self) -> Attacking {
+        let (range, max_angle) = if self.impact_radius > 0.0 {
+            (self.impact_radius, std::f32::consts::PI)
+        } else {
+            (self.range, self.max_angle.to_radians())
+        };
+        Attacking {
+            base_damage: self.base_damage,
+            base_heal: 0,
+            damage_kind: self.damage_kind,
+            range,
+            max_angle,
+            applied: false,
+            hit_count: 0,
+            knockback: self.knockback,
+            vertical_range: None,
+            max_targets: None,
+            dot: None,
+        }
+    }
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -56,27 +86,25 @@ impl CharacterBehavior for Data {
             // Delay before leaping into the air
             StageSection::Buildup => {
                 // Wait for `buildup_duration` to expire
-                if self.timer < self.static_data.buildup_duration {
-                    update.character = CharacterState::LeapMelee(Data {
-                        timer: self
-                            .timer
-                            .checked_add(Duration::from_secs_f32(data.dt.0))
-                            .unwrap_or_default(),
-                        ..*self
-                    });
-                } else {
-                    // Transitions to leap portion of state after buildup delay
-                    update.character = CharacterState::LeapMelee(Data {
-                        timer: Duration::default(),
-                        stage_section: StageSection::Movement,
-                        ..*self
-                    });
+                match advance_section(self.timer, data.dt.0, self.static_data.buildup_duration) {
+                    SectionProgress::Running(timer) => {
+                        update.character = CharacterState::LeapMelee(Data { timer, ..*self });
+                    },
+                    SectionProgress::Finished(overshoot) => {
+                        // Transitions to leap portion of state after buildup delay
+                        update.character = CharacterState::LeapMelee(Data {
+                            timer: overshoot,
+                            stage_section: StageSection::Movement,
+                            ..*self
+                        });
+                    },
                 }
             },
             StageSection::Movement => {
-                if self.timer < self.static_data.movement_duration {
-                    // Apply jumping force while in Movement portion of state
-                    update.vel.0 = Vec3::new(
+                match advance_section(self.timer, data.dt.0, self.static_data.movement_duration) {
+                    SectionProgress::Running(timer) => {
+                        // Apply jumping force while in Movement portion of state
+                        update.vel.0 = Vec3::new(
                         data.inputs.look_dir.x,
                         data.inputs.look_dir.y,
                         self.static_data.vertical_leap_strength,
@@ -98,80 +126,61 @@ impl CharacterBehavior for Data {
                         // look downward at target
                             * (1.0 - data.inputs.look_dir.z.abs());
 
-                    // Increment duration
-                    // If we were to set a timeout for state, this would be
-                    // outside if block and have else check for > movement
-                    // duration * some multiplier
-                    update.character = CharacterState::LeapMelee(Data {
-                        timer: self
-                            .timer
-                            .checked_add(Duration::from_secs_f32(data.dt.0))
-                            .unwrap_or_default(),
-                        ..*self
-                    });
-                } else if data.physics.on_ground {
-                    // Transitions to swing portion of state upon hitting ground
-                    update.character = CharacterState::LeapMelee(Data {
-                        timer: Duration::default(),
-                        stage_section: StageSection::Swing,
-                        ..*self
-                    });
+                        // Increment duration
+                        // If we were to set a timeout for state, this would be
+                        // outside if block and have else check for > movement
+                        // duration * some multiplier
+                        update.character = CharacterState::LeapMelee(Data { timer, ..*self });
+                    },
+                    SectionProgress::Finished(_) if data.physics.on_ground => {
+                        // Transitions to swing portion of state upon hitting ground
+                        update.character = CharacterState::LeapMelee(Data {
+                            timer: Duration::default(),
+                            stage_section: StageSection::Swing,
+                            ..*self
+                        });
+                    },
+                    SectionProgress::Finished(_) => {
+                        // Falls until hitting the ground
+                        update.character = CharacterState::LeapMelee(Data {
+                            timer: self.static_data.movement_duration,
+                            ..*self
+                        });
+                    },
                 }
             },
             StageSection::Swing => {
-                if self.timer < self.static_data.swing_duration {
-                    // Swings weapons
-                    update.character = CharacterState::LeapMelee(Data {
-                        timer: self
-                            .timer
-                            .checked_add(Duration::from_secs_f32(data.dt.0))
-                            .unwrap_or_default(),
-                        ..*self
-                    });
-                } else {
-                    // Transitions to recover portion
-                    update.character = CharacterState::LeapMelee(Data {
-                        timer: Duration::default(),
-                        stage_section: StageSection::Recover,
-                        ..*self
-                    });
+                match advance_section(self.timer, data.dt.0, self.static_data.swing_duration) {
+                    SectionProgress::Running(timer) => {
+                        // Swings weapons
+                        update.character = CharacterState::LeapMelee(Data { timer, ..*self });
+                    },
+                    SectionProgress::Finished(overshoot) => {
+                        // Hit attempt, when animation plays
+                        data.updater.insert(data.entity, self.static_data.impact());
+
+                        // Transitions to recover portion
+                        update.character = CharacterState::LeapMelee(Data {
+                            timer: overshoot,
+                            stage_section: StageSection::Recover,
+                            exhausted: true,
+                            ..*self
+                        });
+                    },
                 }
             },
             StageSection::Recover => {
-                if !self.exhausted {
-                    // Hit attempt, when animation plays
-                    data.updater.insert(data.entity, Attacking {
-                        base_damage: self.static_data.base_damage,
-                        base_heal: 0,
-                        range: self.static_data.range,
-                        max_angle: self.static_data.max_angle.to_radians(),
-                        applied: false,
-                        hit_count: 0,
-                        knockback: self.static_data.knockback,
-                    });
-
-                    update.character = CharacterState::LeapMelee(Data {
-                        timer: self
-                            .timer
-                            .checked_add(Duration::from_secs_f32(data.dt.0))
-                            .unwrap_or_default(),
-                        exhausted: true,
-                        ..*self
-                    });
-                } else if self.timer < self.static_data.recover_duration {
-                    // Complete recovery delay before finishing state
-                    update.character = CharacterState::LeapMelee(Data {
-                        timer: self
-                            .timer
-                            .checked_add(Duration::from_secs_f32(data.dt.0))
-                            .unwrap_or_default(),
-                        ..*self
-                    });
-                } else {
-                    // Done
-                    update.character = CharacterState::Wielding;
-                    // Make sure attack component is removed
-                    data.updater.remove::<Attacking>(data.entity);
+                match advance_section(self.timer, data.dt.0, self.static_data.recover_duration) {
+                    SectionProgress::Running(timer) => {
+                        // Complete recovery delay before finishing state
+                        update.character = CharacterState::LeapMelee(Data { timer, ..*self });
+                    },
+                    SectionProgress::Finished(_) => {
+                        // Done
+                        update.character = CharacterState::Wielding;
+                        // Make sure attack component is removed
+                        data.updater.remove::<Attacking>(data.entity);
+                    },
                 }
             },
             _ => {
@@ -185,3 +194,84 @@ impl CharacterBehavior for Data {
         update
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sys::combat::{hits_target, knockback_dir};
+    use vek::Vec2;
+
+    fn slam() -> StaticData {
+        StaticData {
+            movement_duration: Duration::from_millis(800),
+            buildup_duration: Duration::from_millis(100),
+            swing_duration: Duration::from_millis(150),
+            recover_duration: Duration::from_millis(200),
+            base_damage: 100,
+            knockback: Knockback::from_horizontal(25.0),
+            range: 4.5,
+            max_angle: 30.0,
+            forward_leap_strength: 28.0,
+            vertical_leap_strength: 8.0,
+            impact_radius: 5.0,
+            damage_kind: DamageKind::Physical,
+        }
+    }
+
+    #[test]
+    fn slam_hits_all_around_landing_point() {
+        let attack = slam().impact();
+        let pos = Vec3::new(10.0, 10.0, 0.0);
+        let ori = Vec2::unit_y();
+        let front = pos + Vec3::new(0.0, 3.0, 0.0);
+        let behind = pos + Vec3::new(0.0, -3.0, 0.0);
+
+        assert!(hits_target(&attack, pos, ori, 1.0, front, 0.5, 1.8));
+        assert!(hits_target(&attack, pos, ori, 1.0, behind, 0.5, 1.8));
+        assert!(!hits_target(
+            &attack,
+            pos,
+            ori,
+            1.0,
+            pos + Vec3::new(7.0, 0.0, 0.0),
+            0.5,
+            1.8
+        ));
+
+        // Knockback radiates outwards from the impact
+        let kb_front = knockback_dir(pos, front, Vec3::unit_y());
+        let kb_behind = knockback_dir(pos, behind, Vec3::unit_y());
+        assert!(kb_front.y > 0.0);
+        assert!(kb_behind.y < 0.0);
+    }
+
+    #[test]
+    fn directed_strike_without_impact_radius() {
+        let attack = StaticData {
+            impact_radius: 0.0,
+            ..slam()
+        }
+        .impact();
+        let pos = Vec3::zero();
+        let ori = Vec2::unit_y();
+
+        assert!(hits_target(
+            &attack,
+            pos,
+            ori,
+            1.0,
+            Vec3::new(0.0, 3.0, 0.0),
+            0.5,
+            1.8
+        ));
+        assert!(!hits_target(
+            &attack,
+            pos,
+            ori,
+            1.0,
+            Vec3::new(0.0, -3.0, 0.0),
+            0.5,
+            1.8
+        ));
+    }
+}
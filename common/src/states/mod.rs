@@ -12,13 +12,18 @@ pub mod dash_melee;
 pub mod equipping;
 pub mod glide;
 pub mod glide_wield;
+pub mod grapple;
+pub mod harvest;
 pub mod idle;
 pub mod leap_melee;
+pub mod place_block;
 pub mod repeater_ranged;
 pub mod roll;
 pub mod shockwave;
 pub mod sit;
 pub mod sneak;
 pub mod spin_melee;
+pub mod sprint;
+pub mod swap_weapons;
 pub mod utils;
 pub mod wielding;
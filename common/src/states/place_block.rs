@@ -0,0 +1,98 @@
+use crate::{
+    comp::{CharacterState, StateUpdate},
+    event::ServerEvent,
+    states::utils::*,
+    sys::character_behavior::{CharacterBehavior, JoinData},
+    terrain::{Block, SpriteKind},
+};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use vek::*;
+
+/// Separated out to condense update portions of character state
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StaticData {
+    /// The block that gets placed
+    pub block: Block,
+    /// How long until the block is placed
+    pub buildup_duration: Duration,
+    /// How far away the targeted position can be
+    pub range: f32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Data {
+    /// Struct containing data that does not change over the course of the
+    /// character state
+    pub static_data: StaticData,
+    /// Timer for the buildup
+    pub timer: Duration,
+}
+
+impl CharacterBehavior for Data {
+    fn behavior(&self, data: &JoinData) -> StateUpdate {
+        let mut update = StateUpdate::from(data);
+
+        handle_move(data, &mut update, 0.3);
+
+        if self.timer < self.static_data.buildup_duration {
+            update.character = CharacterState::PlaceBlock(Data {
+                timer: self
+                    .timer
+                    .checked_add(Duration::from_secs_f32(data.dt.0))
+                    .unwrap_or_default(),
+                ..*self
+            });
+        } else {
+            // Place the block wherever is being looked at, if it's close enough.
+            // Whether the position is loaded and still free is checked by the server.
+            if let Some(pos) = data.inputs.select_pos.filter(|pos| {
+                data.pos.0.distance_squared(pos.map(|e| e as f32 + 0.5))
+                    < self.static_data.range.powi(2)
+            }) {
+                update.server_events.push_front(ServerEvent::PlaceBlock {
+                    entity: data.entity,
+                    pos,
+                    block: self.static_data.block,
+                });
+            }
+            // Done
+            update.character = CharacterState::Wielding;
+        }
+
+        update
+    }
+}
+
+/// Returns the block to put in place of `target`, or `None` if the target is
+/// already occupied by something solid, a liquid or a sprite.
+pub fn place_block(target: Block, block: Block) -> Option<Block> {
+    if target.is_air() && target.get_sprite().map_or(true, |s| s == SpriteKind::Empty) {
+        Some(block)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terrain::BlockKind;
+
+    #[test]
+    fn place_into_air() {
+        let stone = Block::new(BlockKind::Rock, Rgb::new(128, 128, 128));
+        assert_eq!(
+            place_block(Block::air(SpriteKind::Empty), stone),
+            Some(stone)
+        );
+    }
+
+    #[test]
+    fn place_into_occupied() {
+        let stone = Block::new(BlockKind::Rock, Rgb::new(128, 128, 128));
+        assert!(place_block(stone, stone).is_none());
+        assert!(place_block(Block::air(SpriteKind::Apple), stone).is_none());
+        assert!(place_block(Block::water(SpriteKind::Empty), stone).is_none());
+    }
+}
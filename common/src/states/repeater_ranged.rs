@@ -63,25 +63,25 @@ impl CharacterBehavior for Data {
                                     / self.static_data.movement_duration.as_secs_f32()),
                     );
                 }
-                if self.timer < self.static_data.movement_duration {
-                    // Do movement
-                    update.character = CharacterState::RepeaterRanged(Data {
-                        static_data: self.static_data.clone(),
-                        timer: self
-                            .timer
-                            .checked_add(Duration::from_secs_f32(data.dt.0))
-                            .unwrap_or_default(),
-                        stage_section: self.stage_section,
-                        reps_remaining: self.reps_remaining,
-                    });
-                } else {
-                    // Transition to buildup
-                    update.character = CharacterState::RepeaterRanged(Data {
-                        static_data: self.static_data.clone(),
-                        timer: Duration::default(),
-                        stage_section: StageSection::Buildup,
-                        reps_remaining: self.reps_remaining,
-                    });
+                match advance_section(self.timer, data.dt.0, self.static_data.movement_duration) {
+                    SectionProgress::Running(timer) => {
+                        // Do movement
+                        update.character = CharacterState::RepeaterRanged(Data {
+                            static_data: self.static_data.clone(),
+                            timer,
+                            stage_section: self.stage_section,
+                            reps_remaining: self.reps_remaining,
+                        });
+                    },
+                    SectionProgress::Finished(overshoot) => {
+                        // Transition to buildup
+                        update.character = CharacterState::RepeaterRanged(Data {
+                            static_data: self.static_data.clone(),
+                            timer: overshoot,
+                            stage_section: StageSection::Buildup,
+                            reps_remaining: self.reps_remaining,
+                        });
+                    },
                 }
             },
             StageSection::Buildup => {
@@ -89,25 +89,25 @@ impl CharacterBehavior for Data {
                 if self.static_data.leap.is_some() {
                     update.vel.0 = Vec3::new(data.vel.0.x, data.vel.0.y, 0.0);
                 }
-                if self.timer < self.static_data.buildup_duration {
-                    // Buildup to attack
-                    update.character = CharacterState::RepeaterRanged(Data {
-                        static_data: self.static_data.clone(),
-                        timer: self
-                            .timer
-                            .checked_add(Duration::from_secs_f32(data.dt.0))
-                            .unwrap_or_default(),
-                        stage_section: self.stage_section,
-                        reps_remaining: self.reps_remaining,
-                    });
-                } else {
-                    // Transition to shoot
-                    update.character = CharacterState::RepeaterRanged(Data {
-                        static_data: self.static_data.clone(),
-                        timer: Duration::default(),
-                        stage_section: StageSection::Shoot,
-                        reps_remaining: self.reps_remaining,
-                    });
+                match advance_section(self.timer, data.dt.0, self.static_data.buildup_duration) {
+                    SectionProgress::Running(timer) => {
+                        // Buildup to attack
+                        update.character = CharacterState::RepeaterRanged(Data {
+                            static_data: self.static_data.clone(),
+                            timer,
+                            stage_section: self.stage_section,
+                            reps_remaining: self.reps_remaining,
+                        });
+                    },
+                    SectionProgress::Finished(overshoot) => {
+                        // Transition to shoot
+                        update.character = CharacterState::RepeaterRanged(Data {
+                            static_data: self.static_data.clone(),
+                            timer: overshoot,
+                            stage_section: StageSection::Shoot,
+                            reps_remaining: self.reps_remaining,
+                        });
+                    },
                 }
             },
             StageSection::Shoot => {
@@ -115,85 +115,87 @@ impl CharacterBehavior for Data {
                 if self.static_data.leap.is_some() {
                     update.vel.0 = Vec3::new(data.vel.0.x, data.vel.0.y, 0.0);
                 }
-                if self.reps_remaining > 0 {
-                    // Fire
-                    let mut projectile = self.static_data.projectile.clone();
-                    projectile.owner = Some(*data.uid);
-                    update.server_events.push_front(ServerEvent::Shoot {
-                        entity: data.entity,
-                        // Provides slight variation to projectile direction
-                        dir: Dir::from_unnormalized(Vec3::new(
-                            data.inputs.look_dir[0]
-                                + (if self.reps_remaining % 2 == 0 {
-                                    self.reps_remaining as f32 / 400.0
-                                } else {
-                                    -1.0 * self.reps_remaining as f32 / 400.0
-                                }),
-                            data.inputs.look_dir[1]
-                                + (if self.reps_remaining % 2 == 0 {
-                                    -1.0 * self.reps_remaining as f32 / 400.0
-                                } else {
-                                    self.reps_remaining as f32 / 400.0
-                                }),
-                            data.inputs.look_dir[2],
-                        ))
-                        .unwrap_or(data.inputs.look_dir),
-                        body: self.static_data.projectile_body,
-                        projectile,
-                        light: self.static_data.projectile_light,
-                        gravity: self.static_data.projectile_gravity,
-                        speed: self.static_data.projectile_speed,
-                    });
+                match advance_section(self.timer, data.dt.0, self.static_data.shoot_duration) {
+                    progress if self.reps_remaining > 0 => {
+                        // Fire
+                        let mut projectile = self.static_data.projectile.clone();
+                        projectile.owner = Some(*data.uid);
+                        update.server_events.push_front(ServerEvent::Shoot {
+                            entity: data.entity,
+                            // Provides slight variation to projectile direction
+                            dir: Dir::from_unnormalized(Vec3::new(
+                                data.inputs.look_dir[0]
+                                    + (if self.reps_remaining % 2 == 0 {
+                                        self.reps_remaining as f32 / 400.0
+                                    } else {
+                                        -1.0 * self.reps_remaining as f32 / 400.0
+                                    }),
+                                data.inputs.look_dir[1]
+                                    + (if self.reps_remaining % 2 == 0 {
+                                        -1.0 * self.reps_remaining as f32 / 400.0
+                                    } else {
+                                        self.reps_remaining as f32 / 400.0
+                                    }),
+                                data.inputs.look_dir[2],
+                            ))
+                            .unwrap_or(data.inputs.look_dir),
+                            body: self.static_data.projectile_body,
+                            projectile,
+                            light: self.static_data.projectile_light,
+                            gravity: self.static_data.projectile_gravity,
+                            speed: self.static_data.projectile_speed,
+                        });
 
-                    // Shoot projectiles
-                    update.character = CharacterState::RepeaterRanged(Data {
-                        static_data: self.static_data.clone(),
-                        timer: self
-                            .timer
-                            .checked_add(Duration::from_secs_f32(data.dt.0))
-                            .unwrap_or_default(),
-                        stage_section: self.stage_section,
-                        reps_remaining: self.reps_remaining - 1,
-                    });
-                } else if self.timer < self.static_data.shoot_duration {
-                    // Finish shooting
-                    update.character = CharacterState::RepeaterRanged(Data {
-                        static_data: self.static_data.clone(),
-                        timer: self
-                            .timer
-                            .checked_add(Duration::from_secs_f32(data.dt.0))
-                            .unwrap_or_default(),
-                        stage_section: self.stage_section,
-                        reps_remaining: self.reps_remaining,
-                    });
-                } else {
-                    // Transition to recover
-                    update.character = CharacterState::RepeaterRanged(Data {
-                        static_data: self.static_data.clone(),
-                        timer: Duration::default(),
-                        stage_section: StageSection::Recover,
-                        reps_remaining: self.reps_remaining,
-                    });
+                        // Shoot projectiles, keeping the section going until every shot is out
+                        update.character = CharacterState::RepeaterRanged(Data {
+                            static_data: self.static_data.clone(),
+                            timer: match progress {
+                                SectionProgress::Running(timer) => timer,
+                                SectionProgress::Finished(_) => self.static_data.shoot_duration,
+                            },
+                            stage_section: self.stage_section,
+                            reps_remaining: self.reps_remaining - 1,
+                        });
+                    },
+                    SectionProgress::Running(timer) => {
+                        // Finish shooting
+                        update.character = CharacterState::RepeaterRanged(Data {
+                            static_data: self.static_data.clone(),
+                            timer,
+                            stage_section: self.stage_section,
+                            reps_remaining: self.reps_remaining,
+                        });
+                    },
+                    SectionProgress::Finished(overshoot) => {
+                        // Transition to recover
+                        update.character = CharacterState::RepeaterRanged(Data {
+                            static_data: self.static_data.clone(),
+                            timer: overshoot,
+                            stage_section: StageSection::Recover,
+                            reps_remaining: self.reps_remaining,
+                        });
+                    },
                 }
             },
             StageSection::Recover => {
-                if self.static_data.leap.is_some() && data.physics.on_ground {
-                    // Done
-                    update.character = CharacterState::Wielding;
-                } else if self.timer < self.static_data.recover_duration {
-                    // Recover from attack
-                    update.character = CharacterState::RepeaterRanged(Data {
-                        static_data: self.static_data.clone(),
-                        timer: self
-                            .timer
-                            .checked_add(Duration::from_secs_f32(data.dt.0))
-                            .unwrap_or_default(),
-                        stage_section: self.stage_section,
-                        reps_remaining: self.reps_remaining,
-                    });
-                } else {
-                    // Done
-                    update.character = CharacterState::Wielding;
+                match advance_section(self.timer, data.dt.0, self.static_data.recover_duration) {
+                    _ if self.static_data.leap.is_some() && data.physics.on_ground => {
+                        // Done
+                        update.character = CharacterState::Wielding;
+                    },
+                    SectionProgress::Running(timer) => {
+                        // Recover from attack
+                        update.character = CharacterState::RepeaterRanged(Data {
+                            static_data: self.static_data.clone(),
+                            timer,
+                            stage_section: self.stage_section,
+                            reps_remaining: self.reps_remaining,
+                        });
+                    },
+                    SectionProgress::Finished(_) => {
+                        // Done
+                        update.character = CharacterState::Wielding;
+                    },
                 }
             },
             _ => {
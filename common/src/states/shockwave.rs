@@ -1,5 +1,5 @@
 use crate::{
-    comp::{shockwave, CharacterState, StateUpdate},
+    comp::{shockwave, CharacterState, Knockback, StateUpdate},
     event::ServerEvent,
     states::utils::*,
     sys::character_behavior::{CharacterBehavior, JoinData},
@@ -19,7 +19,7 @@ pub struct StaticData {
     /// Base damage
     pub damage: u32,
     /// Knockback
-    pub knockback: f32,
+    pub knockback: Knockback,
     /// Angle of the shockwave
     pub shockwave_angle: f32,
     /// Vertical angle of the shockwave
@@ -32,6 +32,10 @@ pub struct StaticData {
     pub requires_ground: bool,
     /// Movement speed efficiency
     pub move_efficiency: f32,
+    /// Maximum number of targets the shockwave can hit, if limited
+    pub max_targets: Option<u32>,
+    /// How long anything hit is stunned for, if at all
+    pub stun: Option<Duration>,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -53,76 +57,78 @@ impl CharacterBehavior for Data {
 
         match self.stage_section {
             StageSection::Buildup => {
-                if self.timer < self.static_data.buildup_duration {
-                    // Build up
-                    update.character = CharacterState::Shockwave(Data {
-                        static_data: self.static_data,
-                        timer: self
-                            .timer
-                            .checked_add(Duration::from_secs_f32(data.dt.0))
-                            .unwrap_or_default(),
-                        stage_section: self.stage_section,
-                    });
-                } else {
-                    // Attack
-                    let properties = shockwave::Properties {
-                        angle: self.static_data.shockwave_angle,
-                        vertical_angle: self.static_data.shockwave_vertical_angle,
-                        speed: self.static_data.shockwave_speed,
-                        duration: self.static_data.shockwave_duration,
-                        damage: self.static_data.damage,
-                        knockback: self.static_data.knockback,
-                        requires_ground: self.static_data.requires_ground,
-                        owner: Some(*data.uid),
-                    };
-                    update.server_events.push_front(ServerEvent::Shockwave {
-                        properties,
-                        pos: *data.pos,
-                        ori: *data.ori,
-                    });
+                match advance_section(self.timer, data.dt.0, self.static_data.buildup_duration) {
+                    SectionProgress::Running(timer) => {
+                        // Build up
+                        update.character = CharacterState::Shockwave(Data {
+                            static_data: self.static_data,
+                            timer,
+                            stage_section: self.stage_section,
+                        });
+                    },
+                    SectionProgress::Finished(overshoot) => {
+                        // Attack
+                        let properties = shockwave::Properties {
+                            angle: self.static_data.shockwave_angle,
+                            vertical_angle: self.static_data.shockwave_vertical_angle,
+                            speed: self.static_data.shockwave_speed,
+                            duration: self.static_data.shockwave_duration,
+                            damage: self.static_data.damage,
+                            knockback: self.static_data.knockback,
+                            requires_ground: self.static_data.requires_ground,
+                            max_targets: self.static_data.max_targets,
+                            stun: self.static_data.stun,
+                            owner: Some(*data.uid),
+                        };
+                        update.server_events.push_front(ServerEvent::Shockwave {
+                            properties,
+                            pos: *data.pos,
+                            ori: *data.ori,
+                        });
 
-                    // Transitions to swing
-                    update.character = CharacterState::Shockwave(Data {
-                        static_data: self.static_data,
-                        timer: Duration::default(),
-                        stage_section: StageSection::Swing,
-                    });
+                        // Transitions to swing
+                        update.character = CharacterState::Shockwave(Data {
+                            static_data: self.static_data,
+                            timer: overshoot,
+                            stage_section: StageSection::Swing,
+                        });
+                    },
                 }
             },
             StageSection::Swing => {
-                if self.timer < self.static_data.swing_duration {
-                    // Swings
-                    update.character = CharacterState::Shockwave(Data {
-                        static_data: self.static_data,
-                        timer: self
-                            .timer
-                            .checked_add(Duration::from_secs_f32(data.dt.0))
-                            .unwrap_or_default(),
-                        stage_section: self.stage_section,
-                    });
-                } else {
-                    // Transitions to recover
-                    update.character = CharacterState::Shockwave(Data {
-                        static_data: self.static_data,
-                        timer: Duration::default(),
-                        stage_section: StageSection::Recover,
-                    });
+                match advance_section(self.timer, data.dt.0, self.static_data.swing_duration) {
+                    SectionProgress::Running(timer) => {
+                        // Swings
+                        update.character = CharacterState::Shockwave(Data {
+                            static_data: self.static_data,
+                            timer,
+                            stage_section: self.stage_section,
+                        });
+                    },
+                    SectionProgress::Finished(overshoot) => {
+                        // Transitions to recover
+                        update.character = CharacterState::Shockwave(Data {
+                            static_data: self.static_data,
+                            timer: overshoot,
+                            stage_section: StageSection::Recover,
+                        });
+                    },
                 }
             },
             StageSection::Recover => {
-                if self.timer < self.static_data.swing_duration {
-                    // Recovers
-                    update.character = CharacterState::Shockwave(Data {
-                        static_data: self.static_data,
-                        timer: self
-                            .timer
-                            .checked_add(Duration::from_secs_f32(data.dt.0))
-                            .unwrap_or_default(),
-                        stage_section: self.stage_section,
-                    });
-                } else {
-                    // Done
-                    update.character = CharacterState::Wielding;
+                match advance_section(self.timer, data.dt.0, self.static_data.swing_duration) {
+                    SectionProgress::Running(timer) => {
+                        // Recovers
+                        update.character = CharacterState::Shockwave(Data {
+                            static_data: self.static_data,
+                            timer,
+                            stage_section: self.stage_section,
+                        });
+                    },
+                    SectionProgress::Finished(_) => {
+                        // Done
+                        update.character = CharacterState::Wielding;
+                    },
                 }
             },
             _ => {
@@ -1,5 +1,5 @@
 use crate::{
-    comp::{Attacking, CharacterState, EnergySource, StateUpdate},
+    comp::{Attacking, BuffKind, CharacterState, DamageKind, EnergySource, Knockback, StateUpdate},
     states::utils::*,
     sys::character_behavior::{CharacterBehavior, JoinData},
 };
@@ -19,9 +19,11 @@ pub struct StaticData {
     /// Base damage
     pub base_damage: u32,
     /// Knockback
-    pub knockback: f32,
+    pub knockback: Knockback,
     /// Range
     pub range: f32,
+    /// How far above or below the caster targets can be hit
+    pub vertical_range: f32,
     /// Energy cost per attack
     pub energy_cost: u32,
     /// Whether spin state is infinite
@@ -34,6 +36,13 @@ pub struct StaticData {
     pub forward_speed: f32,
     /// Number of spins
     pub num_spins: u32,
+    /// Maximum number of targets hit per spin, if limited
+    pub max_targets: Option<u32>,
+    /// Buff granted to the spinner (kind, strength, max duration) for as long
+    /// as they spin
+    pub self_buff: Option<(BuffKind, f32, Duration)>,
+    /// The kind of damage dealt
+    pub damage_kind: DamageKind,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -72,115 +81,117 @@ impl CharacterBehavior for Data {
 
         match self.stage_section {
             StageSection::Buildup => {
-                if self.timer < self.static_data.buildup_duration {
-                    // Build up
-                    update.character = CharacterState::SpinMelee(Data {
-                        static_data: self.static_data,
-                        timer: self
-                            .timer
-                            .checked_add(Duration::from_secs_f32(data.dt.0))
-                            .unwrap_or_default(),
-                        spins_remaining: self.spins_remaining,
-                        stage_section: self.stage_section,
-                        exhausted: self.exhausted,
-                    });
-                } else {
-                    // Transitions to swing section of stage
-                    update.character = CharacterState::SpinMelee(Data {
-                        static_data: self.static_data,
-                        timer: Duration::default(),
-                        spins_remaining: self.spins_remaining,
-                        stage_section: StageSection::Swing,
-                        exhausted: self.exhausted,
-                    });
+                match advance_section(self.timer, data.dt.0, self.static_data.buildup_duration) {
+                    SectionProgress::Running(timer) => {
+                        // Build up
+                        update.character = CharacterState::SpinMelee(Data {
+                            static_data: self.static_data,
+                            timer,
+                            spins_remaining: self.spins_remaining,
+                            stage_section: self.stage_section,
+                            exhausted: self.exhausted,
+                        });
+                    },
+                    SectionProgress::Finished(overshoot) => {
+                        // Transitions to swing section of stage
+                        update.character = CharacterState::SpinMelee(Data {
+                            static_data: self.static_data,
+                            timer: overshoot,
+                            spins_remaining: self.spins_remaining,
+                            stage_section: StageSection::Swing,
+                            exhausted: self.exhausted,
+                        });
+                    },
                 }
             },
             StageSection::Swing => {
                 if !self.exhausted {
-                    update.character = CharacterState::SpinMelee(Data {
-                        static_data: self.static_data,
-                        timer: Duration::default(),
-                        spins_remaining: self.spins_remaining,
-                        stage_section: self.stage_section,
-                        exhausted: true,
-                    });
                     // Hit attempt
                     data.updater.insert(data.entity, Attacking {
                         base_damage: self.static_data.base_damage,
                         base_heal: 0,
+                        damage_kind: self.static_data.damage_kind,
                         range: self.static_data.range,
                         max_angle: 180_f32.to_radians(),
                         applied: false,
                         hit_count: 0,
                         knockback: self.static_data.knockback,
+                        vertical_range: Some(self.static_data.vertical_range),
+                        max_targets: self.static_data.max_targets,
+                        dot: None,
                     });
-                } else if self.timer < self.static_data.swing_duration {
-                    if !self.static_data.is_helicopter {
-                        forward_move(data, &mut update, 0.1, self.static_data.forward_speed);
-                        handle_orientation(data, &mut update, 1.0);
-                    }
+                }
 
-                    // Swings
-                    update.character = CharacterState::SpinMelee(Data {
-                        static_data: self.static_data,
-                        timer: self
-                            .timer
-                            .checked_add(Duration::from_secs_f32(data.dt.0))
-                            .unwrap_or_default(),
-                        spins_remaining: self.spins_remaining,
-                        stage_section: self.stage_section,
-                        exhausted: self.exhausted,
-                    });
-                } else if update.energy.current() >= self.static_data.energy_cost
-                    && (self.spins_remaining != 0
-                        || (self.static_data.is_infinite && data.inputs.secondary.is_pressed()))
-                {
-                    let new_spins_remaining = if self.static_data.is_infinite {
-                        self.spins_remaining
-                    } else {
-                        self.spins_remaining - 1
-                    };
-                    update.character = CharacterState::SpinMelee(Data {
-                        static_data: self.static_data,
-                        timer: Duration::default(),
-                        spins_remaining: new_spins_remaining,
-                        stage_section: self.stage_section,
-                        exhausted: false,
-                    });
-                    // Consumes energy if there's enough left and RMB is held down
-                    update.energy.change_by(
-                        -(self.static_data.energy_cost as i32),
-                        EnergySource::Ability,
-                    );
-                } else {
-                    // Transitions to recover section of stage
-                    update.character = CharacterState::SpinMelee(Data {
-                        static_data: self.static_data,
-                        timer: Duration::default(),
-                        spins_remaining: self.spins_remaining,
-                        stage_section: StageSection::Recover,
-                        exhausted: self.exhausted,
-                    });
+                match advance_section(self.timer, data.dt.0, self.static_data.swing_duration) {
+                    SectionProgress::Running(timer) => {
+                        if !self.static_data.is_helicopter {
+                            forward_move(data, &mut update, 0.1, self.static_data.forward_speed);
+                            handle_orientation(data, &mut update, 1.0);
+                        }
+
+                        // Swings
+                        update.character = CharacterState::SpinMelee(Data {
+                            static_data: self.static_data,
+                            timer,
+                            spins_remaining: self.spins_remaining,
+                            stage_section: self.stage_section,
+                            exhausted: true,
+                        });
+                    },
+                    SectionProgress::Finished(overshoot)
+                        if update.energy.current() >= self.static_data.energy_cost
+                            && (self.spins_remaining != 0
+                                || (self.static_data.is_infinite
+                                    && data.inputs.secondary.is_pressed())) =>
+                    {
+                        let new_spins_remaining = if self.static_data.is_infinite {
+                            self.spins_remaining
+                        } else {
+                            self.spins_remaining - 1
+                        };
+                        update.character = CharacterState::SpinMelee(Data {
+                            static_data: self.static_data,
+                            timer: overshoot,
+                            spins_remaining: new_spins_remaining,
+                            stage_section: self.stage_section,
+                            exhausted: false,
+                        });
+                        // Consumes energy if there's enough left and RMB is held down
+                        update.energy.change_by(
+                            -(self.static_data.energy_cost as i32),
+                            EnergySource::Ability,
+                        );
+                    },
+                    SectionProgress::Finished(overshoot) => {
+                        // Transitions to recover section of stage
+                        update.character = CharacterState::SpinMelee(Data {
+                            static_data: self.static_data,
+                            timer: overshoot,
+                            spins_remaining: self.spins_remaining,
+                            stage_section: StageSection::Recover,
+                            exhausted: true,
+                        });
+                    },
                 }
             },
             StageSection::Recover => {
-                if self.timer < self.static_data.recover_duration {
-                    // Recover
-                    update.character = CharacterState::SpinMelee(Data {
-                        static_data: self.static_data,
-                        timer: self
-                            .timer
-                            .checked_add(Duration::from_secs_f32(data.dt.0))
-                            .unwrap_or_default(),
-                        spins_remaining: self.spins_remaining,
-                        stage_section: self.stage_section,
-                        exhausted: self.exhausted,
-                    });
-                } else {
-                    // Done
-                    update.character = CharacterState::Wielding;
-                    // Make sure attack component is removed
-                    data.updater.remove::<Attacking>(data.entity);
+                match advance_section(self.timer, data.dt.0, self.static_data.recover_duration) {
+                    SectionProgress::Running(timer) => {
+                        // Recover
+                        update.character = CharacterState::SpinMelee(Data {
+                            static_data: self.static_data,
+                            timer,
+                            spins_remaining: self.spins_remaining,
+                            stage_section: self.stage_section,
+                            exhausted: self.exhausted,
+                        });
+                    },
+                    SectionProgress::Finished(_) => {
+                        // Done
+                        update.character = CharacterState::Wielding;
+                        // Make sure attack component is removed
+                        data.updater.remove::<Attacking>(data.entity);
+                    },
                 }
             },
             _ => {
@@ -0,0 +1,109 @@
+use super::utils::*;
+use crate::{
+    comp::{CharacterState, Energy, EnergySource, StateUpdate},
+    sys::character_behavior::{CharacterBehavior, JoinData},
+};
+
+/// How much faster than walking sprinting accelerates
+const SPRINT_EFFICIENCY: f32 = 1.5;
+/// Energy spent per second of sprinting
+const SPRINT_ENERGY_COST: f32 = 120.0;
+
+pub struct Data;
+
+impl CharacterBehavior for Data {
+    fn behavior(&self, data: &JoinData) -> StateUpdate {
+        let mut update = StateUpdate::from(data);
+
+        handle_move(data, &mut update, SPRINT_EFFICIENCY);
+        handle_jump(data, &mut update);
+        handle_wield(data, &mut update);
+        handle_climb(data, &mut update);
+        handle_dodge_input(data, &mut update);
+
+        // Stop once the key is let go, the character stops moving or leaves the
+        // ground, or there's no energy left to keep going
+        if !data.inputs.sprint.is_pressed()
+            || data.inputs.move_dir.magnitude_squared() < 0.01
+            || !data.physics.on_ground
+            || !drain_energy(&mut update.energy, data.dt.0)
+        {
+            if let CharacterState::Sprint = update.character {
+                update.character = CharacterState::Idle;
+            }
+        }
+
+        update
+    }
+
+    fn wield(&self, data: &JoinData) -> StateUpdate {
+        let mut update = StateUpdate::from(data);
+        attempt_wield(data, &mut update);
+        update
+    }
+
+    fn sit(&self, data: &JoinData) -> StateUpdate {
+        let mut update = StateUpdate::from(data);
+        attempt_sit(data, &mut update);
+        update
+    }
+
+    fn dance(&self, data: &JoinData) -> StateUpdate {
+        let mut update = StateUpdate::from(data);
+        attempt_dance(data, &mut update);
+        update
+    }
+
+    fn sneak(&self, data: &JoinData) -> StateUpdate {
+        let mut update = StateUpdate::from(data);
+        attempt_sneak(data, &mut update);
+        update
+    }
+
+    fn glide_wield(&self, data: &JoinData) -> StateUpdate {
+        let mut update = StateUpdate::from(data);
+        attempt_glide_wield(data, &mut update);
+        update
+    }
+
+    fn swap_loadout(&self, data: &JoinData) -> StateUpdate {
+        let mut update = StateUpdate::from(data);
+        attempt_swap_loadout(data, &mut update);
+        update
+    }
+}
+
+/// Spends the energy needed to sprint for `dt` seconds, returning whether
+/// there was enough of it to keep sprinting.
+pub fn drain_energy(energy: &mut Energy, dt: f32) -> bool {
+    let cost = (SPRINT_ENERGY_COST * dt).round().max(1.0) as i32;
+    energy.current() > 0
+        && energy
+            .try_change_by(-cost, EnergySource::Sprint)
+            .or_else(|_| energy.try_change_by(-(energy.current() as i32), EnergySource::Sprint))
+            .is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sprinting_drains_energy() {
+        let mut energy = Energy::new(1000);
+        assert!(drain_energy(&mut energy, 1.0 / 30.0));
+        assert_eq!(energy.current(), 996);
+        assert!(drain_energy(&mut energy, 1.0));
+        assert_eq!(energy.current(), 876);
+    }
+
+    #[test]
+    fn sprinting_stops_at_zero_energy() {
+        let mut energy = Energy::new(1000);
+        energy.set_to(3, EnergySource::Unknown);
+        // The last of the energy is used up rather than left unspendable
+        assert!(drain_energy(&mut energy, 1.0 / 30.0));
+        assert_eq!(energy.current(), 0);
+        assert!(!drain_energy(&mut energy, 1.0 / 30.0));
+    }
+}
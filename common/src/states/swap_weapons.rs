@@ -0,0 +1,219 @@
+use super::utils::*;
+use crate::{
+    comp::{CharacterState, StateUpdate},
+    sys::character_behavior::{CharacterBehavior, JoinData},
+};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// How long swapping weapons takes when done from the wielding state
+pub const DEFAULT_DURATION: Duration = Duration::from_millis(400);
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize, Eq, Hash)]
+pub struct Data {
+    /// How long the swap takes
+    pub duration: Duration,
+    /// How long the swap has been going on for
+    pub timer: Duration,
+}
+
+impl Data {
+    /// The state after `dt` has passed, or `None` once the swap is done
+    pub fn advance(&self, dt: Duration) -> Option<Self> {
+        if self.timer < self.duration {
+            Some(Self {
+                timer: self.timer + dt,
+                ..*self
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl CharacterBehavior for Data {
+    fn behavior(&self, data: &JoinData) -> StateUpdate {
+        let mut update = StateUpdate::from(data);
+
+        // Attacks can't be started while the weapons are being swapped
+        handle_move(&data, &mut update, 0.7);
+        handle_jump(&data, &mut update);
+
+        match self.advance(Duration::from_secs_f32(data.dt.0)) {
+            Some(next) => update.character = CharacterState::SwapWeapons(next),
+            None => {
+                update.swap_loadout = true;
+                update.character = CharacterState::Wielding;
+            },
+        }
+
+        update
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        comp::{
+            humanoid, Body, ControlAction, Controller, Energy, ItemConfig, Loadout, Ori,
+            PhysicsState, Pos, Stats, Vel,
+        },
+        state::State,
+        sync::WorldSyncExt,
+        util::Dir,
+        LoadoutBuilder,
+    };
+    use specs::{Builder, Entity, WorldExt};
+    use vek::Vec3;
+
+    /// Creates a wielding humanoid holding the given items and returns it
+    fn wielder(
+        state: &mut State,
+        active_item: ItemConfig,
+        second_item: Option<ItemConfig>,
+    ) -> Entity {
+        let body = Body::Humanoid(humanoid::Body::random());
+        state
+            .ecs_mut()
+            .create_entity_synced()
+            .with(Pos(Vec3::zero()))
+            .with(Vel::default())
+            .with(Ori(Dir::new(Vec3::unit_y())))
+            .with(Energy::new(1000))
+            .with(Controller::default())
+            .with(Stats::new("Test".to_string(), body))
+            .with(body)
+            .with(PhysicsState::default())
+            .with(
+                LoadoutBuilder::new()
+                    .active_item(Some(active_item))
+                    .second_item(second_item)
+                    .build(),
+            )
+            .with(CharacterState::Wielding)
+            .build()
+    }
+
+    fn character_state(state: &State, entity: Entity) -> Option<CharacterState> {
+        state
+            .ecs()
+            .read_storage::<CharacterState>()
+            .get(entity)
+            .cloned()
+    }
+
+    fn weapons(state: &State, entity: Entity) -> (Option<ItemConfig>, Option<ItemConfig>) {
+        let loadouts = state.ecs().read_storage::<Loadout>();
+        let loadout = loadouts.get(entity).unwrap();
+        (loadout.active_item.clone(), loadout.second_item.clone())
+    }
+
+    fn tick(state: &mut State) { state.tick(Duration::from_millis(100), |_| {}, false); }
+
+    #[test]
+    fn slots_exchanged_after_completion() {
+        let sword = LoadoutBuilder::default_item_config_from_str(
+            "common.items.weapons.sword.zweihander_sword_0",
+        );
+        let axe = LoadoutBuilder::default_item_config_from_str(
+            "common.items.weapons.axe.bloodsteel_axe-0",
+        );
+        let mut state = State::default();
+        let entity = wielder(&mut state, sword.clone(), Some(axe.clone()));
+
+        state
+            .ecs()
+            .write_storage::<Controller>()
+            .get_mut(entity)
+            .unwrap()
+            .actions
+            .push(ControlAction::SwapLoadout);
+        tick(&mut state);
+        assert!(matches!(
+            character_state(&state, entity),
+            Some(CharacterState::SwapWeapons(_))
+        ));
+
+        // Nothing changes until the swap is done
+        while let Some(CharacterState::SwapWeapons(_)) = character_state(&state, entity) {
+            assert_eq!(
+                weapons(&state, entity),
+                (Some(sword.clone()), Some(axe.clone()))
+            );
+            tick(&mut state);
+        }
+
+        assert_eq!(
+            character_state(&state, entity),
+            Some(CharacterState::Wielding)
+        );
+        assert_eq!(weapons(&state, entity), (Some(axe), Some(sword)));
+    }
+
+    #[test]
+    fn nothing_to_swap_to() {
+        let sword = LoadoutBuilder::default_item_config_from_str(
+            "common.items.weapons.sword.zweihander_sword_0",
+        );
+        let mut state = State::default();
+        let entity = wielder(&mut state, sword.clone(), None);
+
+        state
+            .ecs()
+            .write_storage::<Controller>()
+            .get_mut(entity)
+            .unwrap()
+            .actions
+            .push(ControlAction::SwapLoadout);
+        tick(&mut state);
+
+        assert_eq!(
+            character_state(&state, entity),
+            Some(CharacterState::Wielding)
+        );
+        assert_eq!(weapons(&state, entity), (Some(sword), None));
+    }
+
+    #[test]
+    fn off_hand_dagger_drawn_by_ability() {
+        let shield =
+            LoadoutBuilder::default_item_config_from_str("common.items.weapons.shield.shield_1");
+        let dagger = LoadoutBuilder::default_item_config_from_str(
+            "common.items.weapons.dagger.starter_dagger",
+        );
+        let mut state = State::default();
+        let entity = wielder(&mut state, shield.clone(), Some(dagger.clone()));
+
+        state
+            .ecs()
+            .write_storage::<Controller>()
+            .get_mut(entity)
+            .unwrap()
+            .inputs
+            .secondary
+            .set_state(true);
+        state.tick(Duration::from_millis(50), |_| {}, false);
+        assert!(matches!(
+            character_state(&state, entity),
+            Some(CharacterState::SwapWeapons(_))
+        ));
+        state
+            .ecs()
+            .write_storage::<Controller>()
+            .get_mut(entity)
+            .unwrap()
+            .inputs
+            .secondary
+            .set_state(false);
+        for _ in 0..5 {
+            state.tick(Duration::from_millis(50), |_| {}, false);
+        }
+
+        assert_eq!(
+            character_state(&state, entity),
+            Some(CharacterState::Wielding)
+        );
+        assert_eq!(weapons(&state, entity), (Some(dagger), Some(shield)));
+    }
+}
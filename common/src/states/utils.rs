@@ -1,20 +1,24 @@
 use crate::{
     comp::{
         item::{Hands, ItemKind, Tool},
-        Body, CharacterState, StateUpdate,
+        Body, CharacterAbility, CharacterState, Knockback, KnockbackImmunity, LastKnockback,
+        Loadout, StateUpdate,
     },
-    event::LocalEvent,
+    event::{LocalEvent, ServerEvent},
     states::*,
+    sync::Uid,
     sys::{character_behavior::JoinData, phys::GRAVITY},
     util::Dir,
 };
+use hashbrown::HashSet;
 use serde::{Deserialize, Serialize};
+use specs::Entity as EcsEntity;
+use std::{collections::VecDeque, time::Duration};
 use vek::*;
 
 pub const MOVEMENT_THRESHOLD_VEL: f32 = 3.0;
 const BASE_HUMANOID_AIR_ACCEL: f32 = 8.0;
 const BASE_HUMANOID_WATER_ACCEL: f32 = 150.0;
-const BASE_HUMANOID_WATER_SPEED: f32 = 180.0;
 // const BASE_HUMANOID_CLIMB_ACCEL: f32 = 10.0;
 // const ROLL_SPEED: f32 = 17.0;
 // const CHARGE_SPEED: f32 = 20.0;
@@ -68,6 +72,9 @@ impl Body {
 
 /// Handles updating `Components` to move player based on state of `JoinData`
 pub fn handle_move(data: &JoinData, update: &mut StateUpdate, efficiency: f32) {
+    if data.is_stunned() {
+        return;
+    }
     if let Some(depth) = data.physics.in_fluid {
         swim_move(data, update, efficiency, depth);
     } else {
@@ -79,13 +86,17 @@ pub fn handle_move(data: &JoinData, update: &mut StateUpdate, efficiency: f32) {
 #[allow(clippy::assign_op_pattern)] // TODO: Pending review in #587
 fn basic_move(data: &JoinData, update: &mut StateUpdate, efficiency: f32) {
     let accel = if data.physics.on_ground {
-        data.body.base_accel()
+        data.body.base_accel() * ground_speed_mul(data)
     } else {
         BASE_HUMANOID_AIR_ACCEL
     };
 
-    update.vel.0 =
-        update.vel.0 + Vec2::broadcast(data.dt.0) * data.inputs.move_dir * accel * efficiency;
+    update.vel.0 = update.vel.0
+        + Vec2::broadcast(data.dt.0)
+            * data.inputs.move_dir
+            * below_move_speed_cap(data, update, accel)
+            * efficiency
+            * data.movement_speed();
 
     handle_orientation(data, update, data.body.base_ori_rate());
 }
@@ -93,18 +104,36 @@ fn basic_move(data: &JoinData, update: &mut StateUpdate, efficiency: f32) {
 /// Similar to basic_move function, but with forced forward movement
 pub fn forward_move(data: &JoinData, update: &mut StateUpdate, efficiency: f32, forward: f32) {
     let accel = if data.physics.on_ground {
-        data.body.base_accel()
+        data.body.base_accel() * ground_speed_mul(data)
     } else {
         BASE_HUMANOID_AIR_ACCEL
     };
 
     update.vel.0 += Vec2::broadcast(data.dt.0)
-        * accel
+        * below_move_speed_cap(data, update, accel)
+        * data.movement_speed()
         * (data.inputs.move_dir * efficiency + (*update.ori.0).xy() * forward);
 
     handle_orientation(data, update, data.body.base_ori_rate() * efficiency);
 }
 
+/// `accel`, or nothing once the entity moves as fast as the server lets
+/// entities move by themselves
+fn below_move_speed_cap(data: &JoinData, update: &StateUpdate, accel: f32) -> f32 {
+    if update.vel.0.xy().magnitude_squared() < data.gameplay.max_move_speed.powi(2) {
+        accel
+    } else {
+        0.0
+    }
+}
+
+/// How the surface the entity stands on affects its movement speed
+fn ground_speed_mul(data: &JoinData) -> f32 {
+    data.physics
+        .ground_kind
+        .map_or(1.0, |kind| kind.movement_speed_mul())
+}
+
 pub fn handle_orientation(data: &JoinData, update: &mut StateUpdate, rate: f32) {
     // Set direction based on move direction
     let ori_dir = if update.character.is_block() || update.character.is_attack() {
@@ -124,7 +153,7 @@ fn swim_move(data: &JoinData, update: &mut StateUpdate, efficiency: f32, depth:
     // Update velocity
     update.vel.0 += Vec2::broadcast(data.dt.0)
         * data.inputs.move_dir
-        * if update.vel.0.magnitude_squared() < BASE_HUMANOID_WATER_SPEED.powf(2.0) {
+        * if update.vel.0.magnitude_squared() < data.gameplay.max_swim_speed.powf(2.0) {
             BASE_HUMANOID_WATER_ACCEL
         } else {
             0.0
@@ -137,12 +166,12 @@ fn swim_move(data: &JoinData, update: &mut StateUpdate, efficiency: f32, depth:
     if data.inputs.swimup.is_pressed() {
         update.vel.0.z = (update.vel.0.z
             + data.dt.0 * GRAVITY * 4.0 * depth.clamped(0.0, 1.0).powf(3.0))
-        .min(BASE_HUMANOID_WATER_SPEED);
+        .min(data.gameplay.max_swim_speed);
     }
     // Swim
     if data.inputs.swimdown.is_pressed() {
         update.vel.0.z =
-            (update.vel.0.z + data.dt.0 * GRAVITY * -3.5).min(BASE_HUMANOID_WATER_SPEED);
+            (update.vel.0.z + data.dt.0 * GRAVITY * -3.5).min(data.gameplay.max_swim_speed);
     }
 }
 
@@ -187,11 +216,25 @@ pub fn attempt_sneak(data: &JoinData, update: &mut StateUpdate) {
     }
 }
 
+/// Checks that player can start sprinting and updates `CharacterState` if so.
+/// Only called from states that aren't using an ability.
+pub fn handle_sprint(data: &JoinData, update: &mut StateUpdate) {
+    if data.inputs.sprint.is_pressed()
+        && !data.is_stunned()
+        && data.physics.on_ground
+        && data.physics.in_fluid.is_none()
+        && data.inputs.move_dir.magnitude_squared() > 0.01
+        && update.energy.current() > 0
+    {
+        update.character = CharacterState::Sprint;
+    }
+}
+
 /// Checks that player can `Climb` and updates `CharacterState` if so
 pub fn handle_climb(data: &JoinData, update: &mut StateUpdate) {
-    if data.inputs.climb.is_some()
-        && data.physics.on_wall.is_some()
-        && !data.physics.on_ground
+    if !data.is_stunned()
+        && data.inputs.climb.is_some()
+        && climb::climbable_wall(data.physics).is_some()
         && !data
             .physics
             .in_fluid
@@ -229,7 +272,8 @@ pub fn attempt_glide_wield(data: &JoinData, update: &mut StateUpdate) {
 
 /// Checks that player can jump and sends jump event if so
 pub fn handle_jump(data: &JoinData, update: &mut StateUpdate) {
-    if data.inputs.jump.is_pressed()
+    if !data.is_stunned()
+        && data.inputs.jump.is_pressed()
         && data.physics.on_ground
         && !data
             .physics
@@ -243,6 +287,25 @@ pub fn handle_jump(data: &JoinData, update: &mut StateUpdate) {
     }
 }
 
+/// Puts the character into the state for `ability`, applying the effects the
+/// ability has as it starts
+fn activate_ability(
+    data: &JoinData,
+    update: &mut StateUpdate,
+    ability: &CharacterAbility,
+    key: AbilityKey,
+) {
+    update.character = (ability, key).into();
+    // Finishers deal extra damage for the combo they end
+    if let (Some(damage), CharacterState::BasicMelee(melee)) = (
+        ability.finisher_damage(data.character.combo()),
+        &mut update.character,
+    ) {
+        melee.base_healthchange = -(damage as i32);
+    }
+    update.apply_activation_effects(data.entity, *data.uid, ability.activation_effects());
+}
+
 /// Will attempt to go into `loadout.active_item.ability1`
 pub fn handle_ability1_input(data: &JoinData, update: &mut StateUpdate) {
     if data.inputs.primary.is_pressed() {
@@ -251,9 +314,13 @@ pub fn handle_ability1_input(data: &JoinData, update: &mut StateUpdate) {
             .active_item
             .as_ref()
             .and_then(|i| i.ability1.as_ref())
-            .filter(|ability| ability.requirements_paid(data, update))
+            .filter(|ability| {
+                ability
+                    .requirements_paid(AbilityKey::Mouse1, data, update)
+                    .is_ok()
+            })
         {
-            update.character = (ability, AbilityKey::Mouse1).into();
+            activate_ability(data, update, ability, AbilityKey::Mouse1);
         }
     }
 }
@@ -281,9 +348,13 @@ pub fn handle_ability2_input(data: &JoinData, update: &mut StateUpdate) {
                     .active_item
                     .as_ref()
                     .and_then(|i| i.ability2.as_ref())
-                    .filter(|ability| ability.requirements_paid(data, update))
+                    .filter(|ability| {
+                        ability
+                            .requirements_paid(AbilityKey::Mouse2, data, update)
+                            .is_ok()
+                    })
                 {
-                    update.character = (ability, AbilityKey::Mouse2).into();
+                    activate_ability(data, update, ability, AbilityKey::Mouse2);
                 }
             },
             (_, Some(Hands::OneHand)) => {
@@ -292,9 +363,13 @@ pub fn handle_ability2_input(data: &JoinData, update: &mut StateUpdate) {
                     .second_item
                     .as_ref()
                     .and_then(|i| i.ability2.as_ref())
-                    .filter(|ability| ability.requirements_paid(data, update))
+                    .filter(|ability| {
+                        ability
+                            .requirements_paid(AbilityKey::Mouse2, data, update)
+                            .is_ok()
+                    })
                 {
-                    update.character = (ability, AbilityKey::Mouse2).into();
+                    activate_ability(data, update, ability, AbilityKey::Mouse2);
                 }
             },
             (_, _) => {},
@@ -310,9 +385,13 @@ pub fn handle_ability3_input(data: &JoinData, update: &mut StateUpdate) {
             .active_item
             .as_ref()
             .and_then(|i| i.ability3.as_ref())
-            .filter(|ability| ability.requirements_paid(data, update))
+            .filter(|ability| {
+                ability
+                    .requirements_paid(AbilityKey::Skill1, data, update)
+                    .is_ok()
+            })
         {
-            update.character = (ability, AbilityKey::Skill1).into();
+            activate_ability(data, update, ability, AbilityKey::Skill1);
         }
     }
 }
@@ -326,15 +405,19 @@ pub fn handle_dodge_input(data: &JoinData, update: &mut StateUpdate) {
             .active_item
             .as_ref()
             .and_then(|i| i.dodge_ability.as_ref())
-            .filter(|ability| ability.requirements_paid(data, update))
+            .filter(|ability| {
+                ability
+                    .requirements_paid(AbilityKey::Dodge, data, update)
+                    .is_ok()
+            })
         {
             if data.character.is_wield() {
-                update.character = (ability, AbilityKey::Dodge).into();
+                activate_ability(data, update, ability, AbilityKey::Dodge);
                 if let CharacterState::Roll(roll) = &mut update.character {
                     roll.was_wielded = true;
                 }
             } else {
-                update.character = (ability, AbilityKey::Dodge).into();
+                activate_ability(data, update, ability, AbilityKey::Dodge);
             }
         }
     }
@@ -364,6 +447,105 @@ pub fn ability_key_is_pressed(data: &JoinData, ability_key: AbilityKey) -> bool
     }
 }
 
+/// Checks whether a target of radius `rad_b` at `pos_b` is horizontally within
+/// `angle` of the direction `ori` from `pos`. The cone is widened by however
+/// much of the target's width pokes into it. Cones of `angle` π or more, and
+/// targets overlapping `pos`, are hit whatever the direction.
+pub fn in_cone(pos: Vec3<f32>, ori: Vec2<f32>, angle: f32, pos_b: Vec3<f32>, rad_b: f32) -> bool {
+    let pos2 = Vec2::from(pos);
+    let pos_b2 = Vec2::from(pos_b);
+    angle >= std::f32::consts::PI
+        || pos2.distance_squared(pos_b2) <= rad_b.powi(2)
+        || ori.angle_between(pos_b2 - pos2) < angle + (rad_b / pos2.distance(pos_b2)).atan()
+}
+
+/// Collects the targets within `range` of `pos` and inside the cone of
+/// `angle` either side of `ori`, given as their id, position and radius.
+/// Each target is returned once, in the order first seen, even if it appears
+/// among the candidates several times.
+pub fn cone_hit_targets(
+    pos: Vec3<f32>,
+    ori: Vec2<f32>,
+    range: f32,
+    angle: f32,
+    candidates: impl IntoIterator<Item = (Uid, Vec3<f32>, f32)>,
+) -> Vec<Uid> {
+    let mut seen = HashSet::new();
+    candidates
+        .into_iter()
+        .filter(|(_, pos_b, rad_b)| {
+            pos.distance_squared(*pos_b) < (range + rad_b).powi(2)
+                && in_cone(pos, ori, angle, *pos_b, *rad_b)
+        })
+        .filter_map(|(uid, _, _)| Some(uid).filter(|uid| seen.insert(*uid)))
+        .collect()
+}
+
+/// The target of a knockback, along with what decides how much of it lands
+pub struct KnockbackTarget<'a> {
+    pub entity: EcsEntity,
+    pub loadout: Option<&'a Loadout>,
+    pub last_knockback: Option<&'a LastKnockback>,
+    pub immunity: &'a KnockbackImmunity,
+    /// The current time, to check `last_knockback` against `immunity`
+    pub time: f64,
+}
+
+/// Knocks `target` back in `dir` by queueing the impulse onto
+/// `server_events`, and returns it. Every ability applies knockback through
+/// this, so that the target's [`Loadout::knockback_resistance`] and
+/// [`KnockbackImmunity`] are honoured the same way everywhere. Nothing is
+/// queued if the target is immune or fully resists the knockback.
+pub fn apply_knockback(
+    server_events: &mut VecDeque<ServerEvent>,
+    target: &KnockbackTarget,
+    dir: Dir,
+    knockback: Knockback,
+) -> Option<Vec3<f32>> {
+    if knockback.is_zero() || !target.immunity.allows(target.last_knockback, target.time) {
+        return None;
+    }
+    let resistance = target
+        .loadout
+        .map_or(0.0, |loadout| loadout.knockback_resistance());
+    if resistance >= 1.0 {
+        return None;
+    }
+
+    let impulse = knockback.impulse(*dir) * (1.0 - resistance);
+
+    server_events.push_back(ServerEvent::Knockback {
+        entity: target.entity,
+        impulse,
+    });
+    Some(impulse)
+}
+
+/// How far through a timed section of an ability a state is after a tick
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SectionProgress {
+    /// The section is still going, with its timer advanced
+    Running(Duration),
+    /// The section ended during the tick, with how much of the tick was left
+    /// over to count towards the next section
+    Finished(Duration),
+}
+
+/// Advances the `timer` of a section lasting `duration` by `dt` seconds.
+///
+/// Sections end on the tick that reaches their duration, and the time left
+/// over is carried into the next one, so that each section starts at the
+/// same moment whatever the tick rate.
+pub fn advance_section(timer: Duration, dt: f32, duration: Duration) -> SectionProgress {
+    let timer = timer
+        .checked_add(Duration::from_secs_f32(dt.max(0.0)))
+        .unwrap_or_default();
+    match timer.checked_sub(duration) {
+        Some(overshoot) => SectionProgress::Finished(overshoot),
+        None => SectionProgress::Running(timer),
+    }
+}
+
 /// Determines what portion a state is in. Used in all attacks (eventually). Is
 /// used to control aspects of animation code, as well as logic within the
 /// character states.
@@ -385,3 +567,189 @@ pub enum AbilityKey {
     Skill1,
     Dodge,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::comp::{item::ItemDef, Item, KnockbackKind};
+    use std::sync::Arc;
+
+    fn hits(candidates: Vec<(Uid, Vec3<f32>, f32)>) -> Vec<Uid> {
+        cone_hit_targets(
+            Vec3::zero(),
+            Vec2::unit_y(),
+            4.0,
+            45_f32.to_radians(),
+            candidates,
+        )
+    }
+
+    #[test]
+    fn target_at_cone_edge() {
+        // Centre just outside the cone, but wide enough to overlap its edge
+        let edge = Vec3::new(2.2, 2.0, 0.0);
+        assert_eq!(hits(vec![(Uid(1), edge, 0.5)]), vec![Uid(1)]);
+        assert!(hits(vec![(Uid(1), edge, 0.0)]).is_empty());
+    }
+
+    #[test]
+    fn target_behind_missed() {
+        assert!(hits(vec![(Uid(1), Vec3::new(0.0, -2.0, 0.0), 0.5)]).is_empty());
+    }
+
+    #[test]
+    fn overlapping_target_hit() {
+        assert_eq!(hits(vec![(Uid(1), Vec3::unit_z(), 0.5)]), vec![Uid(1)]);
+    }
+
+    #[test]
+    fn duplicate_candidate_hit_once() {
+        let pos = Vec3::new(0.0, 2.0, 0.0);
+        assert_eq!(
+            hits(vec![
+                (Uid(1), pos, 0.5),
+                (Uid(2), pos + Vec3::unit_x(), 0.5),
+                (Uid(1), pos, 0.5),
+            ]),
+            vec![Uid(1), Uid(2)]
+        );
+    }
+
+    fn knockback_target<'a>(
+        loadout: Option<&'a Loadout>,
+        last_knockback: Option<&'a LastKnockback>,
+        immunity: &'a KnockbackImmunity,
+    ) -> KnockbackTarget<'a> {
+        use specs::{Builder, WorldExt};
+        KnockbackTarget {
+            entity: specs::World::new().create_entity().build(),
+            loadout,
+            last_knockback,
+            immunity,
+            time: 10.0,
+        }
+    }
+
+    fn armor(knockback_resistance: f32) -> Option<Item> {
+        let def = ron::de::from_str::<ItemDef>(&format!(
+            "(name: \"Test\", description: \"\", kind: Armor((kind: Chest(\"Test\"), stats: \
+             (protection: Normal(0.0), knockback_resistance: {}))), quality: Common)",
+            knockback_resistance
+        ))
+        .unwrap();
+        Some(Item::new(Arc::new(def)))
+    }
+
+    #[test]
+    fn knockback_resistance_honored() {
+        let immunity = KnockbackImmunity::default();
+        let knockback = Knockback {
+            horizontal: 20.0,
+            vertical: 5.0,
+            kind: KnockbackKind::Away,
+        };
+        let dir = Dir::new(Vec3::unit_x());
+        let mut events = VecDeque::new();
+
+        let unarmored = apply_knockback(
+            &mut events,
+            &knockback_target(None, None, &immunity),
+            dir,
+            knockback,
+        )
+        .unwrap();
+        assert!((unarmored - Vec3::new(20.0, 0.0, 5.0)).magnitude() < 0.001);
+
+        let loadout = Loadout {
+            chest: armor(0.75),
+            ..Loadout::default()
+        };
+        let armored = apply_knockback(
+            &mut events,
+            &knockback_target(Some(&loadout), None, &immunity),
+            dir,
+            knockback,
+        )
+        .unwrap();
+        assert!((armored - unarmored * 0.25).magnitude() < 0.001);
+        assert_eq!(events.len(), 2);
+
+        // Fully resisted knockback doesn't reach the target at all
+        let loadout = Loadout {
+            chest: armor(1.0),
+            ..Loadout::default()
+        };
+        assert_eq!(
+            apply_knockback(
+                &mut events,
+                &knockback_target(Some(&loadout), None, &immunity),
+                dir,
+                knockback,
+            ),
+            None
+        );
+        assert_eq!(events.len(), 2);
+    }
+
+    #[test]
+    fn knockback_immunity_honored() {
+        let immunity = KnockbackImmunity { duration: 0.5 };
+        let knockback = Knockback::from_horizontal(20.0);
+        let dir = Dir::new(Vec3::unit_y());
+        let mut events = VecDeque::new();
+
+        // Knocked back 0.2s ago
+        let recent = LastKnockback(9.8);
+        assert_eq!(
+            apply_knockback(
+                &mut events,
+                &knockback_target(None, Some(&recent), &immunity),
+                dir,
+                knockback,
+            ),
+            None
+        );
+        assert!(events.is_empty());
+
+        // Long enough ago to be knocked back again
+        let old = LastKnockback(9.0);
+        assert!(apply_knockback(
+            &mut events,
+            &knockback_target(None, Some(&old), &immunity),
+            dir,
+            knockback,
+        )
+        .is_some());
+        assert!(matches!(
+            events.pop_front(),
+            Some(ServerEvent::Knockback { .. })
+        ));
+    }
+
+    #[test]
+    fn section_ends_on_time_with_varied_ticks() {
+        let buildup = Duration::from_millis(350);
+        let tick_patterns: [&[f32]; 3] =
+            [&[0.1], &[1.0 / 30.0], &[0.016, 0.05, 0.033, 0.09, 0.004]];
+        for dts in tick_patterns.iter() {
+            let mut timer = Duration::default();
+            let mut elapsed = 0.0;
+            let overshoot = dts
+                .iter()
+                .cycle()
+                .find_map(|&dt| {
+                    elapsed += dt;
+                    match advance_section(timer, dt, buildup) {
+                        SectionProgress::Running(next) => {
+                            timer = next;
+                            None
+                        },
+                        SectionProgress::Finished(overshoot) => Some(overshoot),
+                    }
+                })
+                .unwrap();
+            // The swing starts exactly when the buildup runs out
+            assert!((elapsed - overshoot.as_secs_f32() - buildup.as_secs_f32()).abs() < 1e-4);
+        }
+    }
+}
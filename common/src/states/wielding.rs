@@ -1,8 +1,9 @@
-use super::utils::*;
+use super::{swap_weapons, utils::*};
 use crate::{
     comp::{CharacterState, StateUpdate},
     sys::character_behavior::{CharacterBehavior, JoinData},
 };
+use std::time::Duration;
 
 pub struct Data;
 
@@ -53,7 +54,13 @@ impl CharacterBehavior for Data {
 
     fn swap_loadout(&self, data: &JoinData) -> StateUpdate {
         let mut update = StateUpdate::from(data);
-        attempt_swap_loadout(data, &mut update);
+        // Swapping mid-fight takes a moment, unlike with the weapons sheathed
+        if data.loadout.second_item.is_some() {
+            update.character = CharacterState::SwapWeapons(swap_weapons::Data {
+                duration: swap_weapons::DEFAULT_DURATION,
+                timer: Duration::default(),
+            });
+        }
         update
     }
 }
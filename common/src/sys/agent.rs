@@ -186,12 +186,21 @@ impl<'a> System<'a> for Sys {
             const AVG_FOLLOW_DIST: f32 = 6.0;
             const MAX_FOLLOW_DIST: f32 = 12.0;
             const MAX_CHASE_DIST: f32 = 18.0;
-            const LISTEN_DIST: f32 = 16.0;
-            const SEARCH_DIST: f32 = 48.0;
+            const BASE_LISTEN_DIST: f32 = 16.0;
+            const BASE_SEARCH_DIST: f32 = 48.0;
             const SIGHT_DIST: f32 = 80.0;
             const MIN_ATTACK_DIST: f32 = 2.0;
             const MAX_FLEE_DIST: f32 = 20.0;
 
+            // NPCs in more dangerous biomes are quicker to notice targets
+            let alertness = terrain
+                .get_key(terrain.pos_key(pos.0.map(|e| e.floor() as i32)))
+                .map_or(1.0, |chunk| {
+                    0.75 + chunk.meta().biome().danger_level() * 0.5
+                });
+            let listen_dist = BASE_LISTEN_DIST * alertness;
+            let search_dist = BASE_SEARCH_DIST * alertness;
+
             let scale = scales.get(entity).map(|s| s.0).unwrap_or(1.0);
 
             // This controls how picky NPCs are about their pathfinding. Giants are larger
@@ -560,11 +569,11 @@ impl<'a> System<'a> for Sys {
                 let closest_entity = (&entities, &positions, &stats, alignments.maybe())
                     .join()
                     .filter(|(e, e_pos, e_stats, e_alignment)| {
-                        ((e_pos.0.distance_squared(pos.0) < SEARCH_DIST.powf(2.0) &&
+                        ((e_pos.0.distance_squared(pos.0) < search_dist.powf(2.0) &&
                             // Within our view
                             (e_pos.0 - pos.0).try_normalized().map(|v| v.dot(*inputs.look_dir) > 0.15).unwrap_or(true))
                                 // Within listen distance
-                                || e_pos.0.distance_squared(pos.0) < LISTEN_DIST.powf(2.0))
+                                || e_pos.0.distance_squared(pos.0) < listen_dist.powf(2.0))
                             && *e != entity
                             && !e_stats.is_dead
                             && alignment
@@ -5,10 +5,12 @@ use crate::{
     },
     event::{EventBus, ServerEvent},
     state::{DeltaTime, Time},
+    states::utils::cone_hit_targets,
     sync::{Uid, UidAllocator},
 };
+use hashbrown::HashMap;
 use specs::{saveload::MarkerAllocator, Entities, Join, Read, ReadStorage, System, WriteStorage};
-use std::time::Duration;
+use std::{f32::consts::PI, time::Duration};
 use vek::*;
 
 pub const BLOCK_ANGLE: f32 = 180.0;
@@ -114,7 +116,10 @@ impl<'a> System<'a> for Sys {
                 continue;
             };
 
-            // Go through all other effectable entities
+            // Go through all other effectable entities, both where they are now and where
+            // they were last tick
+            let mut infos = HashMap::new();
+            let mut candidates = Vec::new();
             for (
                 b,
                 uid_b,
@@ -144,17 +149,62 @@ impl<'a> System<'a> for Sys {
                     continue;
                 }
 
+                if entity == b || stats_b.is_dead {
+                    continue;
+                }
+
                 // Scales
                 let scale_b = scale_b_maybe.map_or(1.0, |s| s.0);
                 let rad_b = body_b.radius() * scale_b;
                 let height_b = body_b.height() * scale_b;
 
+                infos.insert(
+                    *uid_b,
+                    (b, uid_b, pos_b, ori_b, character_b, rad_b, height_b),
+                );
+                candidates.push((*uid_b, pos_b.0, rad_b + height_b));
+                if let Some(last_pos_b) = last_pos_b_maybe {
+                    candidates.push((*uid_b, (last_pos_b.0).0, rad_b + height_b));
+                }
+            }
+
+            // Narrow the candidates down to those within the beam's reach horizontally. A
+            // beam pointing steeply up or down spreads all around its origin.
+            let ori2 = Vec2::from(*ori.0);
+            let spread =
+                if beam_segment.angle < PI / 2.0 && ori2.magnitude() > beam_segment.angle.sin() {
+                    (beam_segment.angle.sin() / ori2.magnitude()).asin()
+                } else {
+                    PI
+                };
+            let targets = cone_hit_targets(pos.0, ori2, frame_end_dist, spread, candidates);
+
+            for (b, uid_b, pos_b, ori_b, character_b, rad_b, height_b) in targets
+                .into_iter()
+                .filter_map(|uid_b| infos.get(&uid_b).copied())
+            {
                 // Check if it is a hit
-                let hit = entity != b
-                    && !stats_b.is_dead
-                    // Collision shapes
-                    && (sphere_wedge_cylinder_collision(pos.0, frame_start_dist, frame_end_dist, *ori.0, beam_segment.angle, pos_b.0, rad_b, height_b)
-                    || last_pos_b_maybe.map_or(false, |pos_maybe| {sphere_wedge_cylinder_collision(pos.0, frame_start_dist, frame_end_dist, *ori.0, beam_segment.angle, (pos_maybe.0).0, rad_b, height_b)}));
+                let hit = sphere_wedge_cylinder_collision(
+                    pos.0,
+                    frame_start_dist,
+                    frame_end_dist,
+                    *ori.0,
+                    beam_segment.angle,
+                    pos_b.0,
+                    rad_b,
+                    height_b,
+                ) || last_positions.get(b).map_or(false, |last_pos_b| {
+                    sphere_wedge_cylinder_collision(
+                        pos.0,
+                        frame_start_dist,
+                        frame_end_dist,
+                        *ori.0,
+                        beam_segment.angle,
+                        (last_pos_b.0).0,
+                        rad_b,
+                        height_b,
+                    )
+                });
 
                 if hit {
                     // See if entities are in the same group
@@ -189,11 +239,15 @@ impl<'a> System<'a> for Sys {
                     let mut damage = Damage {
                         healthchange,
                         source,
+                        kind: beam_segment.damage_kind,
                     };
 
-                    let block = character_b.map(|c_b| c_b.is_block()).unwrap_or(false)
-                        // TODO: investigate whether this calculation is proper for beams
-                        && ori_b.0.angle_between(pos.0 - pos_b.0) < BLOCK_ANGLE.to_radians() / 2.0;
+                    let block = character_b
+                        .and_then(|c_b| c_b.block_strength())
+                        .filter(|_| {
+                            // TODO: investigate whether this calculation is proper for beams
+                            ori_b.0.angle_between(pos.0 - pos_b.0) < BLOCK_ANGLE.to_radians() / 2.0
+                        });
 
                     if let Some(loadout) = loadouts.get(b) {
                         damage.modify_damage(block, loadout);
@@ -113,6 +113,8 @@ impl<'a> System<'a> for Sys {
                                     );
                                 },
                             },
+                            // Read by movement and damage handling instead
+                            BuffEffect::MovementSpeed(_) | BuffEffect::DamageReduction(_) => {},
                         };
                     }
                 }
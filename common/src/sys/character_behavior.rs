@@ -1,23 +1,29 @@
 use crate::{
     comp::{
-        Attacking, Beam, Body, CharacterState, ControlAction, Controller, ControllerInputs, Energy,
-        Loadout, Mounting, Ori, PhysicsState, Pos, StateUpdate, Stats, Vel,
+        AbilityCooldowns, Attacking, Beam, Body, Buffs, CharacterState, ControlAction, Controller,
+        ControllerInputs, Energy, Loadout, Mounting, Ori, PhysicsState, Pos, StateUpdate, Stats,
+        Stunned, Vel,
     },
     event::{EventBus, LocalEvent, ServerEvent},
+    gameplay::GameplaySettings,
     metrics::SysMetrics,
     span,
-    state::DeltaTime,
-    states,
+    state::{DeltaTime, Time},
+    states::{self, grapple},
     sync::{Uid, UidAllocator},
+    terrain::TerrainGrid,
 };
 
+use hashbrown::HashMap;
 use specs::{
     hibitset,
+    saveload::MarkerAllocator,
     storage::{PairedStorage, SequentialRestriction},
     Entities, Entity, FlaggedStorage, Join, LazyUpdate, Read, ReadExpect, ReadStorage, System,
     WriteStorage,
 };
 use specs_idvs::IdvStorage;
+use vek::*;
 
 // use std::collections::VecDeque;
 
@@ -56,6 +62,7 @@ pub struct JoinData<'a> {
     pub vel: &'a Vel,
     pub ori: &'a Ori,
     pub dt: &'a DeltaTime,
+    pub time: &'a Time,
     pub controller: &'a Controller,
     pub inputs: &'a ControllerInputs,
     pub stats: &'a Stats,
@@ -64,7 +71,15 @@ pub struct JoinData<'a> {
     pub body: &'a Body,
     pub physics: &'a PhysicsState,
     pub attacking: Option<&'a Attacking>,
+    pub cooldowns: Option<&'a AbilityCooldowns>,
+    pub stunned: Option<&'a Stunned>,
+    pub buffs: Option<&'a Buffs>,
     pub updater: &'a LazyUpdate,
+    pub terrain: &'a TerrainGrid,
+    /// Where the entities that states keep track of are, by `Uid`, as other
+    /// entities' positions can't be looked up while they are being updated
+    pub tracked_positions: &'a HashMap<Uid, Vec3<f32>>,
+    pub gameplay: &'a GameplaySettings,
 }
 
 type RestrictedMut<'a, C> = PairedStorage<
@@ -90,8 +105,25 @@ pub type JoinTuple<'a> = (
     &'a PhysicsState,
     Option<&'a Attacking>,
     Option<&'a Beam>,
+    Option<&'a AbilityCooldowns>,
+    Option<&'a Stunned>,
+    Option<&'a Buffs>,
 );
 
+/// Queue the buff changes caused by the character entering or leaving a state
+/// that buffs its user
+fn queue_self_buff_changes(j: &JoinData, state_update: &mut StateUpdate) {
+    for buff_change in j
+        .character
+        .self_buff_changes(&state_update.character, *j.uid)
+    {
+        state_update.server_events.push_back(ServerEvent::Buff {
+            entity: j.entity,
+            buff_change,
+        });
+    }
+}
+
 fn incorporate_update(tuple: &mut JoinTuple, state_update: StateUpdate) {
     // TODO: if checking equality is expensive use optional field in StateUpdate
     if tuple.2.get_unchecked() != &state_update.character {
@@ -105,13 +137,29 @@ fn incorporate_update(tuple: &mut JoinTuple, state_update: StateUpdate) {
         *tuple.6.get_mut_unchecked() = state_update.energy
     };
     if state_update.swap_loadout {
-        let loadout = tuple.7.get_mut_unchecked();
-        std::mem::swap(&mut loadout.active_item, &mut loadout.second_item);
+        tuple.7.get_mut_unchecked().swap_weapons();
     }
 }
 
 impl<'a> JoinData<'a> {
-    fn new(j: &'a JoinTuple<'a>, updater: &'a LazyUpdate, dt: &'a DeltaTime) -> Self {
+    /// Whether the entity is currently prevented from acting
+    pub fn is_stunned(&self) -> bool { self.stunned.map_or(false, |s| s.is_active(self.time.0)) }
+
+    /// Multiplier applied to the entity's movement speed by its buffs and
+    /// armor set bonuses
+    pub fn movement_speed(&self) -> f32 {
+        self.buffs.map_or(1.0, Buffs::movement_speed) * (1.0 + self.loadout.set_bonus_speed())
+    }
+
+    fn new(
+        j: &'a JoinTuple<'a>,
+        updater: &'a LazyUpdate,
+        dt: &'a DeltaTime,
+        time: &'a Time,
+        terrain: &'a TerrainGrid,
+        tracked_positions: &'a HashMap<Uid, Vec3<f32>>,
+        gameplay: &'a GameplaySettings,
+    ) -> Self {
         Self {
             entity: j.0,
             uid: j.1,
@@ -127,8 +175,15 @@ impl<'a> JoinData<'a> {
             body: j.10,
             physics: j.11,
             attacking: j.12,
+            cooldowns: j.14,
+            stunned: j.15,
+            buffs: j.16,
             updater,
             dt,
+            time,
+            terrain,
+            tracked_positions,
+            gameplay,
         }
     }
 }
@@ -146,8 +201,11 @@ impl<'a> System<'a> for Sys {
         Read<'a, EventBus<ServerEvent>>,
         Read<'a, EventBus<LocalEvent>>,
         Read<'a, DeltaTime>,
+        Read<'a, Time>,
         Read<'a, LazyUpdate>,
+        Read<'a, GameplaySettings>,
         ReadExpect<'a, SysMetrics>,
+        ReadExpect<'a, TerrainGrid>,
         WriteStorage<'a, CharacterState>,
         WriteStorage<'a, Pos>,
         WriteStorage<'a, Vel>,
@@ -162,6 +220,9 @@ impl<'a> System<'a> for Sys {
         ReadStorage<'a, Beam>,
         ReadStorage<'a, Uid>,
         ReadStorage<'a, Mounting>,
+        ReadStorage<'a, AbilityCooldowns>,
+        ReadStorage<'a, Stunned>,
+        ReadStorage<'a, Buffs>,
     );
 
     #[allow(clippy::while_let_on_iterator)] // TODO: Pending review in #587
@@ -169,12 +230,15 @@ impl<'a> System<'a> for Sys {
         &mut self,
         (
             entities,
-            _uid_allocator,
+            uid_allocator,
             server_bus,
             local_bus,
             dt,
+            time,
             updater,
+            gameplay,
             sys_metrics,
+            terrain,
             mut character_states,
             mut positions,
             mut velocities,
@@ -189,6 +253,9 @@ impl<'a> System<'a> for Sys {
             beam_storage,
             uids,
             mountings,
+            cooldowns,
+            stuns,
+            buffs,
         ): Self::SystemData,
     ) {
         let start_time = std::time::Instant::now();
@@ -196,6 +263,26 @@ impl<'a> System<'a> for Sys {
         let mut server_emitter = server_bus.emitter();
         let mut local_emitter = local_bus.emitter();
 
+        // Grapples follow the entity they are attached to, or about to be
+        let tracked_positions = (&character_states, &controllers)
+            .join()
+            .filter_map(|(character_state, controller)| match character_state {
+                CharacterState::Grapple(grapple::Data {
+                    anchor: Some(grapple::GrappleAnchor::Entity(uid)),
+                    ..
+                }) => Some(*uid),
+                CharacterState::Grapple(grapple::Data { anchor: None, .. }) => {
+                    controller.inputs.select_entity
+                },
+                _ => None,
+            })
+            .filter_map(|uid| {
+                let entity = uid_allocator.retrieve_entity_internal(uid.into())?;
+                let point = grapple::tether_point_on(positions.get(entity)?.0, bodies.get(entity)?);
+                Some((uid, point))
+            })
+            .collect::<HashMap<_, _>>();
+
         for mut tuple in (
             &entities,
             &uids,
@@ -211,6 +298,9 @@ impl<'a> System<'a> for Sys {
             &physics_states,
             attacking_storage.maybe(),
             beam_storage.maybe(),
+            cooldowns.maybe(),
+            stuns.maybe(),
+            buffs.maybe(),
         )
             .join()
         {
@@ -224,14 +314,36 @@ impl<'a> System<'a> for Sys {
             if let Some(Mounting(_)) = mountings.get(tuple.0) {
                 let sit_state = CharacterState::Sit {};
                 if tuple.2.get_unchecked() != &sit_state {
+                    for buff_change in tuple
+                        .2
+                        .get_unchecked()
+                        .self_buff_changes(&sit_state, *tuple.1)
+                    {
+                        server_emitter.emit(ServerEvent::Buff {
+                            entity: tuple.0,
+                            buff_change,
+                        });
+                    }
                     *tuple.2.get_mut_unchecked() = sit_state;
                 }
                 continue;
             }
+            // Stuns wear off by themselves
+            if tuple.15.map_or(false, |s| !s.is_active(time.0)) {
+                updater.remove::<Stunned>(tuple.0);
+            }
 
             let actions = std::mem::replace(&mut tuple.8.actions, Vec::new());
             for action in actions {
-                let j = JoinData::new(&tuple, &updater, &dt);
+                let j = JoinData::new(
+                    &tuple,
+                    &updater,
+                    &dt,
+                    &time,
+                    &terrain,
+                    &tracked_positions,
+                    &gameplay,
+                );
                 let mut state_update = match j.character {
                     CharacterState::Idle => states::idle::Data.handle_event(&j, action),
                     CharacterState::Climb => states::climb::Data.handle_event(&j, action),
@@ -248,9 +360,10 @@ impl<'a> System<'a> for Sys {
                     CharacterState::Sneak => {
                         states::sneak::Data::handle_event(&states::sneak::Data, &j, action)
                     },
-                    CharacterState::BasicBlock => {
-                        states::basic_block::Data.handle_event(&j, action)
+                    CharacterState::Sprint => {
+                        states::sprint::Data::handle_event(&states::sprint::Data, &j, action)
                     },
+                    CharacterState::BasicBlock(data) => data.handle_event(&j, action),
                     CharacterState::Roll(data) => data.handle_event(&j, action),
                     CharacterState::Wielding => states::wielding::Data.handle_event(&j, action),
                     CharacterState::Equipping(data) => data.handle_event(&j, action),
@@ -266,13 +379,26 @@ impl<'a> System<'a> for Sys {
                     CharacterState::RepeaterRanged(data) => data.handle_event(&j, action),
                     CharacterState::Shockwave(data) => data.handle_event(&j, action),
                     CharacterState::BasicBeam(data) => data.handle_event(&j, action),
+                    CharacterState::Harvest(data) => data.handle_event(&j, action),
+                    CharacterState::PlaceBlock(data) => data.handle_event(&j, action),
+                    CharacterState::Grapple(data) => data.handle_event(&j, action),
+                    CharacterState::SwapWeapons(data) => data.handle_event(&j, action),
                 };
+                queue_self_buff_changes(&j, &mut state_update);
                 local_emitter.append(&mut state_update.local_events);
                 server_emitter.append(&mut state_update.server_events);
                 incorporate_update(&mut tuple, state_update);
             }
 
-            let j = JoinData::new(&tuple, &updater, &dt);
+            let j = JoinData::new(
+                &tuple,
+                &updater,
+                &dt,
+                &time,
+                &terrain,
+                &tracked_positions,
+                &gameplay,
+            );
 
             let mut state_update = match j.character {
                 CharacterState::Idle => states::idle::Data.behavior(&j),
@@ -282,7 +408,8 @@ impl<'a> System<'a> for Sys {
                 CharacterState::Sit => states::sit::Data::behavior(&states::sit::Data, &j),
                 CharacterState::Dance => states::dance::Data::behavior(&states::dance::Data, &j),
                 CharacterState::Sneak => states::sneak::Data::behavior(&states::sneak::Data, &j),
-                CharacterState::BasicBlock => states::basic_block::Data.behavior(&j),
+                CharacterState::Sprint => states::sprint::Data::behavior(&states::sprint::Data, &j),
+                CharacterState::BasicBlock(data) => data.behavior(&j),
                 CharacterState::Roll(data) => data.behavior(&j),
                 CharacterState::Wielding => states::wielding::Data.behavior(&j),
                 CharacterState::Equipping(data) => data.behavior(&j),
@@ -298,7 +425,12 @@ impl<'a> System<'a> for Sys {
                 CharacterState::RepeaterRanged(data) => data.behavior(&j),
                 CharacterState::Shockwave(data) => data.behavior(&j),
                 CharacterState::BasicBeam(data) => data.behavior(&j),
+                CharacterState::Harvest(data) => data.behavior(&j),
+                CharacterState::PlaceBlock(data) => data.behavior(&j),
+                CharacterState::Grapple(data) => data.behavior(&j),
+                CharacterState::SwapWeapons(data) => data.behavior(&j),
             };
+            queue_self_buff_changes(&j, &mut state_update);
 
             local_emitter.append(&mut state_update.local_events);
             server_emitter.append(&mut state_update.server_events);
@@ -1,17 +1,21 @@
 use crate::{
     comp::{
-        buff, group, Attacking, Body, CharacterState, Damage, DamageSource, HealthChange,
-        HealthSource, Loadout, Ori, Pos, Scale, Stats,
+        buff, group, scaled_damage, Attacking, Body, CharacterState, Damage, DamageSource,
+        HealthChange, HealthSource, KnockbackImmunity, LastKnockback, Loadout, Ori, Pos, Scale,
+        Stats,
     },
     event::{EventBus, LocalEvent, ServerEvent},
     metrics::SysMetrics,
     span,
+    state::Time,
+    states::utils::{apply_knockback, cone_hit_targets, in_cone, KnockbackTarget},
     sync::Uid,
-    util::Dir,
+    util::{Dir, SpatialGrid},
 };
+use hashbrown::HashMap;
 use rand::{thread_rng, Rng};
 use specs::{Entities, Join, Read, ReadExpect, ReadStorage, System, WriteStorage};
-use std::time::Duration;
+use std::{collections::VecDeque, time::Duration};
 use vek::*;
 
 pub const BLOCK_EFFICIENCY: f32 = 0.9;
@@ -27,6 +31,9 @@ impl<'a> System<'a> for Sys {
         Read<'a, EventBus<ServerEvent>>,
         Read<'a, EventBus<LocalEvent>>,
         ReadExpect<'a, SysMetrics>,
+        Read<'a, SpatialGrid>,
+        Read<'a, Time>,
+        Read<'a, KnockbackImmunity>,
         ReadStorage<'a, Uid>,
         ReadStorage<'a, Pos>,
         ReadStorage<'a, Ori>,
@@ -36,6 +43,7 @@ impl<'a> System<'a> for Sys {
         ReadStorage<'a, Loadout>,
         ReadStorage<'a, group::Group>,
         ReadStorage<'a, CharacterState>,
+        ReadStorage<'a, LastKnockback>,
         WriteStorage<'a, Attacking>,
     );
 
@@ -46,6 +54,9 @@ impl<'a> System<'a> for Sys {
             server_bus,
             local_bus,
             sys_metrics,
+            spatial_grid,
+            time,
+            knockback_immunity,
             uids,
             positions,
             orientations,
@@ -55,6 +66,7 @@ impl<'a> System<'a> for Sys {
             loadouts,
             groups,
             character_states,
+            last_knockbacks,
             mut attacking_storage,
         ): Self::SystemData,
     ) {
@@ -62,6 +74,7 @@ impl<'a> System<'a> for Sys {
         span!(_guard, "run", "combat::Sys::run");
         let mut server_emitter = server_bus.emitter();
         let mut _local_emitter = local_bus.emitter();
+        let mut knockbacks = VecDeque::new();
         // Attacks
         for (entity, uid, pos, ori, scale_maybe, attack) in (
             &entities,
@@ -78,36 +91,52 @@ impl<'a> System<'a> for Sys {
             }
             attack.applied = true;
 
-            // Go through all other entities
-            for (b, uid_b, pos_b, ori_b, scale_b_maybe, character_b, stats_b, body_b) in (
-                &entities,
-                &uids,
-                &positions,
-                &orientations,
-                scales.maybe(),
-                character_states.maybe(),
-                &stats,
-                &bodies,
-            )
-                .join()
-            {
-                // 2D versions
-                let pos2 = Vec2::from(pos.0);
-                let pos_b2 = Vec2::<f32>::from(pos_b.0);
-                let ori2 = Vec2::from(*ori.0);
+            // Stronger attackers hit harder
+            let base_damage = stats
+                .get(entity)
+                .map_or(attack.base_damage, |s| scaled_damage(attack.base_damage, s));
+
+            // Go through all other entities that might be in range
+            let scale = scale_maybe.map_or(1.0, |s| s.0);
+            let mut infos = HashMap::new();
+            let mut candidates = Vec::new();
+            for b in spatial_grid.entities_in_radius(pos.0.xy(), scale * attack.range) {
+                let (uid_b, pos_b, ori_b, stats_b, body_b) = match (
+                    uids.get(b),
+                    positions.get(b),
+                    orientations.get(b),
+                    stats.get(b),
+                    bodies.get(b),
+                ) {
+                    (Some(uid_b), Some(pos_b), Some(ori_b), Some(stats_b), Some(body_b)) => {
+                        (uid_b, pos_b, ori_b, stats_b, body_b)
+                    },
+                    _ => continue,
+                };
+                let scale_b_maybe = scales.get(b);
+                let character_b = character_states.get(b);
 
                 // Scales
-                let scale = scale_maybe.map_or(1.0, |s| s.0);
                 let scale_b = scale_b_maybe.map_or(1.0, |s| s.0);
                 let rad_b = body_b.radius() * scale_b;
+                let height_b = body_b.height() * scale_b;
+
+                // Attacks with a vertical range only reach targets overlapping their band,
+                // but then hit them whatever their height within it
+                let pos_b_hit = match attack.vertical_range {
+                    Some(vertical_range) => {
+                        let vertical_range = vertical_range * scale;
+                        if pos_b.0.z + height_b <= pos.0.z - vertical_range
+                            || pos_b.0.z >= pos.0.z + vertical_range
+                        {
+                            continue;
+                        }
+                        Vec3::new(pos_b.0.x, pos_b.0.y, pos.0.z)
+                    },
+                    None => pos_b.0,
+                };
 
-                // Check if it is a hit
-                if entity != b
-                    && !stats_b.is_dead
-                    // Spherical wedge shaped attack field
-                    && pos.0.distance_squared(pos_b.0) < (rad_b + scale * attack.range).powi(2)
-                    && ori2.angle_between(pos_b2 - pos2) < attack.max_angle + (rad_b / pos2.distance(pos_b2)).atan()
-                {
+                if entity != b && !stats_b.is_dead {
                     // See if entities are in the same group
                     let same_group = groups
                         .get(entity)
@@ -121,71 +150,324 @@ impl<'a> System<'a> for Sys {
                         continue;
                     }
 
-                    // Weapon gives base damage
-                    let (source, healthchange) = if is_heal {
-                        (DamageSource::Healing, attack.base_heal as f32)
-                    } else {
-                        (DamageSource::Melee, -(attack.base_damage as f32))
-                    };
-                    let mut damage = Damage {
-                        healthchange,
-                        source,
-                    };
+                    infos.insert(*uid_b, (b, uid_b, pos_b, ori_b, character_b, is_heal));
+                    candidates.push((*uid_b, pos_b_hit, rad_b));
+                }
+            }
 
-                    let block = character_b.map(|c_b| c_b.is_block()).unwrap_or(false)
-                        && ori_b.0.angle_between(pos.0 - pos_b.0) < BLOCK_ANGLE.to_radians() / 2.0;
+            // Check which candidates are hit
+            let targets = cone_hit_targets(
+                pos.0,
+                Vec2::from(*ori.0),
+                scale * attack.range,
+                attack.max_angle,
+                candidates,
+            )
+            .into_iter()
+            .filter_map(|uid_b| infos.get(&uid_b).copied())
+            .map(|info| (info, pos.0.distance_squared((info.2).0)))
+            .collect();
 
-                    if let Some(loadout) = loadouts.get(b) {
-                        damage.modify_damage(block, loadout);
-                    }
+            for (b, uid_b, pos_b, ori_b, character_b, is_heal) in
+                nearest_targets(targets, attack.max_targets)
+            {
+                // Weapon gives base damage
+                let (source, healthchange) = if is_heal {
+                    (DamageSource::Healing, attack.base_heal as f32)
+                } else {
+                    (DamageSource::Melee, -(base_damage as f32))
+                };
+                let mut damage = Damage {
+                    healthchange,
+                    source,
+                    kind: attack.damage_kind,
+                };
 
-                    if damage.healthchange != 0.0 {
-                        let cause = if is_heal {
-                            HealthSource::Healing { by: Some(*uid) }
-                        } else {
-                            HealthSource::Attack { by: *uid }
-                        };
-                        server_emitter.emit(ServerEvent::Damage {
-                            uid: *uid_b,
-                            change: HealthChange {
-                                amount: damage.healthchange as i32,
-                                cause,
-                            },
-                        });
+                let block = character_b
+                    .and_then(|c_b| c_b.block_strength())
+                    .filter(|_| {
+                        ori_b.0.angle_between(pos.0 - pos_b.0) < BLOCK_ANGLE.to_radians() / 2.0
+                    });
 
-                        // Apply bleeding buff on melee hits with 10% chance
-                        // TODO: Don't have buff uniformly applied on all melee attacks
-                        if thread_rng().gen::<f32>() < 0.1 {
-                            use buff::*;
-                            server_emitter.emit(ServerEvent::Buff {
-                                entity: b,
-                                buff_change: BuffChange::Add(Buff::new(
-                                    BuffKind::Bleeding,
-                                    BuffData {
-                                        strength: attack.base_damage as f32 / 10.0,
-                                        duration: Some(Duration::from_secs(10)),
-                                    },
-                                    vec![BuffCategory::Physical],
-                                    BuffSource::Character { by: *uid },
-                                )),
-                            });
-                        }
-                        attack.hit_count += 1;
+                if let Some(loadout) = loadouts.get(b) {
+                    damage.modify_damage(block, loadout);
+                }
+
+                if damage.healthchange != 0.0 {
+                    let cause = if is_heal {
+                        HealthSource::Healing { by: Some(*uid) }
+                    } else {
+                        HealthSource::Attack { by: *uid }
+                    };
+                    server_emitter.emit(ServerEvent::Damage {
+                        uid: *uid_b,
+                        change: HealthChange {
+                            amount: damage.healthchange as i32,
+                            cause,
+                        },
+                    });
+
+                    // Apply bleeding buff on melee hits with 10% chance
+                    // TODO: Don't have buff uniformly applied on all melee attacks
+                    if thread_rng().gen::<f32>() < 0.1 {
+                        use buff::*;
+                        server_emitter.emit(ServerEvent::Buff {
+                            entity: b,
+                            buff_change: BuffChange::Add(Buff::new(
+                                BuffKind::Bleeding,
+                                BuffData {
+                                    strength: base_damage as f32 / 10.0,
+                                    duration: Some(Duration::from_secs(10)),
+                                },
+                                vec![BuffCategory::Physical],
+                                BuffSource::Character { by: *uid },
+                            )),
+                        });
                     }
-                    if attack.knockback != 0.0 && damage.healthchange != 0.0 {
-                        let kb_dir = Dir::new((pos_b.0 - pos.0).try_normalized().unwrap_or(*ori.0));
-                        server_emitter.emit(ServerEvent::Knockback {
+                    // Blocked hits don't draw blood
+                    if let Some(dot) = attack.dot.filter(|_| !is_heal && block.is_none()) {
+                        server_emitter.emit(ServerEvent::DamageOverTime {
                             entity: b,
-                            impulse: attack.knockback
-                                * *Dir::slerp(kb_dir, Dir::new(Vec3::new(0.0, 0.0, 1.0)), 0.5),
+                            dot,
+                            by: *uid,
                         });
                     }
+                    attack.hit_count += 1;
+                }
+                if damage.healthchange != 0.0 {
+                    apply_knockback(
+                        &mut knockbacks,
+                        &KnockbackTarget {
+                            entity: b,
+                            loadout: loadouts.get(b),
+                            last_knockback: last_knockbacks.get(b),
+                            immunity: &knockback_immunity,
+                            time: time.0,
+                        },
+                        knockback_dir(pos.0, pos_b.0, *ori.0),
+                        attack.knockback,
+                    );
                 }
             }
         }
+        server_emitter.append(&mut knockbacks);
         sys_metrics.combat_ns.store(
             start_time.elapsed().as_nanos() as i64,
             std::sync::atomic::Ordering::Relaxed,
         );
     }
 }
+
+/// Checks whether a target at `pos_b` is both in range of `attack` and
+/// within its angle of the attacker's facing direction `ori`.
+pub fn hits_target(
+    attack: &Attacking,
+    pos: Vec3<f32>,
+    ori: Vec2<f32>,
+    scale: f32,
+    pos_b: Vec3<f32>,
+    rad_b: f32,
+    height_b: f32,
+) -> bool {
+    in_attack_range(attack, pos, scale, pos_b, rad_b, height_b)
+        && in_cone(pos, ori, attack.max_angle, pos_b, rad_b)
+}
+
+/// Orders `candidates` by their distance to the attacker and keeps at most
+/// `max_targets` of the nearest ones, or all of them if there is no limit.
+pub fn nearest_targets<T>(
+    mut candidates: Vec<(T, f32)>,
+    max_targets: Option<u32>,
+) -> impl Iterator<Item = T> {
+    if let Some(max_targets) = max_targets {
+        candidates.sort_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        candidates.truncate(max_targets as usize);
+    }
+    candidates.into_iter().map(|(target, _)| target)
+}
+
+/// Direction in which a target at `pos_b` is knocked away from an attacker at
+/// `pos`, falling back to the attacker's facing direction `ori` if they
+/// overlap.
+pub fn knockback_dir(pos: Vec3<f32>, pos_b: Vec3<f32>, ori: Vec3<f32>) -> Dir {
+    Dir::new((pos_b - pos).try_normalized().unwrap_or(ori))
+}
+
+/// Checks whether a target at `pos_b` is close enough to be hit by `attack`.
+///
+/// Attacks without a vertical range use a spherical wedge shaped attack
+/// field. Attacks with one instead use a cylinder, hitting anything within
+/// range horizontally that overlaps the vertical band around the attacker.
+pub fn in_attack_range(
+    attack: &Attacking,
+    pos: Vec3<f32>,
+    scale: f32,
+    pos_b: Vec3<f32>,
+    rad_b: f32,
+    height_b: f32,
+) -> bool {
+    let max_dist_sqr = (rad_b + scale * attack.range).powi(2);
+    match attack.vertical_range {
+        Some(vertical_range) => {
+            let vertical_range = vertical_range * scale;
+            Vec2::from(pos).distance_squared(Vec2::from(pos_b)) < max_dist_sqr
+                && pos_b.z + height_b > pos.z - vertical_range
+                && pos_b.z < pos.z + vertical_range
+        },
+        None => pos.distance_squared(pos_b) < max_dist_sqr,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        comp::{humanoid, DamageKind, DamageOverTime, DotStacking, Knockback},
+        state::State,
+        states::basic_block,
+        sync::WorldSyncExt,
+    };
+    use specs::{Builder, RunNow, WorldExt};
+
+    fn spin_attack(vertical_range: Option<f32>) -> Attacking {
+        Attacking {
+            base_damage: 10,
+            base_heal: 0,
+            damage_kind: DamageKind::Physical,
+            range: 3.5,
+            max_angle: 180_f32.to_radians(),
+            applied: false,
+            hit_count: 0,
+            knockback: Knockback::default(),
+            vertical_range,
+            max_targets: None,
+            dot: None,
+        }
+    }
+
+    /// How many damage over time effects a bleeding slash applies to someone
+    /// standing in front of the attacker, facing them, in `character_state`
+    fn slash_dots(character_state: CharacterState) -> usize {
+        let mut state = State::default();
+        let body = Body::Humanoid(humanoid::Body::random());
+        state
+            .ecs_mut()
+            .create_entity_synced()
+            .with(Pos(Vec3::zero()))
+            .with(Ori(Dir::new(Vec3::unit_y())))
+            .with(body)
+            .with(Stats::new("Attacker".to_string(), body))
+            .with(Attacking {
+                dot: Some(DamageOverTime {
+                    tick_damage: 5,
+                    tick_interval: Duration::from_millis(500),
+                    duration: Duration::from_secs(4),
+                    kind: DamageKind::Physical,
+                    stacking: DotStacking::Refresh,
+                }),
+                ..spin_attack(None)
+            })
+            .build();
+        let target_pos = Vec2::new(0.0, 2.0);
+        let target = state
+            .ecs_mut()
+            .create_entity_synced()
+            .with(Pos(target_pos.into()))
+            .with(Ori(Dir::new(-Vec3::unit_y())))
+            .with(body)
+            .with(Stats::new("Target".to_string(), body))
+            .with(character_state)
+            .build();
+        state
+            .ecs()
+            .write_resource::<SpatialGrid>()
+            .insert(target, target_pos, body.radius());
+
+        Sys.run_now(state.ecs());
+
+        state
+            .ecs()
+            .read_resource::<EventBus<ServerEvent>>()
+            .recv_all()
+            .filter(|event| matches!(event, ServerEvent::DamageOverTime { .. }))
+            .count()
+    }
+
+    #[test]
+    fn blocked_hits_apply_no_dot() {
+        assert_eq!(slash_dots(CharacterState::Wielding), 1);
+        assert_eq!(
+            slash_dots(CharacterState::BasicBlock(basic_block::Data {
+                self_buff: None,
+                block_strength: 0.5,
+                reflect_window: None,
+                timer: Duration::default(),
+            })),
+            0
+        );
+    }
+
+    #[test]
+    fn vertical_range_above_and_below() {
+        let attack = spin_attack(Some(4.0));
+        let pos = Vec3::new(0.0, 0.0, 10.0);
+        let hit =
+            |dz: f32| in_attack_range(&attack, pos, 1.0, pos + Vec3::new(1.0, 0.0, dz), 0.5, 1.8);
+
+        // Within the band
+        assert!(hit(3.5));
+        assert!(hit(-3.5));
+        // Outside the band
+        assert!(!hit(4.5));
+        assert!(!hit(-6.0));
+    }
+
+    #[test]
+    fn vertical_range_extends_spherical_range() {
+        let pos = Vec3::new(0.0, 0.0, 10.0);
+        let target = pos + Vec3::new(3.0, 0.0, 3.0);
+
+        assert!(!in_attack_range(
+            &spin_attack(None),
+            pos,
+            1.0,
+            target,
+            0.5,
+            1.8
+        ));
+        assert!(in_attack_range(
+            &spin_attack(Some(4.0)),
+            pos,
+            1.0,
+            target,
+            0.5,
+            1.8
+        ));
+    }
+
+    #[test]
+    fn max_targets_hits_nearest_first() {
+        let attack = Attacking {
+            max_targets: Some(3),
+            ..spin_attack(Some(2.0))
+        };
+        let pos = Vec3::zero();
+        let targets = [2.5, 0.5, 3.0, 1.5, 1.0]
+            .iter()
+            .enumerate()
+            .map(|(i, &dist)| (i, Vec3::new(dist, 0.0, 0.0)))
+            .filter(|(_, pos_b)| hits_target(&attack, pos, Vec2::unit_y(), 1.0, *pos_b, 0.5, 1.8))
+            .map(|(i, pos_b)| (i, pos.distance_squared(pos_b)))
+            .collect::<Vec<_>>();
+        assert_eq!(targets.len(), 5);
+
+        let hit = nearest_targets(targets, attack.max_targets).collect::<Vec<_>>();
+        assert_eq!(hit, vec![1, 4, 3]);
+    }
+
+    #[test]
+    fn no_max_targets_hits_everything() {
+        let targets = vec![(0, 4.0), (1, 1.0), (2, 9.0)];
+        assert_eq!(nearest_targets(targets, None).count(), 3);
+    }
+}
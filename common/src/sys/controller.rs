@@ -51,7 +51,7 @@ impl<'a> System<'a> for Sys {
         span!(_guard, "run", "controller::Sys::run");
         let mut server_emitter = server_bus.emitter();
 
-        for (entity, _uid, controller, character_state) in
+        for (entity, uid, controller, character_state) in
             (&entities, &uids, &mut controllers, &mut character_states).join()
         {
             let mut inputs = &mut controller.inputs;
@@ -104,6 +104,14 @@ impl<'a> System<'a> for Sys {
                                 InventoryManip::Drop(Slot::Equip(EquipSlot::Mainhand))
                                 | InventoryManip::Swap(_, Slot::Equip(EquipSlot::Mainhand))
                                 | InventoryManip::Swap(Slot::Equip(EquipSlot::Mainhand), _) => {
+                                    for buff_change in character_state
+                                        .self_buff_changes(&CharacterState::Idle, *uid)
+                                    {
+                                        server_emitter.emit(ServerEvent::Buff {
+                                            entity,
+                                            buff_change,
+                                        });
+                                    }
                                     *character_state = CharacterState::Idle;
                                 },
                                 _ => (),
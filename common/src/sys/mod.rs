@@ -8,6 +8,7 @@ mod mount;
 pub mod phys;
 mod projectile;
 mod shockwave;
+mod spatial_grid;
 mod stats;
 
 // External
@@ -23,6 +24,7 @@ pub const MOUNT_SYS: &str = "mount_sys";
 pub const PHYS_SYS: &str = "phys_sys";
 pub const PROJECTILE_SYS: &str = "projectile_sys";
 pub const SHOCKWAVE_SYS: &str = "shockwave_sys";
+pub const SPATIAL_GRID_SYS: &str = "spatial_grid_sys";
 pub const STATS_SYS: &str = "stats_sys";
 pub const BUFFS_SYS: &str = "buffs_sys";
 
@@ -37,7 +39,8 @@ pub fn add_local_systems(dispatch_builder: &mut DispatcherBuilder) {
     dispatch_builder.add(buff::Sys, BUFFS_SYS, &[]);
     dispatch_builder.add(phys::Sys, PHYS_SYS, &[CONTROLLER_SYS, MOUNT_SYS, STATS_SYS]);
     dispatch_builder.add(projectile::Sys, PROJECTILE_SYS, &[PHYS_SYS]);
-    dispatch_builder.add(shockwave::Sys, SHOCKWAVE_SYS, &[PHYS_SYS]);
+    dispatch_builder.add(spatial_grid::Sys, SPATIAL_GRID_SYS, &[PHYS_SYS]);
+    dispatch_builder.add(shockwave::Sys, SHOCKWAVE_SYS, &[SPATIAL_GRID_SYS]);
     dispatch_builder.add(beam::Sys, BEAM_SYS, &[PHYS_SYS]);
-    dispatch_builder.add(combat::Sys, COMBAT_SYS, &[PROJECTILE_SYS]);
+    dispatch_builder.add(combat::Sys, COMBAT_SYS, &[PROJECTILE_SYS, SPATIAL_GRID_SYS]);
 }
@@ -4,6 +4,7 @@ use crate::{
         Shockwave, Sticky, Vel,
     },
     event::{EventBus, ServerEvent},
+    gameplay::GameplaySettings,
     metrics::SysMetrics,
     span,
     state::DeltaTime,
@@ -33,13 +34,19 @@ const FRIC_FLUID: f32 = 0.2;
 // lv = linear velocity
 // damp = linear damping
 // Friction is a type of damping.
-fn integrate_forces(dt: f32, mut lv: Vec3<f32>, grav: f32, damp: f32) -> Vec3<f32> {
+fn integrate_forces(
+    dt: f32,
+    mut lv: Vec3<f32>,
+    grav: f32,
+    damp: f32,
+    max_fall_speed: f32,
+) -> Vec3<f32> {
     // this is not linear damping, because it is proportional to the original
     // velocity this "linear" damping in in fact, quite exponential. and thus
     // must be interpolated accordingly
     let linear_damp = (1.0 - damp.min(1.0)).powf(dt * 60.0);
 
-    lv.z = (lv.z - grav * dt).max(-80.0);
+    lv.z = (lv.z - grav * dt).max(-max_fall_speed);
     lv * linear_damp
 }
 
@@ -52,6 +59,7 @@ impl<'a> System<'a> for Sys {
         ReadStorage<'a, Uid>,
         ReadExpect<'a, TerrainGrid>,
         Read<'a, DeltaTime>,
+        Read<'a, GameplaySettings>,
         ReadExpect<'a, SysMetrics>,
         Read<'a, EventBus<ServerEvent>>,
         ReadStorage<'a, Scale>,
@@ -78,6 +86,7 @@ impl<'a> System<'a> for Sys {
             uids,
             terrain,
             dt,
+            gameplay_settings,
             sys_metrics,
             event_bus,
             scales,
@@ -306,11 +315,17 @@ impl<'a> System<'a> for Sys {
                 .map(|depth| depth > 0.75)
                 .unwrap_or(false)
             {
-                (1.0 - BOUYANCY) * GRAVITY
+                (1.0 - BOUYANCY) * gameplay_settings.gravity
             } else {
-                GRAVITY
+                gameplay_settings.gravity
             } * gravities.get(entity).map(|g| g.0).unwrap_or_default();
-            vel.0 = integrate_forces(dt.0, vel.0, downward_force, friction);
+            vel.0 = integrate_forces(
+                dt.0,
+                vel.0,
+                downward_force,
+                friction,
+                gameplay_settings.max_fall_speed,
+            );
 
             // Don't move if we're not in a loaded chunk
             let mut pos_delta = if in_loaded_chunk {
@@ -576,6 +591,16 @@ impl<'a> System<'a> for Sys {
                         physics_state.on_ground = true;
                     }
 
+                    // The block the entity's feet rest on
+                    physics_state.ground_kind = if physics_state.on_ground {
+                        terrain
+                            .get((pos.0 - Vec3::unit_z() * 0.5).map(|e| e.floor() as i32))
+                            .ok()
+                            .map(|block| block.kind())
+                    } else {
+                        None
+                    };
+
                     let dirs = [
                         Vec3::unit_x(),
                         Vec3::unit_y(),
@@ -583,26 +608,46 @@ impl<'a> System<'a> for Sys {
                         -Vec3::unit_y(),
                     ];
 
-                    if let (wall_dir, true) =
-                        dirs.iter().fold((Vec3::zero(), false), |(a, hit), dir| {
-                            if collision_with(
-                                pos.0 + *dir * 0.01,
-                                &terrain,
-                                block_true,
-                                near_iter.clone(),
-                                radius,
-                                z_range.clone(),
-                            ) {
-                                (a + dir, true)
-                            } else {
-                                (a, hit)
-                            }
-                        })
-                    {
-                        physics_state.on_wall = Some(wall_dir);
-                    } else {
-                        physics_state.on_wall = None;
+                    // Which of `dirs` the entity is up against a wall in
+                    let mut against_wall = [false; 4];
+                    for (against, dir) in against_wall.iter_mut().zip(dirs.iter()) {
+                        *against = collision_with(
+                            pos.0 + *dir * 0.01,
+                            &terrain,
+                            block_true,
+                            near_iter.clone(),
+                            radius,
+                            z_range.clone(),
+                        );
                     }
+                    let wall_dirs = || {
+                        dirs.iter()
+                            .zip(against_wall.iter())
+                            .filter(|(_, against)| **against)
+                            .map(|(dir, _)| *dir)
+                    };
+
+                    physics_state.on_wall = if against_wall.iter().any(|against| *against) {
+                        Some(wall_dirs().fold(Vec3::zero(), |a, dir| a + dir))
+                    } else {
+                        None
+                    };
+
+                    // Vines hanging in front of a wall can be climbed too, so this isn't
+                    // limited to solid blocks. Only the walls the entity is against count.
+                    physics_state.on_climbable = wall_dirs().any(|dir| {
+                        collision_iter(
+                            pos.0 + dir * 0.01,
+                            &terrain,
+                            &|block| block.is_climbable(),
+                            &|_block| 1.0,
+                            near_iter.clone(),
+                            radius,
+                            z_range.clone(),
+                        )
+                        .next()
+                        .is_some()
+                    });
 
                     // Figure out if we're in water
                     physics_state.in_fluid = collision_iter(
@@ -643,6 +688,8 @@ impl<'a> System<'a> for Sys {
                             }
                             vel.0.z = 0.0;
                         } else {
+                            physics_state.on_climbable =
+                                block.unwrap().map_or(false, |block| block.is_climbable());
                             physics_state.on_wall =
                                 Some(if block_rpos.x.abs() > block_rpos.y.abs() {
                                     vel.0.x = 0.0;
@@ -1,19 +1,23 @@
 use crate::{
     comp::{
-        projectile, Damage, DamageSource, Energy, EnergySource, Group, HealthChange, HealthSource,
-        Loadout, Ori, PhysicsState, Pos, Projectile, Vel,
+        projectile, scaled_damage, Body, CharacterState, Damage, DamageSource, DespawnReason,
+        Energy, EnergySource, Group, HealthChange, HealthSource, Homing, KnockbackImmunity,
+        LastKnockback, Loadout, Ori, PhysicsState, Pos, Projectile, Stats, Vel,
     },
     event::{EventBus, LocalEvent, ServerEvent},
     metrics::SysMetrics,
     span,
-    state::DeltaTime,
-    sync::UidAllocator,
+    state::{DeltaTime, Time},
+    states::utils::{apply_knockback, KnockbackTarget},
+    sync::{Uid, UidAllocator},
+    terrain::TerrainGrid,
     util::Dir,
+    vol::ReadVol,
 };
 use specs::{
     saveload::MarkerAllocator, Entities, Join, Read, ReadExpect, ReadStorage, System, WriteStorage,
 };
-use std::time::Duration;
+use std::{cmp::Ordering, collections::VecDeque, time::Duration};
 use vek::*;
 
 /// This system is responsible for handling projectile effect triggers
@@ -23,18 +27,26 @@ impl<'a> System<'a> for Sys {
     type SystemData = (
         Entities<'a>,
         Read<'a, DeltaTime>,
+        Read<'a, Time>,
+        Read<'a, KnockbackImmunity>,
         Read<'a, UidAllocator>,
         Read<'a, EventBus<LocalEvent>>,
         Read<'a, EventBus<ServerEvent>>,
         ReadExpect<'a, SysMetrics>,
+        ReadExpect<'a, TerrainGrid>,
         ReadStorage<'a, Pos>,
         ReadStorage<'a, PhysicsState>,
-        ReadStorage<'a, Vel>,
+        WriteStorage<'a, Vel>,
         WriteStorage<'a, Ori>,
         WriteStorage<'a, Projectile>,
         WriteStorage<'a, Energy>,
         ReadStorage<'a, Loadout>,
         ReadStorage<'a, Group>,
+        ReadStorage<'a, Uid>,
+        ReadStorage<'a, Stats>,
+        ReadStorage<'a, Body>,
+        ReadStorage<'a, CharacterState>,
+        ReadStorage<'a, LastKnockback>,
     );
 
     fn run(
@@ -42,34 +54,37 @@ impl<'a> System<'a> for Sys {
         (
             entities,
             dt,
+            time,
+            knockback_immunity,
             uid_allocator,
             local_bus,
             server_bus,
             sys_metrics,
+            terrain,
             positions,
             physics_states,
-            velocities,
+            mut velocities,
             mut orientations,
             mut projectiles,
             mut energies,
             loadouts,
             groups,
+            uids,
+            stats,
+            bodies,
+            character_states,
+            last_knockbacks,
         ): Self::SystemData,
     ) {
         let start_time = std::time::Instant::now();
         span!(_guard, "run", "projectile::Sys::run");
-        let mut local_emitter = local_bus.emitter();
+        let _local_emitter = local_bus.emitter();
         let mut server_emitter = server_bus.emitter();
+        let mut knockbacks = VecDeque::new();
 
         // Attacks
-        for (entity, pos, physics, ori, projectile) in (
-            &entities,
-            &positions,
-            &physics_states,
-            &mut orientations,
-            &mut projectiles,
-        )
-            .join()
+        for (entity, pos, physics, projectile) in
+            (&entities, &positions, &physics_states, &mut projectiles).join()
         {
             // Hit entity
             for other in physics.touch_entities.iter().copied() {
@@ -95,18 +110,66 @@ impl<'a> System<'a> for Sys {
                     continue;
                 }
 
-                for effect in projectile.hit_entity.drain(..) {
+                // A well-timed block facing the projectile sends it back
+                let other_entity = uid_allocator.retrieve_entity_internal(other.into());
+                if let (Some(vel), Some(other_ori)) = (
+                    velocities.get_mut(entity),
+                    other_entity.and_then(|e| orientations.get(e)),
+                ) {
+                    let can_reflect = other_entity
+                        .and_then(|e| character_states.get(e))
+                        .map_or(false, |c| c.can_reflect());
+                    if can_reflect && other_ori.0.dot(vel.0) < 0.0 {
+                        vel.0 = projectile.reflect(other, vel.0);
+                        continue;
+                    }
+                }
+
+                let effects = match projectile.hit(other) {
+                    Some(effects) => effects,
+                    None => continue,
+                };
+                for effect in effects {
                     match effect {
                         projectile::Effect::Damage(healthchange) => {
                             let owner_uid = projectile.owner.unwrap();
+                            // Scale damage (but not healing) by the shooter's stats
+                            let healthchange = match uid_allocator
+                                .retrieve_entity_internal(owner_uid.into())
+                                .and_then(|e| stats.get(e))
+                            {
+                                Some(owner_stats) if healthchange < 0 => {
+                                    -(scaled_damage(-healthchange as u32, owner_stats) as i32)
+                                },
+                                _ => healthchange,
+                            };
                             let mut damage = Damage {
                                 healthchange: healthchange as f32,
                                 source: DamageSource::Projectile,
+                                kind: projectile.damage_kind,
                             };
 
-                            let other_entity = uid_allocator.retrieve_entity_internal(other.into());
+                            // Reward aiming at the weak spot. The projectile has already
+                            // been moved into the target, so check the path it took over the
+                            // last tick.
+                            if let (Some(other_pos), Some(other_body), Some(vel)) = (
+                                other_entity.and_then(|e| positions.get(e)),
+                                other_entity.and_then(|e| bodies.get(e)),
+                                velocities.get(entity),
+                            ) {
+                                damage.healthchange = projectile.weakpoint_damage(
+                                    damage.healthchange,
+                                    other_body.weakpoint_aabb().map(|aabb| Aabb {
+                                        min: aabb.min + other_pos.0,
+                                        max: aabb.max + other_pos.0,
+                                    }),
+                                    pos.0 - vel.0 * dt.0,
+                                    pos.0,
+                                );
+                            }
+
                             if let Some(loadout) = other_entity.and_then(|e| loadouts.get(e)) {
-                                damage.modify_damage(false, loadout);
+                                damage.modify_damage(None, loadout);
                             }
 
                             if other != owner_uid {
@@ -134,14 +197,21 @@ impl<'a> System<'a> for Sys {
                             }
                         },
                         projectile::Effect::Knockback(knockback) => {
-                            if let Some(entity) =
-                                uid_allocator.retrieve_entity_internal(other.into())
+                            if let (Some(other_entity), Some(ori)) =
+                                (other_entity, orientations.get(entity))
                             {
-                                local_emitter.emit(LocalEvent::ApplyImpulse {
-                                    entity,
-                                    impulse: knockback
-                                        * *Dir::slerp(ori.0, Dir::new(Vec3::unit_z()), 0.5),
-                                });
+                                apply_knockback(
+                                    &mut knockbacks,
+                                    &KnockbackTarget {
+                                        entity: other_entity,
+                                        loadout: loadouts.get(other_entity),
+                                        last_knockback: last_knockbacks.get(other_entity),
+                                        immunity: &knockback_immunity,
+                                        time: time.0,
+                                    },
+                                    ori.0,
+                                    knockback,
+                                );
                             }
                         },
                         projectile::Effect::RewardEnergy(energy) => {
@@ -162,10 +232,12 @@ impl<'a> System<'a> for Sys {
                                 reagent: None,
                             })
                         },
-                        projectile::Effect::Vanish => server_emitter.emit(ServerEvent::Destroy {
-                            entity,
-                            cause: HealthSource::World,
-                        }),
+                        projectile::Effect::Vanish => {
+                            server_emitter.emit(ServerEvent::DespawnProjectile {
+                                entity,
+                                reason: DespawnReason::Hit,
+                            })
+                        },
                         projectile::Effect::Possess => {
                             if other != projectile.owner.unwrap() {
                                 if let Some(owner) = projectile.owner {
@@ -178,8 +250,83 @@ impl<'a> System<'a> for Sys {
                 }
             }
 
+            // Steer towards the tracked target
+            if let Some(homing) = projectile.homing {
+                let owner = projectile.owner;
+                let owner_group = owner
+                    .and_then(|uid| uid_allocator.retrieve_entity_internal(uid.into()))
+                    .and_then(|e| groups.get(e));
+                let ignore_group = projectile.ignore_group;
+                let max_range_sqr = homing.params.max_range.powi(2);
+                // Where to aim at the given entity, if it may be targeted
+                let aim_pos = |target| {
+                    let target_pos = positions.get(target)?.0
+                        + Vec3::unit_z() * bodies.get(target).map_or(0.0, |b| b.height() * 0.5);
+                    let alive = stats.get(target).map_or(false, |s| !s.is_dead);
+                    let friendly = uids.get(target).copied() == owner
+                        || (ignore_group
+                            && owner_group.is_some()
+                            && groups.get(target) == owner_group);
+                    if alive && !friendly && target_pos.distance_squared(pos.0) < max_range_sqr {
+                        Some(target_pos)
+                    } else {
+                        None
+                    }
+                };
+
+                let tracked = homing
+                    .target
+                    .and_then(|uid| uid_allocator.retrieve_entity_internal(uid.into()))
+                    .and_then(aim_pos);
+                let (target, target_pos) = match tracked {
+                    Some(target_pos) => (homing.target, Some(target_pos)),
+                    None if homing.target.is_none() || homing.params.retarget => {
+                        // Pick the nearest valid target
+                        let nearest = (&entities, &uids)
+                            .join()
+                            .filter_map(|(e, uid)| Some((*uid, aim_pos(e)?)))
+                            .min_by(|(_, a), (_, b)| {
+                                a.distance_squared(pos.0)
+                                    .partial_cmp(&b.distance_squared(pos.0))
+                                    .unwrap_or(Ordering::Equal)
+                            });
+                        (nearest.map(|(uid, _)| uid), nearest.map(|(_, p)| p))
+                    },
+                    None => (None, None),
+                };
+
+                if let (Some(target_pos), Some(vel)) = (target_pos, velocities.get_mut(entity)) {
+                    vel.0 = projectile::steer_towards(
+                        vel.0,
+                        target_pos - pos.0,
+                        homing.params.turn_rate * dt.0,
+                    );
+                }
+
+                // If the target was lost and we may not pick another one, keep flying
+                // straight
+                projectile.homing =
+                    if tracked.is_none() && homing.target.is_some() && !homing.params.retarget {
+                        None
+                    } else {
+                        Some(Homing { target, ..homing })
+                    };
+            }
+
+            // Bounce off any terrain about to be hit instead, while it still can
+            let bounced =
+                velocities.get_mut(entity).map_or(false, |vel| {
+                    match bounce_off_terrain(&terrain, projectile, pos.0, vel.0, dt.0) {
+                        Some(new_vel) => {
+                            vel.0 = new_vel;
+                            true
+                        },
+                        None => false,
+                    }
+                });
+
             // Hit something solid
-            if physics.on_wall.is_some() || physics.on_ground || physics.on_ceiling {
+            if !bounced && (physics.on_wall.is_some() || physics.on_ground || physics.on_ceiling) {
                 for effect in projectile.hit_solid.drain(..) {
                     match effect {
                         projectile::Effect::Explode(e) => {
@@ -191,34 +338,246 @@ impl<'a> System<'a> for Sys {
                                 reagent: None,
                             })
                         },
-                        projectile::Effect::Vanish => server_emitter.emit(ServerEvent::Destroy {
-                            entity,
-                            cause: HealthSource::World,
-                        }),
+                        projectile::Effect::Vanish => {
+                            server_emitter.emit(ServerEvent::DespawnProjectile {
+                                entity,
+                                reason: DespawnReason::Hit,
+                            })
+                        },
                         _ => {},
                     }
                 }
-            } else if let Some(dir) = velocities
-                .get(entity)
-                .and_then(|vel| vel.0.try_normalized())
-            {
+            } else if let (Some(dir), Some(ori)) = (
+                velocities
+                    .get(entity)
+                    .and_then(|vel| vel.0.try_normalized()),
+                orientations.get_mut(entity),
+            ) {
                 ori.0 = dir.into();
             }
 
-            if projectile.time_left == Duration::default() {
-                server_emitter.emit(ServerEvent::Destroy {
+            if let Some(reason) = projectile.age(Duration::from_secs_f32(dt.0)) {
+                server_emitter.emit(ServerEvent::DespawnProjectile { entity, reason });
+            } else if terrain.get(pos.0.map(|e| e.floor() as i32)).is_err() {
+                server_emitter.emit(ServerEvent::DespawnProjectile {
                     entity,
-                    cause: HealthSource::World,
+                    reason: DespawnReason::OutOfBounds,
                 });
             }
-            projectile.time_left = projectile
-                .time_left
-                .checked_sub(Duration::from_secs_f32(dt.0))
-                .unwrap_or_default();
         }
+        server_emitter.append(&mut knockbacks);
         sys_metrics.projectile_ns.store(
             start_time.elapsed().as_nanos() as i64,
             std::sync::atomic::Ordering::Relaxed,
         );
     }
 }
+
+/// The velocity of a projectile at `pos` after bouncing off the terrain it's
+/// about to hit within `dt` seconds, if any, and if it has bounces left.
+fn bounce_off_terrain(
+    terrain: &TerrainGrid,
+    projectile: &mut Projectile,
+    pos: Vec3<f32>,
+    vel: Vec3<f32>,
+    dt: f32,
+) -> Option<Vec3<f32>> {
+    if projectile.bounces == 0 {
+        return None;
+    }
+    // A little further than it will travel, so that it turns before touching
+    let (_, _, normal) = terrain.raycast(pos, vel, vel.magnitude() * dt + 0.5)?;
+    projectile.bounce(vel, normal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        comp::{humanoid, projectile::Effect, DamageKind},
+        state::State,
+        sync::WorldSyncExt,
+        terrain::{Block, BlockKind, SpriteKind, TerrainChunk, TerrainChunkMeta},
+        vol::WriteVol,
+    };
+    use specs::{Builder, RunNow, WorldExt};
+    use std::sync::Arc;
+
+    /// The damage dealt by an arrow at `pos` flying with `vel` that has just
+    /// touched a humanoid standing at the origin
+    fn arrow_damage(pos: Vec3<f32>, vel: Vec3<f32>) -> i32 {
+        let mut state = State::default();
+        *state.ecs_mut().write_resource::<DeltaTime>() = DeltaTime(0.05);
+        let body = Body::Humanoid(humanoid::Body::random());
+        let target = state
+            .ecs_mut()
+            .create_entity_synced()
+            .with(Pos(Vec3::zero()))
+            .with(body)
+            .build();
+        let target_uid = *state.ecs().read_storage::<Uid>().get(target).unwrap();
+        state
+            .ecs_mut()
+            .create_entity_synced()
+            .with(Pos(pos))
+            .with(Vel(vel))
+            .with(PhysicsState {
+                touch_entities: vec![target_uid],
+                ..PhysicsState::default()
+            })
+            .with(Projectile {
+                hit_solid: vec![Effect::Vanish],
+                hit_entity: vec![Effect::Damage(-10), Effect::Vanish],
+                time_left: Duration::from_secs(15),
+                owner: Some(Uid(u64::MAX)),
+                ignore_group: true,
+                homing: None,
+                pierce: 0,
+                hit_entities: Vec::new(),
+                damage_kind: DamageKind::Physical,
+                bounces: 0,
+                bounce_damping: 0.0,
+                weakpoint_multiplier: 2.0,
+            })
+            .build();
+
+        Sys.run_now(state.ecs());
+
+        state
+            .ecs()
+            .read_resource::<EventBus<ServerEvent>>()
+            .recv_all()
+            .filter_map(|event| match event {
+                ServerEvent::Damage { uid, change } if uid == target_uid => Some(change.amount),
+                _ => None,
+            })
+            .sum()
+    }
+
+    #[test]
+    fn headshots_deal_extra_damage() {
+        let height = Body::Humanoid(humanoid::Body::random()).height();
+        let head = Vec3::new(0.0, 0.0, height - 0.1);
+        let chest = Vec3::new(0.0, 0.0, height * 0.6);
+        let across = Vec3::new(50.0, 0.0, 0.0);
+
+        assert_eq!(arrow_damage(head, across), -20);
+        assert_eq!(arrow_damage(chest, across), -10);
+        // Only the path it took counts, not where it would have gone next
+        assert_eq!(arrow_damage(chest, Vec3::new(0.0, 0.0, 50.0)), -10);
+    }
+
+    #[test]
+    fn projectile_expires_after_lifetime() {
+        let mut state = State::default();
+        *state.ecs_mut().write_resource::<DeltaTime>() = DeltaTime(0.05);
+        state.terrain_mut().insert(
+            Vec2::zero(),
+            Arc::new(TerrainChunk::new(
+                0,
+                Block::new(BlockKind::Rock, Rgb::new(90, 90, 90)),
+                Block::air(SpriteKind::Empty),
+                TerrainChunkMeta::void(),
+            )),
+        );
+        let arrow = state
+            .ecs_mut()
+            .create_entity_synced()
+            .with(Pos(Vec3::new(5.5, 5.5, 5.5)))
+            .with(PhysicsState::default())
+            .with(Projectile {
+                hit_solid: vec![Effect::Vanish],
+                hit_entity: vec![Effect::Damage(-10), Effect::Vanish],
+                time_left: Duration::from_millis(80),
+                owner: None,
+                ignore_group: true,
+                homing: None,
+                pierce: 0,
+                hit_entities: Vec::new(),
+                damage_kind: DamageKind::Physical,
+                bounces: 0,
+                bounce_damping: 0.0,
+                weakpoint_multiplier: 1.0,
+            })
+            .build();
+        let despawns = |state: &State| {
+            state
+                .ecs()
+                .read_resource::<EventBus<ServerEvent>>()
+                .recv_all()
+                .filter_map(|event| match event {
+                    ServerEvent::DespawnProjectile { entity, reason } if entity == arrow => {
+                        Some(reason)
+                    },
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        Sys.run_now(state.ecs());
+        assert_eq!(despawns(&state), Vec::new());
+        Sys.run_now(state.ecs());
+        assert_eq!(despawns(&state), vec![DespawnReason::Expired]);
+    }
+
+    #[test]
+    fn projectile_bounces_off_wall() {
+        let mut terrain = TerrainGrid::new().unwrap();
+        terrain.insert(
+            Vec2::zero(),
+            Arc::new(TerrainChunk::new(
+                0,
+                Block::new(BlockKind::Rock, Rgb::new(90, 90, 90)),
+                Block::air(SpriteKind::Empty),
+                TerrainChunkMeta::void(),
+            )),
+        );
+        for y in 0..10 {
+            for z in 0..10 {
+                terrain
+                    .set(
+                        Vec3::new(10, y, z),
+                        Block::new(BlockKind::Rock, Rgb::new(90, 90, 90)),
+                    )
+                    .unwrap();
+            }
+        }
+
+        let mut projectile = Projectile {
+            hit_solid: vec![Effect::Vanish],
+            hit_entity: vec![Effect::Damage(-10), Effect::Vanish],
+            time_left: Duration::from_secs(15),
+            owner: None,
+            ignore_group: true,
+            homing: None,
+            pierce: 0,
+            hit_entities: Vec::new(),
+            damage_kind: DamageKind::Physical,
+            bounces: 1,
+            bounce_damping: 0.5,
+            weakpoint_multiplier: 1.0,
+        };
+        let pos = Vec3::new(9.5, 5.5, 5.5);
+        let vel = Vec3::new(20.0, 4.0, 0.0);
+        let dt = 1.0 / 30.0;
+
+        // Reflected off the wall's -x face, losing half its speed
+        assert_eq!(
+            bounce_off_terrain(&terrain, &mut projectile, pos, vel, dt),
+            Some(Vec3::new(-10.0, 2.0, 0.0))
+        );
+        assert_eq!(projectile.bounces, 0);
+        // Out of bounces, so it hits the wall as usual next time
+        assert_eq!(
+            bounce_off_terrain(&terrain, &mut projectile, pos, vel, dt),
+            None
+        );
+        // Far from the wall there's nothing to bounce off
+        projectile.bounces = 1;
+        assert_eq!(
+            bounce_off_terrain(&terrain, &mut projectile, Vec3::new(2.5, 5.5, 5.5), vel, dt),
+            None
+        );
+        assert_eq!(projectile.bounces, 1);
+    }
+}
@@ -1,14 +1,18 @@
 use crate::{
     comp::{
-        group, Body, CharacterState, Damage, DamageSource, HealthChange, HealthSource, Last,
-        Loadout, Ori, PhysicsState, Pos, Scale, Shockwave, ShockwaveHitEntities, Stats,
+        group, Body, CharacterState, Damage, DamageKind, DamageSource, HealthChange, HealthSource,
+        KnockbackImmunity, Last, LastKnockback, Loadout, Ori, PhysicsState, Pos, Scale, Shockwave,
+        ShockwaveHitEntities, Stats, Stunned,
     },
     event::{EventBus, LocalEvent, ServerEvent},
     state::{DeltaTime, Time},
+    states::utils::{apply_knockback, KnockbackTarget},
     sync::{Uid, UidAllocator},
-    util::Dir,
+    sys::combat::nearest_targets,
+    util::{Dir, SpatialGrid},
 };
 use specs::{saveload::MarkerAllocator, Entities, Join, Read, ReadStorage, System, WriteStorage};
+use std::collections::VecDeque;
 use vek::*;
 
 pub const BLOCK_ANGLE: f32 = 180.0;
@@ -25,6 +29,8 @@ impl<'a> System<'a> for Sys {
         Read<'a, Time>,
         Read<'a, DeltaTime>,
         Read<'a, UidAllocator>,
+        Read<'a, SpatialGrid>,
+        Read<'a, KnockbackImmunity>,
         ReadStorage<'a, Uid>,
         ReadStorage<'a, Pos>,
         ReadStorage<'a, Last<Pos>>,
@@ -36,8 +42,10 @@ impl<'a> System<'a> for Sys {
         ReadStorage<'a, group::Group>,
         ReadStorage<'a, CharacterState>,
         ReadStorage<'a, PhysicsState>,
+        ReadStorage<'a, LastKnockback>,
         WriteStorage<'a, Shockwave>,
         WriteStorage<'a, ShockwaveHitEntities>,
+        WriteStorage<'a, Stunned>,
     );
 
     fn run(
@@ -49,6 +57,8 @@ impl<'a> System<'a> for Sys {
             time,
             dt,
             uid_allocator,
+            spatial_grid,
+            knockback_immunity,
             uids,
             positions,
             last_positions,
@@ -60,12 +70,15 @@ impl<'a> System<'a> for Sys {
             groups,
             character_states,
             physics_states,
+            last_knockbacks,
             mut shockwaves,
             mut shockwave_hit_lists,
+            mut stuns,
         ): Self::SystemData,
     ) {
         let mut server_emitter = server_bus.emitter();
         let _local_emitter = local_bus.emitter();
+        let mut knockbacks = VecDeque::new();
 
         let time = time.0;
         let dt = dt.0;
@@ -124,33 +137,33 @@ impl<'a> System<'a> for Sys {
                 .and_then(|uid| uid_allocator.retrieve_entity_internal(uid.into()))
                 .and_then(|e| groups.get(e));
 
-            // Go through all other effectable entities
-            for (
-                b,
-                uid_b,
-                pos_b,
-                last_pos_b_maybe,
-                ori_b,
-                scale_b_maybe,
-                character_b,
-                stats_b,
-                body_b,
-                physics_state_b,
-            ) in (
-                &entities,
-                &uids,
-                &positions,
-                // TODO: make sure that these are maintained on the client and remove `.maybe()`
-                last_positions.maybe(),
-                &orientations,
-                scales.maybe(),
-                character_states.maybe(),
-                &stats,
-                &bodies,
-                &physics_states,
-            )
-                .join()
-            {
+            // Go through all other effectable entities the shockwave may have reached
+            let mut targets = Vec::new();
+            for b in spatial_grid.entities_in_radius(pos2, frame_end_dist) {
+                let (uid_b, pos_b, ori_b, stats_b, body_b, physics_state_b) = match (
+                    uids.get(b),
+                    positions.get(b),
+                    orientations.get(b),
+                    stats.get(b),
+                    bodies.get(b),
+                    physics_states.get(b),
+                ) {
+                    (
+                        Some(uid_b),
+                        Some(pos_b),
+                        Some(ori_b),
+                        Some(stats_b),
+                        Some(body_b),
+                        Some(physics_state_b),
+                    ) => (uid_b, pos_b, ori_b, stats_b, body_b, physics_state_b),
+                    _ => continue,
+                };
+                // TODO: make sure that these are maintained on the client and don't treat
+                // them as optional
+                let last_pos_b_maybe = last_positions.get(b);
+                let scale_b_maybe = scales.get(b);
+                let character_b = character_states.get(b);
+
                 // Check to see if entity has already been hit
                 if shockwave_hit_list
                     .hit_entities
@@ -193,51 +206,73 @@ impl<'a> System<'a> for Sys {
                     && !same_group;
 
                 if hit {
-                    let mut damage = Damage {
-                        healthchange: -(shockwave.damage as f32),
-                        source: DamageSource::Shockwave,
-                    };
+                    targets.push((
+                        (b, uid_b, pos_b, ori_b, character_b),
+                        pos.0.distance_squared(pos_b.0),
+                    ));
+                }
+            }
 
-                    let block = character_b.map(|c_b| c_b.is_block()).unwrap_or(false)
-                        && ori_b.0.angle_between(pos.0 - pos_b.0) < BLOCK_ANGLE.to_radians() / 2.0;
+            // Only hit as many of the nearest targets as the shockwave has left
+            let remaining = shockwave.max_targets.map(|max_targets| {
+                max_targets.saturating_sub(shockwave_hit_list.hit_entities.len() as u32)
+            });
+            for (b, uid_b, pos_b, ori_b, character_b) in nearest_targets(targets, remaining) {
+                let mut damage = Damage {
+                    healthchange: -(shockwave.damage as f32),
+                    source: DamageSource::Shockwave,
+                    kind: DamageKind::Physical,
+                };
 
-                    if let Some(loadout) = loadouts.get(b) {
-                        damage.modify_damage(block, loadout);
-                    }
+                let block = character_b
+                    .and_then(|c_b| c_b.block_strength())
+                    .filter(|_| {
+                        ori_b.0.angle_between(pos.0 - pos_b.0) < BLOCK_ANGLE.to_radians() / 2.0
+                    });
 
-                    if damage.healthchange != 0.0 {
-                        let cause = if damage.healthchange < 0.0 {
-                            HealthSource::Attack {
-                                by: shockwave.owner.unwrap_or(*uid),
-                            }
-                        } else {
-                            HealthSource::Healing {
-                                by: Some(shockwave.owner.unwrap_or(*uid)),
-                            }
-                        };
-                        server_emitter.emit(ServerEvent::Damage {
-                            uid: *uid_b,
-                            change: HealthChange {
-                                amount: damage.healthchange as i32,
-                                cause,
-                            },
-                        });
-                        shockwave_hit_list.hit_entities.push(*uid_b);
-                    }
-                    if shockwave.knockback != 0.0 && damage.healthchange != 0.0 {
-                        let kb_dir = Dir::new((pos_b.0 - pos.0).try_normalized().unwrap_or(*ori.0));
-                        let impulse = if shockwave.knockback < 0.0 {
-                            shockwave.knockback
-                                * *Dir::slerp(kb_dir, Dir::new(Vec3::new(0.0, 0.0, -1.0)), 0.85)
-                        } else {
-                            shockwave.knockback
-                                * *Dir::slerp(kb_dir, Dir::new(Vec3::new(0.0, 0.0, 1.0)), 0.5)
-                        };
-                        server_emitter.emit(ServerEvent::Knockback { entity: b, impulse });
+                if let Some(loadout) = loadouts.get(b) {
+                    damage.modify_damage(block, loadout);
+                }
+
+                if damage.healthchange != 0.0 {
+                    let cause = if damage.healthchange < 0.0 {
+                        HealthSource::Attack {
+                            by: shockwave.owner.unwrap_or(*uid),
+                        }
+                    } else {
+                        HealthSource::Healing {
+                            by: Some(shockwave.owner.unwrap_or(*uid)),
+                        }
+                    };
+                    server_emitter.emit(ServerEvent::Damage {
+                        uid: *uid_b,
+                        change: HealthChange {
+                            amount: damage.healthchange as i32,
+                            cause,
+                        },
+                    });
+                    shockwave_hit_list.hit_entities.push(*uid_b);
+                    if let Some(stun) = shockwave.stun {
+                        let _ = stuns.insert(b, Stunned::new(time, stun));
                     }
                 }
+                if damage.healthchange != 0.0 {
+                    apply_knockback(
+                        &mut knockbacks,
+                        &KnockbackTarget {
+                            entity: b,
+                            loadout: loadouts.get(b),
+                            last_knockback: last_knockbacks.get(b),
+                            immunity: &knockback_immunity,
+                            time,
+                        },
+                        Dir::new((pos_b.0 - pos.0).try_normalized().unwrap_or(*ori.0)),
+                        shockwave.knockback,
+                    );
+                }
             }
         }
+        server_emitter.append(&mut knockbacks);
 
         // Set start time on new shockwaves
         // This change doesn't need to be recorded as it is not sent to the client
@@ -357,3 +392,64 @@ fn intersection_points(
 
     (p1, p2)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{comp::humanoid, state::State, sync::WorldSyncExt};
+    use specs::{Builder, RunNow, WorldExt};
+    use std::time::Duration;
+
+    #[test]
+    fn shockwave_stuns_what_it_hits() {
+        let mut state = State::default();
+        *state.ecs_mut().write_resource::<Time>() = Time(0.1);
+        *state.ecs_mut().write_resource::<DeltaTime>() = DeltaTime(0.05);
+        let body = Body::Humanoid(humanoid::Body::random());
+        let target = state
+            .ecs_mut()
+            .create_entity_synced()
+            .with(Pos(Vec3::new(1.5, 0.0, 0.0)))
+            .with(Ori::default())
+            .with(body)
+            .with(Stats::new("Target".to_string(), body))
+            .with(PhysicsState::default())
+            .build();
+        state.ecs().write_resource::<SpatialGrid>().insert(
+            target,
+            Vec2::new(1.5, 0.0),
+            body.radius(),
+        );
+        state
+            .ecs_mut()
+            .create_entity_synced()
+            .with(Pos(Vec3::zero()))
+            .with(Ori(Dir::new(Vec3::unit_x())))
+            .with(Shockwave {
+                properties: crate::comp::shockwave::Properties {
+                    angle: 360.0,
+                    vertical_angle: 90.0,
+                    speed: 20.0,
+                    damage: 10,
+                    knockback: crate::comp::Knockback::default(),
+                    requires_ground: false,
+                    max_targets: None,
+                    stun: Some(Duration::from_secs(1)),
+                    duration: Duration::from_secs(1),
+                    owner: None,
+                },
+                creation: Some(0.0),
+            })
+            .with(ShockwaveHitEntities {
+                hit_entities: Vec::new(),
+            })
+            .build();
+
+        Sys.run_now(state.ecs());
+
+        let stuns = state.ecs().read_storage::<Stunned>();
+        let stun = stuns.get(target).expect("target should be stunned");
+        assert!(stun.is_active(1.0));
+        assert!(!stun.is_active(1.1));
+    }
+}
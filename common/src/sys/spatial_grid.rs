@@ -0,0 +1,43 @@
+use crate::{
+    comp::{Body, Last, Pos, Scale},
+    span,
+    util::SpatialGrid,
+};
+use specs::{Entities, Join, ReadStorage, System, Write};
+
+/// This system rebuilds the [`SpatialGrid`] from entity positions once they
+/// have been updated for this tick
+pub struct Sys;
+impl<'a> System<'a> for Sys {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, Pos>,
+        ReadStorage<'a, Last<Pos>>,
+        ReadStorage<'a, Body>,
+        ReadStorage<'a, Scale>,
+        Write<'a, SpatialGrid>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, positions, last_positions, bodies, scales, mut grid): Self::SystemData,
+    ) {
+        span!(_guard, "run", "spatial_grid::Sys::run");
+        grid.clear();
+        for (entity, pos, last_pos, body, scale) in (
+            &entities,
+            &positions,
+            last_positions.maybe(),
+            bodies.maybe(),
+            scales.maybe(),
+        )
+            .join()
+        {
+            let radius = body.map_or(0.0, |b| b.radius()) * scale.map_or(1.0, |s| s.0);
+            // Cover the ground moved over this tick, for checks that consider where
+            // entities were on the last tick
+            let moved = last_pos.map_or(0.0, |last| (last.0).0.xy().distance(pos.0.xy()));
+            grid.insert(entity, pos.0.xy(), radius + moved);
+        }
+    }
+}
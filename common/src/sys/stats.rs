@@ -1,5 +1,5 @@
 use crate::{
-    comp::{CharacterState, Energy, EnergySource, HealthSource, Stats},
+    comp::{CharacterState, Energy, EnergyRegen, EnergySource, HealthSource, Loadout, Stats},
     event::{EventBus, ServerEvent},
     metrics::SysMetrics,
     span,
@@ -7,8 +7,6 @@ use crate::{
 };
 use specs::{Entities, Join, Read, ReadExpect, ReadStorage, System, WriteStorage};
 
-const ENERGY_REGEN_ACCEL: f32 = 10.0;
-
 /// This system kills players, levels them up, and regenerates energy.
 pub struct Sys;
 impl<'a> System<'a> for Sys {
@@ -16,16 +14,28 @@ impl<'a> System<'a> for Sys {
     type SystemData = (
         Entities<'a>,
         Read<'a, DeltaTime>,
+        Read<'a, EnergyRegen>,
         Read<'a, EventBus<ServerEvent>>,
         ReadExpect<'a, SysMetrics>,
         ReadStorage<'a, CharacterState>,
+        ReadStorage<'a, Loadout>,
         WriteStorage<'a, Stats>,
         WriteStorage<'a, Energy>,
     );
 
     fn run(
         &mut self,
-        (entities, dt, server_event_bus, sys_metrics, character_states, mut stats, mut energies): Self::SystemData,
+        (
+            entities,
+            dt,
+            energy_regen,
+            server_event_bus,
+            sys_metrics,
+            character_states,
+            loadouts,
+            mut stats,
+            mut energies,
+        ): Self::SystemData,
     ) {
         let start_time = std::time::Instant::now();
         span!(_guard, "run", "stats::Sys::run");
@@ -73,12 +83,21 @@ impl<'a> System<'a> for Sys {
             }
         }
 
+        // Apply the energy granted by armor set bonuses
+        for (stat, loadout, mut energy) in (&stats, &loadouts, &mut energies.restrict_mut()).join()
+        {
+            let maximum = stat.body_type.base_energy() + loadout.set_bonus_energy();
+            if energy.get_unchecked().maximum() != maximum {
+                energy.get_mut_unchecked().set_maximum(maximum);
+            }
+        }
+
         // Update energies
         for (character_state, mut energy) in
             (&character_states, &mut energies.restrict_mut()).join()
         {
             match character_state {
-                // Accelerate recharging energy.
+                // Recharge energy.
                 CharacterState::Idle { .. }
                 | CharacterState::Sit { .. }
                 | CharacterState::Dance { .. }
@@ -87,25 +106,20 @@ impl<'a> System<'a> for Sys {
                 | CharacterState::GlideWield { .. }
                 | CharacterState::Wielding { .. }
                 | CharacterState::Equipping { .. }
-                | CharacterState::Boost { .. } => {
+                | CharacterState::Boost { .. }
+                | CharacterState::Harvest { .. }
+                | CharacterState::PlaceBlock { .. }
+                | CharacterState::SwapWeapons { .. } => {
                     let res = {
                         let energy = energy.get_unchecked();
                         energy.current() < energy.maximum()
                     };
 
                     if res {
-                        let mut energy = energy.get_mut_unchecked();
-                        // Have to account for Calc I differential equations due to acceleration
-                        energy.change_by(
-                            (energy.regen_rate * dt.0 + ENERGY_REGEN_ACCEL * dt.0.powf(2.0) / 2.0)
-                                as i32,
-                            EnergySource::Regen,
-                        );
-                        energy.regen_rate =
-                            (energy.regen_rate + ENERGY_REGEN_ACCEL * dt.0).min(100.0);
+                        energy.get_mut_unchecked().regen(dt.0, &energy_regen);
                     }
                 },
-                // Ability use does not regen and sets the rate back to zero.
+                // Ability use does not regen.
                 CharacterState::BasicMelee { .. }
                 | CharacterState::DashMelee { .. }
                 | CharacterState::LeapMelee { .. }
@@ -117,13 +131,13 @@ impl<'a> System<'a> for Sys {
                 | CharacterState::RepeaterRanged { .. }
                 | CharacterState::Shockwave { .. }
                 | CharacterState::BasicBeam { .. } => {
-                    if energy.get_unchecked().regen_rate != 0.0 {
-                        energy.get_mut_unchecked().regen_rate = 0.0
+                    if energy.get_unchecked().regen_paused(&energy_regen) {
+                        energy.get_mut_unchecked().advance_regen_pause(dt.0);
                     }
                 },
                 // recover small amount of passive energy from blocking, and bonus energy from
                 // blocking attacks?
-                CharacterState::BasicBlock => {
+                CharacterState::BasicBlock(_) => {
                     let res = {
                         let energy = energy.get_unchecked();
                         energy.current() < energy.maximum()
@@ -136,8 +150,15 @@ impl<'a> System<'a> for Sys {
                     }
                 },
                 // Non-combat abilities that consume energy;
-                // temporarily stall energy gain, but preserve regen_rate.
-                CharacterState::Roll { .. } | CharacterState::Climb { .. } => {},
+                // temporarily stall energy gain.
+                CharacterState::Roll { .. }
+                | CharacterState::Climb { .. }
+                | CharacterState::Sprint { .. }
+                | CharacterState::Grapple { .. } => {
+                    if energy.get_unchecked().regen_paused(&energy_regen) {
+                        energy.get_mut_unchecked().advance_regen_pause(dt.0);
+                    }
+                },
             }
         }
         sys_metrics.stats_ns.store(
@@ -146,3 +167,69 @@ impl<'a> System<'a> for Sys {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        comp::{
+            humanoid,
+            item::{Item, ItemDef},
+            Body,
+        },
+        state::State,
+    };
+    use specs::{Builder, RunNow, WorldExt};
+    use std::sync::Arc;
+
+    fn plate(kind: &str) -> Option<Item> {
+        let def = ron::de::from_str::<ItemDef>(&format!(
+            "(name: \"Test\", description: \"\", kind: Armor((kind: {}(\"Test\"), stats: \
+             (protection: Normal(1.0)), set_id: Some(\"Plate\"))), quality: Common)",
+            kind,
+        ))
+        .unwrap();
+        Some(Item::new(Arc::new(def)))
+    }
+
+    #[test]
+    fn full_set_raises_maximum_energy() {
+        let mut state = State::default();
+        let body = Body::Humanoid(humanoid::Body::random());
+        let entity = state
+            .ecs_mut()
+            .create_entity()
+            .with(Stats::new("Test".to_string(), body))
+            .with(Energy::new(body.base_energy()))
+            .with(CharacterState::Idle)
+            .with(Loadout {
+                chest: plate("Chest"),
+                pants: plate("Pants"),
+                foot: plate("Foot"),
+                hand: plate("Hand"),
+                head: plate("Head"),
+                ..Loadout::default()
+            })
+            .build();
+
+        Sys.run_now(state.ecs());
+        let maximum = |state: &State| {
+            state
+                .ecs()
+                .read_storage::<Energy>()
+                .get(entity)
+                .unwrap()
+                .maximum()
+        };
+        assert_eq!(maximum(&state), body.base_energy() + 200);
+
+        state
+            .ecs()
+            .write_storage::<Loadout>()
+            .get_mut(entity)
+            .unwrap()
+            .head = None;
+        Sys.run_now(state.ecs());
+        assert_eq!(maximum(&state), body.base_energy());
+    }
+}
@@ -1,4 +1,6 @@
+use crate::lottery::Lottery;
 use serde::{Deserialize, Serialize};
+use vek::*;
 
 #[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub enum BiomeKind {
@@ -11,3 +13,155 @@ pub enum BiomeKind {
     Swamp,
     Forest,
 }
+
+/// How the air looks within a biome. Colour components are in the range
+/// `0.0..=1.0`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Atmosphere {
+    pub fog_color: Rgb<f32>,
+    /// How thick the fog is, from `0.0` (clear) to `1.0` (opaque)
+    pub fog_density: f32,
+    pub sky_tint: Rgb<f32>,
+}
+
+impl BiomeKind {
+    pub fn atmosphere(&self) -> Atmosphere {
+        let (fog_color, fog_density, sky_tint) = match self {
+            BiomeKind::Void => (Rgb::zero(), 0.0, Rgb::one()),
+            BiomeKind::Grassland => (Rgb::new(0.75, 0.82, 0.9), 0.05, Rgb::new(0.95, 1.0, 1.0)),
+            BiomeKind::Ocean => (Rgb::new(0.65, 0.78, 0.9), 0.1, Rgb::new(0.85, 0.95, 1.0)),
+            BiomeKind::Mountain => (Rgb::new(0.8, 0.82, 0.85), 0.15, Rgb::new(0.9, 0.93, 1.0)),
+            BiomeKind::Snowlands => (Rgb::new(0.9, 0.92, 0.95), 0.25, Rgb::new(0.92, 0.95, 1.0)),
+            BiomeKind::Desert => (Rgb::new(0.92, 0.85, 0.7), 0.08, Rgb::new(1.0, 0.95, 0.85)),
+            BiomeKind::Swamp => (Rgb::new(0.55, 0.6, 0.45), 0.4, Rgb::new(0.85, 0.9, 0.8)),
+            BiomeKind::Forest => (Rgb::new(0.6, 0.72, 0.6), 0.2, Rgb::new(0.9, 1.0, 0.92)),
+        };
+        Atmosphere {
+            fog_color,
+            fog_density,
+            sky_tint,
+        }
+    }
+
+    /// How hostile creatures living in this biome are, from `0.0` (docile) to
+    /// `1.0` (very alert). Used to scale how far NPCs notice their targets.
+    pub fn danger_level(&self) -> f32 {
+        match self {
+            BiomeKind::Void => 1.0,
+            BiomeKind::Grassland => 0.1,
+            BiomeKind::Ocean => 0.3,
+            BiomeKind::Mountain => 0.5,
+            BiomeKind::Snowlands => 0.6,
+            BiomeKind::Desert => 0.4,
+            BiomeKind::Swamp => 0.7,
+            BiomeKind::Forest => 0.3,
+        }
+    }
+
+    /// Extra items dropped by creatures in this biome, as the share of a
+    /// loot table's total rate to add for each item.
+    pub fn loot_bonus(&self) -> &'static [(f32, &'static str)] {
+        match self {
+            BiomeKind::Mountain => &[
+                (0.15, "common.items.crafting_ing.shiny_gem"),
+                (0.1, "common.items.ore.veloritefrag"),
+                (0.03, "common.items.ore.velorite"),
+            ],
+            BiomeKind::Forest => &[
+                (0.15, "common.items.food.mushroom"),
+                (0.1, "common.items.flowers.white"),
+                (0.1, "common.items.crafting_ing.twigs"),
+            ],
+            BiomeKind::Swamp => &[(0.15, "common.items.food.mushroom")],
+            BiomeKind::Snowlands => &[(0.1, "common.items.crafting_ing.icy_fang")],
+            BiomeKind::Void | BiomeKind::Grassland | BiomeKind::Ocean | BiomeKind::Desert => &[],
+        }
+    }
+
+    /// `base` with this biome's [`BiomeKind::loot_bonus`] added to it
+    pub fn loot_table(&self, base: &Lottery<String>) -> Lottery<String> {
+        let total = base.total();
+        base.clone().extended(
+            self.loot_bonus()
+                .iter()
+                .map(|(share, item)| (share * total, item.to_string())),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL_BIOMES: [BiomeKind; 8] = [
+        BiomeKind::Void,
+        BiomeKind::Grassland,
+        BiomeKind::Ocean,
+        BiomeKind::Mountain,
+        BiomeKind::Snowlands,
+        BiomeKind::Desert,
+        BiomeKind::Swamp,
+        BiomeKind::Forest,
+    ];
+
+    #[test]
+    fn atmosphere_in_range() {
+        let in_range = |x: f32| x.is_finite() && (0.0..=1.0).contains(&x);
+        for biome in ALL_BIOMES.iter() {
+            let atmos = biome.atmosphere();
+            assert!(in_range(atmos.fog_density), "{:?}", biome);
+            assert!(
+                atmos
+                    .fog_color
+                    .into_array()
+                    .iter()
+                    .chain(atmos.sky_tint.into_array().iter())
+                    .all(|c| in_range(*c)),
+                "{:?}",
+                biome
+            );
+        }
+    }
+
+    #[test]
+    fn void_is_most_dangerous() {
+        let void = BiomeKind::Void.danger_level();
+        for biome in ALL_BIOMES.iter() {
+            let danger = biome.danger_level();
+            assert!((0.0..=1.0).contains(&danger), "{:?}", biome);
+            assert!(danger <= void, "{:?}", biome);
+        }
+        assert!(BiomeKind::Grassland.danger_level() < 0.25);
+    }
+
+    #[test]
+    fn mountains_drop_gems() {
+        let base =
+            Lottery::from_rates(vec![(3.0, "common.items.food.apple".to_string())].into_iter());
+        let gem = "common.items.crafting_ing.shiny_gem";
+
+        let mountain = BiomeKind::Mountain.loot_table(&base);
+        assert!(mountain.iter().any(|(_, item)| item == gem));
+        assert_eq!(
+            mountain.iter().count(),
+            1 + BiomeKind::Mountain.loot_bonus().len()
+        );
+        assert!(mountain.total() > base.total());
+
+        assert_eq!(BiomeKind::Grassland.loot_table(&base), base);
+    }
+
+    #[test]
+    fn loot_bonus_items_exist() {
+        for biome in ALL_BIOMES.iter() {
+            for (share, item) in biome.loot_bonus() {
+                assert!(*share > 0.0, "{:?}", biome);
+                assert!(
+                    crate::comp::Item::new_from_asset(item).is_ok(),
+                    "Invalid loot bonus item '{}'",
+                    item
+                );
+            }
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use super::SpriteKind;
-use crate::make_case_elim;
+use crate::{comp::DamageKind, make_case_elim};
 use enum_iterator::IntoEnumIterator;
 use lazy_static::lazy_static;
 use num_derive::FromPrimitive;
@@ -26,7 +26,8 @@ make_case_elim!(
     pub enum BlockKind {
         Air = 0x00, // Air counts as a fluid
         Water = 0x01,
-        // 0x02 <= x < 0x10 are reserved for other fluids. These are 2^n aligned to allow bitwise
+        Lava = 0x02,
+        // 0x03 <= x < 0x10 are reserved for other fluids. These are 2^n aligned to allow bitwise
         // checking of common conditions. For example, `is_fluid` is just `block_kind &
         // 0x0F == 0` (this is a very common operation used in meshing that could do with
         // being *very* fast).
@@ -37,10 +38,14 @@ make_case_elim!(
         // 0x21 <= x < 0x30 is reserved for future grasses
         Earth = 0x30,
         Sand = 0x31,
-        // 0x32 <= x < 0x40 is reserved for future earths/muds/gravels/sands/etc.
+        Mud = 0x32,
+        Road = 0x33, // Packed earth of paths
+        // 0x34 <= x < 0x40 is reserved for future earths/muds/gravels/sands/etc.
         Wood = 0x40,
         Leaves = 0x41,
         // 0x42 <= x < 0x50 is reserved for future tree parts
+        Trap = 0x50, // Hurts entities that step on it
+        // 0x51 <= x < 0x60 is reserved for future mechanisms
 
         // Covers all other cases (we sometimes have bizarrely coloured misc blocks, and also we
         // often want to experiment with new kinds of block without allocating them a
@@ -49,6 +54,36 @@ make_case_elim!(
     }
 );
 
+/// The kinds of particle that blocks give off into their surroundings
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub enum AmbientParticle {
+    /// Drifts slowly down to the ground
+    Leaf,
+    /// Flickers up from the surface and soon burns out
+    Ember,
+}
+
+/// How blocks of a kind give off particles, see
+/// [`BlockKind::ambient_particles`]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ParticleSpec {
+    pub kind: AmbientParticle,
+    /// Average number of particles each block emits per second
+    pub rate: f32,
+    /// How many seconds each particle lives for
+    pub lifetime: f32,
+}
+
+/// The action a block offers to a player looking at it
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub enum Interaction {
+    Mine,
+    Open,
+    Sit,
+    Read,
+    Collect,
+}
+
 impl BlockKind {
     #[inline]
     pub const fn is_air(&self) -> bool { matches!(self, BlockKind::Air) }
@@ -71,6 +106,132 @@ impl BlockKind {
     /// fields.
     #[inline]
     pub const fn has_color(&self) -> bool { self.is_filled() }
+
+    /// Determine whether blocks of this kind may be tinted (for example, by
+    /// biome) on a per-block basis.
+    #[inline]
+    pub const fn is_tintable(&self) -> bool { matches!(self, BlockKind::Grass | BlockKind::Leaves) }
+
+    /// How much damage a block of this kind can take before breaking, or
+    /// `None` if it can't be broken.
+    #[inline]
+    pub const fn max_health(&self) -> Option<u16> {
+        match self {
+            BlockKind::Air | BlockKind::Water | BlockKind::Lava => None,
+            BlockKind::Leaves => Some(20),
+            BlockKind::Grass
+            | BlockKind::Earth
+            | BlockKind::Sand
+            | BlockKind::Mud
+            | BlockKind::Road => Some(60),
+            BlockKind::WeakRock | BlockKind::Trap | BlockKind::Misc => Some(100),
+            BlockKind::Wood => Some(150),
+            BlockKind::Rock => Some(400),
+        }
+    }
+
+    /// The fraction of damage of the given kind that a block of this kind
+    /// shrugs off. Negative values make the block take extra damage.
+    pub fn resistance(&self, kind: DamageKind) -> f32 {
+        match (self, kind) {
+            (BlockKind::Rock, DamageKind::Fire) | (BlockKind::WeakRock, DamageKind::Fire) => 0.75,
+            (BlockKind::Rock, DamageKind::Frost) | (BlockKind::WeakRock, DamageKind::Frost) => 0.5,
+            // Meant to be dug through
+            (BlockKind::WeakRock, DamageKind::Physical) => -0.5,
+            (BlockKind::Grass, DamageKind::Fire)
+            | (BlockKind::Earth, DamageKind::Fire)
+            | (BlockKind::Sand, DamageKind::Fire)
+            | (BlockKind::Mud, DamageKind::Fire)
+            | (BlockKind::Road, DamageKind::Fire) => 0.5,
+            (BlockKind::Wood, DamageKind::Fire) => -0.5,
+            (BlockKind::Leaves, DamageKind::Fire) => -1.0,
+            // Nothing to poison
+            (_, DamageKind::Poison) if self.is_filled() && *self != BlockKind::Leaves => 1.0,
+            _ => 0.0,
+        }
+    }
+
+    /// Determine whether blocks of this kind go off when an entity steps on
+    /// them.
+    #[inline]
+    pub const fn is_trap(&self) -> bool { matches!(self, BlockKind::Trap) }
+
+    /// The action a block of this kind offers, ignoring any sprite in it.
+    /// See [`Block::interaction`] for the action of a particular block.
+    #[inline]
+    pub const fn interaction(&self) -> Option<Interaction> {
+        match self {
+            // Meant to be dug through
+            BlockKind::WeakRock => Some(Interaction::Mine),
+            _ => None,
+        }
+    }
+
+    /// How much faster (or slower) entities walk while standing on a block
+    /// of this kind.
+    #[inline]
+    pub const fn movement_speed_mul(&self) -> f32 {
+        match self {
+            BlockKind::Road => 1.15,
+            BlockKind::Sand => 0.85,
+            BlockKind::Mud => 0.7,
+            _ => 1.0,
+        }
+    }
+
+    /// Whether walls made of this kind of block have enough grip to climb,
+    /// like rough rock and timber. Climbable sprites, such as vines, are
+    /// covered by [`Block::is_climbable`].
+    #[inline]
+    pub const fn is_climbable(&self) -> bool {
+        matches!(
+            self,
+            BlockKind::Rock | BlockKind::WeakRock | BlockKind::Wood | BlockKind::Leaves
+        )
+    }
+
+    /// The particles that blocks of this kind give off, or `None` if they
+    /// stay still.
+    #[inline]
+    pub const fn ambient_particles(&self) -> Option<ParticleSpec> {
+        match self {
+            BlockKind::Leaves => Some(ParticleSpec {
+                kind: AmbientParticle::Leaf,
+                rate: 0.00006,
+                lifetime: 30.0,
+            }),
+            BlockKind::Lava => Some(ParticleSpec {
+                kind: AmbientParticle::Ember,
+                rate: 0.002,
+                lifetime: 0.5,
+            }),
+            _ => None,
+        }
+    }
+
+    /// What a block of this kind slowly turns into when it sits next to a
+    /// block of kind `neighbor`, or `None` if it is unaffected.
+    #[inline]
+    pub const fn spread_candidate(&self, neighbor: BlockKind) -> Option<BlockKind> {
+        match (self, neighbor) {
+            // Grass creeps across bare earth
+            (BlockKind::Earth, BlockKind::Grass) => Some(BlockKind::Grass),
+            // Fallen leaves seed the forest floor
+            (BlockKind::Earth, BlockKind::Leaves) => Some(BlockKind::Grass),
+            _ => None,
+        }
+    }
+
+    /// The plant that slowly sprouts on top of a block of this kind when it
+    /// sits next to a block of kind `neighbor`, or `None` if nothing does.
+    #[inline]
+    pub const fn sprout_candidate(&self, neighbor: BlockKind) -> Option<SpriteKind> {
+        match (self, neighbor) {
+            // Flowers bloom on well-watered grass
+            (BlockKind::Grass, BlockKind::Water) => Some(SpriteKind::BlueFlower),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for BlockKind {
@@ -143,6 +304,17 @@ impl Block {
         }
     }
 
+    /// The tint of this block, if its kind can be tinted. The tint shares the
+    /// attribute bits used by [`Block::get_color`], so it costs no extra space.
+    #[inline]
+    pub fn color(&self) -> Option<Rgb<u8>> {
+        if self.is_tintable() {
+            self.get_color()
+        } else {
+            None
+        }
+    }
+
     #[inline]
     pub fn get_sprite(&self) -> Option<SpriteKind> {
         if !self.is_filled() {
@@ -191,6 +363,12 @@ impl Block {
         }
     }
 
+    /// Whether an entity pressed against this block can climb it
+    #[inline]
+    pub fn is_climbable(&self) -> bool {
+        self.kind().is_climbable() || self.get_sprite() == Some(SpriteKind::Liana)
+    }
+
     #[inline]
     pub fn is_collectible(&self) -> bool {
         self.get_sprite()
@@ -198,6 +376,14 @@ impl Block {
             .unwrap_or(false)
     }
 
+    /// The action this block offers, preferring that of its sprite
+    #[inline]
+    pub fn interaction(&self) -> Option<Interaction> {
+        self.get_sprite()
+            .and_then(|s| s.interaction())
+            .or_else(|| self.kind().interaction())
+    }
+
     #[inline]
     pub fn is_opaque(&self) -> bool { self.kind().is_filled() }
 
@@ -220,6 +406,15 @@ impl Block {
         self
     }
 
+    /// If this block can be tinted, give it a new tint.
+    #[inline]
+    pub fn with_color(mut self, color: Rgb<u8>) -> Self {
+        if self.is_tintable() {
+            self.attr = [color.r, color.g, color.b];
+        }
+        self
+    }
+
     /// If this block can have orientation, give it a new orientation.
     #[inline]
     pub fn with_ori(mut self, ori: u8) -> Option<Self> {
@@ -231,6 +426,32 @@ impl Block {
         }
     }
 
+    /// What this block turns into when `neighbor` spreads into it, if
+    /// anything. Nothing spreads into a block that has something solid on top
+    /// of it.
+    #[inline]
+    pub fn spread_from(&self, above: Block, neighbor: Block) -> Option<Self> {
+        if above.is_filled() {
+            return None;
+        }
+        self.kind()
+            .spread_candidate(neighbor.kind())
+            .map(|kind| Block::new(kind, neighbor.get_color().unwrap_or_else(Rgb::zero)))
+    }
+
+    /// What the block `above` this one turns into when a plant sprouts on
+    /// this block next to `neighbor`, if anything. Plants only sprout in open
+    /// air that has nothing else in it.
+    #[inline]
+    pub fn sprout_from(&self, above: Block, neighbor: Block) -> Option<Self> {
+        if !above.is_air() || above.get_sprite() != Some(SpriteKind::Empty) {
+            return None;
+        }
+        self.kind()
+            .sprout_candidate(neighbor.kind())
+            .map(|sprite| above.with_sprite(sprite))
+    }
+
     /// Remove the terrain sprite or solid aspects of a block
     #[inline]
     pub fn into_vacant(self) -> Self {
@@ -248,9 +469,172 @@ impl Block {
 mod tests {
     use super::*;
 
+    #[test]
+    fn leaves_give_off_particles() {
+        let leaves = BlockKind::Leaves.ambient_particles().unwrap();
+        assert_eq!(leaves.kind, AmbientParticle::Leaf);
+        assert!(leaves.rate > 0.0 && leaves.lifetime > 0.0);
+        assert_eq!(BlockKind::Rock.ambient_particles(), None);
+        assert_eq!(BlockKind::Air.ambient_particles(), None);
+    }
+
+    #[test]
+    fn lava_gives_off_embers() {
+        let lava = BlockKind::Lava.ambient_particles().unwrap();
+        assert_eq!(lava.kind, AmbientParticle::Ember);
+        assert!(lava.rate > 0.0 && lava.lifetime > 0.0);
+        assert_eq!(BlockKind::Water.ambient_particles(), None);
+        assert!(BlockKind::Lava.is_liquid());
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn surfaces_change_walk_speed() {
+        assert!(BlockKind::Road.movement_speed_mul() > 1.0);
+        assert!(BlockKind::Mud.movement_speed_mul() < 1.0);
+        assert_eq!(BlockKind::Grass.movement_speed_mul(), 1.0);
+    }
+
+    #[test]
+    fn only_rough_walls_and_vines_are_climbable() {
+        assert!(Block::new(BlockKind::Rock, Rgb::new(100, 100, 100)).is_climbable());
+        assert!(Block::new(BlockKind::Wood, Rgb::new(80, 50, 20)).is_climbable());
+        assert!(!Block::new(BlockKind::Sand, Rgb::new(200, 180, 120)).is_climbable());
+        assert!(!Block::new(BlockKind::Misc, Rgb::new(255, 255, 255)).is_climbable());
+        assert!(Block::air(SpriteKind::Liana).is_climbable());
+        assert!(!Block::air(SpriteKind::Empty).is_climbable());
+    }
+
     #[test]
     fn block_size() {
         assert_eq!(std::mem::size_of::<BlockKind>(), 1);
         assert_eq!(std::mem::size_of::<Block>(), 4);
     }
+
+    #[test]
+    fn color_round_trip() {
+        let tint = Rgb::new(12, 200, 34);
+        for kind in [BlockKind::Grass, BlockKind::Leaves].iter() {
+            let block = Block::new(*kind, Rgb::zero()).with_color(tint);
+            assert_eq!(block.color(), Some(tint));
+            assert_eq!(block.kind(), *kind);
+        }
+        let block = Block::new(BlockKind::Leaves, tint).with_color(Rgb::new(1, 2, 3));
+        assert_eq!(block.color(), Some(Rgb::new(1, 2, 3)));
+    }
+
+    #[test]
+    fn untintable_blocks_have_no_color() {
+        let tint = Rgb::new(12, 200, 34);
+        let rock = Block::new(BlockKind::Rock, Rgb::new(5, 5, 5));
+        assert_eq!(rock.with_color(tint), rock);
+        assert_eq!(rock.color(), None);
+
+        let air = Block::air(SpriteKind::LongGrass);
+        assert_eq!(air.with_color(tint), air);
+        assert_eq!(air.color(), None);
+        assert_eq!(air.get_sprite(), Some(SpriteKind::LongGrass));
+    }
+
+    #[test]
+    fn grass_spreads_to_exposed_earth() {
+        let earth = Block::new(BlockKind::Earth, Rgb::new(80, 50, 20));
+        let grass = Block::new(BlockKind::Grass, Rgb::new(20, 160, 30));
+        let air = Block::air(SpriteKind::Empty);
+        let rock = Block::new(BlockKind::Rock, Rgb::new(90, 90, 90));
+
+        assert_eq!(
+            BlockKind::Earth.spread_candidate(BlockKind::Grass),
+            Some(BlockKind::Grass)
+        );
+        assert_eq!(earth.spread_from(air, grass), Some(grass));
+        // Covered earth gets no light, so grass can't take hold
+        assert_eq!(earth.spread_from(rock, grass), None);
+        // Rock isn't a living thing and spreads nowhere
+        assert_eq!(earth.spread_from(air, rock), None);
+        assert_eq!(BlockKind::Rock.spread_candidate(BlockKind::Grass), None);
+    }
+
+    #[test]
+    fn flowers_sprout_on_grass_by_water() {
+        let grass = Block::new(BlockKind::Grass, Rgb::new(20, 160, 30));
+        let earth = Block::new(BlockKind::Earth, Rgb::new(80, 50, 20));
+        let water = Block::water(SpriteKind::Empty);
+        let air = Block::air(SpriteKind::Empty);
+
+        assert_eq!(
+            grass.sprout_from(air, water),
+            Some(Block::air(SpriteKind::BlueFlower))
+        );
+        // Grass away from water stays bare
+        assert_eq!(grass.sprout_from(air, grass), None);
+        // Flowers don't grow out of bare earth, underwater or over other plants
+        assert_eq!(earth.sprout_from(air, water), None);
+        assert_eq!(grass.sprout_from(water, water), None);
+        assert_eq!(
+            grass.sprout_from(Block::air(SpriteKind::LongGrass), water),
+            None
+        );
+    }
+
+    #[test]
+    fn blocks_offer_interactions() {
+        let stone = Block::new(BlockKind::WeakRock, Rgb::new(90, 90, 90));
+        assert_eq!(stone.interaction(), Some(Interaction::Mine));
+        assert_eq!(
+            Block::air(SpriteKind::Chest).interaction(),
+            Some(Interaction::Open)
+        );
+        assert_eq!(
+            Block::air(SpriteKind::Velorite).interaction(),
+            Some(Interaction::Mine)
+        );
+        assert_eq!(
+            Block::air(SpriteKind::Bench).interaction(),
+            Some(Interaction::Sit)
+        );
+        assert_eq!(
+            Block::air(SpriteKind::HangingSign).interaction(),
+            Some(Interaction::Read)
+        );
+        assert_eq!(
+            Block::air(SpriteKind::Apple).interaction(),
+            Some(Interaction::Collect)
+        );
+        assert_eq!(Block::air(SpriteKind::Empty).interaction(), None);
+        assert_eq!(Block::air(SpriteKind::LongGrass).interaction(), None);
+        assert_eq!(Block::water(SpriteKind::Empty).interaction(), None);
+    }
+
+    #[test]
+    fn only_weak_rock_is_mined() {
+        // Fluids
+        assert_eq!(BlockKind::Air.interaction(), None);
+        assert_eq!(BlockKind::Water.interaction(), None);
+        // Rocks
+        assert_eq!(BlockKind::Rock.interaction(), None);
+        assert_eq!(BlockKind::WeakRock.interaction(), Some(Interaction::Mine));
+        // Grasses and earths
+        assert_eq!(BlockKind::Grass.interaction(), None);
+        assert_eq!(BlockKind::Earth.interaction(), None);
+        assert_eq!(BlockKind::Sand.interaction(), None);
+        assert_eq!(BlockKind::Mud.interaction(), None);
+        assert_eq!(BlockKind::Road.interaction(), None);
+        // Tree parts
+        assert_eq!(BlockKind::Wood.interaction(), None);
+        assert_eq!(BlockKind::Leaves.interaction(), None);
+        // Mechanisms and everything else
+        assert_eq!(BlockKind::Trap.interaction(), None);
+        assert_eq!(BlockKind::Misc.interaction(), None);
+    }
+
+    #[test]
+    fn only_traps_are_traps() {
+        assert_eq!(
+            BlockKind::into_enum_iter()
+                .filter(BlockKind::is_trap)
+                .collect::<Vec<_>>(),
+            vec![BlockKind::Trap]
+        );
+    }
 }
@@ -5,7 +5,12 @@ use crate::{
     },
     volumes::chunk::{Chunk, ChunkError, ChunkPosIter, ChunkVolIter},
 };
-use core::{hash::Hash, marker::PhantomData};
+use core::{
+    fmt,
+    hash::Hash,
+    marker::PhantomData,
+    sync::atomic::{AtomicI32, Ordering},
+};
 use serde::{Deserialize, Serialize};
 use vek::*;
 
@@ -33,6 +38,92 @@ impl<ChonkSize: RectVolSize> VolSize for SubChunkSize<ChonkSize> {
 
 type SubChunk<V, S, M> = Chunk<V, SubChunkSize<S>, M>;
 
+/// A lazily computed value for each column of a chonk, such as the height of
+/// its highest opaque block. Entries are forgotten whenever their column
+/// changes.
+pub struct ColumnCache<S: RectVolSize> {
+    columns: Box<[AtomicI32]>,
+    phantom: PhantomData<S>,
+}
+
+// `i32::MIN` is never a useful column value, so it marks missing entries
+const UNCACHED: i32 = i32::MIN;
+
+impl<S: RectVolSize> ColumnCache<S> {
+    fn idx(pos: Vec2<i32>) -> Option<usize> {
+        if pos.x >= 0
+            && pos.y >= 0
+            && pos.x < S::RECT_SIZE.x as i32
+            && pos.y < S::RECT_SIZE.y as i32
+        {
+            Some((pos.y * S::RECT_SIZE.x as i32 + pos.x) as usize)
+        } else {
+            None
+        }
+    }
+
+    /// The cached value for the column at `pos`, computing it with `f` if
+    /// there is none. Columns outside the chonk are never cached.
+    pub fn get_or_insert_with(&self, pos: Vec2<i32>, f: impl FnOnce() -> i32) -> i32 {
+        let column = match Self::idx(pos) {
+            Some(idx) => &self.columns[idx],
+            None => return f(),
+        };
+        match column.load(Ordering::Relaxed) {
+            UNCACHED => {
+                let value = f();
+                column.store(value, Ordering::Relaxed);
+                value
+            },
+            value => value,
+        }
+    }
+
+    /// Forget the value for the column at `pos`.
+    pub fn invalidate(&mut self, pos: Vec2<i32>) {
+        if let Some(idx) = Self::idx(pos) {
+            *self.columns[idx].get_mut() = UNCACHED;
+        }
+    }
+
+    /// Forget the values for every column.
+    pub fn clear(&mut self) {
+        self.columns
+            .iter_mut()
+            .for_each(|column| *column.get_mut() = UNCACHED);
+    }
+}
+
+impl<S: RectVolSize> Default for ColumnCache<S> {
+    fn default() -> Self {
+        Self {
+            columns: (0..S::RECT_SIZE.product())
+                .map(|_| AtomicI32::new(UNCACHED))
+                .collect(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<S: RectVolSize> Clone for ColumnCache<S> {
+    fn clone(&self) -> Self {
+        Self {
+            columns: self
+                .columns
+                .iter()
+                .map(|column| AtomicI32::new(column.load(Ordering::Relaxed)))
+                .collect(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<S: RectVolSize> fmt::Debug for ColumnCache<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ColumnCache").finish()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Chonk<V, S: RectVolSize, M: Clone> {
     z_offset: i32,
@@ -40,6 +131,9 @@ pub struct Chonk<V, S: RectVolSize, M: Clone> {
     below: V,
     above: V,
     meta: M,
+    /// Derived from the blocks, so it is rebuilt rather than sent
+    #[serde(skip)]
+    column_cache: ColumnCache<S>,
     phantom: PhantomData<S>,
 }
 
@@ -51,12 +145,17 @@ impl<V, S: RectVolSize, M: Clone> Chonk<V, S, M> {
             below,
             above,
             meta,
+            column_cache: ColumnCache::default(),
             phantom: PhantomData,
         }
     }
 
     pub fn meta(&self) -> &M { &self.meta }
 
+    pub fn column_cache(&self) -> &ColumnCache<S> { &self.column_cache }
+
+    pub fn meta_mut(&mut self) -> &mut M { &mut self.meta }
+
     pub fn get_min_z(&self) -> i32 { self.z_offset }
 
     pub fn get_max_z(&self) -> i32 {
@@ -69,6 +168,15 @@ impl<V, S: RectVolSize, M: Clone> Chonk<V, S, M> {
         self.sub_chunks.iter().map(SubChunk::num_groups).sum()
     }
 
+    /// Calls `f` with the voxels stored between `get_min_z` and `get_max_z`
+    /// and how many times each occurs, see `Chunk::for_each_vox_count`. The
+    /// implicit voxels above and below aren't included.
+    pub fn for_each_vox_count(&self, mut f: impl FnMut(&V, u32)) {
+        self.sub_chunks
+            .iter()
+            .for_each(|sub_chunk| sub_chunk.for_each_vox_count(&mut f));
+    }
+
     // Returns the index (in self.sub_chunks) of the SubChunk that contains
     // layer z; note that this index changes when more SubChunks are prepended
     fn sub_chunk_idx(&self, z: i32) -> i32 {
@@ -131,6 +239,64 @@ impl<V, S: RectVolSize, M: Clone> Chonk<V, S, M> {
         // bottom. TODO: Add invariants to justify why `below_len` must fit in
         // i32.
         self.z_offset += below_len as i32 * SubChunkSize::<S>::SIZE.z as i32;
+        // Column values may depend on the chonk's bounds, which may have moved
+        self.column_cache.clear();
+    }
+}
+
+impl<V: Clone + PartialEq, S: RectVolSize, M: Clone> Chonk<V, S, M> {
+    /// Sets every voxel in the box from `min` (inclusive) to `max`
+    /// (exclusive) to `block`. Sub-chunks the box covers entirely are
+    /// replaced with filled ones instead of being written voxel by voxel.
+    pub fn fill_region(
+        &mut self,
+        min: Vec3<i32>,
+        max: Vec3<i32>,
+        block: V,
+    ) -> Result<(), ChonkError> {
+        if min.x < 0 || min.y < 0 || max.x > S::RECT_SIZE.x as i32 || max.y > S::RECT_SIZE.y as i32
+        {
+            return Err(ChonkError::OutOfBounds);
+        }
+        if (0..3).any(|i| min[i] >= max[i]) {
+            return Ok(());
+        }
+
+        // Writing the lowest and highest layers first adds any sub-chunks the box
+        // needs, unless it extends into voxels that are `block` already
+        self.set(min, block.clone())?;
+        self.set(Vec3::new(min.x, min.y, max.z - 1), block.clone())?;
+
+        let sub_chunk_height = SubChunkSize::<S>::SIZE.z as i32;
+        let covers_columns = min.xy() == Vec2::zero() && max.xy() == S::RECT_SIZE.map(|e| e as i32);
+        let mut sub_chunk_min_z = self.sub_chunk_min_z(min.z.max(self.get_min_z()));
+        while sub_chunk_min_z < max.z.min(self.get_max_z()) {
+            let lower = sub_chunk_min_z.max(min.z) - sub_chunk_min_z;
+            let upper = (sub_chunk_min_z + sub_chunk_height).min(max.z) - sub_chunk_min_z;
+            let idx = self.sub_chunk_idx(sub_chunk_min_z) as usize;
+            if covers_columns && lower == 0 && upper == sub_chunk_height {
+                self.sub_chunks[idx] =
+                    SubChunk::<V, S, M>::filled(block.clone(), self.meta.clone());
+            } else {
+                let sub_chunk = &mut self.sub_chunks[idx];
+                for pos in SubChunk::<V, S, M>::pos_iter(
+                    Vec3::new(min.x, min.y, lower),
+                    Vec3::new(max.x, max.y, upper),
+                ) {
+                    sub_chunk
+                        .set(pos, block.clone())
+                        .map_err(ChonkError::SubChunkError)?;
+                }
+            }
+            sub_chunk_min_z += sub_chunk_height;
+        }
+
+        for x in min.x..max.x {
+            for y in min.y..max.y {
+                self.column_cache.invalidate(Vec2::new(x, y));
+            }
+        }
+        Ok(())
     }
 }
 
@@ -175,6 +341,8 @@ impl<V: Clone + PartialEq, S: RectVolSize, M: Clone> WriteVol for Chonk<V, S, M>
             if block == self.below {
                 return Ok(());
             }
+            // The bounds of every column change
+            self.column_cache.clear();
             // Prepend exactly sufficiently many SubChunks via Vec::splice
             let c = Chunk::<V, SubChunkSize<S>, M>::filled(self.below.clone(), self.meta.clone());
             let n = (-sub_chunk_idx) as usize;
@@ -186,12 +354,14 @@ impl<V: Clone + PartialEq, S: RectVolSize, M: Clone> WriteVol for Chonk<V, S, M>
             if block == self.above {
                 return Ok(());
             }
+            self.column_cache.clear();
             // Append exactly sufficiently many SubChunks via Vec::extend
             let c = Chunk::<V, SubChunkSize<S>, M>::filled(self.above.clone(), self.meta.clone());
             let n = 1 + sub_chunk_idx as usize - self.sub_chunks.len();
             self.sub_chunks.extend(std::iter::repeat(c).take(n));
         }
 
+        self.column_cache.invalidate(pos.xy());
         let rpos = pos
             - Vec3::unit_z() * (self.z_offset + sub_chunk_idx * SubChunkSize::<S>::SIZE.z as i32);
         self.sub_chunks[sub_chunk_idx as usize] // TODO (haslersn): self.sub_chunks.get(...).and_then(...)
@@ -341,3 +511,84 @@ impl<'a, V, S: RectVolSize, M: Clone> IntoVolIterator<'a> for &'a Chonk<V, S, M>
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terrain::{Block, BlockKind, SpriteKind, TerrainChunk, TerrainChunkMeta};
+
+    fn chunk() -> TerrainChunk {
+        TerrainChunk::new(
+            0,
+            Block::new(BlockKind::Rock, Rgb::new(90, 90, 90)),
+            Block::air(SpriteKind::Empty),
+            TerrainChunkMeta::void(),
+        )
+    }
+
+    #[test]
+    fn fill_region_stays_in_bounds() {
+        let wood = Block::new(BlockKind::Wood, Rgb::new(120, 80, 40));
+        let mut chunk = chunk();
+        chunk
+            .fill_region(Vec3::new(2, 3, 5), Vec3::new(6, 8, 12), wood)
+            .unwrap();
+
+        for corner in &[Vec3::new(2, 3, 5), Vec3::new(5, 7, 11), Vec3::new(2, 7, 5)] {
+            assert_eq!(chunk.get(*corner).unwrap(), &wood, "{:?}", corner);
+        }
+        for outside in &[
+            Vec3::new(1, 3, 5),
+            Vec3::new(6, 3, 5),
+            Vec3::new(2, 2, 5),
+            Vec3::new(2, 8, 5),
+            Vec3::new(2, 3, 4),
+            Vec3::new(2, 3, 12),
+        ] {
+            assert_eq!(
+                chunk.get(*outside).unwrap(),
+                &Block::air(SpriteKind::Empty),
+                "{:?}",
+                outside
+            );
+        }
+    }
+
+    #[test]
+    fn fill_region_replaces_covered_sub_chunks() {
+        let wood = Block::new(BlockKind::Wood, Rgb::new(120, 80, 40));
+        let height = SubChunkSize::<crate::terrain::TerrainChunkSize>::SIZE.z as i32;
+        let size = TerrainChunk::RECT_SIZE.map(|e| e as i32);
+        let mut chunk = chunk();
+        chunk
+            .fill_region(
+                Vec3::zero(),
+                Vec3::new(size.x, size.y, height * 2 + 3),
+                wood,
+            )
+            .unwrap();
+
+        assert_eq!(chunk.sub_chunks_len(), 3);
+        assert_eq!(chunk.sub_chunks[0].homogeneous(), Some(&wood));
+        assert_eq!(chunk.sub_chunks[1].homogeneous(), Some(&wood));
+        assert_eq!(chunk.get(Vec3::new(0, 0, height * 2 + 2)).unwrap(), &wood);
+        assert_eq!(
+            chunk
+                .get(Vec3::new(size.x - 1, size.y - 1, height * 2 + 3))
+                .unwrap(),
+            &Block::air(SpriteKind::Empty)
+        );
+    }
+
+    #[test]
+    fn fill_region_rejects_other_columns() {
+        let mut chunk = chunk();
+        assert!(chunk
+            .fill_region(
+                Vec3::new(-1, 0, 0),
+                Vec3::new(4, 4, 4),
+                Block::air(SpriteKind::Empty)
+            )
+            .is_err());
+    }
+}
@@ -0,0 +1,102 @@
+use super::Block;
+use crate::vol::{ReadVol, WriteVol};
+use serde::{Deserialize, Serialize};
+use vek::*;
+
+/// A single change to the terrain, replacing the block at `pos` with `new`.
+///
+/// Batches of edits can be applied with [BlockEdit::apply_all], which returns
+/// the edits required to undo them. This allows scripted events (quests,
+/// traps, etc.) to make reversible modifications to the world.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockEdit {
+    pub pos: Vec3<i32>,
+    pub new: Block,
+}
+
+impl BlockEdit {
+    pub fn new(pos: Vec3<i32>, new: Block) -> Self { Self { pos, new } }
+
+    /// Apply a batch of edits to a volume, returning the inverse edits (i.e:
+    /// the blocks that were replaced).
+    ///
+    /// Edits that cannot be applied (for example, because the chunk they fall
+    /// in is not loaded) are skipped and do not appear in the result. The
+    /// inverse edits are returned in reverse order, so applying them restores
+    /// the original blocks even if the batch touched a position more than
+    /// once.
+    pub fn apply_all<V>(edits: impl IntoIterator<Item = BlockEdit>, vol: &mut V) -> Vec<BlockEdit>
+    where
+        V: ReadVol<Vox = Block> + WriteVol,
+    {
+        let mut inverse = edits
+            .into_iter()
+            .filter_map(|edit| {
+                let old = *vol.get(edit.pos).ok()?;
+                vol.set(edit.pos, edit.new).ok()?;
+                Some(BlockEdit::new(edit.pos, old))
+            })
+            .collect::<Vec<_>>();
+        inverse.reverse();
+        inverse
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terrain::{BlockKind, SpriteKind, TerrainChunk, TerrainChunkMeta, TerrainGrid};
+    use std::sync::Arc;
+
+    fn test_grid() -> TerrainGrid {
+        let mut grid = TerrainGrid::new().unwrap();
+        grid.insert(
+            Vec2::zero(),
+            Arc::new(TerrainChunk::new(
+                0,
+                Block::new(BlockKind::Rock, Rgb::new(100, 100, 100)),
+                Block::air(SpriteKind::Empty),
+                TerrainChunkMeta::void(),
+            )),
+        );
+        grid
+    }
+
+    #[test]
+    fn apply_then_undo() {
+        let mut grid = test_grid();
+        let wood = Block::new(BlockKind::Wood, Rgb::new(80, 50, 20));
+        let positions = [
+            Vec3::new(1, 1, -1),
+            Vec3::new(1, 1, 0),
+            Vec3::new(2, 3, 4),
+            // Edited twice in the same batch
+            Vec3::new(1, 1, 0),
+        ];
+        let original = positions
+            .iter()
+            .map(|pos| *grid.get(*pos).unwrap())
+            .collect::<Vec<_>>();
+
+        let inverse = BlockEdit::apply_all(
+            positions.iter().map(|pos| BlockEdit::new(*pos, wood)),
+            &mut grid,
+        );
+        assert_eq!(inverse.len(), positions.len());
+        assert!(positions.iter().all(|pos| *grid.get(*pos).unwrap() == wood));
+
+        BlockEdit::apply_all(inverse, &mut grid);
+        for (pos, block) in positions.iter().zip(original) {
+            assert_eq!(*grid.get(*pos).unwrap(), block);
+        }
+    }
+
+    #[test]
+    fn unloaded_edits_skipped() {
+        let mut grid = test_grid();
+        let wood = Block::new(BlockKind::Wood, Rgb::new(80, 50, 20));
+        let inverse =
+            BlockEdit::apply_all(vec![BlockEdit::new(Vec3::new(-1, 0, 0), wood)], &mut grid);
+        assert!(inverse.is_empty());
+    }
+}
@@ -0,0 +1,139 @@
+use super::{Block, SpriteKind, TerrainChunk, TerrainGrid};
+use crate::{
+    comp::DamageKind,
+    vol::{ReadVol, WriteVol},
+};
+use vek::*;
+
+impl TerrainChunk {
+    /// The remaining health of the block at `pos` (relative to the chunk), or
+    /// `None` if it can't be damaged.
+    pub fn block_health(&self, pos: Vec3<i32>) -> Option<u16> {
+        let max_health = self.get(pos).ok()?.max_health()?;
+        Some(
+            self.meta()
+                .block_health
+                .get(&pos)
+                .copied()
+                .unwrap_or(max_health),
+        )
+    }
+
+    /// Damages the block at `pos` (relative to the chunk), turning it into air
+    /// once its health runs out. The damage is scaled by the block's
+    /// [`BlockKind::resistance`] to `kind`. Returns whether the block broke.
+    ///
+    /// [`BlockKind::resistance`]: super::BlockKind::resistance
+    pub fn damage_block(&mut self, pos: Vec3<i32>, amount: u16, kind: DamageKind) -> bool {
+        let resistance = match self.get(pos) {
+            Ok(block) => block.kind().resistance(kind),
+            Err(_) => return false,
+        };
+        let amount = (amount as f32 * (1.0 - resistance)).round() as u16;
+        let health = match self.block_health(pos) {
+            Some(health) => health.saturating_sub(amount),
+            None => return false,
+        };
+        if health == 0 {
+            self.meta_mut().block_health.remove(&pos);
+            self.set(pos, Block::air(SpriteKind::Empty)).is_ok()
+        } else {
+            self.meta_mut().block_health.insert(pos, health);
+            false
+        }
+    }
+}
+
+impl TerrainGrid {
+    /// Forgets the damage dealt to the block at `pos`, for when it has been
+    /// replaced by another block.
+    pub fn reset_block_health(&mut self, pos: Vec3<i32>) {
+        let key = self.pos_key(pos);
+        let offs = Self::chunk_offs(pos);
+        // Avoid copying shared chunks that have nothing to forget
+        if self
+            .get_key(key)
+            .map_or(false, |chunk| chunk.meta().block_health.contains_key(&offs))
+        {
+            if let Some(chunk) = self.get_key_mut(key) {
+                chunk.meta_mut().block_health.remove(&offs);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terrain::{BlockKind, TerrainChunkMeta};
+
+    fn test_chunk() -> TerrainChunk {
+        let mut chunk = TerrainChunk::new(
+            0,
+            Block::new(BlockKind::Rock, Rgb::new(100, 100, 100)),
+            Block::air(SpriteKind::Empty),
+            TerrainChunkMeta::void(),
+        );
+        chunk
+            .set(
+                Vec3::new(3, 4, 5),
+                Block::new(BlockKind::Wood, Rgb::new(80, 50, 20)),
+            )
+            .unwrap();
+        chunk
+    }
+
+    #[test]
+    fn damage_until_broken() {
+        let mut chunk = test_chunk();
+        let pos = Vec3::new(3, 4, 5);
+        assert_eq!(chunk.block_health(pos), BlockKind::Wood.max_health());
+
+        assert!(!chunk.damage_block(pos, 100, DamageKind::Physical));
+        assert_eq!(chunk.block_health(pos), Some(50));
+        assert!(!chunk.damage_block(pos, 49, DamageKind::Physical));
+        assert_eq!(chunk.block_health(pos), Some(1));
+        assert_eq!(chunk.get(pos).unwrap().kind(), BlockKind::Wood);
+
+        assert!(chunk.damage_block(pos, 10, DamageKind::Physical));
+        assert!(chunk.get(pos).unwrap().is_air());
+        assert_eq!(chunk.block_health(pos), None);
+        // Nothing left to break
+        assert!(!chunk.damage_block(pos, 10, DamageKind::Physical));
+    }
+
+    #[test]
+    fn damage_is_per_block() {
+        let mut chunk = test_chunk();
+        let rock = Vec3::new(3, 4, 4);
+        chunk
+            .set(rock, Block::new(BlockKind::Rock, Rgb::zero()))
+            .unwrap();
+
+        assert!(!chunk.damage_block(Vec3::new(3, 4, 5), 20, DamageKind::Physical));
+        assert_eq!(chunk.block_health(rock), BlockKind::Rock.max_health());
+    }
+
+    #[test]
+    fn air_is_indestructible() {
+        let mut chunk = test_chunk();
+        let pos = Vec3::new(0, 0, 10);
+        assert_eq!(chunk.block_health(pos), None);
+        assert!(!chunk.damage_block(pos, u16::MAX, DamageKind::Physical));
+    }
+
+    #[test]
+    fn fire_resistant_block() {
+        let mut chunk = test_chunk();
+        let rock = Vec3::new(3, 4, 4);
+        chunk
+            .set(rock, Block::new(BlockKind::Rock, Rgb::zero()))
+            .unwrap();
+        let max_health = BlockKind::Rock.max_health().unwrap();
+
+        assert!(!chunk.damage_block(rock, 100, DamageKind::Fire));
+        assert_eq!(chunk.block_health(rock), Some(max_health - 25));
+        assert!(!chunk.damage_block(rock, 100, DamageKind::Physical));
+        assert_eq!(chunk.block_health(rock), Some(max_health - 125));
+    }
+}
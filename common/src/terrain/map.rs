@@ -1,6 +1,6 @@
 use super::{
-    neighbors, quadratic_nearest_point, river_spline_coeffs, uniform_idx_as_vec2,
-    vec2_as_uniform_idx, TerrainChunkSize, NEIGHBOR_DELTA, TERRAIN_CHUNK_BLOCKS_LG,
+    checked_vec2_as_uniform_idx, neighbors, quadratic_nearest_point, river_spline_coeffs,
+    uniform_idx_as_vec2, TerrainChunkSize, NEIGHBOR_DELTA, TERRAIN_CHUNK_BLOCKS_LG,
 };
 use crate::vol::RectVolSize;
 use core::{f32, f64, iter, ops::RangeInclusive};
@@ -197,6 +197,18 @@ impl MapSizeLg {
 
     /// Get the size of an array of the correct size to hold all chunks.
     pub const fn chunks_len(self) -> usize { 1 << (self.0.x + self.0.y) }
+
+    /// Whether the chunk at `pos` lies within the bounds of this map.
+    pub fn contains_chunk(self, pos: Vec2<i32>) -> bool {
+        pos.map2(self.chunks(), |e, sz| e >= 0 && e < i32::from(sz))
+            .reduce_and()
+    }
+
+    /// Clamp a chunk position to the nearest chunk within the bounds of this
+    /// map.
+    pub fn clamp_chunk(self, pos: Vec2<i32>) -> Vec2<i32> {
+        pos.map2(self.chunks(), |e, sz| e.max(0).min(i32::from(sz) - 1))
+    }
 }
 
 impl From<MapSizeLg> for Vec2<u32> {
@@ -463,8 +475,6 @@ impl<'a> MapConfig<'a> {
                 .cmple(&(MAX_WORLD_BLOCKS_LG - TERRAIN_CHUNK_BLOCKS_LG))
                 .reduce_and()
         ); */
-        let world_size = map_size_lg.chunks();
-
         (0..dimensions.y * dimensions.x).for_each(|chunk_idx| {
             let i = chunk_idx % dimensions.x as usize;
             let j = chunk_idx / dimensions.x as usize;
@@ -473,14 +483,7 @@ impl<'a> MapConfig<'a> {
             let pos = wposf.map(|e: f64| e as i32);
             let wposf = wposf * chunk_size;
 
-            let chunk_idx = if pos.reduce_partial_min() >= 0
-                && pos.x < world_size.x as i32
-                && pos.y < world_size.y as i32
-            {
-                Some(vec2_as_uniform_idx(map_size_lg, pos))
-            } else {
-                None
-            };
+            let chunk_idx = checked_vec2_as_uniform_idx(map_size_lg, pos);
 
             let MapSample {
                 rgb,
@@ -711,3 +714,43 @@ impl<'a> MapConfig<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map_size_lg() -> MapSizeLg { MapSizeLg::new(Vec2::new(3, 2)).unwrap() }
+
+    #[test]
+    fn contains_chunk_in_bounds() {
+        let size = map_size_lg();
+        assert!(size.contains_chunk(Vec2::new(0, 0)));
+        assert!(size.contains_chunk(Vec2::new(4, 2)));
+        assert!(size.contains_chunk(Vec2::new(7, 3)));
+    }
+
+    #[test]
+    fn contains_chunk_edges() {
+        let size = map_size_lg();
+        assert!(!size.contains_chunk(Vec2::new(-1, 2)));
+        assert!(!size.contains_chunk(Vec2::new(8, 2)));
+        assert!(!size.contains_chunk(Vec2::new(4, -1)));
+        assert!(!size.contains_chunk(Vec2::new(4, 4)));
+    }
+
+    #[test]
+    fn contains_chunk_negative() {
+        let size = map_size_lg();
+        assert!(!size.contains_chunk(Vec2::new(-5, -5)));
+        assert!(!size.contains_chunk(Vec2::new(i32::MIN, 0)));
+    }
+
+    #[test]
+    fn clamp_chunk() {
+        let size = map_size_lg();
+        assert_eq!(size.clamp_chunk(Vec2::new(3, 1)), Vec2::new(3, 1));
+        assert_eq!(size.clamp_chunk(Vec2::new(-4, 9)), Vec2::new(0, 3));
+        assert_eq!(size.clamp_chunk(Vec2::new(100, -1)), Vec2::new(7, 0));
+        assert!(size.contains_chunk(size.clamp_chunk(Vec2::new(i32::MAX, i32::MIN))));
+    }
+}
@@ -1,22 +1,34 @@
 pub mod biome;
 pub mod block;
 pub mod chonk;
+pub mod edit;
+pub mod health;
 pub mod map;
+pub mod raycast;
+pub mod sky;
 pub mod sprite;
 pub mod structure;
 
 // Reexports
 pub use self::{
-    biome::BiomeKind,
-    block::{Block, BlockKind},
+    biome::{Atmosphere, BiomeKind},
+    block::{AmbientParticle, Block, BlockKind, Interaction, ParticleSpec},
+    edit::BlockEdit,
     map::MapSizeLg,
     sprite::SpriteKind,
     structure::Structure,
 };
+use hashbrown::{HashMap, HashSet};
+use rand::SeedableRng;
+use rand_chacha::ChaChaRng;
 use roots::find_roots_cubic;
 use serde::{Deserialize, Serialize};
 
-use crate::{vol::RectVolSize, volumes::vol_grid_2d::VolGrid2d};
+use crate::{
+    vol::{ReadVol, RectVolSize},
+    volumes::vol_grid_2d::VolGrid2d,
+};
+use std::collections::VecDeque;
 use vek::*;
 
 // TerrainChunkSize
@@ -49,22 +61,57 @@ impl RectVolSize for TerrainChunkSize {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TerrainChunkMeta {
     name: Option<String>,
+    /// Name given to the chunk after generation, e.g. for a player claim or a
+    /// discovered landmark, which takes precedence over `name`
+    #[serde(default)]
+    custom_name: Option<String>,
     biome: BiomeKind,
+    /// Remaining health of damaged blocks, by position within the chunk
+    #[serde(default)]
+    block_health: HashMap<Vec3<i32>, u16>,
+    /// Whether players are protected from each other's attacks here
+    #[serde(default)]
+    safe_zone: bool,
 }
 
 impl TerrainChunkMeta {
-    pub fn new(name: Option<String>, biome: BiomeKind) -> Self { Self { name, biome } }
+    pub fn new(name: Option<String>, biome: BiomeKind) -> Self {
+        Self {
+            name,
+            custom_name: None,
+            biome,
+            block_health: HashMap::new(),
+            safe_zone: false,
+        }
+    }
 
     pub fn void() -> Self {
         Self {
             name: None,
+            custom_name: None,
             biome: BiomeKind::Void,
+            block_health: HashMap::new(),
+            safe_zone: false,
         }
     }
 
-    pub fn name(&self) -> &str { self.name.as_deref().unwrap_or("Wilderness") }
+    pub fn name(&self) -> &str {
+        self.custom_name
+            .as_deref()
+            .or_else(|| self.name.as_deref())
+            .unwrap_or("Wilderness")
+    }
+
+    pub fn custom_name(&self) -> Option<&str> { self.custom_name.as_deref() }
+
+    /// Overrides the generated name, or restores it if `name` is `None`
+    pub fn set_custom_name(&mut self, name: Option<String>) { self.custom_name = name; }
 
     pub fn biome(&self) -> BiomeKind { self.biome }
+
+    pub fn safe_zone(&self) -> bool { self.safe_zone }
+
+    pub fn set_safe_zone(&mut self, safe_zone: bool) { self.safe_zone = safe_zone; }
 }
 
 // Terrain type aliases
@@ -72,6 +119,54 @@ impl TerrainChunkMeta {
 pub type TerrainChunk = chonk::Chonk<Block, TerrainChunkSize, TerrainChunkMeta>;
 pub type TerrainGrid = VolGrid2d<TerrainChunk>;
 
+impl TerrainChunk {
+    /// How many blocks of each kind the chunk stores, for debugging world
+    /// generation. The blocks implicitly filling the space above and below
+    /// the stored ones aren't counted.
+    pub fn block_stats(&self) -> HashMap<BlockKind, u32> {
+        let mut stats = HashMap::new();
+        self.for_each_vox_count(|block, count| *stats.entry(block.kind()).or_insert(0) += count);
+        stats
+    }
+}
+
+impl TerrainGrid {
+    /// Whether an entity two blocks tall could stand at `pos`. See
+    /// [`TerrainGrid::is_walkable_with_headroom`].
+    pub fn is_walkable(&self, pos: Vec3<i32>) -> bool { self.is_walkable_with_headroom(pos, 2) }
+
+    /// Whether an entity could stand at `pos`: there must be a full solid
+    /// floor below it and `headroom` blocks free of anything solid, including
+    /// sprites, from `pos` upwards. Collectible sprites don't count as a
+    /// floor, since they may be taken away at any time.
+    #[allow(clippy::float_cmp)]
+    pub fn is_walkable_with_headroom(&self, pos: Vec3<i32>, headroom: u32) -> bool {
+        let has_floor = self
+            .get(pos - Vec3::unit_z())
+            .map(|b| b.is_solid() && b.solid_height() == 1.0 && !b.is_collectible())
+            .unwrap_or(false);
+        has_floor
+            && (0..headroom as i32).all(|z| {
+                self.get(pos + Vec3::unit_z() * z)
+                    .map(|b| !b.is_solid())
+                    .unwrap_or(true)
+            })
+    }
+
+    /// Whether `wpos` lies in a loaded chunk marked as a safe zone
+    pub fn in_safe_zone(&self, wpos: Vec3<f32>) -> bool {
+        self.get_key(self.pos_key(wpos.map(|e| e.floor() as i32)))
+            .map_or(false, |chunk| chunk.meta().safe_zone())
+    }
+
+    /// Whether a player at `attacker` may damage a player at `target`. Neither
+    /// may be in a safe zone, so that players can't be attacked from outside
+    /// a safe zone nor attack others from within one.
+    pub fn allows_pvp(&self, attacker: Vec3<f32>, target: Vec3<f32>) -> bool {
+        !self.in_safe_zone(attacker) && !self.in_safe_zone(target)
+    }
+}
+
 // Terrain helper functions used across multiple crates.
 
 /// Computes the position Vec2 of a SimChunk from an index, where the index was
@@ -86,12 +181,112 @@ pub fn uniform_idx_as_vec2(map_size_lg: MapSizeLg, idx: usize) -> Vec2<i32> {
 
 /// Computes the index of a Vec2 of a SimChunk from a position, where the index
 /// is generated by uniform_noise.  NOTE: Both components of idx should be
-/// in-bounds!
+/// in-bounds! Use [`checked_vec2_as_uniform_idx`] where that isn't known.
 #[inline(always)]
 pub fn vec2_as_uniform_idx(map_size_lg: MapSizeLg, idx: Vec2<i32>) -> usize {
     ((idx.y as usize) << map_size_lg.vec().x) | idx.x as usize
 }
 
+/// Like [`vec2_as_uniform_idx`], but returns `None` if `idx` lies outside the
+/// map.
+#[inline(always)]
+pub fn checked_vec2_as_uniform_idx(map_size_lg: MapSizeLg, idx: Vec2<i32>) -> Option<usize> {
+    if map_size_lg.contains_chunk(idx) {
+        Some(vec2_as_uniform_idx(map_size_lg, idx))
+    } else {
+        None
+    }
+}
+
+/// Computes the uniform index of the SimChunk containing the world block
+/// position `wpos`, or `None` if that chunk lies outside the map.
+#[inline(always)]
+pub fn wpos_to_uniform_idx(map_size_lg: MapSizeLg, wpos: Vec2<i32>) -> Option<usize> {
+    // Arithmetic shift floors, so negative positions land in negative chunks
+    checked_vec2_as_uniform_idx(map_size_lg, wpos.map(|e| e >> TERRAIN_CHUNK_BLOCKS_LG))
+}
+
+/// All chunk positions within `radius` chunks of `center`, forming a disc
+/// rather than a square. Positions are yielded nearest-first, so loading them
+/// in order fills in the area around `center` first.
+pub fn chunks_in_radius(center: Vec2<i32>, radius: u32) -> impl Iterator<Item = Vec2<i32>> {
+    let radius = radius as i32;
+    let mut offsets = (-radius..=radius)
+        .flat_map(|y| (-radius..=radius).map(move |x| Vec2::new(x, y)))
+        .filter(|offset| offset.magnitude_squared() <= radius.pow(2))
+        .collect::<Vec<_>>();
+    // Stable, so equally distant chunks stay in row order
+    offsets.sort_by_key(|offset| offset.magnitude_squared());
+    offsets.into_iter().map(move |offset| center + offset)
+}
+
+/// Finds the blocks connected to `start` through shared faces that all match
+/// `predicate`, such as an air pocket or a body of water. Unloaded chunks
+/// act as a boundary.
+///
+/// At most `max_cells` blocks are visited, nearest first, so a fill that
+/// escapes into open terrain stays cheap. A result of exactly `max_cells`
+/// blocks means the region may extend further.
+pub fn flood_fill(
+    grid: &TerrainGrid,
+    start: Vec3<i32>,
+    max_cells: usize,
+    predicate: impl Fn(&Block) -> bool,
+) -> HashSet<Vec3<i32>> {
+    // In the same order as `face_neighbors`
+    let offsets = [
+        Vec3::unit_x(),
+        -Vec3::unit_x(),
+        Vec3::unit_y(),
+        -Vec3::unit_y(),
+        Vec3::unit_z(),
+        -Vec3::unit_z(),
+    ];
+
+    let mut filled = HashSet::new();
+    if max_cells == 0 || !grid.get(start).map_or(false, |block| predicate(block)) {
+        return filled;
+    }
+    filled.insert(start);
+    let mut queue = VecDeque::from(vec![start]);
+    while let Some(pos) = queue.pop_front() {
+        for (offset, neighbor) in offsets.iter().zip(grid.face_neighbors(pos).iter()) {
+            let neighbor_pos = pos + *offset;
+            if neighbor.as_ref().map_or(false, |block| predicate(block))
+                && filled.insert(neighbor_pos)
+            {
+                if filled.len() >= max_cells {
+                    return filled;
+                }
+                queue.push_back(neighbor_pos);
+            }
+        }
+    }
+    filled
+}
+
+/// Creates an RNG whose output depends only on the world seed and the chunk
+/// position, so a chunk generates identically no matter when it's generated.
+/// Like the rest of world generation it uses `ChaChaRng`, whose output, unlike
+/// `StdRng`'s, won't change between versions of `rand`.
+pub fn chunk_rng(world_seed: u32, chunk_pos: Vec2<i32>) -> ChaChaRng {
+    // Simple non-cryptographic diffusion, as used for world seeds
+    let diffuse = |mut a: u32| {
+        a ^= a.rotate_right(23);
+        a.wrapping_mul(2654435761)
+    };
+    let mut x = [world_seed, chunk_pos.x as u32, chunk_pos.y as u32]
+        .iter()
+        .fold((1 << 31) - 1, |state, e| diffuse(state ^ e));
+
+    let mut seed = [0; 32];
+    for bytes in seed.chunks_mut(4) {
+        x = diffuse(x);
+        bytes.copy_from_slice(&x.to_le_bytes());
+    }
+    ChaChaRng::from_seed(seed)
+}
+
 // NOTE: want to keep this such that the chunk index is in ascending order!
 pub const NEIGHBOR_DELTA: [(i32, i32); 8] = [
     (-1, -1),
@@ -108,14 +303,22 @@ pub const NEIGHBOR_DELTA: [(i32, i32); 8] = [
 #[inline(always)]
 pub fn neighbors(map_size_lg: MapSizeLg, posi: usize) -> impl Clone + Iterator<Item = usize> {
     let pos = uniform_idx_as_vec2(map_size_lg, posi);
-    let world_size = map_size_lg.chunks();
     NEIGHBOR_DELTA
         .iter()
         .map(move |&(x, y)| Vec2::new(pos.x + x, pos.y + y))
-        .filter(move |pos| {
-            pos.x >= 0 && pos.y >= 0 && pos.x < world_size.x as i32 && pos.y < world_size.y as i32
-        })
-        .map(move |pos| vec2_as_uniform_idx(map_size_lg, pos))
+        .filter_map(move |pos| checked_vec2_as_uniform_idx(map_size_lg, pos))
+}
+
+/// Iterate through the values of all cells adjacent to a chunk, where `grid`
+/// holds one value per chunk by uniform index. Yields the same cells, in the
+/// same order, as [`neighbors`].
+#[inline(always)]
+pub fn neighbor_values<T>(
+    map_size_lg: MapSizeLg,
+    grid: &[T],
+    posi: usize,
+) -> impl Clone + Iterator<Item = &T> {
+    neighbors(map_size_lg, posi).map(move |nposi| &grid[nposi])
 }
 
 pub fn river_spline_coeffs(
@@ -135,6 +338,16 @@ pub fn river_spline_coeffs(
     Vec3::new(dxy - spline_derivative, spline_derivative, chunk_pos)
 }
 
+/// The direction a river flows in at `t` along a spline from
+/// [`river_spline_coeffs`], i.e. the normalized tangent of the spline. `t` is
+/// clamped to the spline's `[0, 1]` range.
+pub fn river_flow_dir(spline: &Vec3<Vec2<f64>>, t: f64) -> Vec2<f64> {
+    let t = t.max(0.0).min(1.0);
+    (spline.x * 2.0 * t + spline.y)
+        .try_normalized()
+        .unwrap_or_else(Vec2::zero)
+}
+
 /// Find the nearest point from a quadratic spline to this point (in terms of t,
 /// the "distance along the curve" by which our spline is parameterized).  Note
 /// that if t < 0.0 or t >= 1.0, we probably shouldn't be considered "on the
@@ -208,3 +421,332 @@ pub fn quadratic_nearest_point(
         });
     min_root
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vol::WriteVol;
+    use rand::Rng;
+    use std::sync::Arc;
+
+    fn map_size_lg() -> MapSizeLg { MapSizeLg::new(Vec2::new(3, 2)).unwrap() }
+
+    #[test]
+    fn wpos_to_uniform_idx_in_bounds() {
+        let size = map_size_lg();
+        let chunk = TerrainChunkSize::RECT_SIZE.map(|e| e as i32);
+        let wpos = Vec2::new(5, 2) * chunk + Vec2::new(7, chunk.y - 1);
+        assert_eq!(
+            wpos_to_uniform_idx(size, wpos),
+            Some(vec2_as_uniform_idx(size, Vec2::new(5, 2)))
+        );
+        assert_eq!(wpos_to_uniform_idx(size, Vec2::zero()), Some(0));
+    }
+
+    #[test]
+    fn wpos_to_uniform_idx_negative() {
+        let size = map_size_lg();
+        // Floors into chunk -1 rather than truncating towards chunk 0
+        assert_eq!(wpos_to_uniform_idx(size, Vec2::new(-1, 10)), None);
+        assert_eq!(wpos_to_uniform_idx(size, Vec2::new(10, -1)), None);
+    }
+
+    #[test]
+    fn wpos_to_uniform_idx_out_of_bounds() {
+        let size = map_size_lg();
+        let chunk = TerrainChunkSize::RECT_SIZE.map(|e| e as i32);
+        assert_eq!(wpos_to_uniform_idx(size, Vec2::new(8 * chunk.x, 0)), None);
+        assert_eq!(wpos_to_uniform_idx(size, Vec2::new(0, 4 * chunk.y)), None);
+        assert_eq!(
+            wpos_to_uniform_idx(size, Vec2::new(8 * chunk.x - 1, 4 * chunk.y - 1)),
+            Some(size.chunks_len() - 1)
+        );
+    }
+
+    #[test]
+    fn neighbor_values_match_lookups() {
+        let size = map_size_lg();
+        let grid = (0..size.chunks_len())
+            .map(|posi| uniform_idx_as_vec2(size, posi))
+            .collect::<Vec<_>>();
+        let corner = 0;
+        let edge = vec2_as_uniform_idx(size, Vec2::new(3, 0));
+        let inner = vec2_as_uniform_idx(size, Vec2::new(4, 2));
+        for &posi in [corner, edge, inner, size.chunks_len() - 1].iter() {
+            let expected = neighbors(size, posi)
+                .map(|nposi| grid[nposi])
+                .collect::<Vec<_>>();
+            assert_eq!(
+                neighbor_values(size, &grid, posi)
+                    .copied()
+                    .collect::<Vec<_>>(),
+                expected
+            );
+        }
+        assert_eq!(
+            neighbor_values(size, &grid, corner).collect::<Vec<_>>(),
+            vec![&Vec2::new(1, 0), &Vec2::new(0, 1), &Vec2::new(1, 1)]
+        );
+        assert_eq!(neighbor_values(size, &grid, edge).count(), 5);
+        assert_eq!(neighbor_values(size, &grid, inner).count(), 8);
+    }
+
+    #[test]
+    fn checked_vec2_as_uniform_idx_in_bounds() {
+        let size = map_size_lg();
+        for &pos in [Vec2::new(0, 0), Vec2::new(5, 2), Vec2::new(7, 3)].iter() {
+            assert_eq!(
+                checked_vec2_as_uniform_idx(size, pos),
+                Some(vec2_as_uniform_idx(size, pos))
+            );
+        }
+        assert_eq!(
+            checked_vec2_as_uniform_idx(size, Vec2::new(7, 3)),
+            Some(size.chunks_len() - 1)
+        );
+    }
+
+    #[test]
+    fn checked_vec2_as_uniform_idx_negative() {
+        let size = map_size_lg();
+        assert_eq!(checked_vec2_as_uniform_idx(size, Vec2::new(-1, 0)), None);
+        assert_eq!(checked_vec2_as_uniform_idx(size, Vec2::new(0, -1)), None);
+        assert_eq!(checked_vec2_as_uniform_idx(size, Vec2::new(-3, -3)), None);
+    }
+
+    #[test]
+    fn checked_vec2_as_uniform_idx_over_max() {
+        let size = map_size_lg();
+        // The unchecked version would wrap these around into other rows
+        assert_eq!(checked_vec2_as_uniform_idx(size, Vec2::new(8, 0)), None);
+        assert_eq!(checked_vec2_as_uniform_idx(size, Vec2::new(0, 4)), None);
+        assert_eq!(checked_vec2_as_uniform_idx(size, Vec2::new(8, 4)), None);
+    }
+
+    #[test]
+    fn chunks_in_radius_nearest_first() {
+        let center = Vec2::new(-3, 17);
+        let chunks = chunks_in_radius(center, 20).collect::<Vec<_>>();
+        assert_eq!(chunks[0], center);
+        let dists = chunks
+            .iter()
+            .map(|pos| (*pos - center).magnitude_squared())
+            .collect::<Vec<_>>();
+        assert!(dists.windows(2).all(|w| w[0] <= w[1]));
+        assert!(dists.iter().all(|d| *d <= 20 * 20));
+
+        // Roughly the area of the disc, rather than that of the square around it
+        let area = std::f32::consts::PI * 20.0f32.powi(2);
+        assert!((chunks.len() as f32 - area).abs() < area * 0.05);
+
+        assert_eq!(chunks_in_radius(center, 0).collect::<Vec<_>>(), vec![
+            center
+        ]);
+    }
+
+    #[test]
+    fn chunk_rng_is_deterministic() {
+        let sample = |seed, pos| {
+            let mut rng = chunk_rng(seed, pos);
+            (0..16).map(|_| rng.gen::<u32>()).collect::<Vec<_>>()
+        };
+        let pos = Vec2::new(12, -7);
+        assert_eq!(sample(1337, pos), sample(1337, pos));
+        assert_ne!(sample(1337, pos), sample(1337, Vec2::new(-7, 12)));
+        assert_ne!(sample(1337, pos), sample(1337, pos + Vec2::unit_x()));
+        assert_ne!(sample(1337, pos), sample(1338, pos));
+    }
+
+    #[test]
+    fn custom_name_round_trip() {
+        let mut meta = TerrainChunkMeta::new(Some("Tallwood".to_string()), BiomeKind::Forest);
+        meta.set_custom_name(Some("Zesty's Keep".to_string()));
+        assert_eq!(meta.custom_name(), Some("Zesty's Keep"));
+        assert_eq!(meta.name(), "Zesty's Keep");
+
+        // The name has to survive being sent to clients
+        let ser = ron::ser::to_string(&meta).unwrap();
+        let de: TerrainChunkMeta = ron::de::from_str(&ser).unwrap();
+        assert_eq!(de.name(), "Zesty's Keep");
+
+        meta.set_custom_name(None);
+        assert_eq!(meta.custom_name(), None);
+        assert_eq!(meta.name(), "Tallwood");
+    }
+
+    #[test]
+    fn river_flow_dir_matches_finite_differences() {
+        let spline = river_spline_coeffs(
+            Vec2::new(10.0, 20.0),
+            Vec2::new(40.0, -12.0),
+            Vec2::new(42.0, 52.0),
+        );
+        let eval = |t: f64| spline.x * t * t + spline.y * t + spline.z;
+        let h = 1e-6;
+        for &t in [0.25, 0.5, 0.9].iter() {
+            let numeric = ((eval(t + h) - eval(t - h)) / (2.0 * h)).normalized();
+            let analytic = river_flow_dir(&spline, t);
+            assert!((analytic.magnitude() - 1.0).abs() < 1e-9);
+            assert!(analytic.distance(numeric) < 1e-6, "t = {}", t);
+        }
+    }
+
+    #[test]
+    fn river_flow_dir_clamps_t() {
+        let spline = river_spline_coeffs(
+            Vec2::new(10.0, 20.0),
+            Vec2::new(40.0, -12.0),
+            Vec2::new(42.0, 52.0),
+        );
+        assert_eq!(river_flow_dir(&spline, -3.0), river_flow_dir(&spline, 0.0));
+        assert_eq!(river_flow_dir(&spline, 7.0), river_flow_dir(&spline, 1.0));
+    }
+
+    #[test]
+    fn block_stats_count_stored_blocks() {
+        let rock = Block::new(BlockKind::Rock, Rgb::zero());
+        let grass = Block::new(BlockKind::Grass, Rgb::zero());
+        let mut chunk = TerrainChunk::new(
+            0,
+            rock,
+            Block::air(SpriteKind::Empty),
+            TerrainChunkMeta::void(),
+        );
+        // Nothing is stored yet, so the implicit rock and air don't count
+        assert!(chunk.block_stats().is_empty());
+
+        for x in 0..4 {
+            chunk.set(Vec3::new(x, 0, 0), grass).unwrap();
+        }
+        // Below the stored blocks, where they're implicitly rock
+        chunk.set(Vec3::new(0, 0, -1), grass).unwrap();
+
+        // Sub chunks are half as tall as they are wide
+        let size = TerrainChunkSize::RECT_SIZE;
+        let sub_chunk_volume = size.x * size.y * size.x / 2;
+        let stats = chunk.block_stats();
+        assert_eq!(stats.get(&BlockKind::Grass), Some(&5));
+        // The sub chunk below is filled with rock, the one above with air
+        assert_eq!(stats.get(&BlockKind::Rock), Some(&(sub_chunk_volume - 1)));
+        assert_eq!(stats.get(&BlockKind::Air), Some(&(sub_chunk_volume - 4)));
+        assert_eq!(stats.values().sum::<u32>(), 2 * sub_chunk_volume);
+    }
+
+    fn flat_grid() -> TerrainGrid {
+        let mut grid = TerrainGrid::new().unwrap();
+        grid.insert(
+            Vec2::zero(),
+            Arc::new(TerrainChunk::new(
+                0,
+                Block::new(BlockKind::Rock, Rgb::new(90, 90, 90)),
+                Block::air(SpriteKind::Empty),
+                TerrainChunkMeta::void(),
+            )),
+        );
+        grid
+    }
+
+    #[test]
+    fn walkable_on_open_ground() {
+        let grid = flat_grid();
+        assert!(grid.is_walkable(Vec3::new(5, 5, 0)));
+        // Floating in the air or buried in rock
+        assert!(!grid.is_walkable(Vec3::new(5, 5, 1)));
+        assert!(!grid.is_walkable(Vec3::new(5, 5, -1)));
+    }
+
+    #[test]
+    fn low_ceiling_isnt_walkable() {
+        let mut grid = flat_grid();
+        let pos = Vec3::new(5, 5, 0);
+        grid.set(
+            pos + Vec3::unit_z(),
+            Block::new(BlockKind::Rock, Rgb::new(90, 90, 90)),
+        )
+        .unwrap();
+        assert!(!grid.is_walkable(pos));
+        // Smaller entities still fit
+        assert!(grid.is_walkable_with_headroom(pos, 1));
+    }
+
+    #[test]
+    fn blocking_sprite_isnt_walkable() {
+        let mut grid = flat_grid();
+        let pos = Vec3::new(5, 5, 0);
+        grid.set(pos, Block::air(SpriteKind::LongGrass)).unwrap();
+        assert!(grid.is_walkable(pos));
+        grid.set(pos, Block::air(SpriteKind::Chest)).unwrap();
+        assert!(!grid.is_walkable(pos));
+        // Nor can anything stand on a sprite that can be picked up
+        grid.set(pos, Block::air(SpriteKind::Velorite)).unwrap();
+        assert!(!grid.is_walkable(pos + Vec3::unit_z()));
+    }
+
+    #[test]
+    fn pvp_disabled_in_safe_zones() {
+        let mut grid = flat_grid();
+        let mut town = TerrainChunk::new(
+            0,
+            Block::new(BlockKind::Rock, Rgb::new(90, 90, 90)),
+            Block::air(SpriteKind::Empty),
+            TerrainChunkMeta::void(),
+        );
+        town.meta_mut().set_safe_zone(true);
+        grid.insert(Vec2::unit_x(), Arc::new(town));
+
+        let chunk = TerrainChunkSize::RECT_SIZE.x as f32;
+        let wild = Vec3::new(chunk - 2.0, 5.0, 0.0);
+        let wild2 = Vec3::new(chunk - 6.0, 5.0, 0.0);
+        let safe = Vec3::new(chunk + 2.0, 5.0, 0.0);
+        let safe2 = Vec3::new(chunk + 6.0, 5.0, 0.0);
+        assert!(!grid.in_safe_zone(wild));
+        assert!(grid.in_safe_zone(safe));
+
+        assert!(grid.allows_pvp(wild, wild2));
+        assert!(!grid.allows_pvp(safe, safe2));
+        // Across the boundary, neither side can hurt the other
+        assert!(!grid.allows_pvp(wild, safe));
+        assert!(!grid.allows_pvp(safe, wild));
+    }
+
+    fn cave_grid() -> TerrainGrid {
+        let mut grid = flat_grid();
+        // A 2x2x2 pocket of air enclosed by rock
+        for x in 4..6 {
+            for y in 4..6 {
+                for z in -6..-4 {
+                    grid.set(Vec3::new(x, y, z), Block::air(SpriteKind::Empty))
+                        .unwrap();
+                }
+            }
+        }
+        grid
+    }
+
+    #[test]
+    fn flood_fill_stays_in_enclosed_pocket() {
+        let grid = cave_grid();
+        let pocket = flood_fill(&grid, Vec3::new(4, 4, -6), 1000, |b| b.is_air());
+        assert_eq!(pocket.len(), 8);
+        assert!(pocket.contains(&Vec3::new(5, 5, -5)));
+        // Blocked by the surrounding rock
+        assert!(!pocket.contains(&Vec3::new(6, 4, -6)));
+
+        // Starting in rock fills nothing
+        assert!(flood_fill(&grid, Vec3::new(0, 0, -1), 1000, |b| b.is_air()).is_empty());
+    }
+
+    #[test]
+    fn flood_fill_stops_at_max_cells() {
+        let grid = cave_grid();
+        assert_eq!(
+            flood_fill(&grid, Vec3::new(4, 4, -6), 3, |b| b.is_air()).len(),
+            3
+        );
+        // The open air above the ground would otherwise go on forever
+        assert_eq!(
+            flood_fill(&grid, Vec3::new(8, 8, 2), 500, |b| b.is_air()).len(),
+            500
+        );
+    }
+}
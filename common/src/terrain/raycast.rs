@@ -0,0 +1,146 @@
+use super::{Block, TerrainGrid};
+use crate::vol::ReadVol;
+use vek::*;
+
+impl TerrainGrid {
+    /// Walks the blocks along a ray (using a DDA traversal) and returns the
+    /// first solid block it hits within `max_dist`, as its position, the
+    /// block itself and the normal of the face the ray entered through.
+    ///
+    /// The normal is zero if `start` is already inside a solid block. The ray
+    /// stops without a hit if it reaches a chunk that isn't loaded.
+    pub fn raycast(
+        &self,
+        start: Vec3<f32>,
+        dir: Vec3<f32>,
+        max_dist: f32,
+    ) -> Option<(Vec3<i32>, Block, Vec3<i32>)> {
+        let dir = dir.try_normalized()?;
+        let mut pos = start.map(|e| e.floor() as i32);
+        let step = dir.map(|e| if e < 0.0 { -1 } else { 1 });
+        // Distance along the ray needed to cross a whole block on each axis
+        let delta = dir.map(|e| {
+            if e == 0.0 {
+                f32::INFINITY
+            } else {
+                1.0 / e.abs()
+            }
+        });
+        // Distance along the ray to the next block boundary on each axis
+        let mut next = Vec3::new(
+            boundary_dist(start.x, dir.x),
+            boundary_dist(start.y, dir.y),
+            boundary_dist(start.z, dir.z),
+        );
+        let mut normal = Vec3::zero();
+
+        loop {
+            match self.get(pos) {
+                Ok(block) if block.is_solid() => return Some((pos, *block, normal)),
+                Ok(_) => {},
+                Err(_) => return None,
+            }
+
+            let axis = if next.x < next.y {
+                if next.x < next.z {
+                    0
+                } else {
+                    2
+                }
+            } else if next.y < next.z {
+                1
+            } else {
+                2
+            };
+            if next[axis] > max_dist {
+                return None;
+            }
+            pos[axis] += step[axis];
+            next[axis] += delta[axis];
+            normal = Vec3::zero();
+            normal[axis] = -step[axis];
+        }
+    }
+}
+
+fn boundary_dist(start: f32, dir: f32) -> f32 {
+    if dir > 0.0 {
+        (start.floor() + 1.0 - start) / dir
+    } else if dir < 0.0 {
+        (start - start.floor()) / -dir
+    } else {
+        f32::INFINITY
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        terrain::{Block, BlockKind, SpriteKind, TerrainChunk, TerrainChunkMeta, TerrainGrid},
+        vol::WriteVol,
+    };
+    use std::sync::Arc;
+    use vek::*;
+
+    fn test_grid() -> TerrainGrid {
+        let mut grid = TerrainGrid::new().unwrap();
+        grid.insert(
+            Vec2::zero(),
+            Arc::new(TerrainChunk::new(
+                0,
+                Block::new(BlockKind::Rock, Rgb::new(100, 100, 100)),
+                Block::air(SpriteKind::Empty),
+                TerrainChunkMeta::void(),
+            )),
+        );
+        grid
+    }
+
+    #[test]
+    fn hits_ground() {
+        let grid = test_grid();
+        let (pos, block, normal) = grid
+            .raycast(Vec3::new(5.5, 5.5, 10.5), -Vec3::unit_z(), 20.0)
+            .unwrap();
+        assert_eq!(pos, Vec3::new(5, 5, -1));
+        assert_eq!(block.kind(), BlockKind::Rock);
+        assert_eq!(normal, Vec3::unit_z());
+    }
+
+    #[test]
+    fn hits_placed_block() {
+        let mut grid = test_grid();
+        let wood = Block::new(BlockKind::Wood, Rgb::new(80, 50, 20));
+        grid.set(Vec3::new(10, 6, 3), wood).unwrap();
+
+        let hit = grid.raycast(Vec3::new(2.5, 5.3, 3.5), Vec3::new(1.0, 0.1, 0.0), 20.0);
+        assert_eq!(hit, Some((Vec3::new(10, 6, 3), wood, -Vec3::unit_x())));
+        // Too short to reach it
+        assert_eq!(
+            grid.raycast(Vec3::new(2.5, 5.3, 3.5), Vec3::new(1.0, 0.1, 0.0), 5.0),
+            None
+        );
+    }
+
+    #[test]
+    fn misses_into_open_air() {
+        let grid = test_grid();
+        assert_eq!(
+            grid.raycast(Vec3::new(5.5, 5.5, 0.5), Vec3::new(0.3, 0.2, 1.0), 25.0),
+            None
+        );
+    }
+
+    #[test]
+    fn stops_at_unloaded_chunks() {
+        let grid = test_grid();
+        assert_eq!(
+            grid.raycast(Vec3::new(5.5, 5.5, 1.5), Vec3::unit_x(), 100.0),
+            None
+        );
+        assert_eq!(
+            grid.raycast(Vec3::new(-5.0, 5.5, 1.5), Vec3::zero(), 10.0),
+            None
+        );
+    }
+}
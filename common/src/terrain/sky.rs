@@ -0,0 +1,91 @@
+use super::TerrainChunk;
+use crate::vol::ReadVol;
+use vek::*;
+
+impl TerrainChunk {
+    /// The lowest z in the column at `pos` (relative to the chunk) that is
+    /// open to the sky, i.e: one above its highest opaque block. Columns
+    /// without any opaque blocks are open down to the bottom of the chunk.
+    ///
+    /// This is cached per column and recomputed after the column is edited.
+    pub fn sky_height(&self, pos: Vec2<i32>) -> i32 {
+        self.column_cache().get_or_insert_with(pos, || {
+            (self.get_min_z()..self.get_max_z())
+                .rev()
+                .find(|z| {
+                    self.get(Vec3::new(pos.x, pos.y, *z))
+                        .map_or(false, |block| block.is_opaque())
+                })
+                .map_or(self.get_min_z(), |z| z + 1)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        comp::DamageKind,
+        terrain::{Block, BlockKind, SpriteKind, TerrainChunkMeta},
+        vol::WriteVol,
+    };
+
+    fn test_chunk() -> TerrainChunk {
+        let mut chunk = TerrainChunk::new(
+            0,
+            Block::new(BlockKind::Rock, Rgb::new(100, 100, 100)),
+            Block::air(SpriteKind::Empty),
+            TerrainChunkMeta::void(),
+        );
+        for z in 0..10 {
+            chunk
+                .set(
+                    Vec3::new(3, 4, z),
+                    Block::new(BlockKind::Earth, Rgb::new(80, 50, 20)),
+                )
+                .unwrap();
+        }
+        chunk
+    }
+
+    #[test]
+    fn edits_update_sky_height() {
+        let mut chunk = test_chunk();
+        let column = Vec2::new(3, 4);
+        assert_eq!(chunk.sky_height(column), 10);
+
+        // Build on top of the column
+        chunk
+            .set(
+                Vec3::new(3, 4, 14),
+                Block::new(BlockKind::Wood, Rgb::new(80, 50, 20)),
+            )
+            .unwrap();
+        assert_eq!(chunk.sky_height(column), 15);
+
+        // Dig it back out
+        chunk
+            .set(Vec3::new(3, 4, 14), Block::air(SpriteKind::Empty))
+            .unwrap();
+        assert_eq!(chunk.sky_height(column), 10);
+
+        // Breaking the top block exposes the one under it
+        let top = Vec3::new(3, 4, 9);
+        assert!(chunk.damage_block(top, u16::MAX, DamageKind::Physical));
+        assert_eq!(chunk.sky_height(column), 9);
+    }
+
+    #[test]
+    fn other_columns_unaffected() {
+        let mut chunk = test_chunk();
+        let other = Vec2::new(0, 0);
+        let height = chunk.sky_height(other);
+        chunk
+            .set(
+                Vec3::new(3, 4, 20),
+                Block::new(BlockKind::Wood, Rgb::new(80, 50, 20)),
+            )
+            .unwrap();
+        assert_eq!(chunk.sky_height(other), height);
+    }
+}
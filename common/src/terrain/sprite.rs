@@ -1,3 +1,4 @@
+use super::block::Interaction;
 use crate::make_case_elim;
 use enum_iterator::IntoEnumIterator;
 use lazy_static::lazy_static;
@@ -188,6 +189,29 @@ impl SpriteKind {
         }
     }
 
+    /// The action this sprite offers when looked at, if any
+    pub fn interaction(&self) -> Option<Interaction> {
+        Some(match self {
+            SpriteKind::Velorite | SpriteKind::VeloriteFrag | SpriteKind::ShinyGem => {
+                Interaction::Mine
+            },
+            SpriteKind::Chest
+            | SpriteKind::Crate
+            | SpriteKind::Door
+            | SpriteKind::DrawerLarge
+            | SpriteKind::DrawerMedium
+            | SpriteKind::DrawerSmall
+            | SpriteKind::WardrobeSingle
+            | SpriteKind::WardrobeDouble => Interaction::Open,
+            SpriteKind::Bench | SpriteKind::ChairSingle | SpriteKind::ChairDouble => {
+                Interaction::Sit
+            },
+            SpriteKind::HangingSign => Interaction::Read,
+            _ if self.is_collectible() => Interaction::Collect,
+            _ => return None,
+        })
+    }
+
     pub fn has_ori(&self) -> bool {
         matches!(
             self,
@@ -1,8 +1,8 @@
-use super::BlockKind;
+use super::{Block, BlockKind};
 use crate::{
     assets::{self, Asset, Ron},
     make_case_elim,
-    vol::{BaseVol, ReadVol, SizedVol, WriteVol},
+    vol::{BaseVol, IntoVolIterator, ReadVol, SizedVol, WriteVol},
     volumes::dyna::{Dyna, DynaError},
 };
 use dot_vox::DotVoxData;
@@ -37,6 +37,13 @@ make_case_elim!(
 #[derive(Debug)]
 pub enum StructureError {}
 
+/// A horizontal axis along which a structure can be mirrored
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Axis {
+    X,
+    Y,
+}
+
 #[derive(Clone)]
 pub struct Structure {
     center: Vec3<i32>,
@@ -74,6 +81,86 @@ impl Structure {
     }
 
     pub fn default_kind(&self) -> BlockKind { self.default_kind }
+
+    /// Iterates over every block of the structure with its position relative
+    /// to the center, so that large structures can be placed a few blocks at a
+    /// time. `resolve` turns each structure block into the block to place, or
+    /// `None` to leave that position untouched.
+    ///
+    /// Blocks come bottom layer first, and in the same order every time, so a
+    /// partially placed structure is built up from the ground.
+    pub fn blocks<'a>(
+        &'a self,
+        mut resolve: impl FnMut(Vec3<i32>, StructureBlock) -> Option<Block> + 'a,
+    ) -> impl Iterator<Item = (Vec3<i32>, Block)> + 'a {
+        let bounds = self.get_bounds();
+        (bounds.min.z..bounds.max.z)
+            .flat_map(move |z| {
+                (bounds.min.y..bounds.max.y).flat_map(move |y| {
+                    (bounds.min.x..bounds.max.x).map(move |x| Vec3::new(x, y, z))
+                })
+            })
+            .filter_map(move |pos| match self.get(pos) {
+                Ok(StructureBlock::None) | Err(_) => None,
+                Ok(sblock) => resolve(pos, *sblock).map(|block| (pos, block)),
+            })
+    }
+
+    /// Returns a copy of this structure rotated anticlockwise (looking down)
+    /// about its center by `quarter_turns` multiples of 90 degrees.
+    ///
+    /// Structure blocks carry no orientation of their own, so only their
+    /// positions change.
+    pub fn rotated(&self, quarter_turns: u8) -> Self {
+        (0..quarter_turns % 4).fold(self.clone(), |s, _| s.rotated_once())
+    }
+
+    fn rotated_once(&self) -> Self {
+        let size = self.vol.size();
+        self.remapped(
+            Vec3::new(size.y, size.x, size.z),
+            Vec3::new(size.y as i32 - self.center.y, self.center.x, self.center.z),
+            |pos| Vec3::new(size.y as i32 - 1 - pos.y, pos.x, pos.z),
+        )
+    }
+
+    /// Returns a copy of this structure mirrored about its center along the
+    /// given axis.
+    pub fn mirrored(&self, axis: Axis) -> Self {
+        let size = self.vol.size().map(|e| e as i32);
+        let mut center = self.center;
+        match axis {
+            Axis::X => center.x = size.x - center.x,
+            Axis::Y => center.y = size.y - center.y,
+        }
+        self.remapped(self.vol.size(), center, |mut pos| {
+            match axis {
+                Axis::X => pos.x = size.x - 1 - pos.x,
+                Axis::Y => pos.y = size.y - 1 - pos.y,
+            }
+            pos
+        })
+    }
+
+    /// Copies every block into a new volume of the given size, moving it from
+    /// its current position to `map(pos)`.
+    fn remapped(
+        &self,
+        size: Vec3<u32>,
+        center: Vec3<i32>,
+        map: impl Fn(Vec3<i32>) -> Vec3<i32>,
+    ) -> Self {
+        let mut vol = Dyna::filled(size, StructureBlock::None, ());
+        for (pos, block) in (&self.vol).vol_iter(Vec3::zero(), self.vol.size().map(|e| e as i32)) {
+            let _ = vol.set(map(pos), *block);
+        }
+        Self {
+            center,
+            vol,
+            empty: self.empty,
+            default_kind: self.default_kind,
+        }
+    }
 }
 
 impl BaseVol for Structure {
@@ -162,3 +249,107 @@ struct StructureSpec {
 }
 
 type StructuresSpec = Ron<Vec<StructureSpec>>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_structure() -> Structure {
+        let mut vol = Dyna::filled(Vec3::new(3, 5, 2), StructureBlock::None, ());
+        let _ = vol.set(Vec3::new(0, 0, 0), StructureBlock::Chest);
+        let _ = vol.set(Vec3::new(2, 4, 1), StructureBlock::Water);
+        let _ = vol.set(Vec3::new(1, 3, 0), StructureBlock::Hollow);
+        Structure {
+            center: Vec3::new(1, 1, 0),
+            vol,
+            empty: StructureBlock::None,
+            default_kind: BlockKind::Misc,
+        }
+    }
+
+    fn same_blocks(a: &Structure, b: &Structure) -> bool {
+        let bounds = a.get_bounds();
+        a.get_bounds() == b.get_bounds()
+            && (bounds.min.x..bounds.max.x).all(|x| {
+                (bounds.min.y..bounds.max.y).all(|y| {
+                    (bounds.min.z..bounds.max.z).all(|z| {
+                        let pos = Vec3::new(x, y, z);
+                        a.get(pos).ok() == b.get(pos).ok()
+                    })
+                })
+            })
+    }
+
+    #[test]
+    fn full_rotation_is_identity() {
+        let structure = test_structure();
+        let rotated = (0..4).fold(structure.clone(), |s, _| s.rotated(1));
+        assert!(same_blocks(&structure, &rotated));
+        assert!(same_blocks(&structure, &structure.rotated(4)));
+    }
+
+    #[test]
+    fn rotation_updates_bounds() {
+        let structure = test_structure();
+        assert_eq!(structure.get_bounds(), Aabb {
+            min: Vec3::new(-1, -1, 0),
+            max: Vec3::new(2, 4, 2),
+        });
+
+        let rotated = structure.rotated(1);
+        assert_eq!(rotated.get_bounds(), Aabb {
+            min: Vec3::new(-4, -1, 0),
+            max: Vec3::new(1, 2, 2),
+        });
+        // (x, y) -> (-y - 1, x) in structure space
+        assert!(rotated.get(Vec3::new(0, -1, 0)).ok() == Some(&StructureBlock::Chest));
+        assert!(rotated.get(Vec3::new(-4, 1, 1)).ok() == Some(&StructureBlock::Water));
+        assert!(rotated.get(Vec3::new(-3, 0, 0)).ok() == Some(&StructureBlock::Hollow));
+    }
+
+    #[test]
+    fn blocks_reproduce_structure() {
+        let structure = test_structure();
+        let mut rebuilt = Structure {
+            vol: Dyna::filled(Vec3::new(3, 5, 2), StructureBlock::None, ()),
+            ..structure.clone()
+        };
+        let blocks = structure
+            .blocks(|pos, sblock| {
+                let _ = rebuilt.vol.set(pos + rebuilt.center, sblock);
+                Some(Block::new(BlockKind::Misc, Rgb::new(0, 0, 0)))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(blocks.len(), 3);
+        // Bottom layer first
+        assert!(blocks.windows(2).all(|w| w[0].0.z <= w[1].0.z));
+        assert!(same_blocks(&structure, &rebuilt));
+    }
+
+    #[test]
+    fn unresolved_blocks_are_skipped() {
+        let structure = test_structure();
+        let blocks = structure
+            .blocks(|_, sblock| match sblock {
+                StructureBlock::Chest => None,
+                _ => Some(Block::new(BlockKind::Misc, Rgb::new(0, 0, 0))),
+            })
+            .map(|(pos, _)| pos)
+            .collect::<Vec<_>>();
+        assert_eq!(blocks, vec![Vec3::new(0, 2, 0), Vec3::new(1, 3, 1)]);
+    }
+
+    #[test]
+    fn mirroring_twice_is_identity() {
+        let structure = test_structure();
+        for axis in [Axis::X, Axis::Y].iter() {
+            let mirrored = structure.mirrored(*axis);
+            assert!(!same_blocks(&structure, &mirrored));
+            assert!(same_blocks(&structure, &mirrored.mirrored(*axis)));
+        }
+        assert!(
+            structure.mirrored(Axis::X).get(Vec3::new(0, -1, 0)).ok()
+                == Some(&StructureBlock::Chest)
+        );
+    }
+}
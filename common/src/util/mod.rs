@@ -1,6 +1,7 @@
 mod color;
 pub mod dir;
 mod option;
+pub mod spatial_grid;
 pub mod userdata_dir;
 
 pub const GIT_VERSION: &str = include_str!(concat!(env!("OUT_DIR"), "/githash"));
@@ -27,6 +28,7 @@ lazy_static::lazy_static! {
 pub use color::*;
 pub use dir::*;
 pub use option::*;
+pub use spatial_grid::SpatialGrid;
 
 #[cfg(feature = "tracy")] pub use tracy_client;
 
@@ -0,0 +1,173 @@
+use hashbrown::HashMap;
+use specs::Entity;
+use vek::*;
+
+/// A coarse horizontal grid of entities, used to quickly find the entities
+/// near a point without looking at every entity in the world.
+///
+/// Each entity is stored with a rectangle bounding its footprint, so queries
+/// are conservative: they may return entities that only come close to the
+/// queried area, and callers are expected to do their own precise checks.
+#[derive(Debug)]
+pub struct SpatialGrid {
+    /// Base two logarithm of the side length of each cell, in blocks
+    cell_size_lg: u32,
+    cells: HashMap<Vec2<i32>, Vec<(Entity, Aabr<f32>)>>,
+}
+
+impl Default for SpatialGrid {
+    fn default() -> Self { Self::new(4) }
+}
+
+impl SpatialGrid {
+    pub fn new(cell_size_lg: u32) -> Self {
+        Self {
+            cell_size_lg,
+            cells: HashMap::new(),
+        }
+    }
+
+    fn cell_of(&self, pos: Vec2<f32>) -> Vec2<i32> {
+        pos.map(|e| (e.floor() as i32) >> self.cell_size_lg)
+    }
+
+    fn cells_in(&self, aabr: Aabr<f32>) -> impl Iterator<Item = Vec2<i32>> {
+        let min = self.cell_of(aabr.min);
+        let max = self.cell_of(aabr.max);
+        (min.y..=max.y).flat_map(move |y| (min.x..=max.x).map(move |x| Vec2::new(x, y)))
+    }
+
+    /// Removes all entities, ready for the grid to be rebuilt. Cells are
+    /// dropped too, so cells that entities have left don't build up.
+    pub fn clear(&mut self) { self.cells.clear(); }
+
+    /// Adds an entity whose footprint is a disk of `radius` around `pos`.
+    pub fn insert(&mut self, entity: Entity, pos: Vec2<f32>, radius: f32) {
+        let bounds = Aabr {
+            min: pos - radius,
+            max: pos + radius,
+        };
+        for cell in self.cells_in(bounds).collect::<Vec<_>>() {
+            self.cells.entry(cell).or_default().push((entity, bounds));
+        }
+    }
+
+    fn entries_in_aabr(&self, aabr: Aabr<f32>) -> impl Iterator<Item = (Entity, Aabr<f32>)> + '_ {
+        self.cells_in(aabr)
+            .filter_map(move |cell| Some((cell, self.cells.get(&cell)?)))
+            .flat_map(move |(cell, entries)| {
+                entries
+                    .iter()
+                    .filter(move |(_, bounds)| {
+                        bounds.min.x <= aabr.max.x
+                            && bounds.min.y <= aabr.max.y
+                            && aabr.min.x <= bounds.max.x
+                            && aabr.min.y <= bounds.max.y
+                    })
+                    // Entities spanning several cells are only returned from the
+                    // first cell they share with the query
+                    .filter(move |(_, bounds)| {
+                        self.cell_of(bounds.min.map2(aabr.min, f32::max)) == cell
+                    })
+                    .copied()
+            })
+    }
+
+    /// Entities whose footprint overlaps `aabr`. Each entity is returned at
+    /// most once.
+    pub fn entities_in_aabr(&self, aabr: Aabr<f32>) -> impl Iterator<Item = Entity> + '_ {
+        self.entries_in_aabr(aabr).map(|(entity, _)| entity)
+    }
+
+    /// Entities whose footprint comes within `radius` of `pos`. Each entity is
+    /// returned at most once.
+    pub fn entities_in_radius(
+        &self,
+        pos: Vec2<f32>,
+        radius: f32,
+    ) -> impl Iterator<Item = Entity> + '_ {
+        let aabr = Aabr {
+            min: pos - radius,
+            max: pos + radius,
+        };
+        self.entries_in_aabr(aabr)
+            .filter(move |(_, bounds)| {
+                let closest = Vec2::new(
+                    pos.x.clamped(bounds.min.x, bounds.max.x),
+                    pos.y.clamped(bounds.min.y, bounds.max.y),
+                );
+                closest.distance_squared(pos) <= radius.powi(2)
+            })
+            .map(|(entity, _)| entity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+    use specs::{Builder, World, WorldExt};
+
+    #[test]
+    fn queries_match_brute_force() {
+        let mut world = World::new();
+        let mut rng = StdRng::seed_from_u64(42);
+        let entities = (0..2000)
+            .map(|_| {
+                let pos = Vec2::new(rng.gen_range(-200.0, 200.0), rng.gen_range(-200.0, 200.0));
+                let radius = rng.gen_range(0.3, 10.0);
+                (world.create_entity().build(), pos, radius)
+            })
+            .collect::<Vec<(Entity, Vec2<f32>, f32)>>();
+
+        let mut grid = SpatialGrid::default();
+        for (entity, pos, radius) in entities.iter() {
+            grid.insert(*entity, *pos, *radius);
+        }
+
+        for _ in 0..100 {
+            let center = Vec2::new(rng.gen_range(-220.0, 220.0), rng.gen_range(-220.0, 220.0));
+            let query_radius = rng.gen_range(0.0, 40.0);
+
+            let mut found = grid
+                .entities_in_radius(center, query_radius)
+                .collect::<Vec<_>>();
+            found.sort();
+            let mut expected = entities
+                .iter()
+                .filter(|(_, pos, radius)| {
+                    let closest = Vec2::new(
+                        center.x.clamped(pos.x - radius, pos.x + radius),
+                        center.y.clamped(pos.y - radius, pos.y + radius),
+                    );
+                    closest.distance_squared(center) <= query_radius.powi(2)
+                })
+                .map(|(entity, _, _)| *entity)
+                .collect::<Vec<_>>();
+            expected.sort();
+            assert_eq!(found, expected);
+
+            // Every entity within the query radius is in its bounding square
+            let aabr = Aabr {
+                min: center - query_radius,
+                max: center + query_radius,
+            };
+            let in_aabr = grid.entities_in_aabr(aabr).collect::<Vec<_>>();
+            assert!(found.iter().all(|entity| in_aabr.contains(entity)));
+            let mut deduped = in_aabr.clone();
+            deduped.sort();
+            deduped.dedup();
+            assert_eq!(deduped.len(), in_aabr.len());
+        }
+    }
+
+    #[test]
+    fn clear_removes_everything() {
+        let mut world = World::new();
+        let mut grid = SpatialGrid::default();
+        grid.insert(world.create_entity().build(), Vec2::zero(), 1.0);
+        grid.clear();
+        assert_eq!(grid.entities_in_radius(Vec2::zero(), 100.0).count(), 0);
+        assert!(grid.cells.is_empty());
+    }
+}
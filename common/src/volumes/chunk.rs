@@ -204,6 +204,24 @@ impl<V, S: VolSize, M> Chunk<V, S, M> {
 
     pub fn num_groups(&self) -> usize { self.vox.len() / Self::GROUP_VOLUME as usize }
 
+    /// Calls `f` with the voxels in the chunk and how many times each occurs.
+    /// Groups that were never written to are counted all at once, as the
+    /// default voxel, rather than voxel by voxel. The same voxel may be
+    /// reported several times.
+    pub fn for_each_vox_count(&self, mut f: impl FnMut(&V, u32)) {
+        let mut default_count = 0;
+        for &base in self.indices.iter() {
+            let start = usize::from(base) * Self::GROUP_VOLUME as usize;
+            match self.vox.get(start..start + Self::GROUP_VOLUME as usize) {
+                Some(group) => group.iter().for_each(|vox| f(vox, 1)),
+                None => default_count += Self::GROUP_VOLUME,
+            }
+        }
+        if default_count > 0 {
+            f(&self.default, default_count);
+        }
+    }
+
     /// Returns `Some(v)` if the block is homogeneous and contains nothing but
     /// voxels of value `v`, and `None` otherwise.  This method is
     /// conservative (it may return None when the chunk is
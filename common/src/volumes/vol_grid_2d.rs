@@ -1,5 +1,5 @@
 use crate::{
-    vol::{BaseVol, ReadVol, RectRasterableVol, SampleVol, WriteVol},
+    vol::{BaseVol, ReadVol, RectRasterableVol, SampleVol, Vox, WriteVol},
     volumes::dyna::DynaError,
 };
 use hashbrown::{hash_map, HashMap};
@@ -55,6 +55,27 @@ impl<V: RectRasterableVol + ReadVol + Debug> ReadVol for VolGrid2d<V> {
     }
 }
 
+impl<V: RectRasterableVol + ReadVol + Debug> VolGrid2d<V>
+where
+    V::Vox: Clone,
+{
+    /// The voxels sharing a face with `pos`, crossing chunk boundaries where
+    /// needed, or `None` for any that lie in a chunk that isn't loaded.
+    ///
+    /// The order is stable and always `[+x, -x, +y, -y, +z, -z]`.
+    pub fn face_neighbors(&self, pos: Vec3<i32>) -> [Option<V::Vox>; 6] {
+        let get = |offs: Vec3<i32>| self.get(pos + offs).ok().cloned();
+        [
+            get(Vec3::unit_x()),
+            get(-Vec3::unit_x()),
+            get(Vec3::unit_y()),
+            get(-Vec3::unit_y()),
+            get(Vec3::unit_z()),
+            get(-Vec3::unit_z()),
+        ]
+    }
+}
+
 // TODO: This actually breaks the API: samples are supposed to have an offset of
 // zero! TODO: Should this be changed, perhaps?
 impl<I: Into<Aabr<i32>>, V: RectRasterableVol + ReadVol + Debug> SampleVol<I> for VolGrid2d<V> {
@@ -103,6 +124,30 @@ impl<V: RectRasterableVol + WriteVol + Clone + Debug> WriteVol for VolGrid2d<V>
     }
 }
 
+impl<V: RectRasterableVol + ReadVol + WriteVol + Clone + Debug> VolGrid2d<V>
+where
+    V::Vox: Vox + PartialEq + Clone,
+{
+    /// Sets the voxel at `pos` to `new`, but only if it's currently
+    /// `expected`. Otherwise nothing is written and the voxel that was found
+    /// is returned instead. Positions that aren't loaded read as empty.
+    pub fn compare_and_set(
+        &mut self,
+        pos: Vec3<i32>,
+        expected: V::Vox,
+        new: V::Vox,
+    ) -> Result<(), V::Vox> {
+        let actual = self
+            .get(pos)
+            .map_or_else(|_| V::Vox::empty(), |vox| vox.clone());
+        if actual == expected {
+            self.set(pos, new).map_err(|_| actual)
+        } else {
+            Err(actual)
+        }
+    }
+}
+
 impl<V: RectRasterableVol> VolGrid2d<V> {
     pub fn new() -> Result<Self, VolGrid2dError<V>> {
         if Self::chunk_size()
@@ -132,6 +177,14 @@ impl<V: RectRasterableVol> VolGrid2d<V> {
 
     pub fn get_key_arc(&self, key: Vec2<i32>) -> Option<&Arc<V>> { self.chunks.get(&key) }
 
+    /// Mutable access to a chunk, copying it first if it's shared
+    pub fn get_key_mut(&mut self, key: Vec2<i32>) -> Option<&mut V>
+    where
+        V: Clone,
+    {
+        self.chunks.get_mut(&key).map(Arc::make_mut)
+    }
+
     pub fn clear(&mut self) { self.chunks.clear(); }
 
     pub fn drain(&mut self) -> hash_map::Drain<Vec2<i32>, Arc<V>> { self.chunks.drain() }
@@ -212,3 +265,80 @@ impl<'a, V: RectRasterableVol> Iterator for ChunkIter<'a, V> {
 
     fn next(&mut self) -> Option<Self::Item> { self.iter.next().map(|(k, c)| (*k, c)) }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        terrain::{Block, BlockKind, SpriteKind, TerrainChunk, TerrainChunkMeta, TerrainGrid},
+        vol::ReadVol,
+    };
+    use std::sync::Arc;
+    use vek::*;
+
+    fn rock() -> Block { Block::new(BlockKind::Rock, Rgb::new(100, 100, 100)) }
+
+    fn sand() -> Block { Block::new(BlockKind::Sand, Rgb::new(200, 180, 100)) }
+
+    fn chunk(below: Block) -> Arc<TerrainChunk> {
+        Arc::new(TerrainChunk::new(
+            0,
+            below,
+            Block::air(SpriteKind::Empty),
+            TerrainChunkMeta::void(),
+        ))
+    }
+
+    #[test]
+    fn face_neighbors_in_chunk_interior() {
+        let mut grid = TerrainGrid::new().unwrap();
+        grid.insert(Vec2::zero(), chunk(rock()));
+
+        let air = Some(Block::air(SpriteKind::Empty));
+        assert_eq!(grid.face_neighbors(Vec3::new(5, 5, 0)), [
+            air,
+            air,
+            air,
+            air,
+            air,
+            Some(rock()),
+        ]);
+    }
+
+    #[test]
+    fn compare_and_set_writes_on_match() {
+        let mut grid = TerrainGrid::new().unwrap();
+        grid.insert(Vec2::zero(), chunk(rock()));
+
+        let pos = Vec3::new(3, 4, -1);
+        assert_eq!(grid.compare_and_set(pos, rock(), sand()), Ok(()));
+        assert_eq!(grid.get(pos).ok(), Some(&sand()));
+    }
+
+    #[test]
+    fn compare_and_set_reports_mismatch() {
+        let mut grid = TerrainGrid::new().unwrap();
+        grid.insert(Vec2::zero(), chunk(rock()));
+
+        let pos = Vec3::new(3, 4, -1);
+        let air = Block::air(SpriteKind::Empty);
+        assert_eq!(grid.compare_and_set(pos, sand(), air), Err(rock()));
+        assert_eq!(grid.get(pos).ok(), Some(&rock()));
+    }
+
+    #[test]
+    fn face_neighbors_at_chunk_corner() {
+        let mut grid = TerrainGrid::new().unwrap();
+        grid.insert(Vec2::zero(), chunk(rock()));
+        grid.insert(Vec2::new(-1, 0), chunk(sand()));
+
+        // The -x neighbor lies in the chunk to the west, the -y neighbor in an
+        // unloaded chunk to the south
+        let neighbors = grid.face_neighbors(Vec3::new(0, 0, -1));
+        assert_eq!(neighbors[0], Some(rock()));
+        assert_eq!(neighbors[1], Some(sand()));
+        assert_eq!(neighbors[2], Some(rock()));
+        assert_eq!(neighbors[3], None);
+        assert_eq!(neighbors[4], Some(Block::air(SpriteKind::Empty)));
+        assert_eq!(neighbors[5], Some(rock()));
+    }
+}
@@ -359,4 +359,34 @@ mod tests {
         let msg = Message::serialize(&msg, &stub_stream(true));
         assert_eq!(msg.buffer.data.len(), 1296);
     }
+
+    /// Bulk messages such as terrain chunk updates rely on the stream
+    /// compression, so they must come out exactly as they went in
+    #[cfg(feature = "compression")]
+    #[test]
+    fn compressed_round_trip() {
+        // Shaped like a terrain chunk update: a chunk key, then layers of
+        // mostly identical blocks (kind and colour) with the odd different one
+        let key = (-12i32, 37i32);
+        let blocks = (0..32 * 32 * 24)
+            .map(|i: u32| match i % 97 {
+                0 => (0x11u8, 80u8, 50u8, 20u8),
+                _ if i < 32 * 32 * 8 => (0x10, 100, 100, 100),
+                _ => (0x00, 0, 0, 0),
+            })
+            .collect::<Vec<_>>();
+        let payload = (key, Some(blocks));
+
+        let raw_len = Message::serialize(&payload, &stub_stream(false))
+            .buffer
+            .data
+            .len();
+        let msg = Message::serialize(&payload, &stub_stream(true));
+        assert!(msg.buffer.data.len() < raw_len / 10);
+        assert_eq!(
+            msg.deserialize::<((i32, i32), Option<Vec<(u8, u8, u8, u8)>>)>()
+                .unwrap(),
+            payload
+        );
+    }
 }
@@ -1,7 +1,7 @@
 use crate::persistence::character_loader::CharacterLoader;
 use common::{
-    comp::{Body, Inventory, Stats},
-    loadout_builder::LoadoutBuilder,
+    comp::{Body, Inventory, Loadout, Stats},
+    loadout_builder::{LoadoutBuilder, LoadoutError},
 };
 use specs::{Entity, ReadExpect};
 
@@ -12,16 +12,9 @@ pub fn create_character(
     character_tool: Option<String>,
     body: Body,
     character_loader: &ReadExpect<'_, CharacterLoader>,
-) {
+) -> Result<(), LoadoutError> {
     let stats = Stats::new(character_alias.to_string(), body);
-
-    let loadout = LoadoutBuilder::new()
-        .defaults()
-        .active_item(Some(LoadoutBuilder::default_item_config_from_str(
-            character_tool.as_deref().unwrap(),
-        )))
-        .build();
-
+    let loadout = starting_loadout(character_tool.as_deref())?;
     let inventory = Inventory::default();
 
     character_loader.create_character(
@@ -30,4 +23,39 @@ pub fn create_character(
         character_alias,
         (body, stats, inventory, loadout),
     );
+    Ok(())
+}
+
+/// Builds the loadout a new character starts with, checking the tool picked
+/// by the client before anything is loaded for it
+fn starting_loadout(character_tool: Option<&str>) -> Result<Loadout, LoadoutError> {
+    let tool = character_tool.ok_or(LoadoutError::MissingTool)?;
+    LoadoutBuilder::new()
+        .defaults()
+        .active_item(Some(LoadoutBuilder::try_item_config_from_str(tool)?))
+        .try_build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::comp::slot::EquipSlot;
+
+    #[test]
+    fn bad_starting_tools_are_rejected() {
+        assert!(starting_loadout(Some("common.items.weapons.sword.starter_sword")).is_ok());
+        assert_eq!(
+            starting_loadout(None).map(|_| ()),
+            Err(LoadoutError::MissingTool)
+        );
+
+        let chest = "common.items.armor.starter.rugged_chest";
+        assert_eq!(
+            starting_loadout(Some(chest)).map(|_| ()),
+            Err(LoadoutError::WrongSlot {
+                slot: EquipSlot::Mainhand,
+                item: chest.to_string(),
+            })
+        );
+    }
 }
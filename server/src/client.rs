@@ -1,5 +1,9 @@
 use crate::error::Error;
-use common::msg::{ClientInGame, ClientType, ServerGeneral, ServerMsg};
+use common::{
+    msg::{ClientInGame, ClientType, ServerGeneral, ServerGeneralStream, ServerMsg},
+    sync::Uid,
+    terrain::TerrainChunk,
+};
 use hashbrown::HashSet;
 use network::{Participant, Stream};
 use serde::{de::DeserializeOwned, Serialize};
@@ -21,6 +25,73 @@ pub struct Client {
     pub network_error: bool,
     pub last_ping: f64,
     pub login_msg_sent: bool,
+    /// Server time of the last position the client reported that was
+    /// accepted, used to check how far it could have moved since
+    pub last_physics_update: Option<f64>,
+    /// The dialogue the client was last shown, until it picks a response
+    pub pending_dialogue: Option<PendingDialogue>,
+    /// Chunks the client is still waiting for after entering the game, until
+    /// its view distance has been filled once
+    pub chunk_gen_progress: Option<ChunkGenProgress>,
+    /// The music mood the client was last sent, see `sys::music_mood`
+    pub music_mood: f32,
+    /// Entities whose threat table the client was last sent, see `sys::threat`
+    pub threat_tables: HashSet<Uid>,
+}
+
+/// A dialogue a client was shown, see `Client::send_dialogue`
+#[derive(Clone, Debug, PartialEq)]
+pub struct PendingDialogue {
+    pub speaker: Uid,
+    /// The responses the client may choose from
+    pub responses: Vec<String>,
+}
+
+/// Tracks which chunks around a client's starting position it has been sent,
+/// see `Client::send_chunk`
+#[derive(Clone, Debug, Default)]
+pub struct ChunkGenProgress {
+    pending: HashSet<Vec2<i32>>,
+    requested: u32,
+    ready: u32,
+}
+
+impl ChunkGenProgress {
+    /// Starts waiting for every chunk within `view_distance` of `center`,
+    /// matching the area the client requests before anything else.
+    pub fn start(&mut self, center: Vec2<i32>, view_distance: u32) {
+        let radius = view_distance.saturating_sub(1) as i32;
+        self.pending = (-radius..=radius)
+            .flat_map(|x| (-radius..=radius).map(move |y| Vec2::new(x, y)))
+            .filter(|offs| offs.magnitude_squared() <= radius.pow(2))
+            .map(|offs| center + offs)
+            .collect();
+        self.requested = self.pending.len() as u32;
+        self.ready = 0;
+    }
+
+    pub fn is_started(&self) -> bool { self.requested > 0 }
+
+    pub fn is_done(&self) -> bool { self.is_started() && self.pending.is_empty() }
+
+    /// Marks a chunk as sent, returning whether it was one being waited on.
+    /// Chunks outside the starting area or sent twice aren't counted again,
+    /// so `ready` never exceeds `requested`.
+    pub fn chunk_ready(&mut self, key: Vec2<i32>) -> bool {
+        if self.pending.remove(&key) {
+            self.ready += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn to_msg(&self) -> ServerGeneral {
+        ServerGeneral::ChunkGenProgress {
+            requested: self.requested,
+            ready: self.ready,
+        }
+    }
 }
 
 impl Component for Client {
@@ -61,35 +132,10 @@ impl Client {
                 Self::internal_send(&mut self.network_error, &mut self.register_stream, &msg)
             },
             ServerMsg::General(msg) => {
-                let stream = match &msg {
-                    //Character Screen related
-                    ServerGeneral::CharacterDataLoadError(_)
-                    | ServerGeneral::CharacterListUpdate(_)
-                    | ServerGeneral::CharacterActionError(_)
-                    | ServerGeneral::CharacterSuccess => &mut self.character_screen_stream,
-                    //Ingame related
-                    ServerGeneral::GroupUpdate(_)
-                    | ServerGeneral::GroupInvite { .. }
-                    | ServerGeneral::InvitePending(_)
-                    | ServerGeneral::InviteComplete { .. }
-                    | ServerGeneral::ExitInGameSuccess
-                    | ServerGeneral::InventoryUpdate(_, _)
-                    | ServerGeneral::TerrainChunkUpdate { .. }
-                    | ServerGeneral::TerrainBlockUpdates(_)
-                    | ServerGeneral::SetViewDistance(_)
-                    | ServerGeneral::Outcomes(_)
-                    | ServerGeneral::Knockback(_) => &mut self.in_game_stream,
-                    // Always possible
-                    ServerGeneral::PlayerListUpdate(_)
-                    | ServerGeneral::ChatMsg(_)
-                    | ServerGeneral::SetPlayerEntity(_)
-                    | ServerGeneral::TimeOfDay(_)
-                    | ServerGeneral::EntitySync(_)
-                    | ServerGeneral::CompSync(_)
-                    | ServerGeneral::CreateEntity(_)
-                    | ServerGeneral::DeleteEntity(_)
-                    | ServerGeneral::Disconnect(_)
-                    | ServerGeneral::Notification(_) => &mut self.general_stream,
+                let stream = match msg.stream() {
+                    ServerGeneralStream::CharacterScreen => &mut self.character_screen_stream,
+                    ServerGeneralStream::InGame => &mut self.in_game_stream,
+                    ServerGeneralStream::General => &mut self.general_stream,
                 };
                 Self::internal_send(&mut self.network_error, stream, &msg)
             },
@@ -99,6 +145,51 @@ impl Client {
         };
     }
 
+    /// Sends a chunk (or the failure to generate it) to the client, reporting
+    /// how far along it is if it's still waiting for its starting area.
+    pub fn send_chunk(&mut self, key: Vec2<i32>, chunk: Result<Box<TerrainChunk>, ()>) {
+        self.send_msg(ServerGeneral::TerrainChunkUpdate { key, chunk });
+        if let Some(progress) = self
+            .chunk_gen_progress
+            .as_mut()
+            .filter(|progress| progress.chunk_ready(key))
+        {
+            let msg = progress.to_msg();
+            if progress.is_done() {
+                self.chunk_gen_progress = None;
+            }
+            self.send_msg(msg);
+        }
+    }
+
+    /// Shows the client a dialogue box, replacing any it hasn't answered yet.
+    pub fn send_dialogue(&mut self, speaker: Uid, lines: Vec<String>, responses: Vec<String>) {
+        self.pending_dialogue = Some(PendingDialogue {
+            speaker,
+            responses: responses.clone(),
+        });
+        self.send_msg(ServerGeneral::Dialogue {
+            speaker,
+            lines,
+            responses,
+        });
+    }
+
+    /// Closes the pending dialogue if `index` is one of its responses,
+    /// returning who the client was speaking to and the response it picked.
+    /// Anything else is rejected and leaves the dialogue open.
+    pub fn answer_dialogue(&mut self, index: usize) -> Option<(Uid, String)> {
+        match self.pending_dialogue.take() {
+            Some(mut dialogue) if index < dialogue.responses.len() => {
+                Some((dialogue.speaker, dialogue.responses.swap_remove(index)))
+            },
+            dialogue => {
+                self.pending_dialogue = dialogue;
+                None
+            },
+        }
+    }
+
     pub async fn internal_recv<M: DeserializeOwned>(
         err: &mut bool,
         s: &mut Stream,
@@ -132,3 +223,148 @@ pub struct RegionSubscription {
 impl Component for RegionSubscription {
     type Storage = FlaggedStorage<Self, IdvStorage<Self>>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_executor::block_on;
+    use network::{Network, Pid, Promises, ProtocolAddr};
+    use std::{net::TcpListener, thread};
+
+    /// Connects a `Client` to a stand-in for a game client, returning it along
+    /// with the game client's in-game stream and the rest of the other end of
+    /// the connection, which has to outlive it.
+    fn connect_client() -> (Client, Stream, impl Sized) {
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let (network, scheduler) = Network::new(Pid::new());
+        thread::spawn(scheduler);
+        let (remote_network, remote_scheduler) = Network::new(Pid::new());
+        thread::spawn(remote_scheduler);
+        block_on(network.listen(ProtocolAddr::Tcp(addr))).unwrap();
+        let remote = block_on(remote_network.connect(ProtocolAddr::Tcp(addr))).unwrap();
+        let participant = block_on(network.connected()).unwrap();
+
+        let open = || block_on(participant.open(10, Promises::ORDERED)).unwrap();
+        let client = Client {
+            registered: true,
+            client_type: ClientType::Game,
+            in_game: Some(ClientInGame::Character),
+            participant: None,
+            general_stream: open(),
+            ping_stream: open(),
+            register_stream: open(),
+            character_screen_stream: open(),
+            in_game_stream: open(),
+            network_error: false,
+            last_ping: 0.0,
+            login_msg_sent: true,
+            last_physics_update: None,
+            pending_dialogue: None,
+            chunk_gen_progress: None,
+            music_mood: 0.0,
+            threat_tables: HashSet::new(),
+        };
+        let mut remote_streams = (0..5)
+            .map(|_| block_on(remote.opened()).unwrap())
+            .collect::<Vec<_>>();
+        // Opened last, like `in_game_stream` above
+        let in_game_stream = remote_streams.pop().unwrap();
+
+        (
+            client,
+            in_game_stream,
+            (network, participant, remote_network, remote, remote_streams),
+        )
+    }
+
+    #[test]
+    fn out_of_range_responses_rejected() {
+        let (mut client, _in_game, _remote) = connect_client();
+        let speaker = Uid(7);
+
+        // Nothing to answer yet
+        assert_eq!(client.answer_dialogue(0), None);
+
+        client.send_dialogue(speaker, vec!["Hello".to_string()], vec![
+            "Hi".to_string(),
+            "Bye".to_string(),
+        ]);
+        assert!(!client.network_error);
+        assert_eq!(client.answer_dialogue(2), None);
+        assert_eq!(client.answer_dialogue(usize::MAX), None);
+        assert_eq!(
+            client.answer_dialogue(1),
+            Some((speaker, "Bye".to_string()))
+        );
+
+        // Each dialogue can only be answered once
+        assert_eq!(client.answer_dialogue(0), None);
+
+        // Dialogues without responses can't be answered at all
+        client.send_dialogue(speaker, vec!["Farewell".to_string()], Vec::new());
+        assert_eq!(client.answer_dialogue(0), None);
+        assert!(client.pending_dialogue.is_some());
+    }
+
+    #[test]
+    fn ready_never_exceeds_requested() {
+        let mut progress = ChunkGenProgress::default();
+        progress.start(Vec2::new(10, -4), 3);
+        assert_eq!(progress.requested, 13);
+
+        // Chunks outside the area and duplicates don't count
+        assert!(!progress.chunk_ready(Vec2::new(20, 20)));
+        assert!(progress.chunk_ready(Vec2::new(10, -4)));
+        assert!(!progress.chunk_ready(Vec2::new(10, -4)));
+        assert_eq!(progress.ready, 1);
+
+        for x in 8..=12 {
+            for y in -6..=-2 {
+                progress.chunk_ready(Vec2::new(x, y));
+            }
+        }
+        assert_eq!(progress.ready, progress.requested);
+        assert!(progress.is_done());
+    }
+
+    #[test]
+    fn progress_sent_with_starting_chunks() {
+        let (mut client, mut in_game, _remote) = connect_client();
+        let mut progress = ChunkGenProgress::default();
+        progress.start(Vec2::zero(), 2);
+        client.chunk_gen_progress = Some(progress);
+
+        let mut recv = || block_on(in_game.recv::<ServerGeneral>()).unwrap();
+        let starting_area = [
+            Vec2::new(0, 0),
+            Vec2::new(1, 0),
+            Vec2::new(-1, 0),
+            Vec2::new(0, 1),
+            Vec2::new(0, -1),
+        ];
+        for (i, key) in starting_area.iter().enumerate() {
+            client.send_chunk(*key, Err(()));
+            assert!(matches!(
+                recv(),
+                ServerGeneral::TerrainChunkUpdate { key: sent, .. } if sent == *key
+            ));
+            match recv() {
+                ServerGeneral::ChunkGenProgress { requested, ready } => {
+                    assert_eq!(requested, 5);
+                    assert_eq!(ready, i as u32 + 1);
+                },
+                msg => panic!("Expected ChunkGenProgress, got {:?}", msg),
+            }
+        }
+        assert!(client.chunk_gen_progress.is_none());
+
+        // Once the starting area is done, chunks are sent without progress
+        client.send_chunk(Vec2::new(5, 5), Err(()));
+        client.send_chunk(Vec2::new(0, 0), Err(()));
+        assert!(matches!(recv(), ServerGeneral::TerrainChunkUpdate { .. }));
+        assert!(matches!(recv(), ServerGeneral::TerrainChunkUpdate { .. }));
+    }
+}
@@ -74,6 +74,7 @@ fn get_handler(cmd: &ChatCommand) -> CommandHandler {
         ChatCommand::Campfire => handle_spawn_campfire,
         ChatCommand::Debug => handle_debug,
         ChatCommand::DebugColumn => handle_debug_column,
+        ChatCommand::Dialogue => handle_dialogue,
         ChatCommand::Dummy => handle_spawn_training_dummy,
         ChatCommand::Explosion => handle_explosion,
         ChatCommand::Faction => handle_faction,
@@ -775,6 +776,7 @@ fn handle_spawn_campfire(
                     strength: 2.0,
                     flicker: 1.0,
                     animated: true,
+                    periodic_flicker: None,
                 })
                 .with(WaypointArea::default())
                 .build();
@@ -1594,6 +1596,47 @@ spawn_rate {:?} "#,
     }
 }
 
+fn handle_dialogue(
+    server: &mut Server,
+    client: EcsEntity,
+    target: EcsEntity,
+    args: String,
+    action: &ChatCommand,
+) {
+    if let Ok((alias, message)) = scan_fmt!(&args, &action.arg_fmt(), String, String) {
+        let ecs = server.state.ecs();
+        let speaker = *ecs
+            .read_storage::<Uid>()
+            .get(target)
+            .expect("Player must have uid");
+        let player = (&ecs.entities(), &ecs.read_storage::<comp::Player>())
+            .join()
+            .find(|(_, player)| player.alias == alias)
+            .map(|(entity, _)| entity);
+        let sent = player
+            .and_then(|player| {
+                let mut clients = ecs.write_storage::<Client>();
+                let mut parts = message.split('|').map(|part| part.trim().to_string());
+                let lines = parts.next().into_iter().collect();
+                clients
+                    .get_mut(player)
+                    .map(|player| player.send_dialogue(speaker, lines, parts.collect()))
+            })
+            .is_some();
+        if !sent {
+            server.notify_client(
+                client,
+                ChatType::CommandError.server_msg(format!("Player '{}' not found!", alias)),
+            );
+        }
+    } else {
+        server.notify_client(
+            client,
+            ChatType::CommandError.server_msg(action.help_string()),
+        );
+    }
+}
+
 fn find_target(
     ecs: &specs::World,
     opt_alias: Option<String>,
@@ -4,6 +4,7 @@ use futures_channel::oneshot;
 use futures_executor::block_on;
 use futures_timer::Delay;
 use futures_util::{select, FutureExt};
+use hashbrown::HashSet;
 use network::{Network, Participant, Promises};
 use std::{sync::Arc, thread, time::Duration};
 use tracing::{debug, error, trace, warn};
@@ -100,6 +101,8 @@ impl ConnectionHandler {
         info_requester_sender.send(sender)?;
 
         let reliable = Promises::ORDERED | Promises::CONSISTENCY;
+        // Bulk messages such as `ServerInit::GameSync` and terrain chunk updates
+        // go over these, so every message on them is compressed by the network
         let reliablec = reliable | Promises::COMPRESSED;
 
         let general_stream = participant.open(10, reliablec).await?;
@@ -137,6 +140,11 @@ impl ConnectionHandler {
             network_error: false,
             last_ping: server_data.time,
             login_msg_sent: false,
+            last_physics_update: None,
+            chunk_gen_progress: None,
+            music_mood: 0.0,
+            threat_tables: HashSet::new(),
+            pending_dialogue: None,
         };
 
         client_sender.send(client)?;
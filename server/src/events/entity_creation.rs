@@ -150,6 +150,7 @@ pub fn handle_create_waypoint(server: &mut Server, pos: Vec3<f32>) {
             strength: 5.0,
             flicker: 1.0,
             animated: true,
+            periodic_flicker: None,
         })
         .with(WaypointArea::default())
         .with(comp::Mass(100000.0))
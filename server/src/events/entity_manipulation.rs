@@ -8,13 +8,14 @@ use common::{
     comp::{
         self, buff,
         chat::{KillSource, KillType},
-        object, Alignment, Body, Damage, DamageSource, Group, HealthChange, HealthSource, Item,
-        Player, Pos, Stats,
+        object, Alignment, Body, Damage, DamageKind, DamageSource, Group, HealthChange,
+        HealthSource, Item, Player, Pos, Stats,
     },
+    gameplay::GameplaySettings,
     lottery::Lottery,
-    msg::{PlayerListUpdate, ServerGeneral},
+    msg::{KillCause, PlayerListUpdate, ServerGeneral},
     outcome::Outcome,
-    state::BlockChange,
+    state::{BlockChange, State, Time},
     sync::{Uid, UidAllocator, WorldSyncExt},
     sys::combat::BLOCK_ANGLE,
     terrain::{Block, TerrainGrid},
@@ -24,21 +25,90 @@ use common::{
 use comp::item::Reagent;
 use rand::prelude::*;
 use specs::{join::Join, saveload::MarkerAllocator, Entity as EcsEntity, WorldExt};
+use std::time::Duration;
 use tracing::error;
-use vek::Vec3;
+use vek::{Rgb, Vec3};
 
-pub fn handle_damage(server: &Server, uid: Uid, change: HealthChange) {
+pub fn handle_damage(server: &Server, uid: Uid, mut change: HealthChange) {
     let state = &server.state;
     let ecs = state.ecs();
     if let Some(entity) = ecs.entity_from_uid(uid.into()) {
+        // Players can't hurt each other in safe zones
+        let attacker = match change.cause {
+            HealthSource::Attack { by } => Some(by),
+            HealthSource::Projectile { owner }
+            | HealthSource::Explosion { owner }
+            | HealthSource::Energy { owner }
+            | HealthSource::Buff { owner } => owner,
+            _ => None,
+        }
+        .and_then(|by| ecs.entity_from_uid(by.into()))
+        .filter(|attacker| *attacker != entity);
+        if let Some(attacker) = attacker.filter(|_| change.amount < 0) {
+            let players = ecs.read_storage::<Player>();
+            let positions = ecs.read_storage::<Pos>();
+            let is_pvp = players.contains(attacker) && players.contains(entity);
+            if let (true, Some(attacker_pos), Some(pos)) =
+                (is_pvp, positions.get(attacker), positions.get(entity))
+            {
+                if !ecs
+                    .read_resource::<TerrainGrid>()
+                    .allows_pvp(attacker_pos.0, pos.0)
+                {
+                    return;
+                }
+            }
+        }
+
+        // Protective buffs soften damage, but can't save you from yourself or an admin
+        let reducible = !matches!(change.cause, HealthSource::Suicide | HealthSource::Command);
+        if change.amount < 0 && reducible {
+            if let Some(buffs) = ecs.read_storage::<comp::Buffs>().get(entity) {
+                change.amount = (change.amount as f32 * (1.0 - buffs.damage_reduction())) as i32;
+            }
+        }
         if let Some(stats) = ecs.write_storage::<Stats>().get_mut(entity) {
             stats.health.change_by(change);
         }
+        // Remember who hurt the entity last, for kill credit. Hurting yourself
+        // doesn't take the credit away from them.
+        if let Some(by) = attacker
+            .filter(|_| change.amount < 0)
+            .and_then(|attacker| ecs.read_storage::<Uid>().get(attacker).copied())
+            .filter(|by| *by != uid)
+        {
+            let _ = ecs.write_storage().insert(entity, comp::LastHitBy {
+                by,
+                time: ecs.read_resource::<Time>().0,
+            });
+        }
+        // NPCs remember who hurt them the most
+        if let Some(by) = attacker
+            .filter(|_| change.amount < 0 && ecs.read_storage::<comp::Agent>().contains(entity))
+            .and_then(|attacker| ecs.read_storage::<Uid>().get(attacker).copied())
+        {
+            if let Ok(entry) = ecs.write_storage::<comp::ThreatTable>().entry(entity) {
+                entry
+                    .or_insert_with(Default::default)
+                    .add(by, -change.amount as f32);
+            }
+        }
     }
 }
 
 pub fn handle_knockback(server: &Server, entity: EcsEntity, impulse: Vec3<f32>) {
     let state = &server.state;
+    let time = state.ecs().read_resource::<Time>().0;
+    let mut last_knockbacks = state.ecs().write_storage::<comp::LastKnockback>();
+    if !state
+        .ecs()
+        .read_resource::<comp::KnockbackImmunity>()
+        .allows(last_knockbacks.get(entity), time)
+    {
+        return;
+    }
+    let _ = last_knockbacks.insert(entity, comp::LastKnockback(time));
+
     let mut velocities = state.ecs().write_storage::<comp::Vel>();
     if let Some(vel) = velocities.get_mut(entity) {
         vel.0 = impulse;
@@ -49,6 +119,45 @@ pub fn handle_knockback(server: &Server, entity: EcsEntity, impulse: Vec3<f32>)
     }
 }
 
+pub fn handle_cancel_ability(server: &Server, entity: EcsEntity) {
+    cancel_ability(&server.state, entity)
+}
+
+fn cancel_ability(state: &State, entity: EcsEntity) {
+    let ecs = state.ecs();
+    let mut character_states = ecs.write_storage::<comp::CharacterState>();
+    let character_state = match character_states.get_mut(entity) {
+        Some(character_state) => character_state,
+        None => return,
+    };
+    let before = character_state.clone();
+    if let Some(refund) = character_state.try_cancel() {
+        if let Some(energy) = ecs.write_storage::<comp::Energy>().get_mut(entity) {
+            energy.change_by(refund as i32, comp::EnergySource::Refund);
+        }
+        // The cancelled ability stops buffing its user
+        if let Some(uid) = ecs.read_storage::<Uid>().get(entity) {
+            for buff_change in before.self_buff_changes(character_state, *uid) {
+                change_buffs(state, entity, buff_change);
+            }
+        }
+    }
+}
+
+/// Who killed `entity` and how, for the kill feed. Deaths with nobody directly
+/// to blame go to whoever hurt them last, if that was recent enough.
+fn kill_credit(state: &State, entity: EcsEntity, cause: &HealthSource) -> (Option<Uid>, KillCause) {
+    let (killer, kill_cause) = KillCause::from_health_source(cause);
+    let killer = killer.or_else(|| {
+        state
+            .ecs()
+            .read_storage::<comp::LastHitBy>()
+            .get(entity)
+            .and_then(|last_hit| last_hit.credit(state.ecs().read_resource::<Time>().0))
+    });
+    (killer, kill_cause)
+}
+
 /// Handle an entity dying. If it is a player, it will send a message to all
 /// other players. If the entity that killed it had stats, then give it exp for
 /// the kill. Experience given is equal to the level of the entity that was
@@ -71,6 +180,29 @@ pub fn handle_destroy(server: &mut Server, entity: EcsEntity, cause: HealthSourc
         return;
     }
 
+    // The dead don't bleed
+    state
+        .ecs()
+        .write_storage::<comp::DamageOverTimeEffects>()
+        .remove(entity);
+
+    // Nor keep the buff of the ability they died using
+    let self_buff_changes = match (
+        state
+            .ecs()
+            .read_storage::<comp::CharacterState>()
+            .get(entity),
+        state.ecs().read_storage::<Uid>().get(entity),
+    ) {
+        (Some(character_state), Some(uid)) => {
+            character_state.self_buff_changes(&comp::CharacterState::default(), *uid)
+        },
+        _ => Vec::new(),
+    };
+    for buff_change in self_buff_changes {
+        change_buffs(state, entity, buff_change);
+    }
+
     // Chat message
     // If it was a player that died
     if let Some(_player) = state.ecs().read_storage::<Player>().get(entity) {
@@ -188,6 +320,7 @@ pub fn handle_destroy(server: &mut Server, entity: EcsEntity, cause: HealthSourc
                     }
                 },
                 HealthSource::World => KillSource::FallDamage,
+                HealthSource::Trap => KillSource::Trap,
                 HealthSource::Suicide => KillSource::Suicide,
                 HealthSource::Projectile { owner: None }
                 | HealthSource::Explosion { owner: None }
@@ -203,6 +336,13 @@ pub fn handle_destroy(server: &mut Server, entity: EcsEntity, cause: HealthSourc
             state.notify_registered_clients(
                 comp::ChatType::Kill(kill_source, *uid).server_msg("".to_string()),
             );
+
+            let (killer, kill_cause) = kill_credit(state, entity, &cause);
+            state.notify_registered_clients(ServerGeneral::KillEvent {
+                killer,
+                victim: *uid,
+                cause: kill_cause,
+            });
         }
     }
 
@@ -328,9 +468,17 @@ pub fn handle_destroy(server: &mut Server, entity: EcsEntity, cause: HealthSourc
 
         // Decide for a loot drop before turning into a lootbag
         let old_body = state.ecs().write_storage::<Body>().remove(entity);
+        let pos = state.ecs().read_storage::<comp::Pos>().get(entity).cloned();
+        // Creatures also drop things found in the biome they died in
+        let biome = pos.and_then(|pos| {
+            let terrain = state.terrain();
+            terrain
+                .get_key(terrain.pos_key(pos.0.map(|e| e.floor() as i32)))
+                .map(|chunk| chunk.meta().biome())
+        });
         let mut rng = rand::thread_rng();
         let mut lottery = || {
-            Lottery::<String>::load_expect(match old_body {
+            let base = Lottery::<String>::load_expect(match old_body {
                 Some(common::comp::Body::Humanoid(_)) => match rng.gen_range(0, 4) {
                     0 => "common.loot_tables.loot_table_humanoids",
                     1 => "common.loot_tables.loot_table_armor_light",
@@ -406,7 +554,11 @@ pub fn handle_destroy(server: &mut Server, entity: EcsEntity, cause: HealthSourc
                     _ => "common.loot_tables.loot_table",
                 },
                 _ => "common.loot_tables.loot_table",
-            })
+            });
+            match biome {
+                Some(biome) => biome.loot_table(&base),
+                None => Lottery::clone(&base),
+            }
         };
 
         let item = {
@@ -417,7 +569,6 @@ pub fn handle_destroy(server: &mut Server, entity: EcsEntity, cause: HealthSourc
             )
         };
 
-        let pos = state.ecs().read_storage::<comp::Pos>().get(entity).cloned();
         if let Some(pos) = pos {
             let _ = state
                 .create_object(
@@ -451,17 +602,43 @@ pub fn handle_destroy(server: &mut Server, entity: EcsEntity, cause: HealthSourc
     */
 }
 
+pub fn handle_despawn_projectile(
+    server: &mut Server,
+    entity: EcsEntity,
+    reason: comp::DespawnReason,
+) {
+    let state = server.state_mut();
+
+    // Several effects may ask for the same projectile to be despawned in one tick
+    if !state.ecs().is_alive(entity) {
+        return;
+    }
+
+    if let Some(pos) = state.ecs().read_storage::<Pos>().get(entity) {
+        state
+            .ecs()
+            .write_resource::<Vec<Outcome>>()
+            .push(Outcome::ProjectileDespawned { pos: pos.0, reason });
+    }
+
+    if let Err(e) = state.delete_entity_recorded(entity) {
+        error!(?e, ?entity, "Failed to delete despawned projectile");
+    }
+}
+
 pub fn handle_land_on_ground(server: &Server, entity: EcsEntity, vel: Vec3<f32>) {
     let state = &server.state;
-    if vel.z <= -30.0 {
+    let gameplay_settings = *state.ecs().read_resource::<GameplaySettings>();
+    if vel.z <= -gameplay_settings.fall_damage_speed {
         if let Some(stats) = state.ecs().write_storage::<comp::Stats>().get_mut(entity) {
-            let falldmg = (vel.z.powi(2) / 20.0 - 40.0) * 10.0;
+            let falldmg = gameplay_settings.fall_damage(-vel.z);
             let mut damage = Damage {
                 healthchange: -falldmg,
                 source: DamageSource::Falling,
+                kind: DamageKind::Physical,
             };
             if let Some(loadout) = state.ecs().read_storage::<comp::Loadout>().get(entity) {
-                damage.modify_damage(false, loadout);
+                damage.modify_damage(None, loadout);
             }
             stats.health.change_by(comp::HealthChange {
                 amount: damage.healthchange as i32,
@@ -491,6 +668,15 @@ pub fn handle_respawn(server: &Server, entity: EcsEntity) {
             .write_storage::<comp::Stats>()
             .get_mut(entity)
             .map(|stats| stats.revive());
+        state
+            .ecs()
+            .write_storage::<comp::DamageOverTimeEffects>()
+            .remove(entity);
+        // Whoever hurt them before they died doesn't get credit for their next death
+        state
+            .ecs()
+            .write_storage::<comp::LastHitBy>()
+            .remove(entity);
         state
             .ecs()
             .write_storage::<comp::Pos>()
@@ -510,6 +696,25 @@ pub fn handle_respawn(server: &Server, entity: EcsEntity) {
     }
 }
 
+/// The colour, strength and duration of the flash of light an explosion of
+/// `power` gives off.
+fn explosion_flash(power: f32, reagent: Option<Reagent>) -> (Rgb<f32>, f32, Duration) {
+    let color = match reagent {
+        Some(Reagent::Blue) => Rgb::new(0.15, 0.4, 1.0),
+        Some(Reagent::Green) => Rgb::new(0.0, 1.0, 0.0),
+        Some(Reagent::Purple) => Rgb::new(0.7, 0.0, 1.0),
+        Some(Reagent::Red) => Rgb::new(1.0, 0.0, 0.0),
+        Some(Reagent::Yellow) => Rgb::new(1.0, 1.0, 0.0),
+        // Healing explosions have negative power
+        None if power < 0.0 => Rgb::new(0.0, 1.0, 0.0),
+        None => Rgb::new(1.0, 0.5, 0.0),
+    };
+    match reagent {
+        Some(_) => (color, power.abs() * 5.0, Duration::from_secs(1)),
+        None => (color, power.abs() * 2.5, Duration::from_millis(500)),
+    }
+}
+
 pub fn handle_explosion(
     server: &Server,
     pos: Vec3<f32>,
@@ -528,15 +733,38 @@ pub fn handle_explosion(
             .terrain_destruction_power
             .max(explosion.max_damage as f32 / 100.0)
     };
+    let outcome = Outcome::Explosion {
+        pos,
+        power: outcome_power,
+        radius: explosion.radius,
+        is_attack: explosion.max_heal > 0 || explosion.max_damage > 0,
+        reagent,
+    };
+    // Light up the surroundings for everybody close enough to see the explosion
+    let (color, strength, duration) = explosion_flash(outcome_power, reagent);
+    for (client, player, client_pos) in (
+        &mut ecs.write_storage::<Client>(),
+        &ecs.read_storage::<Player>(),
+        ecs.read_storage::<Pos>().maybe(),
+    )
+        .join()
+    {
+        if client_pos
+            .zip(player.view_distance)
+            .map_or(true, |(client_pos, vd)| {
+                outcome.is_visible_from(client_pos.0, vd)
+            })
+        {
+            client.send_msg(ServerGeneral::LightFlash {
+                pos,
+                color,
+                strength,
+                duration,
+            });
+        }
+    }
     // Add an outcome
-    ecs.write_resource::<Vec<Outcome>>()
-        .push(Outcome::Explosion {
-            pos,
-            power: outcome_power,
-            radius: explosion.radius,
-            is_attack: explosion.max_heal > 0 || explosion.max_damage > 0,
-            reagent,
-        });
+    ecs.write_resource::<Vec<Outcome>>().push(outcome);
     let owner_entity = owner.and_then(|uid| {
         ecs.read_resource::<UidAllocator>()
             .retrieve_entity_internal(uid.into())
@@ -594,10 +822,12 @@ pub fn handle_explosion(
             let mut damage = Damage {
                 healthchange,
                 source,
+                kind: DamageKind::Physical,
             };
 
-            let block = character_b.map(|c_b| c_b.is_block()).unwrap_or(false)
-                && ori_b.0.angle_between(pos - pos_b.0) < BLOCK_ANGLE.to_radians() / 2.0;
+            let block = character_b
+                .and_then(|c_b| c_b.block_strength())
+                .filter(|_| ori_b.0.angle_between(pos - pos_b.0) < BLOCK_ANGLE.to_radians() / 2.0);
 
             if let Some(loadout) = loadout_b {
                 damage.modify_damage(block, loadout);
@@ -699,8 +929,32 @@ pub fn handle_level_up(server: &mut Server, entity: EcsEntity, new_level: u32) {
         ));
 }
 
-pub fn handle_buff(server: &mut Server, entity: EcsEntity, buff_change: buff::BuffChange) {
+pub fn handle_damage_over_time(
+    server: &mut Server,
+    entity: EcsEntity,
+    dot: comp::DamageOverTime,
+    by: Uid,
+) {
     let ecs = &server.state.ecs();
+    if ecs
+        .read_storage::<Stats>()
+        .get(entity)
+        .map_or(false, |stats| stats.is_dead)
+    {
+        return;
+    }
+    let mut dots = ecs.write_storage::<comp::DamageOverTimeEffects>();
+    if let Ok(entry) = dots.entry(entity) {
+        entry.or_insert_with(Default::default).apply(dot, by);
+    }
+}
+
+pub fn handle_buff(server: &mut Server, entity: EcsEntity, buff_change: buff::BuffChange) {
+    change_buffs(&server.state, entity, buff_change)
+}
+
+fn change_buffs(state: &State, entity: EcsEntity, buff_change: buff::BuffChange) {
+    let ecs = state.ecs();
     let mut buffs_all = ecs.write_storage::<comp::Buffs>();
     if let Some(buffs) = buffs_all.get_mut(entity) {
         use buff::BuffChange;
@@ -760,3 +1014,105 @@ pub fn handle_buff(server: &mut Server, entity: EcsEntity, buff_change: buff::Bu
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::{comp::buff::BuffKind, states};
+    use specs::Builder;
+
+    #[test]
+    fn recent_attacker_credited_for_kill() {
+        let mut state = State::default();
+        let victim = state
+            .ecs_mut()
+            .create_entity_synced()
+            .with(comp::LastHitBy {
+                by: Uid(7),
+                time: 100.0,
+            })
+            .build();
+        state.ecs_mut().write_resource::<Time>().0 = 105.0;
+
+        // Knocked off a ledge
+        assert_eq!(
+            kill_credit(&state, victim, &HealthSource::World),
+            (Some(Uid(7)), KillCause::Fall)
+        );
+        // Whoever landed the killing blow keeps the credit
+        assert_eq!(
+            kill_credit(&state, victim, &HealthSource::Attack { by: Uid(3) }),
+            (Some(Uid(3)), KillCause::Melee)
+        );
+
+        // Fell long after the fight
+        state.ecs_mut().write_resource::<Time>().0 = 100.0 + comp::KILL_CREDIT_WINDOW + 1.0;
+        assert_eq!(
+            kill_credit(&state, victim, &HealthSource::World),
+            (None, KillCause::Fall)
+        );
+    }
+
+    #[test]
+    fn cancelled_spin_stops_buffing() {
+        let spin = comp::CharacterState::SpinMelee(states::spin_melee::Data {
+            static_data: states::spin_melee::StaticData {
+                buildup_duration: Duration::from_millis(500),
+                swing_duration: Duration::from_millis(400),
+                recover_duration: Duration::from_millis(500),
+                base_damage: 60,
+                knockback: comp::Knockback::default(),
+                range: 3.5,
+                vertical_range: 1.5,
+                energy_cost: 150,
+                is_infinite: false,
+                is_helicopter: false,
+                is_interruptible: true,
+                forward_speed: 1.0,
+                num_spins: 1,
+                max_targets: None,
+                self_buff: Some((BuffKind::Swiftness, 0.3, Duration::from_secs(3))),
+                damage_kind: DamageKind::Physical,
+            },
+            timer: Duration::default(),
+            spins_remaining: 0,
+            stage_section: states::utils::StageSection::Buildup,
+            exhausted: false,
+        });
+        let mut state = State::default();
+        let spinner = state
+            .ecs_mut()
+            .create_entity_synced()
+            .with(spin.clone())
+            .with(comp::Energy::new(1000))
+            .with(comp::Buffs::default())
+            .build();
+        let uid = *state.ecs().read_storage::<Uid>().get(spinner).unwrap();
+        for buff_change in comp::CharacterState::Wielding.self_buff_changes(&spin, uid) {
+            change_buffs(&state, spinner, buff_change);
+        }
+        assert_eq!(
+            state
+                .ecs()
+                .read_storage::<comp::Buffs>()
+                .get(spinner)
+                .map(|buffs| buffs.buffs.len()),
+            Some(1)
+        );
+
+        cancel_ability(&state, spinner);
+
+        assert_eq!(
+            state
+                .ecs()
+                .read_storage::<comp::CharacterState>()
+                .get(spinner),
+            Some(&comp::CharacterState::Wielding)
+        );
+        assert!(state
+            .ecs()
+            .read_storage::<comp::Buffs>()
+            .get(spinner)
+            .map_or(false, |buffs| buffs.buffs.is_empty()));
+    }
+}
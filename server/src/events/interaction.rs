@@ -5,10 +5,14 @@ use crate::{
 use common::{
     comp::{self, item},
     msg::ServerGeneral,
+    state::State,
+    states::place_block::place_block,
     sync::{Uid, WorldSyncExt},
+    terrain::Block,
 };
 use specs::{world::WorldExt, Entity as EcsEntity};
-use tracing::error;
+use tracing::{debug, error};
+use vek::Vec3;
 
 pub fn handle_lantern(server: &mut Server, entity: EcsEntity, enable: bool) {
     let ecs = server.state_mut().ecs();
@@ -45,6 +49,7 @@ pub fn handle_lantern(server: &mut Server, entity: EcsEntity, enable: bool) {
                             strength: lantern.strength(),
                             flicker: 0.35,
                             animated: true,
+                            periodic_flicker: None,
                         });
             }
         }
@@ -193,3 +198,94 @@ pub fn handle_possess(server: &Server, possessor_uid: Uid, possesse_uid: Uid) {
         }
     }
 }
+
+/// Tells the speaker which response a player picked. NPCs don't react to
+/// answers yet, so only players showing dialogues with `/dialogue` hear back.
+pub fn handle_dialogue_response(
+    server: &mut Server,
+    entity: EcsEntity,
+    speaker: Uid,
+    response: String,
+) {
+    let ecs = server.state.ecs();
+    let alias = match ecs.read_storage::<comp::Player>().get(entity) {
+        Some(player) => player.alias.clone(),
+        None => return,
+    };
+    let mut clients = ecs.write_storage::<Client>();
+    match ecs
+        .entity_from_uid(speaker.into())
+        .and_then(|speaker| clients.get_mut(speaker))
+    {
+        Some(client) => client.send_msg(
+            comp::ChatType::CommandInfo.server_msg(format!("{} answered: {}", alias, response)),
+        ),
+        None => debug!(?entity, ?speaker, "dialogue answered to no player"),
+    }
+}
+
+pub fn handle_place_block(server: &mut Server, entity: EcsEntity, pos: Vec3<i32>, block: Block) {
+    try_place_block(server.state_mut(), entity, pos, block);
+}
+
+/// Places `block` at `pos` on behalf of `entity`, if it's allowed to build
+fn try_place_block(state: &mut State, entity: EcsEntity, pos: Vec3<i32>, block: Block) {
+    if !state
+        .ecs()
+        .read_storage::<comp::CanBuild>()
+        .contains(entity)
+    {
+        debug!(
+            ?entity,
+            ?pos,
+            "tried to place block without permission to build"
+        );
+        return;
+    }
+
+    // Unloaded chunks, occupied positions and blocks that are already being
+    // changed this tick are left alone
+    match state
+        .get_block(pos)
+        .and_then(|target| place_block(target, block))
+    {
+        Some(block) if state.can_set_block(pos) => state.set_block(pos, block),
+        _ => debug!(?entity, ?pos, "couldn't place block"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::terrain::{BlockKind, SpriteKind, TerrainChunk, TerrainChunkMeta};
+    use specs::Builder;
+    use std::sync::Arc;
+    use vek::{Rgb, Vec2};
+
+    #[test]
+    fn placing_blocks_needs_permission_to_build() {
+        let mut state = State::default();
+        state.terrain_mut().insert(
+            Vec2::zero(),
+            Arc::new(TerrainChunk::new(
+                0,
+                Block::new(BlockKind::Rock, Rgb::zero()),
+                Block::air(SpriteKind::Empty),
+                TerrainChunkMeta::void(),
+            )),
+        );
+        let builder = state.ecs_mut().create_entity().with(comp::CanBuild).build();
+        let visitor = state.ecs_mut().create_entity().build();
+        let stone = Block::new(BlockKind::Rock, Rgb::new(128, 128, 128));
+
+        try_place_block(&mut state, visitor, Vec3::new(1, 1, 1), stone);
+        try_place_block(&mut state, builder, Vec3::new(2, 2, 1), stone);
+        state.apply_terrain_changes();
+
+        assert_eq!(
+            state.get_block(Vec3::new(1, 1, 1)),
+            Some(Block::air(SpriteKind::Empty))
+        );
+        assert_eq!(state.get_block(Vec3::new(2, 2, 1)), Some(stone));
+    }
+}
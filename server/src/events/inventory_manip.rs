@@ -7,6 +7,8 @@ use common::{
     },
     msg::ServerGeneral,
     recipe::default_recipe_book,
+    state::State,
+    states::harvest::harvest_block,
     sync::{Uid, WorldSyncExt},
     vol::ReadVol,
 };
@@ -31,6 +33,31 @@ pub fn snuff_lantern(storage: &mut WriteStorage<comp::LightEmitter>, entity: Ecs
     storage.remove(entity);
 }
 
+/// Use up the consumable in an inventory slot, applying its effect. Slots that
+/// are empty or hold anything else are left alone.
+fn consume(state: &mut State, entity: EcsEntity, slot: usize) {
+    let consumed = state
+        .ecs()
+        .write_storage::<comp::Inventory>()
+        .get_mut(entity)
+        .filter(|inventory| inventory.get_consumable(slot).is_some())
+        .and_then(|inventory| inventory.take(slot));
+    match consumed.as_ref().map(|item| item.kind()) {
+        Some(item::ItemKind::Consumable { kind, effect, .. }) => {
+            state.apply_effect(entity, *effect);
+            state.write_component(
+                entity,
+                comp::InventoryUpdate::new(comp::InventoryUpdateEvent::Consumed(kind.clone())),
+            );
+        },
+        _ => debug!(
+            ?entity,
+            ?slot,
+            "Ignoring request to consume a non-consumable"
+        ),
+    }
+}
+
 #[allow(clippy::blocks_in_if_conditions)]
 #[allow(clippy::same_item_push)] // TODO: Pending review in #587
 pub fn handle_inventory(server: &mut Server, entity: EcsEntity, manip: comp::InventoryManip) {
@@ -66,10 +93,11 @@ pub fn handle_inventory(server: &mut Server, entity: EcsEntity, manip: comp::Inv
                     return;
                 };
 
-                // Attempt to add the item to the player's inventory
-                match inv.push(item) {
-                    None => Some(item_entity),
-                    Some(_) => None, // Inventory was full
+                // Attempt to add the item to the player's inventory. If it's full, the
+                // item entity is simply left where it is on the ground.
+                match inv.try_push(item) {
+                    Ok(()) => Some(item_entity),
+                    Err(_) => None,
                 }
             } else {
                 // Item entity/component could not be found - most likely because the player
@@ -100,39 +128,43 @@ pub fn handle_inventory(server: &mut Server, entity: EcsEntity, manip: comp::Inv
 
             if let Some(block) = block {
                 if block.is_collectible() && state.can_set_block(pos) {
-                    if let Some(item) = comp::Item::try_reclaim_from_block(block) {
-                        let (event, item_was_added) = if let Some(inv) = state
+                    if let Some((item, remaining)) = harvest_block(block) {
+                        let event = if let Some(inv) = state
                             .ecs()
                             .write_storage::<comp::Inventory>()
                             .get_mut(entity)
                         {
-                            match inv.push(item.clone()) {
-                                None => (
-                                    Some(comp::InventoryUpdate::new(
-                                        comp::InventoryUpdateEvent::Collected(item),
-                                    )),
-                                    true,
-                                ),
-                                Some(_) => (
+                            match inv.try_push(item.clone()) {
+                                Ok(()) => Some(comp::InventoryUpdate::new(
+                                    comp::InventoryUpdateEvent::Collected(item),
+                                )),
+                                // The inventory is full, so the harvested item falls to the
+                                // ground instead of being lost
+                                Err(mut item) => {
+                                    item.put_in_world();
+                                    dropped_items.push((
+                                        comp::Pos(pos.map(|e| e as f32) + Vec3::new(0.5, 0.5, 0.0)),
+                                        state
+                                            .read_component_copied::<comp::Ori>(entity)
+                                            .unwrap_or_default(),
+                                        item,
+                                    ));
                                     Some(comp::InventoryUpdate::new(
                                         comp::InventoryUpdateEvent::CollectFailed,
-                                    )),
-                                    false,
-                                ),
+                                    ))
+                                },
                             }
                         } else {
                             debug!(
                                 "Can't add item to inventory: entity has no inventory ({:?})",
                                 entity
                             );
-                            (None, false)
+                            None
                         };
                         if let Some(event) = event {
                             state.write_component(entity, event);
-                            if item_was_added {
-                                // we made sure earlier the block was not already modified this tick
-                                state.set_block(pos, block.into_vacant())
-                            };
+                            // we made sure earlier the block was not already modified this tick
+                            state.set_block(pos, remaining);
                         }
                     } else {
                         debug!(
@@ -421,6 +453,8 @@ pub fn handle_inventory(server: &mut Server, entity: EcsEntity, manip: comp::Inv
                 }
             }
         },
+
+        comp::InventoryManip::Consume(slot) => consume(state, entity, slot),
     }
 
     // Drop items
@@ -487,6 +521,7 @@ pub fn handle_inventory(server: &mut Server, entity: EcsEntity, manip: comp::Inv
                         flicker: 2.0,
                         strength: 2.0,
                         col: Rgb::new(1.0, 1.0, 0.0),
+                        periodic_flicker: None,
                     });
             },
             item::Throwable::TrainingDummy => {
@@ -511,9 +546,90 @@ fn within_pickup_range(player_position: Option<&Pos>, item_position: Option<&Pos
 #[cfg(test)]
 mod tests {
     use super::*;
-    use common::comp::Pos;
+    use common::comp::{HealthChange, HealthSource, Pos};
     use vek::Vec3;
 
+    /// Creates an entity, hurt by 600 health, holding a minor potion in its
+    /// first inventory slot
+    fn hurt_with_potion(state: &mut State) -> EcsEntity {
+        let mut inventory = comp::Inventory::new_empty();
+        inventory.push(comp::Item::new_from_asset_expect(
+            "common.items.consumable.potion_minor",
+        ));
+        let mut stats = comp::Stats::new(
+            "Drinker".to_string(),
+            comp::Body::Humanoid(comp::humanoid::Body::random()),
+        );
+        stats.health.change_by(HealthChange {
+            amount: -600,
+            cause: HealthSource::Unknown,
+        });
+        state
+            .ecs_mut()
+            .create_entity_synced()
+            .with(inventory)
+            .with(stats)
+            .build()
+    }
+
+    fn health(state: &State, entity: EcsEntity) -> u32 {
+        state
+            .ecs()
+            .read_storage::<comp::Stats>()
+            .get(entity)
+            .unwrap()
+            .health
+            .current()
+    }
+
+    #[test]
+    fn consuming_a_potion_heals() {
+        let mut state = State::default();
+        let drinker = hurt_with_potion(&mut state);
+        let before = health(&state, drinker);
+
+        consume(&mut state, drinker, 0);
+
+        assert!(health(&state, drinker) > before);
+        assert!(state
+            .ecs()
+            .read_storage::<comp::Inventory>()
+            .get(drinker)
+            .unwrap()
+            .get(0)
+            .is_none());
+        assert!(matches!(
+            state
+                .ecs()
+                .read_storage::<comp::InventoryUpdate>()
+                .get(drinker)
+                .map(|update| update.event()),
+            Some(comp::InventoryUpdateEvent::Consumed(_))
+        ));
+    }
+
+    #[test]
+    fn consuming_an_empty_slot_does_nothing() {
+        let mut state = State::default();
+        let drinker = hurt_with_potion(&mut state);
+        let before = health(&state, drinker);
+
+        consume(&mut state, drinker, 1);
+
+        assert_eq!(health(&state, drinker), before);
+        assert!(state
+            .ecs()
+            .read_storage::<comp::Inventory>()
+            .get(drinker)
+            .unwrap()
+            .get(0)
+            .is_some());
+        assert!(!state
+            .ecs()
+            .read_storage::<comp::InventoryUpdate>()
+            .contains(drinker));
+    }
+
     #[test]
     fn pickup_distance_within_range() {
         let player_position = Pos(Vec3::zero());
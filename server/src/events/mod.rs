@@ -8,13 +8,17 @@ use entity_creation::{
     handle_loaded_character_data, handle_shockwave, handle_shoot,
 };
 use entity_manipulation::{
-    handle_buff, handle_damage, handle_destroy, handle_explosion, handle_knockback,
+    handle_buff, handle_cancel_ability, handle_damage, handle_damage_over_time,
+    handle_despawn_projectile, handle_destroy, handle_explosion, handle_knockback,
     handle_land_on_ground, handle_level_up, handle_respawn,
 };
 use group_manip::handle_group;
-use interaction::{handle_lantern, handle_mount, handle_possess, handle_unmount};
+use interaction::{
+    handle_dialogue_response, handle_lantern, handle_mount, handle_place_block, handle_possess,
+    handle_unmount,
+};
 use inventory_manip::handle_inventory;
-use player::{handle_client_disconnect, handle_exit_ingame};
+use player::{handle_client_disconnect, handle_exit_ingame, handle_set_hotbar};
 use specs::{Entity as EcsEntity, WorldExt};
 
 mod entity_creation;
@@ -83,8 +87,12 @@ impl Server {
                 ServerEvent::Knockback { entity, impulse } => {
                     handle_knockback(&self, entity, impulse)
                 },
+                ServerEvent::CancelAbility(entity) => handle_cancel_ability(&self, entity),
                 ServerEvent::Damage { uid, change } => handle_damage(&self, uid, change),
                 ServerEvent::Destroy { entity, cause } => handle_destroy(self, entity, cause),
+                ServerEvent::DespawnProjectile { entity, reason } => {
+                    handle_despawn_projectile(self, entity, reason)
+                },
                 ServerEvent::InventoryManip(entity, manip) => handle_inventory(self, entity, manip),
                 ServerEvent::GroupManip(entity, manip) => handle_group(self, entity, manip),
                 ServerEvent::Respawn(entity) => handle_respawn(&self, entity),
@@ -107,6 +115,9 @@ impl Server {
                 },
                 ServerEvent::LevelUp(entity, new_level) => handle_level_up(self, entity, new_level),
                 ServerEvent::ExitIngame { entity } => handle_exit_ingame(self, entity),
+                ServerEvent::SetHotbar { entity, layout } => {
+                    handle_set_hotbar(self, entity, layout)
+                },
                 ServerEvent::CreateNpc {
                     pos,
                     stats,
@@ -137,6 +148,17 @@ impl Server {
                     entity,
                     buff_change,
                 } => handle_buff(self, entity, buff_change),
+                ServerEvent::DamageOverTime { entity, dot, by } => {
+                    handle_damage_over_time(self, entity, dot, by)
+                },
+                ServerEvent::DialogueResponse {
+                    entity,
+                    speaker,
+                    response,
+                } => handle_dialogue_response(self, entity, speaker, response),
+                ServerEvent::PlaceBlock { entity, pos, block } => {
+                    handle_place_block(self, entity, pos, block)
+                },
             }
         }
 
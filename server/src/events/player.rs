@@ -3,6 +3,7 @@ use crate::{
     client::Client, login_provider::LoginProvider, persistence, state_ext::StateExt, Server,
 };
 use common::{
+    character::{HotbarSlot, MAX_HOTBAR_SLOTS},
     comp,
     comp::{group, Player},
     msg::{PlayerListUpdate, ServerGeneral},
@@ -141,14 +142,14 @@ pub fn handle_client_disconnect(server: &mut Server, entity: EcsEntity) -> Event
     }
 
     // Sync the player's character data to the database
-    if let (Some(player), Some(stats), Some(inventory), Some(loadout), updater) = (
+    if let (Some(player), Some(stats), Some(inventory), Some(loadout), mut updater) = (
         state.read_storage::<Player>().get(entity),
         state.read_storage::<comp::Stats>().get(entity),
         state.read_storage::<comp::Inventory>().get(entity),
         state.read_storage::<comp::Loadout>().get(entity),
         state
             .ecs()
-            .read_resource::<persistence::character_updater::CharacterUpdater>(),
+            .write_resource::<persistence::character_updater::CharacterUpdater>(),
     ) {
         if let Some(character_id) = player.character_id {
             updater.update(character_id, stats, inventory, loadout);
@@ -162,3 +163,28 @@ pub fn handle_client_disconnect(server: &mut Server, entity: EcsEntity) -> Event
 
     Event::ClientDisconnected { entity }
 }
+
+pub fn handle_set_hotbar(
+    server: &mut Server,
+    entity: EcsEntity,
+    mut layout: Vec<Option<HotbarSlot>>,
+) {
+    let state = server.state();
+
+    if let Some(character_id) = state
+        .read_storage::<Player>()
+        .get(entity)
+        .and_then(|player| player.character_id)
+    {
+        layout.truncate(MAX_HOTBAR_SLOTS);
+        state
+            .ecs()
+            .write_resource::<persistence::character_updater::CharacterUpdater>()
+            .update_hotbar(character_id, layout);
+    } else {
+        debug!(
+            ?entity,
+            "Ignoring hotbar layout for entity without a character"
+        );
+    }
+}
@@ -16,6 +16,7 @@ pub mod events;
 pub mod input;
 pub mod login_provider;
 pub mod metrics;
+pub mod movement_validator;
 pub mod persistence;
 pub mod settings;
 pub mod state_ext;
@@ -39,6 +40,7 @@ use crate::{
     connection_handler::ConnectionHandler,
     data_dir::DataDir,
     login_provider::LoginProvider,
+    movement_validator::MovementValidator,
     state_ext::StateExt,
     sys::sentinel::{DeletedEntities, TrackedComps},
 };
@@ -72,7 +74,7 @@ use std::{
 };
 #[cfg(not(feature = "worldgen"))]
 use test_world::{IndexOwned, World};
-use tracing::{debug, error, info, trace};
+use tracing::{debug, error, info, trace, warn};
 use uvth::{ThreadPool, ThreadPoolBuilder};
 use vek::*;
 #[cfg(feature = "worldgen")]
@@ -160,6 +162,8 @@ impl Server {
             .ecs_mut()
             .insert(CharacterLoader::new(&persistence_db_dir)?);
         state.ecs_mut().insert(Vec::<Outcome>::new());
+        state.ecs_mut().insert(sys::trap::TrapTriggers::default());
+        state.ecs_mut().insert(sys::terrain::ViewDistanceThrottle::default());
 
         // System timers for performance monitoring
         state.ecs_mut().insert(sys::EntitySyncTimer::default());
@@ -171,11 +175,24 @@ impl Server {
         state.ecs_mut().insert(sys::WaypointTimer::default());
         state.ecs_mut().insert(sys::InviteTimeoutTimer::default());
         state.ecs_mut().insert(sys::PersistenceTimer::default());
+        state.ecs_mut().insert(sys::GroupVitalsTimer::default());
 
         // System schedulers to control execution of systems
         state
             .ecs_mut()
             .insert(sys::PersistenceScheduler::every(Duration::from_secs(10)));
+        state
+            .ecs_mut()
+            .insert(sys::GroupVitalsScheduler::every(Duration::from_millis(500)));
+        state
+            .ecs_mut()
+            .insert(sys::GrowthScheduler::every(Duration::from_secs(5)));
+        state
+            .ecs_mut()
+            .insert(sys::MusicMoodScheduler::every(Duration::from_secs(1)));
+        state
+            .ecs_mut()
+            .insert(sys::ThreatScheduler::every(Duration::from_millis(500)));
 
         // Server-only components
         state.ecs_mut().register::<RegionSubscription>();
@@ -211,6 +228,12 @@ impl Server {
         tracing::debug!(?banned_words_count);
         tracing::trace!(?banned_words);
         state.ecs_mut().insert(AliasValidator::new(banned_words));
+        state
+            .ecs_mut()
+            .insert(MovementValidator::from_gameplay_settings(
+                &settings.gameplay,
+            ));
+        state.ecs_mut().insert(settings.gameplay);
 
         #[cfg(feature = "worldgen")]
         let (world, index) = World::generate(settings.world_seed, WorldOpts {
@@ -232,7 +255,10 @@ impl Server {
         let map = WorldMapMsg {
             dimensions_lg: Vec2::zero(),
             max_height: 1.0,
-            rgba: vec![0],
+            rgba: WorldMapMsg::encode_rgba(
+                common::terrain::MapSizeLg::new(Vec2::zero()).unwrap(),
+                &[0],
+            ),
             horizons: [(vec![0], vec![0]), (vec![0], vec![0])],
             sea_level: 0.0,
             alt: vec![30],
@@ -560,6 +586,15 @@ impl Server {
                         .read_resource::<EventBus<ServerEvent>>()
                         .emit_now(message);
                 },
+                CharacterLoaderResponseType::Hotbar(result) => match result {
+                    Ok(Some(layout)) => {
+                        self.notify_client(query_result.entity, ServerGeneral::Hotbar(layout))
+                    },
+                    // Keep whatever layout the client has until one is saved
+                    Ok(None) => {},
+                    // A broken layout shouldn't keep the character from playing
+                    Err(error) => warn!(?error, "Failed to load hotbar layout"),
+                },
             });
 
         {
@@ -631,8 +666,13 @@ impl Server {
             .ecs()
             .read_resource::<sys::PersistenceTimer>()
             .nanos as i64;
+        let group_vitals_nanos = self
+            .state
+            .ecs()
+            .read_resource::<sys::GroupVitalsTimer>()
+            .nanos as i64;
         let total_sys_ran_in_dispatcher_nanos =
-            terrain_nanos + waypoint_nanos + invite_timeout_nanos;
+            terrain_nanos + waypoint_nanos + invite_timeout_nanos + group_vitals_nanos;
 
         // Report timing info
         self.tick_metrics
@@ -698,6 +738,10 @@ impl Server {
             .tick_time
             .with_label_values(&["invite timeout"])
             .set(invite_timeout_nanos);
+        self.tick_metrics
+            .tick_time
+            .with_label_values(&["group vitals"])
+            .set(group_vitals_nanos);
         self.tick_metrics
             .tick_time
             .with_label_values(&["persistence:stats"])
@@ -847,6 +891,7 @@ impl Server {
                     client_timeout: self.settings().client_timeout,
                     world_map: self.map.clone(),
                     recipe_book: (&*default_recipe_book()).clone(),
+                    gameplay_settings: self.settings().gameplay,
                 })?;
 
             frontend_events.push(Event::ClientConnected { entity });
@@ -914,6 +959,25 @@ impl Server {
         self.state.ecs().read_storage::<Client>().join().count() as i64
     }
 
+    /// Give the chunk at `key` a custom name, or clear it with `None`. The
+    /// name is saved so that it survives restarts, and chunks that aren't
+    /// loaded yet pick it up once they are generated.
+    pub fn set_chunk_name(&mut self, key: Vec2<i32>, name: Option<String>) {
+        use crate::settings::EditableSetting;
+        {
+            let data_dir = self.data_dir();
+            self.editable_settings_mut()
+                .chunk_names
+                .edit(&data_dir.path, |names| match &name {
+                    Some(name) => names.insert(key, name.clone()),
+                    None => names.remove(&key),
+                });
+        }
+        self.state.set_chunk_name(key, name.clone());
+        self.state
+            .notify_in_game_clients(ServerGeneral::ChunkNamed { key, name });
+    }
+
     // TODO: add Admin comp if ingame
     pub fn add_admin(&self, username: &str) {
         let mut editable_settings = self.editable_settings_mut();
@@ -0,0 +1,9 @@
+-- Stores the hotbar layout of each character as a JSON array of slots
+
+CREATE TABLE hotbar
+(
+    character_id INTEGER NOT NULL
+        PRIMARY KEY
+        REFERENCES character(character_id),
+    layout       TEXT NOT NULL
+);
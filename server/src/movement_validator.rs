@@ -0,0 +1,129 @@
+use common::gameplay::GameplaySettings;
+use std::fmt::{self, Display};
+use vek::*;
+
+/// How far past the fastest speed an entity can fall or move at knockbacks and
+/// boosts can briefly push it, in blocks per second
+const BOOST_SPEED: f32 = 20.0;
+
+/// Checks positions reported by clients against how far an entity could
+/// possibly have moved since its last report, to catch teleport hacks.
+#[derive(Debug)]
+pub struct MovementValidator {
+    /// Fastest speed any entity can legitimately reach, in blocks per second
+    max_speed: f32,
+    /// Extra distance allowed on top of `max_speed`, to absorb network
+    /// jitter and reports that arrive in the same tick
+    tolerance: f32,
+}
+
+impl MovementValidator {
+    pub fn new(max_speed: f32, tolerance: f32) -> Self {
+        MovementValidator {
+            max_speed,
+            tolerance,
+        }
+    }
+
+    /// A validator allowing for the terminal falling speed and movement speed
+    /// the server is configured with
+    pub fn from_gameplay_settings(settings: &GameplaySettings) -> Self {
+        Self::new(
+            settings.max_fall_speed.max(settings.max_move_speed) + BOOST_SPEED,
+            4.0,
+        )
+    }
+
+    /// How far an entity may move in `dt` seconds
+    pub fn max_distance(&self, dt: f64) -> f32 {
+        self.max_speed * dt.max(0.0) as f32 + self.tolerance
+    }
+
+    pub fn validate(&self, old: Vec3<f32>, new: Vec3<f32>, dt: f64) -> Result<(), MovementError> {
+        let distance = old.distance(new);
+        let max_distance = self.max_distance(dt);
+        if distance > max_distance {
+            Err(MovementError::TooFast {
+                distance,
+                max_distance,
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// The position the entity actually ends up at when moving from `old`
+    /// towards `new`, cut short at the furthest it could have gone
+    pub fn clamp(&self, old: Vec3<f32>, new: Vec3<f32>, dt: f64) -> Vec3<f32> {
+        let max_distance = self.max_distance(dt);
+        let delta = new - old;
+        if delta.magnitude_squared() > max_distance.powi(2) {
+            old + delta.normalized() * max_distance
+        } else {
+            new
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum MovementError {
+    TooFast { distance: f32, max_distance: f32 },
+}
+
+impl Display for MovementError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooFast {
+                distance,
+                max_distance,
+            } => write!(
+                formatter,
+                "Moved {:.1} blocks but could only have moved {:.1}",
+                distance, max_distance
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legitimate_move_accepted() {
+        let validator = MovementValidator::from_gameplay_settings(&GameplaySettings::default());
+        let old = Vec3::new(100.0, 100.0, 50.0);
+        // Running for one tick
+        let new = old + Vec3::new(0.3, 0.1, 0.0);
+
+        assert_eq!(validator.validate(old, new, 1.0 / 30.0), Ok(()));
+        assert_eq!(validator.clamp(old, new, 1.0 / 30.0), new);
+    }
+
+    #[test]
+    fn superhuman_jump_clamped() {
+        let validator = MovementValidator::new(100.0, 4.0);
+        let old = Vec3::new(100.0, 100.0, 50.0);
+        let new = old + Vec3::new(500.0, 0.0, 0.0);
+
+        assert!(validator.validate(old, new, 1.0).is_err());
+        let clamped = validator.clamp(old, new, 1.0);
+        assert!((clamped.x - 204.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn faster_falls_allowed_when_configured() {
+        let old = Vec3::new(100.0, 100.0, 500.0);
+        // Falling for a second at a terminal speed of 150 blocks per second
+        let new = old - Vec3::new(0.0, 0.0, 150.0);
+
+        let default = MovementValidator::from_gameplay_settings(&GameplaySettings::default());
+        assert!(default.validate(old, new, 1.0).is_err());
+
+        let validator = MovementValidator::from_gameplay_settings(&GameplaySettings {
+            max_fall_speed: 150.0,
+            ..GameplaySettings::default()
+        });
+        assert_eq!(validator.validate(old, new, 1.0), Ok(()));
+    }
+}
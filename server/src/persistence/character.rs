@@ -12,16 +12,17 @@ use crate::{
     persistence::{
         character::conversions::{
             convert_body_from_database, convert_body_to_database_json,
-            convert_character_from_database, convert_inventory_from_database_items,
+            convert_character_from_database, convert_hotbar_from_database,
+            convert_hotbar_to_database, convert_inventory_from_database_items,
             convert_items_to_database_items, convert_loadout_from_database_items,
             convert_stats_from_database, convert_stats_to_database,
         },
-        character_loader::{CharacterDataResult, CharacterListResult},
+        character_loader::{CharacterDataResult, CharacterListResult, HotbarResult},
         error::Error::DatabaseError,
         PersistedComponents,
     },
 };
-use common::character::{CharacterId, CharacterItem, MAX_CHARACTERS_PER_PLAYER};
+use common::character::{CharacterId, CharacterItem, HotbarSlot, MAX_CHARACTERS_PER_PLAYER};
 use core::ops::Range;
 use diesel::{prelude::*, sql_query, sql_types::BigInt};
 use std::sync::Arc;
@@ -91,6 +92,32 @@ pub fn load_character_data(
     ))
 }
 
+/// Load the hotbar layout saved for a character, if they have ever saved one.
+pub fn load_hotbar(
+    requesting_player_uuid: &str,
+    char_id: CharacterId,
+    connection: VelorenTransaction,
+) -> HotbarResult {
+    use schema::{character::dsl::*, hotbar::dsl::*};
+
+    // Ensures that the requesting player owns the character
+    let _character_data = character
+        .filter(
+            schema::character::dsl::character_id
+                .eq(char_id)
+                .and(player_uuid.eq(requesting_player_uuid)),
+        )
+        .first::<Character>(&*connection)?;
+
+    hotbar
+        .filter(schema::hotbar::dsl::character_id.eq(char_id))
+        .first::<Hotbar>(&*connection)
+        .optional()?
+        // Inventories are always loaded with the default number of slots
+        .map(|saved| convert_hotbar_from_database(&saved, comp::Inventory::new_empty().capacity()))
+        .transpose()
+}
+
 /// Loads a list of characters belonging to the player. This data is a small
 /// subset of the character's data, and is used to render the character and
 /// their level in the character list.
@@ -288,7 +315,7 @@ pub fn delete_character(
     char_id: CharacterId,
     connection: VelorenTransaction,
 ) -> CharacterListResult {
-    use schema::{body::dsl::*, character::dsl::*, stats::dsl::*};
+    use schema::{body::dsl::*, character::dsl::*, hotbar::dsl::*, stats::dsl::*};
 
     // Load the character to delete - ensures that the requesting player
     // owns the character
@@ -300,6 +327,10 @@ pub fn delete_character(
         )
         .first::<Character>(&*connection)?;
 
+    // Delete hotbar, which may not exist if the character never saved one
+    diesel::delete(hotbar.filter(schema::hotbar::dsl::character_id.eq(char_id)))
+        .execute(&*connection)?;
+
     // Delete character
     let character_count = diesel::delete(
         character
@@ -589,3 +620,26 @@ pub fn update(
 
     Ok(upserted_comps)
 }
+
+/// Replaces the hotbar layout saved for a character
+pub fn update_hotbar(
+    char_id: CharacterId,
+    layout: Vec<Option<HotbarSlot>>,
+    connection: VelorenTransaction,
+) -> Result<(), Error> {
+    use super::schema::hotbar::dsl::*;
+
+    let db_hotbar = convert_hotbar_to_database(char_id, &layout)?;
+    let hotbar_count = diesel::replace_into(hotbar)
+        .values(&db_hotbar)
+        .execute(&*connection)?;
+
+    if hotbar_count != 1 {
+        return Err(Error::OtherError(format!(
+            "Error updating hotbar table for char_id {}",
+            char_id
+        )));
+    }
+
+    Ok(())
+}
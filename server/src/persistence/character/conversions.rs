@@ -1,11 +1,11 @@
 use crate::persistence::{
     character::EntityId,
-    models::{Body, Character, Item, Stats},
+    models::{Body, Character, Hotbar, Item, Stats},
 };
 
 use crate::persistence::{error::Error, json_models::HumanoidBody};
 use common::{
-    character::CharacterId,
+    character::{CharacterId, HotbarSlot, MAX_HOTBAR_SLOTS},
     comp::{Body as CompBody, *},
     loadout_builder,
 };
@@ -165,6 +165,16 @@ pub fn convert_body_to_database_json(body: &CompBody) -> Result<String, Error> {
     serde_json::to_string(&json_model).map_err(Error::SerializationError)
 }
 
+pub fn convert_hotbar_to_database(
+    character_id: CharacterId,
+    layout: &[Option<HotbarSlot>],
+) -> Result<Hotbar, Error> {
+    Ok(Hotbar {
+        character_id,
+        layout: serde_json::to_string(layout)?,
+    })
+}
+
 pub fn convert_stats_to_database(character_id: CharacterId, stats: &common::comp::Stats) -> Stats {
     Stats {
         stats_id: character_id,
@@ -305,6 +315,25 @@ pub fn convert_body_from_database(body: &Body) -> Result<CompBody, Error> {
     })
 }
 
+/// Converts a saved hotbar layout, clearing any slots bound to an inventory
+/// slot beyond `inventory_slots`
+pub fn convert_hotbar_from_database(
+    hotbar: &Hotbar,
+    inventory_slots: usize,
+) -> Result<Vec<Option<HotbarSlot>>, Error> {
+    let mut layout = serde_json::de::from_str::<Vec<Option<HotbarSlot>>>(&hotbar.layout)?;
+    layout.truncate(MAX_HOTBAR_SLOTS);
+    for slot in layout.iter_mut() {
+        if let Some(HotbarSlot::Inventory(index)) = slot {
+            if *index >= inventory_slots {
+                *slot = None;
+            }
+        }
+    }
+
+    Ok(layout)
+}
+
 pub fn convert_character_from_database(character: &Character) -> common::character::Character {
     common::character::Character {
         id: Some(character.character_id),
@@ -329,3 +358,47 @@ pub fn convert_stats_from_database(stats: &Stats, alias: String) -> common::comp
 
     new_stats
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hotbar_round_trips_through_database() {
+        let layout = vec![
+            Some(HotbarSlot::Inventory(4)),
+            None,
+            Some(HotbarSlot::Ability3),
+            None,
+            Some(HotbarSlot::Inventory(0)),
+        ];
+
+        let model = convert_hotbar_to_database(7, &layout).unwrap();
+        assert_eq!(model.character_id, 7);
+        assert_eq!(convert_hotbar_from_database(&model, 36).unwrap(), layout);
+    }
+
+    #[test]
+    fn hotbar_drops_missing_inventory_slots_on_load() {
+        let model = convert_hotbar_to_database(7, &[
+            Some(HotbarSlot::Inventory(35)),
+            Some(HotbarSlot::Inventory(36)),
+            Some(HotbarSlot::Ability3),
+        ])
+        .unwrap();
+        assert_eq!(convert_hotbar_from_database(&model, 36).unwrap(), vec![
+            Some(HotbarSlot::Inventory(35)),
+            None,
+            Some(HotbarSlot::Ability3),
+        ]);
+    }
+
+    #[test]
+    fn oversized_hotbar_is_truncated_on_load() {
+        let model = convert_hotbar_to_database(7, &[None; MAX_HOTBAR_SLOTS + 3]).unwrap();
+        assert_eq!(
+            convert_hotbar_from_database(&model, 36).unwrap().len(),
+            MAX_HOTBAR_SLOTS
+        );
+    }
+}
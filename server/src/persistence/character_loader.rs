@@ -1,15 +1,18 @@
 use crate::persistence::{
-    character::{create_character, delete_character, load_character_data, load_character_list},
+    character::{
+        create_character, delete_character, load_character_data, load_character_list, load_hotbar,
+    },
     error::Error,
     establish_connection, PersistedComponents,
 };
-use common::character::{CharacterId, CharacterItem};
+use common::character::{CharacterId, CharacterItem, HotbarSlot};
 use crossbeam::{channel, channel::TryIter};
 use std::path::Path;
 use tracing::error;
 
 pub(crate) type CharacterListResult = Result<Vec<CharacterItem>, Error>;
 pub(crate) type CharacterDataResult = Result<PersistedComponents, Error>;
+pub(crate) type HotbarResult = Result<Option<Vec<Option<HotbarSlot>>>, Error>;
 type CharacterLoaderRequest = (specs::Entity, CharacterLoaderRequestKind);
 
 /// Available database operations when modifying a player's character list
@@ -30,14 +33,19 @@ enum CharacterLoaderRequestKind {
         player_uuid: String,
         character_id: CharacterId,
     },
+    LoadHotbar {
+        player_uuid: String,
+        character_id: CharacterId,
+    },
 }
 
 /// Wrapper for results for character actions. Can be a list of
-/// characters, or component data belonging to an individual character
+/// characters, or data belonging to an individual character
 #[derive(Debug)]
 pub enum CharacterLoaderResponseType {
     CharacterList(CharacterListResult),
     CharacterData(Box<CharacterDataResult>),
+    Hotbar(HotbarResult),
 }
 
 /// Common message format dispatched in response to an update request
@@ -111,6 +119,12 @@ impl CharacterLoader {
                                 |txn| load_character_data(player_uuid, character_id, txn),
                             )))
                         },
+                        CharacterLoaderRequestKind::LoadHotbar {
+                            player_uuid,
+                            character_id,
+                        } => CharacterLoaderResponseType::Hotbar(
+                            conn.transaction(|txn| load_hotbar(&player_uuid, character_id, txn)),
+                        ),
                     },
                 }) {
                     error!(?e, "Could not send send persistence request");
@@ -194,6 +208,24 @@ impl CharacterLoader {
         }
     }
 
+    /// Loads the hotbar layout saved for a character
+    pub fn load_hotbar(
+        &self,
+        entity: specs::Entity,
+        player_uuid: String,
+        character_id: CharacterId,
+    ) {
+        if let Err(e) = self
+            .update_tx
+            .send((entity, CharacterLoaderRequestKind::LoadHotbar {
+                player_uuid,
+                character_id,
+            }))
+        {
+            error!(?e, "Could not send hotbar load request");
+        }
+    }
+
     /// Returns a non-blocking iterator over CharacterLoaderResponse messages
     pub fn messages(&self) -> TryIter<CharacterLoaderResponse> { self.update_rx.try_iter() }
 }
@@ -1,47 +1,75 @@
 use crate::comp;
-use common::{character::CharacterId, comp::item::ItemId};
+use common::{
+    character::{CharacterId, HotbarSlot},
+    comp::item::ItemId,
+};
 
 use crate::persistence::{establish_connection, VelorenConnection};
 use crossbeam::channel;
+use hashbrown::HashMap;
 use std::{path::Path, sync::Arc};
 use tracing::{error, trace};
 
 pub type CharacterUpdateData = (comp::Stats, comp::Inventory, comp::Loadout);
 
+/// Writes sent to the background thread
+enum CharacterUpdaterRequest {
+    BatchUpdate(Vec<(CharacterId, CharacterUpdateData)>),
+    UpdateHotbars(Vec<(CharacterId, Vec<Option<HotbarSlot>>)>),
+}
+
 /// A unidirectional messaging resource for saving characters in a
 /// background thread.
 ///
 /// This is used to make updates to a character and their persisted components,
 /// such as inventory, loadout, etc...
 pub struct CharacterUpdater {
-    update_tx: Option<channel::Sender<Vec<(CharacterId, CharacterUpdateData)>>>,
+    update_tx: Option<channel::Sender<CharacterUpdaterRequest>>,
     handle: Option<std::thread::JoinHandle<()>>,
+    /// Hotbar layouts waiting for the next batch update, so that a player
+    /// rearranging their hotbar doesn't cause a write for every change
+    pending_hotbars: HashMap<CharacterId, Vec<Option<HotbarSlot>>>,
 }
 
 impl CharacterUpdater {
     pub fn new(db_dir: &Path) -> diesel::QueryResult<Self> {
-        let (update_tx, update_rx) =
-            channel::unbounded::<Vec<(CharacterId, CharacterUpdateData)>>();
+        let (update_tx, update_rx) = channel::unbounded::<CharacterUpdaterRequest>();
 
         let mut conn = establish_connection(db_dir)?;
 
         let handle = std::thread::spawn(move || {
-            while let Ok(updates) = update_rx.recv() {
-                trace!("Persistence batch update starting");
-                execute_batch_update(updates, &mut conn);
-                trace!("Persistence batch update finished");
+            while let Ok(request) = update_rx.recv() {
+                match request {
+                    CharacterUpdaterRequest::BatchUpdate(updates) => {
+                        trace!("Persistence batch update starting");
+                        execute_batch_update(updates, &mut conn);
+                        trace!("Persistence batch update finished");
+                    },
+                    CharacterUpdaterRequest::UpdateHotbars(hotbars) => {
+                        if let Err(e) = conn.transaction::<_, super::error::Error, _>(|txn| {
+                            for (character_id, layout) in hotbars {
+                                super::character::update_hotbar(character_id, layout, txn)?;
+                            }
+                            Ok(())
+                        }) {
+                            error!(?e, "Error during hotbar update transaction");
+                        }
+                    },
+                }
             }
         });
 
         Ok(Self {
             update_tx: Some(update_tx),
             handle: Some(handle),
+            pending_hotbars: HashMap::new(),
         })
     }
 
-    /// Updates a collection of characters based on their id and components
+    /// Updates a collection of characters based on their id and components,
+    /// along with any hotbar layouts changed since the last update
     pub fn batch_update<'a>(
-        &self,
+        &mut self,
         updates: impl Iterator<
             Item = (
                 CharacterId,
@@ -60,14 +88,44 @@ impl CharacterUpdater {
             })
             .collect::<Vec<(CharacterId, (comp::Stats, comp::Inventory, comp::Loadout))>>();
 
-        if let Err(e) = self.update_tx.as_ref().unwrap().send(updates) {
+        if let Err(e) = self
+            .update_tx
+            .as_ref()
+            .unwrap()
+            .send(CharacterUpdaterRequest::BatchUpdate(updates))
+        {
             error!(?e, "Could not send stats updates");
         }
+
+        self.flush_hotbars();
+    }
+
+    /// Writes the hotbar layouts changed since the last update
+    fn flush_hotbars(&mut self) {
+        if self.pending_hotbars.is_empty() {
+            return;
+        }
+
+        let hotbars = self.pending_hotbars.drain().collect();
+        if let Err(e) = self
+            .update_tx
+            .as_ref()
+            .unwrap()
+            .send(CharacterUpdaterRequest::UpdateHotbars(hotbars))
+        {
+            error!(?e, "Could not send hotbar updates");
+        }
+    }
+
+    /// Replaces the saved hotbar layout of a character with the next batch
+    /// update. Only the latest layout is written.
+    pub fn update_hotbar(&mut self, character_id: CharacterId, layout: Vec<Option<HotbarSlot>>) {
+        self.pending_hotbars.insert(character_id, layout);
     }
 
     /// Updates a single character based on their id and components
     pub fn update(
-        &self,
+        &mut self,
         character_id: CharacterId,
         stats: &comp::Stats,
         inventory: &comp::Inventory,
@@ -105,9 +163,74 @@ fn execute_batch_update(
 
 impl Drop for CharacterUpdater {
     fn drop(&mut self) {
+        self.flush_hotbars();
         drop(self.update_tx.take());
         if let Err(e) = self.handle.take().unwrap().join() {
             error!(?e, "Error from joining character update thread");
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::{
+        character::{create_character, load_hotbar},
+        run_migrations,
+    };
+    use common::comp::{humanoid, Body, Inventory, Loadout, Stats};
+
+    #[test]
+    fn hotbar_saved_with_next_batch_update() {
+        let db_dir = std::env::temp_dir().join(format!("veloren-hotbar-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&db_dir);
+        run_migrations(&db_dir).unwrap();
+        let mut conn = establish_connection(&db_dir).unwrap();
+        let load = |conn: &mut VelorenConnection, character_id| {
+            conn.transaction(|txn| load_hotbar("player", character_id, txn))
+                .unwrap()
+        };
+
+        let body = Body::Humanoid(humanoid::Body::random());
+        let character_id = conn
+            .transaction(|txn| {
+                create_character(
+                    "player",
+                    "Tester",
+                    (
+                        body,
+                        Stats::new("Tester".to_string(), body),
+                        Inventory::default(),
+                        Loadout::default(),
+                    ),
+                    txn,
+                )
+            })
+            .unwrap()[0]
+            .character
+            .id
+            .unwrap();
+
+        let mut updater = CharacterUpdater::new(&db_dir).unwrap();
+        updater.update_hotbar(character_id, vec![Some(HotbarSlot::Ability3)]);
+        updater.update_hotbar(character_id, vec![
+            Some(HotbarSlot::Inventory(2)),
+            None,
+            Some(HotbarSlot::Inventory(500)),
+        ]);
+        assert_eq!(load(&mut conn, character_id), None);
+
+        updater.batch_update(std::iter::empty());
+        // Waits for the writes to finish
+        drop(updater);
+        // Only the latest layout is saved, without the slot that isn't in the
+        // inventory
+        assert_eq!(
+            load(&mut conn, character_id),
+            Some(vec![Some(HotbarSlot::Inventory(2)), None, None])
+        );
+
+        drop(conn);
+        let _ = std::fs::remove_dir_all(&db_dir);
+    }
+}
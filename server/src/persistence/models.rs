@@ -1,6 +1,6 @@
 extern crate serde_json;
 
-use super::schema::{body, character, entity, item, stats};
+use super::schema::{body, character, entity, hotbar, item, stats};
 
 #[derive(Debug, Insertable, PartialEq)]
 #[table_name = "entity"]
@@ -56,3 +56,11 @@ pub struct Body {
     pub variant: String,
     pub body_data: String,
 }
+
+#[derive(AsChangeset, Identifiable, Insertable, Queryable, Debug)]
+#[primary_key(character_id)]
+#[table_name = "hotbar"]
+pub struct Hotbar {
+    pub character_id: i64,
+    pub layout: String,
+}
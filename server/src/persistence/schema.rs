@@ -20,6 +20,13 @@ table! {
     }
 }
 
+table! {
+    hotbar (character_id) {
+        character_id -> BigInt,
+        layout -> Text,
+    }
+}
+
 table! {
     item (item_id) {
         item_id -> BigInt,
@@ -44,4 +51,4 @@ table! {
 joinable!(character -> body (character_id));
 joinable!(character -> stats (character_id));
 
-allow_tables_to_appear_in_same_query!(body, character, entity, item, stats,);
+allow_tables_to_appear_in_same_query!(body, character, entity, hotbar, item, stats,);
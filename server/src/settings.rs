@@ -3,6 +3,7 @@ mod editable;
 pub use editable::EditableSetting;
 
 use authc::Uuid;
+use common::gameplay::GameplaySettings;
 use hashbrown::{HashMap, HashSet};
 use portpicker::pick_unused_port;
 use serde::{Deserialize, Serialize};
@@ -14,6 +15,7 @@ use std::{
     time::Duration,
 };
 use tracing::{error, warn};
+use vek::*;
 use world::sim::FileOpts;
 
 const DEFAULT_WORLD_SEED: u32 = 59686;
@@ -23,6 +25,7 @@ const WHITELIST_FILENAME: &str = "whitelist.ron";
 const BANLIST_FILENAME: &str = "banlist.ron";
 const SERVER_DESCRIPTION_FILENAME: &str = "description.ron";
 const ADMINS_FILENAME: &str = "admins.ron";
+const CHUNK_NAMES_FILENAME: &str = "chunk_names.ron";
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(default)]
@@ -42,6 +45,8 @@ pub struct Settings {
     pub banned_words_files: Vec<PathBuf>,
     pub max_player_group_size: u32,
     pub client_timeout: Duration,
+    /// Balance constants, also sent to clients for movement prediction
+    pub gameplay: GameplaySettings,
 }
 
 impl Default for Settings {
@@ -59,6 +64,7 @@ impl Default for Settings {
             banned_words_files: Vec::new(),
             max_player_group_size: 6,
             client_timeout: Duration::from_secs(40),
+            gameplay: GameplaySettings::default(),
         }
     }
 }
@@ -176,12 +182,18 @@ impl Default for ServerDescription {
 #[serde(transparent)]
 pub struct Admins(HashSet<Uuid>);
 
+/// Custom names given to chunks, by chunk key
+#[derive(Deserialize, Serialize, Default)]
+#[serde(transparent)]
+pub struct ChunkNames(HashMap<Vec2<i32>, String>);
+
 /// Combines all the editable settings into one struct that is stored in the ecs
 pub struct EditableSettings {
     pub whitelist: Whitelist,
     pub banlist: Banlist,
     pub server_description: ServerDescription,
     pub admins: Admins,
+    pub chunk_names: ChunkNames,
 }
 
 impl EditableSettings {
@@ -191,6 +203,7 @@ impl EditableSettings {
             banlist: Banlist::load(data_dir),
             server_description: ServerDescription::load(data_dir),
             admins: Admins::load(data_dir),
+            chunk_names: ChunkNames::load(data_dir),
         }
     }
 
@@ -229,6 +242,10 @@ impl EditableSetting for Admins {
     const FILENAME: &'static str = ADMINS_FILENAME;
 }
 
+impl EditableSetting for ChunkNames {
+    const FILENAME: &'static str = CHUNK_NAMES_FILENAME;
+}
+
 impl Deref for Whitelist {
     type Target = HashSet<Uuid>;
 
@@ -268,3 +285,13 @@ impl Deref for Admins {
 impl DerefMut for Admins {
     fn deref_mut(&mut self) -> &mut Self::Target { &mut self.0 }
 }
+
+impl Deref for ChunkNames {
+    type Target = HashMap<Vec2<i32>, String>;
+
+    fn deref(&self) -> &Self::Target { &self.0 }
+}
+
+impl DerefMut for ChunkNames {
+    fn deref_mut(&mut self) -> &mut Self::Target { &mut self.0 }
+}
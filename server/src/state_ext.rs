@@ -1,5 +1,8 @@
 use crate::{
-    client::Client, persistence::PersistedComponents, sys::sentinel::DeletedEntities, SpawnPoint,
+    client::{ChunkGenProgress, Client},
+    persistence::PersistedComponents,
+    sys::sentinel::DeletedEntities,
+    SpawnPoint,
 };
 use common::{
     character::CharacterId,
@@ -223,6 +226,7 @@ impl StateExt for State {
         // Tell the client its request was successful.
         if let Some(client) = self.ecs().write_storage::<Client>().get_mut(entity) {
             client.in_game = Some(ClientInGame::Character);
+            client.chunk_gen_progress = Some(ChunkGenProgress::default());
             client.send_msg(ServerGeneral::CharacterSuccess)
         }
     }
@@ -236,6 +240,8 @@ impl StateExt for State {
                 PlayerListUpdate::SelectedCharacter(player_uid, CharacterInfo {
                     name: String::from(&stats.name),
                     level: stats.level.level(),
+                    health: None,
+                    energy: None,
                 }),
             ));
 
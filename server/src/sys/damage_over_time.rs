@@ -0,0 +1,55 @@
+use common::{
+    comp::{DamageOverTimeEffects, HealthChange, HealthSource, Loadout},
+    event::{EventBus, ServerEvent},
+    span,
+    state::DeltaTime,
+    sync::Uid,
+};
+use specs::{Entities, Join, Read, ReadStorage, System, WriteStorage};
+use std::time::Duration;
+
+/// This system deals the damage of bleeding, burning and other damage over
+/// time effects, removing them once they run out
+pub struct Sys;
+impl<'a> System<'a> for Sys {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, DeltaTime>,
+        Read<'a, EventBus<ServerEvent>>,
+        ReadStorage<'a, Uid>,
+        ReadStorage<'a, Loadout>,
+        WriteStorage<'a, DamageOverTimeEffects>,
+    );
+
+    fn run(&mut self, (entities, dt, server_bus, uids, loadouts, mut dots): Self::SystemData) {
+        span!(_guard, "run", "damage_over_time::Sys::run");
+        let mut server_emitter = server_bus.emitter();
+        let dt = Duration::from_secs_f32(dt.0);
+
+        let mut expired = Vec::new();
+        for (entity, uid, effects, loadout) in
+            (&entities, &uids, &mut dots, loadouts.maybe()).join()
+        {
+            for (by, damage, kind) in effects.tick(dt) {
+                let resistance = loadout.map_or(0.0, |loadout| loadout.get_resistance(kind));
+                let amount = (damage as f32 * (1.0 - resistance)).round() as i32;
+                if amount > 0 {
+                    server_emitter.emit(ServerEvent::Damage {
+                        uid: *uid,
+                        change: HealthChange {
+                            amount: -amount,
+                            cause: HealthSource::Attack { by },
+                        },
+                    });
+                }
+            }
+            if effects.is_empty() {
+                expired.push(entity);
+            }
+        }
+        for entity in expired {
+            dots.remove(entity);
+        }
+    }
+}
@@ -14,13 +14,10 @@ use common::{
     span,
     state::TimeOfDay,
     sync::{CompSyncPackage, Uid},
-    terrain::TerrainChunkSize,
-    vol::RectVolSize,
 };
 use specs::{
     Entities, Entity as EcsEntity, Join, Read, ReadExpect, ReadStorage, System, Write, WriteStorage,
 };
-use vek::*;
 
 /// This system will send physics updates to the client
 pub struct Sys;
@@ -328,16 +325,13 @@ impl<'a> System<'a> for Sys {
 
         // Sync outcomes
         for (client, player, pos) in (&mut clients, &players, positions.maybe()).join() {
-            let is_near = |o_pos: Vec3<f32>| {
-                pos.zip_with(player.view_distance, |pos, vd| {
-                    pos.0.xy().distance_squared(o_pos.xy())
-                        < (vd as f32 * TerrainChunkSize::RECT_SIZE.x as f32).powf(2.0)
-                })
-            };
-
+            // Clients without a position or view distance get everything
             let outcomes = outcomes
                 .iter()
-                .filter(|o| o.get_pos().and_then(&is_near).unwrap_or(true))
+                .filter(|o| {
+                    pos.zip_with(player.view_distance, |pos, vd| o.is_visible_from(pos.0, vd))
+                        .unwrap_or(true)
+                })
                 .cloned()
                 .collect::<Vec<_>>();
             if !outcomes.is_empty() {
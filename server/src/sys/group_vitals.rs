@@ -0,0 +1,71 @@
+use super::{SysScheduler, SysTimer};
+use crate::client::Client;
+use common::{
+    comp::{group::Group, Energy, Player, Stats},
+    msg::{PlayerListUpdate, ServerGeneral},
+    span,
+    sync::Uid,
+};
+use specs::{Entities, Join, ReadStorage, System, Write, WriteStorage};
+
+/// This system periodically tells players the health and energy of the
+/// other players in their group
+pub struct Sys;
+impl<'a> System<'a> for Sys {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, Uid>,
+        ReadStorage<'a, Player>,
+        ReadStorage<'a, Group>,
+        ReadStorage<'a, Stats>,
+        ReadStorage<'a, Energy>,
+        WriteStorage<'a, Client>,
+        Write<'a, SysScheduler<Self>>,
+        Write<'a, SysTimer<Self>>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, uids, players, groups, stats, energies, mut clients, mut scheduler, mut timer): Self::SystemData,
+    ) {
+        span!(_guard, "run", "group_vitals::Sys::run");
+        if !scheduler.should_run() {
+            return;
+        }
+        timer.start();
+
+        let vitals = (
+            &entities,
+            &uids,
+            &players,
+            &groups,
+            &stats,
+            energies.maybe(),
+        )
+            .join()
+            .map(|(entity, uid, _, group, stats, energy)| {
+                let health = (stats.health.current(), stats.health.maximum());
+                let energy = energy.map(|e| (e.current(), e.maximum()));
+                (
+                    entity,
+                    *group,
+                    ServerGeneral::PlayerListUpdate(PlayerListUpdate::Vitals(
+                        *uid,
+                        Some(health),
+                        energy,
+                    )),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        for (entity, client, group) in (&entities, &mut clients, &groups).join() {
+            vitals
+                .iter()
+                .filter(|(member, member_group, _)| *member != entity && member_group == group)
+                .for_each(|(_, _, msg)| client.send_msg(msg.clone()));
+        }
+
+        timer.end();
+    }
+}
@@ -0,0 +1,78 @@
+use super::SysScheduler;
+use common::{
+    span,
+    state::BlockChange,
+    terrain::{TerrainChunkSize, TerrainGrid},
+    vol::{ReadVol, RectVolSize},
+};
+use rand::Rng;
+use specs::{ReadExpect, System, Write};
+use vek::*;
+
+/// Number of random blocks checked in each loaded chunk every time this
+/// system runs
+const SAMPLES_PER_CHUNK: usize = 8;
+
+/// This system periodically lets blocks such as grass spread into their
+/// neighbours, following [`BlockKind::spread_candidate`], and plants sprout
+/// on top of them, following [`BlockKind::sprout_candidate`].
+///
+/// [`BlockKind::spread_candidate`]: common::terrain::BlockKind::spread_candidate
+/// [`BlockKind::sprout_candidate`]: common::terrain::BlockKind::sprout_candidate
+pub struct Sys;
+impl<'a> System<'a> for Sys {
+    type SystemData = (
+        ReadExpect<'a, TerrainGrid>,
+        Write<'a, BlockChange>,
+        Write<'a, SysScheduler<Self>>,
+    );
+
+    fn run(&mut self, (terrain, mut block_change, mut scheduler): Self::SystemData) {
+        span!(_guard, "run", "growth::Sys::run");
+        if !scheduler.should_run() {
+            return;
+        }
+
+        let mut rng = rand::thread_rng();
+        for (key, chunk) in terrain.iter() {
+            let min_z = chunk.get_min_z();
+            let max_z = chunk.get_max_z();
+            if min_z >= max_z {
+                continue;
+            }
+            let chunk_pos = terrain.key_pos(key);
+
+            for _ in 0..SAMPLES_PER_CHUNK {
+                let wpos = Vec3::new(
+                    chunk_pos.x + rng.gen_range(0, TerrainChunkSize::RECT_SIZE.x as i32),
+                    chunk_pos.y + rng.gen_range(0, TerrainChunkSize::RECT_SIZE.y as i32),
+                    rng.gen_range(min_z, max_z),
+                );
+                let block = match terrain.get(wpos) {
+                    Ok(block) => *block,
+                    Err(_) => continue,
+                };
+                let neighbors = terrain.face_neighbors(wpos);
+                let above = match neighbors[4] {
+                    Some(above) => above,
+                    None => continue,
+                };
+                // Only spread sideways, so growth follows the surface
+                if let Some(new_block) = neighbors[..4]
+                    .iter()
+                    .flatten()
+                    .find_map(|neighbor| block.spread_from(above, *neighbor))
+                {
+                    // Don't fight with changes that were already requested this tick
+                    let _ = block_change.try_set(wpos, new_block);
+                } else if let Some(new_above) = neighbors[..4]
+                    .iter()
+                    .flatten()
+                    .find_map(|neighbor| block.sprout_from(above, *neighbor))
+                {
+                    let _ = block_change.try_set(wpos + Vec3::unit_z(), new_above);
+                }
+            }
+        }
+    }
+}
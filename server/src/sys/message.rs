@@ -2,16 +2,17 @@ use super::SysTimer;
 use crate::{
     alias_validator::AliasValidator,
     character_creator,
-    client::Client,
+    client::{ChunkGenProgress, Client},
     login_provider::LoginProvider,
     metrics::{NetworkRequestMetrics, PlayerMetrics},
+    movement_validator::MovementValidator,
     persistence::character_loader::CharacterLoader,
     EditableSettings, Settings,
 };
 use common::{
     comp::{
-        Admin, CanBuild, ChatMode, ChatType, ControlEvent, Controller, ForceUpdate, Ori, Player,
-        Pos, Stats, UnresolvedChatMsg, Vel,
+        Admin, CanBuild, ChatMode, ChatType, ControlEvent, Controller, ForceUpdate, InventoryManip,
+        Ori, Player, Pos, Stats, UnresolvedChatMsg, Vel,
     },
     event::{EventBus, ServerEvent},
     msg::{
@@ -90,8 +91,9 @@ impl Sys {
         client: &mut Client,
         terrain: &ReadExpect<'_, TerrainGrid>,
         network_metrics: &ReadExpect<'_, NetworkRequestMetrics>,
+        uids: &ReadStorage<'_, Uid>,
         can_build: &ReadStorage<'_, CanBuild>,
-        force_updates: &ReadStorage<'_, ForceUpdate>,
+        force_updates: &mut WriteStorage<'_, ForceUpdate>,
         stats: &mut WriteStorage<'_, Stats>,
         block_changes: &mut Write<'_, BlockChange>,
         positions: &mut WriteStorage<'_, Pos>,
@@ -100,6 +102,8 @@ impl Sys {
         players: &mut WriteStorage<'_, Player>,
         controllers: &mut WriteStorage<'_, Controller>,
         settings: &Read<'_, Settings>,
+        time: &Read<'_, Time>,
+        movement_validator: &ReadExpect<'_, MovementValidator>,
         msg: ClientGeneral,
     ) -> Result<(), crate::error::Error> {
         if client.in_game.is_none() {
@@ -166,25 +170,55 @@ impl Sys {
                     }
                 }
             },
+            ClientGeneral::CancelAbility => {
+                if let Some(ClientInGame::Character) = client.in_game {
+                    server_emitter.emit(ServerEvent::CancelAbility(entity));
+                }
+            },
+            ClientGeneral::UseInventoryItem { slot } => {
+                if let Some(ClientInGame::Character) = client.in_game {
+                    server_emitter.emit(ServerEvent::InventoryManip(
+                        entity,
+                        InventoryManip::Consume(slot),
+                    ));
+                }
+            },
             ClientGeneral::PlayerPhysics { pos, vel, ori } => {
                 if let Some(ClientInGame::Character) = client.in_game {
                     if force_updates.get(entity).is_none()
                         && stats.get(entity).map_or(true, |s| !s.is_dead)
                     {
+                        let mut pos = pos;
+                        if let (Some(last_pos), Some(last_time)) =
+                            (positions.get(entity), client.last_physics_update)
+                        {
+                            let dt = time.0 - last_time;
+                            if let Err(error) = movement_validator.validate(last_pos.0, pos.0, dt) {
+                                warn!(?entity, %error, "Client moved faster than possible");
+                                // Put them where they could have got to and tell them so
+                                pos.0 = movement_validator.clamp(last_pos.0, pos.0, dt);
+                                let _ = force_updates.insert(entity, ForceUpdate);
+                            }
+                        }
+                        client.last_physics_update = Some(time.0);
                         let _ = positions.insert(entity, pos);
                         let _ = velocities.insert(entity, vel);
                         let _ = orientations.insert(entity, ori);
                     }
                 }
             },
-            ClientGeneral::BreakBlock(pos) => {
-                if let Some(block) = can_build.get(entity).and_then(|_| terrain.get(pos).ok()) {
-                    block_changes.set(pos, block.into_vacant());
+            ClientGeneral::BreakBlock(pos, seq) => {
+                if let (Some(block), Some(uid)) = (
+                    can_build.get(entity).and_then(|_| terrain.get(pos).ok()),
+                    uids.get(entity),
+                ) {
+                    // Someone else may change the block before this is applied
+                    block_changes.compare_and_set_from(pos, *block, block.into_vacant(), *uid, seq);
                 }
             },
-            ClientGeneral::PlaceBlock(pos, block) => {
-                if can_build.get(entity).is_some() {
-                    block_changes.try_set(pos, block);
+            ClientGeneral::PlaceBlock(pos, block, seq) => {
+                if let (Some(_), Some(uid)) = (can_build.get(entity), uids.get(entity)) {
+                    block_changes.try_set_from(pos, block, *uid, seq);
                 }
             },
             ClientGeneral::TerrainChunkRequest { key } => {
@@ -199,14 +233,24 @@ impl Sys {
                 } else {
                     true
                 };
+                // The first request marks the start of filling the view distance
+                if let (Some(progress), Some(view_distance), Some(pos)) = (
+                    client
+                        .chunk_gen_progress
+                        .as_mut()
+                        .filter(|progress| !progress.is_started()),
+                    players.get(entity).and_then(|p| p.view_distance),
+                    positions.get(entity),
+                ) {
+                    progress.start(terrain.pos_key(pos.0.map(|e| e as i32)), view_distance);
+                    let msg = progress.to_msg();
+                    client.send_msg(msg);
+                }
                 if in_vd {
                     match terrain.get_key(key) {
                         Some(chunk) => {
                             network_metrics.chunks_served_from_memory.inc();
-                            client.send_msg(ServerGeneral::TerrainChunkUpdate {
-                                key,
-                                chunk: Ok(Box::new(chunk.clone())),
-                            })
+                            client.send_chunk(key, Ok(Box::new(chunk.clone())))
                         },
                         None => {
                             network_metrics.chunks_generation_triggered.inc();
@@ -232,6 +276,21 @@ impl Sys {
                     .get_mut(entity)
                     .map(|s| s.skill_set.unlock_skill_group(skill_group_type));
             },
+            ClientGeneral::SetHotbar(layout) => {
+                server_emitter.emit(ServerEvent::SetHotbar { entity, layout })
+            },
+            ClientGeneral::DialogueResponse { index } => match client.answer_dialogue(index) {
+                Some((speaker, response)) => server_emitter.emit(ServerEvent::DialogueResponse {
+                    entity,
+                    speaker,
+                    response,
+                }),
+                None => debug!(
+                    ?entity,
+                    ?index,
+                    "rejected response to no open dialogue option"
+                ),
+            },
             _ => unreachable!("not a client_in_game msg"),
         }
         Ok(())
@@ -253,7 +312,8 @@ impl Sys {
         match msg {
             // Request spectator state
             ClientGeneral::Spectate if client.registered => {
-                client.in_game = Some(ClientInGame::Spectator)
+                client.in_game = Some(ClientInGame::Spectator);
+                client.chunk_gen_progress = Some(ChunkGenProgress::default());
             },
             ClientGeneral::Spectate => debug!("dropped Spectate msg from unregistered client"),
             ClientGeneral::Character(character_id)
@@ -268,6 +328,7 @@ impl Sys {
                         player.uuid().to_string(),
                         character_id,
                     );
+                    character_loader.load_hotbar(entity, player.uuid().to_string(), character_id);
 
                     // Start inserting non-persisted/default components for the entity
                     // while we load the DB data
@@ -315,14 +376,17 @@ impl Sys {
                     debug!(?error, ?alias, "denied alias as it contained a banned word");
                     client.send_msg(ServerGeneral::CharacterActionError(error.to_string()));
                 } else if let Some(player) = players.get(entity) {
-                    character_creator::create_character(
+                    if let Err(error) = character_creator::create_character(
                         entity,
                         player.uuid().to_string(),
                         alias,
                         tool,
                         body,
                         character_loader,
-                    );
+                    ) {
+                        debug!(?error, "denied character with an invalid loadout");
+                        client.send_msg(ServerGeneral::CharacterActionError(error.to_string()));
+                    }
                 }
             },
             ClientGeneral::DeleteCharacter(character_id) => {
@@ -431,7 +495,7 @@ impl Sys {
         player_metrics: &ReadExpect<'_, PlayerMetrics>,
         uids: &ReadStorage<'_, Uid>,
         can_build: &ReadStorage<'_, CanBuild>,
-        force_updates: &ReadStorage<'_, ForceUpdate>,
+        force_updates: &mut WriteStorage<'_, ForceUpdate>,
         stats: &mut WriteStorage<'_, Stats>,
         chat_modes: &ReadStorage<'_, ChatMode>,
         login_provider: &mut WriteExpect<'_, LoginProvider>,
@@ -445,6 +509,8 @@ impl Sys {
         settings: &Read<'_, Settings>,
         editable_settings: &ReadExpect<'_, EditableSettings>,
         alias_validator: &ReadExpect<'_, AliasValidator>,
+        time: &Read<'_, Time>,
+        movement_validator: &ReadExpect<'_, MovementValidator>,
     ) -> Result<(), crate::error::Error> {
         let (mut b1, mut b2, mut b3, mut b4, mut b5) = (
             client.network_error,
@@ -494,6 +560,7 @@ impl Sys {
                     client,
                     terrain,
                     network_metrics,
+                    uids,
                     can_build,
                     force_updates,
                     stats,
@@ -504,6 +571,8 @@ impl Sys {
                     players,
                     controllers,
                     settings,
+                    time,
+                    movement_validator,
                     msg?,
                 )?;
             }
@@ -560,7 +629,7 @@ impl<'a> System<'a> for Sys {
         Write<'a, SysTimer<Self>>,
         ReadStorage<'a, Uid>,
         ReadStorage<'a, CanBuild>,
-        ReadStorage<'a, ForceUpdate>,
+        WriteStorage<'a, ForceUpdate>,
         WriteStorage<'a, Stats>,
         ReadStorage<'a, ChatMode>,
         WriteExpect<'a, LoginProvider>,
@@ -575,6 +644,7 @@ impl<'a> System<'a> for Sys {
         Read<'a, Settings>,
         ReadExpect<'a, EditableSettings>,
         ReadExpect<'a, AliasValidator>,
+        ReadExpect<'a, MovementValidator>,
     );
 
     #[allow(clippy::match_ref_pats)] // TODO: Pending review in #587
@@ -593,7 +663,7 @@ impl<'a> System<'a> for Sys {
             mut timer,
             uids,
             can_build,
-            force_updates,
+            mut force_updates,
             mut stats,
             chat_modes,
             mut accounts,
@@ -608,6 +678,7 @@ impl<'a> System<'a> for Sys {
             settings,
             editable_settings,
             alias_validator,
+            movement_validator,
         ): Self::SystemData,
     ) {
         span!(_guard, "run", "message::Sys::run");
@@ -628,6 +699,8 @@ impl<'a> System<'a> for Sys {
                     character: stats.map(|stats| CharacterInfo {
                         name: stats.name.clone(),
                         level: stats.level.level(),
+                        health: None,
+                        energy: None,
                     }),
                 })
             })
@@ -654,7 +727,7 @@ impl<'a> System<'a> for Sys {
                     &player_metrics,
                     &uids,
                     &can_build,
-                    &force_updates,
+                    &mut force_updates,
                     &mut stats,
                     &chat_modes,
                     &mut accounts,
@@ -668,6 +741,8 @@ impl<'a> System<'a> for Sys {
                     &settings,
                     &editable_settings,
                     &alias_validator,
+                    &time,
+                    &movement_validator,
                 );
                 select!(
                     _ = Delay::new(std::time::Duration::from_micros(20)).fuse() => Ok(()),
@@ -1,12 +1,18 @@
+pub mod damage_over_time;
 pub mod entity_sync;
+pub mod group_vitals;
+pub mod growth;
 pub mod invite_timeout;
 pub mod message;
+pub mod music_mood;
 pub mod object;
 pub mod persistence;
 pub mod sentinel;
 pub mod subscription;
 pub mod terrain;
 pub mod terrain_sync;
+pub mod threat;
+pub mod trap;
 pub mod waypoint;
 
 use specs::DispatcherBuilder;
@@ -16,11 +22,16 @@ use std::{
 };
 
 pub type EntitySyncTimer = SysTimer<entity_sync::Sys>;
+pub type GroupVitalsTimer = SysTimer<group_vitals::Sys>;
+pub type GroupVitalsScheduler = SysScheduler<group_vitals::Sys>;
+pub type GrowthScheduler = SysScheduler<growth::Sys>;
 pub type MessageTimer = SysTimer<message::Sys>;
+pub type MusicMoodScheduler = SysScheduler<music_mood::Sys>;
 pub type SentinelTimer = SysTimer<sentinel::Sys>;
 pub type SubscriptionTimer = SysTimer<subscription::Sys>;
 pub type TerrainTimer = SysTimer<terrain::Sys>;
 pub type TerrainSyncTimer = SysTimer<terrain_sync::Sys>;
+pub type ThreatScheduler = SysScheduler<threat::Sys>;
 pub type WaypointTimer = SysTimer<waypoint::Sys>;
 pub type InviteTimeoutTimer = SysTimer<invite_timeout::Sys>;
 pub type PersistenceTimer = SysTimer<persistence::Sys>;
@@ -37,6 +48,12 @@ const WAYPOINT_SYS: &str = "server_waypoint_sys";
 const INVITE_TIMEOUT_SYS: &str = "server_invite_timeout_sys";
 const PERSISTENCE_SYS: &str = "server_persistence_sys";
 const OBJECT_SYS: &str = "server_object_sys";
+const GROUP_VITALS_SYS: &str = "server_group_vitals_sys";
+const GROWTH_SYS: &str = "server_growth_sys";
+const TRAP_SYS: &str = "server_trap_sys";
+const DAMAGE_OVER_TIME_SYS: &str = "server_damage_over_time_sys";
+const MUSIC_MOOD_SYS: &str = "server_music_mood_sys";
+const THREAT_SYS: &str = "server_threat_sys";
 
 pub fn add_server_systems(dispatch_builder: &mut DispatcherBuilder) {
     dispatch_builder.add(terrain::Sys, TERRAIN_SYS, &[]);
@@ -44,6 +61,12 @@ pub fn add_server_systems(dispatch_builder: &mut DispatcherBuilder) {
     dispatch_builder.add(invite_timeout::Sys, INVITE_TIMEOUT_SYS, &[]);
     dispatch_builder.add(persistence::Sys, PERSISTENCE_SYS, &[]);
     dispatch_builder.add(object::Sys, OBJECT_SYS, &[]);
+    dispatch_builder.add(group_vitals::Sys, GROUP_VITALS_SYS, &[]);
+    dispatch_builder.add(growth::Sys, GROWTH_SYS, &[]);
+    dispatch_builder.add(trap::Sys, TRAP_SYS, &[]);
+    dispatch_builder.add(damage_over_time::Sys, DAMAGE_OVER_TIME_SYS, &[]);
+    dispatch_builder.add(music_mood::Sys, MUSIC_MOOD_SYS, &[]);
+    dispatch_builder.add(threat::Sys, THREAT_SYS, &[]);
 }
 
 pub fn run_sync_systems(ecs: &mut specs::World) {
@@ -0,0 +1,100 @@
+use super::SysScheduler;
+use crate::client::Client;
+use common::{
+    comp::{Alignment, Pos, Stats},
+    msg::ServerGeneral,
+    span,
+    sync::Uid,
+};
+use specs::{Join, ReadStorage, System, Write, WriteStorage};
+use vek::*;
+
+/// How far away a hostile entity still affects a player's music mood
+const MOOD_RADIUS: f32 = 48.0;
+/// Smallest change in mood worth telling the client about
+const MOOD_EPSILON: f32 = 0.05;
+
+/// Computes how threatening the surroundings of `pos` are, from 0 (calm) to
+/// 1 (intense). Each hostile within `radius` adds more the closer it is, so
+/// a single enemy at the edge of the radius barely registers while a few
+/// right next to the player saturate the mood.
+pub fn music_mood(
+    pos: Vec3<f32>,
+    hostiles: impl IntoIterator<Item = Vec3<f32>>,
+    radius: f32,
+) -> f32 {
+    hostiles
+        .into_iter()
+        .map(|hostile| 1.0 - pos.distance(hostile) / radius)
+        .filter(|weight| *weight > 0.0)
+        .sum::<f32>()
+        .min(1.0)
+}
+
+/// This system periodically tells players how threatening their surroundings
+/// are, so that their music can follow
+pub struct Sys;
+impl<'a> System<'a> for Sys {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        ReadStorage<'a, Uid>,
+        ReadStorage<'a, Pos>,
+        ReadStorage<'a, Alignment>,
+        ReadStorage<'a, Stats>,
+        WriteStorage<'a, Client>,
+        Write<'a, SysScheduler<Self>>,
+    );
+
+    fn run(
+        &mut self,
+        (uids, positions, alignments, stats, mut clients, mut scheduler): Self::SystemData,
+    ) {
+        span!(_guard, "run", "music_mood::Sys::run");
+        if !scheduler.should_run() {
+            return;
+        }
+
+        for (uid, pos, client) in (&uids, &positions, &mut clients).join() {
+            let hostiles = (&positions, &alignments, &stats)
+                .join()
+                .filter(|(_, alignment, stats)| {
+                    !stats.is_dead && alignment.hostile_towards(Alignment::Owned(*uid))
+                })
+                .map(|(pos, _, _)| pos.0);
+            let mood = music_mood(pos.0, hostiles, MOOD_RADIUS);
+
+            // Always send when things calm down entirely, so the music doesn't
+            // get stuck just above calm
+            if (mood - client.music_mood).abs() > MOOD_EPSILON
+                || (mood == 0.0 && client.music_mood != 0.0)
+            {
+                client.music_mood = mood;
+                client.send_msg(ServerGeneral::MusicMood(mood));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mood_from_near_and_far_hostiles() {
+        let pos = Vec3::new(100.0, 100.0, 50.0);
+
+        assert!(music_mood(pos, Vec::<Vec3<f32>>::new(), 48.0).abs() < f32::EPSILON);
+        // Too far away to matter
+        let far = vec![
+            pos + Vec3::new(60.0, 0.0, 0.0),
+            pos + Vec3::new(0.0, -48.0, 0.0),
+        ];
+        assert!(music_mood(pos, far.clone(), 48.0).abs() < f32::EPSILON);
+        // Halfway to the edge of the radius
+        let near = pos + Vec3::new(24.0, 0.0, 0.0);
+        let mood = music_mood(pos, far.into_iter().chain(Some(near)), 48.0);
+        assert!((mood - 0.5).abs() < 0.001);
+        // A crowd right next to the player saturates
+        assert!((music_mood(pos, vec![pos; 4], 48.0) - 1.0).abs() < f32::EPSILON);
+    }
+}
@@ -6,7 +6,7 @@ use common::{
     comp::{Inventory, Loadout, Player, Stats},
     span,
 };
-use specs::{Join, ReadExpect, ReadStorage, System, Write};
+use specs::{Join, ReadStorage, System, Write, WriteExpect};
 
 pub struct Sys;
 
@@ -17,7 +17,7 @@ impl<'a> System<'a> for Sys {
         ReadStorage<'a, Stats>,
         ReadStorage<'a, Inventory>,
         ReadStorage<'a, Loadout>,
-        ReadExpect<'a, character_updater::CharacterUpdater>,
+        WriteExpect<'a, character_updater::CharacterUpdater>,
         Write<'a, SysScheduler<Self>>,
         Write<'a, SysTimer<Self>>,
     );
@@ -29,7 +29,7 @@ impl<'a> System<'a> for Sys {
             player_stats,
             player_inventories,
             player_loadouts,
-            updater,
+            mut updater,
             mut scheduler,
             mut timer,
         ): Self::SystemData,
@@ -1,21 +1,55 @@
 use super::SysTimer;
-use crate::{chunk_generator::ChunkGenerator, client::Client, Tick};
+use crate::{chunk_generator::ChunkGenerator, client::Client, EditableSettings, Tick};
 use common::{
     comp::{self, bird_medium, Alignment, Player, Pos},
     event::{EventBus, ServerEvent},
     generation::get_npc_name,
-    msg::ServerGeneral,
+    msg::{ServerGeneral, ThrottleReason},
     npc::NPC_NAMES,
     span,
     state::TerrainChanges,
     terrain::TerrainGrid,
     LoadoutBuilder,
 };
+use hashbrown::HashSet;
 use rand::Rng;
-use specs::{Join, Read, ReadStorage, System, Write, WriteExpect, WriteStorage};
+use specs::{
+    Entities, Entity, Join, Read, ReadExpect, ReadStorage, System, Write, WriteExpect, WriteStorage,
+};
 use std::sync::Arc;
 use vek::*;
 
+/// Number of chunks waiting to be generated above which the server starts
+/// limiting clients' view distances
+const THROTTLE_PENDING_CHUNKS: usize = 1024;
+/// Number of chunks waiting to be generated below which the limit is lifted
+/// again
+const UNTHROTTLE_PENDING_CHUNKS: usize = 256;
+/// The view distance clients are limited to while chunk generation can't keep
+/// up
+const THROTTLED_VIEW_DISTANCE: u32 = 8;
+
+/// Keeps track of whether chunk generation is falling behind, and which
+/// clients have been told to lower their view distance because of it.
+#[derive(Default)]
+pub struct ViewDistanceThrottle {
+    throttled: bool,
+    notified: HashSet<Entity>,
+}
+
+impl ViewDistanceThrottle {
+    /// Takes the number of chunks waiting to be generated and returns the
+    /// view distance clients should currently be limited to, if any.
+    pub fn update(&mut self, pending_chunks: usize) -> Option<u32> {
+        if pending_chunks > THROTTLE_PENDING_CHUNKS {
+            self.throttled = true;
+        } else if pending_chunks < UNTHROTTLE_PENDING_CHUNKS {
+            self.throttled = false;
+        }
+        Some(THROTTLED_VIEW_DISTANCE).filter(|_| self.throttled)
+    }
+}
+
 /// This system will handle loading generated chunks and unloading
 /// unneeded chunks.
 ///     1. Inserts newly generated chunks into the TerrainGrid
@@ -26,11 +60,14 @@ pub struct Sys;
 impl<'a> System<'a> for Sys {
     #[allow(clippy::type_complexity)] // TODO: Pending review in #587
     type SystemData = (
+        Entities<'a>,
         Read<'a, EventBus<ServerEvent>>,
         Read<'a, Tick>,
         Write<'a, SysTimer<Self>>,
+        Write<'a, ViewDistanceThrottle>,
         WriteExpect<'a, ChunkGenerator>,
         WriteExpect<'a, TerrainGrid>,
+        ReadExpect<'a, EditableSettings>,
         Write<'a, TerrainChanges>,
         ReadStorage<'a, Pos>,
         ReadStorage<'a, Player>,
@@ -40,11 +77,14 @@ impl<'a> System<'a> for Sys {
     fn run(
         &mut self,
         (
+            entities,
             server_event_bus,
             tick,
             mut timer,
+            mut vd_throttle,
             mut chunk_generator,
             mut terrain,
+            editable_settings,
             mut terrain_changes,
             positions,
             players,
@@ -56,17 +96,34 @@ impl<'a> System<'a> for Sys {
 
         let mut server_emitter = server_event_bus.emitter();
 
+        // Ask clients to load less terrain while chunk generation is behind, and
+        // let them go back to their own view distance once it has caught up
+        let vd_limit = vd_throttle.update(chunk_generator.pending_chunks().count());
+        vd_throttle
+            .notified
+            .retain(|entity| entities.is_alive(*entity));
+        for (entity, player, client) in (&entities, &players, &mut clients).join() {
+            let limited = vd_limit
+                .zip(player.view_distance)
+                .map_or(false, |(limit, vd)| vd > limit);
+            if limited && vd_throttle.notified.insert(entity) {
+                client.send_msg(ServerGeneral::ViewDistanceThrottle {
+                    vd: THROTTLED_VIEW_DISTANCE,
+                    reason: ThrottleReason::ServerLoad,
+                });
+            } else if !limited && vd_throttle.notified.remove(&entity) {
+                client.send_msg(ServerGeneral::ViewDistanceThrottleLifted);
+            }
+        }
+
         // Fetch any generated `TerrainChunk`s and insert them into the terrain.
         // Also, send the chunk data to anybody that is close by.
         'insert_terrain_chunks: while let Some((key, res)) = chunk_generator.recv_new_chunk() {
-            let (chunk, supplement) = match res {
+            let (mut chunk, supplement) = match res {
                 Ok((chunk, supplement)) => (chunk, supplement),
                 Err(Some(entity)) => {
                     if let Some(client) = clients.get_mut(entity) {
-                        client.send_msg(ServerGeneral::TerrainChunkUpdate {
-                            key,
-                            chunk: Err(()),
-                        });
+                        client.send_chunk(key, Err(()));
                     }
                     continue 'insert_terrain_chunks;
                 },
@@ -74,11 +131,17 @@ impl<'a> System<'a> for Sys {
                     continue 'insert_terrain_chunks;
                 },
             };
+            if let Some(name) = editable_settings.chunk_names.get(&key) {
+                chunk.meta_mut().set_custom_name(Some(name.clone()));
+            }
+
             // Send the chunk to all nearby players.
             for (view_distance, pos, client) in (&players, &positions, &mut clients)
                 .join()
                 .filter_map(|(player, pos, client)| {
-                    player.view_distance.map(|vd| (vd, pos, client))
+                    player
+                        .view_distance
+                        .map(|vd| (vd_limit.map_or(vd, |limit| vd.min(limit)), pos, client))
                 })
             {
                 let chunk_pos = terrain.pos_key(pos.0.map(|e| e as i32));
@@ -90,10 +153,7 @@ impl<'a> System<'a> for Sys {
                     .magnitude_squared();
 
                 if adjusted_dist_sqr <= view_distance.pow(2) {
-                    client.send_msg(ServerGeneral::TerrainChunkUpdate {
-                        key,
-                        chunk: Ok(Box::new(chunk.clone())),
-                    });
+                    client.send_chunk(key, Ok(Box::new(chunk.clone())));
                 }
             }
 
@@ -248,3 +308,25 @@ pub fn chunk_in_vd(
 
     adjusted_dist_sqr <= vd.pow(2)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn throttle_has_hysteresis() {
+        let mut throttle = ViewDistanceThrottle::default();
+        assert_eq!(throttle.update(0), None);
+        assert_eq!(throttle.update(THROTTLE_PENDING_CHUNKS), None);
+        assert_eq!(
+            throttle.update(THROTTLE_PENDING_CHUNKS + 1),
+            Some(THROTTLED_VIEW_DISTANCE)
+        );
+        // Stays throttled until generation has mostly caught up
+        assert_eq!(
+            throttle.update(UNTHROTTLE_PENDING_CHUNKS),
+            Some(THROTTLED_VIEW_DISTANCE)
+        );
+        assert_eq!(throttle.update(UNTHROTTLE_PENDING_CHUNKS - 1), None);
+    }
+}
@@ -51,7 +51,10 @@ impl<'a> System<'a> for Sys {
 
         // TODO: Don't send all changed blocks to all clients
         // Sync changed blocks
-        let msg = ServerGeneral::TerrainBlockUpdates(terrain_changes.modified_blocks.clone());
+        let msg = ServerGeneral::TerrainBlockUpdates {
+            blocks: terrain_changes.modified_blocks.clone(),
+            confirmed: terrain_changes.confirmed_edits.clone(),
+        };
         for (player, client) in (&players, &mut clients).join() {
             if player.view_distance.is_some() {
                 client.send_msg(msg.clone());
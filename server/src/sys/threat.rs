@@ -0,0 +1,72 @@
+use super::SysScheduler;
+use crate::client::Client;
+use common::{comp::ThreatTable, msg::ServerGeneral, span, state::DeltaTime, sync::Uid};
+use hashbrown::HashSet;
+use specs::{Entities, Join, Read, ReadStorage, System, Write, WriteStorage};
+
+/// Number of attackers sent for each threat table
+const TOP_THREATS: usize = 5;
+
+/// This system lets threat fade and periodically tells players the threat
+/// tables of the entities they are fighting
+pub struct Sys;
+impl<'a> System<'a> for Sys {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, DeltaTime>,
+        ReadStorage<'a, Uid>,
+        WriteStorage<'a, ThreatTable>,
+        WriteStorage<'a, Client>,
+        Write<'a, SysScheduler<Self>>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, dt, uids, mut threat_tables, mut clients, mut scheduler): Self::SystemData,
+    ) {
+        span!(_guard, "run", "threat::Sys::run");
+
+        let mut forgotten = Vec::new();
+        for (entity, threat_table) in (&entities, &mut threat_tables).join() {
+            threat_table.decay(dt.0);
+            if threat_table.is_empty() {
+                forgotten.push(entity);
+            }
+        }
+        for entity in forgotten {
+            threat_tables.remove(entity);
+        }
+
+        if !scheduler.should_run() {
+            return;
+        }
+
+        for (uid, client) in (&uids, &mut clients).join() {
+            let mut shown = HashSet::new();
+            for (entity, threat_table) in (&uids, &threat_tables)
+                .join()
+                .filter(|(_, threat_table)| threat_table.contains(*uid))
+            {
+                shown.insert(*entity);
+                client.send_msg(ServerGeneral::ThreatTable {
+                    entity: *entity,
+                    top: threat_table.top(TOP_THREATS),
+                });
+            }
+            // Let the client know which fights it dropped out of
+            let dropped = client
+                .threat_tables
+                .difference(&shown)
+                .copied()
+                .collect::<Vec<_>>();
+            for entity in dropped {
+                client.send_msg(ServerGeneral::ThreatTable {
+                    entity,
+                    top: Vec::new(),
+                });
+            }
+            client.threat_tables = shown;
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use common::{
+    comp::{HealthChange, HealthSource, PhysicsState, Pos},
+    event::{EventBus, ServerEvent},
+    outcome::Outcome,
+    span,
+    state::Time,
+    sync::Uid,
+    terrain::TerrainGrid,
+    vol::ReadVol,
+};
+use hashbrown::HashMap;
+use specs::{Entities, Entity, Join, Read, ReadExpect, ReadStorage, System, Write};
+use vek::*;
+
+/// Damage dealt by a trap each time it is set off
+const TRAP_DAMAGE: i32 = 100;
+/// Seconds before the same trap can be set off by the same entity again
+const TRAP_COOLDOWN: f64 = 3.0;
+
+/// Keeps track of which entities are standing on which trap blocks, so that
+/// a trap only goes off when it is stepped on rather than on every tick an
+/// entity stands on it.
+#[derive(Default)]
+pub struct TrapTriggers {
+    /// The trap block each entity stood on during the last tick
+    standing_on: HashMap<Entity, Vec3<i32>>,
+    /// When each trap was last set off by each entity
+    last_triggered: HashMap<(Entity, Vec3<i32>), f64>,
+}
+
+impl TrapTriggers {
+    /// Takes the trap block every entity is currently standing on and returns
+    /// those that have just set theirs off.
+    pub fn update(
+        &mut self,
+        footings: impl IntoIterator<Item = (Entity, Vec3<i32>)>,
+        time: f64,
+    ) -> Vec<(Entity, Vec3<i32>)> {
+        self.last_triggered
+            .retain(|_, triggered| time - *triggered < TRAP_COOLDOWN);
+
+        let last_standing_on = std::mem::take(&mut self.standing_on);
+        let mut triggered = Vec::new();
+        for (entity, trap_pos) in footings {
+            self.standing_on.insert(entity, trap_pos);
+            if last_standing_on.get(&entity) == Some(&trap_pos) {
+                // Still standing on it
+                continue;
+            }
+            if self.last_triggered.contains_key(&(entity, trap_pos)) {
+                continue;
+            }
+            self.last_triggered.insert((entity, trap_pos), time);
+            triggered.push((entity, trap_pos));
+        }
+        triggered
+    }
+}
+
+/// This system sets off trap blocks when entities step onto them
+pub struct Sys;
+impl<'a> System<'a> for Sys {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, Time>,
+        ReadExpect<'a, TerrainGrid>,
+        Read<'a, EventBus<ServerEvent>>,
+        Write<'a, Vec<Outcome>>,
+        Write<'a, TrapTriggers>,
+        ReadStorage<'a, Uid>,
+        ReadStorage<'a, Pos>,
+        ReadStorage<'a, PhysicsState>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            time,
+            terrain,
+            server_bus,
+            mut outcomes,
+            mut trap_triggers,
+            uids,
+            positions,
+            physics_states,
+        ): Self::SystemData,
+    ) {
+        span!(_guard, "run", "trap::Sys::run");
+        let mut server_emitter = server_bus.emitter();
+
+        let footings = (&entities, &positions, &physics_states)
+            .join()
+            .filter(|(_, _, physics)| physics.on_ground)
+            .filter_map(|(entity, pos, _)| {
+                // The block the entity's feet rest on
+                let footing = (pos.0 - Vec3::unit_z() * 0.5).map(|e| e.floor() as i32);
+                terrain
+                    .get(footing)
+                    .ok()
+                    .filter(|block| block.is_trap())
+                    .map(|_| (entity, footing))
+            });
+
+        for (entity, trap_pos) in trap_triggers.update(footings, time.0) {
+            outcomes.push(Outcome::TrapTriggered {
+                pos: trap_pos.map(|e| e as f32) + Vec3::new(0.5, 0.5, 1.0),
+            });
+            if let Some(uid) = uids.get(entity) {
+                server_emitter.emit(ServerEvent::Damage {
+                    uid: *uid,
+                    change: HealthChange {
+                        amount: -TRAP_DAMAGE,
+                        cause: HealthSource::Trap,
+                    },
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use specs::{Builder, World, WorldExt};
+
+    #[test]
+    fn stepping_on_a_trap_triggers_once() {
+        let mut world = World::new();
+        let entity = world.create_entity().build();
+        let trap = Vec3::new(3, 4, 5);
+        let mut triggers = TrapTriggers::default();
+
+        assert!(triggers.update(None, 0.0).is_empty());
+        assert_eq!(triggers.update(Some((entity, trap)), 0.1), vec![(
+            entity, trap
+        )]);
+        // Lingering on the trap, even past the cooldown, doesn't set it off again
+        for i in 2..100 {
+            assert!(triggers
+                .update(Some((entity, trap)), i as f64 * 0.1)
+                .is_empty());
+        }
+        // Stepping off and back on does
+        assert!(triggers.update(None, 10.0).is_empty());
+        assert_eq!(triggers.update(Some((entity, trap)), 10.1), vec![(
+            entity, trap
+        )]);
+        // Unless the trap was only just set off
+        assert!(triggers.update(None, 10.5).is_empty());
+        assert!(triggers.update(Some((entity, trap)), 10.6).is_empty());
+    }
+
+    #[test]
+    fn traps_trigger_per_entity() {
+        let mut world = World::new();
+        let a = world.create_entity().build();
+        let b = world.create_entity().build();
+        let trap = Vec3::new(0, 0, 0);
+        let mut triggers = TrapTriggers::default();
+
+        assert_eq!(triggers.update(vec![(a, trap)], 0.0), vec![(a, trap)]);
+        assert_eq!(triggers.update(vec![(a, trap), (b, trap)], 0.1), vec![(
+            b, trap
+        )]);
+    }
+}
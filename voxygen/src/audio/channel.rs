@@ -33,12 +33,13 @@ enum ChannelState {
 /// Each `MusicChannel` has a `MusicChannelTag` which help us determine when we
 /// should transition between two types of in-game music. For example, we
 /// transition between `TitleMusic` and `Exploration` when a player enters the
-/// world by crossfading over a slow duration. In the future, transitions in the
-/// world such as `Exploration` -> `BossBattle` would transition more rapidly.
+/// world by crossfading over a slow duration. Transitions in the world such as
+/// `Exploration` -> `Combat` crossfade more rapidly.
 #[derive(PartialEq, Clone, Copy)]
 pub enum MusicChannelTag {
     TitleMusic,
     Exploration,
+    Combat,
 }
 
 /// A MusicChannel uses a non-positional audio sink designed to play music which
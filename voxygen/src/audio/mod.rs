@@ -122,13 +122,22 @@ impl AudioFrontend {
             } else {
                 let existing_channel = self.music_channels.last_mut()?;
 
-                if existing_channel.get_tag() != next_channel_tag {
+                let existing_tag = existing_channel.get_tag();
+                if existing_tag != next_channel_tag {
+                    // Switching between exploration and combat music in the world has to
+                    // keep up with the action
+                    let fade_in_time = if existing_tag == MusicChannelTag::TitleMusic {
+                        12.0
+                    } else {
+                        4.0
+                    };
+
                     // Fade the existing channel out. It will be removed when the fade completes.
                     existing_channel.set_fader(Fader::fade_out(2.0, self.music_volume));
 
                     let mut next_music_channel = MusicChannel::new(&audio_device);
 
-                    next_music_channel.set_fader(Fader::fade_in(12.0, self.music_volume));
+                    next_music_channel.set_fader(Fader::fade_in(fade_in_time, self.music_volume));
 
                     self.music_channels.push(next_music_channel);
                 }
@@ -196,6 +205,12 @@ impl AudioFrontend {
         }
     }
 
+    pub fn play_combat_music(&mut self, item: &str) {
+        if self.music_enabled() {
+            self.play_music(item, MusicChannelTag::Combat)
+        }
+    }
+
     pub fn get_sfx_volume(&self) -> f32 { self.sfx_volume }
 
     pub fn get_music_volume(&self) -> f32 { self.music_volume }
@@ -19,6 +19,10 @@
 //! transition to another track, without having to spend time determining track
 //! length programmatically.
 //!
+//! Tracks play while exploring unless their `activity` is set to `Combat`, in
+//! which case they are crossfaded to when the server reports that hostiles are
+//! closing in on the player.
+//!
 //! An example of a new night time track:
 //! ```text
 //! (
@@ -47,6 +51,12 @@ use tracing::warn;
 const DAY_START_SECONDS: u32 = 28800; // 8:00
 const DAY_END_SECONDS: u32 = 70200; // 19:30
 
+/// Music mood above which combat music starts
+const COMBAT_MOOD_START: f32 = 0.5;
+/// Music mood below which combat music gives way to exploration music again.
+/// Lower than `COMBAT_MOOD_START` so the music doesn't flip back and forth.
+const COMBAT_MOOD_END: f32 = 0.2;
+
 #[derive(Debug, Default, Deserialize)]
 struct SoundtrackCollection {
     tracks: Vec<SoundtrackItem>,
@@ -61,6 +71,9 @@ pub struct SoundtrackItem {
     length: f64,
     /// Whether this track should play during day or night
     timing: Option<DayPeriod>,
+    /// Whether this track should play while exploring or fighting
+    #[serde(default)]
+    activity: MusicActivity,
 }
 
 /// Allows control over when a track should play based on in-game time of day
@@ -72,6 +85,17 @@ enum DayPeriod {
     Night,
 }
 
+/// What the player is doing, as far as the music is concerned
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+enum MusicActivity {
+    Explore,
+    Combat,
+}
+
+impl Default for MusicActivity {
+    fn default() -> Self { MusicActivity::Explore }
+}
+
 /// Provides methods to control music playback
 pub struct MusicMgr {
     soundtrack: SoundtrackCollection,
@@ -80,6 +104,8 @@ pub struct MusicMgr {
     /// The title of the last track played. Used to prevent a track
     /// being played twice in a row
     last_track: String,
+    /// The kind of music currently playing
+    activity: MusicActivity,
 }
 
 impl MusicMgr {
@@ -90,20 +116,46 @@ impl MusicMgr {
             began_playing: Instant::now(),
             next_track_change: 0.0,
             last_track: String::from("None"),
+            activity: MusicActivity::Explore,
         }
     }
 
-    /// Checks whether the previous track has completed. If so, sends a
-    /// request to play the next (random) track
-    pub fn maintain(&mut self, audio: &mut AudioFrontend, state: &State) {
+    /// Checks whether the previous track has completed, or whether the
+    /// `mood` reported by the server calls for different music. If so, sends
+    /// a request to play the next (random) track
+    pub fn maintain(&mut self, audio: &mut AudioFrontend, state: &State, mood: f32) {
+        let activity = self.next_activity(mood);
+        let activity_changed = activity != self.activity;
+        self.activity = activity;
+
         if audio.music_enabled()
             && !self.soundtrack.tracks.is_empty()
-            && self.began_playing.elapsed().as_secs_f64() > self.next_track_change
+            && (activity_changed
+                || self.began_playing.elapsed().as_secs_f64() > self.next_track_change)
         {
             self.play_random_track(audio, state);
         }
     }
 
+    /// Which kind of music should be playing for the given mood. Combat music
+    /// is only chosen when the soundtrack has some.
+    fn next_activity(&self, mood: f32) -> MusicActivity {
+        match self.activity {
+            MusicActivity::Explore
+                if mood >= COMBAT_MOOD_START
+                    && self
+                        .soundtrack
+                        .tracks
+                        .iter()
+                        .any(|track| track.activity == MusicActivity::Combat) =>
+            {
+                MusicActivity::Combat
+            },
+            MusicActivity::Combat if mood < COMBAT_MOOD_END => MusicActivity::Explore,
+            activity => activity,
+        }
+    }
+
     fn play_random_track(&mut self, audio: &mut AudioFrontend, state: &State) {
         const SILENCE_BETWEEN_TRACKS_SECONDS: f64 = 45.0;
 
@@ -117,6 +169,7 @@ impl MusicMgr {
             .iter()
             .filter(|track| {
                 !track.title.eq(&self.last_track)
+                    && track.activity == self.activity
                     && match &track.timing {
                         Some(period_of_day) => period_of_day == &current_period_of_day,
                         None => true,
@@ -129,7 +182,10 @@ impl MusicMgr {
             self.began_playing = Instant::now();
             self.next_track_change = track.length + SILENCE_BETWEEN_TRACKS_SECONDS;
 
-            audio.play_exploration_music(&track.path);
+            match self.activity {
+                MusicActivity::Explore => audio.play_exploration_music(&track.path),
+                MusicActivity::Combat => audio.play_combat_music(&track.path),
+            }
         }
     }
 
@@ -2,7 +2,8 @@ use super::*;
 use crate::audio::sfx::SfxEvent;
 use common::{
     comp::{
-        item::tool::ToolCategory, CharacterAbilityType, CharacterState, Item, ItemConfig, Loadout,
+        item::tool::ToolCategory, CharacterAbilityType, CharacterState, DamageKind, Item,
+        ItemConfig, Knockback, Loadout,
     },
     states,
 };
@@ -79,11 +80,14 @@ fn maps_basic_melee() {
         &CharacterState::BasicMelee(states::basic_melee::Data {
             buildup_duration: Duration::default(),
             recover_duration: Duration::default(),
-            knockback: 0.0,
+            knockback: Knockback::default(),
             base_healthchange: 10,
             range: 1.0,
             max_angle: 1.0,
-            exhausted: false,
+            timer: Duration::default(),
+            stage_section: states::utils::StageSection::Buildup,
+            dot: None,
+            damage_kind: DamageKind::Physical,
         }),
         &PreviousEntityState {
             event: SfxEvent::Idle,
@@ -121,13 +125,15 @@ fn matches_ability_stage() {
                     base_damage: 100,
                     max_damage: 120,
                     damage_increase: 10,
-                    knockback: 10.0,
+                    knockback: Knockback::from_horizontal(10.0),
                     range: 4.0,
                     angle: 30.0,
                     base_buildup_duration: Duration::from_millis(500),
                     base_swing_duration: Duration::from_millis(200),
                     base_recover_duration: Duration::from_millis(400),
                     forward_movement: 0.5,
+                    branches: Vec::new(),
+                    energy_cost: 0,
                 }],
                 initial_energy_gain: 0,
                 max_energy_gain: 100,
@@ -135,12 +141,15 @@ fn matches_ability_stage() {
                 speed_increase: 0.05,
                 max_speed_increase: 1.8,
                 is_interruptible: true,
+                input_buffer: Duration::default(),
+                damage_kind: DamageKind::Physical,
             },
             stage: 1,
             combo: 0,
             timer: Duration::default(),
             stage_section: states::utils::StageSection::Swing,
-            next_stage: false,
+            next_stage: None,
+            buffered_input: None,
         }),
         &PreviousEntityState {
             event: SfxEvent::Idle,
@@ -181,13 +190,15 @@ fn ignores_different_ability_stage() {
                     base_damage: 100,
                     max_damage: 120,
                     damage_increase: 10,
-                    knockback: 10.0,
+                    knockback: Knockback::from_horizontal(10.0),
                     range: 4.0,
                     angle: 30.0,
                     base_buildup_duration: Duration::from_millis(500),
                     base_swing_duration: Duration::from_millis(200),
                     base_recover_duration: Duration::from_millis(400),
                     forward_movement: 0.5,
+                    branches: Vec::new(),
+                    energy_cost: 0,
                 }],
                 initial_energy_gain: 0,
                 max_energy_gain: 100,
@@ -195,12 +206,15 @@ fn ignores_different_ability_stage() {
                 speed_increase: 0.05,
                 max_speed_increase: 1.8,
                 is_interruptible: true,
+                input_buffer: Duration::default(),
+                damage_kind: DamageKind::Physical,
             },
             stage: 1,
             combo: 0,
             timer: Duration::default(),
             stage_section: states::utils::StageSection::Swing,
-            next_stage: false,
+            next_stage: None,
+            buffered_input: None,
         }),
         &PreviousEntityState {
             event: SfxEvent::Idle,
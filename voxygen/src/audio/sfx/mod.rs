@@ -317,6 +317,9 @@ impl SfxMgr {
                     },
                 }
             },
+            Outcome::TrapTriggered { .. } | Outcome::ProjectileDespawned { .. } => {
+                // not mapped to sfx file
+            },
         }
     }
 
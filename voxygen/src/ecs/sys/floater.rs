@@ -86,7 +86,7 @@ impl<'a> System<'a> for Sys {
                         my_entity.0 == entity || by_me
                     },
                     HealthSource::Suicide => my_entity.0 == entity,
-                    HealthSource::World => my_entity.0 == entity,
+                    HealthSource::World | HealthSource::Trap => my_entity.0 == entity,
                     HealthSource::LevelUp => my_entity.0 == entity,
                     HealthSource::Command => true,
                     HealthSource::Item => true,
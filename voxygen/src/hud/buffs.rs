@@ -207,6 +207,10 @@ impl<'a> Widget for BuffsBar<'a> {
                         BuffKind::Regeneration { .. } => {
                             localized_strings.get("buff.title.heal_test")
                         },
+                        BuffKind::Swiftness { .. } => localized_strings.get("buff.title.swiftness"),
+                        BuffKind::Protection { .. } => {
+                            localized_strings.get("buff.title.protection")
+                        },
                         _ => localized_strings.get("buff.title.missing"),
                     };
                     let remaining_time = if current_duration.is_none() {
@@ -214,11 +218,16 @@ impl<'a> Widget for BuffsBar<'a> {
                     } else {
                         format!("Remaining: {:.0}s", current_duration.unwrap().as_secs_f32())
                     };
+                    let remaining_time = with_stacks(remaining_time, buffs.stacks(buff.kind));
                     let click_to_remove = format!("<{}>", &localized_strings.get("buff.remove"));
                     let desc_txt = match buff.kind {
                         BuffKind::Regeneration { .. } => {
                             localized_strings.get("buff.desc.heal_test")
                         },
+                        BuffKind::Swiftness { .. } => localized_strings.get("buff.desc.swiftness"),
+                        BuffKind::Protection { .. } => {
+                            localized_strings.get("buff.desc.protection")
+                        },
                         _ => localized_strings.get("buff.desc.missing"),
                     };
                     let desc = format!("{}\n\n{}\n\n{}", desc_txt, remaining_time, click_to_remove);
@@ -306,6 +315,7 @@ impl<'a> Widget for BuffsBar<'a> {
                     } else {
                         format!("Remaining: {:.0}s", current_duration.unwrap().as_secs_f32())
                     };
+                    let remaining_time = with_stacks(remaining_time, buffs.stacks(debuff.kind));
                     let desc_txt = match debuff.kind {
                         BuffKind::Bleeding { .. } => {
                             localized_strings.get("debuff.desc.bleed_test")
@@ -381,6 +391,9 @@ impl<'a> Widget for BuffsBar<'a> {
                         BuffKind::Regeneration { .. } => self.imgs.buff_plus_0,
                         BuffKind::Bleeding { .. } => self.imgs.debuff_bleed_0,
                         BuffKind::Cursed { .. } => self.imgs.debuff_skull_0,
+                        BuffKind::Swiftness { .. } | BuffKind::Protection { .. } => {
+                            self.imgs.buff_plus_0
+                        },
                     };
                     let buff_widget = Image::new(buff_img).w_h(40.0, 40.0);
                     // Sort buffs into rows of 6 slots
@@ -405,6 +418,10 @@ impl<'a> Widget for BuffsBar<'a> {
                         BuffKind::Regeneration { .. } => {
                             localized_strings.get("buff.title.heal_test")
                         },
+                        BuffKind::Swiftness { .. } => localized_strings.get("buff.title.swiftness"),
+                        BuffKind::Protection { .. } => {
+                            localized_strings.get("buff.title.protection")
+                        },
                         BuffKind::Bleeding { .. } => {
                             localized_strings.get("debuff.title.bleed_test")
                         },
@@ -415,11 +432,16 @@ impl<'a> Widget for BuffsBar<'a> {
                     } else {
                         format!("{:.0}s", current_duration.unwrap().as_secs_f32())
                     };
+                    let remaining_time = with_stacks(remaining_time, buffs.stacks(buff.kind));
                     let click_to_remove = format!("<{}>", &localized_strings.get("buff.remove"));
                     let desc_txt = match buff.kind {
                         BuffKind::Regeneration { .. } => {
                             localized_strings.get("buff.desc.heal_test")
                         },
+                        BuffKind::Swiftness { .. } => localized_strings.get("buff.desc.swiftness"),
+                        BuffKind::Protection { .. } => {
+                            localized_strings.get("buff.desc.protection")
+                        },
                         BuffKind::Bleeding { .. } => {
                             localized_strings.get("debuff.desc.bleed_test")
                         },
@@ -472,3 +494,14 @@ impl<'a> Widget for BuffsBar<'a> {
         event
     }
 }
+
+/// Appends how many times a buff is stacked to its remaining time, if it is
+fn with_stacks(remaining_time: String, stacks: usize) -> String {
+    if stacks > 1 {
+        format!("{} x{}", remaining_time, stacks)
+            .trim_start()
+            .to_string()
+    } else {
+        remaining_time
+    }
+}
@@ -405,6 +405,10 @@ impl<'a> Widget for Chat<'a> {
                             .localized_strings
                             .get("hud.chat.fall_kill_msg")
                             .to_string(),
+                        KillSource::Trap => self
+                            .localized_strings
+                            .get("hud.chat.trap_kill_msg")
+                            .to_string(),
                         KillSource::Suicide => self
                             .localized_strings
                             .get("hud.chat.suicide_msg")
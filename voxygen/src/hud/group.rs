@@ -479,6 +479,9 @@ impl<'a> Widget for Group<'a> {
                                     BuffKind::Regeneration { .. } => self.imgs.buff_plus_0,
                                     BuffKind::Bleeding { .. } => self.imgs.debuff_bleed_0,
                                     BuffKind::Cursed { .. } => self.imgs.debuff_skull_0,
+                                    BuffKind::Swiftness { .. } | BuffKind::Protection { .. } => {
+                                        self.imgs.buff_plus_0
+                                    },
                                 };
                                 let buff_widget = Image::new(buff_img).w_h(15.0, 15.0);
                                 let buff_widget = if let Some(id) = prev_id {
@@ -1,4 +1,4 @@
-use serde::{Deserialize, Serialize};
+pub use common::character::HotbarSlot as SlotContents;
 
 #[derive(Clone, Copy, PartialEq)]
 pub enum Slot {
@@ -14,12 +14,6 @@ pub enum Slot {
     Ten = 9,
 }
 
-#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
-pub enum SlotContents {
-    Inventory(usize),
-    Ability3,
-}
-
 #[derive(Clone, Debug)]
 pub struct State {
     pub slots: [Option<SlotContents>; 10],
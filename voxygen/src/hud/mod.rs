@@ -64,8 +64,9 @@ use common::{
         item::{ItemDesc, Quality},
         BuffKind,
     },
+    msg::KillCause,
     span,
-    sync::Uid,
+    sync::{Uid, WorldSyncExt},
     terrain::TerrainChunk,
     vol::RectRasterableVol,
 };
@@ -128,6 +129,7 @@ const TELL_COLOR: Color = Color::Rgba(0.98, 0.71, 1.0, 1.0);
 const SAY_COLOR: Color = Color::Rgba(1.0, 0.8, 0.8, 1.0);
 /// Color for group chat
 const GROUP_COLOR: Color = Color::Rgba(0.47, 0.84, 1.0, 1.0);
+
 /// Color for factional chat
 const FACTION_COLOR: Color = Color::Rgba(0.24, 1.0, 0.48, 1.0);
 /// Color for regional chat
@@ -162,6 +164,12 @@ const NAMETAG_DMG_TIME: f32 = 60.0;
 const NAMETAG_DMG_RANGE: f32 = 120.0;
 /// Range to display speech-bubbles at
 const SPEECH_BUBBLE_RANGE: f32 = NAMETAG_RANGE;
+/// Most deaths shown in the kill feed at once
+const MAX_KILL_FEED_ENTRIES: usize = 5;
+/// How long a death stays in the kill feed
+const KILL_FEED_DURATION: Duration = Duration::from_secs(8);
+/// Widest tooltips get before their text wraps onto more lines
+const TOOLTIP_MAX_WIDTH: f64 = 350.0;
 
 widget_ids! {
     struct Ids {
@@ -184,6 +192,17 @@ widget_ids! {
         overheads[],
         overitems[],
 
+        // Game mode objectives
+        objectives[],
+
+        // NPC dialogue
+        dialogue_text,
+        dialogue_responses[],
+        dialogue_close,
+
+        // Recent deaths
+        kill_feed[],
+
         // Intro Text
         intro_bg,
         intro_text,
@@ -222,6 +241,9 @@ widget_ids! {
         debug_info,
         lantern_info,
 
+        // Chunk generation progress
+        chunk_gen_progress,
+
         // Window Frames
         window_frame_0,
         window_frame_1,
@@ -366,6 +388,8 @@ pub enum Event {
     LeaveGroup,
     AssignLeader(common::sync::Uid),
     RemoveBuff(BuffKind),
+    DialogueResponse(usize),
+    CloseDialogue,
 }
 
 // TODO: Are these the possible layouts we want?
@@ -601,6 +625,7 @@ pub struct Hud {
     rot_imgs: ImgsRot,
     new_messages: VecDeque<comp::ChatMsg>,
     new_notifications: VecDeque<common::msg::Notification>,
+    kill_feed: VecDeque<(Option<Uid>, Uid, KillCause, Instant)>,
     speech_bubbles: HashMap<Uid, comp::SpeechBubble>,
     show: Show,
     //never_show: bool,
@@ -627,6 +652,7 @@ impl Hud {
 
         let mut ui = Ui::new(window).unwrap();
         ui.set_scaling_mode(settings.gameplay.ui_scale);
+        ui.set_tooltip_max_width(Some(TOOLTIP_MAX_WIDTH));
         // Generate ids.
         let ids = Ids::new(ui.id_generator());
         // NOTE: Use a border the same color as the LOD ocean color (but with a
@@ -675,6 +701,7 @@ impl Hud {
             ids,
             new_messages: VecDeque::new(),
             new_notifications: VecDeque::new(),
+            kill_feed: VecDeque::new(),
             speech_bubbles: HashMap::new(),
             //intro: false,
             //intro_2: false,
@@ -785,6 +812,138 @@ impl Hud {
                     .mid_top_with_margin_on(ui_widgets.window, 2.0)
                     .set(self.ids.alpha_text, ui_widgets);
 
+                // Game mode objectives, in the order the server sent them
+                let objectives = client.objectives();
+                if self.ids.objectives.len() < objectives.len() {
+                    self.ids
+                        .objectives
+                        .resize(objectives.len(), &mut ui_widgets.widget_id_generator());
+                }
+                for (i, (label, value)) in objectives.iter().enumerate() {
+                    let text = Text::new(&format!("{}: {}", label, value))
+                        .font_id(self.fonts.cyri.conrod_id)
+                        .font_size(self.fonts.cyri.scale(14))
+                        .color(TEXT_COLOR);
+                    let text = if i == 0 {
+                        text.mid_top_with_margin_on(ui_widgets.window, 20.0)
+                    } else {
+                        text.mid_bottom_with_margin_on(self.ids.objectives[i - 1], -20.0)
+                    };
+                    text.set(self.ids.objectives[i], ui_widgets);
+                }
+
+                // NPC dialogue, with a button for each response, or one to close it
+                // if there's nothing to respond
+                if let Some(dialogue) = client.dialogue() {
+                    Text::new(&dialogue.lines.join("\n"))
+                        .font_id(self.fonts.cyri.conrod_id)
+                        .font_size(self.fonts.cyri.scale(16))
+                        .color(TEXT_COLOR)
+                        .mid_bottom_with_margin_on(ui_widgets.window, 300.0)
+                        .set(self.ids.dialogue_text, ui_widgets);
+                    if self.ids.dialogue_responses.len() < dialogue.responses.len() {
+                        self.ids.dialogue_responses.resize(
+                            dialogue.responses.len(),
+                            &mut ui_widgets.widget_id_generator(),
+                        );
+                    }
+                    for (i, response) in dialogue.responses.iter().enumerate() {
+                        let button = Button::image(self.imgs.button)
+                            .w_h(212.0, 32.0)
+                            .hover_image(self.imgs.button_hover)
+                            .press_image(self.imgs.button_press)
+                            .label(response)
+                            .label_font_id(self.fonts.cyri.conrod_id)
+                            .label_font_size(self.fonts.cyri.scale(14))
+                            .label_color(TEXT_COLOR);
+                        let button = if i == 0 {
+                            button.mid_bottom_with_margin_on(self.ids.dialogue_text, -40.0)
+                        } else {
+                            button.mid_bottom_with_margin_on(
+                                self.ids.dialogue_responses[i - 1],
+                                -36.0,
+                            )
+                        };
+                        if button
+                            .set(self.ids.dialogue_responses[i], ui_widgets)
+                            .was_clicked()
+                        {
+                            events.push(Event::DialogueResponse(i));
+                        }
+                    }
+                    if dialogue.responses.is_empty()
+                        && Button::image(self.imgs.button)
+                            .w_h(212.0, 32.0)
+                            .hover_image(self.imgs.button_hover)
+                            .press_image(self.imgs.button_press)
+                            .label(&self.voxygen_i18n.get("common.close"))
+                            .label_font_id(self.fonts.cyri.conrod_id)
+                            .label_font_size(self.fonts.cyri.scale(14))
+                            .label_color(TEXT_COLOR)
+                            .mid_bottom_with_margin_on(self.ids.dialogue_text, -40.0)
+                            .set(self.ids.dialogue_close, ui_widgets)
+                            .was_clicked()
+                    {
+                        events.push(Event::CloseDialogue);
+                    }
+                }
+
+                // Kill feed, newest at the top
+                let now = Instant::now();
+                self.kill_feed
+                    .retain(|(_, _, _, time)| now.duration_since(*time) < KILL_FEED_DURATION);
+                if self.ids.kill_feed.len() < self.kill_feed.len() {
+                    self.ids
+                        .kill_feed
+                        .resize(self.kill_feed.len(), &mut ui_widgets.widget_id_generator());
+                }
+                for (i, (killer, victim, cause, _)) in self.kill_feed.iter().rev().enumerate() {
+                    let victim = client.display_name(*victim);
+                    let msg = match (cause, killer) {
+                        (KillCause::Melee, Some(killer)) => self
+                            .voxygen_i18n
+                            .get("hud.chat.pvp_melee_kill_msg")
+                            .replace("{attacker}", &client.display_name(*killer))
+                            .replace("{victim}", &victim),
+                        (KillCause::Ranged, Some(killer)) => self
+                            .voxygen_i18n
+                            .get("hud.chat.pvp_ranged_kill_msg")
+                            .replace("{attacker}", &client.display_name(*killer))
+                            .replace("{victim}", &victim),
+                        (KillCause::Environment, Some(killer)) => self
+                            .voxygen_i18n
+                            .get("hud.chat.pvp_buff_kill_msg")
+                            .replace("{attacker}", &client.display_name(*killer))
+                            .replace("{victim}", &victim),
+                        (KillCause::Fall, _) => self
+                            .voxygen_i18n
+                            .get("hud.chat.fall_kill_msg")
+                            .replace("{name}", &victim),
+                        (KillCause::Trap, _) => self
+                            .voxygen_i18n
+                            .get("hud.chat.trap_kill_msg")
+                            .replace("{name}", &victim),
+                        (KillCause::Suicide, _) => self
+                            .voxygen_i18n
+                            .get("hud.chat.suicide_msg")
+                            .replace("{name}", &victim),
+                        (_, None) => self
+                            .voxygen_i18n
+                            .get("hud.chat.default_death_msg")
+                            .replace("{name}", &victim),
+                    };
+                    let text = Text::new(&msg)
+                        .font_id(self.fonts.cyri.conrod_id)
+                        .font_size(self.fonts.cyri.scale(14))
+                        .color(KILL_COLOR);
+                    let text = if i == 0 {
+                        text.top_right_with_margins_on(ui_widgets.window, 240.0, 10.0)
+                    } else {
+                        text.down_from(self.ids.kill_feed[i - 1], 4.0).align_right()
+                    };
+                    text.set(self.ids.kill_feed[i], ui_widgets);
+                }
+
                 // Death Frame
                 if stats.is_dead {
                     Image::new(self.imgs.death_bg)
@@ -1144,6 +1303,7 @@ impl Hud {
             }
 
             let speech_bubbles = &self.speech_bubbles;
+            let all_stats = &stats;
 
             // Render overhead name tags and health bars
             for (pos, info, bubble, stats, _, height_offset, hpfl, in_group) in (
@@ -1192,11 +1352,27 @@ impl Hud {
                                     })
                                     .powi(2);
 
+                        // Who the targeted entity is most angry with
+                        let aggro = info
+                            .target_entity
+                            .filter(|e| *e == entity)
+                            .and_then(|_| client.threat_table(*uid)?.first())
+                            .and_then(|(holder, _)| {
+                                client
+                                    .player_list
+                                    .get(holder)
+                                    .map(|player| player.player_alias.as_str())
+                                    .or_else(|| {
+                                        let holder = ecs.entity_from_uid(holder.0)?;
+                                        Some(all_stats.get(holder)?.name.as_str())
+                                    })
+                            });
                         let info = display_overhead_info.then(|| overhead::Info {
                             name: &stats.name,
                             stats,
                             buffs,
                             energy,
+                            aggro,
                         });
                         let bubble = if dist_sqr < SPEECH_BUBBLE_RANGE.powi(2) {
                             speech_bubbles.get(uid)
@@ -1736,6 +1912,26 @@ impl Hud {
             }
         }
 
+        // Chunk generation progress, while the world around the player is first
+        // being generated
+        if let Some((ready, requested)) = client
+            .chunk_gen_progress()
+            .filter(|(ready, requested)| ready < requested)
+        {
+            Text::new(
+                &self
+                    .voxygen_i18n
+                    .get("hud.chunk_gen_progress_fmt")
+                    .replace("{ready}", &ready.to_string())
+                    .replace("{requested}", &requested.to_string()),
+            )
+            .color(TEXT_COLOR)
+            .mid_top_with_margin_on(ui_widgets.window, 80.0)
+            .font_id(self.fonts.cyri.conrod_id)
+            .font_size(self.fonts.cyri.scale(14))
+            .set(self.ids.chunk_gen_progress, ui_widgets);
+        }
+
         // Help Text
         if self.show.help && !self.show.map && !self.show.esc_menu {
             Image::new(self.imgs.help)
@@ -2382,10 +2578,23 @@ impl Hud {
 
     pub fn new_message(&mut self, msg: comp::ChatMsg) { self.new_messages.push_back(msg); }
 
+    /// Replaces the hotbar layout, such as with the one saved on the server
+    pub fn set_hotbar_slots(&mut self, slots: [Option<HotbarSlotContents>; 10]) {
+        self.hotbar.slots = slots;
+    }
+
     pub fn new_notification(&mut self, msg: common::msg::Notification) {
         self.new_notifications.push_back(msg);
     }
 
+    pub fn new_kill(&mut self, killer: Option<Uid>, victim: Uid, cause: KillCause) {
+        self.kill_feed
+            .push_back((killer, victim, cause, Instant::now()));
+        while self.kill_feed.len() > MAX_KILL_FEED_ENTRIES {
+            self.kill_feed.pop_front();
+        }
+    }
+
     pub fn scale_change(&mut self, scale_change: ScaleChange) -> ScaleMode {
         let scale_mode = match scale_change {
             ScaleChange::Adjust(scale) => ScaleMode::Absolute(scale),
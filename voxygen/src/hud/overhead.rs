@@ -41,6 +41,7 @@ widget_ids! {
         // HP
         level,
         level_skull,
+        aggro,
         health_bar,
         health_bar_bg,
         health_txt,
@@ -60,6 +61,8 @@ pub struct Info<'a> {
     pub stats: &'a Stats,
     pub buffs: &'a Buffs,
     pub energy: Option<&'a Energy>,
+    /// The name of whoever has the entity's aggro
+    pub aggro: Option<&'a str>,
 }
 
 /// Determines whether to show the healthbar
@@ -121,6 +124,9 @@ impl<'a> Ingameable for Overhead<'a> {
         // this could be done automatically?
         // - 2 Text::new for name
         //
+        // If someone has aggro:
+        // - 1 Text::new for their name
+        //
         // If HP Info is shown:
         // - 1 for level: either Text or Image
         // - 3 for HP + fg + bg
@@ -136,6 +142,7 @@ impl<'a> Ingameable for Overhead<'a> {
         // - 10 Image::new for speech bubble (9-slice + tail)
         self.info.map_or(0, |info| {
             2 + 1
+                + if info.aggro.is_some() { 1 } else { 0 }
                 + if self.bubble.is_none() {
                     info.buffs.kinds.len().min(10) * 2
                 } else {
@@ -173,6 +180,7 @@ impl<'a> Widget for Overhead<'a> {
             stats,
             buffs,
             energy,
+            aggro,
         }) = self.info
         {
             // Used to set healthbar colours based on hp_percentage
@@ -244,6 +252,9 @@ impl<'a> Widget for Overhead<'a> {
                             BuffKind::Regeneration { .. } => self.imgs.buff_plus_0,
                             BuffKind::Bleeding { .. } => self.imgs.debuff_bleed_0,
                             BuffKind::Cursed { .. } => self.imgs.debuff_skull_0,
+                            BuffKind::Swiftness { .. } | BuffKind::Protection { .. } => {
+                                self.imgs.buff_plus_0
+                            },
                         };
                         let buff_widget = Image::new(buff_img).w_h(20.0, 20.0);
                         // Sort buffs into rows of 5 slots
@@ -302,6 +313,17 @@ impl<'a> Widget for Overhead<'a> {
                 .parent(id)
                 .set(state.ids.name, ui);
 
+            // Aggro
+            if let Some(aggro) = aggro {
+                Text::new(&self.voxygen_i18n.get("hud.aggro").replace("{name}", aggro))
+                    .font_id(self.fonts.cyri.conrod_id)
+                    .font_size(12)
+                    .color(LOW_HP_COLOR)
+                    .x_y(0.0, MANA_BAR_Y - 10.0)
+                    .parent(id)
+                    .set(state.ids.aggro, ui);
+            }
+
             if show_healthbar(stats) {
                 // Show HP Bar
                 let hp_ani = (self.pulse * 4.0/* speed factor */).cos() * 0.5 + 1.0; //Animation timer
@@ -151,6 +151,9 @@ impl PlayState for MainMenuState {
                             client::Error::Other(e) => {
                                 format!("{}: {}", localized_strings.get("common.error"), e)
                             },
+                            client::Error::Replay(e) => {
+                                format!("{}: {:?}", localized_strings.get("common.error"), e)
+                            },
                             client::Error::AuthClientError(e) => match e {
                                 client::AuthClientError::JsonError(e) => format!(
                                     "{}: {}",
@@ -113,6 +113,7 @@ pub enum ParticleMode {
     EnergyNature = 14,
     FlameThrower = 15,
     FireShockwave = 16,
+    Tether = 17,
 }
 
 impl ParticleMode {
@@ -30,7 +30,7 @@ use common::{
         Pos, Scale, Stats, Vel,
     },
     span,
-    state::{DeltaTime, State},
+    state::{DeltaTime, State, Time},
     states::utils::StageSection,
     terrain::TerrainChunk,
     vol::RectRasterableVol,
@@ -390,6 +390,7 @@ impl FigureMgr {
             }
         }
         let dt = ecs.fetch::<DeltaTime>().0;
+        let time = ecs.fetch::<Time>().0;
         let updater = ecs.read_resource::<LazyUpdate>();
         for (entity, light_emitter_opt, body, light_anim) in (
             &ecs.entities(),
@@ -405,6 +406,9 @@ impl FigureMgr {
                         emitter.col,
                         if emitter.strength.is_normal() {
                             emitter.strength
+                                + emitter.periodic_flicker.map_or(0.0, |flicker| {
+                                    flicker.strength_offset(time, entity.id())
+                                })
                         } else {
                             0.0
                         },
@@ -1205,7 +1209,7 @@ impl FigureMgr {
                                 skeleton_attr,
                             )
                         }*/
-                        CharacterState::Equipping { .. } => {
+                        CharacterState::Equipping { .. } | CharacterState::SwapWeapons { .. } => {
                             anim::character::EquipAnimation::update_skeleton(
                                 &target_base,
                                 (active_tool_kind, second_tool_kind, vel.0.magnitude(), time),
@@ -33,9 +33,9 @@ use common::{
     terrain::{BlockKind, TerrainChunk},
     vol::ReadVol,
 };
-use comp::item::Reagent;
 use num::traits::{Float, FloatConst};
 use specs::{Entity as EcsEntity, Join, WorldExt};
+use std::time::Duration;
 use vek::*;
 
 // TODO: Don't hard-code this.
@@ -115,6 +115,9 @@ pub struct SceneData<'a> {
     pub particles_enabled: bool,
     pub figure_lod_render_distance: f32,
     pub is_aiming: bool,
+    /// How threatening the player's surroundings are, see
+    /// `Client::music_mood`
+    pub music_mood: f32,
 }
 
 impl<'a> SceneData<'a> {
@@ -328,6 +331,22 @@ impl Scene {
 
     pub fn select_pos(&self) -> Option<Vec3<i32>> { self.select_pos }
 
+    /// Show a light at `pos` for `duration`, fading out towards the end. It's
+    /// dropped in `maintain` once it times out.
+    pub fn add_light_flash(
+        &mut self,
+        pos: Vec3<f32>,
+        color: Rgb<f32>,
+        strength: f32,
+        duration: Duration,
+    ) {
+        self.event_lights.push(EventLight {
+            light: Light::new(pos, color, strength),
+            timeout: duration.as_secs_f32(),
+            fadeout: |timeout| (timeout * 5.0).min(1.0),
+        });
+    }
+
     /// Handle an incoming user input event (e.g.: cursor moved, key pressed,
     /// window closed).
     ///
@@ -386,42 +405,11 @@ impl Scene {
         self.sfx_mgr.handle_outcome(&outcome, audio);
 
         match outcome {
-            Outcome::Explosion {
-                pos,
-                power,
-                radius: _,
-                is_attack: _,
-                reagent,
-            } => self.event_lights.push(EventLight {
-                light: Light::new(
-                    *pos,
-                    match reagent {
-                        Some(Reagent::Blue) => Rgb::new(0.15, 0.4, 1.0),
-                        Some(Reagent::Green) => Rgb::new(0.0, 1.0, 0.0),
-                        Some(Reagent::Purple) => Rgb::new(0.7, 0.0, 1.0),
-                        Some(Reagent::Red) => Rgb::new(1.0, 0.0, 0.0),
-                        Some(Reagent::Yellow) => Rgb::new(1.0, 1.0, 0.0),
-                        None => {
-                            if *power < 0.0 {
-                                Rgb::new(0.0, 1.0, 0.0)
-                            } else {
-                                Rgb::new(1.0, 0.5, 0.0)
-                            }
-                        },
-                    },
-                    power.abs()
-                        * match reagent {
-                            Some(_) => 5.0,
-                            None => 2.5,
-                        },
-                ),
-                timeout: match reagent {
-                    Some(_) => 1.0,
-                    None => 0.5,
-                },
-                fadeout: |timeout| timeout * 2.0,
-            }),
-            Outcome::ProjectileShot { .. } => {},
+            // The server sends the flash of light explosions give off separately
+            Outcome::Explosion { .. }
+            | Outcome::ProjectileShot { .. }
+            | Outcome::TrapTriggered { .. }
+            | Outcome::ProjectileDespawned { .. } => {},
         }
     }
 
@@ -992,7 +980,8 @@ impl Scene {
             scene_data.player_entity,
             &self.camera,
         );
-        self.music_mgr.maintain(audio, scene_data.state);
+        self.music_mgr
+            .maintain(audio, scene_data.state, scene_data.music_mood);
     }
 
     /// Render the scene using the provided `Renderer`.
@@ -1,4 +1,7 @@
-use super::{terrain::BlocksOfInterest, SceneData, Terrain};
+use super::{
+    terrain::{BlocksOfInterest, AMBIENT_SAMPLE},
+    SceneData, Terrain,
+};
 use crate::{
     mesh::{greedy::GreedyMesh, Meshable},
     render::{
@@ -8,20 +11,21 @@ use crate::{
 };
 use common::{
     assets::Asset,
-    comp::{item::Reagent, object, Body, CharacterState, Ori, Pos, Shockwave},
+    comp::{item::Reagent, object, Body, CharacterState, DespawnReason, Ori, Pos, Shockwave},
     figure::Segment,
     outcome::Outcome,
     span,
     spiral::Spiral2d,
     state::DeltaTime,
-    states::utils::StageSection,
-    terrain::TerrainChunk,
+    states::{grapple, utils::StageSection},
+    sync::UidAllocator,
+    terrain::{AmbientParticle, TerrainChunk},
     vol::{RectRasterableVol, SizedVol},
 };
 use dot_vox::DotVoxData;
 use hashbrown::HashMap;
 use rand::prelude::*;
-use specs::{Join, WorldExt};
+use specs::{saveload::MarkerAllocator, Join, WorldExt};
 use std::{f32::consts::PI, time::Duration};
 use vek::*;
 
@@ -123,7 +127,19 @@ impl ParticleMgr {
                     );
                 }
             },
-            Outcome::ProjectileShot { .. } => {},
+            Outcome::ProjectileDespawned { pos, reason } => {
+                // Projectiles that hit something leave debris, the rest fizzle out
+                let mode = match reason {
+                    DespawnReason::Hit => ParticleMode::Shrapnel,
+                    DespawnReason::Expired | DespawnReason::OutOfBounds => {
+                        ParticleMode::CampfireSmoke
+                    },
+                };
+                self.particles.resize_with(self.particles.len() + 10, || {
+                    Particle::new(Duration::from_millis(500), time, mode, *pos)
+                });
+            },
+            Outcome::ProjectileShot { .. } | Outcome::TrapTriggered { .. } => {},
         }
     }
 
@@ -146,6 +162,7 @@ impl ParticleMgr {
             self.maintain_body_particles(scene_data);
             self.maintain_boost_particles(scene_data);
             self.maintain_beam_particles(scene_data);
+            self.maintain_grapple_particles(scene_data);
             self.maintain_block_particles(scene_data, terrain);
             self.maintain_shockwave_particles(scene_data);
         } else {
@@ -417,6 +434,64 @@ impl ParticleMgr {
         }
     }
 
+    fn maintain_grapple_particles(&mut self, scene_data: &SceneData) {
+        span!(
+            _guard,
+            "grapple_particles",
+            "ParticleMgr::maintain_grapple_particles"
+        );
+        const TETHER_SEGMENTS: usize = 24;
+
+        let state = scene_data.state;
+        let ecs = state.ecs();
+        let time = state.get_time();
+        let heartbeats = self.scheduler.heartbeats(Duration::from_millis(30));
+        let uid_allocator = ecs.read_resource::<UidAllocator>();
+        let positions = ecs.read_storage::<Pos>();
+        let bodies = ecs.read_storage::<Body>();
+
+        for (pos, body, character_state) in
+            (&positions, &bodies, &ecs.read_storage::<CharacterState>()).join()
+        {
+            if let CharacterState::Grapple(grapple::Data {
+                static_data,
+                stage_section: StageSection::Movement,
+                anchor: Some(anchor),
+                ..
+            }) = character_state
+            {
+                let from = grapple::tether_point_on(pos.0, body);
+                let to = match anchor.point(|uid| {
+                    let target = uid_allocator.retrieve_entity_internal(uid.into())?;
+                    Some(grapple::tether_point_on(
+                        positions.get(target)?.0,
+                        bodies.get(target)?,
+                    ))
+                }) {
+                    Some(to) => to,
+                    // The entity holding the other end is out of sight
+                    None => continue,
+                };
+                for _ in 0..heartbeats {
+                    for i in 0..=TETHER_SEGMENTS {
+                        self.particles.push(Particle::new(
+                            Duration::from_millis(30),
+                            time,
+                            ParticleMode::Tether,
+                            grapple::tether_point(
+                                from,
+                                to,
+                                static_data.arc_height,
+                                static_data.range,
+                                i as f32 / TETHER_SEGMENTS as f32,
+                            ),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
     #[allow(clippy::same_item_push)] // TODO: Pending review in #587
     fn maintain_block_particles(
         &mut self,
@@ -453,15 +528,12 @@ impl ParticleMgr {
             cond: fn(&SceneData) -> bool,
         }
 
+        // The range, in chunks, that ambient block particles are generated in
+        const AMBIENT_RANGE: usize = 4;
+        // The most ambient particles of one kind emitted per chunk per second
+        const MAX_AMBIENT_RATE: f32 = 8.0;
+
         let particles: &[BlockParticles] = &[
-            BlockParticles {
-                blocks: |boi| &boi.leaves,
-                range: 4,
-                rate: 0.001,
-                lifetime: 30.0,
-                mode: ParticleMode::Leaf,
-                cond: |_| true,
-            },
             BlockParticles {
                 blocks: |boi| &boi.embers,
                 range: 2,
@@ -536,6 +608,47 @@ impl ParticleMgr {
                 });
             }
         }
+
+        // Blocks that give off particles of their own, see
+        // `BlockKind::ambient_particles`
+        for offset in Spiral2d::new().take((AMBIENT_RANGE * 2 + 1).pow(2)) {
+            let chunk_pos = player_chunk + offset;
+            let chunk_data = match terrain.get(chunk_pos) {
+                Some(chunk_data) => chunk_data,
+                None => continue,
+            };
+
+            for (kind, blocks) in chunk_data.blocks_of_interest.ambient.iter() {
+                let spec = match kind.ambient_particles() {
+                    Some(spec) => spec,
+                    None => continue,
+                };
+                // Each sampled block stands in for the ones that weren't kept track of, but
+                // dense fields are capped so that they don't drown out everything else
+                let avg_particles = (dt * blocks.len() as f32 * AMBIENT_SAMPLE as f32 * spec.rate)
+                    .min(dt * MAX_AMBIENT_RATE);
+                let particle_count = avg_particles.trunc() as usize
+                    + (rng.gen::<f32>() < avg_particles.fract()) as usize;
+                let mode = match spec.kind {
+                    AmbientParticle::Leaf => ParticleMode::Leaf,
+                    AmbientParticle::Ember => ParticleMode::CampfireFire,
+                };
+
+                self.particles
+                    .resize_with(self.particles.len() + particle_count, || {
+                        let block_pos =
+                            Vec3::from(chunk_pos * TerrainChunk::RECT_SIZE.map(|e| e as i32))
+                                + blocks.choose(&mut rng).copied().unwrap(); // Can't fail
+
+                        Particle::new(
+                            Duration::from_secs_f32(spec.lifetime),
+                            time,
+                            mode,
+                            block_pos.map(|e: i32| e as f32 + rng.gen::<f32>()),
+                        )
+                    });
+            }
+        }
     }
 
     fn maintain_shockwave_particles(&mut self, scene_data: &SceneData) {
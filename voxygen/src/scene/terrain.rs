@@ -1,6 +1,6 @@
 mod watcher;
 
-pub use self::watcher::BlocksOfInterest;
+pub use self::watcher::{BlocksOfInterest, AMBIENT_SAMPLE};
 
 use crate::{
     mesh::{greedy::GreedyMesh, Meshable},
@@ -3,11 +3,18 @@ use common::{
     terrain::{BlockKind, SpriteKind, TerrainChunk},
     vol::{IntoVolIterator, RectRasterableVol},
 };
+use hashbrown::HashMap;
 use rand::prelude::*;
 use vek::*;
 
+/// Only one in this many blocks that give off ambient particles is kept track
+/// of, to keep dense areas like forest canopies cheap
+pub const AMBIENT_SAMPLE: u32 = 16;
+
 pub struct BlocksOfInterest {
-    pub leaves: Vec<Vec3<i32>>,
+    /// A sample of the blocks that give off particles of their own, see
+    /// `BlockKind::ambient_particles`
+    pub ambient: Vec<(BlockKind, Vec<Vec3<i32>>)>,
     pub grass: Vec<Vec3<i32>>,
     pub embers: Vec<Vec3<i32>>,
     pub beehives: Vec<Vec3<i32>>,
@@ -18,7 +25,7 @@ pub struct BlocksOfInterest {
 impl BlocksOfInterest {
     pub fn from_chunk(chunk: &TerrainChunk) -> Self {
         span!(_guard, "from_chunk", "BlocksOfInterest::from_chunk");
-        let mut leaves = Vec::new();
+        let mut ambient = HashMap::<_, Vec<_>>::new();
         let mut grass = Vec::new();
         let mut embers = Vec::new();
         let mut beehives = Vec::new();
@@ -35,9 +42,9 @@ impl BlocksOfInterest {
                 ),
             )
             .for_each(|(pos, block)| match block.kind() {
-                BlockKind::Leaves => {
-                    if thread_rng().gen_range(0, 16) == 0 {
-                        leaves.push(pos)
+                kind if kind.ambient_particles().is_some() => {
+                    if thread_rng().gen_range(0, AMBIENT_SAMPLE) == 0 {
+                        ambient.entry(kind).or_default().push(pos)
                     }
                 },
                 BlockKind::Grass => {
@@ -60,7 +67,7 @@ impl BlocksOfInterest {
             });
 
         Self {
-            leaves,
+            ambient: ambient.into_iter().collect(),
             grass,
             embers,
             beehives,
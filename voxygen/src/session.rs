@@ -14,14 +14,17 @@ use crate::{
 use client::{self, Client};
 use common::{
     assets::Asset,
+    character::MAX_HOTBAR_SLOTS,
     comp,
     comp::{
         ChatMsg, ChatType, InventoryUpdateEvent, Pos, Vel, MAX_MOUNT_RANGE_SQR,
         MAX_PICKUP_RANGE_SQR,
     },
     event::EventBus,
+    msg::ThrottleReason,
     outcome::Outcome,
     span,
+    sync::Uid,
     terrain::{Block, BlockKind},
     util::Dir,
     vol::ReadVol,
@@ -170,6 +173,11 @@ impl SessionState {
                     ));
                     return Ok(TickAction::Disconnect);
                 },
+                client::Event::Kill {
+                    killer,
+                    victim,
+                    cause,
+                } => self.hud.new_kill(killer, victim, cause),
                 client::Event::Notification(n) => {
                     self.hud.new_notification(n);
                 },
@@ -177,7 +185,52 @@ impl SessionState {
                     global_state.settings.graphics.view_distance = vd;
                     global_state.settings.save_to_file_warn();
                 },
+                client::Event::ViewDistanceThrottled { vd, reason } => {
+                    let key = match reason {
+                        ThrottleReason::ServerLoad => "hud.chat.view_distance_throttled_load",
+                        ThrottleReason::ClientLag => "hud.chat.view_distance_throttled_lag",
+                    };
+                    self.hud.new_message(ChatMsg {
+                        chat_type: ChatType::CommandInfo,
+                        message: self
+                            .voxygen_i18n
+                            .get(key)
+                            .replace("{vd}", vd.to_string().as_str()),
+                    });
+                },
+                client::Event::ViewDistanceRestored => {
+                    self.hud.new_message(ChatMsg {
+                        chat_type: ChatType::CommandInfo,
+                        message: self
+                            .voxygen_i18n
+                            .get("hud.chat.view_distance_restored")
+                            .to_string(),
+                    });
+                },
                 client::Event::Outcome(outcome) => outcomes.push(outcome),
+                client::Event::LightFlash {
+                    pos,
+                    color,
+                    strength,
+                    duration,
+                } => self.scene.add_light_flash(pos, color, strength, duration),
+                client::Event::Hotbar(layout) => {
+                    let mut slots = [None; MAX_HOTBAR_SLOTS];
+                    slots
+                        .iter_mut()
+                        .zip(layout)
+                        .for_each(|(slot, contents)| *slot = contents);
+                    self.hud.set_hotbar_slots(slots);
+
+                    if let Some(character_id) = client.active_character_id {
+                        global_state.profile.set_hotbar_slots(
+                            &client.server_info.name,
+                            character_id,
+                            slots,
+                        );
+                        global_state.profile.save_to_file_warn();
+                    }
+                },
             }
         }
 
@@ -282,6 +335,13 @@ impl PlayState for SessionState {
                     .map(|b| b.is_collectible() || can_build)
                     .unwrap_or(false)
             }));
+            self.inputs.select_pos = self.scene.select_pos();
+            self.inputs.select_entity = self.target_entity.and_then(|entity| {
+                self.client
+                    .borrow()
+                    .state()
+                    .read_component_copied::<Uid>(entity)
+            });
 
             // Handle window events.
             for event in events {
@@ -346,6 +406,9 @@ impl PlayState for SessionState {
                     Event::InputUpdate(GameInput::Jump, state) => {
                         self.inputs.jump.set_state(state);
                     },
+                    Event::InputUpdate(GameInput::Sprint, state) => {
+                        self.inputs.sprint.set_state(state);
+                    },
                     Event::InputUpdate(GameInput::SwimUp, state) => {
                         self.inputs.swimup.set_state(state);
                     },
@@ -437,6 +500,9 @@ impl PlayState for SessionState {
                             self.client.borrow_mut().swap_loadout();
                         }
                     }
+                    Event::InputUpdate(GameInput::CancelAbility, true) => {
+                        self.client.borrow_mut().cancel_ability();
+                    },
                     Event::InputUpdate(GameInput::ToggleLantern, true) => {
                         let mut client = self.client.borrow_mut();
                         if client.is_lantern_enabled() {
@@ -941,7 +1007,8 @@ impl PlayState for SessionState {
                         }
                     },
                     HudEvent::ChangeHotbarState(state) => {
-                        let client = self.client.borrow();
+                        let mut client = self.client.borrow_mut();
+                        client.set_hotbar(state.slots.to_vec());
 
                         let server = &client.server_info.name;
                         // If we are changing the hotbar state this CANNOT be None.
@@ -1048,6 +1115,12 @@ impl PlayState for SessionState {
                     HudEvent::LeaveGroup => {
                         self.client.borrow_mut().leave_group();
                     },
+                    HudEvent::DialogueResponse(index) => {
+                        self.client.borrow_mut().respond_to_dialogue(index);
+                    },
+                    HudEvent::CloseDialogue => {
+                        self.client.borrow_mut().close_dialogue();
+                    },
                     HudEvent::AssignLeader(uid) => {
                         self.client.borrow_mut().assign_group_leader(uid);
                     },
@@ -1076,6 +1149,7 @@ impl PlayState for SessionState {
                         .figure_lod_render_distance
                         as f32,
                     is_aiming,
+                    music_mood: client.music_mood(),
                 };
 
                 // Runs if either in a multiplayer server or the singleplayer server is unpaused
@@ -1135,6 +1209,7 @@ impl PlayState for SessionState {
                 figure_lod_render_distance: settings.graphics.figure_lod_render_distance as f32,
                 particles_enabled: settings.graphics.particles_enabled,
                 is_aiming: self.is_aiming,
+                music_mood: client.music_mood(),
             };
             self.scene.render(
                 renderer,
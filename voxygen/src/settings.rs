@@ -137,6 +137,7 @@ impl ControlSettings {
             GameInput::SwimUp => KeyMouse::Key(VirtualKeyCode::Space),
             GameInput::SwimDown => KeyMouse::Key(VirtualKeyCode::LShift),
             GameInput::Sneak => KeyMouse::Key(VirtualKeyCode::LControl),
+            GameInput::Sprint => KeyMouse::Key(VirtualKeyCode::R),
             //GameInput::WallLeap => MIDDLE_CLICK_KEY,
             GameInput::ToggleLantern => KeyMouse::Key(VirtualKeyCode::G),
             GameInput::Mount => KeyMouse::Key(VirtualKeyCode::F),
@@ -171,6 +172,7 @@ impl ControlSettings {
             GameInput::Slot9 => KeyMouse::Key(VirtualKeyCode::Key9),
             GameInput::Slot10 => KeyMouse::Key(VirtualKeyCode::Q),
             GameInput::SwapLoadout => KeyMouse::Key(VirtualKeyCode::LAlt),
+            GameInput::CancelAbility => KeyMouse::Key(VirtualKeyCode::X),
             GameInput::Select => KeyMouse::Key(VirtualKeyCode::Y),
             GameInput::AcceptGroupInvite => KeyMouse::Key(VirtualKeyCode::U),
             GameInput::DeclineGroupInvite => KeyMouse::Key(VirtualKeyCode::I),
@@ -204,6 +206,7 @@ impl Default for ControlSettings {
             GameInput::SwimUp,
             GameInput::SwimDown,
             GameInput::Sneak,
+            GameInput::Sprint,
             //GameInput::WallLeap,
             GameInput::ToggleLantern,
             GameInput::Mount,
@@ -241,6 +244,7 @@ impl Default for ControlSettings {
             GameInput::Slot9,
             GameInput::Slot10,
             GameInput::SwapLoadout,
+            GameInput::CancelAbility,
             GameInput::Select,
             GameInput::AcceptGroupInvite,
             GameInput::DeclineGroupInvite,
@@ -229,6 +229,11 @@ impl Ui {
 
     pub fn id_generator(&mut self) -> Generator { self.ui.widget_id_generator() }
 
+    /// Caps the width of tooltips that don't set their own maximum
+    pub fn set_tooltip_max_width(&mut self, max_width: Option<f64>) {
+        self.tooltip_manager.set_max_width(max_width);
+    }
+
     pub fn set_widgets(&mut self) -> (UiCell, &mut TooltipManager) {
         (self.ui.set_widgets(), &mut self.tooltip_manager)
     }
@@ -1,8 +1,10 @@
 use super::image_frame::ImageFrame;
 use conrod_core::{
-    builder_method, builder_methods, image, input::global::Global, position::Dimension, text,
-    widget, widget_ids, Color, Colorable, FontSize, Positionable, Sizeable, Ui, UiCell, Widget,
-    WidgetCommon, WidgetStyle,
+    builder_method, builder_methods, image,
+    input::global::Global,
+    position::{Dimension, Rect},
+    text, widget, widget_ids, Color, Colorable, FontSize, Positionable, Sizeable, Ui, UiCell,
+    Widget, WidgetCommon, WidgetStyle,
 };
 use std::time::{Duration, Instant};
 #[derive(Copy, Clone)]
@@ -28,6 +30,8 @@ pub struct TooltipManager {
     fade_dur: Duration,
     // Current scaling of the ui
     logical_scale_factor: f64,
+    // Widest tooltips can get, for tooltips that don't set their own
+    max_width: Option<f64>,
 }
 impl TooltipManager {
     pub fn new(
@@ -42,9 +46,14 @@ impl TooltipManager {
             hover_dur,
             fade_dur,
             logical_scale_factor,
+            max_width: None,
         }
     }
 
+    /// Caps the width of tooltips that don't set their own maximum, wrapping
+    /// their text onto more lines instead
+    pub fn set_max_width(&mut self, max_width: Option<f64>) { self.max_width = max_width; }
+
     pub fn maintain(&mut self, input: &Global, logical_scale_factor: f64) {
         self.logical_scale_factor = logical_scale_factor;
 
@@ -114,24 +123,15 @@ impl TooltipManager {
                 .desc(desc_text)
                 .title_col(title_col)
                 .image(img_id)
-                .image_dims(image_dims);
-
-            let [t_w, t_h] = tooltip.get_wh(ui).unwrap_or([0.0, 0.0]);
-            let [m_x, m_y] = [mouse_pos[0], mouse_pos[1]];
-            let (w_w, w_h) = (ui.win_w, ui.win_h);
-
-            // Determine position based on size and mouse position
-            // Flow to the top left of the mouse when there is space
-            let x = if (m_x + w_w / 2.0) > t_w {
-                m_x - t_w / 2.0
-            } else {
-                m_x + t_w / 2.0
-            };
-            let y = if w_h - (m_y + w_h / 2.0) > t_h + mp_h {
-                m_y + mp_h + t_h / 2.0
-            } else {
-                m_y - mp_h - t_h / 2.0
-            };
+                .image_dims(image_dims)
+                .max_width(tooltip.max_width.or(self.max_width));
+
+            let dims = tooltip.get_wh(ui).unwrap_or([0.0, 0.0]);
+            // Keep the hovered widget visible
+            let avoid = ui
+                .rect_of(src_id)
+                .unwrap_or_else(|| Rect::from_xy_dim(mouse_pos, [0.0, 0.0]));
+            let [x, y] = tooltip_xy(dims, mouse_pos, avoid, [ui.win_w, ui.win_h], mp_h);
             tooltip
                 .floating(true)
                 .transparency(transparency)
@@ -149,14 +149,89 @@ impl TooltipManager {
             ),
             HoverState::Start(start, id) if id == src_id && start.elapsed() > self.hover_dur => {
                 let xy = ui.global_input().current.mouse.xy;
-                self.state = HoverState::Hovering(Hover(id, xy));
-                tooltip(1.0, xy, ui);
+                let visible = ui.rect_of(src_id).map_or(false, |rect| {
+                    hover_visible(xy, rect, ui.visible_area(src_id))
+                });
+                if visible {
+                    self.state = HoverState::Hovering(Hover(id, xy));
+                    tooltip(1.0, xy, ui);
+                }
             },
             _ => (),
         }
     }
 }
 
+/// Whether hovering at `mouse_xy` over a widget occupying `rect` should show
+/// its tooltip, given the area its parents crop it to (`None` if it is cropped
+/// away entirely). This keeps widgets scrolled out of view in a list from
+/// showing tooltips.
+fn hover_visible(mouse_xy: [f64; 2], rect: Rect, clip: Option<Rect>) -> bool {
+    clip.and_then(|clip| rect.overlap(clip))
+        .map_or(false, |visible| visible.is_over(mouse_xy))
+}
+
+/// Width of a tooltip whose content is `content_w` wide when laid out on a
+/// single line. Text wraps once the content is wider than `default_w` or the
+/// tooltip would be wider than `max_width`, and the tooltip never gets wider
+/// than the window.
+fn tooltip_width(content_w: f64, default_w: f64, max_width: Option<f64>, win_w: f64) -> f64 {
+    let width = content_w.min(default_w) + 2.0 * H_PAD;
+    max_width
+        .map_or(width, |max_width| width.min(max_width))
+        .min(win_w)
+}
+
+/// Determines where to center a tooltip with the given dimensions so that it
+/// stays within the window and doesn't cover `avoid`, preferring the side of it
+/// with the most space. If the tooltip can't fit beside `avoid` at all, the
+/// position overlapping it the least is used.
+fn tooltip_xy(
+    [t_w, t_h]: [f64; 2],
+    [m_x, m_y]: [f64; 2],
+    avoid: Rect,
+    [w_w, w_h]: [f64; 2],
+    pad: f64,
+) -> [f64; 2] {
+    let clamp = |e: f64, size: f64, win: f64| {
+        let max = ((win - size) / 2.0).max(0.0);
+        e.max(-max).min(max)
+    };
+
+    let above = [m_x, avoid.top() + pad + t_h / 2.0];
+    let below = [m_x, avoid.bottom() - pad - t_h / 2.0];
+    let left = [avoid.left() - pad - t_w / 2.0, m_y];
+    let right = [avoid.right() + pad + t_w / 2.0, m_y];
+    // Try the sides with more space first
+    let vertical = if w_h / 2.0 - avoid.top() >= avoid.bottom() + w_h / 2.0 {
+        [above, below]
+    } else {
+        [below, above]
+    };
+    let horizontal = if w_w / 2.0 - avoid.right() > avoid.left() + w_w / 2.0 {
+        [right, left]
+    } else {
+        [left, right]
+    };
+
+    vertical
+        .iter()
+        .chain(horizontal.iter())
+        .map(|&[x, y]| [clamp(x, t_w, w_w), clamp(y, t_h, w_h)])
+        .map(|xy| {
+            let overlap = Rect::from_xy_dim(xy, [t_w, t_h])
+                .overlap(avoid)
+                .map_or(0.0, |o| o.area());
+            (xy, overlap)
+        })
+        // Keep the earliest (most preferred) candidate out of equally good ones
+        .fold(None, |best: Option<([f64; 2], f64)>, (xy, overlap)| match best {
+            Some((_, best_overlap)) if best_overlap <= overlap => best,
+            _ => Some((xy, overlap)),
+        })
+        .map_or([m_x, m_y], |(xy, _)| xy)
+}
+
 pub struct Tooltipped<'a, W> {
     inner: W,
     tooltip_manager: &'a mut TooltipManager,
@@ -250,6 +325,7 @@ pub struct Tooltip<'a> {
     title_col: Color,
     image: Option<image::Id>,
     image_dims: Option<(f64, f64)>,
+    max_width: Option<f64>,
     style: Style,
     transparency: f32,
     image_frame: ImageFrame,
@@ -290,6 +366,7 @@ impl<'a> Tooltip<'a> {
         image_dims { image_dims = Option<(f64, f64)> }
         transparency { transparency = f32 }
         title_col { title_col = Color}
+        pub max_width { max_width = Option<f64> }
     }
 
     pub fn new(image_frame: ImageFrame) -> Self {
@@ -302,6 +379,7 @@ impl<'a> Tooltip<'a> {
             image_frame,
             image: None,
             image_dims: None,
+            max_width: None,
             title_col: TEXT_COLOR,
         }
     }
@@ -446,7 +524,7 @@ impl<'a> Widget for Tooltip<'a> {
     }
 
     /// Default width is based on the description font size unless the text is
-    /// small enough to fit on a single line
+    /// small enough to fit on a single line, capped by the maximum width
     fn default_x_dimension(&self, ui: &Ui) -> Dimension {
         let single_line_title_w = widget::Text::new(self.title_text)
             .with_style(self.style.title)
@@ -467,8 +545,12 @@ impl<'a> Widget for Tooltip<'a> {
             text_w
         };
 
-        let width =
-            inner_w.min(self.style.desc.font_size(&ui.theme) as f64 * DEFAULT_CHAR_W) + 2.0 * H_PAD;
+        let width = tooltip_width(
+            inner_w,
+            self.style.desc.font_size(&ui.theme) as f64 * DEFAULT_CHAR_W,
+            self.max_width,
+            ui.win_w,
+        );
         Dimension::Absolute(width)
     }
 
@@ -506,3 +588,107 @@ impl<'a> Widget for Tooltip<'a> {
 impl<'a> Colorable for Tooltip<'a> {
     builder_method!(color { style.color = Some(Color) });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIN: [f64; 2] = [800.0, 600.0];
+    const DIMS: [f64; 2] = [200.0, 100.0];
+    const PAD: f64 = 15.0;
+
+    fn assert_near(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-6, "{} != {}", a, b);
+    }
+
+    fn place(widget_xy: [f64; 2]) -> Rect {
+        let avoid = Rect::from_xy_dim(widget_xy, [50.0, 50.0]);
+        let xy = tooltip_xy(DIMS, widget_xy, avoid, WIN, PAD);
+        let tooltip = Rect::from_xy_dim(xy, DIMS);
+
+        // Within the window and not covering the widget
+        assert!(tooltip.left() >= -WIN[0] / 2.0 && tooltip.right() <= WIN[0] / 2.0);
+        assert!(tooltip.bottom() >= -WIN[1] / 2.0 && tooltip.top() <= WIN[1] / 2.0);
+        assert!(tooltip.overlap(avoid).is_none());
+        tooltip
+    }
+
+    #[test]
+    fn top_edge() {
+        let tooltip = place([0.0, 275.0]);
+        assert_near(tooltip.top(), 250.0 - PAD);
+    }
+
+    #[test]
+    fn bottom_edge() {
+        let tooltip = place([0.0, -275.0]);
+        assert_near(tooltip.bottom(), -250.0 + PAD);
+    }
+
+    #[test]
+    fn left_edge() {
+        let tooltip = place([-375.0, 0.0]);
+        assert_near(tooltip.left(), -400.0);
+    }
+
+    #[test]
+    fn right_edge() {
+        let tooltip = place([375.0, 100.0]);
+        assert_near(tooltip.right(), 400.0);
+        // More space below the widget
+        assert!(tooltip.top() < 75.0);
+    }
+
+    #[test]
+    fn corner() {
+        let tooltip = place([375.0, 275.0]);
+        assert_near(tooltip.right(), 400.0);
+        assert!(tooltip.top() < 250.0);
+    }
+
+    #[test]
+    fn minimal_overlap_when_no_room() {
+        let avoid = Rect::from_xy_dim([0.0, 10.0], [760.0, 540.0]);
+        let xy = tooltip_xy(DIMS, [0.0, 0.0], avoid, WIN, PAD);
+        let tooltip = Rect::from_xy_dim(xy, DIMS);
+        assert!(tooltip.bottom() >= -WIN[1] / 2.0 && tooltip.top() <= WIN[1] / 2.0);
+        // Pushed against the bottom of the window where the most space is
+        assert_near(tooltip.bottom(), -300.0);
+    }
+
+    #[test]
+    fn clipped_widgets_dont_register_hovers() {
+        // A scrollable list showing the area between y = -100 and y = 100
+        let clip = Rect::from_xy_dim([0.0, 0.0], [200.0, 200.0]);
+
+        let shown = Rect::from_xy_dim([0.0, 50.0], [180.0, 40.0]);
+        assert!(hover_visible([0.0, 50.0], shown, Some(clip)));
+
+        // Scrolled out of view, yet still where the mouse is
+        let scrolled_away = Rect::from_xy_dim([0.0, 150.0], [180.0, 40.0]);
+        assert!(!hover_visible([0.0, 150.0], scrolled_away, Some(clip)));
+
+        // Half scrolled out of view: only the visible half counts
+        let half_shown = Rect::from_xy_dim([0.0, 100.0], [180.0, 40.0]);
+        assert!(hover_visible([0.0, 90.0], half_shown, Some(clip)));
+        assert!(!hover_visible([0.0, 110.0], half_shown, Some(clip)));
+
+        // Cropped away entirely
+        assert!(!hover_visible([0.0, 50.0], shown, None));
+    }
+
+    #[test]
+    fn max_width_wraps_long_text() {
+        // Short text fits on one line either way
+        assert_near(tooltip_width(100.0, 420.0, None, WIN[0]), 120.0);
+        assert_near(tooltip_width(100.0, 420.0, Some(250.0), WIN[0]), 120.0);
+
+        // Long text is wrapped at the default width without a cap...
+        assert_near(tooltip_width(1000.0, 420.0, None, WIN[0]), 440.0);
+        // ...and earlier with one
+        assert_near(tooltip_width(1000.0, 420.0, Some(250.0), WIN[0]), 250.0);
+
+        // Never wider than the window
+        assert_near(tooltip_width(1000.0, 420.0, None, 300.0), 300.0);
+    }
+}
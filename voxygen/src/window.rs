@@ -45,6 +45,7 @@ pub enum GameInput {
     SwimUp,
     SwimDown,
     Sneak,
+    Sprint,
     //WallLeap,
     ToggleLantern,
     Mount,
@@ -69,6 +70,7 @@ pub enum GameInput {
     ToggleWield,
     //Charge,
     SwapLoadout,
+    CancelAbility,
     FreeLook,
     AutoWalk,
     CycleCamera,
@@ -96,6 +98,7 @@ impl GameInput {
             GameInput::SwimUp => "gameinput.swimup",
             GameInput::SwimDown => "gameinput.swimdown",
             GameInput::Sneak => "gameinput.sneak",
+            GameInput::Sprint => "gameinput.sprint",
             //GameInput::WallLeap => "gameinput.wallleap",
             GameInput::ToggleLantern => "gameinput.togglelantern",
             GameInput::Mount => "gameinput.mount",
@@ -133,6 +136,7 @@ impl GameInput {
             GameInput::Slot9 => "gameinput.slot9",
             GameInput::Slot10 => "gameinput.slot10",
             GameInput::SwapLoadout => "gameinput.swaploadout",
+            GameInput::CancelAbility => "gameinput.cancelability",
             GameInput::Select => "gameinput.select",
             GameInput::AcceptGroupInvite => "gameinput.acceptgroupinvite",
             GameInput::DeclineGroupInvite => "gameinput.declinegroupinvite",
@@ -157,6 +161,7 @@ impl GameInput {
             GameInput::SwimUp,
             GameInput::SwimDown,
             GameInput::Sneak,
+            GameInput::Sprint,
             GameInput::ToggleLantern,
             GameInput::Mount,
             GameInput::Chat,
@@ -191,6 +196,7 @@ impl GameInput {
             GameInput::Slot9,
             GameInput::Slot10,
             GameInput::SwapLoadout,
+            GameInput::CancelAbility,
         ]
         .iter()
         .copied()
@@ -929,13 +935,14 @@ impl Window {
                 if let (true, Some(game_inputs)) =
                     // Mouse input not mapped to input if it is not grabbed
                     (
-                    self.cursor_grabbed,
-                    Window::map_input(
-                        KeyMouse::Mouse(button),
-                        controls,
-                        &mut self.remapping_keybindings,
-                    ),
-                ) {
+                        self.cursor_grabbed,
+                        Window::map_input(
+                            KeyMouse::Mouse(button),
+                            controls,
+                            &mut self.remapping_keybindings,
+                        ),
+                    )
+                {
                     for game_input in game_inputs {
                         self.events.push(Event::InputUpdate(
                             *game_input,
@@ -81,10 +81,7 @@ fn main() {
             .into_boxed_slice()
     };
     let get_map_sample = |map_samples: &[MapSample], pos: Vec2<i32>| {
-        if pos.reduce_partial_min() >= 0
-            && pos.x < map_size_lg.chunks().x as i32
-            && pos.y < map_size_lg.chunks().y as i32
-        {
+        if map_size_lg.contains_chunk(pos) {
             map_samples[vec2_as_uniform_idx(map_size_lg, pos)].clone()
         } else {
             MapSample {
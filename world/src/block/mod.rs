@@ -25,6 +25,10 @@ pub struct Colors {
     pub structure_blocks: structure::structure_block::PureCases<Option<Range<(u8, u8, u8)>>>,
 }
 
+/// How far from a river its banks are muddy, matching where they lose their
+/// grass colour in column generation
+const RIVERBANK_WIDTH: f32 = 3.0;
+
 pub struct BlockGen<'a> {
     pub column_cache: SmallCache<Option<ColumnSample<'a>>>,
     pub column_gen: ColumnGen<'a>,
@@ -171,6 +175,7 @@ impl<'a> BlockGen<'a> {
             basement,
             chaos,
             water_level,
+            water_dist,
             warp_factor,
             surface_color,
             sub_surface_color,
@@ -273,9 +278,14 @@ impl<'a> BlockGen<'a> {
                     .div(grass_depth)
                     .powf(0.5);
                 let col = Lerp::lerp(sub_surface_color, surface_color, grass_factor);
+                // Low-lying riverbanks are muddy
+                let riverbank =
+                    water_dist.map_or(false, |d| d < RIVERBANK_WIDTH) && height < water_level + 2.0;
                 // Surface
                 Some(Block::new(
-                    if grass_factor > 0.7 {
+                    if riverbank {
+                        BlockKind::Mud
+                    } else if grass_factor > 0.7 {
                         BlockKind::Grass
                     } else {
                         BlockKind::Earth
@@ -7,7 +7,7 @@ use crate::{
     config::CONFIG,
     sim::WorldSim,
     site::{Castle, Dungeon, Settlement, Site as WorldSite},
-    util::{attempt, seed_expan, MapVec, CARDINALS, NEIGHBORS},
+    util::{attempt, seed_expan, DHashSet, MapVec, Placements, CARDINALS, NEIGHBORS},
     Index,
 };
 use common::{
@@ -133,17 +133,31 @@ impl Civs {
             this.tick(&mut ctx, 1.0);
         }
 
-        // Flatten ground around sites
+        // Sites can be close enough to want to flatten the same chunks. Resolve them by
+        // index rather than by the order they happened to be generated in, so that the
+        // first site by index gets to shape the ground it shares with its neighbours.
+        let mut placements = Placements::new(ctx.sim.map_size_lg());
         for site in this.sites.values() {
+            let flatten_radius = site.kind.flatten_radius();
+            placements.add(
+                site.center,
+                site,
+                Spiral2d::new()
+                    .take((flatten_radius as usize * 2 + 1).pow(2))
+                    .filter(|offs| offs.map(|e| e as f32).magnitude() < flatten_radius)
+                    .map(|offs| site.center + offs),
+            );
+        }
+        let placements = placements.resolve();
+
+        // Flatten ground around sites
+        for (site, area) in placements.iter() {
             let radius = 48i32;
 
             let wpos = site.center * TerrainChunkSize::RECT_SIZE.map(|e: u32| e as i32);
 
-            let flatten_radius = match &site.kind {
-                SiteKind::Settlement => 10.0,
-                SiteKind::Dungeon => 2.0,
-                SiteKind::Castle => 5.0,
-            };
+            let flatten_radius = site.kind.flatten_radius();
+            let area = area.iter().copied().collect::<DHashSet<_>>();
 
             let (raise, raise_dist): (f32, i32) = match &site.kind {
                 SiteKind::Settlement => (10.0, 6),
@@ -161,6 +175,10 @@ impl Civs {
                             0.0
                         }; // Raise the town centre up a little
                     let pos = site.center + offs;
+                    // Another site got to flatten this chunk first
+                    if offs.map(|e| e as f32).magnitude() < flatten_radius && !area.contains(&pos) {
+                        continue;
+                    }
                     let factor = ((1.0
                         - (site.center - pos).map(|e| e as f32).magnitude() / flatten_radius)
                         * 1.25)
@@ -189,7 +207,7 @@ impl Civs {
 
         // Place sites in world
         let mut cnt = 0;
-        for sim_site in this.sites.values() {
+        for (sim_site, _) in placements.iter() {
             cnt += 1;
             let wpos = sim_site
                 .center
@@ -865,6 +883,17 @@ pub enum SiteKind {
     Castle,
 }
 
+impl SiteKind {
+    /// How far out, in chunks, the ground around the site is flattened
+    fn flatten_radius(&self) -> f32 {
+        match self {
+            SiteKind::Settlement => 10.0,
+            SiteKind::Dungeon => 2.0,
+            SiteKind::Castle => 5.0,
+        }
+    }
+}
+
 impl Site {
     #[allow(clippy::let_and_return)] // TODO: Pending review in #587
     pub fn simulate(&mut self, years: f32, nat_res: &NaturalResources) {
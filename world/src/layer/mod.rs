@@ -107,7 +107,7 @@ pub fn apply_paths_to<'a>(
                             let path_color = path.surface_color(
                                 col_sample.sub_surface_color.map(|e| (e * 255.0) as u8),
                             );
-                            Block::new(BlockKind::Earth, noisy_color(path_color, 8))
+                            Block::new(BlockKind::Road, noisy_color(path_color, 8))
                         },
                     );
                 }
@@ -33,6 +33,7 @@ pub use index::{IndexOwned, IndexRef};
 use crate::{
     column::ColumnGen,
     index::Index,
+    site::SiteKind,
     util::{Grid, Sampler},
 };
 use common::{
@@ -151,7 +152,14 @@ impl World {
             },
         };
 
-        let meta = TerrainChunkMeta::new(sim_chunk.get_name(&self.sim), sim_chunk.get_biome());
+        let mut meta = TerrainChunkMeta::new(sim_chunk.get_name(&self.sim), sim_chunk.get_biome());
+        // Towns are safe from player fights
+        meta.set_safe_zone(
+            sim_chunk
+                .sites
+                .iter()
+                .any(|site| matches!(index.sites[*site].kind, SiteKind::Settlement(_))),
+        );
 
         let mut chunk = TerrainChunk::new(base_z, stone, air, meta);
 
@@ -36,8 +36,8 @@ use common::{
     msg::WorldMapMsg,
     store::Id,
     terrain::{
-        map::MapConfig, uniform_idx_as_vec2, vec2_as_uniform_idx, BiomeKind, MapSizeLg,
-        TerrainChunkSize,
+        checked_vec2_as_uniform_idx, map::MapConfig, uniform_idx_as_vec2, vec2_as_uniform_idx,
+        BiomeKind, MapSizeLg, TerrainChunkSize,
     },
     vol::RectVolSize,
 };
@@ -1293,11 +1293,7 @@ impl WorldSim {
             let pos = uniform_idx_as_vec2(map_size_lg, posi);
             for x in pos.x - 1..(pos.x + 1) + 1 {
                 for y in pos.y - 1..(pos.y + 1) + 1 {
-                    if x >= 0
-                        && y >= 0
-                        && x < map_size_lg.chunks().x as i32
-                        && y < map_size_lg.chunks().y as i32
-                    {
+                    if map_size_lg.contains_chunk(Vec2::new(x, y)) {
                         let posi = vec2_as_uniform_idx(map_size_lg, Vec2::new(x, y));
                         if !is_underwater(posi) {
                             return false;
@@ -1513,7 +1509,7 @@ impl WorldSim {
             dimensions_lg: self.map_size_lg().vec(),
             sea_level: CONFIG.sea_level,
             max_height: self.max_height,
-            rgba: v,
+            rgba: WorldMapMsg::encode_rgba(self.map_size_lg(), &v),
             alt: alts,
             horizons,
         }
@@ -1677,14 +1673,8 @@ impl WorldSim {
     }
 
     pub fn get(&self, chunk_pos: Vec2<i32>) -> Option<&SimChunk> {
-        if chunk_pos
-            .map2(self.map_size_lg().chunks(), |e, sz| e >= 0 && e < sz as i32)
-            .reduce_and()
-        {
-            Some(&self.chunks[vec2_as_uniform_idx(self.map_size_lg(), chunk_pos)])
-        } else {
-            None
-        }
+        let posi = checked_vec2_as_uniform_idx(self.map_size_lg(), chunk_pos)?;
+        Some(&self.chunks[posi])
     }
 
     pub fn get_gradient_approx(&self, chunk_pos: Vec2<i32>) -> Option<f32> {
@@ -1709,15 +1699,8 @@ impl WorldSim {
     }
 
     pub fn get_mut(&mut self, chunk_pos: Vec2<i32>) -> Option<&mut SimChunk> {
-        let map_size_lg = self.map_size_lg();
-        if chunk_pos
-            .map2(map_size_lg.chunks(), |e, sz| e >= 0 && e < sz as i32)
-            .reduce_and()
-        {
-            Some(&mut self.chunks[vec2_as_uniform_idx(map_size_lg, chunk_pos)])
-        } else {
-            None
-        }
+        let posi = checked_vec2_as_uniform_idx(self.map_size_lg(), chunk_pos)?;
+        Some(&mut self.chunks[posi])
     }
 
     pub fn get_base_z(&self, chunk_pos: Vec2<i32>) -> Option<f32> {
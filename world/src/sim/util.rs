@@ -1,6 +1,9 @@
 use bitvec::prelude::{bitbox, BitBox};
 use common::{
-    terrain::{neighbors, uniform_idx_as_vec2, vec2_as_uniform_idx, MapSizeLg, TerrainChunkSize},
+    terrain::{
+        checked_vec2_as_uniform_idx, neighbors, uniform_idx_as_vec2, vec2_as_uniform_idx,
+        MapSizeLg, TerrainChunkSize,
+    },
     vol::RectVolSize,
 };
 use noise::{MultiFractal, NoiseFn, Perlin, Point2, Point3, Point4, Seedable};
@@ -226,13 +229,7 @@ pub fn local_cells(map_size_lg: MapSizeLg, posi: usize) -> impl Clone + Iterator
                 pos.y + (index / grid_bounds) - grid_size,
             )
         })
-        .filter(move |pos| {
-            pos.x >= 0
-                && pos.y >= 0
-                && pos.x < map_size_lg.chunks().x as i32
-                && pos.y < map_size_lg.chunks().y as i32
-        })
-        .map(move |e| vec2_as_uniform_idx(map_size_lg, e))
+        .filter_map(move |pos| checked_vec2_as_uniform_idx(map_size_lg, pos))
 }
 
 // Note that we should already have okay cache locality since we have a grid.
@@ -398,13 +395,10 @@ pub fn get_horizon_map<F: Float + Sync, A: Send, H: Send>(
             .map(|posi| {
                 let wposi =
                     bounds.min + Vec2::new((posi % map_size.x) as i32, (posi / map_size.x) as i32);
-                if wposi.reduce_partial_min() < 0
-                    || wposi.x as usize >= usize::from(map_size_lg.chunks().x)
-                    || wposi.y as usize >= usize::from(map_size_lg.chunks().y)
-                {
-                    return (to_angle(F::zero()), to_height(F::zero()));
-                }
-                let posi = vec2_as_uniform_idx(map_size_lg, wposi);
+                let posi = match checked_vec2_as_uniform_idx(map_size_lg, wposi) {
+                    Some(posi) => posi,
+                    None => return (to_angle(F::zero()), to_height(F::zero())),
+                };
                 // March in the given direction.
                 let maxdx = maxdx(wposi.x as isize, map_size_lg);
                 let mut slope = F::zero();
@@ -99,6 +99,36 @@ impl Dungeon {
 
         let entrance = &ENTRANCES[self.seed as usize % ENTRANCES.len()];
 
+        // Apply the dungeon entrance
+        let chunk_size = vol.size_xy().map(|e| e as i32);
+        let entrance_offs = self.origin + TILE_SIZE / 2 - wpos2d;
+        let in_chunk = |offs: Vec2<i32>| {
+            offs.x >= 0 && offs.y >= 0 && offs.x < chunk_size.x && offs.y < chunk_size.y
+        };
+        let entrance_blocks = entrance.blocks(|spos, sblock| {
+            let offs = entrance_offs + Vec2::from(spos);
+            if !in_chunk(offs) {
+                return None;
+            }
+            block_from_structure(
+                index,
+                sblock,
+                spos,
+                self.origin,
+                self.seed,
+                get_column(offs)?,
+                // TODO: Take environment into account.
+                Block::air,
+            )
+        });
+        for (spos, block) in entrance_blocks {
+            let offs = entrance_offs + Vec2::from(spos);
+            let _ = vol.set(
+                Vec3::new(offs.x, offs.y, self.alt + spos.z + ALT_OFFSET),
+                block,
+            );
+        }
+
         for y in 0..vol.size_xy().y as i32 {
             for x in 0..vol.size_xy().x as i32 {
                 let offs = Vec2::new(x, y);
@@ -106,35 +136,8 @@ impl Dungeon {
                 let wpos2d = wpos2d + offs;
                 let rpos = wpos2d - self.origin;
 
-                // Apply the dungeon entrance
-                let col_sample = if let Some(col) = get_column(offs) {
-                    col
-                } else {
+                if get_column(offs).is_none() {
                     continue;
-                };
-                for z in entrance.get_bounds().min.z..entrance.get_bounds().max.z {
-                    let wpos = Vec3::new(offs.x, offs.y, self.alt + z + ALT_OFFSET);
-                    let spos = Vec3::new(rpos.x - TILE_SIZE / 2, rpos.y - TILE_SIZE / 2, z);
-                    if let Some(block) = entrance
-                        .get(spos)
-                        .ok()
-                        .copied()
-                        .map(|sb| {
-                            block_from_structure(
-                                index,
-                                sb,
-                                spos,
-                                self.origin,
-                                self.seed,
-                                col_sample,
-                                // TODO: Take environment into account.
-                                Block::air,
-                            )
-                        })
-                        .unwrap_or(None)
-                    {
-                        let _ = vol.set(wpos, block);
-                    }
                 }
 
                 // Apply the dungeon internals
@@ -632,6 +635,16 @@ impl Floor {
             vacant
         };
 
+        // Pressure plates, one block proud of the tunnel floors
+        let trap = if RandomField::new(4177).chance(Vec3::from(pos), 0.004) {
+            Some(BlockMask::new(
+                Block::new(BlockKind::Trap, colors.stone.into()),
+                1,
+            ))
+        } else {
+            None
+        };
+
         let tunnel_height = if self.final_level { 16.0 } else { 8.0 };
 
         move |z| match self.tiles.get(tile_pos) {
@@ -640,7 +653,11 @@ impl Floor {
                 if dist_to_wall >= wall_thickness
                     && (z as f32) < tunnel_height * (1.0 - tunnel_dist.powf(4.0))
                 {
-                    if z == 0 { floor_sprite } else { vacant }
+                    match trap {
+                        Some(trap) if z == 0 => trap,
+                        _ if z == 0 => floor_sprite,
+                        _ => vacant,
+                    }
                 } else {
                     BlockMask::nothing()
                 }
@@ -1,6 +1,7 @@
 pub mod fast_noise;
 pub mod grid;
 pub mod map_vec;
+pub mod placement;
 pub mod random;
 pub mod sampler;
 pub mod seed_expan;
@@ -13,6 +14,7 @@ pub use self::{
     fast_noise::FastNoise,
     grid::Grid,
     map_vec::MapVec,
+    placement::Placements,
     random::{RandomField, RandomPerm},
     sampler::{Sampler, SamplerMut},
     small_cache::SmallCache,
@@ -0,0 +1,101 @@
+use super::DHashMap;
+use common::terrain::{vec2_as_uniform_idx, MapSizeLg};
+use vek::*;
+
+/// Resolves placements (sites, structures, ...) that want some of the same
+/// chunks, independently of the order they were generated in.
+///
+/// Placements are handled in order of the uniform index of their origin (see
+/// [`vec2_as_uniform_idx`]), falling back to the order they were added in when
+/// two share an origin. When placements conflict, the first one in that order
+/// wins: it keeps every chunk it asked for and later placements only get the
+/// chunks that are still free.
+pub struct Placements<T> {
+    map_size_lg: MapSizeLg,
+    placements: Vec<(Vec2<i32>, T, Vec<Vec2<i32>>)>,
+}
+
+impl<T> Placements<T> {
+    pub fn new(map_size_lg: MapSizeLg) -> Self {
+        Self {
+            map_size_lg,
+            placements: Vec::new(),
+        }
+    }
+
+    /// Adds a placement at `origin` (which must lie on the map) that wants the
+    /// chunks `area`
+    pub fn add(
+        &mut self,
+        origin: Vec2<i32>,
+        placement: T,
+        area: impl IntoIterator<Item = Vec2<i32>>,
+    ) {
+        self.placements
+            .push((origin, placement, area.into_iter().collect()));
+    }
+
+    /// Returns the placements in resolution order, along with the chunks each
+    /// of them ended up with
+    pub fn resolve(mut self) -> Vec<(T, Vec<Vec2<i32>>)> {
+        let map_size_lg = self.map_size_lg;
+        // `sort_by_key` is stable, so placements sharing an origin keep the order
+        // they were added in
+        self.placements
+            .sort_by_key(|(origin, _, _)| vec2_as_uniform_idx(map_size_lg, *origin));
+
+        let mut claims = DHashMap::default();
+        self.placements
+            .into_iter()
+            .enumerate()
+            .map(|(i, (_, placement, area))| {
+                let won = area
+                    .into_iter()
+                    .filter(|chunk| *claims.entry(*chunk).or_insert(i) == i)
+                    .collect();
+                (placement, won)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conflicting_placements_resolve_deterministically() {
+        let map_size_lg = MapSizeLg::new(Vec2::new(5, 5)).unwrap();
+        let square = |center: Vec2<i32>| {
+            (-1..=1).flat_map(move |y| (-1..=1).map(move |x| center + Vec2::new(x, y)))
+        };
+        let castle = Vec2::new(10, 10);
+        let dungeon = Vec2::new(11, 10);
+
+        // Whichever order the placements are generated in, the castle comes first
+        // by index and so keeps the chunks they both want
+        let mut results = Vec::new();
+        for &swap in [false, true, false, true].iter() {
+            let mut placements = Placements::new(map_size_lg);
+            let mut candidates = vec![("castle", castle), ("dungeon", dungeon)];
+            if swap {
+                candidates.reverse();
+            }
+            for (name, origin) in candidates {
+                placements.add(origin, name, square(origin));
+            }
+            results.push(placements.resolve());
+        }
+        assert!(results.windows(2).all(|w| w[0] == w[1]));
+
+        let resolved = &results[0];
+        assert_eq!(resolved[0].0, "castle");
+        assert_eq!(resolved[0].1.len(), 9);
+        assert_eq!(resolved[1].0, "dungeon");
+        assert_eq!(resolved[1].1, vec![
+            Vec2::new(12, 9),
+            Vec2::new(12, 10),
+            Vec2::new(12, 11)
+        ]);
+    }
+}